@@ -8,10 +8,13 @@ pub fn build() {
     let mut file = BufWriter::new(File::create("../tokio-postgres/src/error/sqlstate.rs").unwrap());
 
     let codes = parse_codes();
+    let classes = parse_classes();
 
     make_type(&mut file);
     make_code(&codes, &mut file);
+    make_class(&classes, &mut file);
     make_consts(&codes, &mut file);
+    make_class_type(&classes, &mut file);
     make_inner(&codes, &mut file);
     make_map(&codes, &mut file);
 }
@@ -35,6 +38,45 @@ fn parse_codes() -> LinkedHashMap<String, Vec<String>> {
     codes
 }
 
+struct Class {
+    code: String,
+    variant: String,
+    doc: String,
+}
+
+fn parse_classes() -> Vec<Class> {
+    let mut classes = vec![];
+
+    for line in ERRCODES_TXT.lines() {
+        let line = match line.strip_prefix("Section: Class ") {
+            Some(line) => line,
+            None => continue,
+        };
+
+        let (code, name) = line.split_once(" - ").unwrap();
+        // Parenthesized remarks aren't part of the name of the class.
+        let name = name.split(" (").next().unwrap();
+        let variant = name
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .map(|word| {
+                let mut chars = word.chars();
+                let first = chars.next().unwrap().to_ascii_uppercase();
+                let rest = chars.as_str().to_ascii_lowercase();
+                format!("{}{}", first, rest)
+            })
+            .collect::<String>()
+            .replace('-', "");
+
+        classes.push(Class {
+            code: code.to_owned(),
+            variant,
+            doc: line.to_owned(),
+        });
+    }
+
+    classes
+}
+
 fn make_type(file: &mut BufWriter<File>) {
     write!(
         file,
@@ -88,6 +130,39 @@ fn make_code(codes: &LinkedHashMap<String, Vec<String>>, file: &mut BufWriter<Fi
     .unwrap();
 }
 
+fn make_class(classes: &[Class], file: &mut BufWriter<File>) {
+    write!(
+        file,
+        r#"
+    /// Returns the class of the `SqlState`, identified by the first two characters of its error code.
+    pub fn class(&self) -> SqlStateClass {{
+        let code = self.code();
+        match code.get(..2).unwrap_or(code) {{"#,
+    )
+    .unwrap();
+
+    for class in classes {
+        write!(
+            file,
+            r#"
+            "{code}" => SqlStateClass::{variant},"#,
+            code = class.code,
+            variant = class.variant,
+        )
+        .unwrap();
+    }
+
+    write!(
+        file,
+        r#"
+            class => SqlStateClass::Other(class.into()),
+        }}
+    }}
+"#
+    )
+    .unwrap();
+}
+
 fn make_consts(codes: &LinkedHashMap<String, Vec<String>>, file: &mut BufWriter<File>) {
     for (code, names) in codes {
         for name in names {
@@ -107,6 +182,62 @@ fn make_consts(codes: &LinkedHashMap<String, Vec<String>>, file: &mut BufWriter<
     write!(file, "}}").unwrap();
 }
 
+fn make_class_type(classes: &[Class], file: &mut BufWriter<File>) {
+    write!(
+        file,
+        r#"
+
+/// The class of a SQLSTATE error code.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[non_exhaustive]
+pub enum SqlStateClass {{"#,
+    )
+    .unwrap();
+    for class in classes {
+        write!(
+            file,
+            r#"
+    /// Class {doc}
+    {variant},"#,
+            doc = class.doc,
+            variant = class.variant,
+        )
+        .unwrap();
+    }
+    write!(
+        file,
+        r#"
+    /// A class not known to this crate.
+    Other(Box<str>),
+}}
+
+impl SqlStateClass {{
+    /// Returns the two character code of the class.
+    pub fn code(&self) -> &str {{
+        match self {{"#,
+    )
+    .unwrap();
+    for class in classes {
+        write!(
+            file,
+            r#"
+            SqlStateClass::{variant} => "{code}","#,
+            code = class.code,
+            variant = class.variant,
+        )
+        .unwrap();
+    }
+    write!(
+        file,
+        r#"
+            SqlStateClass::Other(code) => code,
+        }}
+    }}
+}}"#,
+    )
+    .unwrap();
+}
+
 fn make_inner(codes: &LinkedHashMap<String, Vec<String>>, file: &mut BufWriter<File>) {
     write!(
         file,
@@ -0,0 +1,15 @@
+use postgres_types::{FromRow, FromSql};
+
+#[derive(FromSql, Debug)]
+#[postgres(default)]
+struct DefaultContainer {
+    a: i32
+}
+
+#[derive(FromRow, Debug)]
+struct FromRowDefault {
+    #[postgres(default)]
+    a: i32
+}
+
+fn main() {}
@@ -0,0 +1,30 @@
+use postgres_types::{FromRow, FromSql, ToSql};
+
+#[derive(ToSql, Debug)]
+struct ToSqlFlatten {
+    #[postgres(flatten)]
+    a: i32
+}
+
+#[derive(FromSql, Debug)]
+struct FromSqlFlatten {
+    #[postgres(flatten)]
+    a: i32
+}
+
+#[derive(FromRow, Debug)]
+#[postgres(flatten)]
+struct FlattenContainer {
+    a: i32
+}
+
+#[derive(FromRow, Debug)]
+struct FlattenName {
+    #[postgres(flatten, name = "b")]
+    a: i32
+}
+
+#[derive(FromRow, Debug)]
+struct FromRowTupleStruct(i32);
+
+fn main() {}
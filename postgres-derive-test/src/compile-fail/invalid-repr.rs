@@ -0,0 +1,31 @@
+use postgres_types::{FromSql, ToSql};
+
+#[derive(ToSql, Debug)]
+#[postgres(repr = "text")]
+struct ToSqlReprStruct {
+    a: i32,
+}
+
+#[derive(FromSql, Debug)]
+#[postgres(repr = "integer")]
+struct FromSqlReprTupleStruct(i32);
+
+#[derive(ToSql, Debug)]
+#[postgres(repr = "bytes")]
+enum UnknownRepr {
+    A,
+}
+
+#[derive(FromSql, Debug)]
+#[postgres(repr = "text", allow_mismatch)]
+enum ReprAllowMismatch {
+    A,
+}
+
+#[derive(ToSql, Debug)]
+enum VariantRepr {
+    #[postgres(repr = "text")]
+    A,
+}
+
+fn main() {}
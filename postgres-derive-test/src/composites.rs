@@ -34,16 +34,65 @@ fn defaults() {
         price: None,
     };
 
+    let item_escaped = InventoryItem {
+        name: "foo \"bar\"".to_owned(),
+        supplier_id: 100,
+        price: Some(15.50),
+    };
+
     test_type(
         &mut conn,
         "\"InventoryItem\"",
         &[
             (item, "ROW('foobar', 100, 15.50)"),
             (item_null, "ROW('foobar', 100, NULL)"),
+            (item_escaped, "ROW('foo \"bar\"', 100, 15.50)"),
         ],
     );
 }
 
+#[test]
+fn arrays() {
+    #[derive(FromSql, ToSql, Debug, PartialEq)]
+    #[postgres(name = "item")]
+    struct Item {
+        name: String,
+        tags: Vec<Option<String>>,
+    }
+
+    let mut conn = Client::connect("user=postgres host=localhost port=5433", NoTls).unwrap();
+    conn.batch_execute("CREATE TYPE pg_temp.item AS (name TEXT, tags TEXT[]);")
+        .unwrap();
+
+    let items = vec![
+        Item {
+            name: "foo, \"bar\"".to_owned(),
+            tags: vec![Some("a b".to_owned()), None, Some("(c)".to_owned())],
+        },
+        Item {
+            name: "back\\slash".to_owned(),
+            tags: vec![Some("NULL".to_owned()), Some("{}".to_owned())],
+        },
+        Item {
+            name: "".to_owned(),
+            tags: vec![],
+        },
+    ];
+
+    test_type(
+        &mut conn,
+        "item[]",
+        &[(
+            items,
+            "ARRAY[
+                ROW('foo, \"bar\"', ARRAY['a b', NULL, '(c)']),
+                ROW('back\\slash', ARRAY['NULL', '{}']),
+                ROW('', '{}')
+            ]::item[]",
+        )],
+    );
+}
+
 #[test]
 fn name_overrides() {
     #[derive(FromSql, ToSql, Debug, PartialEq)]
@@ -227,6 +276,55 @@ fn missing_field() {
     assert!(err.source().unwrap().is::<WrongType>());
 }
 
+#[test]
+fn missing_default_field() {
+    #[derive(FromSql, ToSql, Debug, PartialEq)]
+    #[postgres(name = "inventory_item")]
+    struct InventoryItem {
+        name: String,
+        supplier_id: i32,
+        #[postgres(default)]
+        price: Option<f64>,
+    }
+
+    #[derive(FromSql, ToSql, Debug, PartialEq)]
+    #[postgres(name = "inventory_item")]
+    struct RequiredInventoryItem {
+        #[postgres(default)]
+        name: String,
+        supplier_id: i32,
+        price: Option<f64>,
+    }
+
+    let mut conn = Client::connect("user=postgres host=localhost port=5433", NoTls).unwrap();
+    conn.batch_execute(
+        "CREATE TYPE pg_temp.inventory_item AS (
+            name TEXT,
+            supplier_id INT
+        );",
+    )
+    .unwrap();
+
+    let item = InventoryItem {
+        name: "foobar".to_owned(),
+        supplier_id: 100,
+        price: None,
+    };
+
+    test_type(&mut conn, "inventory_item", &[(item, "ROW('foobar', 100)")]);
+
+    let item = RequiredInventoryItem {
+        name: "foobar".to_owned(),
+        supplier_id: 100,
+        price: None,
+    };
+
+    let err = conn
+        .execute("SELECT $1::inventory_item", &[&item])
+        .unwrap_err();
+    assert!(err.source().unwrap().is::<WrongType>());
+}
+
 #[test]
 fn wrong_type() {
     #[derive(FromSql, ToSql, Debug, PartialEq)]
@@ -282,6 +380,38 @@ fn raw_ident_field() {
     test_type(&mut conn, "inventory_item", &[(item, "ROW('foo')")]);
 }
 
+#[test]
+fn borrowed() {
+    #[derive(FromSql, ToSql, Debug, PartialEq)]
+    #[postgres(name = "inventory_item")]
+    struct InventoryItem<'a, T> {
+        name: &'a str,
+        data: &'a [u8],
+        supplier_id: T,
+    }
+
+    let mut conn = Client::connect("user=postgres host=localhost port=5433", NoTls).unwrap();
+    conn.batch_execute(
+        "CREATE TYPE pg_temp.inventory_item AS (
+            name TEXT,
+            data BYTEA,
+            supplier_id INT
+        );",
+    )
+    .unwrap();
+
+    let item = InventoryItem {
+        name: "foobar",
+        data: b"\x01\x02",
+        supplier_id: 100,
+    };
+
+    let row = conn
+        .query_one("SELECT $1::inventory_item", &[&item])
+        .unwrap();
+    assert_eq!(row.get::<_, InventoryItem<'_, i32>>(0), item);
+}
+
 #[test]
 fn generics() {
     #[derive(FromSql, Debug, PartialEq)]
@@ -294,7 +424,6 @@ fn generics() {
         price: Option<U>,
     }
 
-    // doesn't make sense to implement derived FromSql on a type with borrows
     #[derive(ToSql, Debug, PartialEq)]
     #[postgres(name = "InventoryItem")]
     struct InventoryItemRef<'a, T: 'a + Clone, U>
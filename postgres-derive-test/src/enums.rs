@@ -201,3 +201,65 @@ fn wrong_name_and_allow_mismatch() {
     let err = conn.query_one("SELECT $1::foo", &[&Foo::Bar]).unwrap_err();
     assert!(err.source().unwrap().is::<WrongType>());
 }
+
+#[test]
+fn text_repr() {
+    #[derive(Debug, ToSql, FromSql, PartialEq)]
+    #[postgres(repr = "text", rename_all = "kebab-case")]
+    enum Status {
+        InProgress,
+        #[postgres(name = "finished")]
+        Done,
+    }
+
+    let mut conn = Client::connect("user=postgres host=localhost port=5433", NoTls).unwrap();
+
+    test_type(
+        &mut conn,
+        "TEXT",
+        &[
+            (Status::InProgress, "'in-progress'"),
+            (Status::Done, "'finished'"),
+        ],
+    );
+    test_type(&mut conn, "VARCHAR", &[(Status::Done, "'finished'")]);
+
+    let err = conn
+        .query_one("SELECT 'unknown'::TEXT", &[])
+        .unwrap()
+        .try_get::<_, Status>(0)
+        .unwrap_err();
+    assert!(err.to_string().contains("invalid variant `unknown`"));
+}
+
+#[test]
+fn integer_repr() {
+    #[derive(Debug, ToSql, FromSql, PartialEq)]
+    #[postgres(repr = "integer")]
+    enum Priority {
+        Low = 1,
+        High = 10,
+        Huge = 100_000,
+    }
+
+    let mut conn = Client::connect("user=postgres host=localhost port=5433", NoTls).unwrap();
+
+    test_type(
+        &mut conn,
+        "SMALLINT",
+        &[(Priority::Low, "1"), (Priority::High, "10")],
+    );
+    test_type(
+        &mut conn,
+        "INT",
+        &[(Priority::Low, "1"), (Priority::Huge, "100000")],
+    );
+    test_type(&mut conn, "BIGINT", &[(Priority::High, "10")]);
+
+    conn.query_one("SELECT $1::SMALLINT", &[&Priority::Huge])
+        .unwrap_err();
+    conn.query_one("SELECT 2::SMALLINT", &[])
+        .unwrap()
+        .try_get::<_, Priority>(0)
+        .unwrap_err();
+}
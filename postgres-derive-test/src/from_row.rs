@@ -0,0 +1,117 @@
+use postgres::{Client, NoTls};
+use postgres_types::FromRow;
+
+#[test]
+fn defaults() {
+    #[derive(FromRow, Debug, PartialEq)]
+    struct InventoryItem {
+        name: String,
+        supplier_id: i32,
+        price: Option<f64>,
+    }
+
+    let mut conn = Client::connect("user=postgres host=localhost port=5433", NoTls).unwrap();
+
+    let items = conn
+        .query_as::<InventoryItem>(
+            "SELECT * FROM (VALUES (15.50::FLOAT8, 'foo', 100), (NULL, 'bar', 101)) AS t (price, name, supplier_id)",
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        items,
+        [
+            InventoryItem {
+                name: "foo".to_owned(),
+                supplier_id: 100,
+                price: Some(15.50),
+            },
+            InventoryItem {
+                name: "bar".to_owned(),
+                supplier_id: 101,
+                price: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn name_overrides() {
+    #[derive(FromRow, Debug, PartialEq)]
+    #[postgres(rename_all = "camelCase")]
+    struct InventoryItem {
+        #[postgres(name = "item_name")]
+        name: String,
+        supplier_id: i32,
+    }
+
+    let mut conn = Client::connect("user=postgres host=localhost port=5433", NoTls).unwrap();
+
+    let row = conn
+        .query_one("SELECT 'foo' AS item_name, 100 AS \"supplierId\"", &[])
+        .unwrap();
+    assert_eq!(
+        row.try_into::<InventoryItem>().unwrap(),
+        InventoryItem {
+            name: "foo".to_owned(),
+            supplier_id: 100,
+        }
+    );
+}
+
+#[test]
+fn flatten() {
+    #[derive(FromRow, Debug, PartialEq)]
+    struct Supplier {
+        supplier_id: i32,
+        supplier_name: String,
+    }
+
+    #[derive(FromRow, Debug, PartialEq)]
+    struct InventoryItem<T> {
+        name: String,
+        #[postgres(flatten)]
+        supplier: T,
+    }
+
+    let mut conn = Client::connect("user=postgres host=localhost port=5433", NoTls).unwrap();
+
+    let items = conn
+        .query_as::<InventoryItem<Supplier>>(
+            "SELECT 'foo' AS name, 100 AS supplier_id, 'acme' AS supplier_name",
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        items,
+        [InventoryItem {
+            name: "foo".to_owned(),
+            supplier: Supplier {
+                supplier_id: 100,
+                supplier_name: "acme".to_owned(),
+            },
+        }]
+    );
+}
+
+#[test]
+fn errors() {
+    #[derive(FromRow, Debug)]
+    #[allow(dead_code)]
+    struct InventoryItem {
+        name: String,
+        supplier_id: i32,
+    }
+
+    let mut conn = Client::connect("user=postgres host=localhost port=5433", NoTls).unwrap();
+
+    let err = conn
+        .query_as::<InventoryItem>("SELECT 'foo' AS name", &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "invalid column `supplier_id`");
+
+    let err = conn
+        .query_as::<InventoryItem>("SELECT NULL::TEXT AS name, 100 AS supplier_id", &[])
+        .unwrap_err();
+    assert!(err.to_string().starts_with("error deserializing column 0"));
+}
@@ -1,5 +1,6 @@
 #![cfg(test)]
 
+use postgres::fallible_iterator::FallibleIterator;
 use postgres::Client;
 use postgres_types::{FromSqlOwned, ToSql};
 use std::fmt;
@@ -7,6 +8,7 @@ use std::fmt;
 mod composites;
 mod domains;
 mod enums;
+mod from_row;
 mod transparent;
 
 pub fn test_type<T, S>(conn: &mut Client, sql_type: &str, checks: &[(T, S)])
@@ -24,6 +26,16 @@ where
         let stmt = conn.prepare(&format!("SELECT $1::{}", sql_type)).unwrap();
         let result = conn.query_one(&stmt, &[val]).unwrap().get(0);
         assert_eq!(val, &result);
+
+        let query = format!("SELECT {}::{}", *repr, sql_type);
+        let row = conn
+            .query_raw_txt(&query, Vec::<String>::new())
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        let result = row.get(0);
+        assert_eq!(val, &result);
     }
 }
 
@@ -16,3 +16,16 @@ fn round_trip() {
         UserId(123)
     );
 }
+
+#[test]
+fn borrowed() {
+    #[derive(FromSql, ToSql, Debug, PartialEq)]
+    #[postgres(transparent)]
+    struct Name<'a>(&'a str);
+
+    let row = Client::connect("user=postgres host=localhost port=5433", NoTls)
+        .unwrap()
+        .query_one("SELECT $1::text", &[&Name("foo")])
+        .unwrap();
+    assert_eq!(row.get::<_, Name<'_>>(0), Name("foo"));
+}
@@ -4,7 +4,7 @@ use std::iter;
 use syn::Ident;
 
 use crate::composites::Field;
-use crate::enums::Variant;
+use crate::enums::{EnumRepr, Variant};
 
 pub fn transparent_body(field: &syn::Field) -> TokenStream {
     let ty = &field.ty;
@@ -66,6 +66,22 @@ pub fn enum_body(name: &str, variants: &[Variant], allow_mismatch: bool) -> Toke
     }
 }
 
+pub fn enum_repr_body(repr: EnumRepr) -> TokenStream {
+    match repr {
+        EnumRepr::Text => quote! {
+            <&str as ::postgres_types::ToSql>::accepts(type_)
+        },
+        EnumRepr::Integer => quote! {
+            match *type_ {
+                ::postgres_types::Type::INT2
+                | ::postgres_types::Type::INT4
+                | ::postgres_types::Type::INT8 => true,
+                _ => false,
+            }
+        },
+    }
+}
+
 pub fn composite_body(name: &str, trait_: &str, fields: &[Field]) -> TokenStream {
     let num_fields = fields.len();
     let trait_ = Ident::new(trait_, Span::call_site());
@@ -73,6 +89,28 @@ pub fn composite_body(name: &str, trait_: &str, fields: &[Field]) -> TokenStream
     let field_names = fields.iter().map(|f| &f.name);
     let field_types = fields.iter().map(|f| &f.type_);
 
+    // Fields with defaults may be missing from the Postgres type, as long as all of the others are there.
+    let len_check = if fields.iter().any(|f| f.default) {
+        let required_names = fields.iter().filter(|f| !f.default).map(|f| &f.name);
+        quote! {
+            if fields.len() > #num_fields {
+                return false;
+            }
+
+            #(
+                if !fields.iter().any(|f| f.name() == #required_names) {
+                    return false;
+                }
+            )*
+        }
+    } else {
+        quote! {
+            if fields.len() != #num_fields {
+                return false;
+            }
+        }
+    };
+
     quote! {
         if type_.name() != #name {
             return false;
@@ -80,9 +118,7 @@ pub fn composite_body(name: &str, trait_: &str, fields: &[Field]) -> TokenStream
 
         match *type_.kind() {
             ::postgres_types::Kind::Composite(ref fields) => {
-                if fields.len() != #num_fields {
-                    return false;
-                }
+                #len_check
 
                 fields.iter().all(|f| {
                     match f.name() {
@@ -10,6 +10,8 @@ pub struct Field {
     pub name: String,
     pub ident: Ident,
     pub type_: Type,
+    pub flatten: bool,
+    pub default: bool,
 }
 
 impl Field {
@@ -17,6 +19,13 @@ impl Field {
         let overrides = Overrides::extract(&raw.attrs, false)?;
         let ident = raw.ident.as_ref().unwrap().clone();
 
+        if overrides.flatten && overrides.name.is_some() {
+            return Err(Error::new_spanned(
+                raw,
+                "#[postgres(flatten)] is not allowed with #[postgres(name = \"...\")]",
+            ));
+        }
+
         // field level name override takes precendence over container level rename_all override
         let name = match overrides.name {
             Some(n) => n,
@@ -35,10 +44,34 @@ impl Field {
             name,
             ident,
             type_: raw.ty.clone(),
+            flatten: overrides.flatten,
+            default: overrides.default,
         })
     }
 }
 
+// Fields of composite types are single values, which can't be flattened.
+pub fn reject_flatten(fields: &[Field]) -> Result<(), Error> {
+    match fields.iter().find(|field| field.flatten) {
+        Some(field) => Err(Error::new_spanned(
+            &field.ident,
+            "#[postgres(flatten)] may only be applied to fields of #[derive(FromRow)] structs",
+        )),
+        None => Ok(()),
+    }
+}
+
+// Columns of rows are looked up by name, so there are no missing trailing fields to default.
+pub fn reject_default(fields: &[Field]) -> Result<(), Error> {
+    match fields.iter().find(|field| field.default) {
+        Some(field) => Err(Error::new_spanned(
+            &field.ident,
+            "#[postgres(default)] may only be applied to fields of composite types",
+        )),
+        None => Ok(()),
+    }
+}
+
 pub(crate) fn append_generic_bound(mut generics: Generics, bound: &TypeParamBound) -> Generics {
     for param in &mut generics.params {
         if let GenericParam::Type(param) = param {
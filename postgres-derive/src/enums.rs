@@ -2,6 +2,27 @@ use syn::{Error, Fields, Ident};
 
 use crate::{case::RenameRule, overrides::Overrides};
 
+/// The representation of an enum which isn't mapped to a Postgres enum type, set with `#[postgres(repr = "...")]`.
+#[derive(Copy, Clone, PartialEq)]
+pub enum EnumRepr {
+    /// Variants are stored as their names, in text columns.
+    Text,
+    /// Variants are stored as their discriminants, in integer columns.
+    Integer,
+}
+
+pub const ENUM_REPRS: &[&str] = &["text", "integer"];
+
+impl EnumRepr {
+    pub fn from_str(repr: &str) -> Option<EnumRepr> {
+        match repr {
+            "text" => Some(EnumRepr::Text),
+            "integer" => Some(EnumRepr::Integer),
+            _ => None,
+        }
+    }
+}
+
 pub struct Variant {
     pub ident: Ident,
     pub name: String,
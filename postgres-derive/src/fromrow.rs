@@ -0,0 +1,77 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, Data, DataStruct, DeriveInput, Error, Fields};
+
+use crate::composites::{reject_default, Field};
+use crate::overrides::Overrides;
+
+pub fn expand_derive_fromrow(input: DeriveInput) -> Result<TokenStream, Error> {
+    // The other container overrides are those of the Postgres type of the struct, which may also derive `FromSql`.
+    let overrides = Overrides::extract(&input.attrs, true)?;
+
+    let fields = match input.data {
+        Data::Struct(DataStruct {
+            fields: Fields::Named(ref fields),
+            ..
+        }) => fields
+            .named
+            .iter()
+            .map(|field| Field::parse(field, overrides.rename_all))
+            .collect::<Result<Vec<_>, _>>()?,
+        _ => {
+            return Err(Error::new_spanned(
+                input,
+                "#[derive(FromRow)] may only be applied to structs with named fields",
+            ))
+        }
+    };
+    reject_default(&fields)?;
+
+    let ident = &input.ident;
+    let mut generics = input.generics.clone();
+    if generics.type_params().next().is_some() {
+        let where_clause = generics.make_where_clause();
+        for field in &fields {
+            let ty = &field.type_;
+            let predicate = if field.flatten {
+                parse_quote!(#ty: postgres_types::FromRow)
+            } else {
+                parse_quote!(#ty: postgres_types::FromSqlOwned)
+            };
+            where_clause.predicates.push(predicate);
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let field_idents = fields.iter().map(|field| &field.ident);
+    let field_values = fields.iter().map(|field| {
+        let ty = &field.type_;
+        if field.flatten {
+            quote!(<#ty as postgres_types::FromRow>::from_row(row)?)
+        } else {
+            let name = &field.name;
+            quote!(postgres_types::NamedRow::get_named::<#ty>(row, #name)?)
+        }
+    });
+
+    let out = quote! {
+        impl #impl_generics postgres_types::FromRow for #ident #ty_generics #where_clause {
+            fn from_row<R>(row: &R)
+                           -> std::result::Result<#ident #ty_generics,
+                                                  std::boxed::Box<dyn std::error::Error +
+                                                                  std::marker::Sync +
+                                                                  std::marker::Send>>
+            where
+                R: postgres_types::NamedRow + ?std::marker::Sized,
+            {
+                std::result::Result::Ok(#ident {
+                    #(
+                        #field_idents: #field_values,
+                    )*
+                })
+            }
+        }
+    };
+
+    Ok(out)
+}
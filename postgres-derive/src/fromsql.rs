@@ -1,17 +1,17 @@
 use proc_macro2::{Span, TokenStream};
 use quote::{format_ident, quote};
 use std::iter;
+use syn::{parse_quote, LifetimeParam, TraitBound, TraitBoundModifier, TypeParamBound};
 use syn::{
     punctuated::Punctuated, token, AngleBracketedGenericArguments, Data, DataStruct, DeriveInput,
     Error, Fields, GenericArgument, GenericParam, Generics, Ident, Lifetime, PathArguments,
-    PathSegment,
+    PathSegment, Type,
 };
-use syn::{LifetimeParam, TraitBound, TraitBoundModifier, TypeParamBound};
 
 use crate::accepts;
 use crate::composites::Field;
-use crate::composites::{append_generic_bound, new_derive_path};
-use crate::enums::Variant;
+use crate::composites::{append_generic_bound, new_derive_path, reject_flatten};
+use crate::enums::{EnumRepr, Variant};
 use crate::overrides::Overrides;
 
 pub fn expand_derive_fromsql(input: DeriveInput) -> Result<TokenStream, Error> {
@@ -24,12 +24,19 @@ pub fn expand_derive_fromsql(input: DeriveInput) -> Result<TokenStream, Error> {
         ));
     }
 
+    if overrides.repr.is_some() && (overrides.transparent || overrides.allow_mismatch) {
+        return Err(Error::new_spanned(
+            &input,
+            "#[postgres(repr = \"...\")] is not allowed with #[postgres(transparent)] or #[postgres(allow_mismatch)]",
+        ));
+    }
+
     let name = overrides
         .name
         .clone()
         .unwrap_or_else(|| input.ident.to_string());
 
-    let (accepts_body, to_sql_body) = if overrides.transparent {
+    let (accepts_body, to_sql_body, text_body, owned_text_body) = if overrides.transparent {
         match input.data {
             Data::Struct(DataStruct {
                 fields: Fields::Unnamed(ref fields),
@@ -39,6 +46,8 @@ pub fn expand_derive_fromsql(input: DeriveInput) -> Result<TokenStream, Error> {
                 (
                     accepts::transparent_body(field),
                     transparent_body(&input.ident, field),
+                    transparent_text_body(&input.ident, field),
+                    transparent_owned_text_body(&input.ident, field),
                 )
             }
             _ => {
@@ -48,6 +57,36 @@ pub fn expand_derive_fromsql(input: DeriveInput) -> Result<TokenStream, Error> {
                 ))
             }
         }
+    } else if let Some(repr) = overrides.repr {
+        match input.data {
+            Data::Enum(ref data) => {
+                let variants = data
+                    .variants
+                    .iter()
+                    .map(|variant| Variant::parse(variant, overrides.rename_all))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match repr {
+                    EnumRepr::Text => (
+                        accepts::enum_repr_body(repr),
+                        enum_body(&input.ident, &variants),
+                        enum_body(&input.ident, &variants),
+                        enum_body(&input.ident, &variants),
+                    ),
+                    EnumRepr::Integer => (
+                        accepts::enum_repr_body(repr),
+                        integer_enum_body(&input.ident, &variants),
+                        integer_enum_text_body(&input.ident, &variants),
+                        integer_enum_text_body(&input.ident, &variants),
+                    ),
+                }
+            }
+            _ => {
+                return Err(Error::new_spanned(
+                    input,
+                    "#[postgres(repr = \"...\")] may only be applied to enums",
+                ));
+            }
+        }
     } else if overrides.allow_mismatch {
         match input.data {
             Data::Enum(ref data) => {
@@ -59,6 +98,8 @@ pub fn expand_derive_fromsql(input: DeriveInput) -> Result<TokenStream, Error> {
                 (
                     accepts::enum_body(&name, &variants, overrides.allow_mismatch),
                     enum_body(&input.ident, &variants),
+                    enum_body(&input.ident, &variants),
+                    enum_body(&input.ident, &variants),
                 )
             }
             _ => {
@@ -79,6 +120,8 @@ pub fn expand_derive_fromsql(input: DeriveInput) -> Result<TokenStream, Error> {
             (
                 accepts::enum_body(&name, &variants, overrides.allow_mismatch),
                 enum_body(&input.ident, &variants),
+                enum_body(&input.ident, &variants),
+                enum_body(&input.ident, &variants),
             )
         }
         Data::Struct(DataStruct {
@@ -89,6 +132,8 @@ pub fn expand_derive_fromsql(input: DeriveInput) -> Result<TokenStream, Error> {
             (
                 domain_accepts_body(&name, field),
                 domain_body(&input.ident, field),
+                domain_text_body(&input.ident, field),
+                domain_owned_text_body(&input.ident, field),
             )
         }
         Data::Struct(DataStruct {
@@ -100,9 +145,12 @@ pub fn expand_derive_fromsql(input: DeriveInput) -> Result<TokenStream, Error> {
                 .iter()
                 .map(|field| Field::parse(field, overrides.rename_all))
                 .collect::<Result<Vec<_>, _>>()?;
+            reject_flatten(&fields)?;
             (
                 accepts::composite_body(&name, "FromSql", &fields),
                 composite_body(&input.ident, &fields),
+                composite_text_body(&input.ident, &fields, false),
+                composite_text_body(&input.ident, &fields, true),
             )
         }
         _ => {
@@ -115,9 +163,9 @@ pub fn expand_derive_fromsql(input: DeriveInput) -> Result<TokenStream, Error> {
     };
 
     let ident = &input.ident;
-    let (generics, lifetime) = build_generics(&input.generics);
-    let (impl_generics, _, _) = generics.split_for_impl();
-    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+    let (generics, lifetime) = build_generics(&input.generics, &field_types(&input.data));
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
     let out = quote! {
         impl #impl_generics postgres_types::FromSql<#lifetime> for #ident #ty_generics #where_clause {
             fn from_sql(_type: &postgres_types::Type, buf: &#lifetime [u8])
@@ -128,6 +176,26 @@ pub fn expand_derive_fromsql(input: DeriveInput) -> Result<TokenStream, Error> {
                 #to_sql_body
             }
 
+            fn from_sql_text(_type: &postgres_types::Type,
+                             buf: &#lifetime [u8],
+                             _settings: &postgres_types::TextSettings)
+                             -> std::result::Result<#ident #ty_generics,
+                                                    std::boxed::Box<dyn std::error::Error +
+                                                                    std::marker::Sync +
+                                                                    std::marker::Send>> {
+                #text_body
+            }
+
+            fn from_sql_text_owned(_type: &postgres_types::Type,
+                                   buf: &[u8],
+                                   _settings: &postgres_types::TextSettings)
+                                   -> std::result::Result<#ident #ty_generics,
+                                                          std::boxed::Box<dyn std::error::Error +
+                                                                          std::marker::Sync +
+                                                                          std::marker::Send>> {
+                #owned_text_body
+            }
+
             fn accepts(type_: &postgres_types::Type) -> bool {
                 #accepts_body
             }
@@ -144,6 +212,20 @@ fn transparent_body(ident: &Ident, field: &syn::Field) -> TokenStream {
     }
 }
 
+fn transparent_text_body(ident: &Ident, field: &syn::Field) -> TokenStream {
+    let ty = &field.ty;
+    quote! {
+        <#ty as postgres_types::FromSql>::from_sql_text(_type, buf, _settings).map(#ident)
+    }
+}
+
+fn transparent_owned_text_body(ident: &Ident, field: &syn::Field) -> TokenStream {
+    let ty = &field.ty;
+    quote! {
+        <#ty as postgres_types::FromSql>::from_sql_text_owned(_type, buf, _settings).map(#ident)
+    }
+}
+
 fn enum_body(ident: &Ident, variants: &[Variant]) -> TokenStream {
     let variant_names = variants.iter().map(|v| &v.name);
     let idents = iter::repeat(ident);
@@ -162,6 +244,49 @@ fn enum_body(ident: &Ident, variants: &[Variant]) -> TokenStream {
     }
 }
 
+fn integer_enum_body(ident: &Ident, variants: &[Variant]) -> TokenStream {
+    let match_body = integer_enum_match(ident, variants);
+
+    quote! {
+        let value = match *_type {
+            postgres_types::Type::INT2 => {
+                i64::from(<i16 as postgres_types::FromSql>::from_sql(_type, buf)?)
+            }
+            postgres_types::Type::INT4 => {
+                i64::from(<i32 as postgres_types::FromSql>::from_sql(_type, buf)?)
+            }
+            _ => <i64 as postgres_types::FromSql>::from_sql(_type, buf)?,
+        };
+
+        #match_body
+    }
+}
+
+fn integer_enum_text_body(ident: &Ident, variants: &[Variant]) -> TokenStream {
+    let match_body = integer_enum_match(ident, variants);
+
+    quote! {
+        let value = std::str::from_utf8(buf)?.parse::<i64>()?;
+
+        #match_body
+    }
+}
+
+fn integer_enum_match(ident: &Ident, variants: &[Variant]) -> TokenStream {
+    let variant_idents = variants.iter().map(|v| &v.ident);
+
+    quote! {
+        #(
+            if value == #ident::#variant_idents as i64 {
+                return std::result::Result::Ok(#ident::#variant_idents);
+            }
+        )*
+
+        std::result::Result::Err(
+            std::convert::Into::into(format!("invalid variant `{}`", value)))
+    }
+}
+
 // Domains are sometimes but not always just represented by the bare type (!?)
 fn domain_accepts_body(name: &str, field: &syn::Field) -> TokenStream {
     let ty = &field.ty;
@@ -183,6 +308,20 @@ fn domain_body(ident: &Ident, field: &syn::Field) -> TokenStream {
     }
 }
 
+fn domain_text_body(ident: &Ident, field: &syn::Field) -> TokenStream {
+    let ty = &field.ty;
+    quote! {
+        <#ty as postgres_types::FromSql>::from_sql_text(_type, buf, _settings).map(#ident)
+    }
+}
+
+fn domain_owned_text_body(ident: &Ident, field: &syn::Field) -> TokenStream {
+    let ty = &field.ty;
+    quote! {
+        <#ty as postgres_types::FromSql>::from_sql_text_owned(_type, buf, _settings).map(#ident)
+    }
+}
+
 fn composite_body(ident: &Ident, fields: &[Field]) -> TokenStream {
     let temp_vars = &fields
         .iter()
@@ -190,6 +329,7 @@ fn composite_body(ident: &Ident, fields: &[Field]) -> TokenStream {
         .collect::<Vec<_>>();
     let field_names = &fields.iter().map(|f| &f.name).collect::<Vec<_>>();
     let field_idents = &fields.iter().map(|f| &f.ident).collect::<Vec<_>>();
+    let field_values = field_values(fields, temp_vars);
 
     quote! {
         let fields = match *_type.kind() {
@@ -227,15 +367,99 @@ fn composite_body(ident: &Ident, fields: &[Field]) -> TokenStream {
 
         std::result::Result::Ok(#ident {
             #(
-                #field_idents: #temp_vars.unwrap(),
+                #field_idents: #field_values,
             )*
         })
     }
 }
 
-fn build_generics(source: &Generics) -> (Generics, Lifetime) {
-    // don't worry about lifetime name collisions, it doesn't make sense to derive FromSql on a struct with a lifetime
-    let lifetime = Lifetime::new("'a", Span::call_site());
+// An owned record can't lend its fields for the lifetime of the value, so they are all read as owned values.
+fn composite_text_body(ident: &Ident, fields: &[Field], owned: bool) -> TokenStream {
+    let temp_vars = &fields
+        .iter()
+        .map(|f| format_ident!("__{}", f.ident))
+        .collect::<Vec<_>>();
+    let field_names = &fields.iter().map(|f| &f.name).collect::<Vec<_>>();
+    let field_idents = &fields.iter().map(|f| &f.ident).collect::<Vec<_>>();
+    let field_values = field_values(fields, temp_vars);
+    let read_value = if owned {
+        quote!(postgres_types::private::read_text_value_owned(
+            field.type_(),
+            value.as_deref(),
+            _settings
+        ))
+    } else {
+        quote!(postgres_types::private::read_text_value(
+            field.type_(),
+            value,
+            _settings
+        ))
+    };
+
+    quote! {
+        let fields = match *_type.kind() {
+            postgres_types::Kind::Composite(ref fields) => fields,
+            _ => unreachable!(),
+        };
+
+        let values = postgres_types::private::split_text_record(buf)?;
+        if values.len() != fields.len() {
+            return std::result::Result::Err(
+                std::convert::Into::into(format!("invalid field count: {} vs {}", values.len(), fields.len())));
+        }
+
+        #(
+            let mut #temp_vars = std::option::Option::None;
+        )*
+
+        for (field, value) in fields.iter().zip(values) {
+            match field.name() {
+                #(
+                    #field_names => {
+                        #temp_vars = std::option::Option::Some(#read_value?);
+                    }
+                )*
+                _ => unreachable!(),
+            }
+        }
+
+        std::result::Result::Ok(#ident {
+            #(
+                #field_idents: #field_values,
+            )*
+        })
+    }
+}
+
+// Fields missing from the Postgres type are left to their defaults.
+fn field_values(fields: &[Field], temp_vars: &[Ident]) -> Vec<TokenStream> {
+    fields
+        .iter()
+        .zip(temp_vars)
+        .map(|(field, temp_var)| {
+            if field.default {
+                quote!(#temp_var.unwrap_or_default())
+            } else {
+                quote!(#temp_var.unwrap())
+            }
+        })
+        .collect()
+}
+
+fn field_types(data: &Data) -> Vec<&Type> {
+    match data {
+        Data::Struct(data) => data.fields.iter().map(|field| &field.ty).collect(),
+        _ => vec![],
+    }
+}
+
+fn build_generics(source: &Generics, field_types: &[&Type]) -> (Generics, Lifetime) {
+    let has_lifetimes = source.lifetimes().next().is_some();
+    let lifetime = if has_lifetimes {
+        Lifetime::new("'__from_sql", Span::call_site())
+    } else {
+        Lifetime::new("'a", Span::call_site())
+    };
 
     let mut out = append_generic_bound(source.to_owned(), &new_fromsql_bound(&lifetime));
     out.params.insert(
@@ -243,6 +467,17 @@ fn build_generics(source: &Generics) -> (Generics, Lifetime) {
         GenericParam::Lifetime(LifetimeParam::new(lifetime.to_owned())),
     );
 
+    // Borrowed fields can only be read from a buffer living as long as what they borrow, which is expressed by
+    // bounding the types of the fields rather than the lifetimes of the type, so that `FromSql<'a>` is implemented by
+    // the type instantiated with `'a` for its lifetimes.
+    if has_lifetimes {
+        let bound = new_fromsql_bound(&lifetime);
+        let where_clause = out.make_where_clause();
+        for ty in field_types {
+            where_clause.predicates.push(parse_quote!(#ty: #bound));
+        }
+    }
+
     (out, lifetime)
 }
 
@@ -2,12 +2,16 @@ use syn::punctuated::Punctuated;
 use syn::{Attribute, Error, Expr, ExprLit, Lit, Meta, Token};
 
 use crate::case::{RenameRule, RENAME_RULES};
+use crate::enums::{EnumRepr, ENUM_REPRS};
 
 pub struct Overrides {
     pub name: Option<String>,
     pub rename_all: Option<RenameRule>,
+    pub repr: Option<EnumRepr>,
     pub transparent: bool,
     pub allow_mismatch: bool,
+    pub flatten: bool,
+    pub default: bool,
 }
 
 impl Overrides {
@@ -15,8 +19,11 @@ impl Overrides {
         let mut overrides = Overrides {
             name: None,
             rename_all: None,
+            repr: None,
             transparent: false,
             allow_mismatch: false,
+            flatten: false,
+            default: false,
         };
 
         for attr in attrs {
@@ -36,13 +43,20 @@ impl Overrides {
                     Meta::NameValue(meta) => {
                         let name_override = meta.path.is_ident("name");
                         let rename_all_override = meta.path.is_ident("rename_all");
+                        let repr_override = meta.path.is_ident("repr");
                         if !container_attr && rename_all_override {
                             return Err(Error::new_spanned(
                                 &meta.path,
                                 "rename_all is a container attribute",
                             ));
                         }
-                        if !name_override && !rename_all_override {
+                        if !container_attr && repr_override {
+                            return Err(Error::new_spanned(
+                                &meta.path,
+                                "repr is a container attribute",
+                            ));
+                        }
+                        if !name_override && !rename_all_override && !repr_override {
                             return Err(Error::new_spanned(&meta.path, "unknown override"));
                         }
 
@@ -73,6 +87,22 @@ impl Overrides {
                             })?;
 
                             overrides.rename_all = Some(rename_rule);
+                        } else if repr_override {
+                            let repr = EnumRepr::from_str(&value).ok_or_else(|| {
+                                Error::new_spanned(
+                                    &meta.value,
+                                    format!(
+                                        "invalid repr, expected one of: {}",
+                                        ENUM_REPRS
+                                            .iter()
+                                            .map(|repr| format!("\"{}\"", repr))
+                                            .collect::<Vec<_>>()
+                                            .join(", ")
+                                    ),
+                                )
+                            })?;
+
+                            overrides.repr = Some(repr);
                         }
                     }
                     Meta::Path(path) => {
@@ -92,6 +122,22 @@ impl Overrides {
                                 ));
                             }
                             overrides.allow_mismatch = true;
+                        } else if path.is_ident("flatten") {
+                            if container_attr {
+                                return Err(Error::new_spanned(
+                                    path,
+                                    "flatten is a field attribute",
+                                ));
+                            }
+                            overrides.flatten = true;
+                        } else if path.is_ident("default") {
+                            if container_attr {
+                                return Err(Error::new_spanned(
+                                    path,
+                                    "default is a field attribute",
+                                ));
+                            }
+                            overrides.default = true;
                         } else {
                             return Err(Error::new_spanned(path, "unknown override"));
                         }
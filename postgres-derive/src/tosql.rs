@@ -8,8 +8,8 @@ use syn::{
 
 use crate::accepts;
 use crate::composites::Field;
-use crate::composites::{append_generic_bound, new_derive_path};
-use crate::enums::Variant;
+use crate::composites::{append_generic_bound, new_derive_path, reject_flatten};
+use crate::enums::{EnumRepr, Variant};
 use crate::overrides::Overrides;
 
 pub fn expand_derive_tosql(input: DeriveInput) -> Result<TokenStream, Error> {
@@ -22,6 +22,13 @@ pub fn expand_derive_tosql(input: DeriveInput) -> Result<TokenStream, Error> {
         ));
     }
 
+    if overrides.repr.is_some() && (overrides.transparent || overrides.allow_mismatch) {
+        return Err(Error::new_spanned(
+            &input,
+            "#[postgres(repr = \"...\")] is not allowed with #[postgres(transparent)] or #[postgres(allow_mismatch)]",
+        ));
+    }
+
     let name = overrides
         .name
         .clone()
@@ -44,6 +51,27 @@ pub fn expand_derive_tosql(input: DeriveInput) -> Result<TokenStream, Error> {
                 ));
             }
         }
+    } else if let Some(repr) = overrides.repr {
+        match input.data {
+            Data::Enum(ref data) => {
+                let variants = data
+                    .variants
+                    .iter()
+                    .map(|variant| Variant::parse(variant, overrides.rename_all))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let to_sql_body = match repr {
+                    EnumRepr::Text => enum_body(&input.ident, &variants),
+                    EnumRepr::Integer => integer_enum_body(&input.ident, &variants),
+                };
+                (accepts::enum_repr_body(repr), to_sql_body)
+            }
+            _ => {
+                return Err(Error::new_spanned(
+                    input,
+                    "#[postgres(repr = \"...\")] may only be applied to enums",
+                ));
+            }
+        }
     } else if overrides.allow_mismatch {
         match input.data {
             Data::Enum(ref data) => {
@@ -94,6 +122,7 @@ pub fn expand_derive_tosql(input: DeriveInput) -> Result<TokenStream, Error> {
                     .iter()
                     .map(|field| Field::parse(field, overrides.rename_all))
                     .collect::<Result<Vec<_>, _>>()?;
+                reject_flatten(&fields)?;
                 (
                     accepts::composite_body(&name, "ToSql", &fields),
                     composite_body(&fields),
@@ -157,6 +186,30 @@ fn enum_body(ident: &Ident, variants: &[Variant]) -> TokenStream {
     }
 }
 
+fn integer_enum_body(ident: &Ident, variants: &[Variant]) -> TokenStream {
+    let variant_idents = variants.iter().map(|v| &v.ident);
+
+    quote! {
+        let value = match *self {
+            #(
+                #ident::#variant_idents => #ident::#variant_idents as i64,
+            )*
+        };
+
+        match *_type {
+            postgres_types::Type::INT2 => {
+                let value = <i16 as std::convert::TryFrom<i64>>::try_from(value)?;
+                postgres_types::ToSql::to_sql(&value, _type, buf)
+            }
+            postgres_types::Type::INT4 => {
+                let value = <i32 as std::convert::TryFrom<i64>>::try_from(value)?;
+                postgres_types::ToSql::to_sql(&value, _type, buf)
+            }
+            _ => postgres_types::ToSql::to_sql(&value, _type, buf),
+        }
+    }
+}
+
 fn domain_body() -> TokenStream {
     quote! {
         let type_ = match *_type.kind() {
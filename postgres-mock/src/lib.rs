@@ -0,0 +1,815 @@
+//! An in-process mock PostgreSQL server, for unit testing code built on `tokio-postgres` without a database.
+//!
+//! A [`MockServer`] holds a script of the requests a client is expected to make, each with the response to send
+//! back. Connecting to it returns a `tokio_postgres::Client` talking to the server over an in-memory stream, along
+//! with a [`MockHandle`] to check that the script was followed once the code under test has run.
+//!
+//! ```
+//! use postgres_mock::{MockServer, Response};
+//! use tokio_postgres::types::Type;
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let (client, connection, server) = MockServer::new()
+//!     .prepare("SELECT name FROM users WHERE id = $1", &[Type::INT4], &[("name", Type::TEXT)])
+//!     .execute(
+//!         Response::new()
+//!             .columns(&[Type::TEXT])
+//!             .row(&[&"alice"])
+//!             .command_complete("SELECT 1"),
+//!     )
+//!     .connect()
+//!     .await
+//!     .unwrap();
+//! tokio::spawn(connection);
+//!
+//! let row = client
+//!     .query_one("SELECT name FROM users WHERE id = $1", &[&1i32])
+//!     .await
+//!     .unwrap();
+//! assert_eq!(row.get::<_, &str>(0), "alice");
+//!
+//! server.finish().unwrap();
+//! # }
+//! ```
+//!
+//! # Requests
+//!
+//! The server groups the messages it receives the way clients send them: a simple query is a request of its own,
+//! while extended query messages are grouped up to the `Sync` or `Flush` which ends them. Each request is matched
+//! against the next step of the script, and answered with the response of the step followed, for requests ending
+//! with a `Sync` or a simple query, by a `ReadyForQuery` message.
+//!
+//! Requests which only close statements or portals, which the client sends as they are dropped, are answered without
+//! being matched against the script. A request which doesn't match the next step is answered with an error, and
+//! reported by [`MockHandle::finish`].
+//!
+//! Values in the binary format, as used by `Client::query` and friends, are only decoded by the client with the
+//! types of the statement: the columns of an [`execute`] response must match the ones given to [`prepare`]. Types
+//! which aren't built in are looked up by the client with queries to the catalog, which would have to be scripted.
+//!
+//! [`execute`]: MockServer::execute
+//! [`prepare`]: MockServer::prepare
+//!
+//! # Record and replay
+//!
+//! Rather than being scripted, a server can replay a session recorded against a real one, to reproduce its exact
+//! sequence of messages offline. Sessions are recorded by connecting through a [`RecordingStream`] with
+//! `Config::connect_raw`, and replayed with a [`Replay`].
+#![warn(rust_2018_idioms, clippy::all, missing_docs)]
+
+use bytes::{Buf, Bytes, BytesMut};
+use postgres_protocol::message::backend::{self, FieldDescription};
+use postgres_protocol::Oid;
+use std::collections::VecDeque;
+use std::error;
+use std::fmt;
+use std::io;
+use std::str;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+use tokio_postgres::tls::{NoTls, NoTlsStream};
+use tokio_postgres::types::{IsNull, ToSql, Type};
+use tokio_postgres::{Client, Config, Connection, TransactionStatus};
+
+pub use crate::replay::{RecordingStream, Replay, ReplayHandle};
+
+mod replay;
+#[cfg(test)]
+mod test;
+
+const SSL_REQUEST_CODE: i32 = 80_877_103;
+const GSSENC_REQUEST_CODE: i32 = 80_877_104;
+const PROTOCOL_VERSION: i32 = 0x00_03_00_00;
+
+/// A message sent by a client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FrontendMessage {
+    /// A simple query.
+    Query {
+        /// The query string.
+        query: String,
+    },
+    /// The parsing of a statement.
+    Parse {
+        /// The name of the statement, empty for the unnamed statement.
+        name: String,
+        /// The query string.
+        query: String,
+        /// The OIDs of the types of the parameters specified by the client.
+        param_types: Vec<Oid>,
+    },
+    /// The binding of a statement to parameters, creating a portal.
+    Bind {
+        /// The name of the portal, empty for the unnamed portal.
+        portal: String,
+        /// The name of the statement.
+        statement: String,
+        /// The formats of the parameters.
+        param_formats: Vec<i16>,
+        /// The values of the parameters.
+        params: Vec<Option<Bytes>>,
+        /// The formats requested for the columns of the results.
+        result_formats: Vec<i16>,
+    },
+    /// A request for the description of a statement (`b'S'`) or a portal (`b'P'`).
+    Describe {
+        /// `b'S'` for a statement or `b'P'` for a portal.
+        variant: u8,
+        /// The name of the statement or portal.
+        name: String,
+    },
+    /// The execution of a portal.
+    Execute {
+        /// The name of the portal.
+        portal: String,
+        /// The maximum number of rows to return, or 0 for all of them.
+        max_rows: i32,
+    },
+    /// The closing of a statement (`b'S'`) or a portal (`b'P'`).
+    Close {
+        /// `b'S'` for a statement or `b'P'` for a portal.
+        variant: u8,
+        /// The name of the statement or portal.
+        name: String,
+    },
+    /// The end of a sequence of extended query messages.
+    Sync,
+    /// A request to send the pending responses.
+    Flush,
+    /// Data of a `COPY ... FROM STDIN`.
+    CopyData(Bytes),
+    /// The end of the data of a `COPY ... FROM STDIN`.
+    CopyDone,
+    /// The failure of a `COPY ... FROM STDIN`.
+    CopyFail(String),
+    /// A password or an authentication response.
+    Password(Bytes),
+    /// The end of the session.
+    Terminate,
+    /// A message of an other type.
+    Other {
+        /// The type of the message.
+        tag: u8,
+        /// The contents of the message.
+        body: Bytes,
+    },
+}
+
+/// The response to a request of a client.
+///
+/// A response is a sequence of backend messages, built up in the order they are sent.
+#[derive(Debug, Clone)]
+pub struct Response {
+    buf: BytesMut,
+    types: Vec<Type>,
+    status: TransactionStatus,
+}
+
+impl Default for Response {
+    fn default() -> Response {
+        Response::new()
+    }
+}
+
+impl Response {
+    /// Creates an empty response.
+    ///
+    /// The `ReadyForQuery` message sent after it reports an idle connection, unless changed with
+    /// [`transaction_status`].
+    ///
+    /// [`transaction_status`]: Response::transaction_status
+    pub fn new() -> Response {
+        Response {
+            buf: BytesMut::new(),
+            types: vec![],
+            status: TransactionStatus::Idle,
+        }
+    }
+
+    /// Adds a `RowDescription` message describing columns of the provided names and types.
+    ///
+    /// This is the start of the results of a simple query. It also sets the types of the values of rows added by
+    /// [`row`].
+    ///
+    /// [`row`]: Response::row
+    pub fn row_description(mut self, columns: &[(&str, Type)]) -> Response {
+        write_row_description(columns, &mut self.buf);
+        self.types = columns.iter().map(|(_, type_)| type_.clone()).collect();
+        self
+    }
+
+    /// Sets the types of the values of rows added by [`row`], without sending a message.
+    ///
+    /// The results of an extended query aren't described, since the client knows the columns of the statement.
+    ///
+    /// [`row`]: Response::row
+    pub fn columns(mut self, types: &[Type]) -> Response {
+        self.types = types.to_vec();
+        self
+    }
+
+    /// Adds a `DataRow` message holding values in the binary format.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of values doesn't match the number of columns, or if a value can't be converted to the
+    /// type of its column.
+    pub fn row(mut self, values: &[&(dyn ToSql + Sync)]) -> Response {
+        assert_eq!(
+            values.len(),
+            self.types.len(),
+            "expected {} values for the columns of the row",
+            self.types.len()
+        );
+
+        let values = values
+            .iter()
+            .zip(&self.types)
+            .map(|(value, type_)| {
+                let mut buf = BytesMut::new();
+                match value.to_sql_checked(type_, &mut buf) {
+                    Ok(IsNull::No) => Some(buf),
+                    Ok(IsNull::Yes) => None,
+                    Err(e) => panic!("error converting a value of the row: {}", e),
+                }
+            })
+            .collect::<Vec<_>>();
+        backend::data_row(values.iter().map(|v| v.as_deref()), &mut self.buf).unwrap();
+        self
+    }
+
+    /// Adds a `DataRow` message holding values in the text format.
+    ///
+    /// The results of simple queries, and of `Client::query_raw_txt`, are in the text format.
+    pub fn text_row(mut self, values: &[Option<&str>]) -> Response {
+        backend::data_row(values.iter().map(|v| v.map(str::as_bytes)), &mut self.buf).unwrap();
+        self
+    }
+
+    /// Adds a `CommandComplete` message with the provided command tag, such as `SELECT 2` or `INSERT 0 1`.
+    pub fn command_complete(mut self, tag: &str) -> Response {
+        backend::command_complete(tag, &mut self.buf).unwrap();
+        self
+    }
+
+    /// Adds an `EmptyQueryResponse` message, the response to an empty query.
+    pub fn empty_query(mut self) -> Response {
+        backend::empty_query_response(&mut self.buf);
+        self
+    }
+
+    /// Adds an `ErrorResponse` message with the provided SQLSTATE code and message.
+    pub fn error(mut self, code: &str, message: &str) -> Response {
+        backend::error_response(
+            [
+                (b'S', "ERROR"),
+                (b'V', "ERROR"),
+                (b'C', code),
+                (b'M', message),
+            ],
+            &mut self.buf,
+        )
+        .unwrap();
+        self
+    }
+
+    /// Adds a `NoticeResponse` message with the provided message.
+    pub fn notice(mut self, message: &str) -> Response {
+        backend::notice_response(
+            [
+                (b'S', "NOTICE"),
+                (b'V', "NOTICE"),
+                (b'C', "00000"),
+                (b'M', message),
+            ],
+            &mut self.buf,
+        )
+        .unwrap();
+        self
+    }
+
+    /// Adds a `NotificationResponse` message, as sent for a `NOTIFY` on a channel the client listens to.
+    pub fn notification(mut self, process_id: i32, channel: &str, payload: &str) -> Response {
+        backend::notification_response(process_id, channel, payload, &mut self.buf).unwrap();
+        self
+    }
+
+    /// Adds a `ParameterStatus` message reporting a new value of a parameter.
+    pub fn parameter_status(mut self, name: &str, value: &str) -> Response {
+        backend::parameter_status(name, value, &mut self.buf).unwrap();
+        self
+    }
+
+    /// Adds a message serialized with the functions of `postgres_protocol::message::backend`.
+    pub fn raw(mut self, message: &[u8]) -> Response {
+        self.buf.extend_from_slice(message);
+        self
+    }
+
+    /// Sets the transaction status reported by the `ReadyForQuery` message sent after the response.
+    pub fn transaction_status(mut self, status: TransactionStatus) -> Response {
+        self.status = status;
+        self
+    }
+}
+
+type Matcher = Box<dyn Fn(&[FrontendMessage]) -> bool + Send>;
+
+struct Step {
+    description: String,
+    matcher: Matcher,
+    response: Response,
+}
+
+struct State {
+    steps: VecDeque<Step>,
+    failures: Vec<String>,
+}
+
+/// A script of the requests a client is expected to make, and of the responses to send back.
+pub struct MockServer {
+    parameters: Vec<(String, String)>,
+    steps: VecDeque<Step>,
+}
+
+impl Default for MockServer {
+    fn default() -> MockServer {
+        MockServer::new()
+    }
+}
+
+impl fmt::Debug for MockServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockServer")
+            .field("parameters", &self.parameters)
+            .field(
+                "steps",
+                &self
+                    .steps
+                    .iter()
+                    .map(|s| &s.description)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl MockServer {
+    /// Creates an empty script.
+    ///
+    /// The server reports the parameters of a PostgreSQL 15 server using UTF-8 and ISO dates on startup.
+    pub fn new() -> MockServer {
+        let parameters = [
+            ("application_name", ""),
+            ("client_encoding", "UTF8"),
+            ("DateStyle", "ISO, MDY"),
+            ("integer_datetimes", "on"),
+            ("IntervalStyle", "postgres"),
+            ("is_superuser", "on"),
+            ("server_encoding", "UTF8"),
+            ("server_version", "15.0"),
+            ("standard_conforming_strings", "on"),
+            ("TimeZone", "UTC"),
+        ];
+
+        MockServer {
+            parameters: parameters
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            steps: VecDeque::new(),
+        }
+    }
+
+    /// Sets a parameter reported by the server on startup.
+    pub fn parameter(mut self, name: &str, value: &str) -> MockServer {
+        match self.parameters.iter_mut().find(|(n, _)| n == name) {
+            Some((_, v)) => *v = value.to_string(),
+            None => self.parameters.push((name.to_string(), value.to_string())),
+        }
+        self
+    }
+
+    /// Expects a simple query, as sent by `Client::simple_query` and `Client::batch_execute`.
+    pub fn simple_query(self, query: &str, response: Response) -> MockServer {
+        let expected = query.to_string();
+        self.expect(
+            &format!("simple query {:?}", query),
+            move |messages| matches!(messages, [FrontendMessage::Query { query }] if *query == expected),
+            response,
+        )
+    }
+
+    /// Expects the preparation of a statement, as done by `Client::prepare` and by queries given as strings.
+    ///
+    /// The statement is described with the provided parameter types and columns.
+    pub fn prepare(self, query: &str, params: &[Type], columns: &[(&str, Type)]) -> MockServer {
+        let mut buf = BytesMut::new();
+        backend::parse_complete(&mut buf);
+        backend::parameter_description(params.iter().map(Type::oid), &mut buf).unwrap();
+        if columns.is_empty() {
+            backend::no_data(&mut buf);
+        } else {
+            write_row_description(columns, &mut buf);
+        }
+
+        let expected = query.to_string();
+        self.expect(
+            &format!("prepare {:?}", query),
+            move |messages| {
+                messages.iter().any(
+                    |m| matches!(m, FrontendMessage::Parse { query, .. } if *query == expected),
+                ) && messages
+                    .iter()
+                    .any(|m| matches!(m, FrontendMessage::Describe { variant: b'S', .. }))
+            },
+            Response {
+                buf,
+                types: vec![],
+                status: TransactionStatus::Idle,
+            },
+        )
+    }
+
+    /// Expects the execution of a prepared statement, as done by `Client::query`, `Client::execute` and friends.
+    ///
+    /// The response follows the `BindComplete` message for the binding of the statement.
+    pub fn execute(self, response: Response) -> MockServer {
+        let mut buf = BytesMut::new();
+        backend::bind_complete(&mut buf);
+        buf.extend_from_slice(&response.buf);
+
+        self.expect(
+            "execute",
+            |messages| {
+                messages
+                    .iter()
+                    .any(|m| matches!(m, FrontendMessage::Bind { .. }))
+                    && messages
+                        .iter()
+                        .any(|m| matches!(m, FrontendMessage::Execute { .. }))
+            },
+            Response { buf, ..response },
+        )
+    }
+
+    /// Expects a request matched by a function of its messages.
+    ///
+    /// The description identifies the step in the errors reported when it isn't matched.
+    pub fn expect<F>(mut self, description: &str, matcher: F, response: Response) -> MockServer
+    where
+        F: Fn(&[FrontendMessage]) -> bool + Send + 'static,
+    {
+        self.steps.push_back(Step {
+            description: description.to_string(),
+            matcher: Box::new(matcher),
+            response,
+        });
+        self
+    }
+
+    /// Starts the server, and connects a client to it with a default configuration.
+    ///
+    /// The server runs in a task spawned on the current Tokio runtime, until the client disconnects.
+    pub async fn connect(
+        self,
+    ) -> Result<(Client, Connection<DuplexStream, NoTlsStream>, MockHandle), tokio_postgres::Error>
+    {
+        let mut config = Config::new();
+        config.user("postgres");
+        self.connect_with(&config).await
+    }
+
+    /// Like [`connect`], but with the provided configuration.
+    ///
+    /// [`connect`]: MockServer::connect
+    pub async fn connect_with(
+        self,
+        config: &Config,
+    ) -> Result<(Client, Connection<DuplexStream, NoTlsStream>, MockHandle), tokio_postgres::Error>
+    {
+        let (client_stream, server_stream) = tokio::io::duplex(64 * 1024);
+        let state = Arc::new(Mutex::new(State {
+            steps: self.steps,
+            failures: vec![],
+        }));
+
+        let parameters = self.parameters;
+        let server_state = state.clone();
+        tokio::spawn(async move {
+            if let Err(e) = serve(server_stream, &parameters, &server_state).await {
+                server_state
+                    .lock()
+                    .unwrap()
+                    .failures
+                    .push(format!("server error: {}", e));
+            }
+        });
+
+        let (client, connection) = config.connect_raw(client_stream, NoTls).await?;
+        Ok((client, connection, MockHandle { state }))
+    }
+}
+
+/// A handle to a running [`MockServer`].
+pub struct MockHandle {
+    state: Arc<Mutex<State>>,
+}
+
+impl fmt::Debug for MockHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockHandle").finish_non_exhaustive()
+    }
+}
+
+impl MockHandle {
+    /// Checks that every step of the script was matched, and that no unexpected request was received.
+    ///
+    /// The server matches steps as it receives requests, so this is accurate once the client has received the
+    /// responses to every request of the code under test.
+    pub fn finish(self) -> Result<(), MockError> {
+        let state = self.state.lock().unwrap();
+        let mut problems = state.failures.clone();
+        problems.extend(
+            state
+                .steps
+                .iter()
+                .map(|step| format!("expected request not received: {}", step.description)),
+        );
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(MockError { problems })
+        }
+    }
+}
+
+/// An error reporting that a client didn't follow the script of a [`MockServer`], or the recording of a [`Replay`].
+#[derive(Debug, Clone)]
+pub struct MockError {
+    problems: Vec<String>,
+}
+
+impl MockError {
+    /// Returns the descriptions of the problems found.
+    pub fn problems(&self) -> &[String] {
+        &self.problems
+    }
+}
+
+impl fmt::Display for MockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.problems.join("; "))
+    }
+}
+
+impl error::Error for MockError {}
+
+fn write_row_description(columns: &[(&str, Type)], buf: &mut BytesMut) {
+    backend::row_description(
+        columns.iter().map(|(name, type_)| FieldDescription {
+            name,
+            table_oid: 0,
+            column_id: 0,
+            type_oid: type_.oid(),
+            type_size: -1,
+            type_modifier: -1,
+            format: 0,
+        }),
+        buf,
+    )
+    .unwrap();
+}
+
+fn status_byte(status: TransactionStatus) -> u8 {
+    match status {
+        TransactionStatus::InTransaction => b'T',
+        TransactionStatus::Failed => b'E',
+        _ => b'I',
+    }
+}
+
+async fn serve(
+    mut stream: DuplexStream,
+    parameters: &[(String, String)],
+    state: &Mutex<State>,
+) -> io::Result<()> {
+    loop {
+        let len = stream.read_i32().await?;
+        let mut body = vec![0; len as usize - 4];
+        stream.read_exact(&mut body).await?;
+
+        match (&body[..]).get_i32() {
+            SSL_REQUEST_CODE | GSSENC_REQUEST_CODE => stream.write_all(b"N").await?,
+            PROTOCOL_VERSION => break,
+            code => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unsupported startup code {}", code),
+                ))
+            }
+        }
+    }
+
+    let mut buf = BytesMut::new();
+    backend::authentication_ok(&mut buf);
+    for (name, value) in parameters {
+        backend::parameter_status(name, value, &mut buf)?;
+    }
+    backend::backend_key_data(1, 1, &mut buf);
+    backend::ready_for_query(b'I', &mut buf);
+    stream.write_all(&buf).await?;
+
+    let mut status = b'I';
+    let mut messages = vec![];
+    loop {
+        let tag = match stream.read_u8().await {
+            Ok(tag) => tag,
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        let len = stream.read_i32().await?;
+        let mut body = vec![0; len as usize - 4];
+        stream.read_exact(&mut body).await?;
+
+        let message = parse_message(tag, Bytes::from(body))?;
+        let ready = match message {
+            FrontendMessage::Terminate => return Ok(()),
+            FrontendMessage::Sync | FrontendMessage::Query { .. } => true,
+            FrontendMessage::Flush => false,
+            message => {
+                messages.push(message);
+                continue;
+            }
+        };
+        if let FrontendMessage::Query { .. } = &message {
+            messages.push(message);
+        }
+
+        let mut buf = BytesMut::new();
+        if messages.is_empty() {
+            // a lone Sync or Flush, which only asks for the state of the connection
+        } else if messages
+            .iter()
+            .all(|m| matches!(m, FrontendMessage::Close { .. }))
+        {
+            for _ in &messages {
+                backend::close_complete(&mut buf);
+            }
+        } else {
+            let mut state = state.lock().unwrap();
+            let matched = match state.steps.front() {
+                Some(step) => (step.matcher)(&messages),
+                None => false,
+            };
+            if matched {
+                let step = state.steps.pop_front().unwrap();
+                buf.extend_from_slice(&step.response.buf);
+                status = status_byte(step.response.status);
+            } else {
+                let failure = match state.steps.front() {
+                    Some(step) => format!(
+                        "unexpected request {:?}, expected {}",
+                        messages, step.description
+                    ),
+                    None => format!("unexpected request {:?}", messages),
+                };
+                backend::error_response(
+                    [
+                        (b'S', "ERROR"),
+                        (b'V', "ERROR"),
+                        (b'C', "XX000"),
+                        (b'M', &failure),
+                    ],
+                    &mut buf,
+                )?;
+                state.failures.push(failure);
+            }
+        }
+        if ready {
+            backend::ready_for_query(status, &mut buf);
+        }
+        messages.clear();
+
+        stream.write_all(&buf).await?;
+    }
+}
+
+fn parse_message(tag: u8, mut body: Bytes) -> io::Result<FrontendMessage> {
+    let message = match tag {
+        b'Q' => FrontendMessage::Query {
+            query: read_cstr(&mut body)?,
+        },
+        b'P' => {
+            let name = read_cstr(&mut body)?;
+            let query = read_cstr(&mut body)?;
+            let len = read_i16(&mut body)?;
+            let param_types = (0..len)
+                .map(|_| read_u32(&mut body))
+                .collect::<io::Result<_>>()?;
+            FrontendMessage::Parse {
+                name,
+                query,
+                param_types,
+            }
+        }
+        b'B' => {
+            let portal = read_cstr(&mut body)?;
+            let statement = read_cstr(&mut body)?;
+            let param_formats = read_formats(&mut body)?;
+            let len = read_i16(&mut body)?;
+            let params = (0..len)
+                .map(|_| {
+                    let len = read_i32(&mut body)?;
+                    if len < 0 {
+                        return Ok(None);
+                    }
+                    if body.remaining() < len as usize {
+                        return Err(invalid_message());
+                    }
+                    Ok(Some(body.split_to(len as usize)))
+                })
+                .collect::<io::Result<_>>()?;
+            let result_formats = read_formats(&mut body)?;
+            FrontendMessage::Bind {
+                portal,
+                statement,
+                param_formats,
+                params,
+                result_formats,
+            }
+        }
+        b'D' => FrontendMessage::Describe {
+            variant: read_u8(&mut body)?,
+            name: read_cstr(&mut body)?,
+        },
+        b'E' => FrontendMessage::Execute {
+            portal: read_cstr(&mut body)?,
+            max_rows: read_i32(&mut body)?,
+        },
+        b'C' => FrontendMessage::Close {
+            variant: read_u8(&mut body)?,
+            name: read_cstr(&mut body)?,
+        },
+        b'S' => FrontendMessage::Sync,
+        b'H' => FrontendMessage::Flush,
+        b'd' => FrontendMessage::CopyData(body),
+        b'c' => FrontendMessage::CopyDone,
+        b'f' => FrontendMessage::CopyFail(read_cstr(&mut body)?),
+        b'p' => FrontendMessage::Password(body),
+        b'X' => FrontendMessage::Terminate,
+        tag => FrontendMessage::Other { tag, body },
+    };
+
+    Ok(message)
+}
+
+fn invalid_message() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "invalid message")
+}
+
+fn read_u8(buf: &mut Bytes) -> io::Result<u8> {
+    if buf.remaining() < 1 {
+        return Err(invalid_message());
+    }
+    Ok(buf.get_u8())
+}
+
+fn read_i16(buf: &mut Bytes) -> io::Result<i16> {
+    if buf.remaining() < 2 {
+        return Err(invalid_message());
+    }
+    Ok(buf.get_i16())
+}
+
+fn read_i32(buf: &mut Bytes) -> io::Result<i32> {
+    if buf.remaining() < 4 {
+        return Err(invalid_message());
+    }
+    Ok(buf.get_i32())
+}
+
+fn read_u32(buf: &mut Bytes) -> io::Result<u32> {
+    if buf.remaining() < 4 {
+        return Err(invalid_message());
+    }
+    Ok(buf.get_u32())
+}
+
+fn read_formats(buf: &mut Bytes) -> io::Result<Vec<i16>> {
+    let len = read_i16(buf)?;
+    (0..len).map(|_| read_i16(buf)).collect()
+}
+
+fn read_cstr(buf: &mut Bytes) -> io::Result<String> {
+    let end = buf
+        .iter()
+        .position(|b| *b == 0)
+        .ok_or_else(invalid_message)?;
+    let s = buf.split_to(end);
+    buf.advance(1);
+    String::from_utf8(s.to_vec()).map_err(|_| invalid_message())
+}
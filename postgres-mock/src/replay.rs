@@ -0,0 +1,405 @@
+use crate::MockError;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, DuplexStream, ReadBuf};
+use tokio::task::JoinHandle;
+use tokio_postgres::tls::{NoTls, NoTlsStream};
+use tokio_postgres::{Client, Config, Connection};
+
+const FRONTEND: u8 = b'F';
+const BACKEND: u8 = b'B';
+const SSL_REQUEST_CODE: i32 = 80_877_103;
+const GSSENC_REQUEST_CODE: i32 = 80_877_104;
+
+/// A stream recording the messages sent through it, for replaying the session later with a [`Replay`].
+///
+/// The stream wraps the one connected to the server, and is passed to `Config::connect_raw`. Since messages are
+/// recorded as they go through the stream, it must not be encrypted: TLS is not supported. The contents of password
+/// messages are not recorded.
+///
+/// A recording is a sequence of entries, each made of a byte for the direction of the message, `b'F'` for the
+/// frontend or `b'B'` for the backend, the length of the message as a big-endian 32-bit integer, and the message.
+pub struct RecordingStream<S> {
+    stream: S,
+    log: Box<dyn Write + Send + Unpin>,
+    frontend: BytesMut,
+    backend: BytesMut,
+    started: bool,
+    ssl_response: bool,
+}
+
+impl<S> RecordingStream<S> {
+    /// Wraps a stream, writing the recording to the provided writer.
+    pub fn new<W>(stream: S, log: W) -> RecordingStream<S>
+    where
+        W: Write + Send + Unpin + 'static,
+    {
+        RecordingStream {
+            stream,
+            log: Box::new(log),
+            frontend: BytesMut::new(),
+            backend: BytesMut::new(),
+            started: false,
+            ssl_response: false,
+        }
+    }
+
+    /// Wraps a stream, writing the recording to a file created at the provided path.
+    pub fn create<P>(stream: S, path: P) -> io::Result<RecordingStream<S>>
+    where
+        P: AsRef<Path>,
+    {
+        let file = File::create(path)?;
+        Ok(RecordingStream::new(stream, io::BufWriter::new(file)))
+    }
+
+    /// Returns a shared reference to the wrapped stream.
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    fn record_frontend(&mut self, data: &[u8]) -> io::Result<()> {
+        self.frontend.extend_from_slice(data);
+
+        loop {
+            if !self.started {
+                if self.frontend.len() < 8 {
+                    return Ok(());
+                }
+                let len = (&self.frontend[..4]).get_i32() as usize;
+                if self.frontend.len() < len {
+                    return Ok(());
+                }
+                match (&self.frontend[4..8]).get_i32() {
+                    SSL_REQUEST_CODE | GSSENC_REQUEST_CODE => self.ssl_response = true,
+                    _ => self.started = true,
+                }
+                let message = self.frontend.split_to(len);
+                write_entry(&mut self.log, FRONTEND, &message)?;
+            } else {
+                let len = match message_len(&self.frontend) {
+                    Some(len) => len,
+                    None => return Ok(()),
+                };
+                let message = self.frontend.split_to(len);
+                if message[0] == b'p' {
+                    write_entry(&mut self.log, FRONTEND, b"p\0\0\0\x04")?;
+                } else {
+                    write_entry(&mut self.log, FRONTEND, &message)?;
+                }
+            }
+        }
+    }
+
+    fn record_backend(&mut self, data: &[u8]) -> io::Result<()> {
+        self.backend.extend_from_slice(data);
+
+        loop {
+            if self.ssl_response {
+                if self.backend.is_empty() {
+                    return Ok(());
+                }
+                self.ssl_response = false;
+                let message = self.backend.split_to(1);
+                write_entry(&mut self.log, BACKEND, &message)?;
+            } else {
+                let len = match message_len(&self.backend) {
+                    Some(len) => len,
+                    None => return Ok(()),
+                };
+                let message = self.backend.split_to(len);
+                write_entry(&mut self.log, BACKEND, &message)?;
+            }
+        }
+    }
+}
+
+impl<S> AsyncRead for RecordingStream<S>
+where
+    S: AsyncRead + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let start = buf.filled().len();
+        match Pin::new(&mut self.stream).poll_read(cx, buf) {
+            Poll::Ready(Ok(())) => Poll::Ready(self.record_backend(&buf.filled()[start..])),
+            poll => poll,
+        }
+    }
+}
+
+impl<S> AsyncWrite for RecordingStream<S>
+where
+    S: AsyncWrite + Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match Pin::new(&mut self.stream).poll_write(cx, buf) {
+            Poll::Ready(Ok(n)) => Poll::Ready(self.record_frontend(&buf[..n]).map(|()| n)),
+            poll => poll,
+        }
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if let Err(e) = self.log.flush() {
+            return Poll::Ready(Err(e));
+        }
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if let Err(e) = self.log.flush() {
+            return Poll::Ready(Err(e));
+        }
+        Pin::new(&mut self.stream).poll_shutdown(cx)
+    }
+}
+
+fn message_len(buf: &[u8]) -> Option<usize> {
+    if buf.len() < 5 {
+        return None;
+    }
+    let len = (&buf[1..5]).get_i32() as usize + 1;
+    if buf.len() < len {
+        return None;
+    }
+    Some(len)
+}
+
+fn write_entry(log: &mut dyn Write, direction: u8, message: &[u8]) -> io::Result<()> {
+    let mut header = [direction, 0, 0, 0, 0];
+    (&mut header[1..]).put_u32(message.len() as u32);
+    log.write_all(&header)?;
+    log.write_all(message)
+}
+
+struct Entry {
+    direction: u8,
+    message: Bytes,
+}
+
+/// A recorded session, replayed against a client.
+///
+/// The replay server sends the recorded backend messages in order, after receiving the frontend messages which
+/// preceded them in the recording. Frontend messages are only checked to be of the same type as the recorded ones,
+/// since the names of statements and portals, for example, change from one session to the next.
+///
+/// Authentication is skipped: the server accepts the client without a password, whatever the recorded session did.
+pub struct Replay {
+    entries: Vec<Entry>,
+}
+
+impl Replay {
+    /// Loads a recording made by a [`RecordingStream`].
+    pub fn new(recording: &[u8]) -> io::Result<Replay> {
+        let mut buf = recording;
+        let mut entries = vec![];
+        let mut ssl_response = false;
+
+        while buf.has_remaining() {
+            if buf.remaining() < 5 {
+                return Err(invalid_recording());
+            }
+            let direction = buf.get_u8();
+            let len = buf.get_u32() as usize;
+            if buf.remaining() < len {
+                return Err(invalid_recording());
+            }
+            let message = Bytes::copy_from_slice(&buf[..len]);
+            buf.advance(len);
+
+            match direction {
+                FRONTEND => {
+                    // the client of the replay doesn't request TLS, nor authenticates
+                    if let Some(SSL_REQUEST_CODE) | Some(GSSENC_REQUEST_CODE) =
+                        startup_code(&message)
+                    {
+                        ssl_response = true;
+                        continue;
+                    }
+                    if message.first() == Some(&b'p') {
+                        continue;
+                    }
+                }
+                BACKEND => {
+                    if ssl_response {
+                        ssl_response = false;
+                        continue;
+                    }
+                    if message.first() == Some(&b'R')
+                        && message.get(5..9) != Some(&[0, 0, 0, 0][..])
+                    {
+                        continue;
+                    }
+                }
+                _ => return Err(invalid_recording()),
+            }
+
+            entries.push(Entry { direction, message });
+        }
+
+        Ok(Replay { entries })
+    }
+
+    /// Loads a recording from a file.
+    pub fn open<P>(path: P) -> io::Result<Replay>
+    where
+        P: AsRef<Path>,
+    {
+        Replay::new(&std::fs::read(path)?)
+    }
+
+    /// Starts the replay, and connects a client to it with a default configuration.
+    ///
+    /// The replay runs in a task spawned on the current Tokio runtime, until the end of the recording or until the
+    /// client disconnects.
+    pub async fn connect(
+        self,
+    ) -> Result<(Client, Connection<DuplexStream, NoTlsStream>, ReplayHandle), tokio_postgres::Error>
+    {
+        let mut config = Config::new();
+        config.user("postgres");
+        self.connect_with(&config).await
+    }
+
+    /// Like [`connect`], but with the provided configuration.
+    ///
+    /// [`connect`]: Replay::connect
+    pub async fn connect_with(
+        self,
+        config: &Config,
+    ) -> Result<(Client, Connection<DuplexStream, NoTlsStream>, ReplayHandle), tokio_postgres::Error>
+    {
+        let (client_stream, server_stream) = tokio::io::duplex(64 * 1024);
+        let task = tokio::spawn(replay(server_stream, self.entries));
+
+        let (client, connection) = config.connect_raw(client_stream, NoTls).await?;
+        Ok((client, connection, ReplayHandle { task }))
+    }
+}
+
+/// A handle to a running [`Replay`].
+pub struct ReplayHandle {
+    task: JoinHandle<Vec<String>>,
+}
+
+impl ReplayHandle {
+    /// Waits for the end of the replay, and checks that the client sent the recorded messages.
+    ///
+    /// The replay ends with the recording, or when the client disconnects: if the recording doesn't end with the
+    /// client terminating the session, the client must be dropped first.
+    pub async fn finish(self) -> Result<(), MockError> {
+        let problems = match self.task.await {
+            Ok(problems) => problems,
+            Err(e) => vec![format!("replay error: {}", e)],
+        };
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(MockError { problems })
+        }
+    }
+}
+
+async fn replay(mut stream: DuplexStream, entries: Vec<Entry>) -> Vec<String> {
+    let mut started = false;
+    let mut problems = vec![];
+
+    for (i, entry) in entries.iter().enumerate() {
+        let result = if entry.direction == FRONTEND {
+            read_message(&mut stream, &mut started)
+                .await
+                .map(|message| match message {
+                    Some(message) if message_type(&message) == message_type(&entry.message) => {}
+                    Some(message) => problems.push(format!(
+                        "unexpected {} message, expected {}",
+                        message_type(&message),
+                        message_type(&entry.message)
+                    )),
+                    None => problems.push(format!(
+                        "client disconnected with {} recorded messages not replayed",
+                        entries.len() - i
+                    )),
+                })
+        } else {
+            stream.write_all(&entry.message).await
+        };
+
+        if let Err(e) = result {
+            problems.push(format!("replay error: {}", e));
+        }
+        if !problems.is_empty() {
+            break;
+        }
+    }
+
+    problems
+}
+
+async fn read_message(stream: &mut DuplexStream, started: &mut bool) -> io::Result<Option<Bytes>> {
+    loop {
+        let mut buf = BytesMut::new();
+        if !*started {
+            let len = match stream.read_i32().await {
+                Ok(len) => len,
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+                Err(e) => return Err(e),
+            };
+            buf.put_i32(len);
+        } else {
+            let tag = match stream.read_u8().await {
+                Ok(tag) => tag,
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+                Err(e) => return Err(e),
+            };
+            let len = stream.read_i32().await?;
+            buf.put_u8(tag);
+            buf.put_i32(len);
+        }
+
+        let len = (&buf[buf.len() - 4..]).get_i32() as usize;
+        let start = buf.len();
+        buf.resize(start + len - 4, 0);
+        stream.read_exact(&mut buf[start..]).await?;
+
+        if !*started {
+            if let Some(SSL_REQUEST_CODE) | Some(GSSENC_REQUEST_CODE) = startup_code(&buf) {
+                stream.write_all(b"N").await?;
+                continue;
+            }
+            *started = true;
+        }
+
+        return Ok(Some(buf.freeze()));
+    }
+}
+
+// Untagged startup messages start with the high byte of their length, which is never the tag of a message.
+fn startup_code(message: &[u8]) -> Option<i32> {
+    if message.first() != Some(&0) {
+        return None;
+    }
+    message.get(4..8).map(|mut code| code.get_i32())
+}
+
+fn message_type(message: &[u8]) -> String {
+    match message[0] {
+        0 => "startup".to_string(),
+        tag => format!("{:?}", tag as char),
+    }
+}
+
+fn invalid_recording() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "invalid recording")
+}
@@ -0,0 +1,241 @@
+use crate::{FrontendMessage, MockServer, RecordingStream, Replay, Response};
+use bytes::BytesMut;
+use postgres_protocol::message::backend;
+use std::io;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpStream;
+use tokio_postgres::tls::NoTls;
+use tokio_postgres::types::Type;
+use tokio_postgres::{Client, Config, SimpleQueryMessage, TransactionStatus};
+
+#[tokio::test]
+async fn query() {
+    let (client, connection, server) = MockServer::new()
+        .prepare(
+            "SELECT id, name FROM users WHERE id = $1",
+            &[Type::INT4],
+            &[("id", Type::INT4), ("name", Type::TEXT)],
+        )
+        .execute(
+            Response::new()
+                .columns(&[Type::INT4, Type::TEXT])
+                .row(&[&1i32, &"alice"])
+                .row(&[&2i32, &None::<&str>])
+                .command_complete("SELECT 2"),
+        )
+        .connect()
+        .await
+        .unwrap();
+    tokio::spawn(connection);
+
+    let rows = client
+        .query("SELECT id, name FROM users WHERE id = $1", &[&1i32])
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].get::<_, i32>(0), 1);
+    assert_eq!(rows[0].get::<_, Option<&str>>(1), Some("alice"));
+    assert_eq!(rows[1].get::<_, i32>(0), 2);
+    assert_eq!(rows[1].get::<_, Option<&str>>(1), None);
+
+    server.finish().unwrap();
+}
+
+#[tokio::test]
+async fn prepared_statement() {
+    let mut bind_complete = BytesMut::new();
+    backend::bind_complete(&mut bind_complete);
+
+    let (client, connection, server) = MockServer::new()
+        .prepare("INSERT INTO users (name) VALUES ($1)", &[Type::TEXT], &[])
+        .execute(Response::new().command_complete("INSERT 0 1"))
+        .expect(
+            "insert bob",
+            |messages| {
+                messages.iter().any(|m| match m {
+                    FrontendMessage::Bind { params, .. } => {
+                        params.len() == 1 && params[0].as_deref() == Some(&b"bob"[..])
+                    }
+                    _ => false,
+                })
+            },
+            Response::new()
+                .raw(&bind_complete)
+                .command_complete("INSERT 0 1"),
+        )
+        .connect()
+        .await
+        .unwrap();
+    tokio::spawn(connection);
+
+    let statement = client
+        .prepare("INSERT INTO users (name) VALUES ($1)")
+        .await
+        .unwrap();
+    assert_eq!(statement.params(), &[Type::TEXT]);
+    assert!(statement.columns().is_empty());
+    assert_eq!(client.execute(&statement, &[&"alice"]).await.unwrap(), 1);
+    assert_eq!(client.execute(&statement, &[&"bob"]).await.unwrap(), 1);
+    drop(statement);
+
+    server.finish().unwrap();
+}
+
+#[tokio::test]
+async fn simple_query() {
+    let (client, connection, server) = MockServer::new()
+        .simple_query(
+            "SELECT 1",
+            Response::new()
+                .row_description(&[("?column?", Type::INT4)])
+                .text_row(&[Some("1")])
+                .command_complete("SELECT 1"),
+        )
+        .simple_query(
+            "BEGIN",
+            Response::new()
+                .command_complete("BEGIN")
+                .transaction_status(TransactionStatus::InTransaction),
+        )
+        .connect()
+        .await
+        .unwrap();
+    tokio::spawn(connection);
+
+    let messages = client.simple_query("SELECT 1").await.unwrap();
+    match &messages[0] {
+        SimpleQueryMessage::Row(row) => assert_eq!(row.get(0), Some("1")),
+        _ => panic!("unexpected message"),
+    }
+    client.batch_execute("BEGIN").await.unwrap();
+    assert_eq!(
+        client.transaction_status(),
+        TransactionStatus::InTransaction
+    );
+
+    server.finish().unwrap();
+}
+
+#[tokio::test]
+async fn error() {
+    let (client, connection, server) = MockServer::new()
+        .simple_query(
+            "SELECT * FROM missing",
+            Response::new().error("42P01", "relation \"missing\" does not exist"),
+        )
+        .connect()
+        .await
+        .unwrap();
+    tokio::spawn(connection);
+
+    let err = client
+        .batch_execute("SELECT * FROM missing")
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.code(),
+        Some(&tokio_postgres::error::SqlState::UNDEFINED_TABLE)
+    );
+
+    server.finish().unwrap();
+}
+
+#[tokio::test]
+async fn unexpected_request() {
+    let (client, connection, server) = MockServer::new()
+        .simple_query("SELECT 1", Response::new().command_complete("SELECT 0"))
+        .connect()
+        .await
+        .unwrap();
+    tokio::spawn(connection);
+
+    client.batch_execute("SELECT 2").await.unwrap_err();
+
+    let err = server.finish().unwrap_err();
+    assert_eq!(err.problems().len(), 2);
+    assert!(err.problems()[0].contains("SELECT 2"));
+    assert!(err.problems()[1].contains("SELECT 1"));
+}
+
+#[tokio::test]
+async fn parameters() {
+    let (client, connection, server) = MockServer::new()
+        .parameter("server_version", "16.1")
+        .simple_query(
+            "SET application_name = 'mock'",
+            Response::new()
+                .parameter_status("application_name", "mock")
+                .command_complete("SET"),
+        )
+        .connect()
+        .await
+        .unwrap();
+    tokio::spawn(connection);
+
+    assert_eq!(client.server_version().unwrap().num(), 160001);
+    client
+        .batch_execute("SET application_name = 'mock'")
+        .await
+        .unwrap();
+    assert_eq!(
+        client.parameter("application_name").as_deref(),
+        Some("mock")
+    );
+
+    server.finish().unwrap();
+}
+
+#[derive(Clone, Default)]
+struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for SharedBuf {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+async fn run_session(client: &Client) -> (i32, String, bool) {
+    let row = client
+        .query_one("SELECT $1::INT4 + 1, 'hello'::TEXT", &[&1i32])
+        .await
+        .unwrap();
+    let failed = client.batch_execute("SELECT * FROM missing").await.is_err();
+    (row.get(0), row.get(1), failed)
+}
+
+#[tokio::test]
+async fn record_replay() {
+    let recording = SharedBuf::default();
+    let stream = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+    let stream = RecordingStream::new(stream, recording.clone());
+    let (client, connection) = "user=postgres"
+        .parse::<Config>()
+        .unwrap()
+        .connect_raw(stream, NoTls)
+        .await
+        .unwrap();
+    let connection = tokio::spawn(connection);
+    let recorded = run_session(&client).await;
+    assert_eq!(recorded, (2, "hello".to_string(), true));
+    drop(client);
+    connection.await.unwrap().unwrap();
+
+    let recording = recording.0.lock().unwrap().clone();
+    let (client, connection, replay) = Replay::new(&recording).unwrap().connect().await.unwrap();
+    let connection = tokio::spawn(connection);
+    assert_eq!(run_session(&client).await, recorded);
+    drop(client);
+    connection.await.unwrap().unwrap();
+    replay.finish().await.unwrap();
+
+    let (client, connection, replay) = Replay::new(&recording).unwrap().connect().await.unwrap();
+    tokio::spawn(connection);
+    client.batch_execute("SELECT 1").await.unwrap_err();
+    drop(client);
+    let err = replay.finish().await.unwrap_err();
+    assert!(err.problems()[0].contains("unexpected 'Q' message"));
+}
@@ -51,17 +51,55 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! Connectors created from a builder callback can be rebuilt, for example to pick up a renewed client certificate:
+//!
+//! ```no_run
+//! use native_tls::{Certificate, Identity};
+//! # #[cfg(feature = "runtime")]
+//! use postgres_native_tls::MakeTlsConnector;
+//! use std::fs;
+//! use std::time::Duration;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! # #[cfg(feature = "runtime")] {
+//! let cert = Certificate::from_pem(&fs::read("database_cert.pem")?)?;
+//! let mut connector = MakeTlsConnector::from_builder(move |builder| {
+//!     let identity = fs::read("client_identity.p12").expect("failed to read client identity");
+//!     builder
+//!         .add_root_certificate(cert.clone())
+//!         .identity(Identity::from_pkcs12(&identity, "password")?);
+//!     Ok(())
+//! })?;
+//! connector.set_reload_interval(Duration::from_secs(3600));
+//! # }
+//! # Ok(())
+//! # }
+//! ```
 #![warn(rust_2018_idioms, clippy::all, missing_docs)]
 
+#[cfg(feature = "runtime")]
+use native_tls::TlsConnectorBuilder;
+use native_tls::{Certificate, Identity};
+use sha2::{Digest, Sha256};
+#[cfg(feature = "runtime")]
+use std::collections::HashMap;
+use std::error::Error;
 use std::future::Future;
-use std::io;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
+use std::sync::Arc;
+#[cfg(feature = "runtime")]
+use std::sync::Mutex;
 use std::task::{Context, Poll};
+#[cfg(feature = "runtime")]
+use std::time::{Duration, Instant};
+use std::{fs, io};
 use tokio::io::{AsyncRead, AsyncWrite, BufReader, ReadBuf};
 use tokio_postgres::tls;
 #[cfg(feature = "runtime")]
 use tokio_postgres::tls::MakeTlsConnect;
-use tokio_postgres::tls::{ChannelBinding, TlsConnect};
+use tokio_postgres::tls::{CertificateVerification, ChannelBinding, TlsConnect};
 
 #[cfg(test)]
 mod test;
@@ -69,15 +107,82 @@ mod test;
 /// A `MakeTlsConnect` implementation using the `native-tls` crate.
 ///
 /// Requires the `runtime` Cargo feature (enabled by default).
+///
+/// TLS session resumption is only supported by `postgres-openssl`, through its `MakeTlsConnector::with_session_cache`
+/// constructor. `native-tls` does not expose the sessions of a connection, so this connector can't resume them, and
+/// whether the platform's TLS implementation does so on its own is unspecified.
 #[cfg(feature = "runtime")]
 #[derive(Clone)]
-pub struct MakeTlsConnector(native_tls::TlsConnector);
+pub struct MakeTlsConnector {
+    connector: native_tls::TlsConnector,
+    builder: Option<Arc<Builder>>,
+    reload_interval: Option<Duration>,
+    pins: Arc<Vec<CertificatePin>>,
+}
 
 #[cfg(feature = "runtime")]
 impl MakeTlsConnector {
     /// Creates a new connector.
+    ///
+    /// Since `native-tls` connectors can't be reconfigured once built, the `Config::ssl_sni` setting is ignored by
+    /// this connector, and the `verify-ca` SSL mode and `Config::ssl_root_cert` setting are rejected. Use
+    /// `from_builder` to support them.
     pub fn new(connector: native_tls::TlsConnector) -> MakeTlsConnector {
-        MakeTlsConnector(connector)
+        MakeTlsConnector {
+            connector,
+            builder: None,
+            reload_interval: None,
+            pins: Arc::new(vec![]),
+        }
+    }
+
+    /// Creates a new connector from a callback configuring a `TlsConnectorBuilder`.
+    ///
+    /// The callback is invoked immediately to build the connector, and again whenever the connector is rebuilt by
+    /// `reload` or once the reload interval has elapsed, which allows client certificates to be reloaded without
+    /// recreating the connector. It is also invoked to build variants of the connector when SNI is disabled with
+    /// `Config::ssl_sni`, or when the server's certificate must be verified according to `Config::ssl_mode` and
+    /// `Config::ssl_root_cert`. The connectors built are shared by the connector and its clones.
+    pub fn from_builder<F>(f: F) -> Result<MakeTlsConnector, native_tls::Error>
+    where
+        F: Fn(&mut TlsConnectorBuilder) -> Result<(), native_tls::Error> + 'static + Sync + Send,
+    {
+        let builder = Builder::new(Box::new(f))?;
+        let connector = builder.current(None)?;
+
+        Ok(MakeTlsConnector {
+            connector,
+            builder: Some(Arc::new(builder)),
+            reload_interval: None,
+            pins: Arc::new(vec![]),
+        })
+    }
+
+    /// Sets the interval after which a connector created with `from_builder` is rebuilt.
+    ///
+    /// `native-tls` doesn't expose the validity period of client certificates, so the interval should be shorter than
+    /// the lifetime of the certificates loaded by the builder callback. This has no effect on connectors created with
+    /// `new`.
+    pub fn set_reload_interval(&mut self, interval: Duration) {
+        self.reload_interval = Some(interval);
+    }
+
+    /// Rebuilds a connector created with `from_builder`, invoking its builder callback again.
+    ///
+    /// This has no effect on connectors created with `new`.
+    pub fn reload(&mut self) -> Result<(), native_tls::Error> {
+        if let Some(builder) = &self.builder {
+            self.connector = builder.reload()?;
+        }
+        Ok(())
+    }
+
+    /// Pins the server's certificate.
+    ///
+    /// Once at least one pin has been added, connections are rejected unless the server's certificate matches one of
+    /// the pins, in addition to passing the usual chain verification.
+    pub fn pin_certificate(&mut self, pin: CertificatePin) {
+        Arc::make_mut(&mut self.pins).push(pin);
     }
 }
 
@@ -91,24 +196,193 @@ where
     type Error = native_tls::Error;
 
     fn make_tls_connect(&mut self, domain: &str) -> Result<TlsConnector, native_tls::Error> {
-        Ok(TlsConnector::new(self.0.clone(), domain))
+        if let Some(builder) = &self.builder {
+            self.connector = builder.current(self.reload_interval)?;
+        }
+        let mut connector = TlsConnector::new(self.connector.clone(), domain);
+        connector.builder.clone_from(&self.builder);
+        connector.pins.clone_from(&self.pins);
+        Ok(connector)
+    }
+}
+
+/// The settings a connector is built with, in addition to the ones of the builder callback.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Settings {
+    sni: bool,
+    verification: CertificateVerification,
+    root_cert: Option<PathBuf>,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            sni: true,
+            verification: CertificateVerification::Default,
+            root_cert: None,
+        }
+    }
+}
+
+#[cfg(feature = "runtime")]
+type BuilderCallback =
+    dyn Fn(&mut TlsConnectorBuilder) -> Result<(), native_tls::Error> + Sync + Send;
+
+#[cfg(feature = "runtime")]
+struct Builder {
+    callback: Box<BuilderCallback>,
+    connectors: Mutex<Connectors>,
+}
+
+#[cfg(feature = "runtime")]
+struct Connectors {
+    built_at: Instant,
+    default: native_tls::TlsConnector,
+    variants: HashMap<Settings, native_tls::TlsConnector>,
+}
+
+#[cfg(feature = "runtime")]
+impl Builder {
+    fn new(callback: Box<BuilderCallback>) -> Result<Builder, native_tls::Error> {
+        let connector = build(&callback, &Settings::default(), &[])?;
+        Ok(Builder {
+            callback,
+            connectors: Mutex::new(Connectors {
+                built_at: Instant::now(),
+                default: connector,
+                variants: HashMap::new(),
+            }),
+        })
+    }
+
+    fn reload(&self) -> Result<native_tls::TlsConnector, native_tls::Error> {
+        let connector = build(&self.callback, &Settings::default(), &[])?;
+        *self.connectors.lock().unwrap() = Connectors {
+            built_at: Instant::now(),
+            default: connector.clone(),
+            variants: HashMap::new(),
+        };
+        Ok(connector)
+    }
+
+    fn current(
+        &self,
+        reload_interval: Option<Duration>,
+    ) -> Result<native_tls::TlsConnector, native_tls::Error> {
+        {
+            let connectors = self.connectors.lock().unwrap();
+            match reload_interval {
+                Some(interval) if connectors.built_at.elapsed() >= interval => {}
+                _ => return Ok(connectors.default.clone()),
+            }
+        }
+        self.reload()
+    }
+
+    fn variant(
+        &self,
+        settings: &Settings,
+        root_certs: &[Certificate],
+    ) -> Result<native_tls::TlsConnector, native_tls::Error> {
+        let mut connectors = self.connectors.lock().unwrap();
+        if let Some(connector) = connectors.variants.get(settings) {
+            return Ok(connector.clone());
+        }
+
+        let connector = build(&self.callback, settings, root_certs)?;
+        connectors
+            .variants
+            .insert(settings.clone(), connector.clone());
+        Ok(connector)
     }
 }
 
+#[cfg(feature = "runtime")]
+fn build(
+    callback: &BuilderCallback,
+    settings: &Settings,
+    root_certs: &[Certificate],
+) -> Result<native_tls::TlsConnector, native_tls::Error> {
+    let mut builder = native_tls::TlsConnector::builder();
+    callback(&mut builder)?;
+    builder.use_sni(settings.sni);
+    match settings.verification {
+        CertificateVerification::Ca => {
+            builder
+                .danger_accept_invalid_certs(false)
+                .danger_accept_invalid_hostnames(true);
+        }
+        CertificateVerification::Full => {
+            builder
+                .danger_accept_invalid_certs(false)
+                .danger_accept_invalid_hostnames(false);
+        }
+        _ => {}
+    }
+    if !root_certs.is_empty() {
+        builder.disable_built_in_roots(true);
+        for cert in root_certs {
+            builder.add_root_certificate(cert.clone());
+        }
+    }
+    builder.build()
+}
+
 /// A `TlsConnect` implementation using the `native-tls` crate.
 pub struct TlsConnector {
     connector: tokio_native_tls::TlsConnector,
     domain: String,
+    #[cfg(feature = "runtime")]
+    builder: Option<Arc<Builder>>,
+    settings: Settings,
+    root_certs: Vec<Certificate>,
+    pins: Arc<Vec<CertificatePin>>,
 }
 
 impl TlsConnector {
     /// Creates a new connector configured to connect to the specified domain.
+    ///
+    /// Since `native-tls` connectors can't be reconfigured once built, the `Config::ssl_sni` setting is ignored by
+    /// this connector, and the `verify-ca` SSL mode and `Config::ssl_root_cert` setting are rejected. The `verify-full`
+    /// SSL mode is accepted, assuming the connector verifies certificates and host names, which is the default.
     pub fn new(connector: native_tls::TlsConnector, domain: &str) -> TlsConnector {
         TlsConnector {
             connector: tokio_native_tls::TlsConnector::from(connector),
             domain: domain.to_string(),
+            #[cfg(feature = "runtime")]
+            builder: None,
+            settings: Settings::default(),
+            root_certs: vec![],
+            pins: Arc::new(vec![]),
         }
     }
+
+    /// Pins the server's certificate.
+    ///
+    /// Once at least one pin has been added, the handshake fails unless the server's certificate matches one of the
+    /// pins, in addition to passing the usual chain verification.
+    pub fn pin_certificate(&mut self, pin: CertificatePin) {
+        Arc::make_mut(&mut self.pins).push(pin);
+    }
+
+    fn can_rebuild(&self) -> bool {
+        #[cfg(feature = "runtime")]
+        return self.builder.is_some();
+        #[cfg(not(feature = "runtime"))]
+        return false;
+    }
+
+    fn connector(&self) -> Result<tokio_native_tls::TlsConnector, native_tls::Error> {
+        #[cfg(feature = "runtime")]
+        if let Some(builder) = &self.builder {
+            if self.settings != Settings::default() {
+                return builder
+                    .variant(&self.settings, &self.root_certs)
+                    .map(Into::into);
+            }
+        }
+        Ok(self.connector.clone())
+    }
 }
 
 impl<S> TlsConnect<S> for TlsConnector
@@ -116,20 +390,139 @@ where
     S: AsyncRead + AsyncWrite + Unpin + 'static + Send,
 {
     type Stream = TlsStream<S>;
-    type Error = native_tls::Error;
+    type Error = Box<dyn Error + Sync + Send>;
     #[allow(clippy::type_complexity)]
-    type Future = Pin<Box<dyn Future<Output = Result<TlsStream<S>, native_tls::Error>> + Send>>;
+    type Future = Pin<Box<dyn Future<Output = Result<TlsStream<S>, Self::Error>> + Send>>;
 
     fn connect(self, stream: S) -> Self::Future {
         let stream = BufReader::with_capacity(8192, stream);
         let future = async move {
-            let stream = self.connector.connect(&self.domain, stream).await?;
+            let stream = self.connector()?.connect(&self.domain, stream).await?;
+            if !matches_pins(stream.get_ref(), &self.pins)? {
+                return Err("server certificate does not match any pinned certificate".into());
+            }
 
             Ok(TlsStream(stream))
         };
 
         Box::pin(future)
     }
+
+    fn set_server_name_indication(&mut self, enabled: bool) {
+        self.settings.sni = enabled;
+    }
+
+    fn set_verification(
+        &mut self,
+        verification: CertificateVerification,
+        root_cert: Option<&Path>,
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        if !self.can_rebuild() {
+            return match (verification, root_cert) {
+                (CertificateVerification::Default | CertificateVerification::Full, None) => Ok(()),
+                _ => Err(
+                    "sslmode=verify-ca and sslrootcert require a connector created with \
+                     MakeTlsConnector::from_builder"
+                        .into(),
+                ),
+            };
+        }
+
+        if let Some(path) = root_cert {
+            self.root_certs = Certificate::stack_from_pem(&fs::read(path)?)?;
+        }
+        self.settings.verification = verification;
+        self.settings.root_cert = root_cert.map(Path::to_path_buf);
+        Ok(())
+    }
+}
+
+/// A pinned server certificate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CertificatePin {
+    /// The SHA-256 hash of the DER-encoded `SubjectPublicKeyInfo` of the certificate.
+    ///
+    /// This is the same value as used in HTTP public key pinning, and survives certificate renewals which keep the key.
+    SpkiSha256([u8; 32]),
+    /// The SHA-256 fingerprint of the DER-encoded certificate.
+    CertificateSha256([u8; 32]),
+}
+
+impl CertificatePin {
+    fn matches(&self, cert: &[u8]) -> bool {
+        match self {
+            CertificatePin::SpkiSha256(pin) => subject_public_key_info(cert)
+                .is_some_and(|spki| Sha256::digest(spki)[..] == pin[..]),
+            CertificatePin::CertificateSha256(pin) => Sha256::digest(cert)[..] == pin[..],
+        }
+    }
+}
+
+fn matches_pins<S>(
+    stream: &native_tls::TlsStream<S>,
+    pins: &[CertificatePin],
+) -> Result<bool, native_tls::Error>
+where
+    S: io::Read + io::Write,
+{
+    if pins.is_empty() {
+        return Ok(true);
+    }
+
+    match stream.peer_certificate()? {
+        Some(cert) => {
+            let cert = cert.to_der()?;
+            Ok(pins.iter().any(|pin| pin.matches(&cert)))
+        }
+        None => Ok(false),
+    }
+}
+
+/// Returns the DER-encoded `SubjectPublicKeyInfo` of a DER-encoded X.509 certificate.
+///
+/// `native-tls` doesn't expose the public key of a certificate, so the certificate is parsed just far enough to
+/// find it.
+fn subject_public_key_info(cert: &[u8]) -> Option<&[u8]> {
+    let (_, cert, _) = der_element(cert, 0x30)?;
+    let (_, tbs_certificate, _) = der_element(cert, 0x30)?;
+
+    let mut buf = tbs_certificate;
+    // the version is an explicitly tagged, optional field
+    if buf.first() == Some(&0xa0) {
+        buf = der_element(buf, 0xa0)?.2;
+    }
+    // serialNumber, signature, issuer, validity and subject precede subjectPublicKeyInfo
+    for tag in [0x02, 0x30, 0x30, 0x30, 0x30] {
+        buf = der_element(buf, tag)?.2;
+    }
+
+    der_element(buf, 0x30).map(|(element, _, _)| element)
+}
+
+/// Splits a DER element with the given tag off the front of a buffer, returning the element, its contents, and the
+/// rest of the buffer.
+fn der_element(buf: &[u8], tag: u8) -> Option<(&[u8], &[u8], &[u8])> {
+    if *buf.first()? != tag {
+        return None;
+    }
+
+    let (len, header_len) = match *buf.get(1)? {
+        len @ 0..=0x7f => (usize::from(len), 2),
+        0x81..=0x84 => {
+            let count = usize::from(buf[1] & 0x7f);
+            let bytes = buf.get(2..2 + count)?;
+            let len = bytes
+                .iter()
+                .fold(0usize, |len, &byte| (len << 8) | usize::from(byte));
+            (len, 2 + count)
+        }
+        _ => return None,
+    };
+
+    let end = header_len.checked_add(len)?;
+    let element = buf.get(..end)?;
+    Some((element, &element[header_len..], &buf[end..]))
 }
 
 /// The stream returned by `TlsConnector`.
@@ -180,3 +573,20 @@ where
         }
     }
 }
+
+/// Loads a client identity from a password-protected PKCS#12 archive.
+///
+/// `native-tls` can't load encrypted PEM or PKCS#8 private keys, so a client key protected by libpq's `sslpassword`
+/// parameter, which is available through `tokio_postgres::Config::get_ssl_password`, must be bundled with its
+/// certificate in a PKCS#12 archive to be used with this crate.
+pub fn identity_from_pkcs12_file<P>(
+    path: P,
+    password: &[u8],
+) -> Result<Identity, Box<dyn Error + Sync + Send>>
+where
+    P: AsRef<Path>,
+{
+    let buf = fs::read(path)?;
+    let password = std::str::from_utf8(password)?;
+    Ok(Identity::from_pkcs12(&buf, password)?)
+}
@@ -1,11 +1,13 @@
 use futures_util::FutureExt;
 use native_tls::{self, Certificate};
+use sha2::{Digest, Sha256};
+use std::convert::TryInto;
 use tokio::net::TcpStream;
 use tokio_postgres::tls::TlsConnect;
 
 #[cfg(feature = "runtime")]
 use crate::MakeTlsConnector;
-use crate::TlsConnector;
+use crate::{CertificatePin, TlsConnector};
 
 async fn smoke_test<T>(s: &str, tls: T)
 where
@@ -72,6 +74,38 @@ async fn scram_user() {
     .await;
 }
 
+#[tokio::test]
+async fn require_channel_binding_err() {
+    let connector = native_tls::TlsConnector::builder()
+        .add_root_certificate(
+            Certificate::from_pem(include_bytes!("../../test/server.crt")).unwrap(),
+        )
+        .build()
+        .unwrap();
+    let connector = TlsConnector::new(connector, "localhost");
+
+    let stream = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+    let builder = "user=pass_user password=password dbname=postgres channel_binding=require"
+        .parse::<tokio_postgres::Config>()
+        .unwrap();
+    builder.connect_raw(stream, connector).await.err().unwrap();
+}
+
+#[tokio::test]
+async fn require_channel_binding_ok() {
+    let connector = native_tls::TlsConnector::builder()
+        .add_root_certificate(
+            Certificate::from_pem(include_bytes!("../../test/server.crt")).unwrap(),
+        )
+        .build()
+        .unwrap();
+    smoke_test(
+        "user=scram_user password=password dbname=postgres channel_binding=require",
+        TlsConnector::new(connector, "localhost"),
+    )
+    .await;
+}
+
 #[tokio::test]
 #[cfg(feature = "runtime")]
 async fn runtime() {
@@ -98,3 +132,113 @@ async fn runtime() {
     assert_eq!(rows.len(), 1);
     assert_eq!(rows[0].get::<_, i32>(0), 1);
 }
+
+#[test]
+fn subject_public_key_info() {
+    let spki = crate::subject_public_key_info(include_bytes!("../../test/server.der")).unwrap();
+    assert_eq!(
+        Sha256::digest(spki)[..],
+        [
+            0x74, 0xa5, 0x49, 0x60, 0x90, 0xed, 0x8b, 0xdf, 0xba, 0xc4, 0x4f, 0xb8, 0xcc, 0xcb,
+            0x09, 0xdf, 0xb8, 0x21, 0x09, 0x3b, 0x22, 0x0f, 0x39, 0xc3, 0x02, 0x2b, 0x84, 0x36,
+            0xea, 0x12, 0xac, 0xe0,
+        ]
+    );
+}
+
+#[tokio::test]
+async fn pinned_certificate() {
+    let cert = Certificate::from_pem(include_bytes!("../../test/server.crt")).unwrap();
+    let digest = Sha256::digest(cert.to_der().unwrap());
+
+    let connector = native_tls::TlsConnector::builder()
+        .add_root_certificate(cert)
+        .build()
+        .unwrap();
+    let mut connector = TlsConnector::new(connector, "localhost");
+    connector.pin_certificate(CertificatePin::CertificateSha256(
+        digest[..].try_into().unwrap(),
+    ));
+    smoke_test("user=ssl_user dbname=postgres sslmode=require", connector).await;
+}
+
+#[tokio::test]
+async fn pinned_spki_mismatch() {
+    let connector = native_tls::TlsConnector::builder()
+        .add_root_certificate(
+            Certificate::from_pem(include_bytes!("../../test/server.crt")).unwrap(),
+        )
+        .build()
+        .unwrap();
+    let mut connector = TlsConnector::new(connector, "localhost");
+    connector.pin_certificate(CertificatePin::SpkiSha256([0; 32]));
+
+    let stream = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+    let builder = "user=ssl_user dbname=postgres sslmode=require"
+        .parse::<tokio_postgres::Config>()
+        .unwrap();
+    builder.connect_raw(stream, connector).await.err().unwrap();
+}
+
+#[test]
+fn pkcs12_identity_with_password() {
+    crate::identity_from_pkcs12_file("../test/client.p12", b"hunter2").unwrap();
+    crate::identity_from_pkcs12_file("../test/client.p12", b"wrong")
+        .err()
+        .unwrap();
+}
+
+#[tokio::test]
+#[cfg(feature = "runtime")]
+async fn runtime_from_builder() {
+    let mut connector = MakeTlsConnector::from_builder(|builder| {
+        builder.add_root_certificate(
+            Certificate::from_pem(include_bytes!("../../test/server.crt")).unwrap(),
+        );
+        Ok(())
+    })
+    .unwrap();
+    connector.set_reload_interval(std::time::Duration::from_secs(0));
+
+    for sni in [1, 0] {
+        let (client, connection) = tokio_postgres::connect(
+            &format!(
+                "host=127.0.0.1 port=5433 user=postgres sslmode=require sslservername=localhost sslsni={}",
+                sni
+            ),
+            connector.clone(),
+        )
+        .await
+        .unwrap();
+        let connection = connection.map(|r| r.unwrap());
+        tokio::spawn(connection);
+
+        client.batch_execute("SELECT 1").await.unwrap();
+    }
+
+    connector.reload().unwrap();
+}
+
+#[tokio::test]
+#[cfg(feature = "runtime")]
+async fn runtime_root_cert() {
+    let connector = MakeTlsConnector::from_builder(|_| Ok(())).unwrap();
+
+    let (client, connection) = tokio_postgres::connect(
+        "host=localhost port=5433 user=postgres sslmode=verify-full sslrootcert=../test/server.crt",
+        connector.clone(),
+    )
+    .await
+    .unwrap();
+    let connection = connection.map(|r| r.unwrap());
+    tokio::spawn(connection);
+    client.batch_execute("SELECT 1").await.unwrap();
+
+    tokio_postgres::connect(
+        "host=localhost port=5433 user=postgres sslmode=verify-ca sslrootcert=../test/server.crt",
+        MakeTlsConnector::new(native_tls::TlsConnector::new().unwrap()),
+    )
+    .await
+    .err()
+    .unwrap();
+}
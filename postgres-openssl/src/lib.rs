@@ -45,34 +45,92 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! TLS sessions can be resumed across connections to the same host, saving a full handshake for workloads that open
+//! many short-lived connections:
+//!
+//! ```no_run
+//! use openssl::ssl::{SslConnector, SslMethod};
+//! # #[cfg(feature = "runtime")]
+//! use postgres_openssl::MakeTlsConnector;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! # #[cfg(feature = "runtime")] {
+//! let mut builder = SslConnector::builder(SslMethod::tls())?;
+//! builder.set_ca_file("database_cert.pem")?;
+//! let connector = MakeTlsConnector::with_session_cache(builder)?;
+//! # }
+//!
+//! // ...
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Client certificates which are renewed while the application runs can be loaded by a callback, which is invoked
+//! again once the certificate it returned has expired:
+//!
+//! ```no_run
+//! use openssl::ssl::{SslConnector, SslMethod};
+//! # #[cfg(feature = "runtime")]
+//! use postgres_openssl::{ClientIdentity, MakeTlsConnector};
+//! use std::fs;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! # #[cfg(feature = "runtime")] {
+//! let mut builder = SslConnector::builder(SslMethod::tls())?;
+//! builder.set_ca_file("database_cert.pem")?;
+//! let mut connector = MakeTlsConnector::new(builder.build());
+//! connector.set_client_identity_callback(|| {
+//!     let cert = fs::read("client_cert.pem")?;
+//!     let key = fs::read("client_key.pem")?;
+//!     Ok(ClientIdentity::from_pem(&cert, &key)?)
+//! });
+//! # }
+//!
+//! // ...
+//! # Ok(())
+//! # }
+//! ```
 #![warn(rust_2018_idioms, clippy::all, missing_docs)]
 
-#[cfg(feature = "runtime")]
+use openssl::asn1::Asn1Time;
 use openssl::error::ErrorStack;
-use openssl::hash::MessageDigest;
+use openssl::ex_data::Index;
+use openssl::hash::{hash, MessageDigest};
 use openssl::nid::Nid;
+use openssl::ocsp::{OcspCertId, OcspCertStatus, OcspFlag, OcspResponse, OcspResponseStatus};
+use openssl::pkey::{PKey, Private};
+use openssl::ssl::{
+    self, ConnectConfiguration, SslConnectorBuilder, SslContext, SslFiletype, SslRef,
+    SslVerifyMode, StatusType,
+};
 #[cfg(feature = "runtime")]
-use openssl::ssl::SslConnector;
-use openssl::ssl::{self, ConnectConfiguration, SslRef};
-use openssl::x509::X509VerifyResult;
+use openssl::ssl::{Ssl, SslConnector, SslSession, SslSessionCacheMode};
+use openssl::x509::store::{X509Lookup, X509Store, X509StoreBuilder};
+use openssl::x509::verify::{X509VerifyFlags, X509VerifyParam};
+use openssl::x509::{X509Ref, X509StoreContext, X509VerifyResult, X509};
+#[cfg(feature = "runtime")]
+use std::collections::HashMap;
 use std::error::Error;
 use std::fmt::{self, Debug};
 use std::future::Future;
-use std::io;
+use std::net::IpAddr;
+use std::path::Path;
 use std::pin::Pin;
-#[cfg(feature = "runtime")]
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, OnceLock};
 use std::task::{Context, Poll};
+use std::{fs, io};
 use tokio::io::{AsyncRead, AsyncWrite, BufReader, ReadBuf};
 use tokio_openssl::SslStream;
 use tokio_postgres::tls;
 #[cfg(feature = "runtime")]
 use tokio_postgres::tls::MakeTlsConnect;
-use tokio_postgres::tls::{ChannelBinding, TlsConnect};
+use tokio_postgres::tls::{CertificateVerification, ChannelBinding, TlsConnect};
 
 #[cfg(test)]
 mod test;
 
+#[cfg(feature = "runtime")]
 type ConfigCallback =
     dyn Fn(&mut ConnectConfiguration, &str) -> Result<(), ErrorStack> + Sync + Send;
 
@@ -84,6 +142,9 @@ type ConfigCallback =
 pub struct MakeTlsConnector {
     connector: SslConnector,
     config: Arc<ConfigCallback>,
+    sessions: Option<SessionCache>,
+    pins: Arc<Vec<CertificatePin>>,
+    identity: Option<Arc<IdentityCache>>,
 }
 
 #[cfg(feature = "runtime")]
@@ -93,6 +154,56 @@ impl MakeTlsConnector {
         MakeTlsConnector {
             connector,
             config: Arc::new(|_, _| Ok(())),
+            sessions: None,
+            pins: Arc::new(vec![]),
+            identity: None,
+        }
+    }
+
+    /// Creates a new connector which resumes TLS sessions.
+    ///
+    /// Sessions established by the connector, or any of its clones, are cached by domain name and offered to the
+    /// server when connecting to the same domain again. Resumption only takes place if the server supports session IDs
+    /// or session tickets.
+    pub fn with_session_cache(
+        mut builder: SslConnectorBuilder,
+    ) -> Result<MakeTlsConnector, ErrorStack> {
+        let index = session_key_index()?;
+        let sessions = SessionCache::default();
+
+        builder.set_session_cache_mode(SslSessionCacheMode::CLIENT);
+        builder.set_new_session_callback({
+            let sessions = sessions.clone();
+            move |ssl, session| {
+                if let Some(key) = ssl.ex_data(index) {
+                    sessions.insert(key.clone(), session);
+                }
+            }
+        });
+
+        Ok(MakeTlsConnector {
+            connector: builder.build(),
+            config: Arc::new(|_, _| Ok(())),
+            sessions: Some(sessions),
+            pins: Arc::new(vec![]),
+            identity: None,
+        })
+    }
+
+    /// Pins the server's certificate.
+    ///
+    /// Once at least one pin has been added, connections are rejected unless the server's certificate matches one of
+    /// the pins, in addition to passing the usual chain verification.
+    pub fn pin_certificate(&mut self, pin: CertificatePin) {
+        Arc::make_mut(&mut self.pins).push(pin);
+    }
+
+    /// Discards all cached TLS sessions.
+    ///
+    /// This has no effect if the connector was not created with `with_session_cache`.
+    pub fn clear_session_cache(&self) {
+        if let Some(sessions) = &self.sessions {
+            sessions.clear();
         }
     }
 
@@ -105,6 +216,33 @@ impl MakeTlsConnector {
     {
         self.config = Arc::new(f);
     }
+
+    /// Sets a callback loading the client's certificate and private key.
+    ///
+    /// The callback is invoked when the first connection is made, and again whenever the certificate it last returned
+    /// has expired, so that renewed certificates are picked up without recreating the connector. The identity is shared
+    /// by the connector and its clones, and replaces any certificate configured on the `SslConnector`.
+    ///
+    /// The callback is invoked from the task making the connection, so it shouldn't block for long.
+    pub fn set_client_identity_callback<F>(&mut self, f: F)
+    where
+        F: Fn() -> Result<ClientIdentity, Box<dyn Error + Sync + Send>> + 'static + Sync + Send,
+    {
+        self.identity = Some(Arc::new(IdentityCache {
+            callback: Box::new(f),
+            current: Mutex::new(None),
+        }));
+    }
+
+    /// Discards the client identity loaded by the callback set with `set_client_identity_callback`.
+    ///
+    /// The callback is invoked again for the next connection. This can be used to pick up a certificate which was
+    /// renewed before the previous one expired.
+    pub fn reload_client_identity(&self) {
+        if let Some(identity) = &self.identity {
+            identity.clear();
+        }
+    }
 }
 
 #[cfg(feature = "runtime")]
@@ -119,7 +257,158 @@ where
     fn make_tls_connect(&mut self, domain: &str) -> Result<TlsConnector, ErrorStack> {
         let mut ssl = self.connector.configure()?;
         (self.config)(&mut ssl, domain)?;
-        Ok(TlsConnector::new(ssl, domain))
+        if let Some(sessions) = &self.sessions {
+            ssl.set_ex_data(session_key_index()?, domain.to_string());
+            if let Some(session) = sessions.get(domain) {
+                // SAFETY: the session was established by a connection created from the same `SslContext`.
+                unsafe { ssl.set_session(&session)? };
+            }
+        }
+        let mut connector = TlsConnector::new(ssl, domain);
+        connector.pins.clone_from(&self.pins);
+        connector.identity.clone_from(&self.identity);
+        Ok(connector)
+    }
+}
+
+#[cfg(feature = "runtime")]
+#[derive(Clone, Default)]
+struct SessionCache(Arc<Mutex<HashMap<String, SslSession>>>);
+
+#[cfg(feature = "runtime")]
+impl SessionCache {
+    fn insert(&self, domain: String, session: SslSession) {
+        self.0.lock().unwrap().insert(domain, session);
+    }
+
+    fn get(&self, domain: &str) -> Option<SslSession> {
+        self.0.lock().unwrap().get(domain).cloned()
+    }
+
+    fn clear(&self) {
+        self.0.lock().unwrap().clear();
+    }
+}
+
+#[cfg(feature = "runtime")]
+fn session_key_index() -> Result<Index<Ssl, String>, ErrorStack> {
+    static INDEX: OnceLock<Index<Ssl, String>> = OnceLock::new();
+
+    if let Some(index) = INDEX.get() {
+        return Ok(*index);
+    }
+    let index = Ssl::new_ex_index()?;
+    Ok(*INDEX.get_or_init(|| index))
+}
+
+/// A client certificate along with its private key.
+#[derive(Clone)]
+pub struct ClientIdentity {
+    cert: X509,
+    key: PKey<Private>,
+}
+
+impl ClientIdentity {
+    /// Creates a new identity from a certificate and its private key.
+    pub fn new(cert: X509, key: PKey<Private>) -> ClientIdentity {
+        ClientIdentity { cert, key }
+    }
+
+    /// Parses an identity from a PEM-encoded certificate and an unencrypted PEM-encoded private key.
+    pub fn from_pem(cert: &[u8], key: &[u8]) -> Result<ClientIdentity, ErrorStack> {
+        Ok(ClientIdentity {
+            cert: X509::from_pem(cert)?,
+            key: PKey::private_key_from_pem(key)?,
+        })
+    }
+
+    /// Returns the certificate of the identity.
+    pub fn certificate(&self) -> &X509Ref {
+        &self.cert
+    }
+
+    fn is_expired(&self) -> bool {
+        match Asn1Time::days_from_now(0) {
+            Ok(now) => self.cert.not_after() <= now,
+            Err(_) => true,
+        }
+    }
+}
+
+impl Debug for ClientIdentity {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("ClientIdentity")
+            .field("cert", &self.cert)
+            .finish_non_exhaustive()
+    }
+}
+
+type IdentityCallback =
+    dyn Fn() -> Result<ClientIdentity, Box<dyn Error + Sync + Send>> + Sync + Send;
+
+struct IdentityCache {
+    callback: Box<IdentityCallback>,
+    current: Mutex<Option<ClientIdentity>>,
+}
+
+impl IdentityCache {
+    fn get(&self) -> Result<ClientIdentity, Box<dyn Error + Sync + Send>> {
+        let mut current = self.current.lock().unwrap();
+        if let Some(identity) = &*current {
+            if !identity.is_expired() {
+                return Ok(identity.clone());
+            }
+        }
+
+        let identity = (self.callback)()?;
+        *current = Some(identity.clone());
+        Ok(identity)
+    }
+
+    #[cfg(feature = "runtime")]
+    fn clear(&self) {
+        *self.current.lock().unwrap() = None;
+    }
+}
+
+/// A pinned server certificate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CertificatePin {
+    /// The SHA-256 hash of the DER-encoded `SubjectPublicKeyInfo` of the certificate.
+    ///
+    /// This is the same value as used in HTTP public key pinning, and survives certificate renewals which keep the key.
+    SpkiSha256([u8; 32]),
+    /// The SHA-256 fingerprint of the DER-encoded certificate.
+    CertificateSha256([u8; 32]),
+}
+
+impl CertificatePin {
+    fn matches(&self, cert: &X509Ref) -> bool {
+        let digest = match self {
+            CertificatePin::SpkiSha256(_) => cert
+                .public_key()
+                .and_then(|key| key.public_key_to_der())
+                .and_then(|der| hash(MessageDigest::sha256(), &der)),
+            CertificatePin::CertificateSha256(_) => cert.digest(MessageDigest::sha256()),
+        };
+
+        match (self, digest) {
+            (CertificatePin::SpkiSha256(pin), Ok(digest))
+            | (CertificatePin::CertificateSha256(pin), Ok(digest)) => *pin == *digest,
+            (_, Err(_)) => false,
+        }
+    }
+}
+
+fn matches_pins(ssl: &SslRef, pins: &[CertificatePin]) -> bool {
+    if pins.is_empty() {
+        return true;
+    }
+
+    match ssl.peer_certificate() {
+        Some(cert) => pins.iter().any(|pin| pin.matches(&cert)),
+        None => false,
     }
 }
 
@@ -127,6 +416,9 @@ where
 pub struct TlsConnector {
     ssl: ConnectConfiguration,
     domain: String,
+    pins: Arc<Vec<CertificatePin>>,
+    identity: Option<Arc<IdentityCache>>,
+    root_certs: Option<X509Store>,
 }
 
 impl TlsConnector {
@@ -135,8 +427,19 @@ impl TlsConnector {
         TlsConnector {
             ssl,
             domain: domain.to_string(),
+            pins: Arc::new(vec![]),
+            identity: None,
+            root_certs: None,
         }
     }
+
+    /// Pins the server's certificate.
+    ///
+    /// Once at least one pin has been added, the handshake fails unless the server's certificate matches one of the
+    /// pins, in addition to passing the usual chain verification.
+    pub fn pin_certificate(&mut self, pin: CertificatePin) {
+        Arc::make_mut(&mut self.pins).push(pin);
+    }
 }
 
 impl<S> TlsConnect<S> for TlsConnector
@@ -151,10 +454,26 @@ where
     fn connect(self, stream: S) -> Self::Future {
         let stream = BufReader::with_capacity(8192, stream);
         let future = async move {
-            let ssl = self.ssl.into_ssl(&self.domain)?;
+            let mut ssl = self.ssl.into_ssl(&self.domain)?;
+            if ssl.ssl_context().ex_data(ocsp_stapling_index()?).is_some() {
+                ssl.set_status_type(StatusType::OCSP)?;
+            }
+            if let Some(identity) = &self.identity {
+                let identity = identity.get()?;
+                ssl.set_certificate(&identity.cert)?;
+                ssl.set_private_key(&identity.key)?;
+            }
             let mut stream = SslStream::new(ssl, stream)?;
             match Pin::new(&mut stream).connect().await {
-                Ok(()) => Ok(TlsStream(stream)),
+                Ok(()) if !matches_pins(stream.ssl(), &self.pins) => {
+                    Err("server certificate does not match any pinned certificate".into())
+                }
+                Ok(()) => {
+                    if let Some(root_certs) = &self.root_certs {
+                        verify_root_certs(stream.ssl(), root_certs)?;
+                    }
+                    Ok(TlsStream(stream))
+                }
                 Err(error) => Err(Box::new(ConnectError {
                     error,
                     verify_result: stream.ssl().verify_result(),
@@ -164,6 +483,177 @@ where
 
         Box::pin(future)
     }
+
+    fn set_server_name_indication(&mut self, enabled: bool) {
+        self.ssl.set_use_server_name_indication(enabled);
+    }
+
+    fn set_verification(
+        &mut self,
+        verification: CertificateVerification,
+        root_cert: Option<&Path>,
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let verify_hostname = match verification {
+            CertificateVerification::Default if root_cert.is_none() => return Ok(()),
+            CertificateVerification::Default | CertificateVerification::Ca => false,
+            CertificateVerification::Full => true,
+            _ => return Err("unsupported certificate verification".into()),
+        };
+
+        match root_cert {
+            // The certificate store of a connection can only be replaced with OpenSSL 1.1.0 or newer, so the server's
+            // certificate is instead verified against the root certificates once the handshake completes.
+            Some(path) => {
+                self.ssl.set_verify(SslVerifyMode::NONE);
+                self.ssl.set_verify_hostname(false);
+                self.root_certs = Some(root_cert_store(path, &self.domain, verify_hostname)?);
+            }
+            None => {
+                self.ssl.set_verify(SslVerifyMode::PEER);
+                self.ssl.set_verify_hostname(verify_hostname);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn root_cert_store(
+    path: &Path,
+    domain: &str,
+    verify_hostname: bool,
+) -> Result<X509Store, Box<dyn Error + Sync + Send>> {
+    let mut builder = X509StoreBuilder::new()?;
+    for cert in X509::stack_from_pem(&fs::read(path)?)? {
+        builder.add_cert(cert)?;
+    }
+    if verify_hostname {
+        let mut param = X509VerifyParam::new()?;
+        match domain.parse::<IpAddr>() {
+            Ok(ip) => param.set_ip(ip)?,
+            Err(_) => param.set_host(domain)?,
+        }
+        builder.set_param(&param)?;
+    }
+    Ok(builder.build())
+}
+
+fn verify_root_certs(ssl: &SslRef, store: &X509Store) -> Result<(), Box<dyn Error + Sync + Send>> {
+    let (cert, chain) = match (ssl.peer_certificate(), ssl.peer_cert_chain()) {
+        (Some(cert), Some(chain)) => (cert, chain),
+        _ => return Err("server did not provide a certificate".into()),
+    };
+
+    let mut context = X509StoreContext::new()?;
+    let result = context.init(store, &cert, chain, |context| {
+        context.verify_cert()?;
+        Ok(context.error())
+    })?;
+    if result != X509VerifyResult::OK {
+        return Err(format!("server certificate verification failed: {}", result).into());
+    }
+    Ok(())
+}
+
+/// Loads the client's private key from a passphrase-protected file.
+///
+/// The key may either be PEM-encoded or a DER-encoded PKCS#8 structure. This corresponds to libpq's `sslpassword`
+/// parameter, which is available through `tokio_postgres::Config::get_ssl_password`.
+pub fn set_private_key_file_with_password<P>(
+    builder: &mut SslConnectorBuilder,
+    path: P,
+    password: &[u8],
+) -> Result<(), Box<dyn Error + Sync + Send>>
+where
+    P: AsRef<Path>,
+{
+    let buf = fs::read(path)?;
+    let key = if buf.starts_with(b"-----BEGIN") {
+        PKey::private_key_from_pem_passphrase(&buf, password)?
+    } else {
+        PKey::private_key_from_pkcs8_passphrase(&buf, password)?
+    };
+    builder.set_private_key(&key)?;
+    Ok(())
+}
+
+/// Enables revocation checking of the server's certificate chain against the certificate revocation lists in a file.
+///
+/// The file may contain multiple PEM-encoded CRLs. A CRL must be available for every certificate in the chain, or the
+/// handshake will fail.
+pub fn set_crl_file<P>(builder: &mut SslConnectorBuilder, path: P) -> Result<(), ErrorStack>
+where
+    P: AsRef<Path>,
+{
+    let store = builder.cert_store_mut();
+    store
+        .add_lookup(X509Lookup::file())?
+        .load_crl_file(path, SslFiletype::PEM)?;
+    store.set_flags(X509VerifyFlags::CRL_CHECK | X509VerifyFlags::CRL_CHECK_ALL)
+}
+
+/// Requires the server to staple an OCSP response to its certificate.
+///
+/// The handshake fails unless the server provides a valid, current OCSP response reporting its certificate as good.
+/// The response is checked against the server's certificate and its issuer, which must be sent by the server as part
+/// of its certificate chain.
+pub fn require_ocsp_stapling(builder: &mut SslConnectorBuilder) -> Result<(), ErrorStack> {
+    builder.set_ex_data(ocsp_stapling_index()?, ());
+    builder.set_status_callback(|ssl| Ok(verify_ocsp_status(ssl)))
+}
+
+fn ocsp_stapling_index() -> Result<Index<SslContext, ()>, ErrorStack> {
+    static INDEX: OnceLock<Index<SslContext, ()>> = OnceLock::new();
+
+    if let Some(index) = INDEX.get() {
+        return Ok(*index);
+    }
+    let index = SslContext::new_ex_index()?;
+    Ok(*INDEX.get_or_init(|| index))
+}
+
+// Acceptable clock skew when checking the validity period of the response.
+const OCSP_LEEWAY_SECS: u32 = 300;
+
+fn verify_ocsp_status(ssl: &SslRef) -> bool {
+    let response = match ssl.ocsp_status().map(OcspResponse::from_der) {
+        Some(Ok(response)) => response,
+        _ => return false,
+    };
+    if response.status() != OcspResponseStatus::SUCCESSFUL {
+        return false;
+    }
+    let basic = match response.basic() {
+        Ok(basic) => basic,
+        Err(_) => return false,
+    };
+
+    let chain = match ssl.peer_cert_chain() {
+        Some(chain) => chain,
+        None => return false,
+    };
+    let (cert, issuer) = match (chain.get(0), chain.get(1)) {
+        (Some(cert), Some(issuer)) => (cert, issuer),
+        _ => return false,
+    };
+
+    if basic
+        .verify(chain, ssl.ssl_context().cert_store(), OcspFlag::empty())
+        .is_err()
+    {
+        return false;
+    }
+
+    let id = match OcspCertId::from_cert(MessageDigest::sha1(), cert, issuer) {
+        Ok(id) => id,
+        Err(_) => return false,
+    };
+    match basic.find_status(&id) {
+        Some(status) => {
+            status.status == OcspCertStatus::GOOD
+                && status.check_validity(OCSP_LEEWAY_SECS, None).is_ok()
+        }
+        None => false,
+    }
 }
 
 #[derive(Debug)]
@@ -1,5 +1,7 @@
 use futures_util::FutureExt;
 use openssl::ssl::{SslConnector, SslMethod};
+use openssl::x509::X509;
+use std::convert::TryInto;
 use tokio::net::TcpStream;
 use tokio_postgres::tls::TlsConnect;
 
@@ -109,3 +111,212 @@ async fn runtime() {
     assert_eq!(rows.len(), 1);
     assert_eq!(rows[0].get::<_, i32>(0), 1);
 }
+
+#[tokio::test]
+#[cfg(feature = "runtime")]
+async fn runtime_session_cache() {
+    let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+    builder.set_ca_file("../test/server.crt").unwrap();
+    let connector = MakeTlsConnector::with_session_cache(builder).unwrap();
+
+    for _ in 0..2 {
+        let (client, connection) = tokio_postgres::connect(
+            "host=localhost port=5433 user=postgres sslmode=require",
+            connector.clone(),
+        )
+        .await
+        .unwrap();
+        let connection = connection.map(|r| r.unwrap());
+        tokio::spawn(connection);
+
+        client.batch_execute("SELECT 1").await.unwrap();
+    }
+
+    connector.clear_session_cache();
+}
+
+#[tokio::test]
+async fn pinned_spki() {
+    let cert = X509::from_pem(&std::fs::read("../test/server.crt").unwrap()).unwrap();
+    let spki = cert.public_key().unwrap().public_key_to_der().unwrap();
+    let digest = hash(MessageDigest::sha256(), &spki).unwrap();
+
+    let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+    builder.set_ca_file("../test/server.crt").unwrap();
+    let ctx = builder.build();
+    let mut connector = TlsConnector::new(ctx.configure().unwrap(), "localhost");
+    connector.pin_certificate(CertificatePin::SpkiSha256((*digest).try_into().unwrap()));
+    smoke_test("user=ssl_user dbname=postgres sslmode=require", connector).await;
+}
+
+#[tokio::test]
+async fn pinned_certificate_mismatch() {
+    let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+    builder.set_ca_file("../test/server.crt").unwrap();
+    let ctx = builder.build();
+    let mut connector = TlsConnector::new(ctx.configure().unwrap(), "localhost");
+    connector.pin_certificate(CertificatePin::CertificateSha256([0; 32]));
+
+    let stream = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+    let builder = "user=ssl_user dbname=postgres sslmode=require"
+        .parse::<tokio_postgres::Config>()
+        .unwrap();
+    builder.connect_raw(stream, connector).await.err().unwrap();
+}
+
+#[tokio::test]
+async fn require_ocsp_stapling_err() {
+    let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+    builder.set_ca_file("../test/server.crt").unwrap();
+    require_ocsp_stapling(&mut builder).unwrap();
+    let ctx = builder.build();
+    let connector = TlsConnector::new(ctx.configure().unwrap(), "localhost");
+
+    let stream = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+    let builder = "user=ssl_user dbname=postgres sslmode=require"
+        .parse::<tokio_postgres::Config>()
+        .unwrap();
+    builder.connect_raw(stream, connector).await.err().unwrap();
+}
+
+#[test]
+fn private_key_with_password() {
+    let key = openssl::pkey::PKey::from_rsa(openssl::rsa::Rsa::generate(2048).unwrap()).unwrap();
+    let pem = key
+        .private_key_to_pem_pkcs8_passphrase(openssl::symm::Cipher::aes_128_cbc(), b"hunter2")
+        .unwrap();
+    let path = std::env::temp_dir().join("postgres-openssl-encrypted-key.pem");
+    std::fs::write(&path, pem).unwrap();
+
+    let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+    set_private_key_file_with_password(&mut builder, &path, b"hunter2").unwrap();
+    set_private_key_file_with_password(&mut builder, &path, b"wrong").unwrap_err();
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+#[cfg(feature = "runtime")]
+async fn runtime_server_name() {
+    let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+    builder.set_ca_file("../test/server.crt").unwrap();
+    let connector = MakeTlsConnector::new(builder.build());
+
+    let (client, connection) = tokio_postgres::connect(
+        "host=127.0.0.1 port=5433 user=postgres sslmode=require sslservername=localhost sslsni=0",
+        connector,
+    )
+    .await
+    .unwrap();
+    let connection = connection.map(|r| r.unwrap());
+    tokio::spawn(connection);
+
+    client.batch_execute("SELECT 1").await.unwrap();
+}
+
+fn client_identity(days: i64) -> ClientIdentity {
+    let key = PKey::from_rsa(openssl::rsa::Rsa::generate(2048).unwrap()).unwrap();
+    let mut name = openssl::x509::X509NameBuilder::new().unwrap();
+    name.append_entry_by_text("CN", "postgres").unwrap();
+    let name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_issuer_name(&name).unwrap();
+    builder.set_pubkey(&key).unwrap();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    builder
+        .set_not_before(&Asn1Time::from_unix(now - 2 * 86400).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::from_unix(now + days * 86400).unwrap())
+        .unwrap();
+    builder.sign(&key, MessageDigest::sha256()).unwrap();
+
+    ClientIdentity::new(builder.build(), key)
+}
+
+#[test]
+fn client_identity_reload() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let loads = Arc::new(AtomicUsize::new(0));
+    let cache = IdentityCache {
+        callback: Box::new({
+            let loads = loads.clone();
+            move || {
+                let days = if loads.fetch_add(1, Ordering::SeqCst) == 0 {
+                    -1
+                } else {
+                    1
+                };
+                Ok(client_identity(days))
+            }
+        }),
+        current: Mutex::new(None),
+    };
+
+    // The first certificate has already expired, so it is replaced on the next connection.
+    cache.get().unwrap();
+    cache.get().unwrap();
+    assert_eq!(loads.load(Ordering::SeqCst), 2);
+    cache.get().unwrap();
+    assert_eq!(loads.load(Ordering::SeqCst), 2);
+
+    #[cfg(feature = "runtime")]
+    {
+        cache.clear();
+        cache.get().unwrap();
+        assert_eq!(loads.load(Ordering::SeqCst), 3);
+    }
+}
+
+#[tokio::test]
+async fn verify_ca() {
+    let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+    builder.set_ca_file("../test/server.crt").unwrap();
+    let ctx = builder.build();
+    smoke_test(
+        "user=ssl_user dbname=postgres sslmode=verify-ca",
+        TlsConnector::new(ctx.configure().unwrap(), "example.com"),
+    )
+    .await;
+
+    let stream = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+    let builder = "user=ssl_user dbname=postgres sslmode=verify-full"
+        .parse::<tokio_postgres::Config>()
+        .unwrap();
+    let connector = TlsConnector::new(ctx.configure().unwrap(), "example.com");
+    builder.connect_raw(stream, connector).await.err().unwrap();
+}
+
+#[tokio::test]
+#[cfg(feature = "runtime")]
+async fn runtime_root_cert() {
+    let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+    builder.set_verify(openssl::ssl::SslVerifyMode::NONE);
+    let connector = MakeTlsConnector::new(builder.build());
+
+    let (client, connection) = tokio_postgres::connect(
+        "host=localhost port=5433 user=postgres sslmode=verify-full sslrootcert=../test/server.crt",
+        connector.clone(),
+    )
+    .await
+    .unwrap();
+    let connection = connection.map(|r| r.unwrap());
+    tokio::spawn(connection);
+    client.batch_execute("SELECT 1").await.unwrap();
+
+    tokio_postgres::connect(
+        "host=localhost port=5433 user=postgres sslmode=verify-full sslrootcert=../test/server.crt \
+         sslservername=example.com",
+        connector,
+    )
+    .await
+    .err()
+    .unwrap();
+}
@@ -61,6 +61,7 @@ enum ChannelBindingInner {
     Unrequested,
     Unsupported,
     TlsServerEndPoint(Vec<u8>),
+    TlsExporter(Vec<u8>),
 }
 
 /// The channel binding configuration for a SCRAM authentication exchange.
@@ -83,18 +84,25 @@ impl ChannelBinding {
         ChannelBinding(ChannelBindingInner::TlsServerEndPoint(signature))
     }
 
+    /// The server requested channel binding and the client will use the `tls-exporter` method.
+    pub fn tls_exporter(keying_material: Vec<u8>) -> ChannelBinding {
+        ChannelBinding(ChannelBindingInner::TlsExporter(keying_material))
+    }
+
     fn gs2_header(&self) -> &'static str {
         match self.0 {
             ChannelBindingInner::Unrequested => "y,,",
             ChannelBindingInner::Unsupported => "n,,",
             ChannelBindingInner::TlsServerEndPoint(_) => "p=tls-server-end-point,,",
+            ChannelBindingInner::TlsExporter(_) => "p=tls-exporter,,",
         }
     }
 
     fn cbind_data(&self) -> &[u8] {
         match self.0 {
             ChannelBindingInner::Unrequested | ChannelBindingInner::Unsupported => &[],
-            ChannelBindingInner::TlsServerEndPoint(ref buf) => buf,
+            ChannelBindingInner::TlsServerEndPoint(ref buf)
+            | ChannelBindingInner::TlsExporter(ref buf) => buf,
         }
     }
 }
@@ -477,4 +485,28 @@ mod test {
 
         scram.finish(server_final.as_bytes()).unwrap();
     }
+
+    #[test]
+    fn tls_exporter_channel_binding() {
+        let nonce = "9IZ2O01zb9IgiIZ1WJ/zgpJB";
+        let server_first =
+            "r=9IZ2O01zb9IgiIZ1WJ/zgpJBjx/oIRLs02gGSHcw1KEty3eY,s=fs3IXBy7U7+IvVjZ,i\
+             =4096";
+
+        let mut scram = ScramSha256::new_inner(
+            b"foobar",
+            ChannelBinding::tls_exporter(vec![1, 2, 3]),
+            nonce.to_string(),
+        );
+        assert_eq!(
+            str::from_utf8(scram.message()).unwrap(),
+            "p=tls-exporter,,n=,r=9IZ2O01zb9IgiIZ1WJ/zgpJB"
+        );
+
+        scram.update(server_first.as_bytes()).unwrap();
+        let cbind = STANDARD.encode(b"p=tls-exporter,,\x01\x02\x03");
+        assert!(str::from_utf8(scram.message())
+            .unwrap()
+            .starts_with(&format!("c={},", cbind)));
+    }
 }
@@ -0,0 +1,223 @@
+//! Serialization and deserialization of the binary format of `COPY` data.
+//!
+//! Binary copy data is made of a header, followed by any number of tuples, and a trailer. The values of the tuples
+//! are in the binary format of their types, like those of the `types` module.
+use byteorder::{BigEndian, ByteOrder};
+use bytes::{BufMut, BytesMut};
+use std::io;
+use std::ops::Range;
+
+use crate::{write_nullable, FromUsize, IsNull};
+
+#[cfg(test)]
+mod test;
+
+/// The signature at the start of binary copy data.
+pub const SIGNATURE: &[u8] = b"PGCOPY\n\xff\r\n\0";
+
+const HAS_OIDS: i32 = 1 << 16;
+
+/// Serializes the header of binary copy data, without OIDs or header extension.
+#[inline]
+pub fn header(buf: &mut BytesMut) {
+    buf.put_slice(SIGNATURE);
+    buf.put_i32(0); // flags
+    buf.put_i32(0); // header extension
+}
+
+/// Serializes a tuple of binary copy data.
+///
+/// `serializer` is called with each value, which it writes in the binary format of its type.
+#[inline]
+pub fn tuple<I, T, F, E>(values: I, mut serializer: F, buf: &mut BytesMut) -> Result<(), E>
+where
+    I: IntoIterator<Item = T>,
+    F: FnMut(T, &mut BytesMut) -> Result<IsNull, E>,
+    E: From<io::Error>,
+{
+    let base = buf.len();
+    buf.put_i16(0);
+    let mut count = 0;
+    for value in values {
+        write_nullable(|buf| serializer(value, buf), buf)?;
+        count += 1;
+    }
+    let count = i16::from_usize(count)?;
+    BigEndian::write_i16(&mut buf[base..], count);
+
+    Ok(())
+}
+
+/// Serializes the trailer ending binary copy data.
+#[inline]
+pub fn trailer(buf: &mut BytesMut) {
+    buf.put_i16(-1);
+}
+
+/// The header of binary copy data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Header {
+    flags: i32,
+    size: usize,
+}
+
+impl Header {
+    /// Returns whether the tuples include the OIDs of their rows, in addition to their values.
+    #[inline]
+    pub fn has_oids(&self) -> bool {
+        self.flags & HAS_OIDS != 0
+    }
+
+    /// Returns the number of bytes of the header, including its extension.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+/// Deserializes the header at the start of binary copy data.
+#[inline]
+pub fn parse_header(buf: &[u8]) -> io::Result<Header> {
+    let fixed = SIGNATURE.len() + 8;
+    check_remaining(buf, fixed)?;
+    if !buf.starts_with(SIGNATURE) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "invalid magic value",
+        ));
+    }
+
+    let flags = BigEndian::read_i32(&buf[SIGNATURE.len()..]);
+    let extension = BigEndian::read_u32(&buf[SIGNATURE.len() + 4..]) as usize;
+    check_remaining(&buf[fixed..], extension)?;
+
+    Ok(Header {
+        flags,
+        size: fixed + extension,
+    })
+}
+
+/// A tuple of binary copy data.
+#[derive(Debug, Clone, Copy)]
+pub struct Tuple<'a> {
+    buf: &'a [u8],
+    len: u16,
+    size: usize,
+}
+
+impl<'a> Tuple<'a> {
+    /// Returns the number of values of the tuple, including its OID if it has one.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    /// Determines if the tuple has no values.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of bytes of the tuple.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns an iterator over the ranges of the values of the tuple in the buffer it was parsed from, or `None` for
+    /// `NULL` values.
+    #[inline]
+    pub fn ranges(&self) -> TupleRanges<'a> {
+        TupleRanges {
+            buf: self.buf,
+            pos: 2,
+            remaining: self.len,
+        }
+    }
+}
+
+/// An iterator over the ranges of the values of a tuple.
+pub struct TupleRanges<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    remaining: u16,
+}
+
+impl Iterator for TupleRanges<'_> {
+    type Item = Option<Range<usize>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Option<Range<usize>>> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        // The tuple was checked when parsed.
+        self.remaining -= 1;
+        let len = BigEndian::read_i32(&self.buf[self.pos..]);
+        self.pos += 4;
+        if len < 0 {
+            Some(None)
+        } else {
+            let start = self.pos;
+            self.pos += len as usize;
+            Some(Some(start..self.pos))
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.remaining as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for TupleRanges<'_> {}
+
+/// Deserializes a tuple at the start of the provided buffer, following the header or another tuple.
+///
+/// Returns `None` if the buffer starts with the trailer instead.
+#[inline]
+pub fn parse_tuple(buf: &[u8], has_oids: bool) -> io::Result<Option<Tuple<'_>>> {
+    check_remaining(buf, 2)?;
+    let count = BigEndian::read_i16(buf);
+    if count == -1 {
+        return Ok(None);
+    }
+    if count < 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "invalid tuple value count",
+        ));
+    }
+
+    // The OID of a tuple isn't included in the count of its values.
+    let len = count as u16 + has_oids as u16;
+    let mut pos = 2;
+    for _ in 0..len {
+        check_remaining(&buf[pos..], 4)?;
+        let value_len = BigEndian::read_i32(&buf[pos..]);
+        pos += 4;
+        if value_len >= 0 {
+            check_remaining(&buf[pos..], value_len as usize)?;
+            pos += value_len as usize;
+        }
+    }
+
+    Ok(Some(Tuple {
+        buf: &buf[..pos],
+        len,
+        size: pos,
+    }))
+}
+
+fn check_remaining(buf: &[u8], len: usize) -> io::Result<()> {
+    if buf.len() < len {
+        Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "unexpected EOF",
+        ))
+    } else {
+        Ok(())
+    }
+}
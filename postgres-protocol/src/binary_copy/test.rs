@@ -0,0 +1,109 @@
+use bytes::{BufMut, BytesMut};
+use std::io;
+
+use super::*;
+use crate::types;
+
+#[test]
+fn round_trip() {
+    let mut buf = BytesMut::new();
+    header(&mut buf);
+    tuple(
+        [Some(1), None, Some(3)],
+        |v, buf| match v {
+            Some(v) => {
+                types::int4_to_sql(v, buf);
+                Ok::<_, io::Error>(IsNull::No)
+            }
+            None => Ok(IsNull::Yes),
+        },
+        &mut buf,
+    )
+    .unwrap();
+    tuple(
+        ["foo"],
+        |v, buf| {
+            types::text_to_sql(v, buf);
+            Ok::<_, io::Error>(IsNull::No)
+        },
+        &mut buf,
+    )
+    .unwrap();
+    trailer(&mut buf);
+
+    let header = parse_header(&buf).unwrap();
+    assert!(!header.has_oids());
+    let mut pos = header.size();
+
+    let first = parse_tuple(&buf[pos..], header.has_oids())
+        .unwrap()
+        .unwrap();
+    assert_eq!(first.len(), 3);
+    let values = first
+        .ranges()
+        .map(|range| range.map(|range| types::int4_from_sql(&buf[pos..][range]).unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(values, [Some(1), None, Some(3)]);
+    pos += first.size();
+
+    let second = parse_tuple(&buf[pos..], header.has_oids())
+        .unwrap()
+        .unwrap();
+    let range = second.ranges().next().unwrap().unwrap();
+    assert_eq!(types::text_from_sql(&buf[pos..][range]).unwrap(), "foo");
+    pos += second.size();
+
+    assert!(parse_tuple(&buf[pos..], header.has_oids())
+        .unwrap()
+        .is_none());
+    assert_eq!(pos + 2, buf.len());
+}
+
+#[test]
+fn oids() {
+    let mut buf = BytesMut::new();
+    buf.put_slice(SIGNATURE);
+    buf.put_i32(1 << 16);
+    buf.put_u32(3);
+    buf.put_slice(b"ext");
+    buf.put_i16(1);
+    buf.put_i32(4);
+    buf.put_u32(1234);
+    buf.put_i32(-1);
+
+    let header = parse_header(&buf).unwrap();
+    assert!(header.has_oids());
+    assert_eq!(header.size(), SIGNATURE.len() + 11);
+
+    let tuple = parse_tuple(&buf[header.size()..], true).unwrap().unwrap();
+    assert_eq!(tuple.len(), 2);
+    assert_eq!(tuple.ranges().collect::<Vec<_>>(), [Some(6..10), None]);
+}
+
+#[test]
+fn truncated() {
+    let mut buf = BytesMut::new();
+    buf.put_slice(&SIGNATURE[..4]);
+    assert_eq!(
+        parse_header(&buf).unwrap_err().kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+
+    let mut buf = BytesMut::new();
+    buf.put_slice(b"PGCOPY\n\xff\r\n\x01");
+    buf.put_i32(0);
+    buf.put_i32(0);
+    assert_eq!(
+        parse_header(&buf).unwrap_err().kind(),
+        io::ErrorKind::InvalidData
+    );
+
+    let mut buf = BytesMut::new();
+    buf.put_i16(1);
+    buf.put_i32(8);
+    buf.put_i32(0);
+    assert_eq!(
+        parse_tuple(&buf, false).unwrap_err().kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+}
@@ -1,7 +1,7 @@
 #![allow(missing_docs)]
 
 use byteorder::{BigEndian, ByteOrder, ReadBytesExt};
-use bytes::{Bytes, BytesMut};
+use bytes::{BufMut, Bytes, BytesMut};
 use fallible_iterator::FallibleIterator;
 use memchr::memchr;
 use std::cmp;
@@ -9,7 +9,8 @@ use std::io::{self, Read};
 use std::ops::Range;
 use std::str;
 
-use crate::Oid;
+use crate::message::frontend::{write_body, write_cstr};
+use crate::{FromUsize, Lsn, Oid};
 
 pub const PARSE_COMPLETE_TAG: u8 = b'1';
 pub const BIND_COMPLETE_TAG: u8 = b'2';
@@ -20,8 +21,10 @@ pub const COMMAND_COMPLETE_TAG: u8 = b'C';
 pub const COPY_DATA_TAG: u8 = b'd';
 pub const DATA_ROW_TAG: u8 = b'D';
 pub const ERROR_RESPONSE_TAG: u8 = b'E';
+pub const FUNCTION_CALL_RESPONSE_TAG: u8 = b'V';
 pub const COPY_IN_RESPONSE_TAG: u8 = b'G';
 pub const COPY_OUT_RESPONSE_TAG: u8 = b'H';
+pub const COPY_BOTH_RESPONSE_TAG: u8 = b'W';
 pub const EMPTY_QUERY_RESPONSE_TAG: u8 = b'I';
 pub const BACKEND_KEY_DATA_TAG: u8 = b'K';
 pub const NO_DATA_TAG: u8 = b'n';
@@ -94,9 +97,11 @@ pub enum Message {
     CopyDone,
     CopyInResponse(CopyInResponseBody),
     CopyOutResponse(CopyOutResponseBody),
+    CopyBothResponse(CopyBothResponseBody),
     DataRow(DataRowBody),
     EmptyQueryResponse,
     ErrorResponse(ErrorResponseBody),
+    FunctionCallResponse(FunctionCallResponseBody),
     NoData,
     NoticeResponse(NoticeResponseBody),
     NotificationResponse(NotificationResponseBody),
@@ -171,6 +176,22 @@ impl Message {
                 let storage = buf.read_all();
                 Message::ErrorResponse(ErrorResponseBody { storage })
             }
+            FUNCTION_CALL_RESPONSE_TAG => {
+                let len = buf.read_i32::<BigEndian>()?;
+                let storage = buf.read_all();
+                if len < 0 {
+                    Message::FunctionCallResponse(FunctionCallResponseBody { storage: None })
+                } else if len as usize == storage.len() {
+                    Message::FunctionCallResponse(FunctionCallResponseBody {
+                        storage: Some(storage),
+                    })
+                } else {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "invalid function call result length",
+                    ));
+                }
+            }
             COPY_IN_RESPONSE_TAG => {
                 let format = buf.read_u8()?;
                 let len = buf.read_u16::<BigEndian>()?;
@@ -191,6 +212,16 @@ impl Message {
                     storage,
                 })
             }
+            COPY_BOTH_RESPONSE_TAG => {
+                let format = buf.read_u8()?;
+                let len = buf.read_u16::<BigEndian>()?;
+                let storage = buf.read_all();
+                Message::CopyBothResponse(CopyBothResponseBody {
+                    format,
+                    len,
+                    storage,
+                })
+            }
             EMPTY_QUERY_RESPONSE_TAG => Message::EmptyQueryResponse,
             BACKEND_KEY_DATA_TAG => {
                 let process_id = buf.read_i32::<BigEndian>()?;
@@ -279,24 +310,24 @@ impl Message {
     }
 }
 
-struct Buffer {
-    bytes: Bytes,
-    idx: usize,
+pub(crate) struct Buffer {
+    pub(crate) bytes: Bytes,
+    pub(crate) idx: usize,
 }
 
 impl Buffer {
     #[inline]
-    fn slice(&self) -> &[u8] {
+    pub(crate) fn slice(&self) -> &[u8] {
         &self.bytes[self.idx..]
     }
 
     #[inline]
-    fn is_empty(&self) -> bool {
+    pub(crate) fn is_empty(&self) -> bool {
         self.slice().is_empty()
     }
 
     #[inline]
-    fn read_cstr(&mut self) -> io::Result<Bytes> {
+    pub(crate) fn read_cstr(&mut self) -> io::Result<Bytes> {
         match memchr(0, self.slice()) {
             Some(pos) => {
                 let start = self.idx;
@@ -313,7 +344,21 @@ impl Buffer {
     }
 
     #[inline]
-    fn read_all(&mut self) -> Bytes {
+    fn read_counted(&mut self) -> io::Result<Bytes> {
+        let len = self.read_i32::<BigEndian>()?;
+        if len < 0 || self.slice().len() < len as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "unexpected EOF",
+            ));
+        }
+        let start = self.idx;
+        self.idx += len as usize;
+        Ok(self.bytes.slice(start..self.idx))
+    }
+
+    #[inline]
+    pub(crate) fn read_all(&mut self) -> Bytes {
         let buf = self.bytes.slice(self.idx..);
         self.idx = self.bytes.len();
         buf
@@ -537,6 +582,28 @@ impl CopyOutResponseBody {
     }
 }
 
+#[derive(Debug, PartialEq)]
+pub struct CopyBothResponseBody {
+    format: u8,
+    len: u16,
+    storage: Bytes,
+}
+
+impl CopyBothResponseBody {
+    #[inline]
+    pub fn format(&self) -> u8 {
+        self.format
+    }
+
+    #[inline]
+    pub fn column_formats(&self) -> ColumnFormats<'_> {
+        ColumnFormats {
+            remaining: self.len,
+            buf: &self.storage,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct DataRowBody {
     storage: Bytes,
@@ -562,6 +629,11 @@ impl DataRowBody {
     pub fn buffer_bytes(&self) -> &Bytes {
         &self.storage
     }
+
+    #[inline]
+    pub fn column_count(&self) -> u16 {
+        self.len
+    }
 }
 
 pub struct DataRowRanges<'a> {
@@ -671,6 +743,24 @@ impl<'a> ErrorField<'a> {
     }
 }
 
+#[derive(Debug, PartialEq)]
+pub struct FunctionCallResponseBody {
+    storage: Option<Bytes>,
+}
+
+impl FunctionCallResponseBody {
+    /// Returns the result of the function, or `None` if it is `NULL`.
+    #[inline]
+    pub fn value(&self) -> Option<&[u8]> {
+        self.storage.as_deref()
+    }
+
+    #[inline]
+    pub fn into_bytes(self) -> Option<Bytes> {
+        self.storage
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct NoticeResponseBody {
     storage: Bytes,
@@ -801,6 +891,21 @@ impl RowDescriptionBody {
             remaining: self.len,
         }
     }
+
+    #[inline]
+    pub fn buffer(&self) -> &[u8] {
+        &self.storage
+    }
+
+    #[inline]
+    pub fn buffer_bytes(&self) -> &Bytes {
+        &self.storage
+    }
+
+    #[inline]
+    pub fn column_count(&self) -> u16 {
+        self.len
+    }
 }
 
 pub struct Fields<'a> {
@@ -895,6 +1000,1025 @@ impl<'a> Field<'a> {
     }
 }
 
+pub const XLOG_DATA_TAG: u8 = b'w';
+pub const PRIMARY_KEEPALIVE_TAG: u8 = b'k';
+
+/// A message sent by the server in a `CopyData` message during streaming replication.
+#[derive(Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ReplicationMessage<D> {
+    XLogData(XLogDataBody<D>),
+    PrimaryKeepAlive(PrimaryKeepAliveBody),
+}
+
+impl ReplicationMessage<Bytes> {
+    #[inline]
+    pub fn parse(buf: &Bytes) -> io::Result<ReplicationMessage<Bytes>> {
+        let mut buf = Buffer {
+            bytes: buf.clone(),
+            idx: 0,
+        };
+
+        let tag = buf.read_u8()?;
+
+        let replication_message = match tag {
+            XLOG_DATA_TAG => {
+                let wal_start = buf.read_u64::<BigEndian>()?;
+                let wal_end = buf.read_u64::<BigEndian>()?;
+                let timestamp = buf.read_i64::<BigEndian>()?;
+                let data = buf.read_all();
+                ReplicationMessage::XLogData(XLogDataBody {
+                    wal_start,
+                    wal_end,
+                    timestamp,
+                    data,
+                })
+            }
+            PRIMARY_KEEPALIVE_TAG => {
+                let wal_end = buf.read_u64::<BigEndian>()?;
+                let timestamp = buf.read_i64::<BigEndian>()?;
+                let reply = buf.read_u8()?;
+                ReplicationMessage::PrimaryKeepAlive(PrimaryKeepAliveBody {
+                    wal_end,
+                    timestamp,
+                    reply,
+                })
+            }
+            tag => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unknown replication message tag `{}`", tag),
+                ));
+            }
+        };
+
+        if !buf.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid message length: expected buffer to be empty",
+            ));
+        }
+
+        Ok(replication_message)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct XLogDataBody<D> {
+    wal_start: Lsn,
+    wal_end: Lsn,
+    timestamp: i64,
+    data: D,
+}
+
+impl<D> XLogDataBody<D> {
+    /// Returns the position in the WAL of the start of the data.
+    #[inline]
+    pub fn wal_start(&self) -> Lsn {
+        self.wal_start
+    }
+
+    /// Returns the current end of the WAL on the server.
+    #[inline]
+    pub fn wal_end(&self) -> Lsn {
+        self.wal_end
+    }
+
+    /// Returns the time of transmission, in microseconds since midnight on 2000-01-01.
+    #[inline]
+    pub fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
+    #[inline]
+    pub fn data(&self) -> &D {
+        &self.data
+    }
+
+    #[inline]
+    pub fn into_data(self) -> D {
+        self.data
+    }
+
+    /// Converts the data of the message, such as to decode it.
+    #[inline]
+    pub fn map_data<F, D2, E>(self, f: F) -> Result<XLogDataBody<D2>, E>
+    where
+        F: FnOnce(D) -> Result<D2, E>,
+    {
+        let data = f(self.data)?;
+        Ok(XLogDataBody {
+            wal_start: self.wal_start,
+            wal_end: self.wal_end,
+            timestamp: self.timestamp,
+            data,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PrimaryKeepAliveBody {
+    wal_end: Lsn,
+    timestamp: i64,
+    reply: u8,
+}
+
+impl PrimaryKeepAliveBody {
+    /// Returns the current end of the WAL on the server.
+    #[inline]
+    pub fn wal_end(&self) -> Lsn {
+        self.wal_end
+    }
+
+    /// Returns the time of transmission, in microseconds since midnight on 2000-01-01.
+    #[inline]
+    pub fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
+    /// Returns 1 if the server asks for a reply to the message as soon as possible, and 0 otherwise.
+    #[inline]
+    pub fn reply(&self) -> u8 {
+        self.reply
+    }
+}
+
+const BEGIN_TAG: u8 = b'B';
+const COMMIT_TAG: u8 = b'C';
+const ORIGIN_TAG: u8 = b'O';
+const RELATION_TAG: u8 = b'R';
+const TYPE_TAG: u8 = b'Y';
+const INSERT_TAG: u8 = b'I';
+const UPDATE_TAG: u8 = b'U';
+const DELETE_TAG: u8 = b'D';
+const TRUNCATE_TAG: u8 = b'T';
+const TUPLE_NEW_TAG: u8 = b'N';
+const TUPLE_KEY_TAG: u8 = b'K';
+const TUPLE_OLD_TAG: u8 = b'O';
+const TUPLE_DATA_NULL_TAG: u8 = b'n';
+const TUPLE_DATA_TOAST_TAG: u8 = b'u';
+const TUPLE_DATA_TEXT_TAG: u8 = b't';
+const TUPLE_DATA_BINARY_TAG: u8 = b'b';
+
+const REPLICA_IDENTITY_DEFAULT_TAG: u8 = b'd';
+const REPLICA_IDENTITY_NOTHING_TAG: u8 = b'n';
+const REPLICA_IDENTITY_FULL_TAG: u8 = b'f';
+const REPLICA_IDENTITY_INDEX_TAG: u8 = b'i';
+
+/// A message of the `pgoutput` logical decoding plugin, sent as the data of `XLogData` messages.
+#[derive(Debug, PartialEq)]
+#[non_exhaustive]
+pub enum LogicalReplicationMessage {
+    Begin(BeginBody),
+    Commit(CommitBody),
+    Origin(OriginBody),
+    Relation(RelationBody),
+    Type(TypeBody),
+    Insert(InsertBody),
+    Update(UpdateBody),
+    Delete(DeleteBody),
+    Truncate(TruncateBody),
+}
+
+impl LogicalReplicationMessage {
+    #[inline]
+    pub fn parse(buf: &Bytes) -> io::Result<LogicalReplicationMessage> {
+        let mut buf = Buffer {
+            bytes: buf.clone(),
+            idx: 0,
+        };
+
+        let tag = buf.read_u8()?;
+
+        let logical_replication_message = match tag {
+            BEGIN_TAG => LogicalReplicationMessage::Begin(BeginBody {
+                final_lsn: buf.read_u64::<BigEndian>()?,
+                timestamp: buf.read_i64::<BigEndian>()?,
+                xid: buf.read_u32::<BigEndian>()?,
+            }),
+            COMMIT_TAG => LogicalReplicationMessage::Commit(CommitBody {
+                flags: buf.read_i8()?,
+                commit_lsn: buf.read_u64::<BigEndian>()?,
+                end_lsn: buf.read_u64::<BigEndian>()?,
+                timestamp: buf.read_i64::<BigEndian>()?,
+            }),
+            ORIGIN_TAG => LogicalReplicationMessage::Origin(OriginBody {
+                commit_lsn: buf.read_u64::<BigEndian>()?,
+                name: buf.read_cstr()?,
+            }),
+            RELATION_TAG => {
+                let rel_id = buf.read_u32::<BigEndian>()?;
+                let namespace = buf.read_cstr()?;
+                let name = buf.read_cstr()?;
+                let replica_identity = match buf.read_u8()? {
+                    REPLICA_IDENTITY_DEFAULT_TAG => ReplicaIdentity::Default,
+                    REPLICA_IDENTITY_NOTHING_TAG => ReplicaIdentity::Nothing,
+                    REPLICA_IDENTITY_FULL_TAG => ReplicaIdentity::Full,
+                    REPLICA_IDENTITY_INDEX_TAG => ReplicaIdentity::Index,
+                    tag => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!("unknown replica identity tag `{}`", tag),
+                        ));
+                    }
+                };
+                let column_len = buf.read_i16::<BigEndian>()?;
+
+                let mut columns = Vec::with_capacity(column_len.max(0) as usize);
+                for _ in 0..column_len {
+                    columns.push(Column {
+                        flags: buf.read_i8()?,
+                        name: buf.read_cstr()?,
+                        type_id: buf.read_u32::<BigEndian>()?,
+                        type_modifier: buf.read_i32::<BigEndian>()?,
+                    });
+                }
+
+                LogicalReplicationMessage::Relation(RelationBody {
+                    rel_id,
+                    namespace,
+                    name,
+                    replica_identity,
+                    columns,
+                })
+            }
+            TYPE_TAG => LogicalReplicationMessage::Type(TypeBody {
+                id: buf.read_u32::<BigEndian>()?,
+                namespace: buf.read_cstr()?,
+                name: buf.read_cstr()?,
+            }),
+            INSERT_TAG => {
+                let rel_id = buf.read_u32::<BigEndian>()?;
+                let tuple = match buf.read_u8()? {
+                    TUPLE_NEW_TAG => Tuple::parse(&mut buf)?,
+                    tag => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!("unexpected tuple tag `{}`", tag),
+                        ));
+                    }
+                };
+
+                LogicalReplicationMessage::Insert(InsertBody { rel_id, tuple })
+            }
+            UPDATE_TAG => {
+                let rel_id = buf.read_u32::<BigEndian>()?;
+                let (key_tuple, old_tuple, new_tuple) = match buf.read_u8()? {
+                    TUPLE_KEY_TAG => {
+                        let key_tuple = Tuple::parse(&mut buf)?;
+                        (Some(key_tuple), None, parse_new_tuple(&mut buf)?)
+                    }
+                    TUPLE_OLD_TAG => {
+                        let old_tuple = Tuple::parse(&mut buf)?;
+                        (None, Some(old_tuple), parse_new_tuple(&mut buf)?)
+                    }
+                    TUPLE_NEW_TAG => (None, None, Tuple::parse(&mut buf)?),
+                    tag => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!("unexpected tuple tag `{}`", tag),
+                        ));
+                    }
+                };
+
+                LogicalReplicationMessage::Update(UpdateBody {
+                    rel_id,
+                    key_tuple,
+                    old_tuple,
+                    new_tuple,
+                })
+            }
+            DELETE_TAG => {
+                let rel_id = buf.read_u32::<BigEndian>()?;
+                let (key_tuple, old_tuple) = match buf.read_u8()? {
+                    TUPLE_KEY_TAG => (Some(Tuple::parse(&mut buf)?), None),
+                    TUPLE_OLD_TAG => (None, Some(Tuple::parse(&mut buf)?)),
+                    tag => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!("unexpected tuple tag `{}`", tag),
+                        ));
+                    }
+                };
+
+                LogicalReplicationMessage::Delete(DeleteBody {
+                    rel_id,
+                    key_tuple,
+                    old_tuple,
+                })
+            }
+            TRUNCATE_TAG => {
+                let relation_len = buf.read_i32::<BigEndian>()?;
+                let options = buf.read_i8()?;
+
+                let mut rel_ids = Vec::with_capacity(relation_len.max(0) as usize);
+                for _ in 0..relation_len {
+                    rel_ids.push(buf.read_u32::<BigEndian>()?);
+                }
+
+                LogicalReplicationMessage::Truncate(TruncateBody { options, rel_ids })
+            }
+            tag => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unknown logical replication message tag `{}`", tag),
+                ));
+            }
+        };
+
+        if !buf.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid message length: expected buffer to be empty",
+            ));
+        }
+
+        Ok(logical_replication_message)
+    }
+}
+
+#[inline]
+fn parse_new_tuple(buf: &mut Buffer) -> io::Result<Tuple> {
+    match buf.read_u8()? {
+        TUPLE_NEW_TAG => Tuple::parse(buf),
+        tag => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("unexpected tuple tag `{}`", tag),
+        )),
+    }
+}
+
+/// The values of a row in a logical replication message.
+#[derive(Debug, PartialEq)]
+pub struct Tuple(Vec<TupleData>);
+
+impl Tuple {
+    #[inline]
+    fn parse(buf: &mut Buffer) -> io::Result<Tuple> {
+        let col_len = buf.read_i16::<BigEndian>()?;
+        let mut tuple = Vec::with_capacity(col_len.max(0) as usize);
+        for _ in 0..col_len {
+            tuple.push(TupleData::parse(buf)?);
+        }
+
+        Ok(Tuple(tuple))
+    }
+
+    #[inline]
+    pub fn tuple_data(&self) -> &[TupleData] {
+        &self.0
+    }
+}
+
+/// A value of a row in a logical replication message.
+#[derive(Debug, PartialEq)]
+pub enum TupleData {
+    /// A `NULL` value.
+    Null,
+    /// A TOASTed value which wasn't changed, and whose actual value isn't sent.
+    UnchangedToast,
+    /// A value in the text format.
+    Text(Bytes),
+    /// A value in the binary format.
+    Binary(Bytes),
+}
+
+impl TupleData {
+    #[inline]
+    fn parse(buf: &mut Buffer) -> io::Result<TupleData> {
+        let type_tag = buf.read_u8()?;
+
+        let tuple = match type_tag {
+            TUPLE_DATA_NULL_TAG => TupleData::Null,
+            TUPLE_DATA_TOAST_TAG => TupleData::UnchangedToast,
+            TUPLE_DATA_TEXT_TAG => TupleData::Text(buf.read_counted()?),
+            TUPLE_DATA_BINARY_TAG => TupleData::Binary(buf.read_counted()?),
+            tag => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unknown tuple data tag `{}`", tag),
+                ));
+            }
+        };
+
+        Ok(tuple)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BeginBody {
+    final_lsn: Lsn,
+    timestamp: i64,
+    xid: u32,
+}
+
+impl BeginBody {
+    /// Returns the position in the WAL of the commit of the transaction.
+    #[inline]
+    pub fn final_lsn(&self) -> Lsn {
+        self.final_lsn
+    }
+
+    /// Returns the commit time of the transaction, in microseconds since midnight on 2000-01-01.
+    #[inline]
+    pub fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
+    #[inline]
+    pub fn xid(&self) -> u32 {
+        self.xid
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CommitBody {
+    flags: i8,
+    commit_lsn: Lsn,
+    end_lsn: Lsn,
+    timestamp: i64,
+}
+
+impl CommitBody {
+    #[inline]
+    pub fn flags(&self) -> i8 {
+        self.flags
+    }
+
+    /// Returns the position in the WAL of the commit.
+    #[inline]
+    pub fn commit_lsn(&self) -> Lsn {
+        self.commit_lsn
+    }
+
+    /// Returns the position in the WAL of the end of the transaction.
+    #[inline]
+    pub fn end_lsn(&self) -> Lsn {
+        self.end_lsn
+    }
+
+    /// Returns the commit time of the transaction, in microseconds since midnight on 2000-01-01.
+    #[inline]
+    pub fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct OriginBody {
+    commit_lsn: Lsn,
+    name: Bytes,
+}
+
+impl OriginBody {
+    /// Returns the position in the WAL of the commit on the origin server.
+    #[inline]
+    pub fn commit_lsn(&self) -> Lsn {
+        self.commit_lsn
+    }
+
+    #[inline]
+    pub fn name(&self) -> io::Result<&str> {
+        get_str(&self.name)
+    }
+}
+
+/// The replica identity setting of a table, determining which values of its rows are sent on updates and deletes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ReplicaIdentity {
+    /// The values of the columns of the primary key, if any.
+    Default,
+    /// No values.
+    Nothing,
+    /// The values of all columns.
+    Full,
+    /// The values of the columns of a specific index.
+    Index,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RelationBody {
+    rel_id: Oid,
+    namespace: Bytes,
+    name: Bytes,
+    replica_identity: ReplicaIdentity,
+    columns: Vec<Column>,
+}
+
+impl RelationBody {
+    #[inline]
+    pub fn rel_id(&self) -> Oid {
+        self.rel_id
+    }
+
+    #[inline]
+    pub fn namespace(&self) -> io::Result<&str> {
+        get_str(&self.namespace)
+    }
+
+    #[inline]
+    pub fn name(&self) -> io::Result<&str> {
+        get_str(&self.name)
+    }
+
+    #[inline]
+    pub fn replica_identity(&self) -> ReplicaIdentity {
+        self.replica_identity
+    }
+
+    #[inline]
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+}
+
+/// A column of a relation in a logical replication message.
+#[derive(Debug, PartialEq)]
+pub struct Column {
+    flags: i8,
+    name: Bytes,
+    type_id: Oid,
+    type_modifier: i32,
+}
+
+impl Column {
+    /// Returns 1 if the column is part of the key of the relation, and 0 otherwise.
+    #[inline]
+    pub fn flags(&self) -> i8 {
+        self.flags
+    }
+
+    #[inline]
+    pub fn name(&self) -> io::Result<&str> {
+        get_str(&self.name)
+    }
+
+    #[inline]
+    pub fn type_id(&self) -> Oid {
+        self.type_id
+    }
+
+    #[inline]
+    pub fn type_modifier(&self) -> i32 {
+        self.type_modifier
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TypeBody {
+    id: Oid,
+    namespace: Bytes,
+    name: Bytes,
+}
+
+impl TypeBody {
+    #[inline]
+    pub fn id(&self) -> Oid {
+        self.id
+    }
+
+    #[inline]
+    pub fn namespace(&self) -> io::Result<&str> {
+        get_str(&self.namespace)
+    }
+
+    #[inline]
+    pub fn name(&self) -> io::Result<&str> {
+        get_str(&self.name)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct InsertBody {
+    rel_id: Oid,
+    tuple: Tuple,
+}
+
+impl InsertBody {
+    #[inline]
+    pub fn rel_id(&self) -> Oid {
+        self.rel_id
+    }
+
+    #[inline]
+    pub fn tuple(&self) -> &Tuple {
+        &self.tuple
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct UpdateBody {
+    rel_id: Oid,
+    key_tuple: Option<Tuple>,
+    old_tuple: Option<Tuple>,
+    new_tuple: Tuple,
+}
+
+impl UpdateBody {
+    #[inline]
+    pub fn rel_id(&self) -> Oid {
+        self.rel_id
+    }
+
+    /// Returns the values of the key of the row before the update, if the key was changed.
+    #[inline]
+    pub fn key_tuple(&self) -> Option<&Tuple> {
+        self.key_tuple.as_ref()
+    }
+
+    /// Returns the values of the row before the update, if the replica identity of the relation is `Full`.
+    #[inline]
+    pub fn old_tuple(&self) -> Option<&Tuple> {
+        self.old_tuple.as_ref()
+    }
+
+    #[inline]
+    pub fn new_tuple(&self) -> &Tuple {
+        &self.new_tuple
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DeleteBody {
+    rel_id: Oid,
+    key_tuple: Option<Tuple>,
+    old_tuple: Option<Tuple>,
+}
+
+impl DeleteBody {
+    #[inline]
+    pub fn rel_id(&self) -> Oid {
+        self.rel_id
+    }
+
+    /// Returns the values of the key of the deleted row, unless the replica identity of the relation is `Full`.
+    #[inline]
+    pub fn key_tuple(&self) -> Option<&Tuple> {
+        self.key_tuple.as_ref()
+    }
+
+    /// Returns the values of the deleted row, if the replica identity of the relation is `Full`.
+    #[inline]
+    pub fn old_tuple(&self) -> Option<&Tuple> {
+        self.old_tuple.as_ref()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TruncateBody {
+    options: i8,
+    rel_ids: Vec<Oid>,
+}
+
+impl TruncateBody {
+    #[inline]
+    pub fn rel_ids(&self) -> &[Oid] {
+        &self.rel_ids
+    }
+
+    /// Returns the options of the truncation: 1 for `CASCADE`, 2 for `RESTART IDENTITY`.
+    #[inline]
+    pub fn options(&self) -> i8 {
+        self.options
+    }
+}
+
+// Serialization of backend messages, the counterpart of `Message::parse`, used to script servers when testing clients
+// and by proxies. Messages only sent through proxies, like those of SASL authentication, require the `proxy` feature.
+
+/// The description of a column of a `RowDescription` message.
+#[derive(Debug, Copy, Clone)]
+pub struct FieldDescription<'a> {
+    pub name: &'a str,
+    pub table_oid: Oid,
+    pub column_id: i16,
+    pub type_oid: Oid,
+    pub type_size: i16,
+    pub type_modifier: i32,
+    pub format: i16,
+}
+
+#[inline]
+pub fn authentication_ok(buf: &mut BytesMut) {
+    buf.put_u8(AUTHENTICATION_TAG);
+    write_body(buf, |buf| {
+        buf.put_i32(0);
+        Ok::<_, io::Error>(())
+    })
+    .unwrap();
+}
+
+#[inline]
+pub fn authentication_cleartext_password(buf: &mut BytesMut) {
+    buf.put_u8(AUTHENTICATION_TAG);
+    write_body(buf, |buf| {
+        buf.put_i32(3);
+        Ok::<_, io::Error>(())
+    })
+    .unwrap();
+}
+
+#[inline]
+pub fn authentication_md5_password(salt: [u8; 4], buf: &mut BytesMut) {
+    buf.put_u8(AUTHENTICATION_TAG);
+    write_body(buf, |buf| {
+        buf.put_i32(5);
+        buf.put_slice(&salt);
+        Ok::<_, io::Error>(())
+    })
+    .unwrap();
+}
+
+#[inline]
+pub fn backend_key_data(process_id: i32, secret_key: i32, buf: &mut BytesMut) {
+    buf.put_u8(BACKEND_KEY_DATA_TAG);
+    write_body(buf, |buf| {
+        buf.put_i32(process_id);
+        buf.put_i32(secret_key);
+        Ok::<_, io::Error>(())
+    })
+    .unwrap();
+}
+
+#[inline]
+pub fn parameter_status(name: &str, value: &str, buf: &mut BytesMut) -> io::Result<()> {
+    buf.put_u8(PARAMETER_STATUS_TAG);
+    write_body(buf, |buf| {
+        write_cstr(name.as_bytes(), buf)?;
+        write_cstr(value.as_bytes(), buf)
+    })
+}
+
+#[inline]
+pub fn ready_for_query(status: u8, buf: &mut BytesMut) {
+    buf.put_u8(READY_FOR_QUERY_TAG);
+    write_body(buf, |buf| {
+        buf.put_u8(status);
+        Ok::<_, io::Error>(())
+    })
+    .unwrap();
+}
+
+#[inline]
+pub fn parse_complete(buf: &mut BytesMut) {
+    write_empty(PARSE_COMPLETE_TAG, buf);
+}
+
+#[inline]
+pub fn bind_complete(buf: &mut BytesMut) {
+    write_empty(BIND_COMPLETE_TAG, buf);
+}
+
+#[inline]
+pub fn close_complete(buf: &mut BytesMut) {
+    write_empty(CLOSE_COMPLETE_TAG, buf);
+}
+
+#[inline]
+pub fn no_data(buf: &mut BytesMut) {
+    write_empty(NO_DATA_TAG, buf);
+}
+
+#[inline]
+pub fn portal_suspended(buf: &mut BytesMut) {
+    write_empty(PORTAL_SUSPENDED_TAG, buf);
+}
+
+#[inline]
+pub fn empty_query_response(buf: &mut BytesMut) {
+    write_empty(EMPTY_QUERY_RESPONSE_TAG, buf);
+}
+
+#[inline]
+pub fn copy_done(buf: &mut BytesMut) {
+    write_empty(COPY_DONE_TAG, buf);
+}
+
+#[inline]
+pub fn parameter_description<I>(types: I, buf: &mut BytesMut) -> io::Result<()>
+where
+    I: IntoIterator<Item = Oid>,
+{
+    buf.put_u8(PARAMETER_DESCRIPTION_TAG);
+    write_body(buf, |buf| {
+        write_counted(types, |oid, buf| buf.put_u32(oid), buf)
+    })
+}
+
+#[inline]
+pub fn row_description<'a, I>(fields: I, buf: &mut BytesMut) -> io::Result<()>
+where
+    I: IntoIterator<Item = FieldDescription<'a>>,
+{
+    buf.put_u8(ROW_DESCRIPTION_TAG);
+    write_body(buf, |buf| {
+        let base = buf.len();
+        buf.put_i16(0);
+        let mut count = 0;
+        for field in fields {
+            write_cstr(field.name.as_bytes(), buf)?;
+            buf.put_u32(field.table_oid);
+            buf.put_i16(field.column_id);
+            buf.put_u32(field.type_oid);
+            buf.put_i16(field.type_size);
+            buf.put_i32(field.type_modifier);
+            buf.put_i16(field.format);
+            count += 1;
+        }
+        let count = i16::from_usize(count)?;
+        BigEndian::write_i16(&mut buf[base..], count);
+        Ok(())
+    })
+}
+
+#[inline]
+pub fn data_row<'a, I>(values: I, buf: &mut BytesMut) -> io::Result<()>
+where
+    I: IntoIterator<Item = Option<&'a [u8]>>,
+{
+    buf.put_u8(DATA_ROW_TAG);
+    write_body(buf, |buf| {
+        let base = buf.len();
+        buf.put_i16(0);
+        let mut count = 0;
+        for value in values {
+            match value {
+                Some(value) => {
+                    buf.put_i32(i32::from_usize(value.len())?);
+                    buf.put_slice(value);
+                }
+                None => buf.put_i32(-1),
+            }
+            count += 1;
+        }
+        let count = i16::from_usize(count)?;
+        BigEndian::write_i16(&mut buf[base..], count);
+        Ok(())
+    })
+}
+
+#[inline]
+pub fn command_complete(tag: &str, buf: &mut BytesMut) -> io::Result<()> {
+    buf.put_u8(COMMAND_COMPLETE_TAG);
+    write_body(buf, |buf| write_cstr(tag.as_bytes(), buf))
+}
+
+/// Writes an `ErrorResponse` message with the provided fields, each made of a field type and a value.
+#[inline]
+pub fn error_response<'a, I>(fields: I, buf: &mut BytesMut) -> io::Result<()>
+where
+    I: IntoIterator<Item = (u8, &'a str)>,
+{
+    buf.put_u8(ERROR_RESPONSE_TAG);
+    write_body(buf, |buf| write_fields(fields, buf))
+}
+
+/// Writes a `NoticeResponse` message with the provided fields, each made of a field type and a value.
+#[inline]
+pub fn notice_response<'a, I>(fields: I, buf: &mut BytesMut) -> io::Result<()>
+where
+    I: IntoIterator<Item = (u8, &'a str)>,
+{
+    buf.put_u8(NOTICE_RESPONSE_TAG);
+    write_body(buf, |buf| write_fields(fields, buf))
+}
+
+#[inline]
+pub fn notification_response(
+    process_id: i32,
+    channel: &str,
+    message: &str,
+    buf: &mut BytesMut,
+) -> io::Result<()> {
+    buf.put_u8(NOTIFICATION_RESPONSE_TAG);
+    write_body(buf, |buf| {
+        buf.put_i32(process_id);
+        write_cstr(channel.as_bytes(), buf)?;
+        write_cstr(message.as_bytes(), buf)
+    })
+}
+
+#[inline]
+pub fn copy_in_response<I>(format: u8, column_formats: I, buf: &mut BytesMut) -> io::Result<()>
+where
+    I: IntoIterator<Item = i16>,
+{
+    buf.put_u8(COPY_IN_RESPONSE_TAG);
+    write_body(buf, |buf| {
+        buf.put_u8(format);
+        write_counted(column_formats, |format, buf| buf.put_i16(format), buf)
+    })
+}
+
+#[inline]
+pub fn copy_out_response<I>(format: u8, column_formats: I, buf: &mut BytesMut) -> io::Result<()>
+where
+    I: IntoIterator<Item = i16>,
+{
+    buf.put_u8(COPY_OUT_RESPONSE_TAG);
+    write_body(buf, |buf| {
+        buf.put_u8(format);
+        write_counted(column_formats, |format, buf| buf.put_i16(format), buf)
+    })
+}
+
+#[inline]
+pub fn copy_both_response<I>(format: u8, column_formats: I, buf: &mut BytesMut) -> io::Result<()>
+where
+    I: IntoIterator<Item = i16>,
+{
+    buf.put_u8(COPY_BOTH_RESPONSE_TAG);
+    write_body(buf, |buf| {
+        buf.put_u8(format);
+        write_counted(column_formats, |format, buf| buf.put_i16(format), buf)
+    })
+}
+
+#[inline]
+pub fn copy_data(data: &[u8], buf: &mut BytesMut) -> io::Result<()> {
+    buf.put_u8(COPY_DATA_TAG);
+    write_body(buf, |buf| {
+        buf.put_slice(data);
+        Ok(())
+    })
+}
+
+#[cfg(feature = "proxy")]
+#[inline]
+pub fn authentication_sasl<'a, I>(mechanisms: I, buf: &mut BytesMut) -> io::Result<()>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    buf.put_u8(AUTHENTICATION_TAG);
+    write_body(buf, |buf| {
+        buf.put_i32(10);
+        for mechanism in mechanisms {
+            write_cstr(mechanism.as_bytes(), buf)?;
+        }
+        buf.put_u8(0);
+        Ok(())
+    })
+}
+
+#[cfg(feature = "proxy")]
+#[inline]
+pub fn authentication_sasl_continue(data: &[u8], buf: &mut BytesMut) -> io::Result<()> {
+    buf.put_u8(AUTHENTICATION_TAG);
+    write_body(buf, |buf| {
+        buf.put_i32(11);
+        buf.put_slice(data);
+        Ok(())
+    })
+}
+
+#[cfg(feature = "proxy")]
+#[inline]
+pub fn authentication_sasl_final(data: &[u8], buf: &mut BytesMut) -> io::Result<()> {
+    buf.put_u8(AUTHENTICATION_TAG);
+    write_body(buf, |buf| {
+        buf.put_i32(12);
+        buf.put_slice(data);
+        Ok(())
+    })
+}
+
+#[inline]
+fn write_empty(tag: u8, buf: &mut BytesMut) {
+    buf.put_u8(tag);
+    write_body(buf, |_| Ok::<(), io::Error>(())).unwrap();
+}
+
+#[inline]
+fn write_counted<I, T, F>(items: I, mut serializer: F, buf: &mut BytesMut) -> io::Result<()>
+where
+    I: IntoIterator<Item = T>,
+    F: FnMut(T, &mut BytesMut),
+{
+    let base = buf.len();
+    buf.put_i16(0);
+    let mut count = 0;
+    for item in items {
+        serializer(item, buf);
+        count += 1;
+    }
+    let count = i16::from_usize(count)?;
+    BigEndian::write_i16(&mut buf[base..], count);
+    Ok(())
+}
+
+#[inline]
+fn write_fields<'a, I>(fields: I, buf: &mut BytesMut) -> io::Result<()>
+where
+    I: IntoIterator<Item = (u8, &'a str)>,
+{
+    for (type_, value) in fields {
+        buf.put_u8(type_);
+        write_cstr(value.as_bytes(), buf)?;
+    }
+    buf.put_u8(0);
+    Ok(())
+}
+
 #[inline]
 fn find_null(buf: &[u8], start: usize) -> io::Result<usize> {
     match memchr(0, &buf[start..]) {
@@ -907,6 +2031,6 @@ fn find_null(buf: &[u8], start: usize) -> io::Result<usize> {
 }
 
 #[inline]
-fn get_str(buf: &[u8]) -> io::Result<&str> {
+pub(crate) fn get_str(buf: &[u8]) -> io::Result<&str> {
     str::from_utf8(buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
 }
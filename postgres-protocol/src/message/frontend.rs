@@ -1,4 +1,7 @@
 //! Frontend message serialization.
+//!
+//! With the `proxy` Cargo feature, frontend messages can also be parsed, for servers and proxies speaking the
+//! protocol with clients.
 #![allow(missing_docs)]
 
 use byteorder::{BigEndian, ByteOrder};
@@ -8,10 +11,21 @@ use std::error::Error;
 use std::io;
 use std::marker;
 
-use crate::{write_nullable, FromUsize, IsNull, Oid};
+use crate::{FromUsize, IsNull, Lsn, Oid};
+
+#[cfg(feature = "proxy")]
+mod parse;
+#[cfg(feature = "proxy")]
+pub use self::parse::{
+    BindBody, BindValues, CancelRequestBody, CloseBody, CopyDataBody, CopyFailBody, DescribeBody,
+    ExecuteBody, Message, ParseBody, PasswordMessageBody, QueryBody, SaslInitialResponse,
+    StartupBody, StartupMessage, StartupParameters, BIND_TAG, CLOSE_TAG, COPY_DATA_TAG,
+    COPY_DONE_TAG, COPY_FAIL_TAG, DEFAULT_MAX_MESSAGE_LEN, DESCRIBE_TAG, EXECUTE_TAG, FLUSH_TAG,
+    MAX_STARTUP_MESSAGE_LEN, PARSE_TAG, PASSWORD_MESSAGE_TAG, QUERY_TAG, SYNC_TAG, TERMINATE_TAG,
+};
 
 #[inline]
-fn write_body<F, E>(buf: &mut BytesMut, f: F) -> Result<(), E>
+pub(crate) fn write_body<F, E>(buf: &mut BytesMut, f: F) -> Result<(), E>
 where
     F: FnOnce(&mut BytesMut) -> Result<(), E>,
     E: From<io::Error>,
@@ -60,8 +74,56 @@ where
     J: IntoIterator<Item = T>,
     F: FnMut(T, &mut BytesMut) -> Result<IsNull, Box<dyn Error + marker::Sync + Send>>,
     K: IntoIterator<Item = i16>,
+{
+    bind_deferred(
+        portal,
+        statement,
+        formats,
+        values,
+        |v, buf| {
+            serializer(v, buf).map(|is_null| match is_null {
+                IsNull::Yes => BindValue::Null,
+                IsNull::No => BindValue::Written,
+            })
+        },
+        result_formats,
+        buf,
+    )
+}
+
+/// A parameter value written by the serializer of [`bind_deferred`].
+pub enum BindValue {
+    /// The value is null.
+    Null,
+    /// The value has been written to the buffer.
+    Written,
+    /// The value, of the given length in bytes, has been left out of the buffer.
+    Deferred(usize),
+}
+
+/// Like [`bind`], but allows values to be left out of the buffer.
+///
+/// The length of a deferred value is accounted for in the message, and its bytes must be sent in place of the value,
+/// right after the first `buf.len()` bytes seen by the serializer when deferring it.
+#[inline]
+pub fn bind_deferred<I, J, F, T, K>(
+    portal: &str,
+    statement: &str,
+    formats: I,
+    values: J,
+    mut serializer: F,
+    result_formats: K,
+    buf: &mut BytesMut,
+) -> Result<(), BindError>
+where
+    I: IntoIterator<Item = i16>,
+    J: IntoIterator<Item = T>,
+    F: FnMut(T, &mut BytesMut) -> Result<BindValue, Box<dyn Error + marker::Sync + Send>>,
+    K: IntoIterator<Item = i16>,
 {
     buf.put_u8(b'B');
+    let base = buf.len();
+    let mut deferred = 0usize;
 
     write_body(buf, |buf| {
         write_cstr(portal.as_bytes(), buf)?;
@@ -76,7 +138,20 @@ where
         )?;
         write_counted(
             values,
-            |v, buf| write_nullable(|buf| serializer(v, buf), buf),
+            |v, buf| {
+                let base = buf.len();
+                buf.put_i32(0);
+                let size = match serializer(v, buf)? {
+                    BindValue::Null => -1,
+                    BindValue::Written => i32::from_usize(buf.len() - base - 4)?,
+                    BindValue::Deferred(len) => {
+                        deferred += len;
+                        i32::from_usize(len)?
+                    }
+                };
+                BigEndian::write_i32(&mut buf[base..], size);
+                Ok::<_, BindError>(())
+            },
             buf,
         )?;
         write_counted(
@@ -88,8 +163,16 @@ where
             buf,
         )?;
 
-        Ok(())
-    })
+        Ok::<_, BindError>(())
+    })?;
+
+    if deferred > 0 {
+        let size = BigEndian::read_i32(&buf[base..]) as usize + deferred;
+        let size = i32::from_usize(size)?;
+        BigEndian::write_i32(&mut buf[base..], size);
+    }
+
+    Ok(())
 }
 
 #[inline]
@@ -158,6 +241,37 @@ where
         out.put_i32(self.len);
         out.put(self.buf);
     }
+
+    /// Serializes the header of the message, returning the data to write after it.
+    ///
+    /// This allows large data to be written without being copied into the output buffer.
+    pub fn write_header(self, out: &mut BytesMut) -> T {
+        out.put_u8(b'd');
+        out.put_i32(self.len);
+        self.buf
+    }
+}
+
+/// Serializes a standby status update, the data of a `CopyData` message reporting the progress of a replication
+/// client.
+///
+/// The timestamp is in microseconds since midnight on 2000-01-01, and `reply` is 1 to ask the server to reply
+/// immediately, or 0 otherwise.
+#[inline]
+pub fn standby_status_update(
+    write_lsn: Lsn,
+    flush_lsn: Lsn,
+    apply_lsn: Lsn,
+    timestamp: i64,
+    reply: u8,
+    buf: &mut BytesMut,
+) {
+    buf.put_u8(b'r');
+    buf.put_u64(write_lsn);
+    buf.put_u64(flush_lsn);
+    buf.put_u64(apply_lsn);
+    buf.put_i64(timestamp);
+    buf.put_u8(reply);
 }
 
 #[inline]
@@ -191,6 +305,54 @@ pub fn execute(portal: &str, max_rows: i32, buf: &mut BytesMut) -> io::Result<()
     })
 }
 
+/// Serializes a FunctionCall message, calling a function through the fast-path interface.
+///
+/// Like the values of a Bind message, the arguments are written by the serializer in the formats given by `formats`.
+#[inline]
+pub fn function_call<I, J, F, T>(
+    function: Oid,
+    formats: I,
+    args: J,
+    mut serializer: F,
+    result_format: i16,
+    buf: &mut BytesMut,
+) -> Result<(), BindError>
+where
+    I: IntoIterator<Item = i16>,
+    J: IntoIterator<Item = T>,
+    F: FnMut(T, &mut BytesMut) -> Result<IsNull, Box<dyn Error + marker::Sync + Send>>,
+{
+    buf.put_u8(b'F');
+    write_body(buf, |buf| {
+        buf.put_u32(function);
+        write_counted(
+            formats,
+            |f, buf| {
+                buf.put_i16(f);
+                Ok::<_, io::Error>(())
+            },
+            buf,
+        )?;
+        write_counted(
+            args,
+            |v, buf| {
+                let base = buf.len();
+                buf.put_i32(0);
+                let size = match serializer(v, buf)? {
+                    IsNull::Yes => -1,
+                    IsNull::No => i32::from_usize(buf.len() - base - 4)?,
+                };
+                BigEndian::write_i32(&mut buf[base..], size);
+                Ok::<_, BindError>(())
+            },
+            buf,
+        )?;
+        buf.put_i16(result_format);
+
+        Ok(())
+    })
+}
+
 #[inline]
 pub fn parse<I>(name: &str, query: &str, param_types: I, buf: &mut BytesMut) -> io::Result<()>
 where
@@ -245,6 +407,24 @@ pub fn sasl_response(data: &[u8], buf: &mut BytesMut) -> io::Result<()> {
     })
 }
 
+#[inline]
+pub fn gss_response(data: &[u8], buf: &mut BytesMut) -> io::Result<()> {
+    buf.put_u8(b'p');
+    write_body(buf, |buf| {
+        buf.put_slice(data);
+        Ok(())
+    })
+}
+
+#[inline]
+pub fn gssenc_request(buf: &mut BytesMut) {
+    write_body(buf, |buf| {
+        buf.put_i32(80_877_104);
+        Ok::<_, io::Error>(())
+    })
+    .unwrap();
+}
+
 #[inline]
 pub fn ssl_request(buf: &mut BytesMut) {
     write_body(buf, |buf| {
@@ -290,7 +470,7 @@ pub fn terminate(buf: &mut BytesMut) {
 }
 
 #[inline]
-fn write_cstr(s: &[u8], buf: &mut BytesMut) -> Result<(), io::Error> {
+pub(crate) fn write_cstr(s: &[u8], buf: &mut BytesMut) -> Result<(), io::Error> {
     if s.contains(&0) {
         return Err(io::Error::new(
             io::ErrorKind::InvalidInput,
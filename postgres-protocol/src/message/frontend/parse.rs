@@ -0,0 +1,803 @@
+use byteorder::{BigEndian, ReadBytesExt};
+use bytes::{Bytes, BytesMut};
+use fallible_iterator::FallibleIterator;
+use memchr::memchr;
+use std::io;
+
+use crate::message::backend::{get_str, Buffer};
+use crate::Oid;
+
+pub const BIND_TAG: u8 = b'B';
+pub const CLOSE_TAG: u8 = b'C';
+pub const COPY_DATA_TAG: u8 = b'd';
+pub const COPY_DONE_TAG: u8 = b'c';
+pub const COPY_FAIL_TAG: u8 = b'f';
+pub const DESCRIBE_TAG: u8 = b'D';
+pub const EXECUTE_TAG: u8 = b'E';
+pub const FLUSH_TAG: u8 = b'H';
+pub const PARSE_TAG: u8 = b'P';
+pub const PASSWORD_MESSAGE_TAG: u8 = b'p';
+pub const QUERY_TAG: u8 = b'Q';
+pub const SYNC_TAG: u8 = b'S';
+pub const TERMINATE_TAG: u8 = b'X';
+
+/// The maximum length of a startup message, as enforced by the server.
+pub const MAX_STARTUP_MESSAGE_LEN: usize = 10_000;
+
+/// The maximum length of a message accepted by `Message::parse`, which is the limit of the server for the largest
+/// messages.
+pub const DEFAULT_MAX_MESSAGE_LEN: usize = 0x3fff_ffff;
+
+const CANCEL_REQUEST_CODE: i32 = 80_877_102;
+const SSL_REQUEST_CODE: i32 = 80_877_103;
+const GSSENC_REQUEST_CODE: i32 = 80_877_104;
+
+/// An enum representing the untagged messages starting a Postgres session.
+#[derive(Debug, PartialEq)]
+#[non_exhaustive]
+pub enum StartupMessage {
+    Startup(StartupBody),
+    SslRequest,
+    GssEncRequest,
+    CancelRequest(CancelRequestBody),
+}
+
+impl StartupMessage {
+    /// Parses the first message sent by a client, which unlike the following ones has no tag.
+    ///
+    /// `SslRequest` and `GssEncRequest` messages are followed by another startup message once the server has answered
+    /// them. Messages longer than `MAX_STARTUP_MESSAGE_LEN` are rejected before being buffered.
+    #[inline]
+    pub fn parse(buf: &mut BytesMut) -> io::Result<Option<StartupMessage>> {
+        if buf.len() < 4 {
+            let to_read = 4 - buf.len();
+            buf.reserve(to_read);
+            return Ok(None);
+        }
+
+        let len = (&buf[..4]).read_u32::<BigEndian>().unwrap() as usize;
+        if len < 8 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid message length: startup message length < 8",
+            ));
+        }
+        if len > MAX_STARTUP_MESSAGE_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid message length: startup message too large",
+            ));
+        }
+
+        if buf.len() < len {
+            let to_read = len - buf.len();
+            buf.reserve(to_read);
+            return Ok(None);
+        }
+
+        let mut buf = Buffer {
+            bytes: buf.split_to(len).freeze(),
+            idx: 4,
+        };
+
+        let message = match buf.read_i32::<BigEndian>()? {
+            CANCEL_REQUEST_CODE => {
+                let process_id = buf.read_i32::<BigEndian>()?;
+                let secret_key = buf.read_i32::<BigEndian>()?;
+                StartupMessage::CancelRequest(CancelRequestBody {
+                    process_id,
+                    secret_key,
+                })
+            }
+            SSL_REQUEST_CODE => StartupMessage::SslRequest,
+            GSSENC_REQUEST_CODE => StartupMessage::GssEncRequest,
+            protocol_version => {
+                let storage = buf.read_all();
+                if storage.last() != Some(&0) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "invalid startup message: missing terminator",
+                    ));
+                }
+                StartupMessage::Startup(StartupBody {
+                    protocol_version,
+                    storage,
+                })
+            }
+        };
+
+        if !buf.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid message length: expected buffer to be empty",
+            ));
+        }
+
+        Ok(Some(message))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct StartupBody {
+    protocol_version: i32,
+    storage: Bytes,
+}
+
+impl StartupBody {
+    /// Returns the protocol version requested by the client, like `0x00_03_00_00` for 3.0.
+    #[inline]
+    pub fn protocol_version(&self) -> i32 {
+        self.protocol_version
+    }
+
+    #[inline]
+    pub fn parameters(&self) -> StartupParameters<'_> {
+        StartupParameters { buf: &self.storage }
+    }
+}
+
+pub struct StartupParameters<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> FallibleIterator for StartupParameters<'a> {
+    type Item = (&'a str, &'a str);
+    type Error = io::Error;
+
+    #[inline]
+    fn next(&mut self) -> io::Result<Option<(&'a str, &'a str)>> {
+        // The parameters end with an empty name.
+        if self.buf.len() <= 1 {
+            return Ok(None);
+        }
+
+        let name = read_cstr(&mut self.buf)?;
+        let value = read_cstr(&mut self.buf)?;
+        Ok(Some((get_str(name)?, get_str(value)?)))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CancelRequestBody {
+    process_id: i32,
+    secret_key: i32,
+}
+
+impl CancelRequestBody {
+    #[inline]
+    pub fn process_id(&self) -> i32 {
+        self.process_id
+    }
+
+    #[inline]
+    pub fn secret_key(&self) -> i32 {
+        self.secret_key
+    }
+}
+
+/// An enum representing Postgres frontend messages.
+#[derive(Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Message {
+    Bind(BindBody),
+    Close(CloseBody),
+    CopyData(CopyDataBody),
+    CopyDone,
+    CopyFail(CopyFailBody),
+    Describe(DescribeBody),
+    Execute(ExecuteBody),
+    Flush,
+    Parse(ParseBody),
+    PasswordMessage(PasswordMessageBody),
+    Query(QueryBody),
+    Sync,
+    Terminate,
+}
+
+impl Message {
+    /// Parses a message sent by a client once its session has started.
+    ///
+    /// Messages longer than `DEFAULT_MAX_MESSAGE_LEN` are rejected before being buffered.
+    #[inline]
+    pub fn parse(buf: &mut BytesMut) -> io::Result<Option<Message>> {
+        Message::parse_with_max_len(buf, DEFAULT_MAX_MESSAGE_LEN)
+    }
+
+    /// Like `parse`, but rejects messages whose length, excluding their tag, exceeds `max_len`.
+    #[inline]
+    pub fn parse_with_max_len(buf: &mut BytesMut, max_len: usize) -> io::Result<Option<Message>> {
+        if buf.len() < 5 {
+            let to_read = 5 - buf.len();
+            buf.reserve(to_read);
+            return Ok(None);
+        }
+
+        let tag = buf[0];
+        let len = (&buf[1..5]).read_u32::<BigEndian>().unwrap();
+
+        if len < 4 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid message length: parsing u32",
+            ));
+        }
+        if len as usize > max_len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid message length: message too large",
+            ));
+        }
+
+        let total_len = len as usize + 1;
+        if buf.len() < total_len {
+            let to_read = total_len - buf.len();
+            buf.reserve(to_read);
+            return Ok(None);
+        }
+
+        let mut buf = Buffer {
+            bytes: buf.split_to(total_len).freeze(),
+            idx: 5,
+        };
+
+        let message = match tag {
+            BIND_TAG => {
+                let portal = buf.read_cstr()?;
+                let statement = buf.read_cstr()?;
+                let parameter_formats = read_formats(&mut buf)?;
+                let len = buf.read_u16::<BigEndian>()?;
+                let start = buf.idx;
+                for _ in 0..len {
+                    let value_len = buf.read_i32::<BigEndian>()?;
+                    if value_len < -1 {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            "invalid message length: parameter value length < -1",
+                        ));
+                    }
+                    if value_len > 0 {
+                        if buf.slice().len() < value_len as usize {
+                            return Err(io::Error::new(
+                                io::ErrorKind::UnexpectedEof,
+                                "unexpected EOF",
+                            ));
+                        }
+                        buf.idx += value_len as usize;
+                    }
+                }
+                let values = buf.bytes.slice(start..buf.idx);
+                let result_formats = read_formats(&mut buf)?;
+                Message::Bind(BindBody {
+                    portal,
+                    statement,
+                    parameter_formats,
+                    values,
+                    len,
+                    result_formats,
+                })
+            }
+            CLOSE_TAG => {
+                let variant = buf.read_u8()?;
+                let name = buf.read_cstr()?;
+                Message::Close(CloseBody { variant, name })
+            }
+            COPY_DATA_TAG => {
+                let storage = buf.read_all();
+                Message::CopyData(CopyDataBody { storage })
+            }
+            COPY_DONE_TAG => Message::CopyDone,
+            COPY_FAIL_TAG => {
+                let message = buf.read_cstr()?;
+                Message::CopyFail(CopyFailBody { message })
+            }
+            DESCRIBE_TAG => {
+                let variant = buf.read_u8()?;
+                let name = buf.read_cstr()?;
+                Message::Describe(DescribeBody { variant, name })
+            }
+            EXECUTE_TAG => {
+                let portal = buf.read_cstr()?;
+                let max_rows = buf.read_i32::<BigEndian>()?;
+                Message::Execute(ExecuteBody { portal, max_rows })
+            }
+            FLUSH_TAG => Message::Flush,
+            PARSE_TAG => {
+                let name = buf.read_cstr()?;
+                let query = buf.read_cstr()?;
+                let len = buf.read_u16::<BigEndian>()?;
+                let parameter_types = (0..len)
+                    .map(|_| buf.read_u32::<BigEndian>())
+                    .collect::<io::Result<_>>()?;
+                Message::Parse(ParseBody {
+                    name,
+                    query,
+                    parameter_types,
+                })
+            }
+            PASSWORD_MESSAGE_TAG => {
+                let storage = buf.read_all();
+                Message::PasswordMessage(PasswordMessageBody { storage })
+            }
+            QUERY_TAG => {
+                let query = buf.read_cstr()?;
+                Message::Query(QueryBody { query })
+            }
+            SYNC_TAG => Message::Sync,
+            TERMINATE_TAG => Message::Terminate,
+            tag => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unknown message tag `{}`", tag),
+                ));
+            }
+        };
+
+        if !buf.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid message length: expected buffer to be empty",
+            ));
+        }
+
+        Ok(Some(message))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BindBody {
+    portal: Bytes,
+    statement: Bytes,
+    parameter_formats: Vec<i16>,
+    values: Bytes,
+    len: u16,
+    result_formats: Vec<i16>,
+}
+
+impl BindBody {
+    #[inline]
+    pub fn portal(&self) -> io::Result<&str> {
+        get_str(&self.portal)
+    }
+
+    #[inline]
+    pub fn statement(&self) -> io::Result<&str> {
+        get_str(&self.statement)
+    }
+
+    #[inline]
+    pub fn parameter_formats(&self) -> &[i16] {
+        &self.parameter_formats
+    }
+
+    /// Returns the values of the parameters, `None` for nulls.
+    #[inline]
+    pub fn values(&self) -> BindValues<'_> {
+        BindValues {
+            buf: &self.values,
+            remaining: self.len,
+        }
+    }
+
+    #[inline]
+    pub fn value_count(&self) -> u16 {
+        self.len
+    }
+
+    #[inline]
+    pub fn result_formats(&self) -> &[i16] {
+        &self.result_formats
+    }
+}
+
+pub struct BindValues<'a> {
+    buf: &'a [u8],
+    remaining: u16,
+}
+
+impl<'a> FallibleIterator for BindValues<'a> {
+    type Item = Option<&'a [u8]>;
+    type Error = io::Error;
+
+    #[inline]
+    fn next(&mut self) -> io::Result<Option<Option<&'a [u8]>>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        self.remaining -= 1;
+        let len = self.buf.read_i32::<BigEndian>()?;
+        if len < 0 {
+            return Ok(Some(None));
+        }
+
+        let len = len as usize;
+        if self.buf.len() < len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "unexpected EOF",
+            ));
+        }
+        let (value, rest) = self.buf.split_at(len);
+        self.buf = rest;
+        Ok(Some(Some(value)))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.remaining as usize;
+        (len, Some(len))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CloseBody {
+    variant: u8,
+    name: Bytes,
+}
+
+impl CloseBody {
+    /// Returns `b'S'` to close a statement, or `b'P'` to close a portal.
+    #[inline]
+    pub fn variant(&self) -> u8 {
+        self.variant
+    }
+
+    #[inline]
+    pub fn name(&self) -> io::Result<&str> {
+        get_str(&self.name)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CopyDataBody {
+    storage: Bytes,
+}
+
+impl CopyDataBody {
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        &self.storage
+    }
+
+    #[inline]
+    pub fn into_bytes(self) -> Bytes {
+        self.storage
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CopyFailBody {
+    message: Bytes,
+}
+
+impl CopyFailBody {
+    #[inline]
+    pub fn message(&self) -> io::Result<&str> {
+        get_str(&self.message)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DescribeBody {
+    variant: u8,
+    name: Bytes,
+}
+
+impl DescribeBody {
+    /// Returns `b'S'` to describe a statement, or `b'P'` to describe a portal.
+    #[inline]
+    pub fn variant(&self) -> u8 {
+        self.variant
+    }
+
+    #[inline]
+    pub fn name(&self) -> io::Result<&str> {
+        get_str(&self.name)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ExecuteBody {
+    portal: Bytes,
+    max_rows: i32,
+}
+
+impl ExecuteBody {
+    #[inline]
+    pub fn portal(&self) -> io::Result<&str> {
+        get_str(&self.portal)
+    }
+
+    /// Returns the maximum number of rows to return, or 0 for all of them.
+    #[inline]
+    pub fn max_rows(&self) -> i32 {
+        self.max_rows
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseBody {
+    name: Bytes,
+    query: Bytes,
+    parameter_types: Vec<Oid>,
+}
+
+impl ParseBody {
+    #[inline]
+    pub fn name(&self) -> io::Result<&str> {
+        get_str(&self.name)
+    }
+
+    #[inline]
+    pub fn query(&self) -> io::Result<&str> {
+        get_str(&self.query)
+    }
+
+    /// Returns the OIDs of the types of the parameters specified by the client, 0 leaving a type unspecified.
+    #[inline]
+    pub fn parameter_types(&self) -> &[Oid] {
+        &self.parameter_types
+    }
+}
+
+/// The body of a message answering an authentication request.
+///
+/// The same message type is used for passwords and the responses of GSSAPI and SASL authentication, so its contents
+/// depend on the request it answers.
+#[derive(Debug, PartialEq)]
+pub struct PasswordMessageBody {
+    storage: Bytes,
+}
+
+impl PasswordMessageBody {
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        &self.storage
+    }
+
+    /// Parses the body as a cleartext or MD5 password.
+    #[inline]
+    pub fn password(&self) -> io::Result<&str> {
+        let mut buf = &self.storage[..];
+        let password = read_cstr(&mut buf)?;
+        if !buf.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid message length: expected buffer to be empty",
+            ));
+        }
+        get_str(password)
+    }
+
+    /// Parses the body as the first response of SASL authentication.
+    ///
+    /// The following responses are made of the data only, as returned by `data`.
+    #[inline]
+    pub fn sasl_initial_response(&self) -> io::Result<SaslInitialResponse<'_>> {
+        let mut buf = &self.storage[..];
+        let mechanism = get_str(read_cstr(&mut buf)?)?;
+        let len = buf.read_i32::<BigEndian>()?;
+        let data = if len < 0 {
+            None
+        } else if buf.len() == len as usize {
+            Some(buf)
+        } else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid message length: SASL response length mismatch",
+            ));
+        };
+        Ok(SaslInitialResponse { mechanism, data })
+    }
+}
+
+pub struct SaslInitialResponse<'a> {
+    mechanism: &'a str,
+    data: Option<&'a [u8]>,
+}
+
+impl<'a> SaslInitialResponse<'a> {
+    /// Returns the name of the SASL mechanism selected by the client.
+    #[inline]
+    pub fn mechanism(&self) -> &'a str {
+        self.mechanism
+    }
+
+    /// Returns the mechanism-specific initial response, if any.
+    #[inline]
+    pub fn data(&self) -> Option<&'a [u8]> {
+        self.data
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct QueryBody {
+    query: Bytes,
+}
+
+impl QueryBody {
+    #[inline]
+    pub fn query(&self) -> io::Result<&str> {
+        get_str(&self.query)
+    }
+}
+
+#[inline]
+fn read_formats(buf: &mut Buffer) -> io::Result<Vec<i16>> {
+    let len = buf.read_u16::<BigEndian>()?;
+    (0..len).map(|_| buf.read_i16::<BigEndian>()).collect()
+}
+
+#[inline]
+fn read_cstr<'a>(buf: &mut &'a [u8]) -> io::Result<&'a [u8]> {
+    match memchr(0, buf) {
+        Some(pos) => {
+            let cstr = &buf[..pos];
+            *buf = &buf[pos + 1..];
+            Ok(cstr)
+        }
+        None => Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "unexpected EOF",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+    use fallible_iterator::FallibleIterator;
+
+    use super::{Message, StartupMessage};
+    use crate::message::frontend;
+    use crate::IsNull;
+
+    #[test]
+    fn startup_message() {
+        let mut buf = BytesMut::new();
+        frontend::ssl_request(&mut buf);
+        frontend::startup_message([("user", "postgres"), ("database", "db")], &mut buf).unwrap();
+        frontend::cancel_request(42, 7, &mut buf);
+
+        assert_eq!(
+            StartupMessage::parse(&mut buf).unwrap(),
+            Some(StartupMessage::SslRequest)
+        );
+        match StartupMessage::parse(&mut buf).unwrap() {
+            Some(StartupMessage::Startup(body)) => {
+                assert_eq!(body.protocol_version(), 0x00_03_00_00);
+                let parameters = body.parameters().collect::<Vec<_>>().unwrap();
+                assert_eq!(parameters, [("user", "postgres"), ("database", "db")]);
+            }
+            m => panic!("unexpected message {:?}", m),
+        }
+        match StartupMessage::parse(&mut buf).unwrap() {
+            Some(StartupMessage::CancelRequest(body)) => {
+                assert_eq!(body.process_id(), 42);
+                assert_eq!(body.secret_key(), 7);
+            }
+            m => panic!("unexpected message {:?}", m),
+        }
+        assert_eq!(StartupMessage::parse(&mut buf).unwrap(), None);
+    }
+
+    #[test]
+    fn extended_query() {
+        let mut buf = BytesMut::new();
+        frontend::parse("s1", "SELECT $1, $2", [23, 0], &mut buf).unwrap();
+        frontend::bind(
+            "p1",
+            "s1",
+            [1],
+            [Some(&b"\0\0\0\x01"[..]), None],
+            |value, buf| match value {
+                Some(value) => {
+                    buf.extend_from_slice(value);
+                    Ok(IsNull::No)
+                }
+                None => Ok(IsNull::Yes),
+            },
+            Some(1),
+            &mut buf,
+        )
+        .map_err(|_| ())
+        .unwrap();
+        frontend::describe(b'P', "p1", &mut buf).unwrap();
+        frontend::execute("p1", 10, &mut buf).unwrap();
+        frontend::sync(&mut buf);
+
+        // Messages are only parsed once they have been entirely received.
+        let mut partial = buf.split_to(3);
+        assert_eq!(Message::parse(&mut partial).unwrap(), None);
+        partial.unsplit(buf);
+        let mut buf = partial;
+
+        match Message::parse(&mut buf).unwrap() {
+            Some(Message::Parse(body)) => {
+                assert_eq!(body.name().unwrap(), "s1");
+                assert_eq!(body.query().unwrap(), "SELECT $1, $2");
+                assert_eq!(body.parameter_types(), [23, 0]);
+            }
+            m => panic!("unexpected message {:?}", m),
+        }
+        match Message::parse(&mut buf).unwrap() {
+            Some(Message::Bind(body)) => {
+                assert_eq!(body.portal().unwrap(), "p1");
+                assert_eq!(body.statement().unwrap(), "s1");
+                assert_eq!(body.parameter_formats(), [1]);
+                let values = body.values().collect::<Vec<_>>().unwrap();
+                assert_eq!(values, [Some(&b"\0\0\0\x01"[..]), None]);
+                assert_eq!(body.result_formats(), [1]);
+            }
+            m => panic!("unexpected message {:?}", m),
+        }
+        match Message::parse(&mut buf).unwrap() {
+            Some(Message::Describe(body)) => {
+                assert_eq!(body.variant(), b'P');
+                assert_eq!(body.name().unwrap(), "p1");
+            }
+            m => panic!("unexpected message {:?}", m),
+        }
+        match Message::parse(&mut buf).unwrap() {
+            Some(Message::Execute(body)) => {
+                assert_eq!(body.portal().unwrap(), "p1");
+                assert_eq!(body.max_rows(), 10);
+            }
+            m => panic!("unexpected message {:?}", m),
+        }
+        assert_eq!(Message::parse(&mut buf).unwrap(), Some(Message::Sync));
+        assert_eq!(Message::parse(&mut buf).unwrap(), None);
+    }
+
+    #[test]
+    fn authentication() {
+        let mut buf = BytesMut::new();
+        frontend::password_message(b"secret", &mut buf).unwrap();
+        frontend::sasl_initial_response("SCRAM-SHA-256", b"n,,n=,r=abc", &mut buf).unwrap();
+
+        match Message::parse(&mut buf).unwrap() {
+            Some(Message::PasswordMessage(body)) => assert_eq!(body.password().unwrap(), "secret"),
+            m => panic!("unexpected message {:?}", m),
+        }
+        match Message::parse(&mut buf).unwrap() {
+            Some(Message::PasswordMessage(body)) => {
+                let response = body.sasl_initial_response().unwrap();
+                assert_eq!(response.mechanism(), "SCRAM-SHA-256");
+                assert_eq!(response.data(), Some(&b"n,,n=,r=abc"[..]));
+            }
+            m => panic!("unexpected message {:?}", m),
+        }
+    }
+
+    #[test]
+    fn message_length_limits() {
+        let mut buf = BytesMut::from(&b"\0\x01\0\0\0\x03\0\0"[..]);
+        StartupMessage::parse(&mut buf).unwrap_err();
+        assert_eq!(buf.capacity(), 8);
+
+        let mut buf = BytesMut::new();
+        frontend::query("SELECT 1", &mut buf).unwrap();
+        Message::parse_with_max_len(&mut buf.clone(), 12).unwrap_err();
+        Message::parse_with_max_len(&mut buf.clone(), 13)
+            .unwrap()
+            .unwrap();
+
+        let mut buf = BytesMut::from(&b"Q\x7f\xff\xff\xff"[..]);
+        Message::parse(&mut buf).unwrap_err();
+    }
+
+    #[test]
+    fn bind_invalid_value_length() {
+        let mut buf = BytesMut::from(&b"B\0\0\0\x10\0\0\0\0\0\x01\xff\xff\xff\xff\0\0"[..]);
+        Message::parse(&mut buf).unwrap().unwrap();
+
+        let mut buf = BytesMut::from(&b"B\0\0\0\x10\0\0\0\0\0\x01\xff\xff\xff\xfe\0\0"[..]);
+        Message::parse(&mut buf).unwrap_err();
+    }
+
+    #[test]
+    fn unknown_message() {
+        let mut buf = BytesMut::from(&b"?\0\0\0\x04"[..]);
+        Message::parse(&mut buf).unwrap_err();
+    }
+}
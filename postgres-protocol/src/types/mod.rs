@@ -1,9 +1,13 @@
-//! Conversions to and from Postgres's binary format for various types.
+//! Conversions to and from Postgres's binary format for various types, and parsing of the text format of arrays and
+//! records.
 use byteorder::{BigEndian, ByteOrder, ReadBytesExt};
 use bytes::{BufMut, BytesMut};
 use fallible_iterator::FallibleIterator;
+use std::borrow::Cow;
 use std::boxed::Box as StdBox;
+use std::convert::TryFrom;
 use std::error::Error;
+use std::fmt::Write;
 use std::io::Read;
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::str;
@@ -19,6 +23,13 @@ const RANGE_UPPER_INCLUSIVE: u8 = 0b0000_0100;
 const RANGE_LOWER_INCLUSIVE: u8 = 0b0000_0010;
 const RANGE_EMPTY: u8 = 0b0000_0001;
 
+const NUMERIC_POS: u16 = 0x0000;
+const NUMERIC_NEG: u16 = 0x4000;
+const NUMERIC_NAN: u16 = 0xC000;
+const NUMERIC_PINF: u16 = 0xD000;
+const NUMERIC_NINF: u16 = 0xF000;
+const NUMERIC_DSCALE_MAX: u16 = 0x3FFF;
+
 const PGSQL_AF_INET: u8 = 2;
 const PGSQL_AF_INET6: u8 = 3;
 
@@ -455,6 +466,160 @@ pub fn uuid_from_sql(buf: &[u8]) -> Result<[u8; 16], StdBox<dyn Error + Sync + S
     Ok(out)
 }
 
+/// Serializes a `NUMERIC` value, given in its decimal text representation.
+///
+/// The value may have a sign, a fractional part and an exponent, or be one of `NaN`, `Infinity` and `-Infinity`. The
+/// scale of the value is the number of digits of its fractional part, less its exponent.
+pub fn numeric_to_sql(v: &str, buf: &mut BytesMut) -> Result<(), StdBox<dyn Error + Sync + Send>> {
+    let (sign, unsigned) = match v.as_bytes().first() {
+        Some(b'-') => (NUMERIC_NEG, &v[1..]),
+        Some(b'+') => (NUMERIC_POS, &v[1..]),
+        _ => (NUMERIC_POS, v),
+    };
+    if unsigned.eq_ignore_ascii_case("infinity") || unsigned.eq_ignore_ascii_case("inf") {
+        let sign = if sign == NUMERIC_NEG {
+            NUMERIC_NINF
+        } else {
+            NUMERIC_PINF
+        };
+        numeric_header(0, 0, sign, 0, buf);
+        return Ok(());
+    }
+    if v.eq_ignore_ascii_case("nan") {
+        numeric_header(0, 0, NUMERIC_NAN, 0, buf);
+        return Ok(());
+    }
+
+    let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+        Some(idx) => (&unsigned[..idx], unsigned[idx + 1..].parse::<i32>()?),
+        None => (unsigned, 0),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if (integer.is_empty() && fraction.is_empty())
+        || !integer
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return Err("invalid numeric".into());
+    }
+
+    let scale = (fraction.len() as i64 - i64::from(exponent)).max(0);
+    let scale = u16::try_from(scale)
+        .ok()
+        .filter(|&scale| scale <= NUMERIC_DSCALE_MAX)
+        .ok_or("numeric scale out of range")?;
+
+    // The position of the decimal point within the digits, which are stripped of their leading and trailing zeros.
+    let digits = integer.bytes().chain(fraction.bytes()).collect::<Vec<_>>();
+    let leading = digits.iter().take_while(|&&b| b == b'0').count();
+    let trailing = digits[leading..]
+        .iter()
+        .rev()
+        .take_while(|&&b| b == b'0')
+        .count();
+    let digits = &digits[leading..digits.len() - trailing];
+    if digits.is_empty() {
+        numeric_header(0, 0, NUMERIC_POS, scale, buf);
+        return Ok(());
+    }
+    let point = integer.len() as i64 + i64::from(exponent) - leading as i64;
+
+    // Base 10000 digits are aligned on the decimal point, so the digits are padded with zeros on both sides.
+    let padding = (4 - point.rem_euclid(4)) % 4;
+    let weight = i16::try_from((point + padding) / 4 - 1).map_err(|_| "numeric out of range")?;
+    let groups = (padding as usize + digits.len()).div_ceil(4);
+    let ndigits = i16::try_from(groups).map_err(|_| "numeric out of range")?;
+
+    numeric_header(ndigits, weight, sign, scale, buf);
+    let mut group = 0;
+    let mut len = padding as usize;
+    for &digit in digits {
+        group = group * 10 + i16::from(digit - b'0');
+        len += 1;
+        if len == 4 {
+            buf.put_i16(group);
+            group = 0;
+            len = 0;
+        }
+    }
+    if len != 0 {
+        buf.put_i16(group * 10i16.pow(4 - len as u32));
+    }
+
+    Ok(())
+}
+
+fn numeric_header(ndigits: i16, weight: i16, sign: u16, scale: u16, buf: &mut BytesMut) {
+    buf.put_i16(ndigits);
+    buf.put_i16(weight);
+    buf.put_u16(sign);
+    buf.put_u16(scale);
+}
+
+/// Deserializes a `NUMERIC` value into its decimal text representation.
+///
+/// The value is formatted as by the server: without an exponent, with as many fractional digits as its scale, or as
+/// one of `NaN`, `Infinity` and `-Infinity`.
+pub fn numeric_from_sql(mut buf: &[u8]) -> Result<String, StdBox<dyn Error + Sync + Send>> {
+    let ndigits = buf.read_i16::<BigEndian>()?;
+    let weight = buf.read_i16::<BigEndian>()?;
+    let sign = buf.read_u16::<BigEndian>()?;
+    let scale = buf.read_u16::<BigEndian>()?;
+    if ndigits < 0 || buf.len() != ndigits as usize * 2 {
+        return Err("invalid message length: numeric size mismatch".into());
+    }
+
+    match sign {
+        NUMERIC_POS | NUMERIC_NEG => {}
+        NUMERIC_NAN => return Ok("NaN".to_string()),
+        NUMERIC_PINF => return Ok("Infinity".to_string()),
+        NUMERIC_NINF => return Ok("-Infinity".to_string()),
+        _ => return Err("invalid numeric sign".into()),
+    }
+
+    let mut digits = Vec::with_capacity(ndigits as usize);
+    for _ in 0..ndigits {
+        let digit = buf.read_i16::<BigEndian>()?;
+        if !(0..10000).contains(&digit) {
+            return Err("invalid numeric digit".into());
+        }
+        digits.push(digit);
+    }
+    let digit = |i: i32| {
+        usize::try_from(i)
+            .ok()
+            .and_then(|i| digits.get(i))
+            .copied()
+            .unwrap_or(0)
+    };
+
+    let mut out = String::new();
+    if sign == NUMERIC_NEG {
+        out.push('-');
+    }
+    if weight < 0 {
+        out.push('0');
+    } else {
+        write!(out, "{}", digit(0)).unwrap();
+        for i in 1..=i32::from(weight) {
+            write!(out, "{:04}", digit(i)).unwrap();
+        }
+    }
+    if scale > 0 {
+        out.push('.');
+        let start = out.len();
+        let mut i = i32::from(weight) + 1;
+        while out.len() - start < usize::from(scale) {
+            write!(out, "{:04}", digit(i)).unwrap();
+            i += 1;
+        }
+        out.truncate(start + usize::from(scale));
+    }
+
+    Ok(out)
+}
+
 /// Serializes an array value.
 #[inline]
 pub fn array_to_sql<T, I, J, F>(
@@ -657,6 +822,354 @@ impl<'a> FallibleIterator for ArrayValues<'a> {
     }
 }
 
+/// Deserializes an array value in the text format.
+///
+/// Elements are separated by `delimiter`, which is `,` for all of the built-in types except `box`, which uses `;`.
+/// Multidimensional arrays are flattened into their elements in row-major order, as in the binary format. Elements
+/// are borrowed from the buffer unless they contain escaped characters, in which case they are unescaped.
+pub fn array_from_text(
+    buf: &[u8],
+    delimiter: u8,
+) -> Result<TextArray<'_>, StdBox<dyn Error + Sync + Send>> {
+    let mut parser = TextParser { buf, pos: 0 };
+    parser.skip_whitespace();
+
+    // Arrays whose lower bounds aren't 1 are prefixed by their dimensions, as in `[0:1][1:2]={{1,2},{3,4}}`.
+    let mut bounds = vec![];
+    while parser.eat(b'[') {
+        let lower_bound = parser.int()?;
+        if !parser.eat(b':') {
+            return Err("invalid array dimensions".into());
+        }
+        let upper_bound = parser.int()?;
+        if !parser.eat(b']') || upper_bound < lower_bound.saturating_sub(1) {
+            return Err("invalid array dimensions".into());
+        }
+        bounds.push((lower_bound, upper_bound));
+        parser.skip_whitespace();
+    }
+    if !bounds.is_empty() && !parser.eat(b'=') {
+        return Err("invalid array dimensions".into());
+    }
+    parser.skip_whitespace();
+
+    let mut array = TextArray {
+        dimensions: vec![],
+        elements: vec![],
+    };
+    // The number of dimensions is only known once the first element is reached.
+    let mut depth = None;
+    parser.array_level(0, delimiter, &mut depth, &mut array)?;
+    parser.skip_whitespace();
+    if parser.pos != buf.len() {
+        return Err("invalid array".into());
+    }
+
+    if array.elements.is_empty() {
+        array.dimensions.clear();
+    }
+    if !bounds.is_empty() {
+        if bounds.len() != array.dimensions.len() {
+            return Err("array dimensions don't match its contents".into());
+        }
+        for (dimension, (lower_bound, upper_bound)) in array.dimensions.iter_mut().zip(bounds) {
+            if i64::from(upper_bound) - i64::from(lower_bound) + 1 != i64::from(dimension.len) {
+                return Err("array dimensions don't match its contents".into());
+            }
+            dimension.lower_bound = lower_bound;
+        }
+    }
+
+    Ok(array)
+}
+
+/// The elements of an array or the fields of a record in the text format, which are `None` if `NULL`.
+pub type TextValues<'a> = Vec<Option<Cow<'a, [u8]>>>;
+
+/// A Postgres array in the text format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextArray<'a> {
+    dimensions: Vec<ArrayDimension>,
+    elements: TextValues<'a>,
+}
+
+impl<'a> TextArray<'a> {
+    /// Returns true if there are `NULL` elements.
+    #[inline]
+    pub fn has_nulls(&self) -> bool {
+        self.elements.iter().any(Option::is_none)
+    }
+
+    /// Returns the dimensions of the array, which are empty if the array is.
+    #[inline]
+    pub fn dimensions(&self) -> &[ArrayDimension] {
+        &self.dimensions
+    }
+
+    /// Returns the elements of the array, in row-major order.
+    #[inline]
+    pub fn values(&self) -> &[Option<Cow<'a, [u8]>>] {
+        &self.elements
+    }
+
+    /// Consumes the array, returning its elements in row-major order.
+    #[inline]
+    pub fn into_values(self) -> TextValues<'a> {
+        self.elements
+    }
+}
+
+/// Deserializes a record, the text format of composite types, into its fields.
+///
+/// Unlike the elements of arrays, `NULL` fields are empty, and quotes are escaped by doubling them as well as with
+/// backslashes. Fields are borrowed from the buffer unless they contain escaped characters.
+pub fn record_from_text(buf: &[u8]) -> Result<TextValues<'_>, StdBox<dyn Error + Sync + Send>> {
+    let mut parser = TextParser { buf, pos: 0 };
+    if !parser.eat(b'(') {
+        return Err("invalid record".into());
+    }
+
+    let mut fields = vec![];
+    loop {
+        fields.push(parser.record_field()?);
+        match parser.next() {
+            Some(b',') => {}
+            Some(b')') if parser.pos == buf.len() => return Ok(fields),
+            _ => return Err("invalid record".into()),
+        }
+    }
+}
+
+struct TextParser<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> TextParser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.buf.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let b = self.peek();
+        if b.is_some() {
+            self.pos += 1;
+        }
+        b
+    }
+
+    fn eat(&mut self, b: u8) -> bool {
+        if self.peek() == Some(b) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b) if b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn int(&mut self) -> Result<i32, StdBox<dyn Error + Sync + Send>> {
+        let start = self.pos;
+        self.eat(b'-');
+        while matches!(self.peek(), Some(b) if b.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        Ok(str::from_utf8(&self.buf[start..self.pos])?.parse()?)
+    }
+
+    // Parses a possibly nested level of braces, checking that all of the sub-arrays of a dimension have the same
+    // length.
+    fn array_level(
+        &mut self,
+        level: usize,
+        delimiter: u8,
+        depth: &mut Option<usize>,
+        array: &mut TextArray<'a>,
+    ) -> Result<(), StdBox<dyn Error + Sync + Send>> {
+        if !self.eat(b'{') {
+            return Err("invalid array".into());
+        }
+        self.skip_whitespace();
+        if array.dimensions.len() == level {
+            array.dimensions.push(ArrayDimension {
+                len: -1,
+                lower_bound: 1,
+            });
+        }
+
+        let mut len = 0;
+        if !self.eat(b'}') {
+            loop {
+                self.skip_whitespace();
+                let nested = self.peek() == Some(b'{');
+                match *depth {
+                    None => *depth = Some(if nested { level + 1 } else { level }),
+                    Some(depth) if nested != (level < depth) => {
+                        return Err(
+                            "multidimensional arrays must have sub-arrays with matching dimensions"
+                                .into(),
+                        )
+                    }
+                    Some(_) => {}
+                }
+
+                if nested {
+                    self.array_level(level + 1, delimiter, depth, array)?;
+                } else {
+                    let element = self.array_element(delimiter)?;
+                    array.elements.push(element);
+                }
+                len += 1;
+
+                self.skip_whitespace();
+                match self.next() {
+                    Some(b'}') => break,
+                    Some(b) if b == delimiter => {}
+                    _ => return Err("invalid array".into()),
+                }
+            }
+        } else if level > 0 {
+            return Err(
+                "multidimensional arrays must have sub-arrays with matching dimensions".into(),
+            );
+        }
+
+        // The length of each dimension is set by its first sub-array.
+        let dimension = &mut array.dimensions[level];
+        if dimension.len < 0 {
+            dimension.len = len;
+        } else if dimension.len != len {
+            return Err(
+                "multidimensional arrays must have sub-arrays with matching dimensions".into(),
+            );
+        }
+        Ok(())
+    }
+
+    fn array_element(
+        &mut self,
+        delimiter: u8,
+    ) -> Result<Option<Cow<'a, [u8]>>, StdBox<dyn Error + Sync + Send>> {
+        if self.eat(b'"') {
+            let start = self.pos;
+            loop {
+                match self.next() {
+                    Some(b'"') => break,
+                    Some(b'\\') => {
+                        self.next();
+                    }
+                    Some(_) => {}
+                    None => return Err("unterminated quoted element".into()),
+                }
+            }
+            let raw = &self.buf[start..self.pos - 1];
+            return Ok(Some(unescape_text(raw, false)));
+        }
+
+        let start = self.pos;
+        loop {
+            match self.peek() {
+                Some(b'}') | None => break,
+                Some(b) if b == delimiter => break,
+                Some(b'{') | Some(b'"') => return Err("invalid array".into()),
+                Some(b'\\') => self.pos = usize::min(self.pos + 2, self.buf.len()),
+                Some(_) => self.pos += 1,
+            }
+        }
+        let raw = &self.buf[start..self.pos];
+        if raw.is_empty() {
+            return Err("invalid array".into());
+        }
+        if raw.eq_ignore_ascii_case(b"NULL") {
+            return Ok(None);
+        }
+        // Unquoted elements are stripped of their trailing whitespace, unless it is escaped.
+        Ok(Some(unescape_text(raw, true)))
+    }
+
+    fn record_field(&mut self) -> Result<Option<Cow<'a, [u8]>>, StdBox<dyn Error + Sync + Send>> {
+        let start = self.pos;
+        let mut quoted = false;
+        loop {
+            match self.peek().ok_or("invalid record")? {
+                b',' | b')' if !quoted => break,
+                b'\\' => self.pos += 1,
+                b'"' if quoted && self.buf.get(self.pos + 1) == Some(&b'"') => self.pos += 1,
+                b'"' => quoted = !quoted,
+                _ => {}
+            }
+            self.pos += 1;
+        }
+        let raw = &self.buf[start..self.pos];
+        let is_plain = |raw: &[u8]| !raw.iter().any(|&b| b == b'"' || b == b'\\');
+        match raw {
+            // Unlike in arrays, NULL fields are empty, and empty strings are quoted.
+            [] => Ok(None),
+            raw if is_plain(raw) => Ok(Some(Cow::Borrowed(raw))),
+            [b'"', inner @ .., b'"'] if is_plain(inner) => Ok(Some(Cow::Borrowed(inner))),
+            raw => {
+                // Quotes may enclose any part of a field, and are escaped within them by doubling them.
+                let mut field = Vec::with_capacity(raw.len());
+                let mut quoted = false;
+                let mut it = raw.iter().peekable();
+                while let Some(&b) = it.next() {
+                    match b {
+                        b'\\' => field.extend(it.next()),
+                        b'"' if quoted && it.peek() == Some(&&b'"') => {
+                            field.push(b'"');
+                            it.next();
+                        }
+                        b'"' => quoted = !quoted,
+                        b => field.push(b),
+                    }
+                }
+                Ok(Some(Cow::Owned(field)))
+            }
+        }
+    }
+}
+
+// Removes the backslashes escaping the characters of an element, borrowing it if there are none, and optionally drops
+// its trailing whitespace which isn't escaped.
+fn unescape_text(raw: &[u8], trim: bool) -> Cow<'_, [u8]> {
+    if !raw.contains(&b'\\') {
+        let len = if trim {
+            raw.iter()
+                .rposition(|b| !b.is_ascii_whitespace())
+                .map_or(0, |idx| idx + 1)
+        } else {
+            raw.len()
+        };
+        return Cow::Borrowed(&raw[..len]);
+    }
+
+    let mut element = Vec::with_capacity(raw.len());
+    // The length of the element up to its last character which isn't trailing whitespace.
+    let mut len = 0;
+    let mut it = raw.iter();
+    while let Some(&b) = it.next() {
+        match b {
+            b'\\' => {
+                element.extend(it.next());
+                len = element.len();
+            }
+            b => {
+                element.push(b);
+                if !trim || !b.is_ascii_whitespace() {
+                    len = element.len();
+                }
+            }
+        }
+    }
+    element.truncate(len);
+    Cow::Owned(element)
+}
+
 /// Serializes an empty range.
 #[inline]
 pub fn empty_range_to_sql(buf: &mut BytesMut) {
@@ -1116,3 +1629,306 @@ pub fn ltxtquery_from_sql(buf: &[u8]) -> Result<&str, StdBox<dyn Error + Sync +
         _ => Err("ltxtquery version 1 only supported".into()),
     }
 }
+
+/// Serializes a `JSONPATH` value.
+#[inline]
+pub fn jsonpath_to_sql(v: &str, buf: &mut BytesMut) {
+    // A version number is prepended to a jsonpath string per spec
+    buf.put_u8(1);
+    buf.put_slice(v.as_bytes());
+}
+
+/// Deserializes a `JSONPATH` value.
+#[inline]
+pub fn jsonpath_from_sql(buf: &[u8]) -> Result<&str, StdBox<dyn Error + Sync + Send>> {
+    match buf {
+        // Remove the version number from the front of the jsonpath per spec
+        [1u8, rest @ ..] => Ok(str::from_utf8(rest)?),
+        _ => Err("jsonpath version 1 only supported".into()),
+    }
+}
+
+/// Serializes a `TSVECTOR` value.
+///
+/// Each lexeme is followed by its positions, whose 14 low bits hold the position and 2 high bits the weight, from 0
+/// for `D` to 3 for `A`. The server sorts the lexemes and their positions.
+#[inline]
+pub fn tsvector_to_sql<'a, I, P>(
+    lexemes: I,
+    buf: &mut BytesMut,
+) -> Result<(), StdBox<dyn Error + Sync + Send>>
+where
+    I: IntoIterator<Item = (&'a str, P)>,
+    P: IntoIterator<Item = u16>,
+{
+    let base = buf.len();
+    buf.put_i32(0);
+
+    let mut count = 0;
+    for (lexeme, positions) in lexemes {
+        count += 1;
+
+        if lexeme.as_bytes().contains(&0) {
+            return Err("tsvector lexemes cannot contain null bytes".into());
+        }
+        buf.put_slice(lexeme.as_bytes());
+        buf.put_u8(0);
+
+        let positions_base = buf.len();
+        buf.put_u16(0);
+        let mut positions_count = 0;
+        for position in positions {
+            positions_count += 1;
+            buf.put_u16(position);
+        }
+        let positions_count = u16::try_from(positions_count)
+            .map_err(|_| "too many tsvector positions to serialize")?;
+        BigEndian::write_u16(&mut buf[positions_base..], positions_count);
+    }
+
+    let count = i32::from_usize(count)?;
+    BigEndian::write_i32(&mut buf[base..], count);
+
+    Ok(())
+}
+
+/// Deserializes a `TSVECTOR` value.
+#[inline]
+pub fn tsvector_from_sql(
+    mut buf: &[u8],
+) -> Result<TsVectorLexemes<'_>, StdBox<dyn Error + Sync + Send>> {
+    let count = buf.read_i32::<BigEndian>()?;
+    if count < 0 {
+        return Err("invalid lexeme count".into());
+    }
+
+    Ok(TsVectorLexemes {
+        remaining: count,
+        buf,
+    })
+}
+
+/// A fallible iterator over the lexemes of a `TSVECTOR` value.
+pub struct TsVectorLexemes<'a> {
+    remaining: i32,
+    buf: &'a [u8],
+}
+
+impl<'a> FallibleIterator for TsVectorLexemes<'a> {
+    type Item = (&'a str, TsVectorPositions<'a>);
+    type Error = StdBox<dyn Error + Sync + Send>;
+
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    fn next(
+        &mut self,
+    ) -> Result<Option<(&'a str, TsVectorPositions<'a>)>, StdBox<dyn Error + Sync + Send>> {
+        if self.remaining == 0 {
+            if !self.buf.is_empty() {
+                return Err("invalid buffer size".into());
+            }
+            return Ok(None);
+        }
+
+        self.remaining -= 1;
+
+        let lexeme = read_cstr(&mut self.buf)?;
+        let count = self.buf.read_u16::<BigEndian>()? as usize;
+        if self.buf.len() < count * 2 {
+            return Err("invalid buffer size".into());
+        }
+        let (positions, buf) = self.buf.split_at(count * 2);
+        self.buf = buf;
+
+        Ok(Some((lexeme, TsVectorPositions(positions))))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.remaining as usize;
+        (len, Some(len))
+    }
+}
+
+/// An iterator over the positions of a `TSVECTOR` lexeme.
+///
+/// The 14 low bits of each position hold the position and its 2 high bits the weight, from 0 for `D` to 3 for `A`.
+#[derive(Debug, Clone)]
+pub struct TsVectorPositions<'a>(&'a [u8]);
+
+impl<'a> Iterator for TsVectorPositions<'a> {
+    type Item = u16;
+
+    #[inline]
+    fn next(&mut self) -> Option<u16> {
+        let position = self.0.read_u16::<BigEndian>().ok()?;
+        Some(position)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.len() / 2;
+        (len, Some(len))
+    }
+}
+
+impl<'a> ExactSizeIterator for TsVectorPositions<'a> {}
+
+const TSQUERY_VAL: u8 = 1;
+const TSQUERY_OPR: u8 = 2;
+
+const TSQUERY_NOT: u8 = 1;
+const TSQUERY_AND: u8 = 2;
+const TSQUERY_OR: u8 = 3;
+const TSQUERY_PHRASE: u8 = 4;
+
+/// An item of a `TSQUERY` value.
+///
+/// Queries are serialized in prefix notation: each operator is followed by its right operand and then by its left
+/// operand, if any.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TsQueryItem<'a> {
+    /// A lexeme to match.
+    Operand {
+        /// The lexeme.
+        value: &'a str,
+        /// The weights the lexeme is restricted to as a bitmask, from `1` for `D` to `8` for `A`, or 0 for all of
+        /// them.
+        weights: u8,
+        /// Whether the lexeme matches the words it prefixes.
+        prefix: bool,
+    },
+    /// The `!` operator.
+    Not,
+    /// The `&` operator.
+    And,
+    /// The `|` operator.
+    Or,
+    /// The `<N>` operator, with the distance between its operands.
+    Phrase(u16),
+}
+
+/// Serializes a `TSQUERY` value.
+#[inline]
+pub fn tsquery_to_sql<'a, I>(
+    items: I,
+    buf: &mut BytesMut,
+) -> Result<(), StdBox<dyn Error + Sync + Send>>
+where
+    I: IntoIterator<Item = TsQueryItem<'a>>,
+{
+    let base = buf.len();
+    buf.put_i32(0);
+
+    let mut count = 0;
+    for item in items {
+        count += 1;
+
+        match item {
+            TsQueryItem::Operand {
+                value,
+                weights,
+                prefix,
+            } => {
+                if value.as_bytes().contains(&0) {
+                    return Err("tsquery lexemes cannot contain null bytes".into());
+                }
+                buf.put_u8(TSQUERY_VAL);
+                buf.put_u8(weights);
+                buf.put_u8(prefix as u8);
+                buf.put_slice(value.as_bytes());
+                buf.put_u8(0);
+            }
+            TsQueryItem::Not => buf.put_slice(&[TSQUERY_OPR, TSQUERY_NOT]),
+            TsQueryItem::And => buf.put_slice(&[TSQUERY_OPR, TSQUERY_AND]),
+            TsQueryItem::Or => buf.put_slice(&[TSQUERY_OPR, TSQUERY_OR]),
+            TsQueryItem::Phrase(distance) => {
+                buf.put_slice(&[TSQUERY_OPR, TSQUERY_PHRASE]);
+                buf.put_u16(distance);
+            }
+        }
+    }
+
+    let count = i32::from_usize(count)?;
+    BigEndian::write_i32(&mut buf[base..], count);
+
+    Ok(())
+}
+
+/// Deserializes a `TSQUERY` value.
+#[inline]
+pub fn tsquery_from_sql(
+    mut buf: &[u8],
+) -> Result<TsQueryItems<'_>, StdBox<dyn Error + Sync + Send>> {
+    let count = buf.read_i32::<BigEndian>()?;
+    if count < 0 {
+        return Err("invalid item count".into());
+    }
+
+    Ok(TsQueryItems {
+        remaining: count,
+        buf,
+    })
+}
+
+/// A fallible iterator over the items of a `TSQUERY` value.
+pub struct TsQueryItems<'a> {
+    remaining: i32,
+    buf: &'a [u8],
+}
+
+impl<'a> FallibleIterator for TsQueryItems<'a> {
+    type Item = TsQueryItem<'a>;
+    type Error = StdBox<dyn Error + Sync + Send>;
+
+    #[inline]
+    fn next(&mut self) -> Result<Option<TsQueryItem<'a>>, StdBox<dyn Error + Sync + Send>> {
+        if self.remaining == 0 {
+            if !self.buf.is_empty() {
+                return Err("invalid buffer size".into());
+            }
+            return Ok(None);
+        }
+
+        self.remaining -= 1;
+
+        let item = match self.buf.read_u8()? {
+            TSQUERY_VAL => {
+                let weights = self.buf.read_u8()?;
+                let prefix = self.buf.read_u8()? != 0;
+                let value = read_cstr(&mut self.buf)?;
+                TsQueryItem::Operand {
+                    value,
+                    weights,
+                    prefix,
+                }
+            }
+            TSQUERY_OPR => match self.buf.read_u8()? {
+                TSQUERY_NOT => TsQueryItem::Not,
+                TSQUERY_AND => TsQueryItem::And,
+                TSQUERY_OR => TsQueryItem::Or,
+                TSQUERY_PHRASE => TsQueryItem::Phrase(self.buf.read_u16::<BigEndian>()?),
+                _ => return Err("invalid tsquery operator".into()),
+            },
+            _ => return Err("invalid tsquery item type".into()),
+        };
+
+        Ok(Some(item))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.remaining as usize;
+        (len, Some(len))
+    }
+}
+
+fn read_cstr<'a>(buf: &mut &'a [u8]) -> Result<&'a str, StdBox<dyn Error + Sync + Send>> {
+    let end = match buf.iter().position(|&b| b == 0) {
+        Some(end) => end,
+        None => return Err("unexpected EOF".into()),
+    };
+    let s = str::from_utf8(&buf[..end])?;
+    *buf = &buf[end + 1..];
+    Ok(s)
+}
@@ -1,5 +1,6 @@
 use bytes::{Buf, BytesMut};
 use fallible_iterator::FallibleIterator;
+use std::borrow::Cow;
 use std::collections::HashMap;
 
 use super::*;
@@ -83,6 +84,39 @@ fn varbit() {
     assert_eq!(out.bytes(), bits);
 }
 
+#[test]
+fn numeric() {
+    let mut buf = BytesMut::new();
+    numeric_to_sql("-12345.678", &mut buf).unwrap();
+    assert_eq!(
+        buf,
+        &[0, 3, 0, 1, 0x40, 0, 0, 3, 0, 1, 0x09, 0x29, 0x1a, 0x7c][..]
+    );
+
+    for (value, formatted) in [
+        ("0", "0"),
+        ("0.000", "0.000"),
+        ("-12345.678", "-12345.678"),
+        ("+1000000", "1000000"),
+        ("0.00012", "0.00012"),
+        ("1.5e3", "1500"),
+        ("12E-6", "0.000012"),
+        ("10000.0001", "10000.0001"),
+        (".5", "0.5"),
+        ("NaN", "NaN"),
+        ("Infinity", "Infinity"),
+        ("-inf", "-Infinity"),
+    ] {
+        let mut buf = BytesMut::new();
+        numeric_to_sql(value, &mut buf).unwrap();
+        assert_eq!(numeric_from_sql(&buf).unwrap(), formatted);
+    }
+
+    for value in ["", ".", "1.2.3", "abc", "1e", "--1"] {
+        assert!(numeric_to_sql(value, &mut BytesMut::new()).is_err());
+    }
+}
+
 #[test]
 fn array() {
     let dimensions = [
@@ -157,6 +191,95 @@ fn non_null_array() {
     assert_eq!(array.values().collect::<Vec<_>>().unwrap(), values);
 }
 
+#[test]
+fn text_array() {
+    let array = array_from_text(br#"{1,NULL,"two, \"2\"",  three\ 3  ,"NULL"}"#, b',').unwrap();
+    assert!(array.has_nulls());
+    assert_eq!(
+        array.dimensions(),
+        [ArrayDimension {
+            len: 5,
+            lower_bound: 1,
+        }]
+    );
+    assert_eq!(
+        array.values(),
+        [
+            Some(Cow::Borrowed(&b"1"[..])),
+            None,
+            Some(Cow::Borrowed(&b"two, \"2\""[..])),
+            Some(Cow::Borrowed(&b"three 3"[..])),
+            Some(Cow::Borrowed(&b"NULL"[..])),
+        ]
+    );
+
+    let array = array_from_text(b"{}", b',').unwrap();
+    assert_eq!(array.dimensions(), []);
+    assert_eq!(array.values(), []);
+
+    let array = array_from_text(b"{(0,0),(1,1);(2,2),(3,3)}", b';').unwrap();
+    assert_eq!(
+        array.into_values(),
+        [
+            Some(Cow::Borrowed(&b"(0,0),(1,1)"[..])),
+            Some(Cow::Borrowed(&b"(2,2),(3,3)"[..])),
+        ]
+    );
+}
+
+#[test]
+fn multidimensional_text_array() {
+    let array = array_from_text(br#"[0:1][1:2]={{1,"(a,b)"},{NULL,4}}"#, b',').unwrap();
+    assert_eq!(
+        array.dimensions(),
+        [
+            ArrayDimension {
+                len: 2,
+                lower_bound: 0,
+            },
+            ArrayDimension {
+                len: 2,
+                lower_bound: 1,
+            },
+        ]
+    );
+    assert_eq!(
+        array.values(),
+        [
+            Some(Cow::Borrowed(&b"1"[..])),
+            Some(Cow::Borrowed(&b"(a,b)"[..])),
+            None,
+            Some(Cow::Borrowed(&b"4"[..])),
+        ]
+    );
+
+    assert!(array_from_text(b"{{1,2},{3}}", b',').is_err());
+    assert!(array_from_text(b"{{1,2},3}", b',').is_err());
+    assert!(array_from_text(b"{1,{2}}", b',').is_err());
+    assert!(array_from_text(b"[1:3]={1,2}", b',').is_err());
+    assert!(array_from_text(b"{1,2", b',').is_err());
+}
+
+#[test]
+fn text_record() {
+    let fields = record_from_text(br#"(1,,"",a "b"" c",\"d\\,"e f")"#).unwrap();
+    assert_eq!(
+        fields,
+        [
+            Some(Cow::Borrowed(&b"1"[..])),
+            None,
+            Some(Cow::Borrowed(&b""[..])),
+            Some(Cow::Borrowed(&b"a b\" c"[..])),
+            Some(Cow::Borrowed(&b"\"d\\"[..])),
+            Some(Cow::Borrowed(&b"e f"[..])),
+        ]
+    );
+
+    assert_eq!(record_from_text(b"()").unwrap(), [None]);
+    assert!(record_from_text(br#"(1,"2)"#).is_err());
+    assert!(record_from_text(b"(1,2)3").is_err());
+}
+
 #[test]
 fn ltree_sql() {
     let mut query = vec![1u8];
@@ -240,3 +363,63 @@ fn ltxtquery_wrong_version() {
 
     assert!(ltree_from_sql(query.as_slice()).is_err())
 }
+
+#[test]
+fn jsonpath() {
+    let mut buf = BytesMut::new();
+    jsonpath_to_sql("$.a[*] ? (@ > 1)", &mut buf);
+    assert_eq!(buf[0], 1);
+    assert_eq!(jsonpath_from_sql(&buf).unwrap(), "$.a[*] ? (@ > 1)");
+
+    assert!(jsonpath_from_sql(b"\x02$.a").is_err());
+}
+
+#[test]
+fn tsvector() {
+    let lexemes = [("cat", vec![1, 0xC000 | 3]), ("fat", vec![])];
+
+    let mut buf = BytesMut::new();
+    tsvector_to_sql(
+        lexemes.iter().map(|(l, p)| (*l, p.iter().copied())),
+        &mut buf,
+    )
+    .unwrap();
+
+    let decoded = tsvector_from_sql(&buf)
+        .unwrap()
+        .map(|(l, p)| Ok((l, p.collect::<Vec<_>>())))
+        .collect::<Vec<_>>()
+        .unwrap();
+    assert_eq!(decoded, lexemes);
+}
+
+#[test]
+fn tsquery() {
+    // 'fat':AB & !'rat' <2> 'cat':*
+    let items = [
+        TsQueryItem::And,
+        TsQueryItem::Phrase(2),
+        TsQueryItem::Operand {
+            value: "cat",
+            weights: 0,
+            prefix: true,
+        },
+        TsQueryItem::Not,
+        TsQueryItem::Operand {
+            value: "rat",
+            weights: 0,
+            prefix: false,
+        },
+        TsQueryItem::Operand {
+            value: "fat",
+            weights: 0b1100,
+            prefix: false,
+        },
+    ];
+
+    let mut buf = BytesMut::new();
+    tsquery_to_sql(items.iter().copied(), &mut buf).unwrap();
+
+    let decoded = tsquery_from_sql(&buf).unwrap().collect::<Vec<_>>().unwrap();
+    assert_eq!(decoded, items);
+}
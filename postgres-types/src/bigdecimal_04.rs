@@ -0,0 +1,50 @@
+use bigdecimal_04::BigDecimal;
+use bytes::BytesMut;
+use postgres_protocol::types;
+use std::error::Error;
+use std::fmt::Write;
+
+use crate::{text, FromSql, IsNull, TextSettings, ToSql, ToText, Type};
+
+fn big_decimal_from_str(s: &str) -> Result<BigDecimal, Box<dyn Error + Sync + Send>> {
+    match s {
+        "NaN" | "Infinity" | "-Infinity" => {
+            Err(format!("BigDecimal cannot represent the numeric value {}", s).into())
+        }
+        s => Ok(s.parse()?),
+    }
+}
+
+impl<'a> FromSql<'a> for BigDecimal {
+    fn from_sql(_: &Type, raw: &[u8]) -> Result<BigDecimal, Box<dyn Error + Sync + Send>> {
+        big_decimal_from_str(&types::numeric_from_sql(raw)?)
+    }
+
+    fn from_sql_text(
+        _: &Type,
+        raw: &[u8],
+        _: &TextSettings,
+    ) -> Result<BigDecimal, Box<dyn Error + Sync + Send>> {
+        big_decimal_from_str(text::str(raw)?)
+    }
+
+    accepts!(NUMERIC);
+}
+
+impl ToSql for BigDecimal {
+    fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        // Large and small values are displayed with an exponent, which is accepted as well.
+        types::numeric_to_sql(&self.to_string(), w)?;
+        Ok(IsNull::No)
+    }
+
+    accepts!(NUMERIC);
+    to_sql_checked!();
+}
+
+impl ToText for BigDecimal {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        write!(out, "{}", self)?;
+        Ok(IsNull::No)
+    }
+}
@@ -3,7 +3,7 @@ use bytes::BytesMut;
 use postgres_protocol::types;
 use std::error::Error;
 
-use crate::{FromSql, IsNull, ToSql, Type};
+use crate::{FromSql, IsNull, TextSettings, ToSql, ToText, Type};
 
 impl<'a> FromSql<'a> for BitVec {
     fn from_sql(_: &Type, raw: &[u8]) -> Result<BitVec, Box<dyn Error + Sync + Send>> {
@@ -16,6 +16,20 @@ impl<'a> FromSql<'a> for BitVec {
         Ok(bitvec)
     }
 
+    fn from_sql_text(
+        _: &Type,
+        raw: &[u8],
+        _: &TextSettings,
+    ) -> Result<BitVec, Box<dyn Error + Sync + Send>> {
+        raw.iter()
+            .map(|bit| match bit {
+                b'0' => Ok(false),
+                b'1' => Ok(true),
+                _ => Err("invalid bit string".into()),
+            })
+            .collect()
+    }
+
     accepts!(BIT, VARBIT);
 }
 
@@ -28,3 +42,10 @@ impl ToSql for BitVec {
     accepts!(BIT, VARBIT);
     to_sql_checked!();
 }
+
+impl ToText for BitVec {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        out.extend(self.iter().map(|bit| if bit { b'1' } else { b'0' }));
+        Ok(IsNull::No)
+    }
+}
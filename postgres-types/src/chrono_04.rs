@@ -5,7 +5,7 @@ use chrono_04::{
 use postgres_protocol::types;
 use std::error::Error;
 
-use crate::{FromSql, IsNull, ToSql, Type};
+use crate::{text, FromSql, IsNull, TextSettings, ToSql, ToText, Type};
 
 fn base() -> NaiveDateTime {
     NaiveDate::from_ymd_opt(2000, 1, 1)
@@ -22,16 +22,23 @@ impl<'a> FromSql<'a> for NaiveDateTime {
             .ok_or_else(|| "value too large to decode".into())
     }
 
+    fn from_sql_text(
+        _: &Type,
+        raw: &[u8],
+        settings: &TextSettings,
+    ) -> Result<NaiveDateTime, Box<dyn Error + Sync + Send>> {
+        let t = text::timestamp(raw, settings)?;
+        base()
+            .checked_add_signed(Duration::microseconds(t))
+            .ok_or_else(|| "value too large to decode".into())
+    }
+
     accepts!(TIMESTAMP);
 }
 
 impl ToSql for NaiveDateTime {
     fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
-        let time = match self.signed_duration_since(base()).num_microseconds() {
-            Some(time) => time,
-            None => return Err("value too large to transmit".into()),
-        };
-        types::timestamp_to_sql(time, w);
+        types::timestamp_to_sql(timestamp_usecs(self)?, w);
         Ok(IsNull::No)
     }
 
@@ -39,12 +46,34 @@ impl ToSql for NaiveDateTime {
     to_sql_checked!();
 }
 
+impl ToText for NaiveDateTime {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        text::write_timestamp(timestamp_usecs(self)?, false, out);
+        Ok(IsNull::No)
+    }
+}
+
+fn timestamp_usecs(t: &NaiveDateTime) -> Result<i64, Box<dyn Error + Sync + Send>> {
+    t.signed_duration_since(base())
+        .num_microseconds()
+        .ok_or_else(|| "value too large to transmit".into())
+}
+
 impl<'a> FromSql<'a> for DateTime<Utc> {
     fn from_sql(type_: &Type, raw: &[u8]) -> Result<DateTime<Utc>, Box<dyn Error + Sync + Send>> {
         let naive = NaiveDateTime::from_sql(type_, raw)?;
         Ok(Utc.from_utc_datetime(&naive))
     }
 
+    fn from_sql_text(
+        type_: &Type,
+        raw: &[u8],
+        settings: &TextSettings,
+    ) -> Result<DateTime<Utc>, Box<dyn Error + Sync + Send>> {
+        let naive = NaiveDateTime::from_sql_text(type_, raw, settings)?;
+        Ok(Utc.from_utc_datetime(&naive))
+    }
+
     accepts!(TIMESTAMPTZ);
 }
 
@@ -61,12 +90,28 @@ impl ToSql for DateTime<Utc> {
     to_sql_checked!();
 }
 
+impl<Tz: TimeZone> ToText for DateTime<Tz> {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        text::write_timestamp(timestamp_usecs(&self.naive_utc())?, true, out);
+        Ok(IsNull::No)
+    }
+}
+
 impl<'a> FromSql<'a> for DateTime<Local> {
     fn from_sql(type_: &Type, raw: &[u8]) -> Result<DateTime<Local>, Box<dyn Error + Sync + Send>> {
         let utc = DateTime::<Utc>::from_sql(type_, raw)?;
         Ok(utc.with_timezone(&Local))
     }
 
+    fn from_sql_text(
+        type_: &Type,
+        raw: &[u8],
+        settings: &TextSettings,
+    ) -> Result<DateTime<Local>, Box<dyn Error + Sync + Send>> {
+        let utc = DateTime::<Utc>::from_sql_text(type_, raw, settings)?;
+        Ok(utc.with_timezone(&Local))
+    }
+
     accepts!(TIMESTAMPTZ);
 }
 
@@ -92,6 +137,15 @@ impl<'a> FromSql<'a> for DateTime<FixedOffset> {
         Ok(utc.with_timezone(&FixedOffset::east_opt(0).unwrap()))
     }
 
+    fn from_sql_text(
+        type_: &Type,
+        raw: &[u8],
+        settings: &TextSettings,
+    ) -> Result<DateTime<FixedOffset>, Box<dyn Error + Sync + Send>> {
+        let utc = DateTime::<Utc>::from_sql_text(type_, raw, settings)?;
+        Ok(utc.with_timezone(&FixedOffset::east_opt(0).unwrap()))
+    }
+
     accepts!(TIMESTAMPTZ);
 }
 
@@ -117,17 +171,24 @@ impl<'a> FromSql<'a> for NaiveDate {
             .ok_or_else(|| "value too large to decode".into())
     }
 
+    fn from_sql_text(
+        _: &Type,
+        raw: &[u8],
+        settings: &TextSettings,
+    ) -> Result<NaiveDate, Box<dyn Error + Sync + Send>> {
+        let jd = text::date(raw, settings)?;
+        base()
+            .date()
+            .checked_add_signed(Duration::days(i64::from(jd)))
+            .ok_or_else(|| "value too large to decode".into())
+    }
+
     accepts!(DATE);
 }
 
 impl ToSql for NaiveDate {
     fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
-        let jd = self.signed_duration_since(base().date()).num_days();
-        if jd > i64::from(i32::max_value()) || jd < i64::from(i32::min_value()) {
-            return Err("value too large to transmit".into());
-        }
-
-        types::date_to_sql(jd as i32, w);
+        types::date_to_sql(date_days(self)?, w);
         Ok(IsNull::No)
     }
 
@@ -135,26 +196,58 @@ impl ToSql for NaiveDate {
     to_sql_checked!();
 }
 
+impl ToText for NaiveDate {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        text::write_date(date_days(self)?, out);
+        Ok(IsNull::No)
+    }
+}
+
+fn date_days(d: &NaiveDate) -> Result<i32, Box<dyn Error + Sync + Send>> {
+    let jd = d.signed_duration_since(base().date()).num_days();
+    if jd > i64::from(i32::max_value()) || jd < i64::from(i32::min_value()) {
+        return Err("value too large to transmit".into());
+    }
+    Ok(jd as i32)
+}
+
 impl<'a> FromSql<'a> for NaiveTime {
     fn from_sql(_: &Type, raw: &[u8]) -> Result<NaiveTime, Box<dyn Error + Sync + Send>> {
         let usec = types::time_from_sql(raw)?;
         Ok(NaiveTime::from_hms_opt(0, 0, 0).unwrap() + Duration::microseconds(usec))
     }
 
+    fn from_sql_text(
+        _: &Type,
+        raw: &[u8],
+        _: &TextSettings,
+    ) -> Result<NaiveTime, Box<dyn Error + Sync + Send>> {
+        let usec = text::time(raw)?;
+        Ok(NaiveTime::from_hms_opt(0, 0, 0).unwrap() + Duration::microseconds(usec))
+    }
+
     accepts!(TIME);
 }
 
 impl ToSql for NaiveTime {
     fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
-        let delta = self.signed_duration_since(NaiveTime::from_hms_opt(0, 0, 0).unwrap());
-        let time = match delta.num_microseconds() {
-            Some(time) => time,
-            None => return Err("value too large to transmit".into()),
-        };
-        types::time_to_sql(time, w);
+        types::time_to_sql(time_usecs(self)?, w);
         Ok(IsNull::No)
     }
 
     accepts!(TIME);
     to_sql_checked!();
 }
+
+impl ToText for NaiveTime {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        text::write_time(time_usecs(self)?, out);
+        Ok(IsNull::No)
+    }
+}
+
+fn time_usecs(t: &NaiveTime) -> Result<i64, Box<dyn Error + Sync + Send>> {
+    t.signed_duration_since(NaiveTime::from_hms_opt(0, 0, 0).unwrap())
+        .num_microseconds()
+        .ok_or_else(|| "value too large to transmit".into())
+}
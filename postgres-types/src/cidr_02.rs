@@ -2,8 +2,9 @@ use bytes::BytesMut;
 use cidr_02::{IpCidr, IpInet};
 use postgres_protocol::types;
 use std::error::Error;
+use std::fmt::Write;
 
-use crate::{FromSql, IsNull, ToSql, Type};
+use crate::{text, FromSql, IsNull, TextSettings, ToSql, ToText, Type};
 
 impl<'a> FromSql<'a> for IpCidr {
     fn from_sql(_: &Type, raw: &[u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
@@ -11,6 +12,14 @@ impl<'a> FromSql<'a> for IpCidr {
         Ok(IpCidr::new(inet.addr(), inet.netmask())?)
     }
 
+    fn from_sql_text(
+        _: &Type,
+        raw: &[u8],
+        _: &TextSettings,
+    ) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        text::parse(raw)
+    }
+
     accepts!(CIDR);
 }
 
@@ -24,12 +33,27 @@ impl ToSql for IpCidr {
     to_sql_checked!();
 }
 
+impl ToText for IpCidr {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        write!(out, "{}/{}", self.first_address(), self.network_length())?;
+        Ok(IsNull::No)
+    }
+}
+
 impl<'a> FromSql<'a> for IpInet {
     fn from_sql(_: &Type, raw: &[u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
         let inet = types::inet_from_sql(raw)?;
         Ok(IpInet::new(inet.addr(), inet.netmask())?)
     }
 
+    fn from_sql_text(
+        _: &Type,
+        raw: &[u8],
+        _: &TextSettings,
+    ) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        text::parse(raw)
+    }
+
     accepts!(INET);
 }
 
@@ -42,3 +66,10 @@ impl ToSql for IpInet {
     accepts!(INET);
     to_sql_checked!();
 }
+
+impl ToText for IpInet {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        write!(out, "{}/{}", self.address(), self.network_length())?;
+        Ok(IsNull::No)
+    }
+}
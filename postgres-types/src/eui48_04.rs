@@ -1,9 +1,9 @@
-use bytes::BytesMut;
+use bytes::{BufMut, BytesMut};
 use eui48_04::MacAddress;
 use postgres_protocol::types;
 use std::error::Error;
 
-use crate::{FromSql, IsNull, ToSql, Type};
+use crate::{text, FromSql, IsNull, TextSettings, ToSql, ToText, Type};
 
 impl<'a> FromSql<'a> for MacAddress {
     fn from_sql(_: &Type, raw: &[u8]) -> Result<MacAddress, Box<dyn Error + Sync + Send>> {
@@ -11,6 +11,14 @@ impl<'a> FromSql<'a> for MacAddress {
         Ok(MacAddress::new(bytes))
     }
 
+    fn from_sql_text(
+        _: &Type,
+        raw: &[u8],
+        _: &TextSettings,
+    ) -> Result<MacAddress, Box<dyn Error + Sync + Send>> {
+        Ok(MacAddress::parse_str(text::str(raw)?)?)
+    }
+
     accepts!(MACADDR);
 }
 
@@ -25,3 +33,10 @@ impl ToSql for MacAddress {
     accepts!(MACADDR);
     to_sql_checked!();
 }
+
+impl ToText for MacAddress {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        out.put_slice(self.to_hex_string().as_bytes());
+        Ok(IsNull::No)
+    }
+}
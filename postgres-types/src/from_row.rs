@@ -0,0 +1,55 @@
+use std::error::Error;
+
+use crate::FromSqlOwned;
+
+/// A row whose values can be looked up by the names of their columns.
+///
+/// This is how [`FromRow`] implementations read the rows they are created from. It is implemented by the `Row` type of
+/// `tokio-postgres` and `postgres`.
+pub trait NamedRow {
+    /// Deserializes the value of the column with the provided name.
+    ///
+    /// An error is returned if the row has no such column, or if its value can't be converted into `T`.
+    fn get_named<T>(&self, name: &str) -> Result<T, Box<dyn Error + Sync + Send>>
+    where
+        T: FromSqlOwned;
+}
+
+/// A trait for types that can be created from a row returned by a query.
+///
+/// # Derive
+///
+/// If the `derive` cargo feature is enabled, `FromRow` can be derived for structs with named fields. Each field is
+/// read from the column of the same name, which can be changed with the `#[postgres(name = "...")]` and
+/// `#[postgres(rename_all = "...")]` attributes as for composite types. Columns which can be `NULL` are read into
+/// `Option` fields. A field marked `#[postgres(flatten)]` is itself created from the row by its `FromRow`
+/// implementation, so that the columns of a row can be split among several structs.
+///
+/// ```rust
+/// # #[cfg(feature = "derive")]
+/// use postgres_types::FromRow;
+///
+/// # #[cfg(feature = "derive")]
+/// #[derive(Debug, FromRow)]
+/// struct Audit {
+///     created_by: String,
+///     updated_by: Option<String>,
+/// }
+///
+/// # #[cfg(feature = "derive")]
+/// #[derive(Debug, FromRow)]
+/// #[postgres(rename_all = "camelCase")]
+/// struct Document {
+///     document_id: i32,
+///     #[postgres(name = "name")]
+///     title: String,
+///     #[postgres(flatten)]
+///     audit: Audit,
+/// }
+/// ```
+pub trait FromRow: Sized {
+    /// Creates a new value of this type from a row.
+    fn from_row<R>(row: &R) -> Result<Self, Box<dyn Error + Sync + Send>>
+    where
+        R: NamedRow + ?Sized;
+}
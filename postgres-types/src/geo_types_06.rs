@@ -1,10 +1,11 @@
-use bytes::BytesMut;
+use bytes::{BufMut, BytesMut};
 use fallible_iterator::FallibleIterator;
 use geo_types_06::{Coordinate, LineString, Point, Rect};
 use postgres_protocol::types;
 use std::error::Error;
+use std::fmt::Write;
 
-use crate::{FromSql, IsNull, ToSql, Type};
+use crate::{text, FromSql, IsNull, TextSettings, ToSql, ToText, Type};
 
 impl<'a> FromSql<'a> for Point<f64> {
     fn from_sql(_: &Type, raw: &[u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
@@ -12,6 +13,17 @@ impl<'a> FromSql<'a> for Point<f64> {
         Ok(Point::new(point.x(), point.y()))
     }
 
+    fn from_sql_text(
+        _: &Type,
+        raw: &[u8],
+        _: &TextSettings,
+    ) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        match *text::points(raw)? {
+            [(x, y)] => Ok(Point::new(x, y)),
+            _ => Err("invalid point".into()),
+        }
+    }
+
     accepts!(POINT);
 }
 
@@ -25,6 +37,13 @@ impl ToSql for Point<f64> {
     to_sql_checked!();
 }
 
+impl ToText for Point<f64> {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        write!(out, "({},{})", self.x(), self.y())?;
+        Ok(IsNull::No)
+    }
+}
+
 impl<'a> FromSql<'a> for Rect<f64> {
     fn from_sql(_: &Type, raw: &[u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
         let rect = types::box_from_sql(raw)?;
@@ -34,6 +53,17 @@ impl<'a> FromSql<'a> for Rect<f64> {
         ))
     }
 
+    fn from_sql_text(
+        _: &Type,
+        raw: &[u8],
+        _: &TextSettings,
+    ) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        match *text::points(raw)? {
+            [upper_right, lower_left] => Ok(Rect::new(lower_left, upper_right)),
+            _ => Err("invalid box".into()),
+        }
+    }
+
     accepts!(BOX);
 }
 
@@ -47,6 +77,20 @@ impl ToSql for Rect<f64> {
     to_sql_checked!();
 }
 
+impl ToText for Rect<f64> {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        write!(
+            out,
+            "(({},{}),({},{}))",
+            self.min().x,
+            self.min().y,
+            self.max().x,
+            self.max().y
+        )?;
+        Ok(IsNull::No)
+    }
+}
+
 impl<'a> FromSql<'a> for LineString<f64> {
     fn from_sql(_: &Type, raw: &[u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
         let path = types::path_from_sql(raw)?;
@@ -57,6 +101,18 @@ impl<'a> FromSql<'a> for LineString<f64> {
         Ok(LineString(points))
     }
 
+    fn from_sql_text(
+        _: &Type,
+        raw: &[u8],
+        _: &TextSettings,
+    ) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let points = text::points(raw)?
+            .into_iter()
+            .map(|(x, y)| Coordinate { x, y })
+            .collect();
+        Ok(LineString(points))
+    }
+
     accepts!(PATH);
 }
 
@@ -70,3 +126,18 @@ impl ToSql for LineString<f64> {
     accepts!(PATH);
     to_sql_checked!();
 }
+
+impl ToText for LineString<f64> {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        // Brackets make an open path, as in the binary format.
+        out.put_u8(b'[');
+        for (i, p) in self.0.iter().enumerate() {
+            if i > 0 {
+                out.put_u8(b',');
+            }
+            write!(out, "({},{})", p.x, p.y)?;
+        }
+        out.put_u8(b']');
+        Ok(IsNull::No)
+    }
+}
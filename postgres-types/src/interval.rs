@@ -0,0 +1,89 @@
+//! The Postgres `INTERVAL` type.
+
+use bytes::{Buf, BufMut, BytesMut};
+use std::error::Error;
+
+use crate::{text, FromSql, IsNull, TextSettings, ToSql, ToText, Type};
+
+/// Postgres `INTERVAL` type.
+///
+/// Like in Postgres, an interval is made of a number of months, days and microseconds, which are kept apart since the
+/// length of months and days depends on the date they are added to, and across daylight saving time transitions. An
+/// interval of `1 mon` is thus different from one of `30 days`, even though Postgres compares them as equal.
+///
+/// In the text format, intervals are parsed according to the `IntervalStyle` of the session.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Interval {
+    months: i32,
+    days: i32,
+    microseconds: i64,
+}
+
+impl Interval {
+    /// Creates an interval from its number of months, days and microseconds.
+    pub fn new(months: i32, days: i32, microseconds: i64) -> Interval {
+        Interval {
+            months,
+            days,
+            microseconds,
+        }
+    }
+
+    /// Returns the number of months of the interval, including its years.
+    pub fn months(&self) -> i32 {
+        self.months
+    }
+
+    /// Returns the number of days of the interval.
+    pub fn days(&self) -> i32 {
+        self.days
+    }
+
+    /// Returns the number of microseconds of the interval, including its hours, minutes and seconds.
+    pub fn microseconds(&self) -> i64 {
+        self.microseconds
+    }
+}
+
+impl<'a> FromSql<'a> for Interval {
+    fn from_sql(_: &Type, mut raw: &'a [u8]) -> Result<Interval, Box<dyn Error + Sync + Send>> {
+        if raw.len() != 16 {
+            return Err("invalid message length: interval size mismatch".into());
+        }
+        let microseconds = raw.get_i64();
+        let days = raw.get_i32();
+        let months = raw.get_i32();
+        Ok(Interval::new(months, days, microseconds))
+    }
+
+    fn from_sql_text(
+        _: &Type,
+        raw: &'a [u8],
+        settings: &TextSettings,
+    ) -> Result<Interval, Box<dyn Error + Sync + Send>> {
+        let (months, days, microseconds) = text::interval(raw, settings)?;
+        Ok(Interval::new(months, days, microseconds))
+    }
+
+    accepts!(INTERVAL);
+}
+
+impl ToSql for Interval {
+    fn to_sql(&self, _: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        out.put_i64(self.microseconds);
+        out.put_i32(self.days);
+        out.put_i32(self.months);
+        Ok(IsNull::No)
+    }
+
+    accepts!(INTERVAL);
+
+    to_sql_checked!();
+}
+
+impl ToText for Interval {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        text::write_interval(self.months, self.days, self.microseconds, out);
+        Ok(IsNull::No)
+    }
+}
@@ -95,6 +95,41 @@
 //! }
 //! ```
 //!
+//! The structs may also be generic, and borrow their fields from the value they are read from:
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")]
+//! use postgres_types::{ToSql, FromSql};
+//!
+//! # #[cfg(feature = "derive")]
+//! #[derive(Debug, ToSql, FromSql)]
+//! #[postgres(name = "InventoryItem")]
+//! struct BorrowedInventoryItem<'a, T> {
+//!     name: &'a str,
+//!     supplier_id: T,
+//!     price: Option<f64>,
+//! }
+//! ```
+//!
+//! The `#[postgres(default)]` attribute allows a field to be missing from the Postgres type, in which case it is read
+//! as its `Default` value and isn't written. This lets fields be added to the Rust type before they are added to the
+//! Postgres one:
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")]
+//! use postgres_types::{ToSql, FromSql};
+//!
+//! # #[cfg(feature = "derive")]
+//! #[derive(Debug, ToSql, FromSql)]
+//! struct InventoryItem {
+//!     name: String,
+//!     supplier_id: i32,
+//!     price: Option<f64>,
+//!     #[postgres(default)]
+//!     discount: Option<f64>,
+//! }
+//! ```
+//!
 //! ## Naming
 //!
 //! The derived implementations will enforce exact matches of type, field, and variant names between the Rust and
@@ -181,6 +216,41 @@
 //!    Meh,
 //! }
 //! ```
+//!
+//! ## Enums Without a Postgres Enum Type
+//!
+//! Enums can also be stored in columns of other types, as is common in schemas avoiding Postgres enums. With
+//! `#[postgres(repr = "text")]`, variants are stored as their names in `TEXT`, `VARCHAR` and other text columns, and
+//! are renamed as with Postgres enums. With `#[postgres(repr = "integer")]`, variants are stored as their
+//! discriminants in `SMALLINT`, `INT` or `BIGINT` columns:
+//!
+//! ```sql
+//! CREATE TABLE task (
+//!     status TEXT CHECK (status IN ('in-progress', 'done')),
+//!     priority SMALLINT CHECK (priority IN (1, 10))
+//! );
+//! ```
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")]
+//! use postgres_types::{ToSql, FromSql};
+//!
+//! # #[cfg(feature = "derive")]
+//! #[derive(Debug, ToSql, FromSql)]
+//! #[postgres(repr = "text", rename_all = "kebab-case")]
+//! enum Status {
+//!     InProgress,
+//!     Done,
+//! }
+//!
+//! # #[cfg(feature = "derive")]
+//! #[derive(Debug, ToSql, FromSql)]
+//! #[postgres(repr = "integer")]
+//! enum Priority {
+//!     Low = 1,
+//!     High = 10,
+//! }
+//! ```
 #![warn(clippy::all, rust_2018_idioms, missing_docs)]
 use fallible_iterator::FallibleIterator;
 use postgres_protocol::types::{self, ArrayDimension};
@@ -195,7 +265,7 @@ use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[cfg(feature = "derive")]
-pub use postgres_derive::{FromSql, ToSql};
+pub use postgres_derive::{FromRow, FromSql, ToSql};
 
 #[cfg(feature = "with-serde_json-1")]
 pub use crate::serde_json_1::Json;
@@ -205,9 +275,15 @@ use crate::type_gen::{Inner, Other};
 pub use postgres_protocol::Oid;
 
 #[doc(inline)]
-pub use pg_lsn::PgLsn;
+pub use pg_lsn::{ParseLsnError, PgLsn};
 
+pub use crate::from_row::{FromRow, NamedRow};
+pub use crate::interval::Interval;
+pub use crate::params::{ParamsBuilder, ParamsIter};
 pub use crate::special::{Date, Timestamp};
+pub use crate::stream::{Chunks, Streamed, ToSqlStream};
+pub use crate::text_search::{TsLexeme, TsPosition, TsQuery, TsVector, TsWeight};
+pub use crate::to_text::{BinaryValue, ToText};
 use bytes::BytesMut;
 
 // Number of seconds from 1970-01-01 to 2000-01-01
@@ -262,6 +338,8 @@ where
     v.to_sql(ty, out)
 }
 
+#[cfg(feature = "with-bigdecimal-0_4")]
+mod bigdecimal_04;
 #[cfg(feature = "with-bit-vec-0_6")]
 mod bit_vec_06;
 #[cfg(feature = "with-chrono-0_4")]
@@ -276,6 +354,8 @@ mod eui48_1;
 mod geo_types_06;
 #[cfg(feature = "with-geo-types-0_7")]
 mod geo_types_07;
+#[cfg(feature = "with-rust_decimal-1")]
+mod rust_decimal_1;
 #[cfg(feature = "with-serde_json-1")]
 mod serde_json_1;
 #[cfg(feature = "with-smol_str-01")]
@@ -293,10 +373,17 @@ mod uuid_1;
 #[cfg(feature = "with-time-0_2")]
 extern crate time_02 as time;
 
+mod from_row;
+mod interval;
+mod params;
 mod pg_lsn;
 #[doc(hidden)]
 pub mod private;
 mod special;
+mod stream;
+mod text;
+mod text_search;
+mod to_text;
 mod type_gen;
 
 /// A Postgres type.
@@ -482,12 +569,16 @@ impl fmt::Display for WrongFormat {
 /// | `i64`                             | BIGINT, BIGSERIAL                             |
 /// | `f32`                             | REAL                                          |
 /// | `f64`                             | DOUBLE PRECISION                              |
-/// | `&str`/`String`                   | VARCHAR, CHAR(n), TEXT, CITEXT, NAME, UNKNOWN |
-/// |                                   | LTREE, LQUERY, LTXTQUERY                      |
-/// | `&[u8]`/`Vec<u8>`                 | BYTEA                                         |
+/// | `&str`/`String`/`Cow<str>`        | VARCHAR, CHAR(n), TEXT, CITEXT, NAME, UNKNOWN |
+/// |                                   | LTREE, LQUERY, LTXTQUERY, JSONPATH            |
+/// | `&[u8]`/`Vec<u8>`/`Cow<[u8]>`     | BYTEA                                         |
 /// | `HashMap<String, Option<String>>` | HSTORE                                        |
 /// | `SystemTime`                      | TIMESTAMP, TIMESTAMP WITH TIME ZONE           |
 /// | `IpAddr`                          | INET                                          |
+/// | `Interval`                        | INTERVAL                                      |
+/// | `PgLsn`                           | PG_LSN                                        |
+/// | `TsVector`                        | TSVECTOR                                      |
+/// | `TsQuery`                         | TSQUERY                                       |
 ///
 /// In addition, some implementations are provided for types in third party
 /// crates. These are disabled by default; to opt into one of these
@@ -520,6 +611,13 @@ impl fmt::Display for WrongFormat {
 /// | `smol_str::SmolStr`             | VARCHAR, CHAR(n), TEXT, CITEXT,     |
 /// |                                 | NAME, UNKNOWN, LTREE, LQUERY,       |
 /// |                                 | LTXTQUERY                           |
+/// | `rust_decimal::Decimal`         | NUMERIC                             |
+/// | `bigdecimal::BigDecimal`        | NUMERIC                             |
+///
+/// Neither `Decimal` nor `BigDecimal` can represent the `NaN` and infinite
+/// values of `NUMERIC`, which are reported as errors when read, and `Decimal`
+/// returns an error for values which don't fit in its 96-bit mantissa rather
+/// than rounding them.
 ///
 /// # Nullability
 ///
@@ -534,6 +632,32 @@ impl fmt::Display for WrongFormat {
 ///
 /// **Note:** the impl for arrays only exist when the Cargo feature `array-impls`
 /// is enabled.
+///
+/// # Text format
+///
+/// Values are normally read in the binary format, but all of the implementations
+/// above, except the one for `&[u8]`, as well as those derived for enums,
+/// domains and composite types, can also read values in the text format
+/// through `from_sql_text`, according to the `TextSettings` of the session. In
+/// the text format, timestamps with time zones are only supported in UTC
+/// unless the `DateStyle` is `ISO`, since the other formats don't report the
+/// offset of the zone.
+///
+/// Array elements and fields of composite types containing escaped characters,
+/// such as strings with quotes or backslashes, can't be borrowed once
+/// unescaped, so they are read through `from_sql_text_owned`. It is
+/// implemented for the owned string types, `Vec<u8>`, `HashMap` and
+/// `serde_json::Value`, whose text format can contain such characters, as
+/// well as for arrays and the types derived with `FromSql`, so that arrays of
+/// composite types and composite types containing arrays can be read.
+///
+/// # Borrowing
+///
+/// `&str` and `&[u8]` borrow the value from the buffer it is read from, such
+/// as that of a row, rather than allocating a copy of it. `Cow<str>` and
+/// `Cow<[u8]>` also borrow it where they can, and only hold an owned copy of
+/// the values which have to be unescaped: `BYTEA` values in the text format,
+/// and escaped array elements and fields of composite types.
 pub trait FromSql<'a>: Sized {
     /// Creates a new value of this type from a buffer of data of the specified
     /// Postgres `Type` in its binary format.
@@ -565,6 +689,57 @@ pub trait FromSql<'a>: Sized {
         }
     }
 
+    /// Creates a new value of this type from a buffer of data of the specified
+    /// Postgres `Type` in its text format, as formatted by a session with the
+    /// specified settings.
+    ///
+    /// The caller of this method is responsible for ensuring that this type
+    /// is compatible with the Postgres `Type`.
+    ///
+    /// The default implementation returns an error, since types have to opt in
+    /// to the text format.
+    #[allow(unused_variables)]
+    fn from_sql_text(
+        ty: &Type,
+        raw: &'a [u8],
+        settings: &TextSettings,
+    ) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Err(format!("{} cannot be read in the text format", type_name::<Self>()).into())
+    }
+
+    /// Like `from_sql_text`, but for a value which doesn't live for `'a`.
+    ///
+    /// This is used for the elements of arrays and the fields of composite types which contain escaped characters,
+    /// since they can't be borrowed from the buffer of the row once unescaped.
+    ///
+    /// The default implementation returns an error. Types which don't borrow from the buffer can implement it by
+    /// delegating to `from_sql_text`.
+    #[allow(unused_variables)]
+    fn from_sql_text_owned(
+        ty: &Type,
+        raw: &[u8],
+        settings: &TextSettings,
+    ) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Err(format!(
+            "{} cannot be read from an escaped value in the text format",
+            type_name::<Self>()
+        )
+        .into())
+    }
+
+    /// A convenience function that delegates to `from_sql_text` and `from_sql_null` depending on
+    /// the value of `raw`.
+    fn from_sql_text_nullable(
+        ty: &Type,
+        raw: Option<&'a [u8]>,
+        settings: &TextSettings,
+    ) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        match raw {
+            Some(raw) => Self::from_sql_text(ty, raw, settings),
+            None => Self::from_sql_null(ty),
+        }
+    }
+
     /// Determines if a value of this type can be created from the specified
     /// Postgres `Type`.
     fn accepts(ty: &Type) -> bool;
@@ -586,6 +761,22 @@ impl<'a, T: FromSql<'a>> FromSql<'a> for Option<T> {
         Ok(None)
     }
 
+    fn from_sql_text(
+        ty: &Type,
+        raw: &'a [u8],
+        settings: &TextSettings,
+    ) -> Result<Option<T>, Box<dyn Error + Sync + Send>> {
+        <T as FromSql>::from_sql_text(ty, raw, settings).map(Some)
+    }
+
+    fn from_sql_text_owned(
+        ty: &Type,
+        raw: &[u8],
+        settings: &TextSettings,
+    ) -> Result<Option<T>, Box<dyn Error + Sync + Send>> {
+        <T as FromSql>::from_sql_text_owned(ty, raw, settings).map(Some)
+    }
+
     fn accepts(ty: &Type) -> bool {
         <T as FromSql>::accepts(ty)
     }
@@ -609,6 +800,38 @@ impl<'a, T: FromSql<'a>> FromSql<'a> for Vec<T> {
             .collect()
     }
 
+    fn from_sql_text(
+        ty: &Type,
+        raw: &'a [u8],
+        settings: &TextSettings,
+    ) -> Result<Vec<T>, Box<dyn Error + Sync + Send>> {
+        let member_type = match *ty.kind() {
+            Kind::Array(ref member) => member,
+            _ => panic!("expected array type"),
+        };
+
+        text_array(ty, member_type, raw)?
+            .into_iter()
+            .map(|v| private::read_text_value(member_type, v, settings))
+            .collect()
+    }
+
+    fn from_sql_text_owned(
+        ty: &Type,
+        raw: &[u8],
+        settings: &TextSettings,
+    ) -> Result<Vec<T>, Box<dyn Error + Sync + Send>> {
+        let member_type = match *ty.kind() {
+            Kind::Array(ref member) => member,
+            _ => panic!("expected array type"),
+        };
+
+        text_array(ty, member_type, raw)?
+            .iter()
+            .map(|v| private::read_text_value_owned(member_type, v.as_deref(), settings))
+            .collect()
+    }
+
     fn accepts(ty: &Type) -> bool {
         match *ty.kind() {
             Kind::Array(ref inner) => T::accepts(inner),
@@ -650,6 +873,58 @@ impl<'a, T: FromSql<'a>, const N: usize> FromSql<'a> for [T; N] {
         Ok(out)
     }
 
+    fn from_sql_text(
+        ty: &Type,
+        raw: &'a [u8],
+        settings: &TextSettings,
+    ) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let member_type = match *ty.kind() {
+            Kind::Array(ref member) => member,
+            _ => panic!("expected array type"),
+        };
+
+        let values = text_array(ty, member_type, raw)?;
+        if values.len() != N {
+            return Err(format!(
+                "wrong number of elements in array (expected {}, got {})",
+                N,
+                values.len(),
+            )
+            .into());
+        }
+
+        let mut values = values.into_iter();
+        array_init::try_array_init(|_| {
+            private::read_text_value(member_type, values.next().unwrap(), settings)
+        })
+    }
+
+    fn from_sql_text_owned(
+        ty: &Type,
+        raw: &[u8],
+        settings: &TextSettings,
+    ) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let member_type = match *ty.kind() {
+            Kind::Array(ref member) => member,
+            _ => panic!("expected array type"),
+        };
+
+        let values = text_array(ty, member_type, raw)?;
+        if values.len() != N {
+            return Err(format!(
+                "wrong number of elements in array (expected {}, got {})",
+                N,
+                values.len(),
+            )
+            .into());
+        }
+
+        let mut values = values.iter();
+        array_init::try_array_init(|_| {
+            private::read_text_value_owned(member_type, values.next().unwrap().as_deref(), settings)
+        })
+    }
+
     fn accepts(ty: &Type) -> bool {
         match *ty.kind() {
             Kind::Array(ref inner) => T::accepts(inner),
@@ -663,6 +938,22 @@ impl<'a, T: FromSql<'a>> FromSql<'a> for Box<[T]> {
         Vec::<T>::from_sql(ty, raw).map(Vec::into_boxed_slice)
     }
 
+    fn from_sql_text(
+        ty: &Type,
+        raw: &'a [u8],
+        settings: &TextSettings,
+    ) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Vec::<T>::from_sql_text(ty, raw, settings).map(Vec::into_boxed_slice)
+    }
+
+    fn from_sql_text_owned(
+        ty: &Type,
+        raw: &[u8],
+        settings: &TextSettings,
+    ) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Vec::<T>::from_sql_text_owned(ty, raw, settings).map(Vec::into_boxed_slice)
+    }
+
     fn accepts(ty: &Type) -> bool {
         Vec::<T>::accepts(ty)
     }
@@ -673,6 +964,22 @@ impl<'a> FromSql<'a> for Vec<u8> {
         Ok(types::bytea_from_sql(raw).to_owned())
     }
 
+    fn from_sql_text(
+        _: &Type,
+        raw: &'a [u8],
+        _: &TextSettings,
+    ) -> Result<Vec<u8>, Box<dyn Error + Sync + Send>> {
+        text::bytea(raw)
+    }
+
+    fn from_sql_text_owned(
+        _: &Type,
+        raw: &[u8],
+        _: &TextSettings,
+    ) -> Result<Vec<u8>, Box<dyn Error + Sync + Send>> {
+        text::bytea(raw)
+    }
+
     accepts!(BYTEA);
 }
 
@@ -684,11 +991,51 @@ impl<'a> FromSql<'a> for &'a [u8] {
     accepts!(BYTEA);
 }
 
+impl<'a> FromSql<'a> for Cow<'a, [u8]> {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Cow<'a, [u8]>, Box<dyn Error + Sync + Send>> {
+        <&[u8] as FromSql>::from_sql(ty, raw).map(Cow::Borrowed)
+    }
+
+    fn from_sql_text(
+        ty: &Type,
+        raw: &'a [u8],
+        settings: &TextSettings,
+    ) -> Result<Cow<'a, [u8]>, Box<dyn Error + Sync + Send>> {
+        Vec::<u8>::from_sql_text(ty, raw, settings).map(Cow::Owned)
+    }
+
+    fn from_sql_text_owned(
+        ty: &Type,
+        raw: &[u8],
+        settings: &TextSettings,
+    ) -> Result<Cow<'a, [u8]>, Box<dyn Error + Sync + Send>> {
+        Vec::<u8>::from_sql_text_owned(ty, raw, settings).map(Cow::Owned)
+    }
+
+    accepts!(BYTEA);
+}
+
 impl<'a> FromSql<'a> for String {
     fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<String, Box<dyn Error + Sync + Send>> {
         <&str as FromSql>::from_sql(ty, raw).map(ToString::to_string)
     }
 
+    fn from_sql_text(
+        ty: &Type,
+        raw: &'a [u8],
+        settings: &TextSettings,
+    ) -> Result<String, Box<dyn Error + Sync + Send>> {
+        <&str as FromSql>::from_sql_text(ty, raw, settings).map(ToString::to_string)
+    }
+
+    fn from_sql_text_owned(
+        ty: &Type,
+        raw: &[u8],
+        settings: &TextSettings,
+    ) -> Result<String, Box<dyn Error + Sync + Send>> {
+        <&str as FromSql>::from_sql_text(ty, raw, settings).map(ToString::to_string)
+    }
+
     fn accepts(ty: &Type) -> bool {
         <&str as FromSql>::accepts(ty)
     }
@@ -701,6 +1048,50 @@ impl<'a> FromSql<'a> for Box<str> {
             .map(String::into_boxed_str)
     }
 
+    fn from_sql_text(
+        ty: &Type,
+        raw: &'a [u8],
+        settings: &TextSettings,
+    ) -> Result<Box<str>, Box<dyn Error + Sync + Send>> {
+        <&str as FromSql>::from_sql_text(ty, raw, settings)
+            .map(ToString::to_string)
+            .map(String::into_boxed_str)
+    }
+
+    fn from_sql_text_owned(
+        ty: &Type,
+        raw: &[u8],
+        settings: &TextSettings,
+    ) -> Result<Box<str>, Box<dyn Error + Sync + Send>> {
+        String::from_sql_text_owned(ty, raw, settings).map(String::into_boxed_str)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        <&str as FromSql>::accepts(ty)
+    }
+}
+
+impl<'a> FromSql<'a> for Cow<'a, str> {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Cow<'a, str>, Box<dyn Error + Sync + Send>> {
+        <&str as FromSql>::from_sql(ty, raw).map(Cow::Borrowed)
+    }
+
+    fn from_sql_text(
+        ty: &Type,
+        raw: &'a [u8],
+        settings: &TextSettings,
+    ) -> Result<Cow<'a, str>, Box<dyn Error + Sync + Send>> {
+        <&str as FromSql>::from_sql_text(ty, raw, settings).map(Cow::Borrowed)
+    }
+
+    fn from_sql_text_owned(
+        ty: &Type,
+        raw: &[u8],
+        settings: &TextSettings,
+    ) -> Result<Cow<'a, str>, Box<dyn Error + Sync + Send>> {
+        String::from_sql_text_owned(ty, raw, settings).map(Cow::Owned)
+    }
+
     fn accepts(ty: &Type) -> bool {
         <&str as FromSql>::accepts(ty)
     }
@@ -712,13 +1103,28 @@ impl<'a> FromSql<'a> for &'a str {
             ref ty if ty.name() == "ltree" => types::ltree_from_sql(raw),
             ref ty if ty.name() == "lquery" => types::lquery_from_sql(raw),
             ref ty if ty.name() == "ltxtquery" => types::ltxtquery_from_sql(raw),
+            Type::JSONPATH => types::jsonpath_from_sql(raw),
             _ => types::text_from_sql(raw),
         }
     }
 
+    fn from_sql_text(
+        _: &Type,
+        raw: &'a [u8],
+        _: &TextSettings,
+    ) -> Result<&'a str, Box<dyn Error + Sync + Send>> {
+        // Unlike in the binary format, ltree and jsonpath values aren't prefixed by a version.
+        text::str(raw)
+    }
+
     fn accepts(ty: &Type) -> bool {
         match *ty {
-            Type::VARCHAR | Type::TEXT | Type::BPCHAR | Type::NAME | Type::UNKNOWN => true,
+            Type::VARCHAR
+            | Type::TEXT
+            | Type::BPCHAR
+            | Type::NAME
+            | Type::UNKNOWN
+            | Type::JSONPATH => true,
             ref ty
                 if (ty.name() == "citext"
                     || ty.name() == "ltree"
@@ -733,25 +1139,42 @@ impl<'a> FromSql<'a> for &'a str {
 }
 
 macro_rules! simple_from {
-    ($t:ty, $f:ident, $($expected:ident),+) => {
+    ($t:ty, $f:ident, $text:path, $($expected:ident),+) => {
         impl<'a> FromSql<'a> for $t {
             fn from_sql(_: &Type, raw: &'a [u8]) -> Result<$t, Box<dyn Error + Sync + Send>> {
                 types::$f(raw)
             }
 
+            fn from_sql_text(_: &Type, raw: &'a [u8], _: &TextSettings) -> Result<$t, Box<dyn Error + Sync + Send>> {
+                $text(raw)
+            }
+
             accepts!($($expected),+);
         }
     }
 }
 
-simple_from!(bool, bool_from_sql, BOOL);
-simple_from!(i8, char_from_sql, CHAR);
-simple_from!(i16, int2_from_sql, INT2);
-simple_from!(i32, int4_from_sql, INT4);
-simple_from!(u32, oid_from_sql, OID);
-simple_from!(i64, int8_from_sql, INT8);
-simple_from!(f32, float4_from_sql, FLOAT4);
-simple_from!(f64, float8_from_sql, FLOAT8);
+simple_from!(bool, bool_from_sql, text::bool, BOOL);
+simple_from!(i8, char_from_sql, text::char, CHAR);
+simple_from!(i16, int2_from_sql, text::parse, INT2);
+simple_from!(i32, int4_from_sql, text::parse, INT4);
+simple_from!(u32, oid_from_sql, text::parse, OID);
+simple_from!(i64, int8_from_sql, text::parse, INT8);
+simple_from!(f32, float4_from_sql, text::parse, FLOAT4);
+simple_from!(f64, float8_from_sql, text::parse, FLOAT8);
+
+// Splits an array in the text format, taking into account the delimiter of its elements and the format of vectors.
+fn text_array<'a>(
+    ty: &Type,
+    member_type: &Type,
+    raw: &'a [u8],
+) -> Result<text::Elements<'a>, Box<dyn Error + Sync + Send>> {
+    match *ty {
+        Type::INT2_VECTOR | Type::OID_VECTOR => Ok(text::vector(raw)),
+        _ if *member_type == Type::BOX => text::array(raw, b';'),
+        _ => text::array(raw, b','),
+    }
+}
 
 impl<'a, S> FromSql<'a> for HashMap<String, Option<String>, S>
 where
@@ -766,6 +1189,22 @@ where
             .collect()
     }
 
+    fn from_sql_text(
+        _: &Type,
+        raw: &'a [u8],
+        _: &TextSettings,
+    ) -> Result<HashMap<String, Option<String>, S>, Box<dyn Error + Sync + Send>> {
+        Ok(text::hstore(raw)?.into_iter().collect())
+    }
+
+    fn from_sql_text_owned(
+        _: &Type,
+        raw: &[u8],
+        _: &TextSettings,
+    ) -> Result<HashMap<String, Option<String>, S>, Box<dyn Error + Sync + Send>> {
+        Ok(text::hstore(raw)?.into_iter().collect())
+    }
+
     fn accepts(ty: &Type) -> bool {
         ty.name() == "hstore"
     }
@@ -773,26 +1212,35 @@ where
 
 impl<'a> FromSql<'a> for SystemTime {
     fn from_sql(_: &Type, raw: &'a [u8]) -> Result<SystemTime, Box<dyn Error + Sync + Send>> {
-        let time = types::timestamp_from_sql(raw)?;
-        let epoch = UNIX_EPOCH + Duration::from_secs(TIME_SEC_CONVERSION);
+        Ok(system_time(types::timestamp_from_sql(raw)?))
+    }
 
-        let negative = time < 0;
-        let time = time.unsigned_abs();
+    fn from_sql_text(
+        _: &Type,
+        raw: &'a [u8],
+        settings: &TextSettings,
+    ) -> Result<SystemTime, Box<dyn Error + Sync + Send>> {
+        Ok(system_time(text::timestamp(raw, settings)?))
+    }
 
-        let secs = time / USEC_PER_SEC;
-        let nsec = (time % USEC_PER_SEC) * NSEC_PER_USEC;
-        let offset = Duration::new(secs, nsec as u32);
+    accepts!(TIMESTAMP, TIMESTAMPTZ);
+}
 
-        let time = if negative {
-            epoch - offset
-        } else {
-            epoch + offset
-        };
+fn system_time(time: i64) -> SystemTime {
+    let epoch = UNIX_EPOCH + Duration::from_secs(TIME_SEC_CONVERSION);
 
-        Ok(time)
-    }
+    let negative = time < 0;
+    let time = time.unsigned_abs();
 
-    accepts!(TIMESTAMP, TIMESTAMPTZ);
+    let secs = time / USEC_PER_SEC;
+    let nsec = (time % USEC_PER_SEC) * NSEC_PER_USEC;
+    let offset = Duration::new(secs, nsec as u32);
+
+    if negative {
+        epoch - offset
+    } else {
+        epoch + offset
+    }
 }
 
 impl<'a> FromSql<'a> for IpAddr {
@@ -801,6 +1249,16 @@ impl<'a> FromSql<'a> for IpAddr {
         Ok(inet.addr())
     }
 
+    fn from_sql_text(
+        _: &Type,
+        raw: &'a [u8],
+        _: &TextSettings,
+    ) -> Result<IpAddr, Box<dyn Error + Sync + Send>> {
+        let inet = text::str(raw)?;
+        let addr = inet.split_once('/').map_or(inet, |(addr, _)| addr);
+        Ok(addr.parse()?)
+    }
+
     accepts!(INET);
 }
 
@@ -830,11 +1288,15 @@ pub enum IsNull {
 /// | `f32`                             | REAL                                 |
 /// | `f64`                             | DOUBLE PRECISION                     |
 /// | `&str`/`String`                   | VARCHAR, CHAR(n), TEXT, CITEXT, NAME |
-/// |                                   | LTREE, LQUERY, LTXTQUERY             |
+/// |                                   | LTREE, LQUERY, LTXTQUERY, JSONPATH   |
 /// | `&[u8]`/`Vec<u8>`/`[u8; N]`       | BYTEA                                |
 /// | `HashMap<String, Option<String>>` | HSTORE                               |
 /// | `SystemTime`                      | TIMESTAMP, TIMESTAMP WITH TIME ZONE  |
 /// | `IpAddr`                          | INET                                 |
+/// | `Interval`                        | INTERVAL                             |
+/// | `PgLsn`                           | PG_LSN                               |
+/// | `TsVector`                        | TSVECTOR                             |
+/// | `TsQuery`                         | TSQUERY                              |
 ///
 /// In addition, some implementations are provided for types in third party
 /// crates. These are disabled by default; to opt into one of these
@@ -911,6 +1373,18 @@ pub trait ToSql: fmt::Debug {
     fn encode_format(&self, _ty: &Type) -> Format {
         Format::Binary
     }
+
+    /// Returns the length in bytes and the chunks of the value in the binary format of the specified Postgres
+    /// `Type`, if it should be written in chunks rather than buffered by `to_sql`.
+    ///
+    /// The default implementation returns `None`. It is overridden by the [`Streamed`] wrapper, which also checks
+    /// that the type is compatible.
+    fn to_sql_stream(
+        &self,
+        _ty: &Type,
+    ) -> Option<Result<(usize, Chunks), Box<dyn Error + Sync + Send>>> {
+        None
+    }
 }
 
 /// Supported Postgres message format types
@@ -924,6 +1398,91 @@ pub enum Format {
     Binary,
 }
 
+/// The settings of a session which determine how the server formats values in the text format.
+///
+/// They are passed to `FromSql::from_sql_text`. The `bytea_output` setting isn't part of them, since both formats of
+/// `BYTEA` values can be told apart.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TextSettings {
+    date_style: DateStyle,
+    interval_style: IntervalStyle,
+}
+
+impl TextSettings {
+    /// Creates settings matching the server's defaults.
+    pub fn new() -> TextSettings {
+        TextSettings::default()
+    }
+
+    /// Sets the format of dates and times, corresponding to the `DateStyle` setting.
+    ///
+    /// Defaults to `DateStyle::Iso`.
+    pub fn date_style(mut self, date_style: DateStyle) -> TextSettings {
+        self.date_style = date_style;
+        self
+    }
+
+    /// Gets the format of dates and times.
+    pub fn get_date_style(&self) -> DateStyle {
+        self.date_style
+    }
+
+    /// Sets the format of intervals, corresponding to the `IntervalStyle` setting.
+    ///
+    /// Defaults to `IntervalStyle::Postgres`.
+    pub fn interval_style(mut self, interval_style: IntervalStyle) -> TextSettings {
+        self.interval_style = interval_style;
+        self
+    }
+
+    /// Gets the format of intervals.
+    pub fn get_interval_style(&self) -> IntervalStyle {
+        self.interval_style
+    }
+}
+
+/// The format of dates and times, corresponding to the `DateStyle` setting.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DateStyle {
+    /// The `ISO` format, as in `1997-12-17 07:37:16-08`.
+    #[default]
+    Iso,
+    /// The `SQL` format, as in `12/17/1997 07:37:16.00 PST`.
+    Sql(DateOrder),
+    /// The `Postgres` format, as in `Wed Dec 17 07:37:16 1997 PST`.
+    Postgres(DateOrder),
+    /// The `German` format, as in `17.12.1997 07:37:16.00 CET`.
+    German,
+}
+
+/// The order of the day and month of dates in the `SQL` and `Postgres` formats.
+///
+/// The server formats dates in the `YMD` order like in the `MDY` one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DateOrder {
+    /// The month comes before the day.
+    #[default]
+    Mdy,
+    /// The day comes before the month.
+    Dmy,
+}
+
+/// The format of intervals, corresponding to the `IntervalStyle` setting.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IntervalStyle {
+    /// The `postgres` format, as in `1 year 2 mons -3 days +04:05:06`.
+    #[default]
+    Postgres,
+    /// The `postgres_verbose` format, as in `@ 1 year 2 mons -3 days 4 hours 5 mins 6 secs ago`.
+    PostgresVerbose,
+    /// The `sql_standard` format, as in `+1-2 -3 +4:05:06`.
+    SqlStandard,
+    /// The `iso_8601` format, as in `P1Y2M-3DT4H5M6S`.
+    Iso8601,
+}
+
 impl<'a, T> ToSql for &'a T
 where
     T: ToSql,
@@ -944,6 +1503,13 @@ where
         (*self).encode_format(ty)
     }
 
+    fn to_sql_stream(
+        &self,
+        ty: &Type,
+    ) -> Option<Result<(usize, Chunks), Box<dyn Error + Sync + Send>>> {
+        (*self).to_sql_stream(ty)
+    }
+
     to_sql_checked!();
 }
 
@@ -970,6 +1536,13 @@ impl<T: ToSql> ToSql for Option<T> {
         }
     }
 
+    fn to_sql_stream(
+        &self,
+        ty: &Type,
+    ) -> Option<Result<(usize, Chunks), Box<dyn Error + Sync + Send>>> {
+        self.as_ref().and_then(|val| val.to_sql_stream(ty))
+    }
+
     to_sql_checked!();
 }
 
@@ -1104,6 +1677,7 @@ impl<'a> ToSql for &'a str {
             "ltree" => types::ltree_to_sql(self, w),
             "lquery" => types::lquery_to_sql(self, w),
             "ltxtquery" => types::ltxtquery_to_sql(self, w),
+            _ if *ty == Type::JSONPATH => types::jsonpath_to_sql(self, w),
             _ => types::text_to_sql(self, w),
         }
         Ok(IsNull::No)
@@ -1112,7 +1686,7 @@ impl<'a> ToSql for &'a str {
     fn accepts(ty: &Type) -> bool {
         matches!(
             *ty,
-            Type::VARCHAR | Type::TEXT | Type::BPCHAR | Type::NAME | Type::UNKNOWN
+            Type::VARCHAR | Type::TEXT | Type::BPCHAR | Type::NAME | Type::UNKNOWN | Type::JSONPATH
         ) || matches!(ty.name(), "citext" | "ltree" | "lquery" | "ltxtquery")
     }
 
@@ -1203,17 +1777,7 @@ where
 
 impl ToSql for SystemTime {
     fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
-        let epoch = UNIX_EPOCH + Duration::from_secs(TIME_SEC_CONVERSION);
-
-        let to_usec =
-            |d: Duration| d.as_secs() * USEC_PER_SEC + u64::from(d.subsec_nanos()) / NSEC_PER_USEC;
-
-        let time = match self.duration_since(epoch) {
-            Ok(duration) => to_usec(duration) as i64,
-            Err(e) => -(to_usec(e.duration()) as i64),
-        };
-
-        types::timestamp_to_sql(time, w);
+        types::timestamp_to_sql(system_time_usecs(self), w);
         Ok(IsNull::No)
     }
 
@@ -1222,6 +1786,19 @@ impl ToSql for SystemTime {
     to_sql_checked!();
 }
 
+// Returns the number of microseconds between 2000-01-01 and a time.
+fn system_time_usecs(time: &SystemTime) -> i64 {
+    let epoch = UNIX_EPOCH + Duration::from_secs(TIME_SEC_CONVERSION);
+
+    let to_usec =
+        |d: Duration| d.as_secs() * USEC_PER_SEC + u64::from(d.subsec_nanos()) / NSEC_PER_USEC;
+
+    match time.duration_since(epoch) {
+        Ok(duration) => to_usec(duration) as i64,
+        Err(e) => -(to_usec(e.duration()) as i64),
+    }
+}
+
 impl ToSql for IpAddr {
     fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
         let netmask = match self {
@@ -0,0 +1,186 @@
+//! Dynamic lists of query parameters.
+
+use std::fmt;
+use std::iter::FusedIterator;
+use std::slice;
+use std::vec;
+
+use crate::ToSql;
+
+/// A list of query parameters of mixed types, built at runtime.
+///
+/// Parameters are pushed one after the other, each push returning the number of the parameter in the query, which
+/// makes it easy to build queries with optional filters. A reference to the builder can be passed to the `query_raw`
+/// family of methods of clients, and `ParamsBuilder::to_vec` returns the references taken by `query` and its variants.
+///
+/// The [`params!`](crate::params) macro creates a builder from a list of values.
+///
+/// # Examples
+///
+/// ```
+/// use postgres_types::params;
+///
+/// let name = Some("alice");
+/// let min_age = None::<i32>;
+///
+/// let mut query = "SELECT id FROM users WHERE active = $1".to_string();
+/// let mut params = params![true];
+/// if let Some(n) = params.push_opt(name) {
+///     query += &format!(" AND name = ${}", n);
+/// }
+/// if let Some(n) = params.push_opt(min_age) {
+///     query += &format!(" AND age >= ${}", n);
+/// }
+///
+/// assert_eq!(query, "SELECT id FROM users WHERE active = $1 AND name = $2");
+/// assert_eq!(params.len(), 2);
+/// // let rows = client.query(&query, &params.to_vec()).await?;
+/// ```
+#[derive(Default)]
+pub struct ParamsBuilder<'a> {
+    params: Vec<Box<dyn ToSql + Sync + Send + 'a>>,
+}
+
+impl fmt::Debug for ParamsBuilder<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a> ParamsBuilder<'a> {
+    /// Creates an empty list of parameters.
+    pub fn new() -> ParamsBuilder<'a> {
+        ParamsBuilder::default()
+    }
+
+    /// Creates an empty list with room for `capacity` parameters.
+    pub fn with_capacity(capacity: usize) -> ParamsBuilder<'a> {
+        ParamsBuilder {
+            params: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Appends a parameter, returning its number, which is referenced as `$n` in the query.
+    ///
+    /// The value can be owned or borrowed.
+    pub fn push<T>(&mut self, value: T) -> usize
+    where
+        T: ToSql + Sync + Send + 'a,
+    {
+        self.params.push(Box::new(value));
+        self.params.len()
+    }
+
+    /// Appends a parameter if it is `Some`, returning its number.
+    ///
+    /// Unlike `push`, which binds `None` as `NULL`, nothing is appended if the value is `None`.
+    pub fn push_opt<T>(&mut self, value: Option<T>) -> Option<usize>
+    where
+        T: ToSql + Sync + Send + 'a,
+    {
+        value.map(|value| self.push(value))
+    }
+
+    /// Returns the number of parameters.
+    pub fn len(&self) -> usize {
+        self.params.len()
+    }
+
+    /// Determines if there are no parameters.
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    /// Returns an iterator over references to the parameters.
+    pub fn iter(&self) -> ParamsIter<'_> {
+        ParamsIter(self.params.iter())
+    }
+
+    /// Returns references to the parameters, in the form taken by the `query` method of clients and its variants.
+    pub fn to_vec(&self) -> Vec<&(dyn ToSql + Sync)> {
+        self.iter().collect()
+    }
+}
+
+impl<'a, T> Extend<T> for ParamsBuilder<'a>
+where
+    T: ToSql + Sync + Send + 'a,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<'b> IntoIterator for &'b ParamsBuilder<'_> {
+    type Item = &'b (dyn ToSql + Sync);
+    type IntoIter = ParamsIter<'b>;
+
+    fn into_iter(self) -> ParamsIter<'b> {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for ParamsBuilder<'a> {
+    type Item = Box<dyn ToSql + Sync + Send + 'a>;
+    type IntoIter = vec::IntoIter<Box<dyn ToSql + Sync + Send + 'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.params.into_iter()
+    }
+}
+
+/// An iterator over references to the parameters of a [`ParamsBuilder`].
+pub struct ParamsIter<'b>(slice::Iter<'b, Box<dyn ToSql + Sync + Send + 'b>>);
+
+impl fmt::Debug for ParamsIter<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParamsIter")
+            .field("remaining", &self.0.len())
+            .finish()
+    }
+}
+
+impl<'b> Iterator for ParamsIter<'b> {
+    type Item = &'b (dyn ToSql + Sync);
+
+    fn next(&mut self) -> Option<&'b (dyn ToSql + Sync)> {
+        self.0.next().map(|param| &**param as _)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl ExactSizeIterator for ParamsIter<'_> {}
+
+impl FusedIterator for ParamsIter<'_> {}
+
+/// Creates a [`ParamsBuilder`] from a list of parameters of mixed types.
+///
+/// Values are moved into the builder, so values which are used afterwards should be passed by reference.
+///
+/// # Examples
+///
+/// ```
+/// use postgres_types::params;
+///
+/// let name = "alice".to_string();
+/// let params = params![&name, 42i32, None::<&str>];
+/// assert_eq!(params.len(), 3);
+/// // client.execute_raw("INSERT INTO users (name, age, email) VALUES ($1, $2, $3)", &params).await?;
+/// ```
+#[macro_export]
+macro_rules! params {
+    ($($value:expr),* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut params = $crate::ParamsBuilder::new();
+        $(params.push($value);)*
+        params
+    }};
+}
@@ -5,18 +5,93 @@ use bytes::BytesMut;
 use postgres_protocol::types;
 use std::error::Error;
 use std::fmt;
+use std::fmt::Write;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
 use std::str::FromStr;
 
-use crate::{FromSql, IsNull, ToSql, Type};
+use crate::{text, FromSql, IsNull, TextSettings, ToSql, ToText, Type};
 
 /// Postgres `PG_LSN` type.
-#[derive(Clone, Copy, Eq, Ord, PartialEq, PartialOrd)]
+///
+/// An LSN is a byte offset in the WAL: adding a number of bytes to an LSN and subtracting an LSN from another work
+/// like they do in Postgres, and panic on overflow in debug builds like integers do.
+#[derive(Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct PgLsn(u64);
 
 /// Error parsing LSN.
 #[derive(Debug)]
 pub struct ParseLsnError(());
 
+impl fmt::Display for ParseLsnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid LSN")
+    }
+}
+
+impl Error for ParseLsnError {}
+
+impl PgLsn {
+    /// The invalid LSN `0/0`.
+    pub const INVALID: PgLsn = PgLsn(0);
+
+    /// Returns `true` if this is the invalid LSN `0/0`.
+    pub fn is_invalid(self) -> bool {
+        self == PgLsn::INVALID
+    }
+
+    /// Adds a number of bytes to the LSN, returning `None` on overflow.
+    pub fn checked_add(self, bytes: u64) -> Option<PgLsn> {
+        self.0.checked_add(bytes).map(PgLsn)
+    }
+
+    /// Subtracts a number of bytes from the LSN, returning `None` on overflow.
+    pub fn checked_sub(self, bytes: u64) -> Option<PgLsn> {
+        self.0.checked_sub(bytes).map(PgLsn)
+    }
+
+    /// Returns the number of bytes from `earlier` to this LSN, or `None` if `earlier` is greater.
+    pub fn checked_distance(self, earlier: PgLsn) -> Option<u64> {
+        self.0.checked_sub(earlier.0)
+    }
+}
+
+impl Add<u64> for PgLsn {
+    type Output = PgLsn;
+
+    fn add(self, bytes: u64) -> PgLsn {
+        PgLsn(self.0 + bytes)
+    }
+}
+
+impl AddAssign<u64> for PgLsn {
+    fn add_assign(&mut self, bytes: u64) {
+        self.0 += bytes;
+    }
+}
+
+impl Sub<u64> for PgLsn {
+    type Output = PgLsn;
+
+    fn sub(self, bytes: u64) -> PgLsn {
+        PgLsn(self.0 - bytes)
+    }
+}
+
+impl SubAssign<u64> for PgLsn {
+    fn sub_assign(&mut self, bytes: u64) {
+        self.0 -= bytes;
+    }
+}
+
+/// The number of bytes between two LSNs.
+impl Sub for PgLsn {
+    type Output = u64;
+
+    fn sub(self, earlier: PgLsn) -> u64 {
+        self.0 - earlier.0
+    }
+}
+
 impl From<u64> for PgLsn {
     fn from(lsn_u64: u64) -> Self {
         PgLsn(lsn_u64)
@@ -62,6 +137,14 @@ impl<'a> FromSql<'a> for PgLsn {
         Ok(v.into())
     }
 
+    fn from_sql_text(
+        _: &Type,
+        raw: &'a [u8],
+        _: &TextSettings,
+    ) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        text::str(raw)?.parse().map_err(|_| "invalid LSN".into())
+    }
+
     accepts!(PG_LSN);
 }
 
@@ -75,3 +158,10 @@ impl ToSql for PgLsn {
 
     to_sql_checked!();
 }
+
+impl ToText for PgLsn {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        write!(out, "{}", self)?;
+        Ok(IsNull::No)
+    }
+}
@@ -1,5 +1,6 @@
-use crate::{FromSql, Type};
+use crate::{text, FromSql, TextSettings, Type};
 pub use bytes::BytesMut;
+use std::borrow::Cow;
 use std::error::Error;
 
 pub fn read_be_i32(buf: &mut &[u8]) -> Result<i32, Box<dyn Error + Sync + Send>> {
@@ -32,3 +33,38 @@ where
     };
     T::from_sql_nullable(type_, value)
 }
+
+pub fn read_text_value<'a, T>(
+    type_: &Type,
+    raw: Option<Cow<'a, [u8]>>,
+    settings: &TextSettings,
+) -> Result<T, Box<dyn Error + Sync + Send>>
+where
+    T: FromSql<'a>,
+{
+    match raw {
+        Some(Cow::Borrowed(raw)) => T::from_sql_text(type_, raw, settings),
+        Some(Cow::Owned(raw)) => T::from_sql_text_owned(type_, &raw, settings),
+        None => T::from_sql_null(type_),
+    }
+}
+
+/// Like `read_text_value`, but for a value which was unescaped out of an array or record which was itself escaped, and
+/// so doesn't live for `'a` even if borrowed.
+pub fn read_text_value_owned<'a, T>(
+    type_: &Type,
+    raw: Option<&[u8]>,
+    settings: &TextSettings,
+) -> Result<T, Box<dyn Error + Sync + Send>>
+where
+    T: FromSql<'a>,
+{
+    match raw {
+        Some(raw) => T::from_sql_text_owned(type_, raw, settings),
+        None => T::from_sql_null(type_),
+    }
+}
+
+pub fn split_text_record(raw: &[u8]) -> Result<text::Elements<'_>, Box<dyn Error + Sync + Send>> {
+    text::record(raw)
+}
@@ -0,0 +1,49 @@
+use bytes::BytesMut;
+use postgres_protocol::types;
+use rust_decimal_1::Decimal;
+use std::error::Error;
+use std::fmt::Write;
+
+use crate::{text, FromSql, IsNull, TextSettings, ToSql, ToText, Type};
+
+fn decimal_from_str(s: &str) -> Result<Decimal, Box<dyn Error + Sync + Send>> {
+    match s {
+        "NaN" | "Infinity" | "-Infinity" => {
+            Err(format!("Decimal cannot represent the numeric value {}", s).into())
+        }
+        s => Ok(Decimal::from_str_exact(s)?),
+    }
+}
+
+impl<'a> FromSql<'a> for Decimal {
+    fn from_sql(_: &Type, raw: &[u8]) -> Result<Decimal, Box<dyn Error + Sync + Send>> {
+        decimal_from_str(&types::numeric_from_sql(raw)?)
+    }
+
+    fn from_sql_text(
+        _: &Type,
+        raw: &[u8],
+        _: &TextSettings,
+    ) -> Result<Decimal, Box<dyn Error + Sync + Send>> {
+        decimal_from_str(text::str(raw)?)
+    }
+
+    accepts!(NUMERIC);
+}
+
+impl ToSql for Decimal {
+    fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        types::numeric_to_sql(&self.to_string(), w)?;
+        Ok(IsNull::No)
+    }
+
+    accepts!(NUMERIC);
+    to_sql_checked!();
+}
+
+impl ToText for Decimal {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        write!(out, "{}", self)?;
+        Ok(IsNull::No)
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{FromSql, IsNull, ToSql, Type};
+use crate::{FromSql, IsNull, TextSettings, ToSql, ToText, Type};
 use bytes::{BufMut, BytesMut};
 use serde_1::{Deserialize, Serialize};
 use serde_json_1::Value;
@@ -28,6 +28,17 @@ where
             .map_err(Into::into)
     }
 
+    fn from_sql_text(
+        _: &Type,
+        raw: &'a [u8],
+        _: &TextSettings,
+    ) -> Result<Json<T>, Box<dyn Error + Sync + Send>> {
+        // Unlike in the binary format, JSONB values aren't prefixed by a version.
+        serde_json_1::de::from_slice(raw)
+            .map(Json)
+            .map_err(Into::into)
+    }
+
     accepts!(JSON, JSONB);
 }
 
@@ -51,11 +62,37 @@ where
     to_sql_checked!();
 }
 
+impl<T> ToText for Json<T>
+where
+    T: Serialize,
+{
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        serde_json_1::ser::to_writer(out.writer(), &self.0)?;
+        Ok(IsNull::No)
+    }
+}
+
 impl<'a> FromSql<'a> for Value {
     fn from_sql(ty: &Type, raw: &[u8]) -> Result<Value, Box<dyn Error + Sync + Send>> {
         Json::<Value>::from_sql(ty, raw).map(|json| json.0)
     }
 
+    fn from_sql_text(
+        ty: &Type,
+        raw: &[u8],
+        settings: &TextSettings,
+    ) -> Result<Value, Box<dyn Error + Sync + Send>> {
+        Json::<Value>::from_sql_text(ty, raw, settings).map(|json| json.0)
+    }
+
+    fn from_sql_text_owned(
+        ty: &Type,
+        raw: &[u8],
+        settings: &TextSettings,
+    ) -> Result<Value, Box<dyn Error + Sync + Send>> {
+        Value::from_sql_text(ty, raw, settings)
+    }
+
     accepts!(JSON, JSONB);
 }
 
@@ -71,3 +108,9 @@ impl ToSql for Value {
     accepts!(JSON, JSONB);
     to_sql_checked!();
 }
+
+impl ToText for Value {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        Json(self).to_text(out)
+    }
+}
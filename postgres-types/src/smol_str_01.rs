@@ -2,13 +2,29 @@ use bytes::BytesMut;
 use smol_str_01::SmolStr;
 use std::error::Error;
 
-use crate::{FromSql, IsNull, ToSql, Type};
+use crate::{FromSql, IsNull, TextSettings, ToSql, ToText, Type};
 
 impl<'a> FromSql<'a> for SmolStr {
     fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<SmolStr, Box<dyn Error + Sync + Send>> {
         <&str as FromSql>::from_sql(ty, raw).map(SmolStr::from)
     }
 
+    fn from_sql_text(
+        ty: &Type,
+        raw: &'a [u8],
+        settings: &TextSettings,
+    ) -> Result<SmolStr, Box<dyn Error + Sync + Send>> {
+        <&str as FromSql>::from_sql_text(ty, raw, settings).map(SmolStr::from)
+    }
+
+    fn from_sql_text_owned(
+        ty: &Type,
+        raw: &[u8],
+        settings: &TextSettings,
+    ) -> Result<SmolStr, Box<dyn Error + Sync + Send>> {
+        <&str as FromSql>::from_sql_text(ty, raw, settings).map(SmolStr::from)
+    }
+
     fn accepts(ty: &Type) -> bool {
         <&str as FromSql>::accepts(ty)
     }
@@ -25,3 +41,9 @@ impl ToSql for SmolStr {
 
     to_sql_checked!();
 }
+
+impl ToText for SmolStr {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        self.as_str().to_text(out)
+    }
+}
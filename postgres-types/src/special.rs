@@ -1,9 +1,9 @@
-use bytes::BytesMut;
+use bytes::{BufMut, BytesMut};
 use postgres_protocol::types;
 use std::error::Error;
 use std::{i32, i64};
 
-use crate::{FromSql, IsNull, ToSql, Type};
+use crate::{text, FromSql, IsNull, TextSettings, ToSql, ToText, Type};
 
 /// A wrapper that can be used to represent infinity with `Type::Date` types.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -25,6 +25,18 @@ impl<'a, T: FromSql<'a>> FromSql<'a> for Date<T> {
         }
     }
 
+    fn from_sql_text(
+        ty: &Type,
+        raw: &'a [u8],
+        settings: &TextSettings,
+    ) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        match text::date(raw, settings)? {
+            i32::MAX => Ok(Date::PosInfinity),
+            i32::MIN => Ok(Date::NegInfinity),
+            _ => T::from_sql_text(ty, raw, settings).map(Date::Value),
+        }
+    }
+
     fn accepts(ty: &Type) -> bool {
         *ty == Type::DATE && T::accepts(ty)
     }
@@ -53,6 +65,17 @@ impl<T: ToSql> ToSql for Date<T> {
     to_sql_checked!();
 }
 
+impl<T: ToText> ToText for Date<T> {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        match self {
+            Date::PosInfinity => out.put_slice(b"infinity"),
+            Date::NegInfinity => out.put_slice(b"-infinity"),
+            Date::Value(v) => return v.to_text(out),
+        }
+        Ok(IsNull::No)
+    }
+}
+
 /// A wrapper that can be used to represent infinity with `Type::Timestamp` and `Type::Timestamptz`
 /// types.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -74,6 +97,18 @@ impl<'a, T: FromSql<'a>> FromSql<'a> for Timestamp<T> {
         }
     }
 
+    fn from_sql_text(
+        ty: &Type,
+        raw: &'a [u8],
+        settings: &TextSettings,
+    ) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        match text::timestamp(raw, settings)? {
+            i64::MAX => Ok(Timestamp::PosInfinity),
+            i64::MIN => Ok(Timestamp::NegInfinity),
+            _ => T::from_sql_text(ty, raw, settings).map(Timestamp::Value),
+        }
+    }
+
     fn accepts(ty: &Type) -> bool {
         matches!(*ty, Type::TIMESTAMP | Type::TIMESTAMPTZ if T::accepts(ty))
     }
@@ -101,3 +136,14 @@ impl<T: ToSql> ToSql for Timestamp<T> {
 
     to_sql_checked!();
 }
+
+impl<T: ToText> ToText for Timestamp<T> {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        match self {
+            Timestamp::PosInfinity => out.put_slice(b"infinity"),
+            Timestamp::NegInfinity => out.put_slice(b"-infinity"),
+            Timestamp::Value(v) => return v.to_text(out),
+        }
+        Ok(IsNull::No)
+    }
+}
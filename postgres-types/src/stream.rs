@@ -0,0 +1,108 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use crate::{IsNull, ToSql, Type, WrongType};
+
+/// The chunks of a value written by a [`ToSqlStream`] type.
+pub type Chunks = Box<dyn Iterator<Item = io::Result<Bytes>> + Send>;
+
+// The size of the chunks `Bytes` values are split into.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// A trait for types which can be written to a parameter in chunks, without buffering the entire value in memory.
+///
+/// The length of the value must be known up front, since it is sent before the value. Values implementing this trait
+/// are bound with the [`Streamed`] wrapper.
+pub trait ToSqlStream: fmt::Debug {
+    /// Determines if a value of this type can be converted to the specified Postgres `Type`.
+    fn accepts(ty: &Type) -> bool
+    where
+        Self: Sized;
+
+    /// Returns the length in bytes of the value converted to the binary format of the specified Postgres `Type`.
+    fn encoded_len(&self, ty: &Type) -> usize;
+
+    /// Returns the chunks of the value converted to the binary format of the specified Postgres `Type`.
+    ///
+    /// The chunks are pulled while they are written to the connection, and must add up to `encoded_len` bytes. They
+    /// shouldn't block, since they may be pulled from an asynchronous task.
+    fn chunks(&self, ty: &Type) -> Result<Chunks, Box<dyn Error + Sync + Send>>;
+}
+
+/// A wrapper binding a [`ToSqlStream`] value as a parameter written in chunks.
+///
+/// When a statement is executed with `query` or `execute` and their variants, the encoded value is never copied to
+/// the buffer of the Bind message: its chunks are written to the connection one after the other, so that at most a
+/// few chunks are held in memory at once. Other operations, like pipelines or batches of executions, fall back to
+/// buffering the entire value.
+///
+/// # Examples
+///
+/// ```
+/// use bytes::Bytes;
+/// use postgres_types::Streamed;
+///
+/// let data = Bytes::from(vec![0u8; 100 * 1024 * 1024]);
+/// let param = Streamed(data);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Streamed<T>(pub T);
+
+impl<T: ToSqlStream> ToSql for Streamed<T> {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        let len = self.0.encoded_len(ty);
+        out.reserve(len);
+        let base = out.len();
+        for chunk in self.0.chunks(ty)? {
+            out.put_slice(&chunk?);
+        }
+        if out.len() - base != len {
+            return Err("streamed value doesn't match its encoded length".into());
+        }
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        T::accepts(ty)
+    }
+
+    fn to_sql_stream(
+        &self,
+        ty: &Type,
+    ) -> Option<Result<(usize, Chunks), Box<dyn Error + Sync + Send>>> {
+        if !T::accepts(ty) {
+            return Some(Err(Box::new(WrongType::new::<T>(ty.clone()))));
+        }
+        Some(
+            self.0
+                .chunks(ty)
+                .map(|chunks| (self.0.encoded_len(ty), chunks)),
+        )
+    }
+
+    to_sql_checked!();
+}
+
+/// The chunks of a `Bytes` value are slices sharing its memory.
+impl ToSqlStream for Bytes {
+    fn accepts(ty: &Type) -> bool {
+        <&[u8] as ToSql>::accepts(ty)
+    }
+
+    fn encoded_len(&self, _: &Type) -> usize {
+        self.len()
+    }
+
+    fn chunks(&self, _: &Type) -> Result<Chunks, Box<dyn Error + Sync + Send>> {
+        let bytes = self.clone();
+        Ok(Box::new((0..bytes.len()).step_by(CHUNK_SIZE).map(
+            move |start| Ok(bytes.slice(start..bytes.len().min(start + CHUNK_SIZE))),
+        )))
+    }
+}
@@ -0,0 +1,659 @@
+//! Parsing and formatting of values in the text format.
+//!
+//! Dates and times are parsed into and formatted from the same representation as the binary format, so that
+//! implementations can share their conversions between both formats. The server formats them according to the
+//! `DateStyle` of the session, which only reports the offsets of time zones in the `ISO` format. In the others, the
+//! server prints the abbreviation of the zone, which can't be resolved without the time zone database, so only
+//! timestamps in UTC are supported. Values are always formatted in the `ISO` format, which the server accepts in any
+//! `DateStyle`. Intervals are likewise parsed according to the `IntervalStyle` of the session, and formatted in the
+//! `postgres` format with explicitly signed fields, which the server reads the same way in any `IntervalStyle`.
+
+use bytes::{BufMut, BytesMut};
+use postgres_protocol::types;
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::Write;
+use std::str::{self, FromStr};
+
+use crate::{DateOrder, DateStyle, IntervalStyle, TextSettings};
+
+type Result<T> = std::result::Result<T, Box<dyn Error + Sync + Send>>;
+
+/// The elements of an array or the fields of a record, which are `None` if `NULL`.
+pub(crate) type Elements<'a> = types::TextValues<'a>;
+
+const USEC_PER_SEC: i64 = 1_000_000;
+const SEC_PER_DAY: i64 = 86_400;
+// The number of days between the Unix epoch and the Postgres epoch, 2000-01-01.
+const POSTGRES_EPOCH_DAYS: i64 = 10_957;
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+pub(crate) fn str(raw: &[u8]) -> Result<&str> {
+    Ok(str::from_utf8(raw)?)
+}
+
+pub(crate) fn parse<T>(raw: &[u8]) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Error + Sync + Send + 'static,
+{
+    Ok(str(raw)?.parse()?)
+}
+
+pub(crate) fn bool(raw: &[u8]) -> Result<bool> {
+    match raw {
+        b"t" => Ok(true),
+        b"f" => Ok(false),
+        _ => Err("invalid boolean".into()),
+    }
+}
+
+pub(crate) fn char(raw: &[u8]) -> Result<i8> {
+    match raw {
+        [] => Ok(0),
+        [c] => Ok(*c as i8),
+        // Bytes which aren't ASCII are printed as octal escapes.
+        [b'\\', digits @ ..] if digits.len() == 3 => Ok(u8::from_str_radix(str(digits)?, 8)? as i8),
+        _ => Err("invalid \"char\"".into()),
+    }
+}
+
+pub(crate) fn bytea(raw: &[u8]) -> Result<Vec<u8>> {
+    if let Some(hex) = raw.strip_prefix(b"\\x") {
+        if hex.len() % 2 != 0 {
+            return Err("invalid hex bytea".into());
+        }
+        return hex
+            .chunks(2)
+            .map(|byte| Ok(u8::from_str_radix(str(byte)?, 16)?))
+            .collect();
+    }
+
+    // The escape format, used when `bytea_output` is set to `escape`.
+    let mut bytes = Vec::with_capacity(raw.len());
+    let mut it = raw.iter();
+    while let Some(&b) = it.next() {
+        if b != b'\\' {
+            bytes.push(b);
+            continue;
+        }
+        match it.as_slice() {
+            [b'\\', ..] => {
+                bytes.push(b'\\');
+                it.next();
+            }
+            [a, b, c, ..] => {
+                bytes.push(u8::from_str_radix(str(&[*a, *b, *c])?, 8)?);
+                it.nth(2);
+            }
+            _ => return Err("invalid escaped bytea".into()),
+        }
+    }
+    Ok(bytes)
+}
+
+pub(crate) fn hstore(raw: &[u8]) -> Result<Vec<(String, Option<String>)>> {
+    let mut parser = Parser(raw);
+    let mut pairs = vec![];
+    loop {
+        parser.skip_whitespace();
+        if parser.0.is_empty() {
+            break;
+        }
+        if !pairs.is_empty() {
+            parser.expect(b",")?;
+            parser.skip_whitespace();
+        }
+
+        let key = parser.quoted()?;
+        parser.skip_whitespace();
+        parser.expect(b"=>")?;
+        parser.skip_whitespace();
+        let value = if parser.0.starts_with(b"NULL") {
+            parser.0 = &parser.0[4..];
+            None
+        } else {
+            Some(parser.quoted()?)
+        };
+        pairs.push((key, value));
+    }
+    Ok(pairs)
+}
+
+struct Parser<'a>(&'a [u8]);
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while let [b' ', rest @ ..] = self.0 {
+            self.0 = rest;
+        }
+    }
+
+    fn expect(&mut self, expected: &[u8]) -> Result<()> {
+        match self.0.strip_prefix(expected) {
+            Some(rest) => {
+                self.0 = rest;
+                Ok(())
+            }
+            None => Err(format!("expected `{}`", String::from_utf8_lossy(expected)).into()),
+        }
+    }
+
+    fn quoted(&mut self) -> Result<String> {
+        self.expect(b"\"")?;
+        let mut value = vec![];
+        loop {
+            match self.0 {
+                [b'"', rest @ ..] => {
+                    self.0 = rest;
+                    return Ok(String::from_utf8(value)?);
+                }
+                [b'\\', c, rest @ ..] | [c, rest @ ..] => {
+                    value.push(*c);
+                    self.0 = rest;
+                }
+                [] => return Err("unterminated quoted string".into()),
+            }
+        }
+    }
+}
+
+/// Splits a one-dimensional array into its elements.
+///
+/// Elements are borrowed from the array unless they contain escaped characters, in which case they are unescaped.
+pub(crate) fn array(raw: &[u8], delimiter: u8) -> Result<Elements<'_>> {
+    let array = types::array_from_text(raw, delimiter)?;
+    if array.dimensions().len() > 1 {
+        return Err("array contains too many dimensions".into());
+    }
+    Ok(array.into_values())
+}
+
+/// Splits a record, the text format of composite types, into its fields.
+///
+/// Like the elements of arrays, fields are borrowed unless they contain escaped characters.
+pub(crate) fn record(raw: &[u8]) -> Result<Elements<'_>> {
+    types::record_from_text(raw)
+}
+
+/// Splits an `int2vector` or an `oidvector` into its elements.
+pub(crate) fn vector(raw: &[u8]) -> Elements<'_> {
+    raw.split(|&b| b == b' ')
+        .filter(|e| !e.is_empty())
+        .map(|e| Some(Cow::Borrowed(e)))
+        .collect()
+}
+
+/// Parses the coordinates of the points of a geometric type.
+#[cfg(any(feature = "with-geo-types-0_6", feature = "with-geo-types-0_7"))]
+pub(crate) fn points(raw: &[u8]) -> Result<Vec<(f64, f64)>> {
+    let coordinates = str(raw)?
+        .split(['(', ')', '[', ']', ',', ' '])
+        .filter(|c| !c.is_empty())
+        .map(|c| c.parse())
+        .collect::<std::result::Result<Vec<f64>, _>>()?;
+    if coordinates.len() % 2 != 0 {
+        return Err("invalid point".into());
+    }
+    Ok(coordinates.chunks(2).map(|c| (c[0], c[1])).collect())
+}
+
+/// Parses a date into the number of days since 2000-01-01.
+pub(crate) fn date(raw: &[u8], settings: &TextSettings) -> Result<i32> {
+    match raw {
+        b"infinity" => return Ok(i32::MAX),
+        b"-infinity" => return Ok(i32::MIN),
+        _ => {}
+    }
+
+    let (raw, bc) = era(str(raw)?);
+    let days = days(raw, settings.get_date_style(), bc)?;
+    i32::try_from(days).map_err(|_| "value too large to decode".into())
+}
+
+/// Parses a time into the number of microseconds since midnight.
+#[cfg(any(
+    feature = "with-chrono-0_4",
+    feature = "with-time-0_2",
+    feature = "with-time-0_3"
+))]
+pub(crate) fn time(raw: &[u8]) -> Result<i64> {
+    let raw = str(raw)?;
+    let usecs = usecs(raw)?;
+    if usecs > SEC_PER_DAY * USEC_PER_SEC {
+        return Err("invalid time".into());
+    }
+    Ok(usecs)
+}
+
+/// Parses a timestamp, with or without a time zone, into the number of microseconds since 2000-01-01 in UTC.
+pub(crate) fn timestamp(raw: &[u8], settings: &TextSettings) -> Result<i64> {
+    match raw {
+        b"infinity" => return Ok(i64::MAX),
+        b"-infinity" => return Ok(i64::MIN),
+        _ => {}
+    }
+
+    let (raw, bc) = era(str(raw)?);
+    let (days, time, offset) = match settings.get_date_style() {
+        DateStyle::Iso => {
+            let (date, time) = raw.split_once(' ').ok_or("invalid timestamp")?;
+            let (time, offset) = match time.find(['+', '-']) {
+                Some(idx) => (&time[..idx], offset(&time[idx..])?),
+                None => (time, 0),
+            };
+            (days(date, DateStyle::Iso, bc)?, time, offset)
+        }
+        // As in `Wed Dec 17 07:37:16 1997 PST`, or `Wed 17 Dec 07:37:16 1997 PST` in the DMY order.
+        DateStyle::Postgres(_) => {
+            let mut fields = raw.split(' ');
+            let mut next = || fields.next().ok_or("invalid timestamp");
+            let (_, first, second, time, year) = (next()?, next()?, next()?, next()?, next()?);
+            let (month, day) = match first.parse() {
+                Ok(day) => (second, day),
+                Err(_) => (first, second.parse()?),
+            };
+            let month = MONTHS
+                .iter()
+                .position(|m| *m == month)
+                .ok_or("invalid month")?;
+            let days = civil(year.parse()?, month as i64 + 1, day, bc)?;
+            (days, time, zone(fields)?)
+        }
+        // As in `12/17/1997 07:37:16.00 PST`.
+        style => {
+            let mut fields = raw.split(' ');
+            let date = fields.next().ok_or("invalid timestamp")?;
+            let time = fields.next().ok_or("invalid timestamp")?;
+            (days(date, style, bc)?, time, zone(fields)?)
+        }
+    };
+
+    days.checked_mul(SEC_PER_DAY * USEC_PER_SEC)
+        .and_then(|t| t.checked_add(usecs(time).ok()?))
+        .and_then(|t| t.checked_sub(offset * USEC_PER_SEC))
+        .ok_or_else(|| "invalid timestamp".into())
+}
+
+fn era(raw: &str) -> (&str, bool) {
+    match raw.strip_suffix(" BC") {
+        Some(raw) => (raw, true),
+        None => (raw, false),
+    }
+}
+
+// Returns the number of days between 2000-01-01 and a date.
+fn days(raw: &str, style: DateStyle, bc: bool) -> Result<i64> {
+    let separator = match style {
+        DateStyle::Iso | DateStyle::Postgres(_) => '-',
+        DateStyle::Sql(_) => '/',
+        DateStyle::German => '.',
+    };
+    let mut parts = raw.splitn(3, separator);
+    let (first, second, third) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(first), Some(second), Some(third)) => (
+            first.parse::<i64>()?,
+            second.parse::<i64>()?,
+            third.parse::<i64>()?,
+        ),
+        _ => return Err("invalid date".into()),
+    };
+    match style {
+        DateStyle::Iso => civil(first, second, third, bc),
+        DateStyle::Sql(DateOrder::Mdy) | DateStyle::Postgres(DateOrder::Mdy) => {
+            civil(third, first, second, bc)
+        }
+        DateStyle::Sql(DateOrder::Dmy)
+        | DateStyle::Postgres(DateOrder::Dmy)
+        | DateStyle::German => civil(third, second, first, bc),
+    }
+}
+
+// Returns the number of days between 2000-01-01 and a date of the proleptic Gregorian calendar.
+fn civil(year: i64, month: i64, day: i64, bc: bool) -> Result<i64> {
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return Err("invalid date".into());
+    }
+    // There is no year 0, so 1 BC is year 0 of the proleptic Gregorian calendar.
+    let year = if bc { 1 - year } else { year };
+
+    // Howard Hinnant's days_from_civil algorithm.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    Ok(era * 146_097 + day_of_era - 719_468 - POSTGRES_EPOCH_DAYS)
+}
+
+// Returns the offset of the time zone following the time in the formats other than `ISO`, which only report the
+// abbreviation of the zone.
+fn zone<'a>(mut fields: impl Iterator<Item = &'a str>) -> Result<i64> {
+    let offset = match fields.next() {
+        // Timestamps without time zones.
+        None => 0,
+        Some("UTC" | "GMT") => 0,
+        // Zones without an abbreviation, such as numeric offsets.
+        Some("") => {
+            return Err("the time zone of the value is not reported in this DateStyle".into())
+        }
+        Some(zone) => {
+            return Err(format!(
+                "the offset of time zone `{}` is unknown, use the ISO DateStyle",
+                zone
+            )
+            .into())
+        }
+    };
+    if fields.next().is_some() {
+        return Err("invalid timestamp".into());
+    }
+    Ok(offset)
+}
+
+// Returns the number of microseconds since midnight of a `HH:MM:SS[.ffffff]` time.
+fn usecs(raw: &str) -> Result<i64> {
+    let (time, fraction) = match raw.split_once('.') {
+        Some((time, fraction)) => (time, fraction),
+        None => (raw, ""),
+    };
+    let mut parts = time.splitn(3, ':');
+    let (hours, minutes, seconds) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(hours), Some(minutes), Some(seconds)) => (
+            hours.parse::<i64>()?,
+            minutes.parse::<i64>()?,
+            seconds.parse::<i64>()?,
+        ),
+        _ => return Err("invalid time".into()),
+    };
+    if fraction.len() > 6 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return Err("invalid time".into());
+    }
+    let micros = format!("{:0<6}", fraction).parse::<i64>()?;
+
+    Ok(((hours * 60 + minutes) * 60 + seconds) * USEC_PER_SEC + micros)
+}
+
+// Returns the number of seconds of a `+HH[:MM[:SS]]` time zone offset.
+fn offset(raw: &str) -> Result<i64> {
+    let sign = if raw.starts_with('-') { -1 } else { 1 };
+    let mut offset = 0;
+    for (i, part) in raw[1..].split(':').enumerate() {
+        if i > 2 {
+            return Err("invalid time zone offset".into());
+        }
+        offset += part.parse::<i64>()? * 60i64.pow(2 - i as u32);
+    }
+    Ok(sign * offset)
+}
+
+/// Parses an interval into its number of months, days and microseconds.
+pub(crate) fn interval(raw: &[u8], settings: &TextSettings) -> Result<(i32, i32, i64)> {
+    let raw = str(raw)?;
+    let (months, days, usecs) = match settings.get_interval_style() {
+        IntervalStyle::Postgres => postgres_interval(raw)?,
+        // As in `@ 1 year 2 mons -3 days 4 hours 5 mins 6 secs ago`, where `ago` negates all of the fields.
+        IntervalStyle::PostgresVerbose => {
+            let raw = raw.strip_prefix("@ ").ok_or("invalid interval")?;
+            match raw.strip_suffix(" ago") {
+                Some(raw) => {
+                    let (months, days, usecs) = postgres_interval(raw)?;
+                    (-months, -days, -usecs)
+                }
+                None => postgres_interval(raw)?,
+            }
+        }
+        IntervalStyle::SqlStandard => sql_standard_interval(raw)?,
+        IntervalStyle::Iso8601 => iso_8601_interval(raw)?,
+    };
+    let months = i32::try_from(months).map_err(|_| "value too large to decode")?;
+    let days = i32::try_from(days).map_err(|_| "value too large to decode")?;
+    Ok((months, days, usecs))
+}
+
+// Parses the fields of an interval in the `postgres` format, as in `1 year 2 mons -3 days +04:05:06`, or in the
+// `postgres_verbose` format without its `@` prefix and `ago` suffix, as in `1 year 2 mons -3 days 4 hours 5 mins`.
+fn postgres_interval(raw: &str) -> Result<(i64, i64, i64)> {
+    let (mut months, mut days, mut usecs) = (0i64, 0i64, 0i64);
+    let mut fields = raw.split(' ');
+    while let Some(field) = fields.next() {
+        if field.contains(':') {
+            usecs = add(usecs, signed_time(field)?)?;
+            continue;
+        }
+        // A zero interval is printed as a lone `0` in the `postgres_verbose` format.
+        let unit = match fields.next() {
+            Some(unit) => unit,
+            None if field == "0" => break,
+            None => return Err("invalid interval".into()),
+        };
+        match unit.strip_suffix('s').unwrap_or(unit) {
+            "year" => months = add(months, mul(field.parse()?, 12)?)?,
+            "mon" => months = add(months, field.parse()?)?,
+            "day" => days = add(days, field.parse()?)?,
+            "hour" => usecs = add(usecs, mul(field.parse()?, 3600 * USEC_PER_SEC)?)?,
+            "min" => usecs = add(usecs, mul(field.parse()?, 60 * USEC_PER_SEC)?)?,
+            "sec" => usecs = add(usecs, seconds(field)?)?,
+            _ => return Err(format!("invalid interval unit `{}`", unit).into()),
+        }
+    }
+    Ok((months, days, usecs))
+}
+
+// Parses an interval in the `sql_standard` format, as in `+1-2 -3 +4:05:06`, made of optional year-month, day and
+// time fields. A leading `-` applies to all of the fields if none of the others is signed, as in `-1-2 3 4:05:06`.
+fn sql_standard_interval(raw: &str) -> Result<(i64, i64, i64)> {
+    let (mut months, mut days, mut usecs) = (0i64, 0i64, 0i64);
+    let mut fields = raw.split(' ');
+    let first = fields.next().unwrap_or_default();
+    let negative_all = first.starts_with('-') && fields.all(|f| !f.starts_with(['+', '-']));
+
+    for field in raw.split(' ') {
+        let (negative, value) = match field.strip_prefix('-') {
+            Some(value) => (true, value),
+            None => (negative_all, field.strip_prefix('+').unwrap_or(field)),
+        };
+        let sign = if negative { -1 } else { 1 };
+        if value.contains(':') {
+            usecs = add(usecs, sign * usecs_unsigned(value)?)?;
+        } else if let Some((years, year_months)) = value.split_once('-') {
+            let value = add(mul(years.parse()?, 12)?, year_months.parse()?)?;
+            months = add(months, sign * value)?;
+        } else {
+            days = add(days, sign * value.parse::<i64>()?)?;
+        }
+    }
+    Ok((months, days, usecs))
+}
+
+// Parses an interval in the `iso_8601` format with designators, as in `P1Y2M-3DT4H5M6.5S`.
+fn iso_8601_interval(raw: &str) -> Result<(i64, i64, i64)> {
+    let raw = raw.strip_prefix('P').ok_or("invalid interval")?;
+    let (date, time) = raw.split_once('T').unwrap_or((raw, ""));
+    let (mut months, mut days, mut usecs) = (0i64, 0i64, 0i64);
+
+    for (designators, part) in [(false, date), (true, time)] {
+        let mut part = part;
+        while !part.is_empty() {
+            let end = part
+                .find(|c: char| c.is_ascii_alphabetic())
+                .ok_or("invalid interval")?;
+            let value = &part[..end];
+            match (designators, part.as_bytes()[end]) {
+                (false, b'Y') => months = add(months, mul(value.parse()?, 12)?)?,
+                (false, b'M') => months = add(months, value.parse()?)?,
+                (false, b'W') => days = add(days, mul(value.parse()?, 7)?)?,
+                (false, b'D') => days = add(days, value.parse()?)?,
+                (true, b'H') => usecs = add(usecs, mul(value.parse()?, 3600 * USEC_PER_SEC)?)?,
+                (true, b'M') => usecs = add(usecs, mul(value.parse()?, 60 * USEC_PER_SEC)?)?,
+                (true, b'S') => usecs = add(usecs, seconds(value)?)?,
+                _ => return Err("invalid interval".into()),
+            }
+            part = &part[end + 1..];
+        }
+    }
+    Ok((months, days, usecs))
+}
+
+// Returns the number of microseconds of a signed `[+-]HH:MM:SS[.ffffff]` time.
+fn signed_time(raw: &str) -> Result<i64> {
+    match raw.strip_prefix('-') {
+        Some(raw) => Ok(-usecs_unsigned(raw)?),
+        None => usecs_unsigned(raw.strip_prefix('+').unwrap_or(raw)),
+    }
+}
+
+// Returns the number of microseconds of an unsigned `HH:MM:SS[.ffffff]` time, whose hours may exceed a day.
+fn usecs_unsigned(raw: &str) -> Result<i64> {
+    if raw.starts_with(['+', '-']) {
+        return Err("invalid time".into());
+    }
+    usecs(raw)
+}
+
+// Returns the number of microseconds of a signed `[-]S[.ffffff]` number of seconds.
+fn seconds(raw: &str) -> Result<i64> {
+    let (sign, raw) = match raw.strip_prefix('-') {
+        Some(raw) => (-1, raw),
+        None => (1, raw.strip_prefix('+').unwrap_or(raw)),
+    };
+    let (secs, fraction) = raw.split_once('.').unwrap_or((raw, ""));
+    if secs.starts_with(['+', '-'])
+        || fraction.len() > 6
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+    {
+        return Err("invalid seconds".into());
+    }
+    let micros = format!("{:0<6}", fraction).parse::<i64>()?;
+    Ok(sign * add(mul(secs.parse()?, USEC_PER_SEC)?, micros)?)
+}
+
+fn add(a: i64, b: i64) -> Result<i64> {
+    a.checked_add(b)
+        .ok_or_else(|| "value too large to decode".into())
+}
+
+fn mul(a: i64, b: i64) -> Result<i64> {
+    a.checked_mul(b)
+        .ok_or_else(|| "value too large to decode".into())
+}
+
+/// Writes a date, given as a number of days since 2000-01-01, in the `ISO` format.
+#[cfg(any(
+    feature = "with-chrono-0_4",
+    feature = "with-time-0_2",
+    feature = "with-time-0_3"
+))]
+pub(crate) fn write_date(days: i32, out: &mut BytesMut) {
+    match days {
+        i32::MAX => out.put_slice(b"infinity"),
+        i32::MIN => out.put_slice(b"-infinity"),
+        days => {
+            let (year, month, day) = civil_from_days(i64::from(days));
+            write_ymd(year, month, day, out);
+            write_era(year, out);
+        }
+    }
+}
+
+/// Writes a time, given as a number of microseconds since midnight.
+pub(crate) fn write_time(usecs: i64, out: &mut BytesMut) {
+    let secs = usecs / USEC_PER_SEC;
+    write!(
+        out,
+        "{:02}:{:02}:{:02}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+    .unwrap();
+    let fraction = usecs % USEC_PER_SEC;
+    if fraction != 0 {
+        write!(out, ".{:06}", fraction).unwrap();
+    }
+}
+
+/// Writes a timestamp, given as a number of microseconds since 2000-01-01, in the `ISO` format.
+///
+/// Timestamps with a time zone are written in UTC.
+pub(crate) fn write_timestamp(usecs: i64, with_zone: bool, out: &mut BytesMut) {
+    match usecs {
+        i64::MAX => out.put_slice(b"infinity"),
+        i64::MIN => out.put_slice(b"-infinity"),
+        usecs => {
+            let day_usecs = SEC_PER_DAY * USEC_PER_SEC;
+            let (year, month, day) = civil_from_days(usecs.div_euclid(day_usecs));
+            write_ymd(year, month, day, out);
+            out.put_u8(b' ');
+            write_time(usecs.rem_euclid(day_usecs), out);
+            if with_zone {
+                out.put_slice(b"+00");
+            }
+            write_era(year, out);
+        }
+    }
+}
+
+/// Writes an interval in the `postgres` format, with each field explicitly signed so that the server doesn't apply the
+/// sign of the first one to the others in the `sql_standard` `IntervalStyle`.
+pub(crate) fn write_interval(months: i32, days: i32, usecs: i64, out: &mut BytesMut) {
+    write!(out, "{:+} mons {:+} days ", months, days).unwrap();
+    out.put_u8(if usecs < 0 { b'-' } else { b'+' });
+    let usecs = usecs.unsigned_abs();
+    let secs = usecs / USEC_PER_SEC as u64;
+    write!(
+        out,
+        "{:02}:{:02}:{:02}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+    .unwrap();
+    let fraction = usecs % USEC_PER_SEC as u64;
+    if fraction != 0 {
+        write!(out, ".{:06}", fraction).unwrap();
+    }
+}
+
+fn write_ymd(year: i64, month: i64, day: i64, out: &mut BytesMut) {
+    // There is no year 0, so year 0 of the proleptic Gregorian calendar is 1 BC.
+    let year = if year <= 0 { 1 - year } else { year };
+    write!(out, "{:04}-{:02}-{:02}", year, month, day).unwrap();
+}
+
+fn write_era(year: i64, out: &mut BytesMut) {
+    if year <= 0 {
+        out.put_slice(b" BC");
+    }
+}
+
+// Returns the year, month and day of the proleptic Gregorian calendar a number of days after 2000-01-01.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    // Howard Hinnant's civil_from_days algorithm.
+    let days = days + POSTGRES_EPOCH_DAYS + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+/// Writes an element of an array or a value of an `hstore`, quoted and escaped.
+pub(crate) fn write_quoted(value: &[u8], out: &mut BytesMut) {
+    out.put_u8(b'"');
+    for &b in value {
+        if b == b'"' || b == b'\\' {
+            out.put_u8(b'\\');
+        }
+        out.put_u8(b);
+    }
+    out.put_u8(b'"');
+}
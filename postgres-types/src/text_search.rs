@@ -0,0 +1,273 @@
+//! Full text search types.
+
+use bytes::BytesMut;
+use fallible_iterator::FallibleIterator;
+use postgres_protocol::types::{self, TsQueryItem, TsQueryItems};
+use std::error::Error;
+
+use crate::{FromSql, IsNull, ToSql, Type};
+
+// The largest position of a lexeme, whose 2 high bits hold its weight.
+const MAX_POSITION: u16 = 0x3FFF;
+
+/// The weight of a lexeme, `D` being the default.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum TsWeight {
+    /// The `A` weight, the highest.
+    A,
+    /// The `B` weight.
+    B,
+    /// The `C` weight.
+    C,
+    /// The `D` weight, the lowest.
+    #[default]
+    D,
+}
+
+impl TsWeight {
+    fn bits(self) -> u8 {
+        match self {
+            TsWeight::A => 3,
+            TsWeight::B => 2,
+            TsWeight::C => 1,
+            TsWeight::D => 0,
+        }
+    }
+
+    fn from_bits(bits: u8) -> TsWeight {
+        match bits & 3 {
+            3 => TsWeight::A,
+            2 => TsWeight::B,
+            1 => TsWeight::C,
+            _ => TsWeight::D,
+        }
+    }
+}
+
+/// A position of a lexeme in a document, starting at 1.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TsPosition {
+    /// The position, from 1 to 16383.
+    pub position: u16,
+    /// The weight of the lexeme at this position.
+    pub weight: TsWeight,
+}
+
+/// A lexeme of a `TsVector`, with its positions if any.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TsLexeme {
+    /// The normalized word.
+    pub word: String,
+    /// The positions of the word in the document, which may be empty.
+    pub positions: Vec<TsPosition>,
+}
+
+/// Postgres `TSVECTOR` type.
+///
+/// The server sorts lexemes and removes duplicates, so values aren't always read back in the order they were written.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct TsVector(pub Vec<TsLexeme>);
+
+impl<'a> FromSql<'a> for TsVector {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<TsVector, Box<dyn Error + Sync + Send>> {
+        types::tsvector_from_sql(raw)?
+            .map(|(word, positions)| {
+                Ok(TsLexeme {
+                    word: word.to_string(),
+                    positions: positions
+                        .map(|p| TsPosition {
+                            position: p & MAX_POSITION,
+                            weight: TsWeight::from_bits((p >> 14) as u8),
+                        })
+                        .collect(),
+                })
+            })
+            .collect()
+            .map(TsVector)
+    }
+
+    accepts!(TS_VECTOR);
+}
+
+impl ToSql for TsVector {
+    fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        for lexeme in &self.0 {
+            if lexeme.positions.iter().any(|p| p.position > MAX_POSITION) {
+                return Err(format!("tsvector positions must not exceed {}", MAX_POSITION).into());
+            }
+        }
+
+        types::tsvector_to_sql(
+            self.0.iter().map(|lexeme| {
+                let positions = lexeme
+                    .positions
+                    .iter()
+                    .map(|p| (u16::from(p.weight.bits()) << 14) | p.position);
+                (&*lexeme.word, positions)
+            }),
+            w,
+        )?;
+        Ok(IsNull::No)
+    }
+
+    accepts!(TS_VECTOR);
+
+    to_sql_checked!();
+}
+
+/// Postgres `TSQUERY` type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TsQuery {
+    /// A query without lexemes, as returned for queries made only of stop words. It can't be an operand, nor a
+    /// query parameter.
+    Empty,
+    /// A lexeme, matching words of the given weights, or of any weight if there are none.
+    Lexeme {
+        /// The normalized word.
+        word: String,
+        /// The weights of the words matched.
+        weights: Vec<TsWeight>,
+        /// Whether the lexeme matches the words it prefixes.
+        prefix: bool,
+    },
+    /// `!query`.
+    Not(Box<TsQuery>),
+    /// `left & right`.
+    And(Box<TsQuery>, Box<TsQuery>),
+    /// `left | right`.
+    Or(Box<TsQuery>, Box<TsQuery>),
+    /// `left <distance> right`, `<->` being a distance of 1.
+    Phrase {
+        /// The query matching the first words.
+        left: Box<TsQuery>,
+        /// The query matching the following words.
+        right: Box<TsQuery>,
+        /// The distance between the words matched by both queries.
+        distance: u16,
+    },
+}
+
+impl TsQuery {
+    fn items<'a>(
+        &'a self,
+        items: &mut Vec<TsQueryItem<'a>>,
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        // Operators are followed by their right operand, and then by their left operand.
+        match self {
+            TsQuery::Empty => return Err("an empty tsquery can't be an operand".into()),
+            TsQuery::Lexeme {
+                word,
+                weights,
+                prefix,
+            } => items.push(TsQueryItem::Operand {
+                value: word,
+                weights: weights.iter().fold(0, |w, weight| w | 1 << weight.bits()),
+                prefix: *prefix,
+            }),
+            TsQuery::Not(query) => {
+                items.push(TsQueryItem::Not);
+                query.items(items)?;
+            }
+            TsQuery::And(left, right) => {
+                items.push(TsQueryItem::And);
+                right.items(items)?;
+                left.items(items)?;
+            }
+            TsQuery::Or(left, right) => {
+                items.push(TsQueryItem::Or);
+                right.items(items)?;
+                left.items(items)?;
+            }
+            TsQuery::Phrase {
+                left,
+                right,
+                distance,
+            } => {
+                items.push(TsQueryItem::Phrase(*distance));
+                right.items(items)?;
+                left.items(items)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn from_items(items: &mut TsQueryItems<'_>) -> Result<TsQuery, Box<dyn Error + Sync + Send>> {
+        let item = match items.next()? {
+            Some(item) => item,
+            None => return Err("missing tsquery operand".into()),
+        };
+
+        let query = match item {
+            TsQueryItem::Operand {
+                value,
+                weights,
+                prefix,
+            } => TsQuery::Lexeme {
+                word: value.to_string(),
+                weights: [TsWeight::A, TsWeight::B, TsWeight::C, TsWeight::D]
+                    .iter()
+                    .copied()
+                    .filter(|weight| weights & 1 << weight.bits() != 0)
+                    .collect(),
+                prefix,
+            },
+            TsQueryItem::Not => TsQuery::Not(Box::new(TsQuery::from_items(items)?)),
+            TsQueryItem::And => {
+                let right = TsQuery::from_items(items)?;
+                let left = TsQuery::from_items(items)?;
+                TsQuery::And(Box::new(left), Box::new(right))
+            }
+            TsQueryItem::Or => {
+                let right = TsQuery::from_items(items)?;
+                let left = TsQuery::from_items(items)?;
+                TsQuery::Or(Box::new(left), Box::new(right))
+            }
+            TsQueryItem::Phrase(distance) => {
+                let right = TsQuery::from_items(items)?;
+                let left = TsQuery::from_items(items)?;
+                TsQuery::Phrase {
+                    left: Box::new(left),
+                    right: Box::new(right),
+                    distance,
+                }
+            }
+        };
+        Ok(query)
+    }
+}
+
+impl<'a> FromSql<'a> for TsQuery {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<TsQuery, Box<dyn Error + Sync + Send>> {
+        let mut items = types::tsquery_from_sql(raw)?;
+        if items.size_hint().0 == 0 {
+            items.next()?;
+            return Ok(TsQuery::Empty);
+        }
+
+        let query = TsQuery::from_items(&mut items)?;
+        if items.next()?.is_some() {
+            return Err("invalid tsquery: unexpected trailing items".into());
+        }
+        Ok(query)
+    }
+
+    accepts!(TSQUERY);
+}
+
+impl ToSql for TsQuery {
+    fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        // The server can't decode a tsquery without items in the binary format.
+        if *self == TsQuery::Empty {
+            return Err("an empty tsquery can't be sent to the server".into());
+        }
+
+        let mut items = vec![];
+        self.items(&mut items)?;
+        types::tsquery_to_sql(items, w)?;
+        Ok(IsNull::No)
+    }
+
+    accepts!(TSQUERY);
+
+    to_sql_checked!();
+}
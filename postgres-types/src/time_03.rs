@@ -4,7 +4,7 @@ use std::convert::TryFrom;
 use std::error::Error;
 use time_03::{Date, Duration, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
 
-use crate::{FromSql, IsNull, ToSql, Type};
+use crate::{text, FromSql, IsNull, TextSettings, ToSql, ToText, Type};
 
 fn base() -> PrimitiveDateTime {
     PrimitiveDateTime::new(Date::from_ordinal_date(2000, 1).unwrap(), Time::MIDNIGHT)
@@ -16,16 +16,21 @@ impl<'a> FromSql<'a> for PrimitiveDateTime {
         Ok(base() + Duration::microseconds(t))
     }
 
+    fn from_sql_text(
+        _: &Type,
+        raw: &[u8],
+        settings: &TextSettings,
+    ) -> Result<PrimitiveDateTime, Box<dyn Error + Sync + Send>> {
+        let t = text::timestamp(raw, settings)?;
+        Ok(base() + Duration::microseconds(t))
+    }
+
     accepts!(TIMESTAMP);
 }
 
 impl ToSql for PrimitiveDateTime {
     fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
-        let time = match i64::try_from((*self - base()).whole_microseconds()) {
-            Ok(time) => time,
-            Err(_) => return Err("value too large to transmit".into()),
-        };
-        types::timestamp_to_sql(time, w);
+        types::timestamp_to_sql(timestamp_usecs(*self)?, w);
         Ok(IsNull::No)
     }
 
@@ -33,12 +38,33 @@ impl ToSql for PrimitiveDateTime {
     to_sql_checked!();
 }
 
+impl ToText for PrimitiveDateTime {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        text::write_timestamp(timestamp_usecs(*self)?, false, out);
+        Ok(IsNull::No)
+    }
+}
+
+fn timestamp_usecs(t: PrimitiveDateTime) -> Result<i64, Box<dyn Error + Sync + Send>> {
+    i64::try_from((t - base()).whole_microseconds())
+        .map_err(|_| "value too large to transmit".into())
+}
+
 impl<'a> FromSql<'a> for OffsetDateTime {
     fn from_sql(type_: &Type, raw: &[u8]) -> Result<OffsetDateTime, Box<dyn Error + Sync + Send>> {
         let primitive = PrimitiveDateTime::from_sql(type_, raw)?;
         Ok(primitive.assume_utc())
     }
 
+    fn from_sql_text(
+        type_: &Type,
+        raw: &[u8],
+        settings: &TextSettings,
+    ) -> Result<OffsetDateTime, Box<dyn Error + Sync + Send>> {
+        let primitive = PrimitiveDateTime::from_sql_text(type_, raw, settings)?;
+        Ok(primitive.assume_utc())
+    }
+
     accepts!(TIMESTAMPTZ);
 }
 
@@ -59,23 +85,36 @@ impl ToSql for OffsetDateTime {
     to_sql_checked!();
 }
 
+impl ToText for OffsetDateTime {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        let utc_datetime = self.to_offset(UtcOffset::UTC);
+        let primitive = PrimitiveDateTime::new(utc_datetime.date(), utc_datetime.time());
+        text::write_timestamp(timestamp_usecs(primitive)?, true, out);
+        Ok(IsNull::No)
+    }
+}
+
 impl<'a> FromSql<'a> for Date {
     fn from_sql(_: &Type, raw: &[u8]) -> Result<Date, Box<dyn Error + Sync + Send>> {
         let jd = types::date_from_sql(raw)?;
         Ok(base().date() + Duration::days(i64::from(jd)))
     }
 
+    fn from_sql_text(
+        _: &Type,
+        raw: &[u8],
+        settings: &TextSettings,
+    ) -> Result<Date, Box<dyn Error + Sync + Send>> {
+        let jd = text::date(raw, settings)?;
+        Ok(base().date() + Duration::days(i64::from(jd)))
+    }
+
     accepts!(DATE);
 }
 
 impl ToSql for Date {
     fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
-        let jd = (*self - base().date()).whole_days();
-        if jd > i64::from(i32::max_value()) || jd < i64::from(i32::min_value()) {
-            return Err("value too large to transmit".into());
-        }
-
-        types::date_to_sql(jd as i32, w);
+        types::date_to_sql(date_days(*self)?, w);
         Ok(IsNull::No)
     }
 
@@ -83,26 +122,57 @@ impl ToSql for Date {
     to_sql_checked!();
 }
 
+impl ToText for Date {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        text::write_date(date_days(*self)?, out);
+        Ok(IsNull::No)
+    }
+}
+
+fn date_days(d: Date) -> Result<i32, Box<dyn Error + Sync + Send>> {
+    let jd = (d - base().date()).whole_days();
+    if jd > i64::from(i32::max_value()) || jd < i64::from(i32::min_value()) {
+        return Err("value too large to transmit".into());
+    }
+    Ok(jd as i32)
+}
+
 impl<'a> FromSql<'a> for Time {
     fn from_sql(_: &Type, raw: &[u8]) -> Result<Time, Box<dyn Error + Sync + Send>> {
         let usec = types::time_from_sql(raw)?;
         Ok(Time::MIDNIGHT + Duration::microseconds(usec))
     }
 
+    fn from_sql_text(
+        _: &Type,
+        raw: &[u8],
+        _: &TextSettings,
+    ) -> Result<Time, Box<dyn Error + Sync + Send>> {
+        let usec = text::time(raw)?;
+        Ok(Time::MIDNIGHT + Duration::microseconds(usec))
+    }
+
     accepts!(TIME);
 }
 
 impl ToSql for Time {
     fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
-        let delta = *self - Time::MIDNIGHT;
-        let time = match i64::try_from(delta.whole_microseconds()) {
-            Ok(time) => time,
-            Err(_) => return Err("value too large to transmit".into()),
-        };
-        types::time_to_sql(time, w);
+        types::time_to_sql(time_usecs(*self)?, w);
         Ok(IsNull::No)
     }
 
     accepts!(TIME);
     to_sql_checked!();
 }
+
+impl ToText for Time {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        text::write_time(time_usecs(*self)?, out);
+        Ok(IsNull::No)
+    }
+}
+
+fn time_usecs(t: Time) -> Result<i64, Box<dyn Error + Sync + Send>> {
+    i64::try_from((t - Time::MIDNIGHT).whole_microseconds())
+        .map_err(|_| "value too large to transmit".into())
+}
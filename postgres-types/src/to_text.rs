@@ -0,0 +1,315 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Write;
+use std::hash::BuildHasher;
+use std::net::IpAddr;
+use std::time::SystemTime;
+
+use crate::{system_time_usecs, text, Format, IsNull, Oid};
+
+/// A trait for types that can be converted into Postgres values in the text format.
+///
+/// This is the format of the parameters of `Client::query_raw_txt`, whose types are inferred by the server from the
+/// query rather than declared by the client. The text a value is converted into is the one Postgres parses for its
+/// type, so it doesn't depend on the settings of the session: dates and times are written in the `ISO` format, and
+/// timestamps with a time zone in UTC.
+///
+/// # Types
+///
+/// `ToText` is implemented for the same Rust types as `ToSql`, including those of the optional features, and for
+/// `Option<T>` where `T` implements `ToText`, which converts `None` into `NULL`. Slices, `Vec<T>`, `Box<[T]>` and
+/// `[T; N]` are converted into one-dimensional arrays, except for those of `u8`, which are converted
+/// into `BYTEA` values.
+///
+/// Values already encoded in the binary format can be passed along with values in the text format as
+/// [`BinaryValue`]s.
+pub trait ToText {
+    /// Converts the value of `self` into the text format, appending it to `out`.
+    ///
+    /// The return value indicates if this value should be represented as
+    /// `NULL`. If this is the case, implementations **must not** write
+    /// anything to `out`.
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>>;
+
+    /// Returns the format the value is converted into.
+    ///
+    /// Values in the binary format are written by `to_text` as is, and declared with the type returned by
+    /// `type_oid`.
+    fn encode_format(&self) -> Format {
+        Format::Text
+    }
+
+    /// Returns the OID of the type of the value, or 0 to let the server infer it from the query.
+    fn type_oid(&self) -> Oid {
+        0
+    }
+}
+
+impl<T> ToText for &T
+where
+    T: ?Sized + ToText,
+{
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        (*self).to_text(out)
+    }
+
+    fn encode_format(&self) -> Format {
+        (*self).encode_format()
+    }
+
+    fn type_oid(&self) -> Oid {
+        (*self).type_oid()
+    }
+}
+
+impl<T: ToText> ToText for Option<T> {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        match self {
+            Some(val) => val.to_text(out),
+            None => Ok(IsNull::Yes),
+        }
+    }
+
+    fn encode_format(&self) -> Format {
+        match self {
+            Some(val) => val.encode_format(),
+            None => Format::Text,
+        }
+    }
+
+    fn type_oid(&self) -> Oid {
+        match self {
+            Some(val) => val.type_oid(),
+            None => 0,
+        }
+    }
+}
+
+/// A value already encoded in the binary format of a Postgres type.
+///
+/// It is passed as is among parameters in the text format, avoiding the cost of converting binary data, like that of
+/// `BYTEA` or `UUID` values, into text. Unlike the types of parameters in the text format, its type is declared to
+/// the server rather than inferred from the query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinaryValue {
+    oid: Oid,
+    raw: Bytes,
+}
+
+impl BinaryValue {
+    /// Creates a value of the type of the provided OID, encoded in its binary format.
+    pub fn new(oid: Oid, raw: impl Into<Bytes>) -> BinaryValue {
+        BinaryValue {
+            oid,
+            raw: raw.into(),
+        }
+    }
+
+    /// Returns the OID of the type of the value.
+    pub fn oid(&self) -> Oid {
+        self.oid
+    }
+
+    /// Returns the encoded value.
+    pub fn raw(&self) -> &Bytes {
+        &self.raw
+    }
+}
+
+impl ToText for BinaryValue {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        out.put_slice(&self.raw);
+        Ok(IsNull::No)
+    }
+
+    fn encode_format(&self) -> Format {
+        Format::Binary
+    }
+
+    fn type_oid(&self) -> Oid {
+        self.oid
+    }
+}
+
+impl ToText for bool {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        out.put_u8(if *self { b't' } else { b'f' });
+        Ok(IsNull::No)
+    }
+}
+
+impl ToText for i8 {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        // Like `ToSql`, a `"char"`, whose bytes which aren't ASCII are written as octal escapes.
+        match *self as u8 {
+            0 => {}
+            b @ 1..=0x7f if b != b'\\' => out.put_u8(b),
+            b => write!(out, "\\{:03o}", b).unwrap(),
+        }
+        Ok(IsNull::No)
+    }
+}
+
+macro_rules! display_to_text {
+    ($($t:ty),+) => {
+        $(
+            impl ToText for $t {
+                fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+                    write!(out, "{}", self).unwrap();
+                    Ok(IsNull::No)
+                }
+            }
+        )+
+    }
+}
+
+display_to_text!(i16, i32, i64, u32, IpAddr);
+
+macro_rules! float_to_text {
+    ($($t:ty),+) => {
+        $(
+            impl ToText for $t {
+                fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+                    if self.is_nan() {
+                        out.put_slice(b"NaN");
+                    } else if self.is_infinite() {
+                        out.put_slice(if *self > 0. { b"Infinity" } else { b"-Infinity" });
+                    } else {
+                        write!(out, "{}", self).unwrap();
+                    }
+                    Ok(IsNull::No)
+                }
+            }
+        )+
+    }
+}
+
+float_to_text!(f32, f64);
+
+impl ToText for str {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        out.put_slice(self.as_bytes());
+        Ok(IsNull::No)
+    }
+}
+
+impl ToText for String {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        self.as_str().to_text(out)
+    }
+}
+
+impl ToText for Box<str> {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        (**self).to_text(out)
+    }
+}
+
+impl ToText for Cow<'_, str> {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        (**self).to_text(out)
+    }
+}
+
+impl ToText for [u8] {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        out.put_slice(b"\\x");
+        for b in self {
+            write!(out, "{:02x}", b).unwrap();
+        }
+        Ok(IsNull::No)
+    }
+}
+
+impl ToText for Vec<u8> {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        (**self).to_text(out)
+    }
+}
+
+impl ToText for Cow<'_, [u8]> {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        (**self).to_text(out)
+    }
+}
+
+#[cfg(feature = "array-impls")]
+impl<const N: usize> ToText for [u8; N] {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        self[..].to_text(out)
+    }
+}
+
+impl<T: ToText> ToText for [T] {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        array_to_text(self, out)
+    }
+}
+
+impl<T: ToText> ToText for Vec<T> {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        array_to_text(self, out)
+    }
+}
+
+impl<T: ToText> ToText for Box<[T]> {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        array_to_text(self, out)
+    }
+}
+
+#[cfg(feature = "array-impls")]
+impl<T: ToText, const N: usize> ToText for [T; N] {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        array_to_text(self, out)
+    }
+}
+
+fn array_to_text<T: ToText>(
+    elements: &[T],
+    out: &mut BytesMut,
+) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+    let mut element = BytesMut::new();
+    out.put_u8(b'{');
+    for (i, e) in elements.iter().enumerate() {
+        if i > 0 {
+            out.put_u8(b',');
+        }
+        match e.to_text(&mut element)? {
+            IsNull::No => text::write_quoted(&element, out),
+            IsNull::Yes => out.put_slice(b"NULL"),
+        }
+        element.clear();
+    }
+    out.put_u8(b'}');
+    Ok(IsNull::No)
+}
+
+impl<H> ToText for HashMap<String, Option<String>, H>
+where
+    H: BuildHasher,
+{
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        for (i, (key, value)) in self.iter().enumerate() {
+            if i > 0 {
+                out.put_slice(b", ");
+            }
+            text::write_quoted(key.as_bytes(), out);
+            out.put_slice(b"=>");
+            match value {
+                Some(value) => text::write_quoted(value.as_bytes(), out),
+                None => out.put_slice(b"NULL"),
+            }
+        }
+        Ok(IsNull::No)
+    }
+}
+
+impl ToText for SystemTime {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        text::write_timestamp(system_time_usecs(self), true, out);
+        Ok(IsNull::No)
+    }
+}
@@ -1,9 +1,10 @@
 use bytes::BytesMut;
 use postgres_protocol::types;
 use std::error::Error;
+use std::fmt::Write;
 use uuid_1::Uuid;
 
-use crate::{FromSql, IsNull, ToSql, Type};
+use crate::{text, FromSql, IsNull, TextSettings, ToSql, ToText, Type};
 
 impl<'a> FromSql<'a> for Uuid {
     fn from_sql(_: &Type, raw: &[u8]) -> Result<Uuid, Box<dyn Error + Sync + Send>> {
@@ -11,6 +12,14 @@ impl<'a> FromSql<'a> for Uuid {
         Ok(Uuid::from_bytes(bytes))
     }
 
+    fn from_sql_text(
+        _: &Type,
+        raw: &[u8],
+        _: &TextSettings,
+    ) -> Result<Uuid, Box<dyn Error + Sync + Send>> {
+        text::parse(raw)
+    }
+
     accepts!(UUID);
 }
 
@@ -23,3 +32,10 @@ impl ToSql for Uuid {
     accepts!(UUID);
     to_sql_checked!();
 }
+
+impl ToText for Uuid {
+    fn to_text(&self, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        write!(out, "{}", self)?;
+        Ok(IsNull::No)
+    }
+}
@@ -6,9 +6,9 @@ use crate::{CopyInWriter, CopyOutReader, Error};
 use fallible_iterator::FallibleIterator;
 use futures_util::StreamExt;
 use std::pin::Pin;
-#[doc(inline)]
-pub use tokio_postgres::binary_copy::BinaryCopyOutRow;
 use tokio_postgres::binary_copy::{self, BinaryCopyOutStream};
+#[doc(inline)]
+pub use tokio_postgres::binary_copy::{BinaryCopyOutRow, FromCopyRow, ToCopyRow};
 
 /// A type which serializes rows into the PostgreSQL binary copy format.
 ///
@@ -41,6 +41,18 @@ impl<'a> BinaryCopyInWriter<'a> {
         self.connection.block_on(self.sink.as_mut().write(values))
     }
 
+    /// Writes a single row from a type implementing `ToCopyRow`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of values provided does not match the number expected.
+    pub fn write_row<R>(&mut self, row: &R) -> Result<(), Error>
+    where
+        R: ?Sized + ToCopyRow,
+    {
+        self.connection.block_on(self.sink.as_mut().write_row(row))
+    }
+
     /// A maximally-flexible version of `write`.
     ///
     /// # Panics
@@ -56,6 +68,13 @@ impl<'a> BinaryCopyInWriter<'a> {
             .block_on(self.sink.as_mut().write_raw(values))
     }
 
+    /// Returns the number of rows written so far.
+    ///
+    /// Rows are buffered before being sent to the server, so some of them may not have been sent yet.
+    pub fn rows_written(&self) -> u64 {
+        self.sink.rows_written()
+    }
+
     /// Completes the copy, returning the number of rows added.
     ///
     /// This method *must* be used to complete the copy process. If it is not, the copy will be aborted.
@@ -83,6 +102,11 @@ impl<'a> BinaryCopyOutIter<'a> {
             stream: Box::pin(BinaryCopyOutStream::new(stream, types)),
         }
     }
+
+    /// Returns the number of rows read so far.
+    pub fn rows_read(&self) -> u64 {
+        self.stream.rows_read()
+    }
 }
 
 impl FallibleIterator for BinaryCopyOutIter<'_> {
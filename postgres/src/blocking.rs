@@ -0,0 +1,371 @@
+//! A backend driving connections over blocking sockets.
+//!
+//! Clients opened with [`Config::connect_blocking`] don't embed a tokio runtime. Instead, the protocol is processed
+//! directly on the calling thread over standard library sockets, which block the thread while waiting for the
+//! server. This makes them lighter than regular clients, which is mostly noticeable for short-lived programs opening
+//! a single connection.
+//!
+//! [`Config::connect_blocking`]: crate::Config::connect_blocking
+
+use crate::connection::Connection;
+use crate::Error;
+use std::cell::RefCell;
+use std::fmt;
+use std::future::Future;
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_postgres::config::Host;
+use tokio_postgres::error::DbError;
+use tokio_postgres::tls::MakeTlsConnect;
+
+enum Stream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Stream {
+    fn read(&self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => (&*stream).read(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => (&*stream).read(buf),
+        }
+    }
+
+    fn write(&self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => (&*stream).write(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => (&*stream).write(buf),
+        }
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.set_nonblocking(nonblocking),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.set_nonblocking(nonblocking),
+        }
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.set_read_timeout(timeout),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.set_read_timeout(timeout),
+        }
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.shutdown(std::net::Shutdown::Write),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.shutdown(std::net::Shutdown::Write),
+        }
+    }
+
+    // Runs a blocking operation on the socket, which is otherwise kept in nonblocking mode.
+    fn blocking<T>(&self, f: impl FnOnce(&Stream) -> io::Result<T>) -> io::Result<T> {
+        self.set_nonblocking(false)?;
+        let result = f(self);
+        self.set_nonblocking(true)?;
+        result
+    }
+}
+
+#[derive(Default)]
+struct State {
+    buf: Vec<u8>,
+    pos: usize,
+    error: Option<io::Error>,
+    deadline: Option<Instant>,
+}
+
+struct Inner {
+    stream: Stream,
+    state: Mutex<State>,
+}
+
+/// A socket connected to a server by [`Config::connect_blocking`](crate::Config::connect_blocking).
+///
+/// This is the stream type TLS implementations wrap for clients using the blocking backend.
+pub struct Socket(Arc<Inner>);
+
+impl fmt::Debug for Socket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.0.stream {
+            Stream::Tcp(_) => "tcp",
+            #[cfg(unix)]
+            Stream::Unix(_) => "unix",
+        };
+        f.debug_struct("Socket").field("kind", &kind).finish()
+    }
+}
+
+impl Socket {
+    fn new(stream: Stream) -> io::Result<Socket> {
+        stream.set_nonblocking(true)?;
+        Ok(Socket(Arc::new(Inner {
+            stream,
+            state: Mutex::new(State::default()),
+        })))
+    }
+
+    fn parker(&self) -> Parker {
+        Parker(self.0.clone())
+    }
+}
+
+impl AsyncRead for Socket {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let inner = &*self.0;
+
+        let mut state = inner.state.lock().unwrap();
+        if state.pos < state.buf.len() {
+            let len = usize::min(buf.remaining(), state.buf.len() - state.pos);
+            buf.put_slice(&state.buf[state.pos..state.pos + len]);
+            state.pos += len;
+            if state.pos == state.buf.len() {
+                state.buf.clear();
+                state.pos = 0;
+            }
+            return Poll::Ready(Ok(()));
+        }
+        if let Some(e) = state.error.take() {
+            return Poll::Ready(Err(e));
+        }
+        drop(state);
+
+        loop {
+            match inner.stream.read(buf.initialize_unfilled()) {
+                Ok(len) => {
+                    buf.advance(len);
+                    return Poll::Ready(Ok(()));
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                // The executor blocks on the socket once nothing else can make progress.
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Poll::Pending,
+                Err(e) => return Poll::Ready(Err(e)),
+            }
+        }
+    }
+}
+
+impl AsyncWrite for Socket {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let stream = &self.0.stream;
+        match stream.write(buf) {
+            // Writes block until the kernel accepts some of the data, rather than waiting for the socket to become
+            // writable, which the standard library has no mean to do.
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                Poll::Ready(stream.blocking(|stream| stream.write(buf)))
+            }
+            result => Poll::Ready(result),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.0.stream.shutdown() {
+            Err(e) if e.kind() != io::ErrorKind::NotConnected => Poll::Ready(Err(e)),
+            _ => Poll::Ready(Ok(())),
+        }
+    }
+}
+
+/// A handle blocking the thread until the server sends data or a deadline is reached.
+#[derive(Clone)]
+pub(crate) struct Parker(Arc<Inner>);
+
+impl Parker {
+    /// Registers a deadline at which the thread should be woken up, if it is earlier than the current one.
+    pub(crate) fn wake_at(&self, deadline: Instant) {
+        let mut state = self.0.state.lock().unwrap();
+        state.deadline = Some(match state.deadline {
+            Some(current) => current.min(deadline),
+            None => deadline,
+        });
+    }
+
+    fn park(&self) {
+        let timeout = {
+            let mut state = self.0.state.lock().unwrap();
+            if state.pos < state.buf.len() || state.error.is_some() {
+                return;
+            }
+            match state.deadline.take() {
+                Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                    Some(timeout) if !timeout.is_zero() => Some(timeout),
+                    _ => return,
+                },
+                None => None,
+            }
+        };
+
+        let mut buf = [0; 8 * 1024];
+        let result = self.0.stream.blocking(|stream| {
+            stream.set_read_timeout(timeout)?;
+            stream.read(&mut buf)
+        });
+
+        let mut state = self.0.state.lock().unwrap();
+        match result {
+            // Reaching the end of the stream is reported by the next read of the nonblocking socket.
+            Ok(len) => state.buf.extend_from_slice(&buf[..len]),
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock
+                        | io::ErrorKind::TimedOut
+                        | io::ErrorKind::Interrupted
+                ) => {}
+            Err(e) => state.error = Some(e),
+        }
+    }
+
+    pub(crate) fn poll_block_on<F, T>(&self, f: F) -> T
+    where
+        F: FnMut(&mut Context<'_>) -> Poll<T>,
+    {
+        poll_block_on(|| self.park(), f)
+    }
+}
+
+struct Flag(AtomicBool);
+
+impl Wake for Flag {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+// Polls until completion, parking the thread whenever the last poll didn't wake the task up, in which case only the
+// server or a deadline can allow it to make progress.
+fn poll_block_on<P, F, T>(park: P, mut f: F) -> T
+where
+    P: Fn(),
+    F: FnMut(&mut Context<'_>) -> Poll<T>,
+{
+    let flag = Arc::new(Flag(AtomicBool::new(false)));
+    let waker = Waker::from(flag.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    loop {
+        flag.0.store(false, Ordering::SeqCst);
+        if let Poll::Ready(value) = f(&mut cx) {
+            return value;
+        }
+        if !flag.0.load(Ordering::SeqCst) {
+            park();
+        }
+    }
+}
+
+pub(crate) fn connect<T>(
+    config: &tokio_postgres::Config,
+    tls: T,
+    notice_callback: Arc<dyn Fn(DbError) + Sync + Send>,
+) -> Result<(tokio_postgres::Client, Connection), Error>
+where
+    T: MakeTlsConnect<Socket>,
+    T::Stream: Send + 'static,
+{
+    // The parker of the socket currently being connected, which is the one of the connection once it succeeds.
+    let current = RefCell::new(None::<Parker>);
+
+    let result = {
+        let connect = config.__private_api_connect_with(tls, |host, port| {
+            let (stream, ip) = connect_socket(config, host, port)?;
+            let socket = Socket::new(stream).map_err(Error::__private_api_connect)?;
+            *current.borrow_mut() = Some(socket.parker());
+            Ok((socket, ip))
+        });
+        futures_util::pin_mut!(connect);
+        poll_block_on(
+            || {
+                if let Some(parker) = &*current.borrow() {
+                    parker.park();
+                }
+            },
+            |cx| connect.as_mut().poll(cx),
+        )
+    };
+
+    let (client, connection) = result?;
+    let parker = current.into_inner().unwrap();
+    Ok((
+        client,
+        Connection::new_blocking(parker, connection, notice_callback),
+    ))
+}
+
+fn connect_socket(
+    config: &tokio_postgres::Config,
+    host: &Host,
+    port: u16,
+) -> Result<(Stream, Option<IpAddr>), Error> {
+    match host {
+        Host::Tcp(host) => {
+            let addrs = (&**host, port)
+                .to_socket_addrs()
+                .map_err(Error::__private_api_connect)?;
+
+            let mut last_err = None;
+            for addr in addrs {
+                let result = match config.get_connect_timeout() {
+                    Some(timeout) => TcpStream::connect_timeout(&addr, *timeout),
+                    None => TcpStream::connect(addr),
+                };
+                match result {
+                    Ok(stream) => {
+                        stream
+                            .set_nodelay(true)
+                            .map_err(Error::__private_api_connect)?;
+                        config.__private_api_configure_tcp(&stream)?;
+                        return Ok((Stream::Tcp(stream), Some(addr.ip())));
+                    }
+                    Err(e) => last_err = Some(e),
+                }
+            }
+
+            Err(Error::__private_api_connect(last_err.unwrap_or_else(
+                || {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "could not resolve any addresses",
+                    )
+                },
+            )))
+        }
+        #[cfg(unix)]
+        Host::Unix(path) => {
+            let stream = UnixStream::connect(path.join(format!(".s.PGSQL.{}", port)))
+                .map_err(Error::__private_api_connect)?;
+            Ok((Stream::Unix(stream), None))
+        }
+    }
+}
@@ -1,6 +1,8 @@
+use std::future::Future;
+use std::time::Duration;
 use tokio::runtime;
 use tokio_postgres::tls::MakeTlsConnect;
-use tokio_postgres::{Error, Socket};
+use tokio_postgres::{CancelOptions, CancelOutcome, Error, Socket};
 
 /// The capability to request cancellation of in-progress queries on a
 /// connection.
@@ -26,10 +28,80 @@ impl CancelToken {
     where
         T: MakeTlsConnect<Socket>,
     {
-        runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()
-            .unwrap() // FIXME don't unwrap
-            .block_on(self.0.cancel_query(tls))
+        block_on(self.0.cancel_query(tls))
     }
+
+    /// Like `cancel_query`, but returns an error if the cancellation request could not be sent before the timeout.
+    ///
+    /// The timeout covers connecting to the server, performing the TLS handshake if requested, and sending the
+    /// request, so the calling thread is never blocked for longer than it even if the server is unresponsive.
+    pub fn cancel_query_timeout<T>(&self, tls: T, timeout: Duration) -> Result<(), Error>
+    where
+        T: MakeTlsConnect<Socket>,
+    {
+        block_on(async {
+            tokio::time::timeout(timeout, self.0.cancel_query(tls))
+                .await
+                .map_err(|_| Error::__private_api_timeout())?
+        })
+    }
+
+    /// Like `cancel_query`, but with control over the delivery of the request, and returning its outcome.
+    ///
+    /// See `tokio_postgres::CancelToken::cancel_query_with` for details.
+    pub fn cancel_query_with<T>(&self, tls: T, options: CancelOptions) -> CancelOutcome
+    where
+        T: MakeTlsConnect<Socket>,
+    {
+        block_on(self.0.cancel_query_with(tls, options))
+    }
+}
+
+/// The capability to request cancellation of a single query.
+///
+/// Unlike a `CancelToken`, which cancels whatever is running on the connection, the cancellation is only requested
+/// while the query is in progress. The query may still complete before the cancellation request reaches the server.
+#[derive(Clone)]
+pub struct QueryCancelToken(tokio_postgres::QueryCancelToken);
+
+impl QueryCancelToken {
+    pub(crate) fn new(inner: tokio_postgres::QueryCancelToken) -> QueryCancelToken {
+        QueryCancelToken(inner)
+    }
+
+    /// Determines if the query is in progress.
+    pub fn is_active(&self) -> bool {
+        self.0.is_active()
+    }
+
+    /// Attempts to cancel the query, if it is in progress.
+    ///
+    /// Like `CancelToken::cancel_query`, the server provides no information about whether the attempt was successful.
+    pub fn cancel_query<T>(&self, tls: T) -> Result<(), Error>
+    where
+        T: MakeTlsConnect<Socket>,
+    {
+        block_on(self.0.cancel_query(tls))
+    }
+
+    /// Like `cancel_query`, but with control over the delivery of the request, and returning its outcome.
+    ///
+    /// See `tokio_postgres::CancelToken::cancel_query_with` for details.
+    pub fn cancel_query_with<T>(&self, tls: T, options: CancelOptions) -> CancelOutcome
+    where
+        T: MakeTlsConnect<Socket>,
+    {
+        block_on(self.0.cancel_query_with(tls, options))
+    }
+}
+
+fn block_on<F>(future: F) -> F::Output
+where
+    F: Future,
+{
+    runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap() // FIXME don't unwrap
+        .block_on(future)
 }
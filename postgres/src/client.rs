@@ -1,18 +1,48 @@
 use crate::connection::Connection;
+use crate::pipeline::Pipeline;
 use crate::{
-    CancelToken, Config, CopyInWriter, CopyOutReader, Notifications, RowIter, Statement,
-    ToStatement, Transaction, TransactionBuilder,
+    copy_in_writer, copy_out_reader, CancelToken, Config, CopyInWriter, CopyOutReader,
+    Notifications, QueryOptions, RowChunks, RowIter, Statement, ToStatement, Transaction,
+    TransactionBuilder,
 };
+use futures_util::future::{self, Either};
+use futures_util::pin_mut;
+#[cfg(feature = "arrow")]
+use futures_util::TryStreamExt;
+use std::io::{Read, Write};
+use std::mem;
+use std::sync::Arc;
 use std::task::Poll;
 use std::time::Duration;
+#[cfg(feature = "arrow")]
+use tokio_postgres::arrow::RecordBatch;
+#[cfg(feature = "with-serde_json-1")]
+use tokio_postgres::explain::{ExplainOptions, Plan};
+use tokio_postgres::interceptor::Interceptor;
+#[cfg(feature = "migrations")]
+use tokio_postgres::migrations::Migrations;
+use tokio_postgres::schema::{ForeignKey, Index, PrimaryKey, Table, TableColumn};
+use tokio_postgres::security::ConnectionSecurity;
+use tokio_postgres::stats::StatementStats;
 use tokio_postgres::tls::{MakeTlsConnect, TlsConnect};
-use tokio_postgres::types::{BorrowToSql, ToSql, Type};
-use tokio_postgres::{Error, Row, SimpleQueryMessage, Socket};
+use tokio_postgres::types::{BorrowToSql, Format, FromRow, FromSqlOwned, Oid, ToSql, ToText, Type};
+use tokio_postgres::{
+    Cursor, Error, ExecuteManyError, Row, Rows, ServerVersion, SimpleQueryMessage, Socket,
+    StatementDescription, TransactionStatus,
+};
 
 /// A synchronous PostgreSQL client.
 pub struct Client {
     connection: Connection,
     client: tokio_postgres::Client,
+    reconnect: Option<Reconnect>,
+}
+
+struct Reconnect {
+    connect: Box<dyn FnMut() -> Result<Client, Error> + Send>,
+    interceptors: Vec<Arc<dyn Interceptor>>,
+    types: Vec<Type>,
+    reconnects: u64,
 }
 
 impl Drop for Client {
@@ -23,7 +53,23 @@ impl Drop for Client {
 
 impl Client {
     pub(crate) fn new(connection: Connection, client: tokio_postgres::Client) -> Client {
-        Client { connection, client }
+        Client {
+            connection,
+            client,
+            reconnect: None,
+        }
+    }
+
+    pub(crate) fn set_reconnect(
+        &mut self,
+        connect: Box<dyn FnMut() -> Result<Client, Error> + Send>,
+    ) {
+        self.reconnect = Some(Reconnect {
+            connect,
+            interceptors: vec![],
+            types: vec![],
+            reconnects: 0,
+        });
     }
 
     /// A convenience function which parses a configuration string into a `Config` and then connects to the database.
@@ -80,9 +126,45 @@ impl Client {
     where
         T: ?Sized + ToStatement,
     {
+        self.reconnect()?;
         self.connection.block_on(self.client.execute(query, params))
     }
 
+    /// Like `execute`, but with options controlling the execution of the statement.
+    pub fn execute_with_opts<T>(
+        &mut self,
+        query: &T,
+        params: &[&(dyn ToSql + Sync)],
+        options: QueryOptions,
+    ) -> Result<u64, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        self.reconnect()?;
+        self.connection.check_options(&options)?;
+        self.connection
+            .block_on(self.client.execute_with_opts(query, params, options))
+    }
+
+    /// Executes a statement once for each row of parameters, returning the total number of rows modified.
+    ///
+    /// The executions are sent at once and run in a single implicit transaction, as detailed in the `tokio_postgres`
+    /// documentation. If a row fails, none of the rows take effect, and the returned error holds the index of the
+    /// failing row.
+    pub fn execute_many<T, P, I, R>(&mut self, query: &T, rows: R) -> Result<u64, ExecuteManyError>
+    where
+        T: ?Sized + ToStatement,
+        P: BorrowToSql,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+        R: IntoIterator<Item = I>,
+    {
+        self.reconnect()?;
+        let client = &self.client;
+        let execute = async { Ok(client.execute_many(query, rows).await) };
+        self.connection.block_on(execute)?
+    }
+
     /// Executes a statement, returning the resulting rows.
     ///
     /// A statement may contain parameters, specified by `$n`, where `n` is the index of the parameter of the list
@@ -112,9 +194,86 @@ impl Client {
     where
         T: ?Sized + ToStatement,
     {
+        self.reconnect()?;
         self.connection.block_on(self.client.query(query, params))
     }
 
+    /// Like `query`, but with options controlling the execution of the query.
+    pub fn query_with_opts<T>(
+        &mut self,
+        query: &T,
+        params: &[&(dyn ToSql + Sync)],
+        options: QueryOptions,
+    ) -> Result<Vec<Row>, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        self.reconnect()?;
+        self.connection.check_options(&options)?;
+        self.connection
+            .block_on(self.client.query_with_opts(query, params, options))
+    }
+
+    /// Like `query`, but returns at most `max_rows` rows.
+    ///
+    /// The limit is enforced by the server, which stops executing the statement once it returned `max_rows` rows,
+    /// without the query having to be rewritten with a `LIMIT` clause. All of the rows are returned if `max_rows` is
+    /// negative or 0.
+    pub fn query_with_limit<T>(
+        &mut self,
+        query: &T,
+        params: &[&(dyn ToSql + Sync)],
+        max_rows: i32,
+    ) -> Result<Vec<Row>, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        self.reconnect()?;
+        self.connection
+            .block_on(self.client.query_with_limit(query, params, max_rows))
+    }
+
+    /// Like `query`, but collects the rows into a compact [`Rows`] container.
+    ///
+    /// The values of all of the rows are stored in a single buffer rather than in one per row, which takes much less
+    /// memory for results made of many small rows.
+    pub fn query_rows<T>(
+        &mut self,
+        query: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Rows, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        self.reconnect()?;
+        self.connection
+            .block_on(self.client.query_rows(query, params))
+    }
+
+    /// Like `query`, but executes the query with a statement prepared and cached by the client.
+    ///
+    /// The first time a query is executed by this method, it is prepared as a named statement kept in a least
+    /// recently used cache of the client, keyed by its text. Later executions of the same query reuse the statement
+    /// instead of preparing it again. The capacity of the cache is set by `Config::statement_cache_capacity`, and
+    /// statements evicted from it are closed.
+    ///
+    /// Statements whose result type was changed by a schema change are evicted and, outside of a transaction,
+    /// prepared again and retried once.
+    pub fn query_cached(
+        &mut self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, Error> {
+        self.reconnect()?;
+        self.connection
+            .block_on(self.client.query_cached(query, params))
+    }
+
+    /// Closes the statements cached by `query_cached`, once they are no longer used elsewhere.
+    pub fn clear_statement_cache(&self) {
+        self.client.clear_statement_cache()
+    }
+
     /// Executes a statement which returns a single row, returning it.
     ///
     /// Returns an error if the query does not return exactly one row.
@@ -145,6 +304,7 @@ impl Client {
     where
         T: ?Sized + ToStatement,
     {
+        self.reconnect()?;
         self.connection
             .block_on(self.client.query_one(query, params))
     }
@@ -188,10 +348,94 @@ impl Client {
     where
         T: ?Sized + ToStatement,
     {
+        self.reconnect()?;
         self.connection
             .block_on(self.client.query_opt(query, params))
     }
 
+    /// Executes a statement which returns a single row of a single column, returning its value.
+    ///
+    /// Returns an error if the query does not return exactly one row, or if the row does not have exactly one column.
+    ///
+    /// A statement may contain parameters, specified by `$n`, where `n` is the index of the parameter of the list
+    /// provided, 1-indexed.
+    ///
+    /// The `query` argument can either be a `Statement`, or a raw query string. If the same statement will be
+    /// repeatedly executed (perhaps with different query parameters), consider preparing the statement up front
+    /// with the `prepare` method.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use postgres::{Client, NoTls};
+    ///
+    /// # fn main() -> Result<(), postgres::Error> {
+    /// let mut client = Client::connect("host=localhost user=postgres", NoTls)?;
+    ///
+    /// let count = client.query_scalar::<i64>("SELECT count(*) FROM foo", &[])?;
+    /// println!("{} foos", count);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn query_scalar<R>(
+        &mut self,
+        query: &(impl ?Sized + ToStatement),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<R, Error>
+    where
+        R: FromSqlOwned,
+    {
+        self.reconnect()?;
+        self.connection
+            .block_on(self.client.query_scalar(query, params))
+    }
+
+    /// Executes a statement which returns zero or one rows of a single column, returning its value.
+    ///
+    /// Returns an error if the query returns more than one row, or if the row does not have exactly one column.
+    ///
+    /// A statement may contain parameters, specified by `$n`, where `n` is the index of the parameter of the list
+    /// provided, 1-indexed.
+    ///
+    /// The `query` argument can either be a `Statement`, or a raw query string. If the same statement will be
+    /// repeatedly executed (perhaps with different query parameters), consider preparing the statement up front
+    /// with the `prepare` method.
+    pub fn query_scalar_opt<R>(
+        &mut self,
+        query: &(impl ?Sized + ToStatement),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<R>, Error>
+    where
+        R: FromSqlOwned,
+    {
+        self.reconnect()?;
+        self.connection
+            .block_on(self.client.query_scalar_opt(query, params))
+    }
+
+    /// Executes a statement, returning the resulting rows deserialized into values of a type implementing `FromRow`.
+    ///
+    /// The fields of the values are read from the columns of the same name, as with `Row::try_into`.
+    ///
+    /// A statement may contain parameters, specified by `$n`, where `n` is the index of the parameter of the list
+    /// provided, 1-indexed.
+    ///
+    /// The `query` argument can either be a `Statement`, or a raw query string. If the same statement will be
+    /// repeatedly executed (perhaps with different query parameters), consider preparing the statement up front
+    /// with the `prepare` method.
+    pub fn query_as<R>(
+        &mut self,
+        query: &(impl ?Sized + ToStatement),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<R>, Error>
+    where
+        R: FromRow,
+    {
+        self.reconnect()?;
+        self.connection
+            .block_on(self.client.query_as(query, params))
+    }
+
     /// A maximally-flexible version of `query`.
     ///
     /// It takes an iterator of parameters rather than a slice, and returns an iterator of rows rather than collecting
@@ -251,12 +495,313 @@ impl Client {
         I: IntoIterator<Item = P>,
         I::IntoIter: ExactSizeIterator,
     {
+        self.reconnect()?;
         let stream = self
             .connection
             .block_on(self.client.query_raw(query, params))?;
         Ok(RowIter::new(self.connection.as_ref(), stream))
     }
 
+    /// Like `query_raw`, but fetches the rows from the server in chunks of at most `fetch_size` rows.
+    ///
+    /// The statement is bound to a portal in a new transaction, and each chunk is fetched once the rows of the previous
+    /// one have been returned by the iterator, so the memory used doesn't grow with the number of rows of the result.
+    /// The transaction is committed once all of the rows have been returned, and rolled back if the iterator is
+    /// dropped before.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fetch_size` is not positive.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use postgres::{Client, NoTls};
+    /// use fallible_iterator::FallibleIterator;
+    /// use std::iter;
+    /// # fn main() -> Result<(), postgres::Error> {
+    /// # let mut client = Client::connect("host=localhost user=postgres", NoTls)?;
+    ///
+    /// let mut it = client.query_raw_chunked("SELECT id FROM events", iter::empty::<i32>(), 1000)?;
+    /// while let Some(row) = it.next()? {
+    ///     let id: i32 = row.get(0);
+    ///     println!("event {}", id);
+    /// }
+    /// println!("{} events", it.rows_affected().unwrap());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn query_raw_chunked<T, P, I>(
+        &mut self,
+        query: &T,
+        params: I,
+        fetch_size: i32,
+    ) -> Result<RowIter<'_>, Error>
+    where
+        T: ?Sized + ToStatement,
+        P: BorrowToSql,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.reconnect()?;
+        let transaction = self.connection.block_on(self.client.transaction())?;
+        RowIter::chunked(
+            self.connection.as_ref(),
+            transaction,
+            query,
+            params,
+            fetch_size,
+        )
+    }
+
+    /// Executes a query with parameters in their text format, without preparing a statement first.
+    ///
+    /// The server infers the types of the parameters and the query is executed in a single roundtrip. A `None`
+    /// parameter is sent as `NULL`. Columns of the returned rows are in the text format, and can be retrieved
+    /// regardless of their type with `Row::as_text`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use postgres::{Client, NoTls};
+    /// use fallible_iterator::FallibleIterator;
+    /// # fn main() -> Result<(), postgres::Error> {
+    /// # let mut client = Client::connect("host=localhost user=postgres", NoTls)?;
+    ///
+    /// let mut it = client.query_raw_txt(
+    ///     "SELECT foo FROM bar WHERE biz = $1",
+    ///     [Some("first param")],
+    /// )?;
+    ///
+    /// while let Some(row) = it.next()? {
+    ///     let foo = row.as_text(0)?;
+    ///     println!("foo: {:?}", foo);
+    /// }
+    /// println!("{:?}", it.command_tag());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn query_raw_txt<P, I>(&mut self, query: &str, params: I) -> Result<RowIter<'_>, Error>
+    where
+        P: ToText,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.reconnect()?;
+        let stream = self
+            .connection
+            .block_on(self.client.query_raw_txt(query, params))?;
+        Ok(RowIter::new(self.connection.as_ref(), stream))
+    }
+
+    /// Like `query_raw_txt`, but for a statement which returns a single row, returning it.
+    ///
+    /// Returns an error if the query does not return exactly one row.
+    pub fn query_one_txt<P, I>(&mut self, query: &str, params: I) -> Result<Row, Error>
+    where
+        P: ToText,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.reconnect()?;
+        self.connection
+            .block_on(self.client.query_one_txt(query, params))
+    }
+
+    /// Like `query_raw_txt`, but for a statement which returns zero or one rows, returning it.
+    ///
+    /// Returns an error if the query returns more than one row.
+    pub fn query_opt_txt<P, I>(&mut self, query: &str, params: I) -> Result<Option<Row>, Error>
+    where
+        P: ToText,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.reconnect()?;
+        self.connection
+            .block_on(self.client.query_opt_txt(query, params))
+    }
+
+    /// Executes a query with parameters already encoded, in the formats given, returning rows in the format requested.
+    ///
+    /// The formats follow the rules of the protocol's `Bind` message, as detailed in the `tokio_postgres`
+    /// documentation.
+    pub fn query_with_format<P, I>(
+        &mut self,
+        query: &str,
+        params: I,
+        param_formats: &[Format],
+        result_format: Format,
+    ) -> Result<RowIter<'_>, Error>
+    where
+        P: AsRef<[u8]>,
+        I: IntoIterator<Item = Option<P>>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.reconnect()?;
+        let stream = self.connection.block_on(self.client.query_with_format(
+            query,
+            params,
+            param_formats,
+            result_format,
+        ))?;
+        Ok(RowIter::new(self.connection.as_ref(), stream))
+    }
+
+    /// Like `query_raw`, but for a query whose parameters are encoded against the types inferred by the server.
+    ///
+    /// The query is described and executed in the same request, as detailed in the `tokio_postgres` documentation.
+    pub fn query_raw_inferred<P, I>(&mut self, query: &str, params: I) -> Result<RowIter<'_>, Error>
+    where
+        P: BorrowToSql,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.reconnect()?;
+        let stream = self
+            .connection
+            .block_on(self.client.query_raw_inferred(query, params))?;
+        Ok(RowIter::new(self.connection.as_ref(), stream))
+    }
+
+    /// Executes a statement, returning Arrow record batches of at most `batch_size` rows each holding the resulting
+    /// rows.
+    ///
+    /// See the [`arrow`](crate::arrow) module for details.
+    ///
+    /// Requires the `arrow` Cargo feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `batch_size` is 0, or if the number of parameters provided does not match the number expected.
+    #[cfg(feature = "arrow")]
+    pub fn query_arrow<T>(
+        &mut self,
+        query: &T,
+        params: &[&(dyn ToSql + Sync)],
+        batch_size: usize,
+    ) -> Result<Vec<RecordBatch>, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        self.reconnect()?;
+        let client = &self.client;
+        self.connection.block_on(async {
+            let batches = client.query_arrow(query, params, batch_size).await?;
+            batches.try_collect().await
+        })
+    }
+
+    /// Executes a statement, returning an iterator over batches of at most `chunk_size` rows.
+    ///
+    /// The statement is bound to a portal in a new transaction, and the batches are fetched from it one at a time as the
+    /// iterator advances, so the memory used doesn't grow with the number of rows of the result. The transaction is
+    /// rolled back when the iterator is dropped, or committed by `RowChunks::finish`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is not positive, or if the number of parameters provided does not match the number
+    /// expected.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use postgres::{Client, NoTls};
+    /// use fallible_iterator::FallibleIterator;
+    ///
+    /// # fn main() -> Result<(), postgres::Error> {
+    /// let mut client = Client::connect("host=localhost user=postgres", NoTls)?;
+    ///
+    /// let mut chunks = client.query_chunked("SELECT id FROM events", &[], 1000)?;
+    /// while let Some(rows) = chunks.next()? {
+    ///     for row in rows {
+    ///         let id: i32 = row.get(0);
+    ///         println!("event {}", id);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn query_chunked<T>(
+        &mut self,
+        query: &T,
+        params: &[&(dyn ToSql + Sync)],
+        chunk_size: i32,
+    ) -> Result<RowChunks<'_>, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        self.reconnect()?;
+        let chunks = self
+            .connection
+            .block_on(self.client.query_chunked(query, params, chunk_size))?;
+        Ok(RowChunks::new(self.connection.as_ref(), chunks))
+    }
+
+    /// Declares a cursor `WITH HOLD` over the rows of a query.
+    ///
+    /// The rows are fetched from the cursor with `fetch`, and it remains open across the commits of transactions,
+    /// which suits paging through a large result while committing work between pages. Declared outside of a
+    /// transaction, the query runs to completion up front and its rows are kept by the server until the cursor is
+    /// closed. Declared inside of one, they are kept once it commits, and the cursor is gone if it rolls back.
+    ///
+    /// The query is embedded in a `DECLARE` statement, so it must be a string rather than a prepared statement. It may
+    /// contain parameters, specified by `$n`, where `n` is the index of the parameter of the list provided, 1-indexed.
+    ///
+    /// The cursor is closed when dropped, or by `close_cursor`, which returns any error encountered.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use postgres::{Client, NoTls};
+    ///
+    /// # fn main() -> Result<(), postgres::Error> {
+    /// let mut client = Client::connect("host=localhost user=postgres", NoTls)?;
+    ///
+    /// let cursor = client.declare_cursor("SELECT id FROM events WHERE NOT processed", &[])?;
+    /// loop {
+    ///     let rows = client.fetch(&cursor, 100)?;
+    ///     if rows.is_empty() {
+    ///         break;
+    ///     }
+    ///
+    ///     let mut transaction = client.transaction()?;
+    ///     for row in rows {
+    ///         let id: i32 = row.get(0);
+    ///         transaction.execute("UPDATE events SET processed = true WHERE id = $1", &[&id])?;
+    ///     }
+    ///     transaction.commit()?;
+    /// }
+    /// client.close_cursor(cursor)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn declare_cursor(
+        &mut self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Cursor, Error> {
+        self.reconnect()?;
+        self.connection
+            .block_on(self.client.declare_cursor(query, params))
+    }
+
+    /// Fetches the next rows of a cursor, returning at most `count` of them.
+    ///
+    /// Returns fewer rows than requested once the end of the cursor is reached.
+    pub fn fetch(&mut self, cursor: &Cursor, count: i64) -> Result<Vec<Row>, Error> {
+        self.reconnect()?;
+        self.connection.block_on(self.client.fetch(cursor, count))
+    }
+
+    /// Closes a cursor.
+    ///
+    /// This is equivalent to `Cursor`'s `Drop` implementation, but provides any error encountered to the caller.
+    pub fn close_cursor(&mut self, cursor: Cursor) -> Result<(), Error> {
+        self.reconnect()?;
+        self.connection.block_on(self.client.close_cursor(cursor))
+    }
+
     /// Creates a new prepared statement.
     ///
     /// Prepared statements can be executed repeatedly, and may contain query parameters (indicated by `$1`, `$2`, etc),
@@ -281,6 +826,7 @@ impl Client {
     /// # }
     /// ```
     pub fn prepare(&mut self, query: &str) -> Result<Statement, Error> {
+        self.reconnect()?;
         self.connection.block_on(self.client.prepare(query))
     }
 
@@ -312,10 +858,153 @@ impl Client {
     /// # }
     /// ```
     pub fn prepare_typed(&mut self, query: &str, types: &[Type]) -> Result<Statement, Error> {
+        self.reconnect()?;
         self.connection
             .block_on(self.client.prepare_typed(query, types))
     }
 
+    /// Like `prepare_typed`, but prepares the unnamed statement rather than a statement of its own.
+    ///
+    /// The session has a single unnamed statement, which is replaced by the next query prepared into it, including
+    /// queries executed from a string and simple queries. Using the statement once it has been replaced returns an
+    /// error, so two unnamed statements can't be used concurrently. This avoids running out of statement names when
+    /// connected through a pooler such as PgBouncer in transaction mode.
+    pub fn prepare_unnamed(&mut self, query: &str, types: &[Type]) -> Result<Statement, Error> {
+        self.reconnect()?;
+        self.connection
+            .block_on(self.client.prepare_unnamed(query, types))
+    }
+
+    /// Prepares a statement again, updating its parameter and column types.
+    ///
+    /// See the documentation of `tokio_postgres::Statement::refresh` for details.
+    pub fn refresh_statement(&mut self, statement: &mut Statement) -> Result<(), Error> {
+        self.reconnect()?;
+        self.connection.block_on(statement.refresh(&self.client))
+    }
+
+    /// Returns the parameter and column types of a query, without executing it.
+    ///
+    /// Unlike `prepare`, this doesn't create a prepared statement on the server: the query is only parsed into the
+    /// unnamed statement, which is replaced by the next query executed from a string.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use postgres::{Client, NoTls};
+    ///
+    /// # fn main() -> Result<(), postgres::Error> {
+    /// let mut client = Client::connect("host=localhost user=postgres", NoTls)?;
+    ///
+    /// let description = client.describe("SELECT id, name FROM people WHERE id = $1")?;
+    /// for column in description.columns() {
+    ///     println!("{}: {}", column.name(), column.type_());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn describe(&mut self, query: &str) -> Result<StatementDescription, Error> {
+        self.reconnect()?;
+        self.connection.block_on(self.client.describe(query))
+    }
+
+    /// Like `describe`, but allows the types of query parameters to be explicitly specified.
+    ///
+    /// The list of types may be smaller than the number of parameters - the types of the remaining parameters will be
+    /// inferred.
+    pub fn describe_typed(
+        &mut self,
+        query: &str,
+        parameter_types: &[Type],
+    ) -> Result<StatementDescription, Error> {
+        self.reconnect()?;
+        self.connection
+            .block_on(self.client.describe_typed(query, parameter_types))
+    }
+
+    /// Looks up the type with the given OID in the server's catalog.
+    ///
+    /// This resolves a type fully, including the variants of an enum, the fields of a composite type and the types
+    /// arrays, ranges and domains are made of. Resolved types are cached by the client, so each is only looked up
+    /// once. Statements resolve the types of their parameters and columns the same way when prepared, unless disabled
+    /// with `Config::resolve_types`, in which case types which aren't built into Postgres are reported as `TEXT`.
+    ///
+    /// An error is returned if no type has this OID.
+    pub fn resolve_type(&mut self, oid: Oid) -> Result<Type, Error> {
+        self.reconnect()?;
+        self.connection.block_on(self.client.resolve_type(oid))
+    }
+
+    /// Adds types to those known to the client, as if they had been resolved from the server's catalog.
+    ///
+    /// Parameters and columns of these types are reported with them without looking them up, which lets enums,
+    /// composites and other user-defined types be decoded when `Config::resolve_types` is disabled. The OIDs of the
+    /// types must match those assigned by the server, and the types they are made of are registered along with them.
+    /// Types built into Postgres are ignored, and types can also be registered for every connection with
+    /// `Config::register_type`.
+    ///
+    /// Only statements prepared after this call are affected.
+    pub fn register_types<I>(&mut self, types: I)
+    where
+        I: IntoIterator<Item = Type>,
+    {
+        let types = types.into_iter().collect::<Vec<_>>();
+        if let Some(reconnect) = &mut self.reconnect {
+            reconnect.types.extend(types.iter().cloned());
+        }
+        self.client.register_types(types);
+    }
+
+    /// Creates an empty large object, returning its OID.
+    ///
+    /// See the [`large_object`](crate::large_object) module for details.
+    pub fn create_large_object(&mut self) -> Result<Oid, Error> {
+        self.reconnect()?;
+        self.connection.block_on(self.client.create_large_object())
+    }
+
+    /// Deletes the large object with the given OID.
+    pub fn unlink_large_object(&mut self, oid: Oid) -> Result<(), Error> {
+        self.reconnect()?;
+        self.connection
+            .block_on(self.client.unlink_large_object(oid))
+    }
+
+    /// Returns the tables of a schema, ordered by name.
+    ///
+    /// Views, materialized views and foreign tables are included, as detailed in the [`schema`](crate::schema)
+    /// module.
+    pub fn tables(&mut self, schema: &str) -> Result<Vec<Table>, Error> {
+        self.reconnect()?;
+        self.connection.block_on(self.client.tables(schema))
+    }
+
+    /// Returns the columns of the table with the given OID, ordered by their number.
+    ///
+    /// Their types are resolved like with `resolve_type`.
+    pub fn table_columns(&mut self, table: Oid) -> Result<Vec<TableColumn>, Error> {
+        self.reconnect()?;
+        self.connection.block_on(self.client.table_columns(table))
+    }
+
+    /// Returns the primary key of the table with the given OID, or `None` if it has none.
+    pub fn primary_key(&mut self, table: Oid) -> Result<Option<PrimaryKey>, Error> {
+        self.reconnect()?;
+        self.connection.block_on(self.client.primary_key(table))
+    }
+
+    /// Returns the foreign keys of the table with the given OID, ordered by name.
+    pub fn foreign_keys(&mut self, table: Oid) -> Result<Vec<ForeignKey>, Error> {
+        self.reconnect()?;
+        self.connection.block_on(self.client.foreign_keys(table))
+    }
+
+    /// Returns the indexes of the table with the given OID, ordered by name.
+    pub fn indexes(&mut self, table: Oid) -> Result<Vec<Index>, Error> {
+        self.reconnect()?;
+        self.connection.block_on(self.client.indexes(table))
+    }
+
     /// Executes a `COPY FROM STDIN` statement, returning the number of rows created.
     ///
     /// The `query` argument can either be a `Statement`, or a raw query string. The data in the provided reader is
@@ -343,6 +1032,7 @@ impl Client {
     where
         T: ?Sized + ToStatement,
     {
+        self.reconnect()?;
         let sink = self.connection.block_on(self.client.copy_in(query))?;
         Ok(CopyInWriter::new(self.connection.as_ref(), sink))
     }
@@ -371,10 +1061,80 @@ impl Client {
     where
         T: ?Sized + ToStatement,
     {
+        self.reconnect()?;
         let stream = self.connection.block_on(self.client.copy_out(query))?;
         Ok(CopyOutReader::new(self.connection.as_ref(), stream))
     }
 
+    /// Executes a `COPY TO STDOUT` statement, writing the resulting data into a writer and returning the number of
+    /// bytes written.
+    ///
+    /// Up to `buffer_size` bytes of data are buffered between the writes to the writer, which is flushed once the copy
+    /// completes. The data is streamed rather than loaded into memory, so tables of any size can be written out, for
+    /// example to a file.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use postgres::{Client, NoTls};
+    /// use std::fs::File;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut client = Client::connect("host=localhost user=postgres", NoTls)?;
+    ///
+    /// let file = File::create("people.csv")?;
+    /// client.copy_to_writer("COPY people TO stdout (FORMAT csv)", file, 64 * 1024)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn copy_to_writer<T, W>(
+        &mut self,
+        query: &T,
+        writer: W,
+        buffer_size: usize,
+    ) -> Result<u64, Error>
+    where
+        T: ?Sized + ToStatement,
+        W: Write,
+    {
+        let reader = self.copy_out(query)?;
+        copy_out_reader::copy_to_writer(reader, writer, buffer_size)
+    }
+
+    /// Executes a `COPY FROM STDIN` statement with the data of a reader, returning the number of rows created.
+    ///
+    /// The reader is read in chunks of `buffer_size` bytes, each sent to the server as it is read, and the copy is
+    /// completed once the reader is exhausted. If the reader returns an error, the copy is aborted and the error is
+    /// returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use postgres::{Client, NoTls};
+    /// use std::fs::File;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut client = Client::connect("host=localhost user=postgres", NoTls)?;
+    ///
+    /// let file = File::open("people.csv")?;
+    /// let rows = client.copy_from_reader("COPY people FROM stdin (FORMAT csv)", file, 64 * 1024)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn copy_from_reader<T, R>(
+        &mut self,
+        query: &T,
+        reader: R,
+        buffer_size: usize,
+    ) -> Result<u64, Error>
+    where
+        T: ?Sized + ToStatement,
+        R: Read,
+    {
+        let writer = self.copy_in(query)?;
+        copy_in_writer::copy_from_reader(writer, reader, buffer_size)
+    }
+
     /// Executes a sequence of SQL statements using the simple query protocol.
     ///
     /// Statements should be separated by semicolons. If an error occurs, execution of the sequence will stop at that
@@ -390,7 +1150,9 @@ impl Client {
     /// Prepared statements should be used for any query which contains user-specified data, as they provided the
     /// functionality to safely embed that data in the request. Do not form statements via string concatenation and pass
     /// them to this method!
+    /// If values must be embedded in a statement, quote them with the [`escape`](crate::escape) module.
     pub fn simple_query(&mut self, query: &str) -> Result<Vec<SimpleQueryMessage>, Error> {
+        self.reconnect()?;
         self.connection.block_on(self.client.simple_query(query))
     }
 
@@ -398,13 +1160,16 @@ impl Client {
     ///
     /// If the specified timeout is reached before the backend responds, an error will be returned.
     pub fn is_valid(&mut self, timeout: Duration) -> Result<(), Error> {
+        self.reconnect()?;
         let inner_client = &self.client;
+        let delay = self.connection.delay(timeout);
         self.connection.block_on(async {
             let trivial_query = inner_client.simple_query("");
-            tokio::time::timeout(timeout, trivial_query)
-                .await
-                .map_err(|_| Error::__private_api_timeout())?
-                .map(|_| ())
+            pin_mut!(trivial_query);
+            match future::select(trivial_query, delay).await {
+                Either::Left((result, _)) => result.map(|_| ()),
+                Either::Right(((), _)) => Err(Error::__private_api_timeout()),
+            }
         })
     }
 
@@ -418,10 +1183,132 @@ impl Client {
     /// Prepared statements should be use for any query which contains user-specified data, as they provided the
     /// functionality to safely embed that data in the request. Do not form statements via string concatenation and pass
     /// them to this method!
+    /// If values must be embedded in a statement, quote them with the [`escape`](crate::escape) module.
     pub fn batch_execute(&mut self, query: &str) -> Result<(), Error> {
+        self.reconnect()?;
         self.connection.block_on(self.client.batch_execute(query))
     }
 
+    /// Executes a sequence of SQL statements with parameters in a single round trip, returning their command tags.
+    ///
+    /// The statements share the parameters and are executed in a single implicit transaction, as detailed in the
+    /// `tokio_postgres` documentation.
+    pub fn batch_execute_params<P, I>(
+        &mut self,
+        query: &str,
+        params: I,
+    ) -> Result<Vec<String>, Error>
+    where
+        P: ToText,
+        I: IntoIterator<Item = P>,
+    {
+        self.reconnect()?;
+        self.connection
+            .block_on(self.client.batch_execute_params(query, params))
+    }
+
+    /// Sends a notification to the sessions listening on a channel.
+    ///
+    /// The notification is sent with a `NOTIFY` command using the simple query protocol, in which the channel is
+    /// quoted and the payload escaped, so they don't need to be sanitized. The channel is matched exactly, like the
+    /// channels of a [`Listener`](crate::Listener).
+    ///
+    /// Inside of a transaction, the notification is only delivered once the transaction commits.
+    pub fn notify(&mut self, channel: &str, payload: &str) -> Result<(), Error> {
+        self.reconnect()?;
+        self.connection
+            .block_on(self.client.notify(channel, payload))
+    }
+
+    /// Stops listening on a channel.
+    ///
+    /// The channel is passed to `UNLISTEN` as a quoted identifier, so it is matched exactly.
+    pub fn unlisten(&mut self, channel: &str) -> Result<(), Error> {
+        self.reconnect()?;
+        self.connection.block_on(self.client.unlisten(channel))
+    }
+
+    /// Stops listening on all channels.
+    pub fn unlisten_all(&mut self) -> Result<(), Error> {
+        self.reconnect()?;
+        self.connection.block_on(self.client.unlisten_all())
+    }
+
+    /// Sets the value of a runtime parameter of the session, like `SET`.
+    ///
+    /// The name and value are passed to `set_config` as parameters rather than embedded into a `SET` command, so they
+    /// don't need to be quoted or escaped. If `local` is `true`, the value only applies until the end of the current
+    /// transaction, like with `SET LOCAL`.
+    pub fn set_parameter(&mut self, name: &str, value: &str, local: bool) -> Result<(), Error> {
+        self.reconnect()?;
+        self.connection
+            .block_on(self.client.set_parameter(name, value, local))
+    }
+
+    /// Returns the current value of a runtime parameter of the session, like `SHOW`.
+    ///
+    /// Unlike `parameter`, this queries the server, so it works for all parameters rather than only the reported ones.
+    /// Returns an error if the parameter doesn't exist.
+    pub fn get_parameter(&mut self, name: &str) -> Result<String, Error> {
+        self.reconnect()?;
+        self.connection.block_on(self.client.get_parameter(name))
+    }
+
+    /// Explains a query, returning the plan chosen by the server.
+    ///
+    /// Note that when the `analyze` option is enabled the query is actually executed, with all of its side effects.
+    ///
+    /// Requires the `with-serde_json-1` Cargo feature.
+    #[cfg(feature = "with-serde_json-1")]
+    pub fn explain(
+        &mut self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+        options: ExplainOptions,
+    ) -> Result<Plan, Error> {
+        self.reconnect()?;
+        self.connection
+            .block_on(self.client.explain(query, params, options))
+    }
+
+    /// Applies the migrations which haven't been applied to the database yet.
+    ///
+    /// See the [`migrations`](crate::migrations) module for details. The functions of the migrations are called with
+    /// the underlying asynchronous transaction.
+    ///
+    /// Requires the `migrations` Cargo feature.
+    #[cfg(feature = "migrations")]
+    pub fn run_migrations(&mut self, migrations: &Migrations) -> Result<Vec<i64>, Error> {
+        self.reconnect()?;
+        self.connection.block_on(migrations.run(&mut self.client))
+    }
+
+    /// Creates a pipeline of statements to be sent to the server at once.
+    ///
+    /// See the [`pipeline`](crate::pipeline) module for details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use postgres::{Client, NoTls};
+    ///
+    /// # fn main() -> Result<(), postgres::Error> {
+    /// let mut client = Client::connect("host=localhost user=postgres", NoTls)?;
+    ///
+    /// let mut pipeline = client.pipeline();
+    /// pipeline
+    ///     .execute("INSERT INTO foo (bar) VALUES ($1)", &[&1i32])
+    ///     .query("SELECT bar FROM foo", &[]);
+    /// for result in pipeline.run()? {
+    ///     println!("{:?}", result?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn pipeline(&mut self) -> Pipeline<'_> {
+        Pipeline::new(self.connection.as_ref(), self.client.pipeline())
+    }
+
     /// Begins a new database transaction.
     ///
     /// The transaction will roll back by default - use the `commit` method to commit it.
@@ -443,10 +1330,45 @@ impl Client {
     /// # }
     /// ```
     pub fn transaction(&mut self) -> Result<Transaction<'_>, Error> {
+        self.reconnect()?;
         let transaction = self.connection.block_on(self.client.transaction())?;
         Ok(Transaction::new(self.connection.as_ref(), transaction))
     }
 
+    /// Runs a closure in a new database transaction.
+    ///
+    /// The transaction is committed if the closure returns `Ok`, and rolled back if it returns an error or panics. The
+    /// closure can nest transactions within it with `Transaction::with_savepoint`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use postgres::{Client, NoTls};
+    ///
+    /// # fn main() -> Result<(), postgres::Error> {
+    /// let mut client = Client::connect("host=localhost user=postgres", NoTls)?;
+    ///
+    /// let updated = client.with_transaction(|transaction| {
+    ///     let updated = transaction.execute("UPDATE foo SET bar = 10", &[])?;
+    ///     transaction.with_savepoint("audit", |transaction| {
+    ///         transaction.execute("INSERT INTO audit (event) VALUES ('update')", &[])
+    ///     })?;
+    ///     Ok::<_, postgres::Error>(updated)
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_transaction<F, T, E>(&mut self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&mut Transaction<'_>) -> Result<T, E>,
+        E: From<Error>,
+    {
+        let mut transaction = self.transaction()?;
+        let value = f(&mut transaction)?;
+        transaction.commit()?;
+        Ok(value)
+    }
+
     /// Returns a builder for a transaction with custom settings.
     ///
     /// Unlike the `transaction` method, the builder can be used to control the transaction's isolation level and other
@@ -521,11 +1443,153 @@ impl Client {
         CancelToken::new(self.client.cancel_token())
     }
 
+    /// Requests cancellation of the query running on this connection, waiting at most for the given duration for the
+    /// request to be sent.
+    ///
+    /// This is a shorthand for `cancel_token().cancel_query_timeout(tls, timeout)`. Since the client is borrowed
+    /// mutably while a query is running, a `CancelToken` has to be used to cancel it from another thread; this method
+    /// is useful to cancel a query which is still running on the server after its result was abandoned, such as once
+    /// a `RowIter` has been dropped.
+    pub fn cancel_query<T>(&self, tls: T, timeout: Duration) -> Result<(), Error>
+    where
+        T: MakeTlsConnect<Socket>,
+    {
+        self.cancel_token().cancel_query_timeout(tls, timeout)
+    }
+
+    /// Returns the value of a runtime parameter reported by the server.
+    ///
+    /// The server reports the values of a fixed set of parameters, such as `server_version` or `TimeZone`, when the
+    /// connection is established and whenever they change afterwards. The returned value is the latest one received
+    /// by the connection. Parameters which aren't reported return `None`; use `SHOW` to query those.
+    pub fn parameter(&self, name: &str) -> Option<String> {
+        self.client.parameter(name)
+    }
+
+    /// Returns the version of the server, as reported by the `server_version` parameter.
+    ///
+    /// `None` is returned if the parameter isn't reported or can't be parsed, in which case its raw value can still be
+    /// retrieved with `parameter`.
+    pub fn server_version(&self) -> Option<ServerVersion> {
+        self.client.server_version()
+    }
+
+    /// Returns the encoding of the database, as reported by the `server_encoding` parameter.
+    pub fn server_encoding(&self) -> Option<String> {
+        self.client.server_encoding()
+    }
+
+    /// Returns the encoding used by the connection, as reported by the `client_encoding` parameter.
+    pub fn client_encoding(&self) -> Option<String> {
+        self.client.client_encoding()
+    }
+
+    /// Returns the time zone of the session, as reported by the `TimeZone` parameter.
+    pub fn timezone(&self) -> Option<String> {
+        self.client.timezone()
+    }
+
+    /// Returns the display format of dates and times, as reported by the `DateStyle` parameter.
+    pub fn date_style(&self) -> Option<String> {
+        self.client.date_style()
+    }
+
+    /// Returns the display format of intervals, as reported by the `IntervalStyle` parameter.
+    pub fn interval_style(&self) -> Option<String> {
+        self.client.interval_style()
+    }
+
+    /// Returns the application name of the session, as reported by the `application_name` parameter.
+    pub fn application_name(&self) -> Option<String> {
+        self.client.application_name()
+    }
+
+    /// Returns whether the session user is a superuser, as reported by the `is_superuser` parameter.
+    pub fn is_superuser(&self) -> Option<bool> {
+        self.client.is_superuser()
+    }
+
+    /// Returns whether backslashes are treated literally in string literals, as reported by the
+    /// `standard_conforming_strings` parameter.
+    pub fn standard_conforming_strings(&self) -> Option<bool> {
+        self.client.standard_conforming_strings()
+    }
+
+    /// Returns the process ID of the backend serving the connection, as sent by the server during startup.
+    pub fn backend_pid(&self) -> i32 {
+        self.client.backend_pid()
+    }
+
+    /// Returns the secret key sent by the server during startup, which is required to cancel the requests of the
+    /// connection.
+    pub fn backend_secret_key(&self) -> i32 {
+        self.client.backend_secret_key()
+    }
+
+    /// Returns how the connection was authenticated and encrypted.
+    pub fn connection_security(&self) -> ConnectionSecurity {
+        self.client.connection_security()
+    }
+
+    /// Registers an interceptor invoked around the operations performed by the client.
+    ///
+    /// Interceptors are invoked in the order they were registered. See the [`interceptor`](crate::interceptor) module
+    /// for details.
+    pub fn add_interceptor(&mut self, interceptor: Arc<dyn Interceptor>) {
+        if let Some(reconnect) = &mut self.reconnect {
+            reconnect.interceptors.push(interceptor.clone());
+        }
+        self.client.add_interceptor(interceptor);
+    }
+
+    /// Returns the statistics of the statements executed by the client.
+    ///
+    /// Statistics are only collected if enabled with [`Config::statement_stats`], and this returns an empty list
+    /// otherwise. See the [`stats`](crate::stats) module for details.
+    pub fn statement_stats(&self) -> Vec<StatementStats> {
+        self.client.statement_stats()
+    }
+
+    /// Discards the statistics collected so far by the client.
+    pub fn reset_statement_stats(&self) {
+        self.client.reset_statement_stats()
+    }
+
+    /// Returns the transaction status of the session.
+    ///
+    /// Connection pools can combine it with `is_valid` to only reuse healthy connections which aren't in the middle of
+    /// a transaction.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use postgres::{Client, TransactionStatus};
+    /// use std::time::Duration;
+    ///
+    /// fn is_reusable(client: &mut Client) -> bool {
+    ///     !client.is_closed()
+    ///         && client.transaction_status() == TransactionStatus::Idle
+    ///         && client.is_valid(Duration::from_secs(1)).is_ok()
+    /// }
+    /// ```
+    pub fn transaction_status(&self) -> TransactionStatus {
+        self.client.transaction_status()
+    }
+
     /// Determines if the client's connection has already closed.
     ///
     /// If this returns `true`, the client is no longer usable.
     pub fn is_closed(&self) -> bool {
-        self.client.is_closed()
+        self.client.is_closed() || self.connection.is_closed()
+    }
+
+    /// Returns the number of times the connection has been reestablished.
+    ///
+    /// This is always 0 unless the client was created with [`Config::connect_with_reconnect`].
+    pub fn reconnects(&self) -> u64 {
+        self.reconnect
+            .as_ref()
+            .map_or(0, |reconnect| reconnect.reconnects)
     }
 
     /// Closes the client's connection to the server.
@@ -536,6 +1600,31 @@ impl Client {
         self.close_inner()
     }
 
+    /// Reestablishes the connection if it is closed and the client was created with `connect_with_reconnect`.
+    fn reconnect(&mut self) -> Result<(), Error> {
+        if !self.is_closed() {
+            return Ok(());
+        }
+        let reconnect = match &mut self.reconnect {
+            Some(reconnect) => reconnect,
+            None => return Ok(()),
+        };
+
+        let mut client = (reconnect.connect)()?;
+        for interceptor in &reconnect.interceptors {
+            client.client.add_interceptor(interceptor.clone());
+        }
+        client
+            .client
+            .register_types(reconnect.types.iter().cloned());
+        reconnect.reconnects += 1;
+
+        // The old connection is closed when the new client is dropped.
+        mem::swap(&mut self.connection, &mut client.connection);
+        mem::swap(&mut self.client, &mut client.client);
+        Ok(())
+    }
+
     fn close_inner(&mut self) -> Result<(), Error> {
         self.client.__private_api_close();
 
@@ -1,21 +1,28 @@
 //! Connection configuration.
 
+use crate::blocking;
 use crate::connection::Connection;
 use crate::Client;
 use log::info;
-use std::fmt;
+use std::future::Future;
 use std::net::IpAddr;
 use std::path::Path;
+use std::pin::Pin;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
+use std::{error, fmt};
 use tokio::runtime;
 #[doc(inline)]
 pub use tokio_postgres::config::{
-    ChannelBinding, Host, LoadBalanceHosts, SslMode, TargetSessionAttrs,
+    ChannelBinding, GssEncMode, Host, HostCredentials, LifecycleEvent, LoadBalanceHosts,
+    ReplicationMode, SlowStatement, SslMode, TargetSessionAttrs,
 };
 use tokio_postgres::error::DbError;
+use tokio_postgres::gss::GssContext;
+use tokio_postgres::metrics::Metrics;
 use tokio_postgres::tls::{MakeTlsConnect, TlsConnect};
+use tokio_postgres::types::Type;
 use tokio_postgres::{Error, Socket};
 
 /// Connection configuration.
@@ -34,12 +41,33 @@ use tokio_postgres::{Error, Socket};
 /// * `dbname` - The name of the database to connect to. Defaults to the username.
 /// * `options` - Command line options used to configure the server.
 /// * `application_name` - Sets the `application_name` parameter on the server.
+/// * `fallback_application_name` - Sets the `application_name` parameter on the server if `application_name` is not
+///     set. With the `default-application-name` feature enabled, defaults to the name of the running executable.
 /// * `sslmode` - Controls usage of TLS. If set to `disable`, TLS will not be used. If set to `prefer`, TLS will be used
-///     if available, but not used otherwise. If set to `require`, TLS will be forced to be used. Defaults to `prefer`.
+///     if available, but not used otherwise. If set to `require`, TLS will be forced to be used. If set to `verify-ca`
+///     or `verify-full`, TLS will be forced to be used and the server's certificate will be verified, along with the
+///     host it was issued for with `verify-full`. Otherwise, verification is left to the TLS connector. Defaults to
+///     `prefer`.
+/// * `sslrootcert` - The path to a file containing the PEM-encoded certificates of the certificate authorities trusted
+///     to issue the server's certificate, in place of the ones configured on the TLS connector. If set, the server's
+///     certificate is verified as with `verify-ca`, unless `sslmode` is `verify-full`.
+/// * `gssencmode` - Controls usage of GSSAPI transport encryption. If set to `disable`, GSSAPI encryption will not be
+///     used. If set to `prefer`, GSSAPI encryption will be used if available, but not used otherwise. If set to
+///     `require`, GSSAPI encryption will be forced to be used. A GSSAPI encrypted session takes precedence over TLS.
+///     GSSAPI encryption is only available if a context provider has been configured with
+///     [`Config::gss_context_provider`]; without one, `prefer` behaves like `disable`. Defaults to `prefer`.
+/// * `sslpassword` - The password used to decrypt the client's TLS private key. This is not used by this crate itself,
+///     but is made available to TLS connectors which load client keys.
+/// * `sslsni` - Controls the use of the TLS Server Name Indication extension. A value of 0 disables SNI and nonzero
+///     integers enable it. Defaults to 1.
+/// * `sslservername` - The server name used for the TLS handshake, both for SNI and to verify the server's
+///     certificate, in place of the host. This is useful when connecting through a TLS-terminating proxy whose address
+///     differs from the name of the server it presents.
 /// * `host` - The host to connect to. On Unix platforms, if the host starts with a `/` character it is treated as the
-///     path to the directory containing Unix domain sockets. Otherwise, it is treated as a hostname. Multiple hosts
-///     can be specified, separated by commas. Each host will be tried in turn when connecting. Required if connecting
-///     with the `connect` method.
+///     path to the directory containing Unix domain sockets, or to a socket file itself. On Linux, if the host starts
+///     with a `@` character it is treated as a directory in the abstract socket namespace. Otherwise, it is treated as
+///     a hostname. Multiple hosts can be specified, separated by commas. Each host will be tried in turn when
+///     connecting. Required if connecting with the `connect` method.
 /// * `hostaddr` - Numeric IP address of host to connect to. This should be in the standard IPv4 address format,
 ///     e.g., 172.28.40.9. If your machine supports IPv6, you can also use those addresses.
 ///     If this parameter is not specified, the value of `host` will be looked up to find the corresponding IP address,
@@ -56,6 +84,9 @@ use tokio_postgres::{Error, Socket};
 /// * `port` - The port to connect to. Multiple ports can be specified, separated by commas. The number of ports must be
 ///     either 1, in which case it will be used for all hosts, or the same as the number of hosts. Defaults to 5432 if
 ///     omitted or the empty string.
+/// * `requirepeer` - The operating system user the server must be running as when connecting with a Unix socket. The
+///     credentials of the server process are checked before the startup packet is sent, and the connection fails if
+///     they don't match. This is only supported on Linux, macOS and BSD systems, and is ignored for TCP connections.
 /// * `connect_timeout` - The time limit in seconds applied to each socket-level connection attempt. Note that hostnames
 ///     can resolve to multiple IP addresses, and this limit is applied to each address. Defaults to no timeout.
 /// * `tcp_user_timeout` - The time limit that transmitted data may remain unacknowledged before a connection is forcibly closed.
@@ -71,7 +102,10 @@ use tokio_postgres::{Error, Socket};
 ///     This option is ignored when connecting with Unix sockets.
 /// * `target_session_attrs` - Specifies requirements of the session. If set to `read-write`, the client will check that
 ///     the `transaction_read_write` session parameter is set to `on`. This can be used to connect to the primary server
-///     in a database cluster as opposed to the secondary read-only mirrors. Defaults to `all`.
+///     in a database cluster as opposed to the secondary read-only mirrors. If set to `read-only`, the parameter must
+///     instead be set to `off`. If set to `primary` or `standby`, the client will check whether the server is in hot
+///     standby mode with `pg_is_in_recovery()`. If set to `prefer-standby`, the hosts are first searched for a standby,
+///     and then tried again accepting any server. Defaults to `all`.
 /// * `channel_binding` - Controls usage of channel binding in the authentication process. If set to `disable`, channel
 ///     binding will not be used. If set to `prefer`, channel binding will be used if available, but not used otherwise.
 ///     If set to `require`, the authentication process will fail if channel binding is not used. Defaults to `prefer`.
@@ -81,6 +115,13 @@ use tokio_postgres::{Error, Socket};
 ///     `disable`, hosts and addresses will be tried in the order provided. If set to `random`, hosts will be tried
 ///     in a random order, and the IP addresses resolved from a hostname will also be tried in a random order. Defaults
 ///     to `disable`.
+/// * `replication` - Starts the session in replication mode. If set to `true`, the session is in physical replication
+///     mode, and only accepts replication commands. If set to `database`, the session is in logical replication mode
+///     on the database it is connected to, and also accepts SQL commands. Defaults to `false`.
+/// * `log_slow_statements` - Logs statements which take longer than this to execute, measured client-side. The value
+///     is an integer with an optional unit of `us`, `ms`, `s`, `min` or `h`, and is in milliseconds if no unit is
+///     given, as with the server's `log_min_duration_statement` parameter. A negative value disables logging, which
+///     is the default.
 ///
 /// ## Examples
 ///
@@ -128,12 +169,16 @@ use tokio_postgres::{Error, Socket};
 pub struct Config {
     config: tokio_postgres::Config,
     notice_callback: Arc<dyn Fn(DbError) + Send + Sync>,
+    session_setup: Option<Arc<SessionSetup>>,
 }
 
+type SessionSetup = dyn Fn(&mut Client) -> Result<(), Error> + Send + Sync;
+
 impl fmt::Debug for Config {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         fmt.debug_struct("Config")
             .field("config", &self.config)
+            .field("session_setup", &self.session_setup.is_some())
             .finish()
     }
 }
@@ -179,6 +224,27 @@ impl Config {
         self.config.get_password()
     }
 
+    /// Sets an asynchronous callback which produces the password to authenticate with.
+    ///
+    /// The callback is invoked during authentication of every connection attempt, and only if the server actually
+    /// requests a password. It takes precedence over a password configured with the `password` method.
+    pub fn password_provider<F, Fut, T, E>(&mut self, provider: F) -> &mut Config
+    where
+        F: Fn() -> Fut + Sync + Send + 'static,
+        Fut: Future<Output = Result<T, E>> + Send + 'static,
+        T: AsRef<[u8]>,
+        E: Into<Box<dyn error::Error + Sync + Send>>,
+    {
+        self.config.password_provider(provider);
+        self
+    }
+
+    /// Reports whether a password callback has been configured with the
+    /// `password_provider` method.
+    pub fn has_password_provider(&self) -> bool {
+        self.config.has_password_provider()
+    }
+
     /// Sets the name of the database to connect to.
     ///
     /// Defaults to the user.
@@ -205,6 +271,22 @@ impl Config {
         self.config.get_options()
     }
 
+    /// Appends a parameter to the startup message sent to the server, such as a runtime parameter of the session or a
+    /// routing hint understood by a connection pooler.
+    ///
+    /// The keys set by the client itself (`user`, `database`, `options`, `application_name`, `client_encoding` and
+    /// `replication`) must be configured through their own methods instead, and connecting will fail if they are used
+    /// here.
+    pub fn startup_param(&mut self, key: &str, value: &str) -> &mut Config {
+        self.config.startup_param(key, value);
+        self
+    }
+
+    /// Gets the startup parameters set with the `startup_param` method, in the order they have been set.
+    pub fn get_startup_params(&self) -> &[(String, String)] {
+        self.config.get_startup_params()
+    }
+
     /// Sets the value of the `application_name` runtime parameter.
     pub fn application_name(&mut self, application_name: &str) -> &mut Config {
         self.config.application_name(application_name);
@@ -217,6 +299,21 @@ impl Config {
         self.config.get_application_name()
     }
 
+    /// Sets the value of the `application_name` runtime parameter to use if one is not set with the
+    /// `application_name` method.
+    ///
+    /// With the `default-application-name` feature enabled, defaults to the name of the running executable.
+    pub fn fallback_application_name(&mut self, fallback_application_name: &str) -> &mut Config {
+        self.config
+            .fallback_application_name(fallback_application_name);
+        self
+    }
+
+    /// Gets the fallback value of the `application_name` runtime parameter, if one has been set.
+    pub fn get_fallback_application_name(&self) -> Option<&str> {
+        self.config.get_fallback_application_name()
+    }
+
     /// Sets the SSL configuration.
     ///
     /// Defaults to `prefer`.
@@ -230,11 +327,134 @@ impl Config {
         self.config.get_ssl_mode()
     }
 
+    /// Sets the password used to decrypt the client's TLS private key.
+    ///
+    /// This is not used by this crate itself, but can be passed on to the TLS connector loading the key.
+    pub fn ssl_password<T>(&mut self, ssl_password: T) -> &mut Config
+    where
+        T: AsRef<[u8]>,
+    {
+        self.config.ssl_password(ssl_password);
+        self
+    }
+
+    /// Gets the password used to decrypt the client's TLS private key, if one has been configured with the
+    /// `ssl_password` method.
+    pub fn get_ssl_password(&self) -> Option<&[u8]> {
+        self.config.get_ssl_password()
+    }
+
+    /// Controls the use of the TLS Server Name Indication extension.
+    ///
+    /// TLS connectors which can't disable SNI ignore this setting. Defaults to `true`.
+    pub fn ssl_sni(&mut self, ssl_sni: bool) -> &mut Config {
+        self.config.ssl_sni(ssl_sni);
+        self
+    }
+
+    /// Reports whether the TLS Server Name Indication extension is used.
+    pub fn get_ssl_sni(&self) -> bool {
+        self.config.get_ssl_sni()
+    }
+
+    /// Sets the server name used for the TLS handshake.
+    ///
+    /// The name is sent with SNI and used to verify the server's certificate, in place of the host being connected
+    /// to. This allows connecting through a TLS-terminating proxy, or to an IP address, while still verifying the
+    /// certificate of the server behind it.
+    pub fn ssl_server_name(&mut self, ssl_server_name: &str) -> &mut Config {
+        self.config.ssl_server_name(ssl_server_name);
+        self
+    }
+
+    /// Gets the server name used for the TLS handshake, if one has been configured with the `ssl_server_name`
+    /// method.
+    pub fn get_ssl_server_name(&self) -> Option<&str> {
+        self.config.get_ssl_server_name()
+    }
+
+    /// Sets the path to a file containing the certificates of the certificate authorities trusted to issue the
+    /// server's certificate.
+    ///
+    /// The certificates replace the ones configured on the TLS connector, and the server's certificate is verified
+    /// against them even if the SSL mode is `prefer` or `require`.
+    pub fn ssl_root_cert<T>(&mut self, ssl_root_cert: T) -> &mut Config
+    where
+        T: AsRef<Path>,
+    {
+        self.config.ssl_root_cert(ssl_root_cert);
+        self
+    }
+
+    /// Gets the path to the trusted certificate authorities, if one has been configured with the `ssl_root_cert`
+    /// method.
+    pub fn get_ssl_root_cert(&self) -> Option<&Path> {
+        self.config.get_ssl_root_cert()
+    }
+
+    /// Sets the GSSAPI encryption configuration.
+    ///
+    /// Defaults to `prefer`.
+    pub fn gss_enc_mode(&mut self, gss_enc_mode: GssEncMode) -> &mut Config {
+        self.config.gss_enc_mode(gss_enc_mode);
+        self
+    }
+
+    /// Gets the GSSAPI encryption configuration.
+    pub fn get_gss_enc_mode(&self) -> GssEncMode {
+        self.config.get_gss_enc_mode()
+    }
+
+    /// Sets a callback creating the GSSAPI security contexts used to encrypt and authenticate connections.
+    ///
+    /// The callback is passed the hostname of the server being connected to, and is invoked for each connection
+    /// attempt before GSSAPI encryption is requested from the server. If it fails, for example because no credentials
+    /// are available, the connection proceeds unencrypted by GSSAPI unless `gssencmode` is `require`.
+    ///
+    /// It is invoked again if the server requests GSSAPI or SSPI authentication, which fails if no context can be
+    /// created.
+    pub fn gss_context_provider<F, C, E>(&mut self, provider: F) -> &mut Config
+    where
+        F: Fn(&str) -> Result<C, E> + Sync + Send + 'static,
+        C: GssContext + 'static,
+        E: Into<Box<dyn error::Error + Sync + Send>>,
+    {
+        self.config.gss_context_provider(provider);
+        self
+    }
+
+    /// Returns `true` if a GSSAPI context provider has been configured.
+    pub fn has_gss_context_provider(&self) -> bool {
+        self.config.has_gss_context_provider()
+    }
+
+    /// Sets a callback producing [sqlcommenter] tags which are appended as a comment to the SQL of outgoing queries.
+    ///
+    /// The callback is invoked whenever a statement is prepared or a simple query is sent. This allows the trace
+    /// context of the current span to be propagated to the server, for example by returning a `traceparent` tag, so
+    /// that server-side logs and `pg_stat_statements` can be correlated with distributed traces. Tags are sorted and
+    /// URL-encoded, and queries which already contain a comment are left unchanged.
+    ///
+    /// [sqlcommenter]: https://google.github.io/sqlcommenter/spec/
+    pub fn sqlcommenter<F>(&mut self, commenter: F) -> &mut Config
+    where
+        F: Fn() -> Vec<(String, String)> + Sync + Send + 'static,
+    {
+        self.config.sqlcommenter(commenter);
+        self
+    }
+
+    /// Returns `true` if an sqlcommenter callback has been configured.
+    pub fn has_sqlcommenter(&self) -> bool {
+        self.config.has_sqlcommenter()
+    }
+
     /// Adds a host to the configuration.
     ///
     /// Multiple hosts can be specified by calling this method multiple times, and each will be tried in order. On Unix
-    /// systems, a host starting with a `/` is interpreted as a path to a directory containing Unix domain sockets.
-    /// There must be either no hosts, or the same number of hosts as hostaddrs.
+    /// systems, a host starting with a `/` is interpreted as a path to a directory containing Unix domain sockets, or
+    /// to a socket file. On Linux, a host starting with a `@` is interpreted as a directory in the abstract socket
+    /// namespace. There must be either no hosts, or the same number of hosts as hostaddrs.
     pub fn host(&mut self, host: &str) -> &mut Config {
         self.config.host(host);
         self
@@ -252,7 +472,8 @@ impl Config {
 
     /// Adds a Unix socket host to the configuration.
     ///
-    /// Unlike `host`, this method allows non-UTF8 paths.
+    /// The path is either a directory containing the server's socket, whose file name is derived from the port, or the
+    /// socket file itself. Unlike `host`, this method allows non-UTF8 paths.
     #[cfg(unix)]
     pub fn host_path<T>(&mut self, host: T) -> &mut Config
     where
@@ -262,6 +483,19 @@ impl Config {
         self
     }
 
+    /// Adds a Unix socket host to the configuration, for the directory containing the server's socket.
+    ///
+    /// Unlike with `host_path`, the path is always treated as a directory, and the socket file name is derived from
+    /// the port of the host, as `.s.PGSQL.<port>`.
+    #[cfg(unix)]
+    pub fn socket_dir<T>(&mut self, dir: T) -> &mut Config
+    where
+        T: AsRef<Path>,
+    {
+        self.config.socket_dir(dir);
+        self
+    }
+
     /// Adds a hostaddr to the configuration.
     ///
     /// Multiple hostaddrs can be specified by calling this method multiple times, and each will be tried in order.
@@ -286,6 +520,36 @@ impl Config {
         self.config.get_ports()
     }
 
+    /// Sets the operating system user the server must be running as when connecting with a Unix socket.
+    ///
+    /// The credentials of the server process are checked before the startup packet is sent, and the connection fails
+    /// if they don't match. This is only supported on Linux, macOS and BSD systems, and is ignored for TCP connections.
+    pub fn requirepeer(&mut self, requirepeer: &str) -> &mut Config {
+        self.config.requirepeer(requirepeer);
+        self
+    }
+
+    /// Gets the user the server must be running as, if one has been set with the `requirepeer` method.
+    pub fn get_requirepeer(&self) -> Option<&str> {
+        self.config.get_requirepeer()
+    }
+
+    /// Sets credentials which override the `user`, `password` and `dbname` of the configuration when connecting to
+    /// the host at `index`.
+    ///
+    /// Hosts are indexed in the order they were added with `host`, `host_path` or `hostaddr`. This allows, for
+    /// example, a disaster recovery site to be attempted with different credentials than the primary.
+    pub fn host_credentials(&mut self, index: usize, credentials: HostCredentials) -> &mut Config {
+        self.config.host_credentials(index, credentials);
+        self
+    }
+
+    /// Gets the credentials overriding the configuration for the host at `index`, if they have been set with the
+    /// `host_credentials` method.
+    pub fn get_host_credentials(&self, index: usize) -> Option<&HostCredentials> {
+        self.config.get_host_credentials(index)
+    }
+
     /// Sets the timeout applied to socket-level connection attempts.
     ///
     /// Note that hostnames can resolve to multiple IP addresses, and this timeout will apply to each address of each
@@ -414,6 +678,198 @@ impl Config {
         self.config.get_load_balance_hosts()
     }
 
+    /// Sets the replication mode of the session.
+    ///
+    /// Sessions are in the normal mode by default.
+    pub fn replication_mode(&mut self, replication_mode: ReplicationMode) -> &mut Config {
+        self.config.replication_mode(replication_mode);
+        self
+    }
+
+    /// Gets the replication mode of the session, if one has been set with the `replication_mode` method.
+    pub fn get_replication_mode(&self) -> Option<ReplicationMode> {
+        self.config.get_replication_mode()
+    }
+
+    /// Sets the threshold above which the execution of a statement is considered slow.
+    ///
+    /// Slow statements are reported to the callback configured with the `slow_statement_callback` method, or logged
+    /// at the warn level if there is none. The duration is measured client-side, from the moment a statement is sent
+    /// until the server reports its completion.
+    pub fn log_slow_statements(&mut self, threshold: Duration) -> &mut Config {
+        self.config.log_slow_statements(threshold);
+        self
+    }
+
+    /// Gets the threshold above which the execution of a statement is considered slow, if one has been set with the
+    /// `log_slow_statements` method.
+    pub fn get_log_slow_statements(&self) -> Option<Duration> {
+        self.config.get_log_slow_statements()
+    }
+
+    /// Sets a callback invoked with the statements exceeding the threshold set with the `log_slow_statements` method,
+    /// instead of logging them.
+    pub fn slow_statement_callback<F>(&mut self, callback: F) -> &mut Config
+    where
+        F: Fn(&SlowStatement<'_>) + Sync + Send + 'static,
+    {
+        self.config.slow_statement_callback(callback);
+        self
+    }
+
+    /// Returns `true` if a slow statement callback has been configured.
+    pub fn has_slow_statement_callback(&self) -> bool {
+        self.config.has_slow_statement_callback()
+    }
+
+    /// Sets the handler invoked with the metrics produced by connections and their clients.
+    ///
+    /// See the [`metrics`](crate::metrics) module for details.
+    pub fn metrics(&mut self, metrics: Arc<dyn Metrics>) -> &mut Config {
+        self.config.metrics(metrics);
+        self
+    }
+
+    /// Gets the metrics handler, if one has been set with the `metrics` method.
+    pub fn get_metrics(&self) -> Option<&Arc<dyn Metrics>> {
+        self.config.get_metrics()
+    }
+
+    /// Controls the collection of statement statistics by clients.
+    ///
+    /// See the [`stats`](crate::stats) module for details. Defaults to `false`.
+    pub fn statement_stats(&mut self, statement_stats: bool) -> &mut Config {
+        self.config.statement_stats(statement_stats);
+        self
+    }
+
+    /// Reports whether clients collect statement statistics.
+    pub fn get_statement_stats(&self) -> bool {
+        self.config.get_statement_stats()
+    }
+
+    /// Controls the resolution of types which aren't built into Postgres when statements are prepared.
+    ///
+    /// The types of parameters and columns which are enums, composites, domains or other user-defined types are looked
+    /// up in the server's catalog the first time they're seen, and cached by the client. Disabling this avoids the
+    /// extra queries, for example with servers which don't allow reading the catalog, but such types are then
+    /// reported as `TEXT`, unless registered with `register_type`. Defaults to `true`.
+    pub fn resolve_types(&mut self, resolve_types: bool) -> &mut Config {
+        self.config.resolve_types(resolve_types);
+        self
+    }
+
+    /// Reports whether clients resolve types which aren't built into Postgres.
+    pub fn get_resolve_types(&self) -> bool {
+        self.config.get_resolve_types()
+    }
+
+    /// Adds a type to those known to clients from the start, as if it had been resolved from the server's catalog.
+    ///
+    /// Parameters and columns of this type are reported with it without looking it up, which lets enums, composites
+    /// and other user-defined types be decoded when `resolve_types` is disabled. The OID of the type must match the
+    /// one assigned by the server, and the types it is made of are registered along with it. Can be called multiple
+    /// times to register multiple types.
+    pub fn register_type(&mut self, type_: Type) -> &mut Config {
+        self.config.register_type(type_);
+        self
+    }
+
+    /// Gets the types registered with the `register_type` method.
+    pub fn get_types(&self) -> &[Type] {
+        self.config.get_types()
+    }
+
+    /// Sets the number of statements kept prepared by `Client::query_cached`.
+    ///
+    /// The least recently used statement is closed once the cache is full. A capacity of 0 disables the cache, so
+    /// that statements are prepared again each time. Defaults to 100.
+    pub fn statement_cache_capacity(&mut self, statement_cache_capacity: usize) -> &mut Config {
+        self.config
+            .statement_cache_capacity(statement_cache_capacity);
+        self
+    }
+
+    /// Gets the number of statements kept prepared by `Client::query_cached`.
+    pub fn get_statement_cache_capacity(&self) -> usize {
+        self.config.get_statement_cache_capacity()
+    }
+
+    /// Sets the time limit applied by the client to queries.
+    ///
+    /// The limit applies to the methods of `Client` which wait for the complete results of a query, such as `query`,
+    /// `execute` or `batch_execute`, and can be overridden for a single query with `QueryOptions`. Once it expires,
+    /// a cancellation request is sent for the query and a timeout error is returned. Unlike the `statement_timeout`
+    /// server parameter, the limit isn't reset by connection poolers. Defaults to no limit.
+    ///
+    /// Like libpq, the cancellation request is sent without TLS.
+    pub fn query_timeout(&mut self, query_timeout: Duration) -> &mut Config {
+        self.config.query_timeout(query_timeout);
+        self
+    }
+
+    /// Gets the time limit applied by the client to queries, if one has been set with the `query_timeout` method.
+    pub fn get_query_timeout(&self) -> Option<&Duration> {
+        self.config.get_query_timeout()
+    }
+
+    /// Sets a callback invoked as connections move through their lifecycle.
+    ///
+    /// The callback is invoked as each connection attempt starts, is encrypted, authenticated and becomes ready, and
+    /// once the connection terminates or fails. Connection attempts to each host and address are reported separately,
+    /// so a single call to `connect` may produce several sequences of events.
+    pub fn lifecycle_callback<F>(&mut self, callback: F) -> &mut Config
+    where
+        F: Fn(&LifecycleEvent<'_>) + Sync + Send + 'static,
+    {
+        self.config.lifecycle_callback(callback);
+        self
+    }
+
+    /// Returns `true` if a lifecycle callback has been configured.
+    pub fn has_lifecycle_callback(&self) -> bool {
+        self.config.has_lifecycle_callback()
+    }
+
+    /// Adds statements run on each connection once it is established, before it is handed to the caller.
+    ///
+    /// The statements are run with `Client::batch_execute`, so a string can contain several statements separated by
+    /// semicolons. They also apply to the connections reopened by clients created with `connect_with_reconnect`. If
+    /// they fail, the connection attempt fails with their error. Can be called multiple times to add more statements,
+    /// which are run in order.
+    pub fn after_connect(&mut self, statements: &str) -> &mut Config {
+        self.config.after_connect(statements);
+        self
+    }
+
+    /// Gets the statements added with the `after_connect` method.
+    pub fn get_after_connect(&self) -> &[String] {
+        self.config.get_after_connect()
+    }
+
+    /// Sets an asynchronous callback run on each connection once it is established, after the statements added with
+    /// `after_connect`.
+    ///
+    /// The callback is given the asynchronous client of the connection, and an error returned by the callback fails
+    /// the connection attempt.
+    pub fn after_connect_callback<F>(&mut self, callback: F) -> &mut Config
+    where
+        F: for<'a> Fn(
+                &'a tokio_postgres::Client,
+            ) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>>
+            + Sync
+            + Send
+            + 'static,
+    {
+        self.config.after_connect_callback(callback);
+        self
+    }
+
+    /// Reports whether a callback has been configured with the `after_connect_callback` method.
+    pub fn has_after_connect_callback(&self) -> bool {
+        self.config.has_after_connect_callback()
+    }
+
     /// Sets the notice callback.
     ///
     /// This callback will be invoked with the contents of every
@@ -433,6 +889,24 @@ impl Config {
         self
     }
 
+    /// Sets a callback run on every new connection before it is returned, to prepare the session.
+    ///
+    /// This is typically used to set configuration parameters or to create temporary objects, and is especially
+    /// useful with [`connect_with_reconnect`](Config::connect_with_reconnect), since the session state is lost when
+    /// the connection is reestablished. The connection attempt fails if the callback returns an error.
+    pub fn session_setup<F>(&mut self, f: F) -> &mut Config
+    where
+        F: Fn(&mut Client) -> Result<(), Error> + Send + Sync + 'static,
+    {
+        self.session_setup = Some(Arc::new(f));
+        self
+    }
+
+    /// Reports whether a session setup callback has been set with the `session_setup` method.
+    pub fn has_session_setup(&self) -> bool {
+        self.session_setup.is_some()
+    }
+
     /// Opens a connection to a PostgreSQL database.
     pub fn connect<T>(&self, tls: T) -> Result<Client, Error>
     where
@@ -449,7 +923,68 @@ impl Config {
         let (client, connection) = runtime.block_on(self.config.connect(tls))?;
 
         let connection = Connection::new(runtime, connection, self.notice_callback.clone());
-        Ok(Client::new(connection, client))
+        self.setup(Client::new(connection, client))
+    }
+
+    /// Opens a connection to a PostgreSQL database without a tokio runtime.
+    ///
+    /// The connection is processed on the calling thread over blocking standard library sockets, as described in the
+    /// [`blocking`](crate::blocking) module. Except for the socket type TLS implementations are used with, the client
+    /// behaves the same as one opened with `connect`.
+    ///
+    /// Note that with random load balancing, the addresses a host resolves to are still tried in order, and that the
+    /// connection timeout doesn't apply to Unix sockets.
+    ///
+    /// Query time limits and keepalive queries rely on tokio timers, so an error is returned if `query_timeout` or
+    /// `keepalive_query_interval` is set, and when running a query with a `QueryOptions` timeout.
+    pub fn connect_blocking<T>(&self, tls: T) -> Result<Client, Error>
+    where
+        T: MakeTlsConnect<blocking::Socket>,
+        T::Stream: Send + 'static,
+    {
+        if self.config.get_query_timeout().is_some() {
+            return Err(Error::__private_api_config(
+                "query_timeout is not supported by blocking connections".into(),
+            ));
+        }
+        if self.config.get_keepalive_query_interval().is_some() {
+            return Err(Error::__private_api_config(
+                "keepalive_query_interval is not supported by blocking connections".into(),
+            ));
+        }
+
+        let (client, connection) =
+            blocking::connect(&self.config, tls, self.notice_callback.clone())?;
+        self.setup(Client::new(connection, client))
+    }
+
+    fn setup(&self, mut client: Client) -> Result<Client, Error> {
+        if let Some(session_setup) = &self.session_setup {
+            session_setup(&mut client)?;
+        }
+        Ok(client)
+    }
+
+    /// Opens a connection to a PostgreSQL database which is transparently reestablished if it breaks.
+    ///
+    /// Once the connection is found to be closed, the next method of the client which returns a `Result` opens a new
+    /// one with this configuration before proceeding, while the operation which failed when the connection broke
+    /// still returns its error. Since a new connection starts a new session, prepared statements and session state,
+    /// such as temporary tables or parameters changed with `SET`, are lost; use
+    /// [`session_setup`](Config::session_setup) to recreate the latter. Transactions in progress when the connection
+    /// broke are rolled back by the server, and are never resumed on the new connection.
+    pub fn connect_with_reconnect<T>(&self, tls: T) -> Result<Client, Error>
+    where
+        T: MakeTlsConnect<Socket> + Clone + 'static + Send,
+        T::TlsConnect: Send,
+        T::Stream: Send,
+        <T::TlsConnect as TlsConnect<Socket>>::Future: Send,
+    {
+        let config = self.clone();
+        let connect = move || config.connect(tls.clone());
+        let mut client = connect()?;
+        client.set_reconnect(Box::new(connect));
+        Ok(client)
     }
 }
 
@@ -468,6 +1003,7 @@ impl From<tokio_postgres::Config> for Config {
             notice_callback: Arc::new(|notice| {
                 info!("{}: {}", notice.severity(), notice.message())
             }),
+            session_setup: None,
         }
     }
 }
@@ -1,3 +1,4 @@
+use crate::blocking::Parker;
 use crate::{Error, Notification};
 use futures_util::{future, pin_mut, Stream};
 use std::collections::VecDeque;
@@ -6,16 +7,24 @@ use std::ops::{Deref, DerefMut};
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::runtime::Runtime;
+use tokio::time::{self, Sleep};
 use tokio_postgres::error::DbError;
-use tokio_postgres::AsyncMessage;
+use tokio_postgres::{AsyncMessage, QueryOptions};
+
+enum Driver {
+    Runtime(Runtime),
+    Blocking(Parker),
+}
 
 pub struct Connection {
-    runtime: Runtime,
+    driver: Driver,
     connection: Pin<Box<dyn Stream<Item = Result<AsyncMessage, Error>> + Send>>,
     notifications: VecDeque<Notification>,
     notice_callback: Arc<dyn Fn(DbError) + Sync + Send>,
+    closed: bool,
 }
 
 impl Connection {
@@ -24,15 +33,40 @@ impl Connection {
         connection: tokio_postgres::Connection<S, T>,
         notice_callback: Arc<dyn Fn(DbError) + Sync + Send>,
     ) -> Connection
+    where
+        S: AsyncRead + AsyncWrite + Unpin + 'static + Send,
+        T: AsyncRead + AsyncWrite + Unpin + 'static + Send,
+    {
+        Connection::with_driver(Driver::Runtime(runtime), connection, notice_callback)
+    }
+
+    pub fn new_blocking<S, T>(
+        parker: Parker,
+        connection: tokio_postgres::Connection<S, T>,
+        notice_callback: Arc<dyn Fn(DbError) + Sync + Send>,
+    ) -> Connection
+    where
+        S: AsyncRead + AsyncWrite + Unpin + 'static + Send,
+        T: AsyncRead + AsyncWrite + Unpin + 'static + Send,
+    {
+        Connection::with_driver(Driver::Blocking(parker), connection, notice_callback)
+    }
+
+    fn with_driver<S, T>(
+        driver: Driver,
+        connection: tokio_postgres::Connection<S, T>,
+        notice_callback: Arc<dyn Fn(DbError) + Sync + Send>,
+    ) -> Connection
     where
         S: AsyncRead + AsyncWrite + Unpin + 'static + Send,
         T: AsyncRead + AsyncWrite + Unpin + 'static + Send,
     {
         Connection {
-            runtime,
+            driver,
             connection: Box::pin(ConnectionStream { connection }),
             notifications: VecDeque::new(),
             notice_callback,
+            closed: false,
         }
     }
 
@@ -40,12 +74,29 @@ impl Connection {
         ConnectionRef { connection: self }
     }
 
-    pub fn enter<F, T>(&self, f: F) -> T
-    where
-        F: FnOnce() -> T,
-    {
-        let _guard = self.runtime.enter();
-        f()
+    /// Returns a future completing after the timeout, which can be polled in `block_on` whatever the driver.
+    pub fn delay(&self, timeout: Duration) -> Delay {
+        let deadline = Instant::now() + timeout;
+        let kind = match &self.driver {
+            Driver::Runtime(runtime) => {
+                let _guard = runtime.enter();
+                DelayKind::Runtime(Box::pin(time::sleep(timeout)))
+            }
+            Driver::Blocking(parker) => DelayKind::Blocking(parker.clone()),
+        };
+        Delay { deadline, kind }
+    }
+
+    /// Returns an error if the options can't be honored by the driver.
+    ///
+    /// The time limits of queries rely on tokio timers, which aren't available to the blocking driver.
+    pub fn check_options(&self, options: &QueryOptions) -> Result<(), Error> {
+        match (&self.driver, options.get_timeout()) {
+            (Driver::Blocking(_), Some(_)) => Err(Error::__private_api_config(
+                "query timeouts are not supported by blocking connections".into(),
+            )),
+            _ => Ok(()),
+        }
     }
 
     pub fn block_on<F, T>(&mut self, future: F) -> Result<T, Error>
@@ -63,26 +114,39 @@ impl Connection {
         let connection = &mut self.connection;
         let notifications = &mut self.notifications;
         let notice_callback = &mut self.notice_callback;
-        self.runtime.block_on({
-            future::poll_fn(|cx| {
-                let done = loop {
-                    match connection.as_mut().poll_next(cx) {
-                        Poll::Ready(Some(Ok(AsyncMessage::Notification(notification)))) => {
-                            notifications.push_back(notification);
-                        }
-                        Poll::Ready(Some(Ok(AsyncMessage::Notice(notice)))) => {
-                            notice_callback(notice)
-                        }
-                        Poll::Ready(Some(Ok(_))) => {}
-                        Poll::Ready(Some(Err(e))) => return Poll::Ready(Err(e)),
-                        Poll::Ready(None) => break true,
-                        Poll::Pending => break false,
+        let closed = &mut self.closed;
+        let poll = |cx: &mut Context<'_>| {
+            let done = loop {
+                match connection.as_mut().poll_next(cx) {
+                    Poll::Ready(Some(Ok(AsyncMessage::Notification(notification)))) => {
+                        notifications.push_back(notification);
                     }
-                };
+                    Poll::Ready(Some(Ok(AsyncMessage::Notice(notice)))) => notice_callback(notice),
+                    Poll::Ready(Some(Ok(_))) => {}
+                    Poll::Ready(Some(Err(e))) => {
+                        // Errors reported by the connection are fatal.
+                        *closed = true;
+                        return Poll::Ready(Err(e));
+                    }
+                    Poll::Ready(None) => {
+                        *closed = true;
+                        break true;
+                    }
+                    Poll::Pending => break false,
+                }
+            };
+
+            f(cx, notifications, done)
+        };
 
-                f(cx, notifications, done)
-            })
-        })
+        match &self.driver {
+            Driver::Runtime(runtime) => runtime.block_on(future::poll_fn(poll)),
+            Driver::Blocking(parker) => parker.poll_block_on(poll),
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
     }
 
     pub fn notifications(&self) -> &VecDeque<Notification> {
@@ -94,6 +158,42 @@ impl Connection {
     }
 }
 
+enum DelayKind {
+    Runtime(Pin<Box<Sleep>>),
+    Blocking(Parker),
+}
+
+/// A timer usable with both the tokio and the blocking drivers.
+pub struct Delay {
+    deadline: Instant,
+    kind: DelayKind,
+}
+
+impl Delay {
+    pub fn reset(&mut self, deadline: Instant) {
+        self.deadline = deadline;
+        if let DelayKind::Runtime(sleep) = &mut self.kind {
+            sleep.as_mut().reset(deadline.into());
+        }
+    }
+}
+
+impl Future for Delay {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let deadline = self.deadline;
+        match &mut self.kind {
+            DelayKind::Runtime(sleep) => sleep.as_mut().poll(cx),
+            DelayKind::Blocking(_) if Instant::now() >= deadline => Poll::Ready(()),
+            DelayKind::Blocking(parker) => {
+                parker.wake_at(deadline);
+                Poll::Pending
+            }
+        }
+    }
+}
+
 pub struct ConnectionRef<'a> {
     connection: &'a mut Connection,
 }
@@ -1,11 +1,14 @@
 use crate::connection::ConnectionRef;
+use crate::copy_options;
 use crate::lazy_pin::LazyPin;
 use bytes::{Bytes, BytesMut};
 use futures_util::SinkExt;
 use std::io;
-use std::io::Write;
+use std::io::{Read, Write};
 use tokio_postgres::{CopyInSink, Error};
 
+const DEFAULT_BUFFER_SIZE: usize = 4096;
+
 /// The writer returned by the `copy_in` method.
 ///
 /// The copy *must* be explicitly completed via the `finish` method. If it is not, the copy will be aborted.
@@ -13,6 +16,7 @@ pub struct CopyInWriter<'a> {
     pub(crate) connection: ConnectionRef<'a>,
     pub(crate) sink: LazyPin<CopyInSink<Bytes>>,
     buf: BytesMut,
+    buffer_size: usize,
 }
 
 impl<'a> CopyInWriter<'a> {
@@ -21,9 +25,22 @@ impl<'a> CopyInWriter<'a> {
             connection,
             sink: LazyPin::new(sink),
             buf: BytesMut::new(),
+            buffer_size: DEFAULT_BUFFER_SIZE,
         }
     }
 
+    /// Sets the number of bytes the writer buffers before sending them to the server.
+    ///
+    /// Defaults to 4 KiB.
+    pub fn set_buffer_size(&mut self, buffer_size: usize) {
+        self.buffer_size = buffer_size;
+    }
+
+    /// Returns the number of bytes the writer buffers before sending them to the server.
+    pub fn buffer_size(&self) -> usize {
+        self.buffer_size
+    }
+
     /// Completes the copy, returning the number of rows written.
     ///
     /// If this is not called, the copy will be aborted.
@@ -32,6 +49,18 @@ impl<'a> CopyInWriter<'a> {
         self.connection.block_on(self.sink.pinned().finish())
     }
 
+    /// Returns the number of bytes of data written to the connection so far.
+    ///
+    /// This doesn't include the data still buffered by the writer.
+    pub fn bytes_written(&self) -> u64 {
+        self.sink.get().bytes_written()
+    }
+
+    /// Returns the number of `CopyData` messages written to the connection so far.
+    pub fn chunks_written(&self) -> u64 {
+        self.sink.get().chunks_written()
+    }
+
     fn flush_inner(&mut self) -> Result<(), Error> {
         if self.buf.is_empty() {
             return Ok(());
@@ -44,7 +73,7 @@ impl<'a> CopyInWriter<'a> {
 
 impl Write for CopyInWriter<'_> {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        if self.buf.len() > 4096 {
+        if self.buf.len() > self.buffer_size {
             self.flush()?;
         }
 
@@ -57,3 +86,26 @@ impl Write for CopyInWriter<'_> {
             .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
     }
 }
+
+/// Copies the data of a reader into a copy, in chunks of `buffer_size` bytes, and completes it.
+pub(crate) fn copy_from_reader<R>(
+    mut writer: CopyInWriter<'_>,
+    mut reader: R,
+    buffer_size: usize,
+) -> Result<u64, Error>
+where
+    R: Read,
+{
+    writer.set_buffer_size(buffer_size);
+    let mut buf = vec![0; buffer_size.max(1)];
+    loop {
+        let len = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(len) => len,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(Error::__private_api_io(e)),
+        };
+        writer.write_all(&buf[..len]).map_err(copy_options::error)?;
+    }
+    writer.finish()
+}
@@ -0,0 +1,112 @@
+//! Options of `COPY` statements.
+//!
+//! [`CopyOptions`] builds the `COPY ... FROM STDIN` and `COPY ... TO STDOUT` statements passed to `Client::copy_in`
+//! and `Client::copy_out`, taking care of quoting the format options.
+//!
+//! The [`dump`] and [`restore`] functions run these statements directly, copying data out of a table or query into
+//! a reader, and from a reader into a table. [`transfer`] combines both to copy data between connections, for example
+//! to migrate a table to another database, without buffering more than a few kilobytes in memory, and [`copy_table`]
+//! does so for a table with the same name on both sides.
+
+use crate::{CopyOutReader, Error, GenericClient};
+use std::io::{self, Read};
+#[doc(inline)]
+pub use tokio_postgres::copy_options::{CopyFormat, CopyOptions};
+
+/// Copies data out of a source, which is either a table name optionally followed by a list of columns, or a
+/// parenthesized query.
+///
+/// This executes the statement returned by [`CopyOptions::copy_out`].
+pub fn dump<'a, C>(
+    client: &'a mut C,
+    source: &str,
+    options: &CopyOptions,
+) -> Result<CopyOutReader<'a>, Error>
+where
+    C: GenericClient,
+{
+    client.copy_out(&*options.copy_out(source))
+}
+
+/// Copies the data of a reader into a target, which is a table name optionally followed by a list of columns,
+/// returning the number of rows copied.
+///
+/// This executes the statement returned by [`CopyOptions::copy_in`]. The data must be in the format set by the
+/// options. If the reader returns an error, the copy is aborted and the error is returned.
+pub fn restore<C, R>(
+    client: &mut C,
+    target: &str,
+    options: &CopyOptions,
+    mut data: R,
+) -> Result<u64, Error>
+where
+    C: GenericClient,
+    R: Read,
+{
+    let mut writer = client.copy_in(&*options.copy_in(target))?;
+    io::copy(&mut data, &mut writer).map_err(error)?;
+    writer.finish()
+}
+
+/// Copies data from a source on one connection into a target on another one, returning the number of rows copied.
+///
+/// The source and target are the ones of [`dump`] and [`restore`], and the data is exchanged in the format set by
+/// the options. With the binary format, the columns of the target must have the same types as the ones of the
+/// source.
+pub fn transfer<C, D>(
+    source_client: &mut C,
+    source: &str,
+    target_client: &mut D,
+    target: &str,
+    options: &CopyOptions,
+) -> Result<u64, Error>
+where
+    C: GenericClient,
+    D: GenericClient,
+{
+    let reader = dump(source_client, source, options)?;
+    restore(target_client, target, options, reader)
+}
+
+/// Copies the rows of a table into the table with the same name on another connection, returning the number of rows
+/// copied.
+///
+/// The table name can be followed by a list of columns, as with [`transfer`]. The data is exchanged in the binary
+/// format, so the columns of the target must have the same types as the ones of the source.
+///
+/// # Examples
+///
+/// ```no_run
+/// use postgres::copy_options;
+/// use postgres::{Client, NoTls};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut source = Client::connect("host=primary user=postgres", NoTls)?;
+/// let mut target = Client::connect("host=warehouse user=postgres", NoTls)?;
+///
+/// let rows = copy_options::copy_table(&mut source, &mut target, "events (id, payload)")?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn copy_table<C, D>(
+    source_client: &mut C,
+    target_client: &mut D,
+    table: &str,
+) -> Result<u64, Error>
+where
+    C: GenericClient,
+    D: GenericClient,
+{
+    let options = CopyOptions::new().format(CopyFormat::Binary);
+    transfer(source_client, table, target_client, table, &options)
+}
+
+// The readers and writers of copies wrap the errors of their connection into I/O errors.
+pub(crate) fn error(e: io::Error) -> Error {
+    let wrapped = matches!(e.get_ref(), Some(inner) if inner.is::<Error>());
+    if wrapped {
+        *e.into_inner().unwrap().downcast::<Error>().unwrap()
+    } else {
+        Error::__private_api_io(e)
+    }
+}
@@ -1,9 +1,10 @@
 use crate::connection::ConnectionRef;
+use crate::copy_options;
 use crate::lazy_pin::LazyPin;
 use bytes::{Buf, Bytes};
 use futures_util::StreamExt;
-use std::io::{self, BufRead, Read};
-use tokio_postgres::CopyOutStream;
+use std::io::{self, BufRead, BufWriter, Read, Write};
+use tokio_postgres::{CopyOutStream, Error};
 
 /// The reader returned by the `copy_out` method.
 pub struct CopyOutReader<'a> {
@@ -53,3 +54,29 @@ impl BufRead for CopyOutReader<'_> {
         self.cur.advance(amt);
     }
 }
+
+/// Writes the data of a copy into a writer, buffering up to `buffer_size` bytes between writes, and returns the number
+/// of bytes written.
+pub(crate) fn copy_to_writer<W>(
+    mut reader: CopyOutReader<'_>,
+    writer: W,
+    buffer_size: usize,
+) -> Result<u64, Error>
+where
+    W: Write,
+{
+    let mut writer = BufWriter::with_capacity(buffer_size, writer);
+    let mut written = 0;
+    loop {
+        let buf = reader.fill_buf().map_err(copy_options::error)?;
+        if buf.is_empty() {
+            break;
+        }
+        writer.write_all(buf).map_err(Error::__private_api_io)?;
+        let len = buf.len();
+        reader.consume(len);
+        written += len as u64;
+    }
+    writer.flush().map_err(Error::__private_api_io)?;
+    Ok(written)
+}
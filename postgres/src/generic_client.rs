@@ -1,7 +1,7 @@
-use crate::types::{BorrowToSql, ToSql, Type};
+use crate::types::{BorrowToSql, FromRow, FromSqlOwned, ToSql, ToText, Type};
 use crate::{
     Client, CopyInWriter, CopyOutReader, Error, Row, RowIter, SimpleQueryMessage, Statement,
-    ToStatement, Transaction,
+    StatementDescription, ToStatement, Transaction,
 };
 
 mod private {
@@ -36,6 +36,33 @@ pub trait GenericClient: private::Sealed {
     where
         T: ?Sized + ToStatement;
 
+    /// Like `Client::query_scalar`.
+    fn query_scalar<R>(
+        &mut self,
+        query: &(impl ?Sized + ToStatement),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<R, Error>
+    where
+        R: FromSqlOwned;
+
+    /// Like `Client::query_scalar_opt`.
+    fn query_scalar_opt<R>(
+        &mut self,
+        query: &(impl ?Sized + ToStatement),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<R>, Error>
+    where
+        R: FromSqlOwned;
+
+    /// Like `Client::query_as`.
+    fn query_as<R>(
+        &mut self,
+        query: &(impl ?Sized + ToStatement),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<R>, Error>
+    where
+        R: FromRow;
+
     /// Like `Client::query_raw`.
     fn query_raw<T, P, I>(&mut self, query: &T, params: I) -> Result<RowIter<'_>, Error>
     where
@@ -44,12 +71,36 @@ pub trait GenericClient: private::Sealed {
         I: IntoIterator<Item = P>,
         I::IntoIter: ExactSizeIterator;
 
+    /// Like `Client::query_raw_txt`.
+    fn query_raw_txt<P, I>(&mut self, query: &str, params: I) -> Result<RowIter<'_>, Error>
+    where
+        P: ToText,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator;
+
+    /// Like `Client::query_one_txt`.
+    fn query_one_txt<P, I>(&mut self, query: &str, params: I) -> Result<Row, Error>
+    where
+        P: ToText,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator;
+
+    /// Like `Client::query_opt_txt`.
+    fn query_opt_txt<P, I>(&mut self, query: &str, params: I) -> Result<Option<Row>, Error>
+    where
+        P: ToText,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator;
+
     /// Like `Client::prepare`.
     fn prepare(&mut self, query: &str) -> Result<Statement, Error>;
 
     /// Like `Client::prepare_typed`.
     fn prepare_typed(&mut self, query: &str, types: &[Type]) -> Result<Statement, Error>;
 
+    /// Like `Client::describe`.
+    fn describe(&mut self, query: &str) -> Result<StatementDescription, Error>;
+
     /// Like `Client::copy_in`.
     fn copy_in<T>(&mut self, query: &T) -> Result<CopyInWriter<'_>, Error>
     where
@@ -105,6 +156,39 @@ impl GenericClient for Client {
         self.query_opt(query, params)
     }
 
+    fn query_scalar<R>(
+        &mut self,
+        query: &(impl ?Sized + ToStatement),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<R, Error>
+    where
+        R: FromSqlOwned,
+    {
+        self.query_scalar(query, params)
+    }
+
+    fn query_scalar_opt<R>(
+        &mut self,
+        query: &(impl ?Sized + ToStatement),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<R>, Error>
+    where
+        R: FromSqlOwned,
+    {
+        self.query_scalar_opt(query, params)
+    }
+
+    fn query_as<R>(
+        &mut self,
+        query: &(impl ?Sized + ToStatement),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<R>, Error>
+    where
+        R: FromRow,
+    {
+        self.query_as(query, params)
+    }
+
     fn query_raw<T, P, I>(&mut self, query: &T, params: I) -> Result<RowIter<'_>, Error>
     where
         T: ?Sized + ToStatement,
@@ -115,6 +199,33 @@ impl GenericClient for Client {
         self.query_raw(query, params)
     }
 
+    fn query_raw_txt<P, I>(&mut self, query: &str, params: I) -> Result<RowIter<'_>, Error>
+    where
+        P: ToText,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.query_raw_txt(query, params)
+    }
+
+    fn query_one_txt<P, I>(&mut self, query: &str, params: I) -> Result<Row, Error>
+    where
+        P: ToText,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.query_one_txt(query, params)
+    }
+
+    fn query_opt_txt<P, I>(&mut self, query: &str, params: I) -> Result<Option<Row>, Error>
+    where
+        P: ToText,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.query_opt_txt(query, params)
+    }
+
     fn prepare(&mut self, query: &str) -> Result<Statement, Error> {
         self.prepare(query)
     }
@@ -123,6 +234,10 @@ impl GenericClient for Client {
         self.prepare_typed(query, types)
     }
 
+    fn describe(&mut self, query: &str) -> Result<StatementDescription, Error> {
+        self.describe(query)
+    }
+
     fn copy_in<T>(&mut self, query: &T) -> Result<CopyInWriter<'_>, Error>
     where
         T: ?Sized + ToStatement,
@@ -185,6 +300,39 @@ impl GenericClient for Transaction<'_> {
         self.query_opt(query, params)
     }
 
+    fn query_scalar<R>(
+        &mut self,
+        query: &(impl ?Sized + ToStatement),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<R, Error>
+    where
+        R: FromSqlOwned,
+    {
+        self.query_scalar(query, params)
+    }
+
+    fn query_scalar_opt<R>(
+        &mut self,
+        query: &(impl ?Sized + ToStatement),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<R>, Error>
+    where
+        R: FromSqlOwned,
+    {
+        self.query_scalar_opt(query, params)
+    }
+
+    fn query_as<R>(
+        &mut self,
+        query: &(impl ?Sized + ToStatement),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<R>, Error>
+    where
+        R: FromRow,
+    {
+        self.query_as(query, params)
+    }
+
     fn query_raw<T, P, I>(&mut self, query: &T, params: I) -> Result<RowIter<'_>, Error>
     where
         T: ?Sized + ToStatement,
@@ -195,6 +343,33 @@ impl GenericClient for Transaction<'_> {
         self.query_raw(query, params)
     }
 
+    fn query_raw_txt<P, I>(&mut self, query: &str, params: I) -> Result<RowIter<'_>, Error>
+    where
+        P: ToText,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.query_raw_txt(query, params)
+    }
+
+    fn query_one_txt<P, I>(&mut self, query: &str, params: I) -> Result<Row, Error>
+    where
+        P: ToText,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.query_one_txt(query, params)
+    }
+
+    fn query_opt_txt<P, I>(&mut self, query: &str, params: I) -> Result<Option<Row>, Error>
+    where
+        P: ToText,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.query_opt_txt(query, params)
+    }
+
     fn prepare(&mut self, query: &str) -> Result<Statement, Error> {
         self.prepare(query)
     }
@@ -203,6 +378,10 @@ impl GenericClient for Transaction<'_> {
         self.prepare_typed(query, types)
     }
 
+    fn describe(&mut self, query: &str) -> Result<StatementDescription, Error> {
+        self.describe(query)
+    }
+
     fn copy_in<T>(&mut self, query: &T) -> Result<CopyInWriter<'_>, Error>
     where
         T: ?Sized + ToStatement,
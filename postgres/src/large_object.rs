@@ -0,0 +1,82 @@
+//! Large objects.
+//!
+//! See the documentation of the `tokio_postgres::large_object` module for details.
+
+use crate::connection::ConnectionRef;
+use std::future::Future;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio_postgres::types::Oid;
+use tokio_postgres::Error;
+
+#[doc(inline)]
+pub use tokio_postgres::large_object::Mode;
+
+/// A handle to an open large object, returned by the `Transaction::open_large_object` method.
+///
+/// The object is read, written and repositioned with the `Read`, `Write` and `Seek` traits. Reads and writes transfer
+/// at most 256 KiB per call to the server, so they should go through buffers of about that size.
+///
+/// The handle is only valid in the transaction it was opened in, which closes it if it isn't closed explicitly.
+pub struct LargeObject<'a> {
+    connection: ConnectionRef<'a>,
+    object: tokio_postgres::large_object::LargeObject<'a>,
+}
+
+impl<'a> LargeObject<'a> {
+    pub(crate) fn new(
+        connection: ConnectionRef<'a>,
+        object: tokio_postgres::large_object::LargeObject<'a>,
+    ) -> LargeObject<'a> {
+        LargeObject { connection, object }
+    }
+
+    /// Returns the OID of the object.
+    pub fn oid(&self) -> Oid {
+        self.object.oid()
+    }
+
+    /// Truncates or extends the object to a length in bytes.
+    ///
+    /// The object is extended with zeroes. Its position is unchanged.
+    pub fn truncate(&mut self, len: u64) -> Result<(), Error> {
+        self.connection.block_on(self.object.truncate(len))
+    }
+
+    /// Closes the handle.
+    pub fn close(mut self) -> Result<(), Error> {
+        self.connection.block_on(self.object.close())
+    }
+}
+
+fn block_on_io<F, T>(connection: &mut ConnectionRef<'_>, future: F) -> io::Result<T>
+where
+    F: Future<Output = io::Result<T>>,
+{
+    // Errors of the connection itself are reported by the outer result.
+    connection
+        .block_on(async { Ok(future.await) })
+        .map_err(io::Error::other)?
+}
+
+impl Read for LargeObject<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        block_on_io(&mut self.connection, self.object.read(buf))
+    }
+}
+
+impl Write for LargeObject<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        block_on_io(&mut self.connection, self.object.write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        block_on_io(&mut self.connection, self.object.flush())
+    }
+}
+
+impl Seek for LargeObject<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        block_on_io(&mut self.connection, self.object.seek(pos))
+    }
+}
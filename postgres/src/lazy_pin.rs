@@ -18,6 +18,10 @@ impl<T> LazyPin<T> {
         unsafe { Pin::new_unchecked(&mut *self.value) }
     }
 
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+
     pub fn into_unpinned(self) -> Option<T> {
         if self.pinned {
             None
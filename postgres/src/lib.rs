@@ -38,7 +38,8 @@
 //!
 //! This crate is a lightweight wrapper over tokio-postgres. The `postgres::Client` is simply a wrapper around a
 //! `tokio_postgres::Client` along side a tokio `Runtime`. The client simply blocks on the futures provided by the async
-//! client.
+//! client. Alternatively, `Config::connect_blocking` opens clients which drive the futures on blocking sockets
+//! themselves, without a runtime.
 //!
 //! # SSL/TLS support
 //!
@@ -53,6 +54,11 @@
 //!
 //! | Feature | Description | Extra dependencies | Default |
 //! | ------- | ----------- | ------------------ | ------- |
+//! | `arrow` | Enable the `arrow` module converting query results and binary COPY data to and from Apache Arrow record batches. | [arrow-array](https://crates.io/crates/arrow-array) and [arrow-schema](https://crates.io/crates/arrow-schema) 60 | no |
+//! | `default-application-name` | Default the `fallback_application_name` configuration option to the name of the running executable. | - | no |
+//! | `migrations` | Enable the `migrations` module applying schema migrations. | - | no |
+//! | `r2d2` | Enable the `r2d2` module providing a manager for `r2d2` connection pools. | [r2d2](https://crates.io/crates/r2d2) 0.8 | no |
+//! | `tracing` | Enable `tracing` spans with OpenTelemetry database semantic convention attributes for connection, prepare, query, transaction and COPY operations, and events for the notices sent by the server. | [tracing](https://crates.io/crates/tracing) 0.1 | no |
 //! | `with-bit-vec-0_6` | Enable support for the `bit-vec` crate. | [bit-vec](https://crates.io/crates/bit-vec) 0.6 | no |
 //! | `with-chrono-0_4` | Enable support for the `chrono` crate. | [chrono](https://crates.io/crates/chrono) 0.4 | no |
 //! | `with-eui48-0_4` | Enable support for the 0.4 version of the `eui48` crate. This is deprecated and will be removed. | [eui48](https://crates.io/crates/eui48) 0.4 | no |
@@ -67,12 +73,21 @@
 #![warn(clippy::all, rust_2018_idioms, missing_docs)]
 
 pub use fallible_iterator;
+#[cfg(feature = "arrow")]
+pub use tokio_postgres::arrow;
+#[cfg(feature = "with-serde_json-1")]
+pub use tokio_postgres::explain;
+#[cfg(feature = "migrations")]
+pub use tokio_postgres::migrations;
 pub use tokio_postgres::{
-    error, row, tls, types, Column, IsolationLevel, Notification, Portal, SimpleQueryMessage,
-    Socket, Statement, ToStatement,
+    error, escape, gss, interceptor, metrics, row, schema, security, stats, tls, types,
+    CancelOptions, CancelOutcome, Column, Cursor, ExecuteManyError, IsolationLevel, Notification,
+    ParseServerVersionError, Portal, QueryOptions, RowRef, Rows, RowsIter, ServerVersion,
+    SimpleColumn, SimpleQueryMessage, Socket, Statement, StatementDescription, ToStatement,
+    TransactionStatus,
 };
 
-pub use crate::cancel_token::CancelToken;
+pub use crate::cancel_token::{CancelToken, QueryCancelToken};
 pub use crate::client::*;
 pub use crate::config::Config;
 pub use crate::copy_in_writer::CopyInWriter;
@@ -81,9 +96,13 @@ pub use crate::copy_out_reader::CopyOutReader;
 pub use crate::error::Error;
 pub use crate::generic_client::GenericClient;
 #[doc(inline)]
+pub use crate::listener::Listener;
+#[doc(inline)]
 pub use crate::notifications::Notifications;
+pub use crate::portal_cursor::PortalCursor;
 #[doc(no_inline)]
 pub use crate::row::{Row, SimpleQueryRow};
+pub use crate::row_chunks::RowChunks;
 pub use crate::row_iter::RowIter;
 #[doc(no_inline)]
 pub use crate::tls::NoTls;
@@ -91,15 +110,24 @@ pub use crate::transaction::*;
 pub use crate::transaction_builder::TransactionBuilder;
 
 pub mod binary_copy;
+pub mod blocking;
 mod cancel_token;
 mod client;
 pub mod config;
 mod connection;
 mod copy_in_writer;
+pub mod copy_options;
 mod copy_out_reader;
 mod generic_client;
+pub mod large_object;
 mod lazy_pin;
+pub mod listener;
 pub mod notifications;
+pub mod pipeline;
+mod portal_cursor;
+#[cfg(feature = "r2d2")]
+pub mod r2d2;
+mod row_chunks;
 mod row_iter;
 mod transaction;
 mod transaction_builder;
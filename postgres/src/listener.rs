@@ -0,0 +1,215 @@
+//! A dedicated connection for receiving notifications.
+
+use crate::tls::{MakeTlsConnect, TlsConnect};
+use crate::{Client, Config, Error, Notification, Socket};
+use fallible_iterator::FallibleIterator;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// A connection subscribed to notification channels with `LISTEN`.
+///
+/// Unlike [`Client::notifications`], a `Listener` owns its connection and reestablishes it if it is lost, subscribing
+/// to all of its channels again. Notifications sent while the listener was disconnected are lost, so applications
+/// relying on them should check [`Listener::reconnects`] and resynchronize their state when it changes.
+///
+/// # Examples
+///
+/// ```no_run
+/// use fallible_iterator::FallibleIterator;
+/// use postgres::{Listener, NoTls};
+///
+/// # fn main() -> Result<(), postgres::Error> {
+/// let config = "host=localhost user=postgres".parse()?;
+/// let mut listener = Listener::connect(config, NoTls)?;
+/// listener.listen("jobs")?;
+///
+/// let mut it = listener.iter();
+/// while let Some(notification) = it.next()? {
+///     println!("{}: {}", notification.channel(), notification.payload());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Client::notifications`]: crate::Client::notifications
+pub struct Listener {
+    connect: Box<dyn FnMut() -> Result<Client, Error> + Send>,
+    client: Option<Client>,
+    channels: BTreeSet<String>,
+    reconnects: u64,
+}
+
+impl fmt::Debug for Listener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Listener")
+            .field("connected", &self.client.is_some())
+            .field("channels", &self.channels)
+            .field("reconnects", &self.reconnects)
+            .finish()
+    }
+}
+
+impl Listener {
+    /// Opens a connection for a listener.
+    ///
+    /// The configuration and TLS connector are kept to reestablish the connection if it is lost.
+    pub fn connect<T>(config: Config, tls: T) -> Result<Listener, Error>
+    where
+        T: MakeTlsConnect<Socket> + Clone + 'static + Send,
+        T::TlsConnect: Send,
+        T::Stream: Send,
+        <T::TlsConnect as TlsConnect<Socket>>::Future: Send,
+    {
+        let connect = Box::new(move || config.connect(tls.clone()));
+        let client = connect()?;
+        Ok(Listener {
+            connect,
+            client: Some(client),
+            channels: BTreeSet::new(),
+            reconnects: 0,
+        })
+    }
+
+    /// Subscribes to a channel.
+    ///
+    /// Subscribing to a channel the listener is already subscribed to has no effect.
+    pub fn listen(&mut self, channel: &str) -> Result<(), Error> {
+        self.execute(&format!("LISTEN {}", quote(channel)))?;
+        self.channels.insert(channel.to_string());
+        Ok(())
+    }
+
+    /// Unsubscribes from a channel.
+    pub fn unlisten(&mut self, channel: &str) -> Result<(), Error> {
+        self.channels.remove(channel);
+        self.execute(&format!("UNLISTEN {}", quote(channel)))
+    }
+
+    /// Unsubscribes from all channels.
+    pub fn unlisten_all(&mut self) -> Result<(), Error> {
+        self.channels.clear();
+        self.execute("UNLISTEN *")
+    }
+
+    /// Returns an iterator over the channels the listener is subscribed to.
+    pub fn channels(&self) -> impl Iterator<Item = &str> {
+        self.channels.iter().map(|channel| &**channel)
+    }
+
+    /// Returns the number of times the connection has been reestablished.
+    pub fn reconnects(&self) -> u64 {
+        self.reconnects
+    }
+
+    /// Returns a pending notification, if there is one, without blocking.
+    pub fn try_recv(&mut self) -> Result<Option<Notification>, Error> {
+        let result = self.connection()?.notifications().iter().next();
+        if result.is_err() {
+            self.client = None;
+        }
+        result
+    }
+
+    /// Waits for a notification.
+    ///
+    /// If the connection is lost while waiting, it is reestablished once. An error is returned if it cannot be, or
+    /// if it is lost again, in which case the next call will try to reestablish it again.
+    pub fn recv(&mut self) -> Result<Notification, Error> {
+        let mut reconnected = false;
+        loop {
+            let result = self.connection()?.notifications().blocking_iter().next();
+            match result {
+                Ok(Some(notification)) => return Ok(notification),
+                Ok(None) => self.disconnected(Error::__private_api_closed(), &mut reconnected)?,
+                Err(e) => self.disconnected(e, &mut reconnected)?,
+            }
+        }
+    }
+
+    /// Waits for a notification for up to the given duration, returning `None` if none arrived in time.
+    ///
+    /// The connection is reestablished like with `recv`.
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<Option<Notification>, Error> {
+        let deadline = Instant::now() + timeout;
+        let mut reconnected = false;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let result = self
+                .connection()?
+                .notifications()
+                .timeout_iter(remaining)
+                .next();
+            match result {
+                Ok(Some(notification)) => return Ok(Some(notification)),
+                Ok(None) if Instant::now() >= deadline => return Ok(None),
+                Ok(None) => self.disconnected(Error::__private_api_closed(), &mut reconnected)?,
+                Err(e) => self.disconnected(e, &mut reconnected)?,
+            }
+        }
+    }
+
+    /// Returns a blocking iterator over notifications.
+    ///
+    /// The iterator never ends, but returns an error under the same conditions as `recv`.
+    pub fn iter(&mut self) -> Iter<'_> {
+        Iter { listener: self }
+    }
+
+    /// Returns the connection, reestablishing it and subscribing to all channels again if it was lost.
+    fn connection(&mut self) -> Result<&mut Client, Error> {
+        if self.client.is_none() {
+            let mut client = (self.connect)()?;
+            if !self.channels.is_empty() {
+                let query = self
+                    .channels
+                    .iter()
+                    .map(|channel| format!("LISTEN {};", quote(channel)))
+                    .collect::<String>();
+                client.batch_execute(&query)?;
+            }
+            self.reconnects += 1;
+            self.client = Some(client);
+        }
+
+        Ok(self.client.as_mut().unwrap())
+    }
+
+    fn execute(&mut self, query: &str) -> Result<(), Error> {
+        let result = self.connection()?.batch_execute(query);
+        // Errors not reported by the server mean that the connection is unusable.
+        if let Err(e) = &result {
+            if e.as_db_error().is_none() {
+                self.client = None;
+            }
+        }
+        result
+    }
+
+    fn disconnected(&mut self, error: Error, reconnected: &mut bool) -> Result<(), Error> {
+        self.client = None;
+        if *reconnected {
+            return Err(error);
+        }
+        *reconnected = true;
+        Ok(())
+    }
+}
+
+/// A blocking iterator over the notifications received by a [`Listener`].
+pub struct Iter<'a> {
+    listener: &'a mut Listener,
+}
+
+impl FallibleIterator for Iter<'_> {
+    type Item = Notification;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Notification>, Error> {
+        self.listener.recv().map(Some)
+    }
+}
+
+fn quote(channel: &str) -> String {
+    format!("\"{}\"", channel.replace('"', "\"\""))
+}
@@ -1,13 +1,11 @@
 //! Asynchronous notifications.
 
-use crate::connection::ConnectionRef;
+use crate::connection::{ConnectionRef, Delay};
 use crate::{Error, Notification};
 use fallible_iterator::FallibleIterator;
 use futures_util::{ready, FutureExt};
-use std::pin::Pin;
 use std::task::Poll;
-use std::time::Duration;
-use tokio::time::{self, Instant, Sleep};
+use std::time::{Duration, Instant};
 
 /// Notifications from a PostgreSQL backend.
 pub struct Notifications<'a> {
@@ -65,7 +63,7 @@ impl<'a> Notifications<'a> {
     /// This iterator may start returning `Some` after previously returning `None` if more notifications are received.
     pub fn timeout_iter(&mut self, timeout: Duration) -> TimeoutIter<'_> {
         TimeoutIter {
-            delay: Box::pin(self.connection.enter(|| time::sleep(timeout))),
+            delay: self.connection.delay(timeout),
             timeout,
             connection: self.connection.as_ref(),
         }
@@ -125,7 +123,7 @@ impl<'a> FallibleIterator for BlockingIter<'a> {
 /// A time-limited blocking iterator over pending notifications.
 pub struct TimeoutIter<'a> {
     connection: ConnectionRef<'a>,
-    delay: Pin<Box<Sleep>>,
+    delay: Delay,
     timeout: Duration,
 }
 
@@ -135,7 +133,7 @@ impl<'a> FallibleIterator for TimeoutIter<'a> {
 
     fn next(&mut self) -> Result<Option<Self::Item>, Self::Error> {
         if let Some(notification) = self.connection.notifications_mut().pop_front() {
-            self.delay.as_mut().reset(Instant::now() + self.timeout);
+            self.delay.reset(Instant::now() + self.timeout);
             return Ok(Some(notification));
         }
 
@@ -144,7 +142,7 @@ impl<'a> FallibleIterator for TimeoutIter<'a> {
         self.connection.poll_block_on(|cx, notifications, done| {
             match notifications.pop_front() {
                 Some(notification) => {
-                    delay.as_mut().reset(Instant::now() + timeout);
+                    delay.reset(Instant::now() + timeout);
                     return Poll::Ready(Ok(Some(notification)));
                 }
                 None if done => return Poll::Ready(Ok(None)),
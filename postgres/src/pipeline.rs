@@ -0,0 +1,89 @@
+//! Pipelined execution of statements.
+//!
+//! A [`Pipeline`] created with [`Client::pipeline`] queues statements along with their parameters, and sends them all
+//! to the server at once when run, without waiting for the results of a statement before sending the next one. This
+//! saves a round trip per statement, which dominates the execution time of scripts running many short statements.
+//!
+//! With [`Pipeline::run`], each statement is executed independently of the others, in its own implicit transaction
+//! unless the pipeline is run inside of an explicit one, so a failing statement does not prevent the following ones
+//! from being executed. [`Pipeline::run_batch`] instead executes them all in a single implicit transaction.
+//!
+//! [`Client::pipeline`]: crate::Client::pipeline
+
+use crate::connection::ConnectionRef;
+use crate::types::ToSql;
+use crate::{Error, ToStatement};
+use tokio_postgres::pipeline;
+#[doc(inline)]
+pub use tokio_postgres::pipeline::PipelineResult;
+
+/// A queue of statements to be sent to the server at once.
+///
+/// Created with [`Client::pipeline`](crate::Client::pipeline) or [`Transaction::pipeline`](crate::Transaction::pipeline).
+pub struct Pipeline<'a> {
+    connection: ConnectionRef<'a>,
+    pipeline: pipeline::Pipeline<'a>,
+}
+
+impl<'a> Pipeline<'a> {
+    pub(crate) fn new(
+        connection: ConnectionRef<'a>,
+        pipeline: pipeline::Pipeline<'a>,
+    ) -> Pipeline<'a> {
+        Pipeline {
+            connection,
+            pipeline,
+        }
+    }
+
+    /// Queues a statement returning rows.
+    ///
+    /// Like `Client::query`, the statement can either be a `Statement` or a raw query string.
+    pub fn query<T>(&mut self, statement: &'a T, params: &[&'a (dyn ToSql + Sync)]) -> &mut Self
+    where
+        T: ?Sized + ToStatement,
+    {
+        self.pipeline.query(statement, params);
+        self
+    }
+
+    /// Queues a statement returning the number of rows modified.
+    ///
+    /// Like `Client::execute`, the statement can either be a `Statement` or a raw query string.
+    pub fn execute<T>(&mut self, statement: &'a T, params: &[&'a (dyn ToSql + Sync)]) -> &mut Self
+    where
+        T: ?Sized + ToStatement,
+    {
+        self.pipeline.execute(statement, params);
+        self
+    }
+
+    /// Returns the number of statements queued.
+    pub fn len(&self) -> usize {
+        self.pipeline.len()
+    }
+
+    /// Returns `true` if no statement has been queued.
+    pub fn is_empty(&self) -> bool {
+        self.pipeline.is_empty()
+    }
+
+    /// Sends the queued statements to the server and waits for their results.
+    ///
+    /// Results are returned in the order the statements were queued. Raw query strings are prepared first, which
+    /// takes one additional round trip for the whole pipeline. An error is returned if the connection fails while the
+    /// pipeline is running.
+    pub fn run(mut self) -> Result<Vec<Result<PipelineResult, Error>>, Error> {
+        let pipeline = self.pipeline;
+        self.connection.block_on(async { Ok(pipeline.run().await) })
+    }
+
+    /// Sends the queued statements to the server as a single batch, executed in one implicit transaction.
+    ///
+    /// Results are returned in the order the statements were queued. If a statement fails, its error is returned and
+    /// none of the statements take effect. Inside of an explicit transaction, the transaction is aborted instead.
+    pub fn run_batch(mut self) -> Result<Vec<PipelineResult>, Error> {
+        let pipeline = self.pipeline;
+        self.connection.block_on(pipeline.run_batch())
+    }
+}
@@ -0,0 +1,43 @@
+use crate::connection::ConnectionRef;
+use tokio_postgres::{Error, Portal, Row};
+
+/// The rows of a portal, fetched incrementally by `Transaction::bind_cursor`.
+///
+/// The portal is closed when the `PortalCursor` is dropped.
+pub struct PortalCursor<'a> {
+    connection: ConnectionRef<'a>,
+    cursor: tokio_postgres::PortalCursor<'a>,
+}
+
+impl<'a> PortalCursor<'a> {
+    pub(crate) fn new(
+        connection: ConnectionRef<'a>,
+        cursor: tokio_postgres::PortalCursor<'a>,
+    ) -> PortalCursor<'a> {
+        PortalCursor { connection, cursor }
+    }
+
+    /// Returns the portal the rows are fetched from.
+    pub fn portal(&self) -> &Portal {
+        self.cursor.portal()
+    }
+
+    /// Determines if all of the rows of the portal have been returned.
+    pub fn is_done(&self) -> bool {
+        self.cursor.is_done()
+    }
+
+    /// Like `tokio_postgres::PortalCursor::fetch_next_chunk`.
+    pub fn fetch_next_chunk(&mut self, max_rows: i32) -> Result<(Vec<Row>, bool), Error> {
+        let cursor = &mut self.cursor;
+        self.connection.block_on(cursor.fetch_next_chunk(max_rows))
+    }
+
+    /// Closes the portal.
+    ///
+    /// This is equivalent to dropping the `PortalCursor`, but waits for the server to close the portal and provides
+    /// any error encountered to the caller.
+    pub fn close(mut self) -> Result<(), Error> {
+        self.connection.block_on(self.cursor.close())
+    }
+}
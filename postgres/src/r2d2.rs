@@ -0,0 +1,91 @@
+//! A connection pool manager for `r2d2`.
+//!
+//! A [`Manager`] opens connections with a [`Config`]. Connections returned to the pool which have closed or are in the
+//! middle of a transaction are discarded, and connections are checked with `Client::is_valid` before they are handed
+//! out again.
+//!
+//! Requires the `r2d2` Cargo feature.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use postgres::r2d2::{Manager, Pool};
+//! use postgres::NoTls;
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!
+//! let config = "host=localhost user=postgres".parse()?;
+//! let pool = Pool::builder()
+//!     .max_size(16)
+//!     .build(Manager::new(config, NoTls))?;
+//!
+//! let mut client = pool.get()?;
+//! let rows = client.query("SELECT name FROM users", &[])?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::tls::{MakeTlsConnect, TlsConnect};
+use crate::{Client, Config, Error, Socket, TransactionStatus};
+use std::time::Duration;
+
+/// A connection pool of clients opened by a [`Manager`].
+pub type Pool<T> = ::r2d2::Pool<Manager<T>>;
+
+/// A client checked out of a [`Pool`].
+pub type PooledConnection<T> = ::r2d2::PooledConnection<Manager<T>>;
+
+/// An `r2d2` manager opening and validating connections.
+///
+/// See the [module level documentation](self) for details.
+#[derive(Debug)]
+pub struct Manager<T> {
+    config: Config,
+    tls: T,
+    validation_timeout: Duration,
+}
+
+impl<T> Manager<T> {
+    /// Creates a manager opening connections with the given configuration and TLS connector.
+    pub fn new(config: Config, tls: T) -> Manager<T> {
+        Manager {
+            config,
+            tls,
+            validation_timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Sets the time to wait for a connection to answer when checking it before handing it out.
+    ///
+    /// Defaults to 5 seconds.
+    pub fn set_validation_timeout(&mut self, timeout: Duration) {
+        self.validation_timeout = timeout;
+    }
+
+    /// Returns the configuration used to open connections.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+}
+
+impl<T> ::r2d2::ManageConnection for Manager<T>
+where
+    T: MakeTlsConnect<Socket> + Clone + 'static + Sync + Send,
+    T::TlsConnect: Send,
+    T::Stream: Send,
+    <T::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    type Connection = Client;
+    type Error = Error;
+
+    fn connect(&self) -> Result<Client, Error> {
+        self.config.connect(self.tls.clone())
+    }
+
+    fn is_valid(&self, client: &mut Client) -> Result<(), Error> {
+        client.is_valid(self.validation_timeout)
+    }
+
+    fn has_broken(&self, client: &mut Client) -> bool {
+        client.is_closed() || client.transaction_status() != TransactionStatus::Idle
+    }
+}
@@ -0,0 +1,35 @@
+use crate::connection::ConnectionRef;
+use fallible_iterator::FallibleIterator;
+use tokio_postgres::{Error, Row};
+
+/// The iterator returned by `query_chunked`, yielding the rows of a query in batches.
+///
+/// Dropping it rolls back the transaction the query runs in, while `finish` commits it.
+pub struct RowChunks<'a> {
+    connection: ConnectionRef<'a>,
+    chunks: tokio_postgres::RowChunks<'a>,
+}
+
+impl<'a> RowChunks<'a> {
+    pub(crate) fn new(
+        connection: ConnectionRef<'a>,
+        chunks: tokio_postgres::RowChunks<'a>,
+    ) -> RowChunks<'a> {
+        RowChunks { connection, chunks }
+    }
+
+    /// Closes the query and commits the transaction it ran in.
+    pub fn finish(mut self) -> Result<(), Error> {
+        self.connection.block_on(self.chunks.finish())
+    }
+}
+
+impl FallibleIterator for RowChunks<'_> {
+    type Item = Vec<Row>;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Vec<Row>>, Error> {
+        let chunks = &mut self.chunks;
+        self.connection.block_on(chunks.next())
+    }
+}
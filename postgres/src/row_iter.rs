@@ -1,13 +1,32 @@
 use crate::connection::ConnectionRef;
+use crate::QueryCancelToken;
 use fallible_iterator::FallibleIterator;
 use futures_util::StreamExt;
 use std::pin::Pin;
-use tokio_postgres::{Error, Row, RowStream};
+use tokio_postgres::types::BorrowToSql;
+use tokio_postgres::{Column, Error, Portal, Row, RowStream, ToStatement, Transaction};
 
-/// The iterator returned by `query_raw`.
+/// The iterator returned by `query_raw` and its variants.
 pub struct RowIter<'a> {
     connection: ConnectionRef<'a>,
     it: Pin<Box<RowStream>>,
+    chunks: Option<Chunks<'a>>,
+}
+
+// The portal the rows of an iterator returned by `query_raw_chunked` are fetched from.
+struct Chunks<'a> {
+    transaction: Option<Transaction<'a>>,
+    portal: Portal,
+    fetch_size: i32,
+    rows: u64,
+}
+
+impl Drop for RowIter<'_> {
+    fn drop(&mut self) {
+        if let Some(transaction) = self.chunks.as_mut().and_then(|c| c.transaction.take()) {
+            let _ = self.connection.block_on(transaction.rollback());
+        }
+    }
 }
 
 impl<'a> RowIter<'a> {
@@ -15,14 +34,92 @@ impl<'a> RowIter<'a> {
         RowIter {
             connection,
             it: Box::pin(stream),
+            chunks: None,
         }
     }
 
+    pub(crate) fn chunked<T, P, I>(
+        mut connection: ConnectionRef<'a>,
+        transaction: Transaction<'a>,
+        query: &T,
+        params: I,
+        fetch_size: i32,
+    ) -> Result<RowIter<'a>, Error>
+    where
+        T: ?Sized + ToStatement,
+        P: BorrowToSql,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        assert!(fetch_size > 0, "fetch size must be positive");
+
+        let (portal, stream) = connection.block_on(async {
+            let portal = transaction.bind_raw(query, params).await?;
+            let stream = transaction.query_portal_raw(&portal, fetch_size).await?;
+            Ok::<_, Error>((portal, stream))
+        })?;
+        Ok(RowIter {
+            connection,
+            it: Box::pin(stream),
+            chunks: Some(Chunks {
+                transaction: Some(transaction),
+                portal,
+                fetch_size,
+                rows: 0,
+            }),
+        })
+    }
+
+    /// Returns the columns of the rows of the iterator.
+    ///
+    /// The columns of a query which was not prepared, such as with `query_raw_txt`, are only known once the server has
+    /// described its rows. This waits until then, without receiving any row.
+    pub fn columns(&mut self) -> Result<&[Column], Error> {
+        let it = &mut self.it;
+        self.connection.block_on(it.as_mut().ready())?;
+        Ok(self.it.columns().unwrap())
+    }
+
+    /// Returns a token which can be used to cancel the query of the iterator from another thread, without affecting
+    /// the other queries of the connection.
+    ///
+    /// For an iterator returned by `query_raw_chunked`, the token only cancels the fetch of the current chunk.
+    pub fn cancel_token(&self) -> QueryCancelToken {
+        QueryCancelToken::new(self.it.cancel_token())
+    }
+
     /// Returns the number of rows affected by the query.
     ///
     /// This function will return `None` until the iterator has been exhausted.
     pub fn rows_affected(&self) -> Option<u64> {
-        self.it.rows_affected()
+        let rows = self.it.rows_affected()?;
+        match &self.chunks {
+            Some(chunks) => Some(chunks.rows),
+            None => Some(rows),
+        }
+    }
+
+    /// Returns the command tag of the query, such as `SELECT 2`.
+    ///
+    /// This function will return `None` until the iterator has been exhausted.
+    pub fn command_tag(&self) -> Option<String> {
+        let tag = self.it.command_tag()?;
+        match &self.chunks {
+            // The tag of the last chunk only counts its own rows.
+            Some(chunks) => match tag.rsplit_once(' ') {
+                Some((command, _)) => Some(format!("{} {}", command, chunks.rows)),
+                None => Some(tag),
+            },
+            None => Some(tag),
+        }
+    }
+
+    /// Returns the transaction status reported by the server once the query completed.
+    ///
+    /// This is `b'I'` if the connection is idle, `b'T'` if it is in a transaction block and `b'E'` if it is in a failed
+    /// transaction block. This function will return `None` until the iterator has been exhausted.
+    pub fn ready_status(&self) -> Option<u8> {
+        self.it.ready_status()
     }
 }
 
@@ -32,7 +129,29 @@ impl FallibleIterator for RowIter<'_> {
 
     fn next(&mut self) -> Result<Option<Row>, Error> {
         let it = &mut self.it;
-        self.connection
-            .block_on(async { it.next().await.transpose() })
+        let chunks = &mut self.chunks;
+        self.connection.block_on(async {
+            let chunks = match chunks {
+                Some(chunks) => chunks,
+                None => return it.next().await.transpose(),
+            };
+
+            while let Some(transaction) = &chunks.transaction {
+                if let Some(row) = it.next().await.transpose()? {
+                    chunks.rows += 1;
+                    return Ok(Some(row));
+                }
+
+                if it.is_suspended() {
+                    let stream = transaction
+                        .query_portal_raw(&chunks.portal, chunks.fetch_size)
+                        .await?;
+                    *it = Box::pin(stream);
+                } else {
+                    chunks.transaction.take().unwrap().commit().await?;
+                }
+            }
+            Ok(None)
+        })
     }
 }
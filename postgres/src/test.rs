@@ -1,14 +1,19 @@
-use std::io::{Read, Write};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::iter;
 use std::str::FromStr;
 use std::sync::mpsc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio_postgres::error::SqlState;
-use tokio_postgres::types::Type;
+use tokio_postgres::types::{Kind, Type};
 use tokio_postgres::NoTls;
 
 use super::*;
-use crate::binary_copy::{BinaryCopyInWriter, BinaryCopyOutIter};
+use crate::binary_copy::{
+    BinaryCopyInWriter, BinaryCopyOutIter, BinaryCopyOutRow, FromCopyRow, ToCopyRow,
+};
+use crate::copy_options::{self, CopyFormat, CopyOptions};
+use crate::types::ToSql;
 use fallible_iterator::FallibleIterator;
 
 #[test]
@@ -32,6 +37,22 @@ fn query_prepared() {
     assert_eq!(rows[0].get::<_, &str>(0), "hello");
 }
 
+#[test]
+fn query_scalar() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    let value = client
+        .query_scalar::<i32>("SELECT $1::INT4", &[&1i32])
+        .unwrap();
+    assert_eq!(value, 1);
+
+    let mut transaction = client.transaction().unwrap();
+    let value = transaction
+        .query_scalar_opt::<i32>("SELECT 1 WHERE false", &[])
+        .unwrap();
+    assert_eq!(value, None);
+}
+
 #[test]
 fn query_unprepared() {
     let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
@@ -41,6 +62,338 @@ fn query_unprepared() {
     assert_eq!(rows[0].get::<_, &str>(0), "hello");
 }
 
+#[test]
+fn query_cached() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    for value in ["hello", "world"] {
+        let rows = client.query_cached("SELECT $1::TEXT", &[&value]).unwrap();
+        assert_eq!(rows[0].get::<_, &str>(0), value);
+    }
+
+    let mut transaction = client.transaction().unwrap();
+    let rows = transaction
+        .query_cached("SELECT $1::TEXT", &[&"hello"])
+        .unwrap();
+    assert_eq!(rows[0].get::<_, &str>(0), "hello");
+}
+
+#[test]
+fn query_timeout() {
+    let mut client = Config::from_str("host=localhost port=5433 user=postgres")
+        .unwrap()
+        .query_timeout(Duration::from_millis(100))
+        .connect(NoTls)
+        .unwrap();
+
+    let options = QueryOptions::new().timeout(Duration::from_secs(10));
+    let rows = client
+        .query_with_opts("SELECT pg_sleep(0.5)::TEXT", &[], options)
+        .unwrap();
+    assert_eq!(rows.len(), 1);
+
+    let err = client.batch_execute("SELECT pg_sleep(100)").unwrap_err();
+    assert!(err.is_timeout());
+}
+
+#[test]
+fn query_raw_txt() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    let mut it = client
+        .query_raw_txt("SELECT $1::INT + 1, $2::TEXT", [Some("41"), None])
+        .unwrap();
+    let row = it.next().unwrap().unwrap();
+    assert_eq!(row.as_text(0).unwrap(), Some("42"));
+    assert_eq!(row.as_text(1).unwrap(), None);
+    assert!(it.next().unwrap().is_none());
+    assert_eq!(it.rows_affected(), Some(1));
+    assert_eq!(it.command_tag().as_deref(), Some("SELECT 1"));
+    drop(it);
+
+    let mut transaction = client.transaction().unwrap();
+    let rows = transaction
+        .query_raw_txt("SELECT generate_series(1, $1::INT)", [Some("3")])
+        .unwrap()
+        .collect::<Vec<_>>()
+        .unwrap();
+    assert_eq!(rows.len(), 3);
+}
+
+#[test]
+fn query_raw_chunked() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    let mut it = client
+        .query_raw_chunked("SELECT generate_series(1, $1)", [&10], 3)
+        .unwrap();
+    assert_eq!(it.columns().unwrap()[0].type_(), &Type::INT4);
+    let mut ids = vec![];
+    while let Some(row) = it.next().unwrap() {
+        ids.push(row.get::<_, i32>(0));
+    }
+    assert_eq!(ids, (1..=10).collect::<Vec<_>>());
+    assert_eq!(it.rows_affected(), Some(10));
+    assert_eq!(it.command_tag().as_deref(), Some("SELECT 10"));
+    assert!(it.next().unwrap().is_none());
+    drop(it);
+
+    // The transaction of the query is rolled back when the iterator is dropped early.
+    client
+        .simple_query("CREATE TEMPORARY TABLE foo (id INT)")
+        .unwrap();
+    let mut it = client
+        .query_raw_chunked(
+            "INSERT INTO foo SELECT generate_series(1, 10) RETURNING id",
+            iter::empty::<i32>(),
+            2,
+        )
+        .unwrap();
+    it.next().unwrap().unwrap();
+    drop(it);
+    let row = client.query_one("SELECT COUNT(*) FROM foo", &[]).unwrap();
+    assert_eq!(row.get::<_, i64>(0), 0);
+
+    let mut transaction = client.transaction().unwrap();
+    let rows = transaction
+        .query_raw_chunked("SELECT generate_series(1, 5)", iter::empty::<i32>(), 2)
+        .unwrap()
+        .collect::<Vec<_>>()
+        .unwrap();
+    assert_eq!(rows.len(), 5);
+    transaction.commit().unwrap();
+}
+
+#[test]
+fn batch_execute_params() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    let mut transaction = client.transaction().unwrap();
+    let tags = transaction
+        .batch_execute_params(
+            "CREATE TEMPORARY TABLE foo (id INT); INSERT INTO foo (id) VALUES ($1), ($1 + 1)",
+            [1i32],
+        )
+        .unwrap();
+    assert_eq!(tags, ["CREATE TABLE", "INSERT 0 2"]);
+}
+
+#[test]
+fn execute_many() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    client
+        .batch_execute("CREATE TEMPORARY TABLE foo (id INT PRIMARY KEY)")
+        .unwrap();
+
+    let inserted = client
+        .execute_many("INSERT INTO foo (id) VALUES ($1)", (0..100).map(|i| [i]))
+        .unwrap();
+    assert_eq!(inserted, 100);
+
+    let mut transaction = client.transaction().unwrap();
+    let err = transaction
+        .execute_many("INSERT INTO foo (id) VALUES ($1)", [[100], [0]])
+        .unwrap_err();
+    assert_eq!(err.row(), Some(1));
+}
+
+#[test]
+fn query_raw_inferred() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    let mut transaction = client.transaction().unwrap();
+    let rows = transaction
+        .query_raw_inferred("SELECT generate_series(1, $1)", [3i32])
+        .unwrap()
+        .collect::<Vec<_>>()
+        .unwrap();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[2].get::<_, i32>(0), 3);
+}
+
+#[test]
+fn query_one_txt() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    let row = client
+        .query_one_txt("SELECT $1::TEXT", [Some("hello")])
+        .unwrap();
+    assert_eq!(row.get::<_, &str>(0), "hello");
+
+    let mut transaction = client.transaction().unwrap();
+    let row = transaction
+        .query_opt_txt("SELECT 1 WHERE false", [] as [Option<&str>; 0])
+        .unwrap();
+    assert!(row.is_none());
+}
+
+#[test]
+fn set_parameter() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    client
+        .set_parameter("search_path", "public", false)
+        .unwrap();
+    assert_eq!(client.get_parameter("search_path").unwrap(), "public");
+
+    let mut transaction = client.transaction().unwrap();
+    transaction
+        .set_parameter("search_path", "pg_catalog", true)
+        .unwrap();
+    assert_eq!(
+        transaction.get_parameter("search_path").unwrap(),
+        "pg_catalog"
+    );
+    transaction.rollback().unwrap();
+    assert_eq!(client.get_parameter("search_path").unwrap(), "public");
+}
+
+#[test]
+fn schema_introspection() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+    let mut transaction = client.transaction().unwrap();
+
+    transaction
+        .batch_execute(
+            "CREATE SCHEMA sync_introspection;
+            CREATE TABLE sync_introspection.foo (id INT4 PRIMARY KEY, name TEXT);",
+        )
+        .unwrap();
+
+    let tables = transaction.tables("sync_introspection").unwrap();
+    assert_eq!(tables.len(), 1);
+    assert_eq!(tables[0].name(), "foo");
+
+    let columns = transaction.table_columns(tables[0].oid()).unwrap();
+    assert_eq!(columns.len(), 2);
+    assert_eq!(columns[1].type_(), &Type::TEXT);
+    let primary_key = transaction.primary_key(tables[0].oid()).unwrap().unwrap();
+    assert_eq!(primary_key.columns(), ["id"]);
+    assert_eq!(transaction.indexes(tables[0].oid()).unwrap().len(), 1);
+}
+
+#[cfg(feature = "migrations")]
+#[test]
+fn run_migrations() {
+    use crate::migrations::Migrations;
+
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+    client
+        .batch_execute("DROP TABLE IF EXISTS sync_migrations_log")
+        .unwrap();
+
+    let mut migrations = Migrations::new();
+    migrations
+        .table("sync_migrations_log")
+        .sql(1, "noop", "SELECT 1");
+    assert_eq!(client.run_migrations(&migrations).unwrap(), [1]);
+    assert!(client.run_migrations(&migrations).unwrap().is_empty());
+
+    client
+        .batch_execute("DROP TABLE sync_migrations_log")
+        .unwrap();
+}
+
+#[cfg(feature = "arrow")]
+#[test]
+fn query_arrow() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    let batches = client
+        .query_arrow(
+            "SELECT i, i::TEXT AS s FROM generate_series(1, 5) i",
+            &[],
+            2,
+        )
+        .unwrap();
+    assert_eq!(
+        batches.iter().map(|b| b.num_rows()).collect::<Vec<_>>(),
+        [2, 2, 1]
+    );
+    let schema = batches[0].schema();
+    assert_eq!(schema.field(0).name(), "i");
+    assert_eq!(schema.field(1).name(), "s");
+}
+
+#[test]
+fn pipeline() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    client
+        .batch_execute("CREATE TEMPORARY TABLE foo (id INT PRIMARY KEY)")
+        .unwrap();
+
+    let mut transaction = client.transaction().unwrap();
+    let mut pipeline = transaction.pipeline();
+    pipeline
+        .execute("INSERT INTO foo (id) VALUES ($1)", &[&1i32])
+        .execute("INSERT INTO foo (id) VALUES ($1)", &[&2i32])
+        .query("SELECT id FROM foo ORDER BY id", &[]);
+    let results = pipeline.run().unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[1].as_ref().unwrap().rows_affected(), Some(1));
+    let rows = results
+        .into_iter()
+        .nth(2)
+        .unwrap()
+        .unwrap()
+        .into_rows()
+        .unwrap();
+    assert_eq!(rows.len(), 2);
+    transaction.commit().unwrap();
+
+    let mut pipeline = client.pipeline();
+    pipeline.execute("INSERT INTO foo (id) VALUES ($1)", &[&1i32]);
+    let results = pipeline.run().unwrap();
+    assert_eq!(
+        results[0].as_ref().unwrap_err().code(),
+        Some(&SqlState::UNIQUE_VIOLATION)
+    );
+
+    let mut pipeline = client.pipeline();
+    pipeline
+        .execute("INSERT INTO foo (id) VALUES ($1)", &[&3i32])
+        .execute("INSERT INTO foo (id) VALUES ($1)", &[&1i32]);
+    let err = pipeline.run_batch().unwrap_err();
+    assert_eq!(err.code(), Some(&SqlState::UNIQUE_VIOLATION));
+    let row = client.query_one("SELECT count(*) FROM foo", &[]).unwrap();
+    assert_eq!(row.get::<_, i64>(0), 2);
+}
+
+#[test]
+fn row_iter_metadata() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    let mut it = client
+        .query_raw("SELECT generate_series(1, 2)", std::iter::empty::<i32>())
+        .unwrap();
+    assert_eq!(it.command_tag(), None);
+    assert_eq!(it.ready_status(), None);
+    while it.next().unwrap().is_some() {}
+    assert_eq!(it.rows_affected(), Some(2));
+    assert_eq!(it.command_tag().as_deref(), Some("SELECT 2"));
+    assert_eq!(it.ready_status(), Some(b'I'));
+    drop(it);
+
+    let mut transaction = client.transaction().unwrap();
+    let mut it = transaction
+        .query_raw("SELECT 1", std::iter::empty::<i32>())
+        .unwrap();
+    while it.next().unwrap().is_some() {}
+    assert_eq!(it.ready_status(), Some(b'T'));
+    drop(it);
+
+    let mut it = transaction
+        .query_raw_txt(
+            "SELECT 1 AS one WHERE false",
+            std::iter::empty::<Option<&str>>(),
+        )
+        .unwrap();
+    assert_eq!(it.columns().unwrap()[0].name(), "one");
+    assert!(it.next().unwrap().is_none());
+}
+
 #[test]
 fn transaction_commit() {
     let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
@@ -62,6 +415,33 @@ fn transaction_commit() {
     assert_eq!(rows[0].get::<_, i32>(0), 1);
 }
 
+#[test]
+fn transaction_builder() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    let mut transaction = client
+        .build_transaction()
+        .isolation_level(IsolationLevel::Serializable)
+        .read_only(true)
+        .set_local("search_path", "pg_catalog")
+        .start()
+        .unwrap();
+
+    let row = transaction
+        .query_one(
+            "SELECT current_setting('transaction_isolation'), \
+                current_setting('transaction_read_only'), \
+                current_setting('search_path')",
+            &[],
+        )
+        .unwrap();
+    assert_eq!(row.get::<_, &str>(0), "serializable");
+    assert_eq!(row.get::<_, &str>(1), "on");
+    assert_eq!(row.get::<_, &str>(2), "pg_catalog");
+
+    transaction.commit().unwrap();
+}
+
 #[test]
 fn transaction_rollback() {
     let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
@@ -102,6 +482,67 @@ fn transaction_drop() {
     assert_eq!(rows.len(), 0);
 }
 
+#[test]
+fn with_transaction() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    client
+        .simple_query("CREATE TEMPORARY TABLE foo (id INT PRIMARY KEY)")
+        .unwrap();
+
+    let depths = client
+        .with_transaction(|transaction| {
+            transaction.execute("INSERT INTO foo VALUES (1)", &[])?;
+            let depths = transaction.with_savepoint("nested", |transaction| {
+                transaction.execute("INSERT INTO foo VALUES (2)", &[])?;
+                Ok::<_, Error>((
+                    transaction.savepoint_name().unwrap().to_string(),
+                    transaction.depth(),
+                ))
+            })?;
+
+            // A failed savepoint is rolled back without affecting its transaction.
+            let err = transaction
+                .with_savepoint("duplicate", |transaction| {
+                    transaction.execute("INSERT INTO foo VALUES (3)", &[])?;
+                    transaction.execute("INSERT INTO foo VALUES (1)", &[])
+                })
+                .unwrap_err();
+            assert_eq!(err.code(), Some(&SqlState::UNIQUE_VIOLATION));
+
+            assert_eq!(transaction.savepoint_name(), None);
+            Ok::<_, Error>((depths, transaction.depth()))
+        })
+        .unwrap();
+    assert_eq!(depths, (("nested".to_string(), 1), 0));
+
+    let err = client
+        .with_transaction(|transaction| {
+            transaction.execute("INSERT INTO foo VALUES (4)", &[])?;
+            Err::<(), Box<dyn std::error::Error>>("failed".into())
+        })
+        .unwrap_err();
+    assert_eq!(err.to_string(), "failed");
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        client.with_transaction(|transaction| {
+            transaction.execute("INSERT INTO foo VALUES (5)", &[])?;
+            panic!("failed");
+            #[allow(unreachable_code)]
+            Ok::<_, Error>(())
+        })
+    }));
+    assert!(result.is_err());
+
+    let ids = client
+        .query("SELECT id FROM foo ORDER BY id", &[])
+        .unwrap()
+        .iter()
+        .map(|row| row.get(0))
+        .collect::<Vec<i32>>();
+    assert_eq!(ids, [1, 2]);
+}
+
 #[test]
 fn transaction_drop_immediate_rollback() {
     let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
@@ -142,6 +583,8 @@ fn nested_transactions() {
         .unwrap();
 
     let mut transaction2 = transaction.transaction().unwrap();
+    assert_eq!(transaction2.savepoint_name(), Some("sp_1"));
+    assert_eq!(transaction2.depth(), 1);
 
     transaction2
         .execute("INSERT INTO foo (id) VALUES (2)", &[])
@@ -213,6 +656,8 @@ fn savepoints() {
         .unwrap();
 
     let mut savepoint3 = savepoint2.savepoint("savepoint3").unwrap();
+    assert_eq!(savepoint3.savepoint_name(), Some("savepoint3"));
+    assert_eq!(savepoint3.depth(), 2);
 
     savepoint3
         .execute("INSERT INTO foo (id) VALUES(4)", &[])
@@ -239,6 +684,9 @@ fn copy_in() {
 
     let mut writer = client.copy_in("COPY foo FROM stdin").unwrap();
     writer.write_all(b"1\tsteven\n2\ttimothy").unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.bytes_written(), 18);
+    assert_eq!(writer.chunks_written(), 1);
     writer.finish().unwrap();
 
     let rows = client
@@ -344,61 +792,378 @@ fn binary_copy_out() {
 }
 
 #[test]
-fn portal() {
+fn typed_binary_copy() {
+    struct Foo {
+        id: i32,
+        name: String,
+    }
+
+    impl ToCopyRow for Foo {
+        fn to_copy_row(&self) -> Vec<&(dyn ToSql + Sync)> {
+            vec![&self.id, &self.name]
+        }
+    }
+
+    impl FromCopyRow for Foo {
+        fn from_copy_row(row: &BinaryCopyOutRow) -> Result<Foo, Error> {
+            Ok(Foo {
+                id: row.try_get(0)?,
+                name: row.try_get(1)?,
+            })
+        }
+    }
+
     let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
 
     client
-        .simple_query(
-            "CREATE TEMPORARY TABLE foo (id INT);
-             INSERT INTO foo (id) VALUES (1), (2), (3);",
-        )
+        .simple_query("CREATE TEMPORARY TABLE foo (id INT, name TEXT)")
         .unwrap();
 
-    let mut transaction = client.transaction().unwrap();
-
-    let portal = transaction
-        .bind("SELECT * FROM foo ORDER BY id", &[])
-        .unwrap();
+    let writer = client.copy_in("COPY foo FROM stdin BINARY").unwrap();
+    let mut writer = BinaryCopyInWriter::new(writer, &[Type::INT4, Type::TEXT]);
+    for (id, name) in [(1, "steven"), (2, "timothy")] {
+        writer
+            .write_row(&Foo {
+                id,
+                name: name.to_string(),
+            })
+            .unwrap();
+    }
+    assert_eq!(writer.rows_written(), 2);
+    assert_eq!(writer.finish().unwrap(), 2);
+
+    let reader = client.copy_out("COPY foo TO STDOUT BINARY").unwrap();
+    let mut iter = BinaryCopyOutIter::new(reader, &[Type::INT4, Type::TEXT]);
+    let mut rows = vec![];
+    while let Some(row) = iter.next().unwrap() {
+        rows.push(row.decode::<Foo>().unwrap());
+    }
+    assert_eq!(iter.rows_read(), 2);
+    drop(iter);
 
-    let rows = transaction.query_portal(&portal, 2).unwrap();
     assert_eq!(rows.len(), 2);
-    assert_eq!(rows[0].get::<_, i32>(0), 1);
-    assert_eq!(rows[1].get::<_, i32>(0), 2);
-
-    let rows = transaction.query_portal(&portal, 2).unwrap();
-    assert_eq!(rows.len(), 1);
-    assert_eq!(rows[0].get::<_, i32>(0), 3);
+    assert_eq!(rows[0].id, 1);
+    assert_eq!(rows[0].name, "steven");
+    assert_eq!(rows[1].id, 2);
+    assert_eq!(rows[1].name, "timothy");
 }
 
 #[test]
-fn cancel_query() {
+fn csv_copy() {
     let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
 
-    let cancel_token = client.cancel_token();
-    let cancel_thread = thread::spawn(move || {
-        thread::sleep(Duration::from_millis(100));
-        cancel_token.cancel_query(NoTls).unwrap();
-    });
+    client
+        .simple_query("CREATE TEMPORARY TABLE foo (id INT, name TEXT)")
+        .unwrap();
 
-    match client.batch_execute("SELECT pg_sleep(100)") {
-        Err(e) if e.code() == Some(&SqlState::QUERY_CANCELED) => {}
-        t => panic!("unexpected return: {:?}", t),
-    }
+    let options = CopyOptions::new()
+        .format(CopyFormat::Csv)
+        .delimiter(';')
+        .null("nil")
+        .quote('\'')
+        .header(true);
 
-    cancel_thread.join().unwrap();
+    let mut writer = client.copy_in(&*options.copy_in("foo (id, name)")).unwrap();
+    writer.write_all(b"id;name\n1;'steven'\n2;nil\n").unwrap();
+    writer.finish().unwrap();
+
+    let mut reader = client
+        .copy_out(&*options.copy_out("(SELECT * FROM foo ORDER BY id)"))
+        .unwrap();
+    let mut s = String::new();
+    reader.read_to_string(&mut s).unwrap();
+    drop(reader);
+
+    assert_eq!(s, "id;name\n1;steven\n2;nil\n");
+
+    let name = client
+        .query_one("SELECT name FROM foo WHERE id = 1", &[])
+        .unwrap()
+        .get::<_, String>(0);
+    assert_eq!(name, "steven");
 }
 
 #[test]
-fn notifications_iter() {
-    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+fn copy_transfer() {
+    let mut source = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+    let mut target = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
 
-    client
+    source
         .batch_execute(
-            "\
-        LISTEN notifications_iter;
-        NOTIFY notifications_iter, 'hello';
-        NOTIFY notifications_iter, 'world';
-    ",
+            "CREATE TEMPORARY TABLE foo (id INT, name TEXT);
+            INSERT INTO foo VALUES (1, 'jim'), (2, NULL);",
+        )
+        .unwrap();
+    target
+        .batch_execute("CREATE TEMPORARY TABLE bar (id INT, name TEXT)")
+        .unwrap();
+
+    let options = CopyOptions::new().format(CopyFormat::Binary);
+    let rows = copy_options::transfer(&mut source, "foo", &mut target, "bar", &options).unwrap();
+    assert_eq!(rows, 2);
+
+    let options = CopyOptions::new().format(CopyFormat::Csv);
+    let rows = copy_options::restore(&mut target, "bar", &options, &b"3,joe\n"[..]).unwrap();
+    assert_eq!(rows, 1);
+
+    let mut reader =
+        copy_options::dump(&mut target, "(SELECT * FROM bar ORDER BY id)", &options).unwrap();
+    let mut s = String::new();
+    reader.read_to_string(&mut s).unwrap();
+    assert_eq!(s, "1,jim\n2,\n3,joe\n");
+}
+
+#[test]
+fn copy_writer_and_reader() {
+    let mut source = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+    let mut target = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    source
+        .batch_execute(
+            "CREATE TEMPORARY TABLE foo (id INT, name TEXT);
+            INSERT INTO foo SELECT i, 'name ' || i FROM generate_series(1, 1000) i;",
+        )
+        .unwrap();
+    target
+        .batch_execute("CREATE TEMPORARY TABLE foo (id INT, name TEXT)")
+        .unwrap();
+
+    let mut data = vec![];
+    let bytes = source
+        .copy_to_writer("COPY foo TO STDOUT", &mut data, 100)
+        .unwrap();
+    assert_eq!(bytes, data.len() as u64);
+    assert!(data.starts_with(b"1\tname 1\n2\tname 2\n"));
+
+    let rows = target
+        .copy_from_reader("COPY foo FROM STDIN", &data[..], 100)
+        .unwrap();
+    assert_eq!(rows, 1000);
+
+    let rows = copy_options::copy_table(&mut source, &mut target, "foo (id)").unwrap();
+    assert_eq!(rows, 1000);
+
+    let mut transaction = target.transaction().unwrap();
+    let mut copied = vec![];
+    transaction
+        .copy_to_writer(
+            "COPY (SELECT count(*), count(name) FROM foo) TO STDOUT",
+            &mut copied,
+            100,
+        )
+        .unwrap();
+    assert_eq!(copied, b"2000\t1000\n");
+}
+
+#[test]
+fn close_portal() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    let stmt = client.prepare("SELECT generate_series(1, 3)").unwrap();
+    let mut transaction = client.transaction().unwrap();
+    let portal = transaction.bind(&stmt, &[]).unwrap();
+    assert_eq!(transaction.query_portal(&portal, 2).unwrap().len(), 2);
+    transaction.close_portal(portal).unwrap();
+    transaction.commit().unwrap();
+}
+
+#[test]
+fn cursor() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    let cursor = client
+        .declare_cursor("SELECT generate_series(1, 3)", &[])
+        .unwrap();
+    assert_eq!(client.fetch(&cursor, 2).unwrap().len(), 2);
+
+    let mut transaction = client.transaction().unwrap();
+    assert_eq!(transaction.fetch(&cursor, 2).unwrap().len(), 1);
+    transaction.commit().unwrap();
+
+    client.close_cursor(cursor).unwrap();
+}
+
+#[test]
+fn query_chunked() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    let sizes = client
+        .query_chunked("SELECT generate_series(1, 5)", &[], 2)
+        .unwrap()
+        .map(|rows| Ok(rows.len()))
+        .collect::<Vec<_>>()
+        .unwrap();
+    assert_eq!(sizes, [2, 2, 1]);
+
+    let mut transaction = client.transaction().unwrap();
+    let mut chunks = transaction
+        .query_chunked("SELECT generate_series(1, 3)", &[], 3)
+        .unwrap();
+    assert_eq!(chunks.next().unwrap().unwrap().len(), 3);
+    assert!(chunks.next().unwrap().is_none());
+    chunks.finish().unwrap();
+    transaction.commit().unwrap();
+}
+
+#[test]
+fn query_rows() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    let rows = client
+        .query_rows("SELECT generate_series(1, 3) AS id, 'foo' AS name", &[])
+        .unwrap();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows.get::<_, i32>(2, "id"), 3);
+    let names = rows
+        .iter()
+        .map(|row| row.get::<_, &str>(1))
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["foo", "foo", "foo"]);
+
+    let mut transaction = client.transaction().unwrap();
+    let rows = transaction.query_rows("SELECT 1 WHERE false", &[]).unwrap();
+    assert!(rows.is_empty());
+    transaction.commit().unwrap();
+}
+
+#[test]
+fn query_with_limit() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    let rows = client
+        .query_with_limit("SELECT generate_series(1, 10)", &[], 3)
+        .unwrap();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[2].get::<_, i32>(0), 3);
+
+    let mut transaction = client.transaction().unwrap();
+    let rows = transaction
+        .query_with_limit("SELECT generate_series(1, 10)", &[], 20)
+        .unwrap();
+    assert_eq!(rows.len(), 10);
+    transaction.commit().unwrap();
+}
+
+#[test]
+fn portal() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    client
+        .simple_query(
+            "CREATE TEMPORARY TABLE foo (id INT);
+             INSERT INTO foo (id) VALUES (1), (2), (3);",
+        )
+        .unwrap();
+
+    let mut transaction = client.transaction().unwrap();
+
+    let portal = transaction
+        .bind("SELECT * FROM foo ORDER BY id", &[])
+        .unwrap();
+
+    let rows = transaction.query_portal(&portal, 2).unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].get::<_, i32>(0), 1);
+    assert_eq!(rows[1].get::<_, i32>(0), 2);
+
+    let rows = transaction.query_portal(&portal, 2).unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].get::<_, i32>(0), 3);
+}
+
+#[test]
+fn portal_cursor() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+    let mut transaction = client.transaction().unwrap();
+
+    let mut cursor = transaction
+        .bind_cursor("SELECT generate_series(1, 3)", &[])
+        .unwrap();
+    let (rows, more) = cursor.fetch_next_chunk(2).unwrap();
+    assert_eq!(rows.len(), 2);
+    assert!(more);
+    let (rows, more) = cursor.fetch_next_chunk(2).unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].get::<_, i32>(0), 3);
+    assert!(!more);
+    cursor.close().unwrap();
+}
+
+#[test]
+fn cancel_query() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    let cancel_token = client.cancel_token();
+    let cancel_thread = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(100));
+        cancel_token.cancel_query(NoTls).unwrap();
+    });
+
+    match client.batch_execute("SELECT pg_sleep(100)") {
+        Err(e) if e.code() == Some(&SqlState::QUERY_CANCELED) => {}
+        t => panic!("unexpected return: {:?}", t),
+    }
+
+    cancel_thread.join().unwrap();
+}
+
+#[test]
+fn cancel_query_timeout() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    let cancel_token = client.cancel_token();
+    let cancel_thread = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(100));
+        cancel_token
+            .cancel_query_timeout(NoTls, Duration::from_secs(10))
+            .unwrap();
+    });
+
+    match client.batch_execute("SELECT pg_sleep(100)") {
+        Err(e) if e.code() == Some(&SqlState::QUERY_CANCELED) => {}
+        t => panic!("unexpected return: {:?}", t),
+    }
+
+    cancel_thread.join().unwrap();
+
+    client.cancel_query(NoTls, Duration::from_secs(10)).unwrap();
+    client.batch_execute("SELECT 1").unwrap();
+}
+
+#[test]
+fn cancel_query_with() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    let cancel_token = client.cancel_token();
+    let cancel_thread = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(100));
+        let options = CancelOptions::new()
+            .timeout(Duration::from_secs(10))
+            .retries(1);
+        assert!(cancel_token
+            .cancel_query_with(NoTls, options)
+            .is_delivered());
+    });
+
+    match client.batch_execute("SELECT pg_sleep(100)") {
+        Err(e) if e.code() == Some(&SqlState::QUERY_CANCELED) => {}
+        t => panic!("unexpected return: {:?}", t),
+    }
+
+    cancel_thread.join().unwrap();
+}
+
+#[test]
+fn notifications_iter() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    client
+        .batch_execute(
+            "\
+        LISTEN notifications_iter;
+        NOTIFY notifications_iter, 'hello';
+        NOTIFY notifications_iter, 'world';
+    ",
         )
         .unwrap();
 
@@ -478,6 +1243,69 @@ fn notifications_timeout_iter() {
     assert_eq!(notifications[1].payload(), "world");
 }
 
+#[test]
+fn listener() {
+    let config = "host=localhost port=5433 user=postgres application_name=listener"
+        .parse()
+        .unwrap();
+    let mut listener = Listener::connect(config, NoTls).unwrap();
+    listener.listen("listener_a").unwrap();
+    listener.listen("listener \"b\"").unwrap();
+    assert_eq!(
+        listener.channels().collect::<Vec<_>>(),
+        ["listener \"b\"", "listener_a"]
+    );
+    assert!(listener.try_recv().unwrap().is_none());
+
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+    client
+        .batch_execute("NOTIFY listener_a, 'hello'; NOTIFY \"listener \"\"b\"\"\", 'world'")
+        .unwrap();
+
+    let notification = listener.recv().unwrap();
+    assert_eq!(notification.channel(), "listener_a");
+    assert_eq!(notification.payload(), "hello");
+    let notification = listener.iter().next().unwrap().unwrap();
+    assert_eq!(notification.channel(), "listener \"b\"");
+    assert!(listener
+        .recv_timeout(Duration::from_millis(100))
+        .unwrap()
+        .is_none());
+
+    client
+        .batch_execute(
+            "SELECT pg_terminate_backend(pid) FROM pg_stat_activity \
+             WHERE application_name = 'listener'",
+        )
+        .unwrap();
+    assert!(listener
+        .recv_timeout(Duration::from_millis(500))
+        .unwrap()
+        .is_none());
+    assert_eq!(listener.reconnects(), 1);
+
+    client.batch_execute("NOTIFY listener_a, 'again'").unwrap();
+    let notification = listener
+        .recv_timeout(Duration::from_secs(5))
+        .unwrap()
+        .unwrap();
+    assert_eq!(notification.payload(), "again");
+
+    client.notify("listener \"b\"", "it's").unwrap();
+    let notification = listener.recv().unwrap();
+    assert_eq!(notification.channel(), "listener \"b\"");
+    assert_eq!(notification.payload(), "it's");
+
+    listener.unlisten("listener_a").unwrap();
+    client
+        .batch_execute("NOTIFY listener_a, 'ignored'")
+        .unwrap();
+    assert!(listener
+        .recv_timeout(Duration::from_millis(100))
+        .unwrap()
+        .is_none());
+}
+
 #[test]
 fn notice_callback() {
     let (notice_tx, notice_rx) = mpsc::sync_channel(64);
@@ -494,6 +1322,364 @@ fn notice_callback() {
     assert_eq!(notice_rx.recv().unwrap().message(), "custom");
 }
 
+#[test]
+fn server_parameters() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    assert_eq!(client.client_encoding().as_deref(), Some("UTF8"));
+    assert!(client.server_version().is_some());
+
+    let row = client.query_one("SELECT pg_backend_pid()", &[]).unwrap();
+    assert_eq!(client.backend_pid(), row.get::<_, i32>(0));
+
+    client.batch_execute("SET TimeZone = 'UTC'").unwrap();
+    assert_eq!(client.timezone().as_deref(), Some("UTC"));
+}
+
+#[test]
+fn connect_blocking() {
+    for params in [
+        "host=localhost port=5433 user=postgres",
+        "host=/tmp port=5433 user=postgres",
+    ] {
+        let config = params.parse::<Config>().unwrap();
+        let mut client = config.connect_blocking(NoTls).unwrap();
+
+        client
+            .batch_execute("CREATE TEMPORARY TABLE foo (id SERIAL, name TEXT)")
+            .unwrap();
+        let mut transaction = client.transaction().unwrap();
+        transaction
+            .execute(
+                "INSERT INTO foo (name) VALUES ($1), ($2)",
+                &[&"steven", &"timothy"],
+            )
+            .unwrap();
+        transaction.commit().unwrap();
+
+        let rows = client
+            .query("SELECT name FROM foo ORDER BY id", &[])
+            .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].get::<_, &str>(0), "steven");
+        assert_eq!(rows[1].get::<_, &str>(0), "timothy");
+
+        let mut writer = client.copy_in("COPY foo (name) FROM STDIN").unwrap();
+        writer.write_all(b"jim\n").unwrap();
+        writer.finish().unwrap();
+        let mut data = String::new();
+        client
+            .copy_out("COPY (SELECT name FROM foo ORDER BY id) TO STDOUT")
+            .unwrap()
+            .read_to_string(&mut data)
+            .unwrap();
+        assert_eq!(data, "steven\ntimothy\njim\n");
+
+        client.is_valid(Duration::from_secs(1)).unwrap();
+
+        client.batch_execute("LISTEN blocking").unwrap();
+        let start = Instant::now();
+        let notifications = client
+            .notifications()
+            .timeout_iter(Duration::from_millis(100))
+            .collect::<Vec<_>>()
+            .unwrap();
+        assert!(notifications.is_empty());
+        assert!(start.elapsed() >= Duration::from_millis(100));
+
+        let notifier = thread::spawn(|| {
+            let mut client =
+                Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+            thread::sleep(Duration::from_millis(50));
+            client.batch_execute("NOTIFY blocking, 'hello'").unwrap();
+        });
+        let notification = client
+            .notifications()
+            .blocking_iter()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(notification.payload(), "hello");
+        notifier.join().unwrap();
+
+        client.close().unwrap();
+    }
+}
+
+#[test]
+fn connect_blocking_query_timeout() {
+    let mut config = "host=localhost port=5433 user=postgres"
+        .parse::<Config>()
+        .unwrap();
+    config.query_timeout(Duration::from_secs(1));
+    let err = config.connect_blocking(NoTls).err().unwrap();
+    assert!(err.to_string().contains("query_timeout"), "{}", err);
+
+    let config = "host=localhost port=5433 user=postgres"
+        .parse::<Config>()
+        .unwrap();
+    let mut client = config.connect_blocking(NoTls).unwrap();
+    let options = QueryOptions::new().timeout(Duration::from_secs(1));
+    let err = client
+        .query_with_opts("SELECT 1", &[], options)
+        .unwrap_err();
+    assert!(err.to_string().contains("query timeouts"), "{}", err);
+    let err = client
+        .execute_with_opts("SELECT 1", &[], options)
+        .unwrap_err();
+    assert!(err.to_string().contains("query timeouts"), "{}", err);
+
+    // The client is still usable.
+    let rows = client
+        .query_with_opts("SELECT 1", &[], QueryOptions::new())
+        .unwrap();
+    assert_eq!(rows.len(), 1);
+}
+
+#[test]
+fn connect_blocking_keepalive_query_interval() {
+    let config = "host=localhost port=5433 user=postgres keepalive_query_interval=1"
+        .parse::<Config>()
+        .unwrap();
+    let err = config.connect_blocking(NoTls).err().unwrap();
+    assert!(
+        err.to_string().contains("keepalive_query_interval"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn socket_dir() {
+    let mut config = Config::new();
+    config.socket_dir("/tmp").port(5433).user("postgres");
+    let mut client = config.connect(NoTls).unwrap();
+    client.simple_query("SELECT 1").unwrap();
+    let mut client = config.connect_blocking(NoTls).unwrap();
+    client.simple_query("SELECT 1").unwrap();
+
+    let mut config = Config::new();
+    config
+        .socket_dir("/tmp/.s.PGSQL.5433")
+        .port(5433)
+        .user("postgres");
+    config.connect(NoTls).err().unwrap();
+}
+
+#[test]
+fn describe() {
+    fn describe_generic<C: GenericClient>(client: &mut C) -> StatementDescription {
+        client
+            .describe("SELECT $1::INT4 AS a, 'x'::TEXT AS b")
+            .unwrap()
+    }
+
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    let description = describe_generic(&mut client);
+    assert_eq!(description.params(), &[Type::INT4]);
+    let columns = description.columns();
+    assert_eq!(columns.len(), 2);
+    assert_eq!(columns[0].name(), "a");
+    assert_eq!(columns[0].type_(), &Type::INT4);
+    assert_eq!(columns[1].name(), "b");
+    assert_eq!(columns[1].type_(), &Type::TEXT);
+
+    let mut transaction = client.transaction().unwrap();
+    let description = describe_generic(&mut transaction);
+    assert_eq!(description.params(), &[Type::INT4]);
+}
+
+#[test]
+fn prepare_unnamed() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    let first = client.prepare_unnamed("SELECT $1::INT4", &[]).unwrap();
+    assert_eq!(
+        client.query_one(&first, &[&1i32]).unwrap().get::<_, i32>(0),
+        1
+    );
+
+    let mut transaction = client.transaction().unwrap();
+    assert!(transaction.query(&first, &[&1i32]).is_err());
+    let second = transaction.prepare_unnamed("SELECT 2", &[]).unwrap();
+    assert_eq!(
+        transaction
+            .query_one(&second, &[])
+            .unwrap()
+            .get::<_, i32>(0),
+        2
+    );
+}
+
+#[test]
+fn refresh_statement() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    client
+        .batch_execute("CREATE TEMPORARY TABLE foo (id INT); INSERT INTO foo VALUES (1)")
+        .unwrap();
+    let mut statement = client.prepare("SELECT * FROM foo").unwrap();
+    client
+        .batch_execute("ALTER TABLE foo ADD COLUMN name TEXT DEFAULT 'steven'")
+        .unwrap();
+
+    let mut transaction = client.transaction().unwrap();
+    transaction.refresh_statement(&mut statement).unwrap();
+    assert_eq!(statement.columns().len(), 2);
+    let row = transaction.query_one(&statement, &[]).unwrap();
+    assert_eq!(row.get::<_, &str>("name"), "steven");
+    transaction.commit().unwrap();
+
+    client
+        .batch_execute("ALTER TABLE foo ADD COLUMN age INT")
+        .unwrap();
+    client.refresh_statement(&mut statement).unwrap();
+    assert_eq!(statement.columns().len(), 3);
+}
+
+#[test]
+fn resolve_type() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    client
+        .batch_execute("CREATE TYPE pg_temp.mood AS ENUM ('sad', 'happy')")
+        .unwrap();
+    let oid = client
+        .query_one("SELECT 'mood'::regtype::oid", &[])
+        .unwrap()
+        .get(0);
+
+    let ty = client.resolve_type(oid).unwrap();
+    assert_eq!(ty.name(), "mood");
+    assert_eq!(
+        ty.kind(),
+        &Kind::Enum(vec!["sad".to_string(), "happy".to_string()])
+    );
+
+    let mut transaction = client.transaction().unwrap();
+    assert_eq!(transaction.resolve_type(oid).unwrap(), ty);
+}
+
+#[test]
+fn register_types() {
+    let mut client = Client::connect(
+        "host=localhost port=5433 user=postgres resolve_types=0",
+        NoTls,
+    )
+    .unwrap();
+
+    client
+        .batch_execute("CREATE TYPE pg_temp.mood AS ENUM ('sad', 'happy')")
+        .unwrap();
+    let row = client
+        .query_one(
+            "SELECT oid, typnamespace::regnamespace::text FROM pg_type WHERE oid = 'mood'::regtype",
+            &[],
+        )
+        .unwrap();
+    let ty = Type::new(
+        "mood".to_string(),
+        row.get(0),
+        Kind::Enum(vec!["sad".to_string(), "happy".to_string()]),
+        row.get(1),
+    );
+
+    client.register_types(vec![ty.clone()]);
+    let stmt = client.prepare("SELECT 'sad'::mood").unwrap();
+    assert_eq!(stmt.columns()[0].type_(), &ty);
+}
+
+#[test]
+fn after_connect() {
+    let mut client = "host=localhost port=5433 user=postgres"
+        .parse::<Config>()
+        .unwrap()
+        .after_connect("SET application_name = hooked")
+        .connect(NoTls)
+        .unwrap();
+
+    let row = client
+        .query_one("SELECT current_setting('application_name')", &[])
+        .unwrap();
+    assert_eq!(row.get::<_, &str>(0), "hooked");
+}
+
+#[test]
+fn transaction_status() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+    assert_eq!(client.transaction_status(), TransactionStatus::Idle);
+
+    let mut transaction = client.transaction().unwrap();
+    transaction.batch_execute("SELECT 1/0").unwrap_err();
+    drop(transaction);
+    assert_eq!(client.transaction_status(), TransactionStatus::Idle);
+
+    client.batch_execute("BEGIN").unwrap();
+    assert_eq!(
+        client.transaction_status(),
+        TransactionStatus::InTransaction
+    );
+    client.batch_execute("COMMIT").unwrap();
+    assert_eq!(client.transaction_status(), TransactionStatus::Idle);
+}
+
+#[cfg(feature = "r2d2")]
+#[test]
+fn r2d2_pool() {
+    use crate::r2d2::{Manager, Pool};
+
+    let config = "host=localhost port=5433 user=postgres".parse().unwrap();
+    let pool = Pool::builder()
+        .max_size(1)
+        .build(Manager::new(config, NoTls))
+        .unwrap();
+
+    let mut client = pool.get().unwrap();
+    let row = client.query_one("SELECT pg_backend_pid()", &[]).unwrap();
+    let pid = row.get::<_, i32>(0);
+    drop(client);
+
+    let mut client = pool.get().unwrap();
+    let row = client.query_one("SELECT pg_backend_pid()", &[]).unwrap();
+    assert_eq!(row.get::<_, i32>(0), pid);
+    client.batch_execute("BEGIN").unwrap();
+    drop(client);
+
+    let mut client = pool.get().unwrap();
+    assert_eq!(client.transaction_status(), TransactionStatus::Idle);
+    let row = client.query_one("SELECT pg_backend_pid()", &[]).unwrap();
+    assert_ne!(row.get::<_, i32>(0), pid);
+}
+
+#[test]
+fn reconnect() {
+    let mut config = "host=localhost port=5433 user=postgres application_name=reconnect"
+        .parse::<Config>()
+        .unwrap();
+    config.session_setup(|client| client.batch_execute("SET TimeZone = 'Europe/Paris'"));
+    let mut client = config.connect_with_reconnect(NoTls).unwrap();
+    assert_eq!(client.timezone().as_deref(), Some("Europe/Paris"));
+
+    let mut other = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+    other
+        .batch_execute(
+            "SELECT pg_terminate_backend(pid) FROM pg_stat_activity \
+             WHERE application_name = 'reconnect'",
+        )
+        .unwrap();
+    thread::sleep(Duration::from_millis(100));
+
+    client.batch_execute("SELECT 1").unwrap_err();
+    assert!(client.is_closed());
+    assert_eq!(client.reconnects(), 0);
+
+    let row = client.query_one("SHOW TimeZone", &[]).unwrap();
+    assert_eq!(row.get::<_, &str>(0), "Europe/Paris");
+    assert!(!client.is_closed());
+    assert_eq!(client.reconnects(), 1);
+}
+
 #[test]
 fn explicit_close() {
     let client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
@@ -508,3 +1694,22 @@ fn check_send() {
     is_send::<Statement>();
     is_send::<Transaction<'_>>();
 }
+
+#[test]
+fn large_object() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    let mut transaction = client.transaction().unwrap();
+    let oid = transaction.create_large_object().unwrap();
+    let mut object = transaction
+        .open_large_object(oid, large_object::Mode::ReadWrite)
+        .unwrap();
+    object.write_all(b"hello world").unwrap();
+    object.seek(SeekFrom::Start(6)).unwrap();
+    let mut data = String::new();
+    object.read_to_string(&mut data).unwrap();
+    assert_eq!(data, "world");
+    object.close().unwrap();
+    transaction.unlink_large_object(oid).unwrap();
+    transaction.commit().unwrap();
+}
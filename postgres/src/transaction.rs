@@ -1,7 +1,16 @@
 use crate::connection::ConnectionRef;
-use crate::{CancelToken, CopyInWriter, CopyOutReader, Portal, RowIter, Statement, ToStatement};
-use tokio_postgres::types::{BorrowToSql, ToSql, Type};
-use tokio_postgres::{Error, Row, SimpleQueryMessage};
+use crate::large_object::{LargeObject, Mode};
+use crate::pipeline::Pipeline;
+use crate::{
+    copy_in_writer, copy_out_reader, CancelToken, CopyInWriter, CopyOutReader, Portal,
+    PortalCursor, QueryOptions, RowChunks, RowIter, Statement, ToStatement,
+};
+use std::io::{Read, Write};
+use tokio_postgres::schema::{ForeignKey, Index, PrimaryKey, Table, TableColumn};
+use tokio_postgres::types::{BorrowToSql, Format, FromRow, FromSqlOwned, Oid, ToSql, ToText, Type};
+use tokio_postgres::{
+    Cursor, Error, ExecuteManyError, Row, Rows, SimpleQueryMessage, StatementDescription,
+};
 
 /// A representation of a PostgreSQL database transaction.
 ///
@@ -61,6 +70,102 @@ impl<'a> Transaction<'a> {
         )
     }
 
+    /// Like `Client::prepare_unnamed`.
+    pub fn prepare_unnamed(&mut self, query: &str, types: &[Type]) -> Result<Statement, Error> {
+        self.connection.block_on(
+            self.transaction
+                .as_ref()
+                .unwrap()
+                .prepare_unnamed(query, types),
+        )
+    }
+
+    /// Like `Client::refresh_statement`.
+    pub fn refresh_statement(&mut self, statement: &mut Statement) -> Result<(), Error> {
+        let client = self.transaction.as_ref().unwrap().client();
+        self.connection.block_on(statement.refresh(client))
+    }
+
+    /// Like `Client::describe`.
+    pub fn describe(&mut self, query: &str) -> Result<StatementDescription, Error> {
+        self.connection
+            .block_on(self.transaction.as_ref().unwrap().describe(query))
+    }
+
+    /// Like `Client::describe_typed`.
+    pub fn describe_typed(
+        &mut self,
+        query: &str,
+        parameter_types: &[Type],
+    ) -> Result<StatementDescription, Error> {
+        self.connection.block_on(
+            self.transaction
+                .as_ref()
+                .unwrap()
+                .describe_typed(query, parameter_types),
+        )
+    }
+
+    /// Like `Client::resolve_type`.
+    pub fn resolve_type(&mut self, oid: Oid) -> Result<Type, Error> {
+        self.connection
+            .block_on(self.transaction.as_ref().unwrap().resolve_type(oid))
+    }
+
+    /// Like `Client::create_large_object`.
+    pub fn create_large_object(&mut self) -> Result<Oid, Error> {
+        self.connection
+            .block_on(self.transaction.as_ref().unwrap().create_large_object())
+    }
+
+    /// Like `Client::unlink_large_object`.
+    pub fn unlink_large_object(&mut self, oid: Oid) -> Result<(), Error> {
+        self.connection
+            .block_on(self.transaction.as_ref().unwrap().unlink_large_object(oid))
+    }
+
+    /// Opens the large object with the given OID.
+    ///
+    /// The handle is only valid until the end of the transaction. See the [`large_object`](crate::large_object)
+    /// module for details.
+    pub fn open_large_object(&mut self, oid: Oid, mode: Mode) -> Result<LargeObject<'_>, Error> {
+        let transaction = self.transaction.as_ref().unwrap();
+        let object = self
+            .connection
+            .block_on(transaction.open_large_object(oid, mode))?;
+        Ok(LargeObject::new(self.connection.as_ref(), object))
+    }
+
+    /// Like `Client::tables`.
+    pub fn tables(&mut self, schema: &str) -> Result<Vec<Table>, Error> {
+        self.connection
+            .block_on(self.transaction.as_ref().unwrap().tables(schema))
+    }
+
+    /// Like `Client::table_columns`.
+    pub fn table_columns(&mut self, table: Oid) -> Result<Vec<TableColumn>, Error> {
+        self.connection
+            .block_on(self.transaction.as_ref().unwrap().table_columns(table))
+    }
+
+    /// Like `Client::primary_key`.
+    pub fn primary_key(&mut self, table: Oid) -> Result<Option<PrimaryKey>, Error> {
+        self.connection
+            .block_on(self.transaction.as_ref().unwrap().primary_key(table))
+    }
+
+    /// Like `Client::foreign_keys`.
+    pub fn foreign_keys(&mut self, table: Oid) -> Result<Vec<ForeignKey>, Error> {
+        self.connection
+            .block_on(self.transaction.as_ref().unwrap().foreign_keys(table))
+    }
+
+    /// Like `Client::indexes`.
+    pub fn indexes(&mut self, table: Oid) -> Result<Vec<Index>, Error> {
+        self.connection
+            .block_on(self.transaction.as_ref().unwrap().indexes(table))
+    }
+
     /// Like `Client::execute`.
     pub fn execute<T>(&mut self, query: &T, params: &[&(dyn ToSql + Sync)]) -> Result<u64, Error>
     where
@@ -70,6 +175,39 @@ impl<'a> Transaction<'a> {
             .block_on(self.transaction.as_ref().unwrap().execute(query, params))
     }
 
+    /// Like `Client::execute_with_opts`.
+    pub fn execute_with_opts<T>(
+        &mut self,
+        query: &T,
+        params: &[&(dyn ToSql + Sync)],
+        options: QueryOptions,
+    ) -> Result<u64, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        self.connection.check_options(&options)?;
+        self.connection.block_on(
+            self.transaction
+                .as_ref()
+                .unwrap()
+                .execute_with_opts(query, params, options),
+        )
+    }
+
+    /// Like `Client::execute_many`.
+    pub fn execute_many<T, P, I, R>(&mut self, query: &T, rows: R) -> Result<u64, ExecuteManyError>
+    where
+        T: ?Sized + ToStatement,
+        P: BorrowToSql,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+        R: IntoIterator<Item = I>,
+    {
+        let transaction = self.transaction.as_ref().unwrap();
+        let execute = async { Ok(transaction.execute_many(query, rows).await) };
+        self.connection.block_on(execute)?
+    }
+
     /// Like `Client::query`.
     pub fn query<T>(&mut self, query: &T, params: &[&(dyn ToSql + Sync)]) -> Result<Vec<Row>, Error>
     where
@@ -79,6 +217,70 @@ impl<'a> Transaction<'a> {
             .block_on(self.transaction.as_ref().unwrap().query(query, params))
     }
 
+    /// Like `Client::query_with_opts`.
+    pub fn query_with_opts<T>(
+        &mut self,
+        query: &T,
+        params: &[&(dyn ToSql + Sync)],
+        options: QueryOptions,
+    ) -> Result<Vec<Row>, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        self.connection.check_options(&options)?;
+        self.connection.block_on(
+            self.transaction
+                .as_ref()
+                .unwrap()
+                .query_with_opts(query, params, options),
+        )
+    }
+
+    /// Like `Client::query_with_limit`.
+    pub fn query_with_limit<T>(
+        &mut self,
+        query: &T,
+        params: &[&(dyn ToSql + Sync)],
+        max_rows: i32,
+    ) -> Result<Vec<Row>, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        self.connection.block_on(
+            self.transaction
+                .as_ref()
+                .unwrap()
+                .query_with_limit(query, params, max_rows),
+        )
+    }
+
+    /// Like `Client::query_rows`.
+    pub fn query_rows<T>(
+        &mut self,
+        query: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Rows, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        self.connection
+            .block_on(self.transaction.as_ref().unwrap().query_rows(query, params))
+    }
+
+    /// Like `Client::query_cached`.
+    pub fn query_cached(
+        &mut self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, Error> {
+        self.connection.block_on(
+            self.transaction
+                .as_ref()
+                .unwrap()
+                .query_cached(query, params),
+        )
+    }
+
     /// Like `Client::query_one`.
     pub fn query_one<T>(&mut self, query: &T, params: &[&(dyn ToSql + Sync)]) -> Result<Row, Error>
     where
@@ -101,6 +303,53 @@ impl<'a> Transaction<'a> {
             .block_on(self.transaction.as_ref().unwrap().query_opt(query, params))
     }
 
+    /// Like `Client::query_scalar`.
+    pub fn query_scalar<R>(
+        &mut self,
+        query: &(impl ?Sized + ToStatement),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<R, Error>
+    where
+        R: FromSqlOwned,
+    {
+        self.connection.block_on(
+            self.transaction
+                .as_ref()
+                .unwrap()
+                .query_scalar(query, params),
+        )
+    }
+
+    /// Like `Client::query_scalar_opt`.
+    pub fn query_scalar_opt<R>(
+        &mut self,
+        query: &(impl ?Sized + ToStatement),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<R>, Error>
+    where
+        R: FromSqlOwned,
+    {
+        self.connection.block_on(
+            self.transaction
+                .as_ref()
+                .unwrap()
+                .query_scalar_opt(query, params),
+        )
+    }
+
+    /// Like `Client::query_as`.
+    pub fn query_as<R>(
+        &mut self,
+        query: &(impl ?Sized + ToStatement),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<R>, Error>
+    where
+        R: FromRow,
+    {
+        self.connection
+            .block_on(self.transaction.as_ref().unwrap().query_as(query, params))
+    }
+
     /// Like `Client::query_raw`.
     pub fn query_raw<T, P, I>(&mut self, query: &T, params: I) -> Result<RowIter<'_>, Error>
     where
@@ -115,6 +364,138 @@ impl<'a> Transaction<'a> {
         Ok(RowIter::new(self.connection.as_ref(), stream))
     }
 
+    /// Like `Client::query_raw_chunked`, but binds the statement in a nested transaction, created with a savepoint.
+    pub fn query_raw_chunked<T, P, I>(
+        &mut self,
+        query: &T,
+        params: I,
+        fetch_size: i32,
+    ) -> Result<RowIter<'_>, Error>
+    where
+        T: ?Sized + ToStatement,
+        P: BorrowToSql,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let transaction = self
+            .connection
+            .block_on(self.transaction.as_mut().unwrap().transaction())?;
+        RowIter::chunked(
+            self.connection.as_ref(),
+            transaction,
+            query,
+            params,
+            fetch_size,
+        )
+    }
+
+    /// Like `Client::query_raw_txt`.
+    pub fn query_raw_txt<P, I>(&mut self, query: &str, params: I) -> Result<RowIter<'_>, Error>
+    where
+        P: ToText,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let stream = self.connection.block_on(
+            self.transaction
+                .as_ref()
+                .unwrap()
+                .query_raw_txt(query, params),
+        )?;
+        Ok(RowIter::new(self.connection.as_ref(), stream))
+    }
+
+    /// Like `Client::query_one_txt`.
+    pub fn query_one_txt<P, I>(&mut self, query: &str, params: I) -> Result<Row, Error>
+    where
+        P: ToText,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.connection.block_on(
+            self.transaction
+                .as_ref()
+                .unwrap()
+                .query_one_txt(query, params),
+        )
+    }
+
+    /// Like `Client::query_opt_txt`.
+    pub fn query_opt_txt<P, I>(&mut self, query: &str, params: I) -> Result<Option<Row>, Error>
+    where
+        P: ToText,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.connection.block_on(
+            self.transaction
+                .as_ref()
+                .unwrap()
+                .query_opt_txt(query, params),
+        )
+    }
+
+    /// Like `Client::query_with_format`.
+    pub fn query_with_format<P, I>(
+        &mut self,
+        query: &str,
+        params: I,
+        param_formats: &[Format],
+        result_format: Format,
+    ) -> Result<RowIter<'_>, Error>
+    where
+        P: AsRef<[u8]>,
+        I: IntoIterator<Item = Option<P>>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let stream =
+            self.connection
+                .block_on(self.transaction.as_ref().unwrap().query_with_format(
+                    query,
+                    params,
+                    param_formats,
+                    result_format,
+                ))?;
+        Ok(RowIter::new(self.connection.as_ref(), stream))
+    }
+
+    /// Like `Client::query_raw_inferred`.
+    pub fn query_raw_inferred<P, I>(&mut self, query: &str, params: I) -> Result<RowIter<'_>, Error>
+    where
+        P: BorrowToSql,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let stream = self.connection.block_on(
+            self.transaction
+                .as_ref()
+                .unwrap()
+                .query_raw_inferred(query, params),
+        )?;
+        Ok(RowIter::new(self.connection.as_ref(), stream))
+    }
+
+    /// Like `Client::query_chunked`.
+    ///
+    /// The portal is bound in a savepoint of this transaction.
+    pub fn query_chunked<T>(
+        &mut self,
+        query: &T,
+        params: &[&(dyn ToSql + Sync)],
+        chunk_size: i32,
+    ) -> Result<RowChunks<'_>, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        let chunks = self.connection.block_on(
+            self.transaction
+                .as_mut()
+                .unwrap()
+                .query_chunked(query, params, chunk_size),
+        )?;
+        Ok(RowChunks::new(self.connection.as_ref(), chunks))
+    }
+
     /// Binds parameters to a statement, creating a "portal".
     ///
     /// Portals can be used with the `query_portal` method to page through the results of a query without being forced
@@ -133,6 +514,28 @@ impl<'a> Transaction<'a> {
             .block_on(self.transaction.as_ref().unwrap().bind(query, params))
     }
 
+    /// Binds parameters to a statement, returning a `PortalCursor` which fetches the resulting rows in chunks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of parameters provided does not match the number expected.
+    pub fn bind_cursor<T>(
+        &mut self,
+        query: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<PortalCursor<'_>, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        let cursor = self.connection.block_on(
+            self.transaction
+                .as_ref()
+                .unwrap()
+                .bind_cursor(query, params),
+        )?;
+        Ok(PortalCursor::new(self.connection.as_ref(), cursor))
+    }
+
     /// Continues execution of a portal, returning the next set of rows.
     ///
     /// Unlike `query`, portals can be incrementally evaluated by limiting the number of rows returned in each call to
@@ -161,6 +564,42 @@ impl<'a> Transaction<'a> {
         Ok(RowIter::new(self.connection.as_ref(), stream))
     }
 
+    /// Closes a portal.
+    ///
+    /// This is equivalent to `Portal`'s `Drop` implementation, but waits for the server to close the portal and
+    /// provides any error encountered to the caller.
+    pub fn close_portal(&mut self, portal: Portal) -> Result<(), Error> {
+        self.connection.block_on(portal.close())
+    }
+
+    /// Like `Client::declare_cursor`.
+    ///
+    /// The rows of the cursor are kept by the server once the transaction commits.
+    pub fn declare_cursor(
+        &mut self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Cursor, Error> {
+        self.connection.block_on(
+            self.transaction
+                .as_ref()
+                .unwrap()
+                .declare_cursor(query, params),
+        )
+    }
+
+    /// Like `Client::fetch`.
+    pub fn fetch(&mut self, cursor: &Cursor, count: i64) -> Result<Vec<Row>, Error> {
+        self.connection
+            .block_on(self.transaction.as_ref().unwrap().fetch(cursor, count))
+    }
+
+    /// Like `Client::close_cursor`.
+    pub fn close_cursor(&mut self, cursor: Cursor) -> Result<(), Error> {
+        self.connection
+            .block_on(self.transaction.as_ref().unwrap().close_cursor(cursor))
+    }
+
     /// Like `Client::copy_in`.
     pub fn copy_in<T>(&mut self, query: &T) -> Result<CopyInWriter<'_>, Error>
     where
@@ -183,18 +622,108 @@ impl<'a> Transaction<'a> {
         Ok(CopyOutReader::new(self.connection.as_ref(), stream))
     }
 
+    /// Like `Client::copy_to_writer`.
+    pub fn copy_to_writer<T, W>(
+        &mut self,
+        query: &T,
+        writer: W,
+        buffer_size: usize,
+    ) -> Result<u64, Error>
+    where
+        T: ?Sized + ToStatement,
+        W: Write,
+    {
+        let reader = self.copy_out(query)?;
+        copy_out_reader::copy_to_writer(reader, writer, buffer_size)
+    }
+
+    /// Like `Client::copy_from_reader`.
+    pub fn copy_from_reader<T, R>(
+        &mut self,
+        query: &T,
+        reader: R,
+        buffer_size: usize,
+    ) -> Result<u64, Error>
+    where
+        T: ?Sized + ToStatement,
+        R: Read,
+    {
+        let writer = self.copy_in(query)?;
+        copy_in_writer::copy_from_reader(writer, reader, buffer_size)
+    }
+
     /// Like `Client::simple_query`.
     pub fn simple_query(&mut self, query: &str) -> Result<Vec<SimpleQueryMessage>, Error> {
         self.connection
             .block_on(self.transaction.as_ref().unwrap().simple_query(query))
     }
 
+    /// Like `Client::pipeline`.
+    pub fn pipeline(&mut self) -> Pipeline<'_> {
+        Pipeline::new(
+            self.connection.as_ref(),
+            self.transaction.as_ref().unwrap().pipeline(),
+        )
+    }
+
     /// Like `Client::batch_execute`.
     pub fn batch_execute(&mut self, query: &str) -> Result<(), Error> {
         self.connection
             .block_on(self.transaction.as_ref().unwrap().batch_execute(query))
     }
 
+    /// Like `Client::batch_execute_params`.
+    pub fn batch_execute_params<P, I>(
+        &mut self,
+        query: &str,
+        params: I,
+    ) -> Result<Vec<String>, Error>
+    where
+        P: ToText,
+        I: IntoIterator<Item = P>,
+    {
+        self.connection.block_on(
+            self.transaction
+                .as_ref()
+                .unwrap()
+                .batch_execute_params(query, params),
+        )
+    }
+
+    /// Like `Client::notify`.
+    pub fn notify(&mut self, channel: &str, payload: &str) -> Result<(), Error> {
+        self.connection
+            .block_on(self.transaction.as_ref().unwrap().notify(channel, payload))
+    }
+
+    /// Like `Client::unlisten`.
+    pub fn unlisten(&mut self, channel: &str) -> Result<(), Error> {
+        self.connection
+            .block_on(self.transaction.as_ref().unwrap().unlisten(channel))
+    }
+
+    /// Like `Client::unlisten_all`.
+    pub fn unlisten_all(&mut self) -> Result<(), Error> {
+        self.connection
+            .block_on(self.transaction.as_ref().unwrap().unlisten_all())
+    }
+
+    /// Like `Client::set_parameter`.
+    pub fn set_parameter(&mut self, name: &str, value: &str, local: bool) -> Result<(), Error> {
+        self.connection.block_on(
+            self.transaction
+                .as_ref()
+                .unwrap()
+                .set_parameter(name, value, local),
+        )
+    }
+
+    /// Like `Client::get_parameter`.
+    pub fn get_parameter(&mut self, name: &str) -> Result<String, Error> {
+        self.connection
+            .block_on(self.transaction.as_ref().unwrap().get_parameter(name))
+    }
+
     /// Like `Client::cancel_token`.
     pub fn cancel_token(&self) -> CancelToken {
         CancelToken::new(self.transaction.as_ref().unwrap().cancel_token())
@@ -218,4 +747,30 @@ impl<'a> Transaction<'a> {
             .block_on(self.transaction.as_mut().unwrap().savepoint(name))?;
         Ok(Transaction::new(self.connection.as_ref(), transaction))
     }
+
+    /// Runs a closure in a nested transaction via a savepoint with the specified name.
+    ///
+    /// Like `Client::with_transaction`, the savepoint is released if the closure returns `Ok`, and rolled back if it
+    /// returns an error or panics.
+    pub fn with_savepoint<I, F, T, E>(&mut self, name: I, f: F) -> Result<T, E>
+    where
+        I: Into<String>,
+        F: FnOnce(&mut Transaction<'_>) -> Result<T, E>,
+        E: From<Error>,
+    {
+        let mut transaction = self.savepoint(name)?;
+        let value = f(&mut transaction)?;
+        transaction.commit()?;
+        Ok(value)
+    }
+
+    /// Like `tokio_postgres::Transaction::savepoint_name`.
+    pub fn savepoint_name(&self) -> Option<&str> {
+        self.transaction.as_ref().unwrap().savepoint_name()
+    }
+
+    /// Like `tokio_postgres::Transaction::depth`.
+    pub fn depth(&self) -> u32 {
+        self.transaction.as_ref().unwrap().depth()
+    }
 }
@@ -40,9 +40,29 @@ impl<'a> TransactionBuilder<'a> {
         self
     }
 
+    /// Makes the transaction see the same data as another transaction, by importing the snapshot it exported with
+    /// `pg_export_snapshot()`.
+    ///
+    /// The isolation level of the transaction must be `RepeatableRead` or `Serializable`.
+    pub fn snapshot(mut self, snapshot_id: &str) -> Self {
+        self.builder = self.builder.snapshot(snapshot_id);
+        self
+    }
+
+    /// Sets a configuration parameter for the duration of the transaction, like `SET LOCAL`.
+    ///
+    /// Parameters are set in the order of the calls to this method.
+    pub fn set_local(mut self, name: &str, value: &str) -> Self {
+        self.builder = self.builder.set_local(name, value);
+        self
+    }
+
     /// Begins the transaction.
     ///
     /// The transaction will roll back by default - use the `commit` method to commit it.
+    ///
+    /// If the snapshot can't be imported or a parameter can't be set, the transaction is rolled back and an error is
+    /// returned.
     pub fn start(mut self) -> Result<Transaction<'a>, Error> {
         let transaction = self.connection.block_on(self.builder.start())?;
         Ok(Transaction::new(self.connection, transaction))
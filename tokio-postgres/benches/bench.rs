@@ -54,5 +54,81 @@ fn query_prepared(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, query_prepared);
+fn large_bind(c: &mut Criterion) {
+    let (client, runtime) = setup();
+    let statement = runtime
+        .block_on(client.prepare("SELECT length($1::BYTEA)"))
+        .unwrap();
+    let value = vec![0u8; 8 * 1024 * 1024];
+
+    c.bench_function("large_bind", move |b| {
+        b.iter(|| {
+            runtime
+                .block_on(client.query(&statement, &[&value]))
+                .unwrap()
+        })
+    });
+}
+
+fn wide_rows(c: &mut Criterion) {
+    let (client, runtime) = setup();
+    let columns = (0..128)
+        .map(|i| format!("{0}::INT4 AS column_{0}", i))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let rows = runtime
+        .block_on(client.query(
+            &*format!("SELECT {} FROM generate_series(1, 100)", columns),
+            &[],
+        ))
+        .unwrap();
+    let names = (0..128)
+        .map(|i| format!("column_{}", i))
+        .collect::<Vec<_>>();
+
+    c.bench_function("wide_rows_by_name", |b| {
+        b.iter(|| {
+            let mut sum = 0;
+            for row in &rows {
+                for name in &names {
+                    sum += row.get::<_, i32>(&**name);
+                }
+            }
+            sum
+        })
+    });
+
+    c.bench_function("wide_rows_by_index", |b| {
+        b.iter(|| {
+            let mut sum = 0;
+            for row in &rows {
+                for i in 0..row.len() {
+                    sum += row.try_get_by_index::<i32>(i).unwrap();
+                }
+            }
+            sum
+        })
+    });
+}
+
+fn many_rows(c: &mut Criterion) {
+    let (client, runtime) = setup();
+    let statement = runtime
+        .block_on(client.prepare("SELECT i, i::TEXT FROM generate_series(1, 100000) i"))
+        .unwrap();
+
+    c.bench_function("many_rows_vec", |b| {
+        b.iter(|| runtime.block_on(client.query(&statement, &[])).unwrap())
+    });
+
+    c.bench_function("many_rows_columnar", |b| {
+        b.iter(|| {
+            runtime
+                .block_on(client.query_rows(&statement, &[]))
+                .unwrap()
+        })
+    });
+}
+
+criterion_group!(benches, query_prepared, large_bind, wide_rows, many_rows);
 criterion_main!(benches);
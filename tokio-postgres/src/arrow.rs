@@ -0,0 +1,693 @@
+//! Conversion of query results to Apache Arrow record batches.
+//!
+//! [`Client::query_arrow`](crate::Client::query_arrow) executes a query and returns a stream of [`RecordBatch`]es
+//! holding its rows. The schema of the batches is derived from the columns of the statement, and the rows, received in
+//! the binary format, are decoded a column at a time once enough of them have been received to fill a batch.
+//!
+//! Bulk transfers can skip the conversion of the rows altogether with the COPY converters:
+//! [`RecordBatchCopyOutStream`] decodes the rows of a `COPY ... TO STDOUT (FORMAT binary)` query into record batches,
+//! and [`RecordBatchCopyInWriter`] encodes record batches into the rows of a `COPY ... FROM STDIN (FORMAT binary)`
+//! query.
+//!
+//! The following types are supported, as well as domains over them:
+//!
+//! | Postgres type | Arrow type |
+//! | ------------- | ---------- |
+//! | `BOOL` | `Boolean` |
+//! | `INT2` | `Int16` |
+//! | `INT4` | `Int32` |
+//! | `INT8` | `Int64` |
+//! | `OID` | `UInt32` |
+//! | `FLOAT4` | `Float32` |
+//! | `FLOAT8` | `Float64` |
+//! | `NUMERIC` | `Utf8` |
+//! | `TEXT`, `VARCHAR`, `BPCHAR`, `NAME`, `UNKNOWN`, enums | `Utf8` |
+//! | `JSON`, `JSONB` | `Utf8` |
+//! | `BYTEA` | `Binary` |
+//! | `UUID` | `FixedSizeBinary(16)` |
+//! | `DATE` | `Date32` |
+//! | `TIME` | `Time64(Microsecond)` |
+//! | `TIMESTAMP` | `Timestamp(Microsecond, None)` |
+//! | `TIMESTAMPTZ` | `Timestamp(Microsecond, Some("UTC"))` |
+//! | `INTERVAL` | `Interval(MonthDayNano)` |
+//!
+//! All of the fields of the schemas are nullable. Infinite dates and timestamps can't be represented in Arrow, and are
+//! reported as errors. When writing, `TIMESTAMPTZ` columns accept timestamps of any time zone, and intervals with a
+//! precision finer than microseconds are reported as errors.
+//!
+//! Requires the `arrow` Cargo feature.
+
+use crate::binary_copy::{BinaryCopyOutRow, BinaryCopyOutStream};
+use crate::types::{Kind, Type};
+use crate::{Column, CopyInSink, CopyOutStream, Error, Row, RowStream};
+use arrow_array::builder::{
+    BinaryBuilder, BooleanBuilder, FixedSizeBinaryBuilder, PrimitiveBuilder, StringBuilder,
+};
+use arrow_array::cast::AsArray;
+use arrow_array::types::{
+    Date32Type, Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, IntervalMonthDayNano,
+    IntervalMonthDayNanoType, Time64MicrosecondType, TimestampMicrosecondType, UInt32Type,
+};
+use arrow_array::{Array, ArrayRef, ArrowPrimitiveType, RecordBatchOptions};
+use arrow_schema::{DataType, Field, IntervalUnit, TimeUnit};
+use byteorder::{BigEndian, ReadBytesExt};
+use bytes::{BufMut, Bytes, BytesMut};
+use futures_util::{ready, SinkExt, Stream};
+use pin_project_lite::pin_project;
+use postgres_protocol::message::frontend::BindError;
+use postgres_protocol::types;
+use postgres_protocol::{binary_copy, IsNull};
+use std::convert::TryInto;
+use std::error;
+use std::mem;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+pub use arrow_array::RecordBatch;
+pub use arrow_schema::{Schema, SchemaRef};
+
+type BoxError = Box<dyn error::Error + Sync + Send>;
+
+// The number of days and microseconds between the Unix epoch and the Postgres epoch, 2000-01-01.
+const EPOCH_DAYS: i32 = 10_957;
+const EPOCH_MICROS: i64 = 946_684_800_000_000;
+
+/// Returns the Arrow data type the values of a Postgres type are converted to, or `None` if the type isn't supported.
+pub fn data_type(ty: &Type) -> Option<DataType> {
+    Encoding::new(ty).map(Encoding::data_type)
+}
+
+/// Returns the Arrow schema of rows with the given columns.
+///
+/// Returns an error if the type of a column isn't supported.
+pub fn schema(columns: &[Column]) -> Result<Schema, Error> {
+    let (schema, _) = schema_and_encodings(columns.iter().map(|c| (c.name(), c.type_())))?;
+    Ok(schema)
+}
+
+// Returns the schema of rows with the given column names and types, and the encodings of their values.
+fn schema_and_encodings<'a, I>(columns: I) -> Result<(Schema, Vec<Encoding>), Error>
+where
+    I: IntoIterator<Item = (&'a str, &'a Type)>,
+{
+    let mut fields = vec![];
+    let mut encodings = vec![];
+    for (idx, (name, type_)) in columns.into_iter().enumerate() {
+        let encoding =
+            Encoding::new(type_).ok_or_else(|| Error::from_sql(unsupported(type_), idx))?;
+        fields.push(Field::new(name, encoding.data_type(), true));
+        encodings.push(encoding);
+    }
+    Ok((Schema::new(fields), encodings))
+}
+
+fn unsupported(type_: &Type) -> BoxError {
+    format!(
+        "the type {} is not supported by the Arrow conversions",
+        type_
+    )
+    .into()
+}
+
+// The binary encoding of the values of a supported type.
+#[derive(Copy, Clone)]
+enum Encoding {
+    Bool,
+    Int2,
+    Int4,
+    Int8,
+    Oid,
+    Float4,
+    Float8,
+    Numeric,
+    Text,
+    Jsonb,
+    Bytea,
+    Uuid,
+    Date,
+    Time,
+    Timestamp,
+    Timestamptz,
+    Interval,
+}
+
+impl Encoding {
+    fn new(ty: &Type) -> Option<Encoding> {
+        let encoding = match *ty {
+            Type::BOOL => Encoding::Bool,
+            Type::INT2 => Encoding::Int2,
+            Type::INT4 => Encoding::Int4,
+            Type::INT8 => Encoding::Int8,
+            Type::OID => Encoding::Oid,
+            Type::FLOAT4 => Encoding::Float4,
+            Type::FLOAT8 => Encoding::Float8,
+            Type::NUMERIC => Encoding::Numeric,
+            Type::TEXT | Type::VARCHAR | Type::BPCHAR | Type::NAME | Type::UNKNOWN | Type::JSON => {
+                Encoding::Text
+            }
+            Type::JSONB => Encoding::Jsonb,
+            Type::BYTEA => Encoding::Bytea,
+            Type::UUID => Encoding::Uuid,
+            Type::DATE => Encoding::Date,
+            Type::TIME => Encoding::Time,
+            Type::TIMESTAMP => Encoding::Timestamp,
+            Type::TIMESTAMPTZ => Encoding::Timestamptz,
+            Type::INTERVAL => Encoding::Interval,
+            _ => match ty.kind() {
+                Kind::Domain(ty) => return Encoding::new(ty),
+                Kind::Enum(_) => Encoding::Text,
+                _ => return None,
+            },
+        };
+        Some(encoding)
+    }
+
+    fn data_type(self) -> DataType {
+        match self {
+            Encoding::Bool => DataType::Boolean,
+            Encoding::Int2 => DataType::Int16,
+            Encoding::Int4 => DataType::Int32,
+            Encoding::Int8 => DataType::Int64,
+            Encoding::Oid => DataType::UInt32,
+            Encoding::Float4 => DataType::Float32,
+            Encoding::Float8 => DataType::Float64,
+            Encoding::Numeric | Encoding::Text | Encoding::Jsonb => DataType::Utf8,
+            Encoding::Bytea => DataType::Binary,
+            Encoding::Uuid => DataType::FixedSizeBinary(16),
+            Encoding::Date => DataType::Date32,
+            Encoding::Time => DataType::Time64(TimeUnit::Microsecond),
+            Encoding::Timestamp => DataType::Timestamp(TimeUnit::Microsecond, None),
+            Encoding::Timestamptz => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            Encoding::Interval => DataType::Interval(IntervalUnit::MonthDayNano),
+        }
+    }
+
+    // Returns whether the values of an array of the given type can be encoded.
+    fn accepts(self, data_type: &DataType) -> bool {
+        match (self, data_type) {
+            (Encoding::Timestamptz, DataType::Timestamp(TimeUnit::Microsecond, Some(_))) => true,
+            _ => *data_type == self.data_type(),
+        }
+    }
+
+    // Encodes the value of a row of an array, whose type is accepted by the encoding.
+    fn encode(self, array: &dyn Array, row: usize, buf: &mut BytesMut) -> Result<IsNull, BoxError> {
+        if array.is_null(row) {
+            return Ok(IsNull::Yes);
+        }
+
+        match self {
+            Encoding::Bool => types::bool_to_sql(array.as_boolean().value(row), buf),
+            Encoding::Int2 => types::int2_to_sql(array.as_primitive::<Int16Type>().value(row), buf),
+            Encoding::Int4 => types::int4_to_sql(array.as_primitive::<Int32Type>().value(row), buf),
+            Encoding::Int8 => types::int8_to_sql(array.as_primitive::<Int64Type>().value(row), buf),
+            Encoding::Oid => types::oid_to_sql(array.as_primitive::<UInt32Type>().value(row), buf),
+            Encoding::Float4 => {
+                types::float4_to_sql(array.as_primitive::<Float32Type>().value(row), buf)
+            }
+            Encoding::Float8 => {
+                types::float8_to_sql(array.as_primitive::<Float64Type>().value(row), buf)
+            }
+            Encoding::Numeric => types::numeric_to_sql(array.as_string::<i32>().value(row), buf)?,
+            Encoding::Text => types::text_to_sql(array.as_string::<i32>().value(row), buf),
+            Encoding::Jsonb => {
+                buf.put_u8(1);
+                types::text_to_sql(array.as_string::<i32>().value(row), buf);
+            }
+            Encoding::Bytea => types::bytea_to_sql(array.as_binary::<i32>().value(row), buf),
+            Encoding::Uuid => {
+                let uuid = array.as_fixed_size_binary().value(row).try_into()?;
+                types::uuid_to_sql(uuid, buf);
+            }
+            Encoding::Date => {
+                let days = array.as_primitive::<Date32Type>().value(row);
+                let days = days
+                    .checked_sub(EPOCH_DAYS)
+                    .ok_or("date out of range for Postgres")?;
+                types::date_to_sql(days, buf);
+            }
+            Encoding::Time => types::time_to_sql(
+                array.as_primitive::<Time64MicrosecondType>().value(row),
+                buf,
+            ),
+            Encoding::Timestamp | Encoding::Timestamptz => {
+                let micros = array.as_primitive::<TimestampMicrosecondType>().value(row);
+                let micros = micros
+                    .checked_sub(EPOCH_MICROS)
+                    .ok_or("timestamp out of range for Postgres")?;
+                types::timestamp_to_sql(micros, buf);
+            }
+            Encoding::Interval => {
+                let interval = array.as_primitive::<IntervalMonthDayNanoType>().value(row);
+                if interval.nanoseconds % 1000 != 0 {
+                    return Err("intervals with a precision finer than microseconds can't be converted to Postgres".into());
+                }
+                buf.put_i64(interval.nanoseconds / 1000);
+                buf.put_i32(interval.days);
+                buf.put_i32(interval.months);
+            }
+        }
+        Ok(IsNull::No)
+    }
+
+    // Decodes the values of a column into an array.
+    fn decode<'a, I>(self, values: I) -> Result<ArrayRef, BoxError>
+    where
+        I: ExactSizeIterator<Item = Option<&'a [u8]>>,
+    {
+        match self {
+            Encoding::Bool => {
+                let mut builder = BooleanBuilder::with_capacity(values.len());
+                for value in values {
+                    match value {
+                        Some(buf) => builder.append_value(types::bool_from_sql(buf)?),
+                        None => builder.append_null(),
+                    }
+                }
+                Ok(Arc::new(builder.finish()))
+            }
+            Encoding::Int2 => primitive::<Int16Type, _, _>(self, values, types::int2_from_sql),
+            Encoding::Int4 => primitive::<Int32Type, _, _>(self, values, types::int4_from_sql),
+            Encoding::Int8 => primitive::<Int64Type, _, _>(self, values, types::int8_from_sql),
+            Encoding::Oid => primitive::<UInt32Type, _, _>(self, values, types::oid_from_sql),
+            Encoding::Float4 => {
+                primitive::<Float32Type, _, _>(self, values, types::float4_from_sql)
+            }
+            Encoding::Float8 => {
+                primitive::<Float64Type, _, _>(self, values, types::float8_from_sql)
+            }
+            Encoding::Numeric => strings(values, types::numeric_from_sql),
+            Encoding::Text => strings(values, types::text_from_sql),
+            Encoding::Jsonb => strings(values, jsonb_from_sql),
+            Encoding::Bytea => {
+                let mut builder = BinaryBuilder::with_capacity(values.len(), 0);
+                for value in values {
+                    match value {
+                        Some(buf) => builder.append_value(types::bytea_from_sql(buf)),
+                        None => builder.append_null(),
+                    }
+                }
+                Ok(Arc::new(builder.finish()))
+            }
+            Encoding::Uuid => {
+                let mut builder = FixedSizeBinaryBuilder::with_capacity(values.len(), 16);
+                for value in values {
+                    match value {
+                        Some(buf) => builder.append_value(types::uuid_from_sql(buf)?)?,
+                        None => builder.append_null(),
+                    }
+                }
+                Ok(Arc::new(builder.finish()))
+            }
+            Encoding::Date => primitive::<Date32Type, _, _>(self, values, date_from_sql),
+            Encoding::Time => {
+                primitive::<Time64MicrosecondType, _, _>(self, values, types::time_from_sql)
+            }
+            Encoding::Timestamp | Encoding::Timestamptz => {
+                primitive::<TimestampMicrosecondType, _, _>(self, values, timestamp_from_sql)
+            }
+            Encoding::Interval => {
+                primitive::<IntervalMonthDayNanoType, _, _>(self, values, interval_from_sql)
+            }
+        }
+    }
+}
+
+fn primitive<'a, T, I, F>(encoding: Encoding, values: I, decode: F) -> Result<ArrayRef, BoxError>
+where
+    T: ArrowPrimitiveType,
+    I: ExactSizeIterator<Item = Option<&'a [u8]>>,
+    F: Fn(&'a [u8]) -> Result<T::Native, BoxError>,
+{
+    let mut builder =
+        PrimitiveBuilder::<T>::with_capacity(values.len()).with_data_type(encoding.data_type());
+    for value in values {
+        match value {
+            Some(buf) => builder.append_value(decode(buf)?),
+            None => builder.append_null(),
+        }
+    }
+    Ok(Arc::new(builder.finish()))
+}
+
+fn strings<'a, I, F, S>(values: I, decode: F) -> Result<ArrayRef, BoxError>
+where
+    I: ExactSizeIterator<Item = Option<&'a [u8]>>,
+    F: Fn(&'a [u8]) -> Result<S, BoxError>,
+    S: AsRef<str>,
+{
+    let mut builder = StringBuilder::with_capacity(values.len(), 0);
+    for value in values {
+        match value {
+            Some(buf) => builder.append_value(decode(buf)?),
+            None => builder.append_null(),
+        }
+    }
+    Ok(Arc::new(builder.finish()))
+}
+
+fn jsonb_from_sql(buf: &[u8]) -> Result<&str, BoxError> {
+    match buf.split_first() {
+        Some((1, buf)) => types::text_from_sql(buf),
+        _ => Err("unsupported JSONB encoding version".into()),
+    }
+}
+
+fn date_from_sql(buf: &[u8]) -> Result<i32, BoxError> {
+    match types::date_from_sql(buf)? {
+        i32::MIN | i32::MAX => Err("infinite dates can't be converted to Arrow".into()),
+        days => days
+            .checked_add(EPOCH_DAYS)
+            .ok_or_else(|| "date out of range for Arrow".into()),
+    }
+}
+
+fn timestamp_from_sql(buf: &[u8]) -> Result<i64, BoxError> {
+    match types::timestamp_from_sql(buf)? {
+        i64::MIN | i64::MAX => Err("infinite timestamps can't be converted to Arrow".into()),
+        micros => micros
+            .checked_add(EPOCH_MICROS)
+            .ok_or_else(|| "timestamp out of range for Arrow".into()),
+    }
+}
+
+fn interval_from_sql(mut buf: &[u8]) -> Result<IntervalMonthDayNano, BoxError> {
+    let micros = buf.read_i64::<BigEndian>()?;
+    let days = buf.read_i32::<BigEndian>()?;
+    let months = buf.read_i32::<BigEndian>()?;
+    if !buf.is_empty() {
+        return Err("invalid buffer size".into());
+    }
+    let nanoseconds = micros
+        .checked_mul(1000)
+        .ok_or("interval out of range for Arrow")?;
+    Ok(IntervalMonthDayNano::new(months, days, nanoseconds))
+}
+
+// Builds a batch of `len` rows, decoding the values of each column returned by `column`.
+fn record_batch<'a, F, I>(
+    schema: &SchemaRef,
+    encodings: &[Encoding],
+    len: usize,
+    mut column: F,
+) -> Result<RecordBatch, Error>
+where
+    F: FnMut(usize) -> I,
+    I: ExactSizeIterator<Item = Option<&'a [u8]>>,
+{
+    let columns = encodings
+        .iter()
+        .enumerate()
+        .map(|(idx, encoding)| {
+            encoding
+                .decode(column(idx))
+                .map_err(|e| Error::from_sql(e, idx))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    let options = RecordBatchOptions::new().with_row_count(Some(len));
+    RecordBatch::try_new_with_options(schema.clone(), columns, &options)
+        .map_err(|e| Error::from_sql(Box::new(e), 0))
+}
+
+pin_project! {
+    /// A stream of Arrow record batches holding the rows of a query.
+    pub struct RecordBatchStream {
+        #[pin]
+        rows: RowStream,
+        schema: SchemaRef,
+        encodings: Vec<Encoding>,
+        batch_size: usize,
+        buf: Vec<Row>,
+        done: bool,
+    }
+}
+
+impl RecordBatchStream {
+    pub(crate) fn new(
+        rows: RowStream,
+        columns: &[Column],
+        batch_size: usize,
+    ) -> Result<RecordBatchStream, Error> {
+        assert!(batch_size > 0, "batch size must be positive");
+
+        let (schema, encodings) =
+            schema_and_encodings(columns.iter().map(|c| (c.name(), c.type_())))?;
+
+        Ok(RecordBatchStream {
+            rows,
+            schema: Arc::new(schema),
+            encodings,
+            batch_size,
+            buf: Vec::with_capacity(batch_size),
+            done: false,
+        })
+    }
+
+    /// Returns the schema of the batches.
+    pub fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    /// Returns the number of rows affected by the query.
+    ///
+    /// This function will return `None` until the stream has been exhausted.
+    pub fn rows_affected(&self) -> Option<u64> {
+        self.rows.rows_affected()
+    }
+}
+
+impl Stream for RecordBatchStream {
+    type Item = Result<RecordBatch, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        while !*this.done {
+            match ready!(this.rows.as_mut().poll_next(cx)?) {
+                Some(row) => {
+                    this.buf.push(row);
+                    if this.buf.len() == *this.batch_size {
+                        break;
+                    }
+                }
+                None => *this.done = true,
+            }
+        }
+
+        if this.buf.is_empty() {
+            return Poll::Ready(None);
+        }
+
+        let rows = mem::replace(this.buf, Vec::with_capacity(*this.batch_size));
+        let batch = record_batch(this.schema, this.encodings, rows.len(), |idx| {
+            rows.iter().map(move |row| row.col_buffer(idx))
+        });
+        Poll::Ready(Some(batch))
+    }
+}
+
+pin_project! {
+    /// A stream of Arrow record batches decoded from the PostgreSQL binary copy format.
+    pub struct RecordBatchCopyOutStream {
+        #[pin]
+        stream: BinaryCopyOutStream,
+        schema: SchemaRef,
+        encodings: Vec<Encoding>,
+        batch_size: usize,
+        buf: Vec<BinaryCopyOutRow>,
+        done: bool,
+    }
+}
+
+impl RecordBatchCopyOutStream {
+    /// Creates a stream of batches of at most `batch_size` rows from a raw copy out stream and the names and types of
+    /// the columns being returned.
+    ///
+    /// Returns an error if one of the types isn't supported.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `batch_size` is 0.
+    pub fn new(
+        stream: CopyOutStream,
+        columns: &[(&str, Type)],
+        batch_size: usize,
+    ) -> Result<RecordBatchCopyOutStream, Error> {
+        assert!(batch_size > 0, "batch size must be positive");
+
+        let (schema, encodings) =
+            schema_and_encodings(columns.iter().map(|(name, type_)| (*name, type_)))?;
+        let types = columns
+            .iter()
+            .map(|(_, type_)| type_.clone())
+            .collect::<Vec<_>>();
+
+        Ok(RecordBatchCopyOutStream {
+            stream: BinaryCopyOutStream::new(stream, &types),
+            schema: Arc::new(schema),
+            encodings,
+            batch_size,
+            buf: Vec::with_capacity(batch_size),
+            done: false,
+        })
+    }
+
+    /// Returns the schema of the batches.
+    pub fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    /// Returns the number of rows read so far.
+    pub fn rows_read(&self) -> u64 {
+        self.stream.rows_read()
+    }
+}
+
+impl Stream for RecordBatchCopyOutStream {
+    type Item = Result<RecordBatch, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        while !*this.done {
+            match ready!(this.stream.as_mut().poll_next(cx)?) {
+                Some(row) => {
+                    this.buf.push(row);
+                    if this.buf.len() == *this.batch_size {
+                        break;
+                    }
+                }
+                None => *this.done = true,
+            }
+        }
+
+        if this.buf.is_empty() {
+            return Poll::Ready(None);
+        }
+
+        let rows = mem::replace(this.buf, Vec::with_capacity(*this.batch_size));
+        let batch = record_batch(this.schema, this.encodings, rows.len(), |idx| {
+            rows.iter().map(move |row| row.raw(idx))
+        });
+        Poll::Ready(Some(batch))
+    }
+}
+
+pin_project! {
+    /// A type which serializes Arrow record batches into the PostgreSQL binary copy format.
+    ///
+    /// The copy *must* be explicitly completed via the `finish` method. If it is not, the copy will be aborted.
+    pub struct RecordBatchCopyInWriter {
+        #[pin]
+        sink: CopyInSink<Bytes>,
+        types: Vec<Type>,
+        encodings: Vec<Encoding>,
+        buf: BytesMut,
+        rows: u64,
+    }
+}
+
+impl RecordBatchCopyInWriter {
+    /// Creates a new writer which will write rows of the provided types to the provided sink.
+    ///
+    /// Returns an error if one of the types isn't supported.
+    pub fn new(sink: CopyInSink<Bytes>, types: &[Type]) -> Result<RecordBatchCopyInWriter, Error> {
+        let encodings = types
+            .iter()
+            .enumerate()
+            .map(|(idx, type_)| {
+                Encoding::new(type_).ok_or_else(|| Error::to_sql(unsupported(type_), idx))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let mut buf = BytesMut::new();
+        binary_copy::header(&mut buf);
+
+        Ok(RecordBatchCopyInWriter {
+            sink,
+            types: types.to_vec(),
+            encodings,
+            buf,
+            rows: 0,
+        })
+    }
+
+    /// Returns the number of rows written so far.
+    ///
+    /// Rows are buffered before being sent to the server, so some of them may not have been sent yet.
+    pub fn rows_written(&self) -> u64 {
+        self.rows
+    }
+
+    /// Writes the rows of a batch.
+    ///
+    /// Returns an error if the data type of a column of the batch doesn't match the type it is written as, as
+    /// returned by [`data_type`]. Rows of the batch preceding the one a value couldn't be converted in have already been
+    /// written when an error is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of columns of the batch does not match the number of types expected.
+    pub async fn write(self: Pin<&mut Self>, batch: &RecordBatch) -> Result<(), Error> {
+        let mut this = self.project();
+
+        assert!(
+            batch.num_columns() == this.types.len(),
+            "expected {} columns but got {}",
+            this.types.len(),
+            batch.num_columns(),
+        );
+
+        for (idx, (encoding, column)) in this.encodings.iter().zip(batch.columns()).enumerate() {
+            if !encoding.accepts(column.data_type()) {
+                return Err(Error::to_sql(
+                    format!(
+                        "cannot convert between the Arrow type {} and the Postgres type {}",
+                        column.data_type(),
+                        this.types[idx],
+                    )
+                    .into(),
+                    idx,
+                ));
+            }
+        }
+
+        for row in 0..batch.num_rows() {
+            let mut error_idx = 0;
+            let r = binary_copy::tuple(
+                this.encodings.iter().zip(batch.columns()).enumerate(),
+                |(idx, (encoding, column)), buf| {
+                    encoding.encode(column, row, buf).map_err(|e| {
+                        error_idx = idx;
+                        BindError::Conversion(e)
+                    })
+                },
+                this.buf,
+            );
+            match r {
+                Ok(()) => {}
+                Err(BindError::Conversion(e)) => return Err(Error::to_sql(e, error_idx)),
+                Err(BindError::Serialization(e)) => return Err(Error::encode(e)),
+            }
+            *this.rows += 1;
+
+            if this.buf.len() > 4096 {
+                this.sink.send(this.buf.split().freeze()).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Completes the copy, returning the number of rows added.
+    ///
+    /// This method *must* be used to complete the copy process. If it is not, the copy will be aborted.
+    pub async fn finish(self: Pin<&mut Self>) -> Result<u64, Error> {
+        let mut this = self.project();
+
+        binary_copy::trailer(this.buf);
+        this.sink.send(this.buf.split().freeze()).await?;
+        this.sink.finish().await
+    }
+}
@@ -0,0 +1,341 @@
+use crate::client::InnerClient;
+use crate::codec::FrontendMessage;
+use crate::connection::RequestMessages;
+use crate::interceptor::{Operation, Outcome};
+use crate::query::extract_row_affected;
+use crate::types::{Format, IsNull, ToText};
+use crate::Error;
+use log::debug;
+use postgres_protocol::message::backend::Message;
+use postgres_protocol::message::frontend;
+use std::fmt::Write;
+
+/// A statement split off of a batch, with its parameters renumbered.
+#[derive(Debug, PartialEq)]
+struct BatchStatement {
+    sql: String,
+    /// The indices of the parameters of the batch referenced by the statement, in the order of their new numbers.
+    params: Vec<usize>,
+}
+
+pub async fn batch_execute_params<P, I>(
+    client: &InnerClient,
+    query: &str,
+    params: I,
+) -> Result<Vec<String>, Error>
+where
+    P: ToText,
+    I: IntoIterator<Item = P>,
+{
+    let params = params.into_iter().collect::<Vec<_>>();
+
+    let interceptors = client.interceptors();
+    let query = interceptors.before_sql(Operation::Execute, query)?;
+
+    debug!("executing parameterized statement batch: {}", query);
+
+    let statements = split(&query);
+    let permit = client.reserve().await;
+    let buf = client.with_buf(|buf| {
+        for statement in &statements {
+            if let Some(&idx) = statement.params.iter().find(|&&idx| idx >= params.len()) {
+                return Err(Error::parameters(params.len(), idx + 1));
+            }
+            let statement_params = statement
+                .params
+                .iter()
+                .map(|&idx| &params[idx])
+                .collect::<Vec<_>>();
+
+            let annotated = client.instrumentation().annotate(&statement.sql);
+            client.replace_unnamed_statement();
+            frontend::parse(
+                "",
+                &annotated,
+                statement_params.iter().map(|p| p.type_oid()),
+                buf,
+            )
+            .map_err(Error::encode)?;
+
+            let mut error_idx = 0;
+            match frontend::bind(
+                "",
+                "",
+                statement_params.iter().map(|p| match p.encode_format() {
+                    Format::Text => 0,
+                    Format::Binary => 1,
+                }),
+                statement.params.iter().zip(&statement_params),
+                |(&idx, param), buf| match param.to_text(buf) {
+                    Ok(IsNull::No) => Ok(postgres_protocol::IsNull::No),
+                    Ok(IsNull::Yes) => Ok(postgres_protocol::IsNull::Yes),
+                    Err(e) => {
+                        error_idx = idx;
+                        Err(e)
+                    }
+                },
+                Some(0),
+                buf,
+            ) {
+                Ok(()) => {}
+                Err(frontend::BindError::Conversion(e)) => return Err(Error::to_sql(e, error_idx)),
+                Err(frontend::BindError::Serialization(e)) => return Err(Error::encode(e)),
+            }
+
+            frontend::execute("", 0, buf).map_err(Error::encode)?;
+        }
+        // The statements are synced once all of them are executed.
+        frontend::sync(buf);
+
+        Ok(buf.split().freeze())
+    })?;
+
+    let timer = client
+        .instrumentation()
+        .timer(Operation::Execute, Some(&query));
+    let mut responses = client.send(permit, RequestMessages::Single(FrontendMessage::Raw(buf)))?;
+
+    let mut tags = Vec::with_capacity(statements.len());
+    let mut rows = 0;
+    let result = loop {
+        let message = match responses.next().await {
+            Ok(message) => message,
+            Err(e) => break Err(e),
+        };
+
+        match message {
+            Message::ReadyForQuery(_) => break Ok(()),
+            Message::CommandComplete(body) => match (extract_row_affected(&body), body.tag()) {
+                (Ok(n), Ok(tag)) => {
+                    rows += n;
+                    tags.push(tag.to_string());
+                }
+                (Err(e), _) => break Err(e),
+                (_, Err(e)) => break Err(Error::parse(e)),
+            },
+            Message::ParseComplete
+            | Message::BindComplete
+            | Message::DataRow(_)
+            | Message::EmptyQueryResponse => {}
+            m => break Err(Error::unexpected_message(m)),
+        }
+    };
+
+    let rows = result.as_ref().map(|()| Some(rows));
+    if let Some(timer) = timer {
+        timer.finish(None, rows);
+    }
+    interceptors.after(&Outcome {
+        operation: Operation::Execute,
+        sql: Some(&query),
+        statement: None,
+        result: rows,
+    });
+
+    result.map(|()| tags)
+}
+
+/// Splits a batch into its statements, on the semicolons outside of literals, quoted identifiers and comments.
+///
+/// Parameter references are renumbered so that each statement only declares the parameters it uses. Statements
+/// containing nothing but whitespace and comments are skipped.
+fn split(query: &str) -> Vec<BatchStatement> {
+    let bytes = query.as_bytes();
+    let mut statements = vec![];
+    let mut sql = String::new();
+    let mut params = vec![];
+    let mut empty = true;
+    // The start of the part of the current statement not yet copied into `sql`.
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                i = find(bytes, i + 2, b"\n").map_or(bytes.len(), |end| end + 1);
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => i = skip_block_comment(bytes, i),
+            b'\'' => {
+                empty = false;
+                let escapes = i > 0
+                    && matches!(bytes[i - 1], b'e' | b'E')
+                    && !follows_identifier(bytes, i - 1);
+                i = skip_string(bytes, i, escapes);
+            }
+            b'"' => {
+                empty = false;
+                i = find(bytes, i + 1, b"\"").map_or(bytes.len(), |end| end + 1);
+            }
+            b'$' if !follows_identifier(bytes, i) => {
+                empty = false;
+                let digits = bytes[i + 1..]
+                    .iter()
+                    .take_while(|b| b.is_ascii_digit())
+                    .count();
+                if digits > 0 {
+                    let end = i + 1 + digits;
+                    // `$0` and out of range numbers are left for the server to reject.
+                    match query[i + 1..end].parse::<usize>() {
+                        Ok(n) if n > 0 => {
+                            let idx = match params.iter().position(|&p| p == n - 1) {
+                                Some(idx) => idx,
+                                None => {
+                                    params.push(n - 1);
+                                    params.len() - 1
+                                }
+                            };
+                            sql.push_str(&query[start..i]);
+                            write!(sql, "${}", idx + 1).unwrap();
+                            start = end;
+                        }
+                        _ => {}
+                    }
+                    i = end;
+                } else {
+                    let tag_len = bytes[i + 1..]
+                        .iter()
+                        .take_while(|&&b| is_identifier(b))
+                        .count();
+                    let tag_end = i + 1 + tag_len;
+                    if bytes.get(tag_end) == Some(&b'$') {
+                        let tag = &bytes[i..=tag_end];
+                        i = find(bytes, tag_end + 1, tag)
+                            .map_or(bytes.len(), |end| end + tag.len());
+                    } else {
+                        i += 1;
+                    }
+                }
+            }
+            b';' => {
+                sql.push_str(&query[start..i]);
+                if !empty {
+                    statements.push(BatchStatement {
+                        sql: sql.trim().to_string(),
+                        params: params.split_off(0),
+                    });
+                }
+                sql.clear();
+                params.clear();
+                empty = true;
+                i += 1;
+                start = i;
+            }
+            b if b.is_ascii_whitespace() => i += 1,
+            _ => {
+                empty = false;
+                i += 1;
+            }
+        }
+    }
+
+    sql.push_str(&query[start..]);
+    if !empty {
+        statements.push(BatchStatement {
+            sql: sql.trim().to_string(),
+            params,
+        });
+    }
+
+    statements
+}
+
+fn is_identifier(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b >= 0x80
+}
+
+// `$` can be part of an identifier or keyword, in which case it doesn't start a parameter or dollar-quoted string.
+fn follows_identifier(bytes: &[u8], i: usize) -> bool {
+    i > 0 && (is_identifier(bytes[i - 1]) || bytes[i - 1] == b'$')
+}
+
+fn find(bytes: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    bytes[from..]
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|pos| from + pos)
+}
+
+fn skip_string(bytes: &[u8], start: usize, escapes: bool) -> usize {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if escapes => i += 2,
+            // A doubled quote is read as the end of the string immediately followed by the start of another one.
+            b'\'' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+fn skip_block_comment(bytes: &[u8], start: usize) -> usize {
+    let mut depth = 0;
+    let mut i = start;
+    while i < bytes.len() {
+        if bytes[i..].starts_with(b"/*") {
+            depth += 1;
+            i += 2;
+        } else if bytes[i..].starts_with(b"*/") {
+            depth -= 1;
+            i += 2;
+            if depth == 0 {
+                return i;
+            }
+        } else {
+            i += 1;
+        }
+    }
+    bytes.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split, BatchStatement};
+
+    fn statement(sql: &str, params: &[usize]) -> BatchStatement {
+        BatchStatement {
+            sql: sql.to_string(),
+            params: params.to_vec(),
+        }
+    }
+
+    #[test]
+    fn split_statements() {
+        assert_eq!(
+            split("CREATE TABLE foo (id INT); INSERT INTO foo VALUES (1);\n"),
+            [
+                statement("CREATE TABLE foo (id INT)", &[]),
+                statement("INSERT INTO foo VALUES (1)", &[]),
+            ],
+        );
+        assert_eq!(split(" ; -- nothing;\n /* here; */ "), []);
+    }
+
+    #[test]
+    fn split_quoted() {
+        assert_eq!(
+            split(
+                "SELECT 'a;''$1', E'\\';$1', \"b;\"\"\"; SELECT $$;$1$$, $tag$ $$; $tag$ -- ;\n/* /* ; */ ; */"
+            ),
+            [
+                statement("SELECT 'a;''$1', E'\\';$1', \"b;\"\"\"", &[]),
+                statement(
+                    "SELECT $$;$1$$, $tag$ $$; $tag$ -- ;\n/* /* ; */ ; */",
+                    &[]
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn split_params() {
+        assert_eq!(
+            split("SELECT $2, $1, $2; SELECT $3 + $3; SELECT foo$1, $0"),
+            [
+                statement("SELECT $1, $2, $1", &[1, 0]),
+                statement("SELECT $1 + $1", &[2]),
+                statement("SELECT foo$1, $0", &[]),
+            ],
+        );
+    }
+}
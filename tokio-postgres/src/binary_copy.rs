@@ -2,21 +2,29 @@
 
 use crate::types::{FromSql, IsNull, ToSql, Type, WrongType};
 use crate::{slice_iter, CopyInSink, CopyOutStream, Error};
-use byteorder::{BigEndian, ByteOrder};
-use bytes::{Buf, BufMut, Bytes, BytesMut};
+use bytes::{Bytes, BytesMut};
 use futures_util::{ready, SinkExt, Stream};
 use pin_project_lite::pin_project;
+use postgres_protocol::binary_copy;
+use postgres_protocol::message::frontend::BindError;
 use postgres_types::BorrowToSql;
-use std::convert::TryFrom;
 use std::io;
-use std::io::Cursor;
 use std::ops::Range;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
-const MAGIC: &[u8] = b"PGCOPY\n\xff\r\n\0";
-const HEADER_LEN: usize = MAGIC.len() + 4 + 4;
+/// A type which can be written as a row of a binary copy.
+pub trait ToCopyRow {
+    /// Returns the values of the row, in the order of the types the writer was created with.
+    fn to_copy_row(&self) -> Vec<&(dyn ToSql + Sync)>;
+}
+
+/// A type which can be read from a row of a binary copy.
+pub trait FromCopyRow: Sized {
+    /// Converts a row read from a binary copy.
+    fn from_copy_row(row: &BinaryCopyOutRow) -> Result<Self, Error>;
+}
 
 pin_project! {
     /// A type which serializes rows into the PostgreSQL binary copy format.
@@ -27,6 +35,7 @@ pin_project! {
         sink: CopyInSink<Bytes>,
         types: Vec<Type>,
         buf: BytesMut,
+        rows: u64,
     }
 }
 
@@ -34,17 +43,23 @@ impl BinaryCopyInWriter {
     /// Creates a new writer which will write rows of the provided types to the provided sink.
     pub fn new(sink: CopyInSink<Bytes>, types: &[Type]) -> BinaryCopyInWriter {
         let mut buf = BytesMut::new();
-        buf.put_slice(MAGIC);
-        buf.put_i32(0); // flags
-        buf.put_i32(0); // header extension
+        binary_copy::header(&mut buf);
 
         BinaryCopyInWriter {
             sink,
             types: types.to_vec(),
             buf,
+            rows: 0,
         }
     }
 
+    /// Returns the number of rows written so far.
+    ///
+    /// Rows are buffered before being sent to the server, so some of them may not have been sent yet.
+    pub fn rows_written(&self) -> u64 {
+        self.rows
+    }
+
     /// Writes a single row.
     ///
     /// # Panics
@@ -54,6 +69,18 @@ impl BinaryCopyInWriter {
         self.write_raw(slice_iter(values)).await
     }
 
+    /// Writes a single row from a type implementing `ToCopyRow`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of values provided does not match the number expected.
+    pub async fn write_row<R>(self: Pin<&mut Self>, row: &R) -> Result<(), Error>
+    where
+        R: ?Sized + ToCopyRow,
+    {
+        self.write(&row.to_copy_row()).await
+    }
+
     /// A maximally-flexible version of `write`.
     ///
     /// # Panics
@@ -75,22 +102,25 @@ impl BinaryCopyInWriter {
             values.len(),
         );
 
-        this.buf.put_i16(this.types.len() as i16);
-
-        for (i, (value, type_)) in values.zip(this.types).enumerate() {
-            let idx = this.buf.len();
-            this.buf.put_i32(0);
-            let len = match value
-                .borrow_to_sql()
-                .to_sql_checked(type_, this.buf)
-                .map_err(|e| Error::to_sql(e, i))?
-            {
-                IsNull::Yes => -1,
-                IsNull::No => i32::try_from(this.buf.len() - idx - 4)
-                    .map_err(|e| Error::encode(io::Error::new(io::ErrorKind::InvalidInput, e)))?,
-            };
-            BigEndian::write_i32(&mut this.buf[idx..], len);
+        let mut error_idx = 0;
+        let r = binary_copy::tuple(
+            values.zip(this.types.iter()).enumerate(),
+            |(idx, (value, type_)), buf| match value.borrow_to_sql().to_sql_checked(type_, buf) {
+                Ok(IsNull::No) => Ok(postgres_protocol::IsNull::No),
+                Ok(IsNull::Yes) => Ok(postgres_protocol::IsNull::Yes),
+                Err(e) => {
+                    error_idx = idx;
+                    Err(BindError::Conversion(e))
+                }
+            },
+            this.buf,
+        );
+        match r {
+            Ok(()) => {}
+            Err(BindError::Conversion(e)) => return Err(Error::to_sql(e, error_idx)),
+            Err(BindError::Serialization(e)) => return Err(Error::encode(e)),
         }
+        *this.rows += 1;
 
         if this.buf.len() > 4096 {
             this.sink.send(this.buf.split().freeze()).await?;
@@ -105,7 +135,7 @@ impl BinaryCopyInWriter {
     pub async fn finish(self: Pin<&mut Self>) -> Result<u64, Error> {
         let mut this = self.project();
 
-        this.buf.put_i16(-1);
+        binary_copy::trailer(this.buf);
         this.sink.send(this.buf.split().freeze()).await?;
         this.sink.finish().await
     }
@@ -122,6 +152,7 @@ pin_project! {
         stream: CopyOutStream,
         types: Arc<Vec<Type>>,
         header: Option<Header>,
+        rows: u64,
     }
 }
 
@@ -132,8 +163,14 @@ impl BinaryCopyOutStream {
             stream,
             types: Arc::new(types.to_vec()),
             header: None,
+            rows: 0,
         }
     }
+
+    /// Returns the number of rows read so far.
+    pub fn rows_read(&self) -> u64 {
+        self.rows
+    }
 }
 
 impl Stream for BinaryCopyOutStream {
@@ -147,82 +184,50 @@ impl Stream for BinaryCopyOutStream {
             Some(Err(e)) => return Poll::Ready(Some(Err(e))),
             None => return Poll::Ready(Some(Err(Error::closed()))),
         };
-        let mut chunk = Cursor::new(chunk);
-
+        let mut offset = 0;
         let has_oids = match &this.header {
             Some(header) => header.has_oids,
             None => {
-                check_remaining(&chunk, HEADER_LEN)?;
-                if !chunk.chunk().starts_with(MAGIC) {
-                    return Poll::Ready(Some(Err(Error::parse(io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        "invalid magic value",
-                    )))));
-                }
-                chunk.advance(MAGIC.len());
-
-                let flags = chunk.get_i32();
-                let has_oids = (flags & (1 << 16)) != 0;
-
-                let header_extension = chunk.get_u32() as usize;
-                check_remaining(&chunk, header_extension)?;
-                chunk.advance(header_extension);
+                let header = binary_copy::parse_header(&chunk).map_err(Error::parse)?;
+                offset = header.size();
 
-                *this.header = Some(Header { has_oids });
-                has_oids
+                *this.header = Some(Header {
+                    has_oids: header.has_oids(),
+                });
+                header.has_oids()
             }
         };
 
-        check_remaining(&chunk, 2)?;
-        let mut len = chunk.get_i16();
-        if len == -1 {
-            return Poll::Ready(None);
-        }
-
-        if has_oids {
-            len += 1;
-        }
-        if len as usize != this.types.len() {
+        let tuple =
+            match binary_copy::parse_tuple(&chunk[offset..], has_oids).map_err(Error::parse)? {
+                Some(tuple) => tuple,
+                None => return Poll::Ready(None),
+            };
+        if tuple.len() != this.types.len() {
             return Poll::Ready(Some(Err(Error::parse(io::Error::new(
                 io::ErrorKind::InvalidInput,
-                format!("expected {} values but got {}", this.types.len(), len),
+                format!(
+                    "expected {} values but got {}",
+                    this.types.len(),
+                    tuple.len()
+                ),
             )))));
         }
 
-        let mut ranges = vec![];
-        for _ in 0..len {
-            check_remaining(&chunk, 4)?;
-            let len = chunk.get_i32();
-            if len == -1 {
-                ranges.push(None);
-            } else {
-                let len = len as usize;
-                check_remaining(&chunk, len)?;
-                let start = chunk.position() as usize;
-                ranges.push(Some(start..start + len));
-                chunk.advance(len);
-            }
-        }
+        let ranges = tuple
+            .ranges()
+            .map(|range| range.map(|range| offset + range.start..offset + range.end))
+            .collect();
 
+        *this.rows += 1;
         Poll::Ready(Some(Ok(BinaryCopyOutRow {
-            buf: chunk.into_inner(),
+            buf: chunk,
             ranges,
             types: this.types.clone(),
         })))
     }
 }
 
-fn check_remaining(buf: &Cursor<Bytes>, len: usize) -> Result<(), Error> {
-    if buf.remaining() < len {
-        Err(Error::parse(io::Error::new(
-            io::ErrorKind::UnexpectedEof,
-            "unexpected EOF",
-        )))
-    } else {
-        Ok(())
-    }
-}
-
 /// A row of data parsed from a binary copy out stream.
 pub struct BinaryCopyOutRow {
     buf: Bytes,
@@ -231,6 +236,12 @@ pub struct BinaryCopyOutRow {
 }
 
 impl BinaryCopyOutRow {
+    // Returns the raw value of a column, in the binary format.
+    #[cfg(feature = "arrow")]
+    pub(crate) fn raw(&self, idx: usize) -> Option<&[u8]> {
+        self.ranges[idx].clone().map(|range| &self.buf[range])
+    }
+
     /// Like `get`, but returns a `Result` rather than panicking.
     pub fn try_get<'a, T>(&'a self, idx: usize) -> Result<T, Error>
     where
@@ -270,4 +281,12 @@ impl BinaryCopyOutRow {
             Err(e) => panic!("error retrieving column {}: {}", idx, e),
         }
     }
+
+    /// Converts the row into a type implementing `FromCopyRow`.
+    pub fn decode<R>(&self) -> Result<R, Error>
+    where
+        R: FromCopyRow,
+    {
+        R::from_copy_row(self)
+    }
 }
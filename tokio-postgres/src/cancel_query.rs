@@ -1,13 +1,13 @@
 use crate::client::SocketConfig;
-use crate::config::SslMode;
+use crate::connect_tls::TlsSettings;
 use crate::tls::MakeTlsConnect;
 use crate::{cancel_query_raw, connect_socket, Error, Socket};
 use std::io;
 
 pub(crate) async fn cancel_query<T>(
     config: Option<SocketConfig>,
-    ssl_mode: SslMode,
-    mut tls: T,
+    settings: &TlsSettings,
+    tls: &mut T,
     process_id: i32,
     secret_key: i32,
 ) -> Result<(), Error>
@@ -24,10 +24,14 @@ where
         }
     };
 
+    let server_name = config
+        .ssl_server_name
+        .as_deref()
+        .or(config.hostname.as_deref());
     let tls = tls
-        .make_tls_connect(config.hostname.as_deref().unwrap_or(""))
+        .make_tls_connect(server_name.unwrap_or(""))
         .map_err(|e| Error::tls(e.into()))?;
-    let has_hostname = config.hostname.is_some();
+    let has_hostname = server_name.is_some();
 
     let socket = connect_socket::connect_socket(
         &config.addr,
@@ -35,9 +39,10 @@ where
         config.connect_timeout,
         config.tcp_user_timeout,
         config.keepalive.as_ref(),
+        None,
     )
     .await?;
 
-    cancel_query_raw::cancel_query_raw(socket, ssl_mode, tls, has_hostname, process_id, secret_key)
+    cancel_query_raw::cancel_query_raw(socket, settings, tls, has_hostname, process_id, secret_key)
         .await
 }
@@ -1,9 +1,117 @@
-use crate::config::SslMode;
+use crate::connect_tls::TlsSettings;
+use crate::connection::RequestState;
 use crate::tls::TlsConnect;
 #[cfg(feature = "runtime")]
-use crate::{cancel_query, client::SocketConfig, tls::MakeTlsConnect, Socket};
+use crate::{
+    cancel_query,
+    client::{Session, SocketConfig},
+    config::SslMode,
+    tls::MakeTlsConnect,
+    Socket,
+};
 use crate::{cancel_query_raw, Error};
+use std::sync::Arc;
+#[cfg(feature = "runtime")]
+use std::sync::Weak;
+#[cfg(feature = "runtime")]
+use std::time::Duration;
 use tokio::io::{AsyncRead, AsyncWrite};
+#[cfg(feature = "runtime")]
+use tokio::time;
+
+/// Options controlling the delivery of a cancellation request by `CancelToken::cancel_query_with`.
+///
+/// Requires the `runtime` Cargo feature (enabled by default).
+#[cfg(feature = "runtime")]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct CancelOptions {
+    connect_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    ssl_mode: Option<SslMode>,
+    retries: u32,
+    retry_delay: Duration,
+}
+
+#[cfg(feature = "runtime")]
+impl CancelOptions {
+    /// Creates options using the configuration of the connection, which make a single attempt.
+    pub fn new() -> CancelOptions {
+        CancelOptions::default()
+    }
+
+    /// Sets the timeout applied to opening the connection to the server.
+    ///
+    /// Defaults to the timeout set with `Config::connect_timeout`.
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> CancelOptions {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// Sets the time limit of each attempt, from opening the connection to the server to sending the request.
+    ///
+    /// Unlike the connect timeout, it also covers the TLS handshake, so that an unresponsive server can't stall the
+    /// attempt. Defaults to no limit.
+    pub fn timeout(mut self, timeout: Duration) -> CancelOptions {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the TLS mode used to send the request.
+    ///
+    /// Defaults to the mode set with `Config::ssl_mode`. Overriding it is useful when the request goes through a
+    /// proxy which doesn't negotiate TLS the same way as the server.
+    pub fn ssl_mode(mut self, ssl_mode: SslMode) -> CancelOptions {
+        self.ssl_mode = Some(ssl_mode);
+        self
+    }
+
+    /// Sets the number of times the request is retried after a network error.
+    ///
+    /// Defaults to 0.
+    pub fn retries(mut self, retries: u32) -> CancelOptions {
+        self.retries = retries;
+        self
+    }
+
+    /// Sets the delay before the first retry, which is doubled before each of the following ones.
+    ///
+    /// Defaults to no delay.
+    pub fn retry_delay(mut self, retry_delay: Duration) -> CancelOptions {
+        self.retry_delay = retry_delay;
+        self
+    }
+}
+
+/// The outcome of a cancellation request sent by `CancelToken::cancel_query_with`.
+///
+/// Requires the `runtime` Cargo feature (enabled by default).
+#[cfg(feature = "runtime")]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CancelOutcome {
+    /// The request was sent to the server.
+    ///
+    /// The server doesn't acknowledge cancellation requests, so the query may still have completed before the
+    /// request reached it.
+    Delivered,
+    /// The server closed the connection, so its backend has exited and there is nothing left to cancel. No request
+    /// was sent.
+    BackendGone,
+    /// The query wasn't in progress, so no request was sent.
+    ///
+    /// Only returned by `QueryCancelToken::cancel_query_with`.
+    Inactive,
+    /// The request couldn't be sent, with the error of the last attempt.
+    NetworkError(Error),
+}
+
+#[cfg(feature = "runtime")]
+impl CancelOutcome {
+    /// Determines if the request was sent to the server.
+    pub fn is_delivered(&self) -> bool {
+        matches!(self, CancelOutcome::Delivered)
+    }
+}
 
 /// The capability to request cancellation of in-progress queries on a
 /// connection.
@@ -11,7 +119,9 @@ use tokio::io::{AsyncRead, AsyncWrite};
 pub struct CancelToken {
     #[cfg(feature = "runtime")]
     pub(crate) socket_config: Option<SocketConfig>,
-    pub(crate) ssl_mode: SslMode,
+    #[cfg(feature = "runtime")]
+    pub(crate) session: Weak<Session>,
+    pub(crate) tls_settings: TlsSettings,
     pub(crate) process_id: i32,
     pub(crate) secret_key: i32,
 }
@@ -30,20 +140,106 @@ impl CancelToken {
     ///
     /// Requires the `runtime` Cargo feature (enabled by default).
     #[cfg(feature = "runtime")]
-    pub async fn cancel_query<T>(&self, tls: T) -> Result<(), Error>
+    pub async fn cancel_query<T>(&self, mut tls: T) -> Result<(), Error>
     where
         T: MakeTlsConnect<Socket>,
     {
         cancel_query::cancel_query(
             self.socket_config.clone(),
-            self.ssl_mode,
-            tls,
+            &self.tls_settings,
+            &mut tls,
             self.process_id,
             self.secret_key,
         )
         .await
     }
 
+    /// Like `cancel_query`, but with control over the delivery of the request, and returning its outcome.
+    ///
+    /// Requests failing with a network error, like a refused connection or a timeout, are retried as set by the
+    /// options. No request is sent once the server has closed the connection of this token, since its backend has
+    /// exited. A connection closed by the client though may still have its backend running a query, which is cancelled
+    /// as usual.
+    ///
+    /// Requires the `runtime` Cargo feature (enabled by default).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use tokio_postgres::{CancelOptions, CancelOutcome, NoTls};
+    ///
+    /// # async fn run(client: &tokio_postgres::Client) {
+    /// let options = CancelOptions::new()
+    ///     .connect_timeout(Duration::from_secs(1))
+    ///     .retries(2)
+    ///     .retry_delay(Duration::from_millis(100));
+    /// match client.cancel_token().cancel_query_with(NoTls, options).await {
+    ///     CancelOutcome::NetworkError(e) => eprintln!("unable to cancel the query: {}", e),
+    ///     _ => {}
+    /// }
+    /// # }
+    /// ```
+    #[cfg(feature = "runtime")]
+    pub async fn cancel_query_with<T>(&self, mut tls: T, options: CancelOptions) -> CancelOutcome
+    where
+        T: MakeTlsConnect<Socket>,
+    {
+        let mut socket_config = self.socket_config.clone();
+        if let (Some(config), Some(connect_timeout)) = (&mut socket_config, options.connect_timeout)
+        {
+            config.connect_timeout = Some(connect_timeout);
+        }
+        let mut tls_settings = self.tls_settings.clone();
+        if let Some(ssl_mode) = options.ssl_mode {
+            tls_settings.mode = ssl_mode;
+        }
+        // Without a known host, retrying can't succeed.
+        let retries = if socket_config.is_some() {
+            options.retries
+        } else {
+            0
+        };
+
+        let mut delay = options.retry_delay;
+        let mut attempt = 0;
+        loop {
+            if self.is_backend_gone() {
+                return CancelOutcome::BackendGone;
+            }
+
+            let cancel = cancel_query::cancel_query(
+                socket_config.clone(),
+                &tls_settings,
+                &mut tls,
+                self.process_id,
+                self.secret_key,
+            );
+            let result = match options.timeout {
+                Some(timeout) => time::timeout(timeout, cancel)
+                    .await
+                    .unwrap_or_else(|_| Err(Error::timeout())),
+                None => cancel.await,
+            };
+            match result {
+                Ok(()) => return CancelOutcome::Delivered,
+                Err(e) if attempt < retries && e.is_network() => {
+                    attempt += 1;
+                    time::sleep(delay).await;
+                    delay *= 2;
+                }
+                Err(e) => return CancelOutcome::NetworkError(e),
+            }
+        }
+    }
+
+    #[cfg(feature = "runtime")]
+    fn is_backend_gone(&self) -> bool {
+        self.session
+            .upgrade()
+            .is_some_and(|session| session.is_server_closed())
+    }
+
     /// Like `cancel_query`, but uses a stream which is already connected to the server rather than opening a new
     /// connection itself.
     pub async fn cancel_query_raw<S, T>(&self, stream: S, tls: T) -> Result<(), Error>
@@ -53,7 +249,7 @@ impl CancelToken {
     {
         cancel_query_raw::cancel_query_raw(
             stream,
-            self.ssl_mode,
+            &self.tls_settings,
             tls,
             true,
             self.process_id,
@@ -62,3 +258,79 @@ impl CancelToken {
         .await
     }
 }
+
+/// The capability to request cancellation of a single query.
+///
+/// Unlike a `CancelToken`, which cancels whatever is running on the connection, the cancellation is only requested
+/// while the query is in progress, so that it doesn't cancel the requests which were pipelined after it.
+///
+/// The query may still complete between the check and the arrival of the cancellation request at the server. In that
+/// case the request may cancel the next query if it has already started.
+#[derive(Clone)]
+pub struct QueryCancelToken {
+    token: Arc<CancelToken>,
+    state: Arc<RequestState>,
+}
+
+impl QueryCancelToken {
+    pub(crate) fn new(token: Arc<CancelToken>, state: Arc<RequestState>) -> QueryCancelToken {
+        QueryCancelToken { token, state }
+    }
+
+    /// Determines if the query is in progress.
+    ///
+    /// A query is in progress from the time the server is done with the requests sent before it, until the server has
+    /// returned all of its results.
+    pub fn is_active(&self) -> bool {
+        self.state.is_active()
+    }
+
+    /// Attempts to cancel the query, if it is in progress.
+    ///
+    /// Like `CancelToken::cancel_query`, the server provides no information about whether the attempt was successful.
+    ///
+    /// Requires the `runtime` Cargo feature (enabled by default).
+    #[cfg(feature = "runtime")]
+    pub async fn cancel_query<T>(&self, tls: T) -> Result<(), Error>
+    where
+        T: MakeTlsConnect<Socket>,
+    {
+        if !self.is_active() {
+            return Ok(());
+        }
+
+        self.token.cancel_query(tls).await
+    }
+
+    /// Like `cancel_query`, but with control over the delivery of the request, and returning its outcome.
+    ///
+    /// See `CancelToken::cancel_query_with` for details. Returns `CancelOutcome::Inactive` if the query isn't in
+    /// progress.
+    ///
+    /// Requires the `runtime` Cargo feature (enabled by default).
+    #[cfg(feature = "runtime")]
+    pub async fn cancel_query_with<T>(&self, tls: T, options: CancelOptions) -> CancelOutcome
+    where
+        T: MakeTlsConnect<Socket>,
+    {
+        if !self.is_active() {
+            return CancelOutcome::Inactive;
+        }
+
+        self.token.cancel_query_with(tls, options).await
+    }
+
+    /// Like `cancel_query`, but uses a stream which is already connected to the server rather than opening a new
+    /// connection itself.
+    pub async fn cancel_query_raw<S, T>(&self, stream: S, tls: T) -> Result<(), Error>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+        T: TlsConnect<S>,
+    {
+        if !self.is_active() {
+            return Ok(());
+        }
+
+        self.token.cancel_query_raw(stream, tls).await
+    }
+}
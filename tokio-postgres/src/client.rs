@@ -1,45 +1,78 @@
+#[cfg(feature = "arrow")]
+use crate::arrow::RecordBatchStream;
 use crate::codec::{BackendMessages, FrontendMessage};
-use crate::config::SslMode;
-use crate::connection::{Request, RequestMessages};
+use crate::config::Config;
+use crate::connect_tls::TlsSettings;
+use crate::connection::{Request, RequestMessages, RequestState};
+use crate::copy_both::CopyBothDuplex;
 use crate::copy_out::CopyOutStream;
+use crate::error::SqlState;
+#[cfg(feature = "with-serde_json-1")]
+use crate::explain::{ExplainOptions, Plan};
+use crate::instrument::{Instrumentation, Span};
+use crate::interceptor::{Interceptor, Interceptors, Operation};
 #[cfg(feature = "runtime")]
 use crate::keepalive::KeepaliveConfig;
+use crate::large_object;
+use crate::passthrough::{self, ForwardStream, PassthroughStream};
+use crate::pipeline::Pipeline;
 use crate::query::RowStream;
+use crate::raw::{self, RawResponses};
+use crate::schema::{self, ForeignKey, Index, PrimaryKey, Table, TableColumn};
+use crate::security::ConnectionSecurity;
 use crate::simple_query::SimpleQueryStream;
+use crate::statement_cache::StatementCache;
+use crate::stats::StatementStats;
 #[cfg(feature = "runtime")]
 use crate::tls::MakeTlsConnect;
 use crate::tls::TlsConnect;
-use crate::types::{ToSql, Type};
+use crate::types::{
+    DateOrder, DateStyle, Format, FromRow, FromSqlOwned, IntervalStyle, Oid, TextSettings, ToSql,
+    ToText, Type,
+};
 #[cfg(feature = "runtime")]
 use crate::Socket;
 use crate::{
-    copy_in, copy_out, prepare, query, simple_query, slice_iter, CancelToken, CopyInSink, Error,
-    Row, SimpleQueryMessage, Statement, ToStatement, Transaction, TransactionBuilder,
+    batch, copy_both, copy_in, copy_out, cursor, execute_many, infer, listen, prepare, query,
+    query_options, simple_query, slice_iter, CancelToken, CopyInSink, Cursor, Error,
+    ExecuteManyError, Notification, NotificationStream, ParameterChanges, Portal, QueryOptions,
+    Row, RowChunks, Rows, ServerVersion, SimpleQueryMessage, Statement, StatementDescription,
+    ToStatement, Transaction, TransactionBuilder,
 };
-use bytes::{Buf, BytesMut};
+use bytes::{Buf, Bytes, BytesMut};
 use fallible_iterator::FallibleIterator;
-use futures_channel::mpsc;
+use futures_channel::{mpsc, oneshot};
 use futures_util::{future, pin_mut, ready, StreamExt, TryStreamExt};
 use parking_lot::Mutex;
 use postgres_protocol::message::{backend::Message, frontend};
 use postgres_types::BorrowToSql;
+use std::collections::HashMap;
 use std::fmt;
 #[cfg(feature = "runtime")]
 use std::net::IpAddr;
 #[cfg(feature = "runtime")]
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::task::{Context, Poll};
 #[cfg(feature = "runtime")]
 use std::time::Duration;
 use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+#[cfg(feature = "runtime")]
+use tokio::time;
 
 pub struct Responses {
     receiver: mpsc::Receiver<BackendMessages>,
     cur: BackendMessages,
+    state: Arc<RequestState>,
 }
 
 impl Responses {
+    pub fn state(&self) -> &Arc<RequestState> {
+        &self.state
+    }
+
     pub fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Result<Message, Error>> {
         loop {
             match self.cur.next().map_err(Error::parse)? {
@@ -58,18 +91,280 @@ impl Responses {
     pub async fn next(&mut self) -> Result<Message, Error> {
         future::poll_fn(|cx| self.poll_next(cx)).await
     }
+
+    /// Like `poll_next`, but returns messages as they were received, including error responses.
+    pub fn poll_next_raw(&mut self, cx: &mut Context<'_>) -> Poll<Result<Bytes, Error>> {
+        loop {
+            if let Some(message) = self.cur.next_raw().map_err(Error::parse)? {
+                return Poll::Ready(Ok(message));
+            }
+
+            match ready!(self.receiver.poll_next_unpin(cx)) {
+                Some(messages) => self.cur = messages,
+                None => return Poll::Ready(Err(Error::closed())),
+            }
+        }
+    }
+}
+
+impl Drop for Responses {
+    fn drop(&mut self) {
+        self.state.abandon();
+    }
+}
+
+/// A slot among the requests a client may have in flight, as limited by `Config::max_inflight_requests`.
+///
+/// The slot must be reserved before the request is encoded, so that requests relying on the unnamed statement are
+/// sent in the order they were encoded in.
+pub struct RequestPermit {
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl RequestPermit {
+    /// Returns a permit which doesn't take a slot, for requests which can't wait, like those sent while a value is
+    /// dropped.
+    pub fn exempt() -> RequestPermit {
+        RequestPermit { _permit: None }
+    }
+}
+
+/// A request which was sent to the connection and hasn't completed yet, releasing its permit once dropped.
+pub(crate) struct InFlight {
+    session: Arc<Session>,
+    _permit: RequestPermit,
+}
+
+impl InFlight {
+    fn new(session: &Arc<Session>, permit: RequestPermit) -> InFlight {
+        session.pending_requests.fetch_add(1, Ordering::Relaxed);
+        InFlight {
+            session: session.clone(),
+            _permit: permit,
+        }
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.session
+            .pending_requests
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// The state of the session reported by the server, shared between a client and its connection.
+pub(crate) struct Session {
+    pub(crate) parameters: Mutex<HashMap<String, String>>,
+    pub(crate) transaction_status: AtomicU8,
+    /// Incremented whenever a `DISCARD ALL` or `DEALLOCATE ALL` command deallocates the prepared statements.
+    pub(crate) statement_epoch: AtomicU64,
+    /// Incremented whenever a request replacing the unnamed statement is encoded.
+    unnamed_statement: AtomicU64,
+    /// The streams of the channels subscribed to with `Client::listen`, keyed by channel.
+    listeners: Mutex<HashMap<String, Vec<mpsc::UnboundedSender<Notification>>>>,
+    /// The streams of the parameter changes subscribed to with `Client::parameter_changes`.
+    parameter_listeners: Mutex<Vec<mpsc::UnboundedSender<(String, String)>>>,
+    /// Whether the last request, or keepalive round trip, completed in time.
+    healthy: AtomicBool,
+    /// Whether the server closed the connection, which its backend only does when it exits.
+    server_closed: AtomicBool,
+    /// The state of the request the server is processing, or last processed.
+    pub(crate) active_request: Mutex<Option<Arc<RequestState>>>,
+    /// The number of requests sent to the connection which haven't completed yet.
+    pending_requests: AtomicUsize,
+}
+
+impl Session {
+    pub(crate) fn new(parameters: HashMap<String, String>) -> Session {
+        Session {
+            parameters: Mutex::new(parameters),
+            transaction_status: AtomicU8::new(b'I'),
+            statement_epoch: AtomicU64::new(0),
+            unnamed_statement: AtomicU64::new(0),
+            listeners: Mutex::new(HashMap::new()),
+            parameter_listeners: Mutex::new(vec![]),
+            healthy: AtomicBool::new(true),
+            server_closed: AtomicBool::new(false),
+            active_request: Mutex::new(None),
+            pending_requests: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn subscribe(&self, channel: &str) -> mpsc::UnboundedReceiver<Notification> {
+        let (sender, receiver) = mpsc::unbounded();
+        self.listeners
+            .lock()
+            .entry(channel.to_string())
+            .or_default()
+            .push(sender);
+        receiver
+    }
+
+    /// Removes the streams of a channel which were closed, returning `true` if the channel has none left.
+    pub(crate) fn unsubscribe(&self, channel: &str) -> bool {
+        let mut listeners = self.listeners.lock();
+        let senders = match listeners.get_mut(channel) {
+            Some(senders) => senders,
+            // The connection closed.
+            None => return false,
+        };
+        senders.retain(|sender| !sender.is_closed());
+        if !senders.is_empty() {
+            return false;
+        }
+        listeners.remove(channel);
+        true
+    }
+
+    /// Sends a notification to the streams of its channel, returning `false` if it has none.
+    pub(crate) fn route_notification(&self, notification: &Notification) -> bool {
+        match self.listeners.lock().get_mut(notification.channel()) {
+            Some(senders) => {
+                senders.retain(|sender| sender.unbounded_send(notification.clone()).is_ok());
+                !senders.is_empty()
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn subscribe_parameters(&self) -> mpsc::UnboundedReceiver<(String, String)> {
+        let (sender, receiver) = mpsc::unbounded();
+        self.parameter_listeners.lock().push(sender);
+        receiver
+    }
+
+    /// Records the new value of a parameter, and sends it to the streams of parameter changes.
+    pub(crate) fn update_parameter(&self, name: String, value: String) {
+        self.parameter_listeners
+            .lock()
+            .retain(|sender| sender.unbounded_send((name.clone(), value.clone())).is_ok());
+        self.parameters.lock().insert(name, value);
+    }
+
+    pub(crate) fn set_healthy(&self, healthy: bool) {
+        self.healthy.store(healthy, Ordering::Relaxed);
+    }
+
+    pub(crate) fn set_server_closed(&self) {
+        self.server_closed.store(true, Ordering::Relaxed);
+    }
+
+    #[cfg(feature = "runtime")]
+    pub(crate) fn is_server_closed(&self) -> bool {
+        self.server_closed.load(Ordering::Relaxed)
+    }
+
+    /// Ends the streams of a channel.
+    pub(crate) fn close_channel(&self, channel: &str) {
+        self.listeners.lock().remove(channel);
+    }
+
+    /// Ends the streams of all channels.
+    pub(crate) fn close_channels(&self) {
+        self.listeners.lock().clear();
+    }
+
+    /// Ends the streams of all channels and of parameter changes.
+    pub(crate) fn close_listeners(&self) {
+        self.listeners.lock().clear();
+        self.parameter_listeners.lock().clear();
+    }
+}
+
+// Parses a `DateStyle` setting, such as `ISO, MDY`. Unknown formats are assumed to be `ISO`, the server's default.
+fn parse_date_style(value: &str) -> DateStyle {
+    let (format, order) = value.split_once(',').unwrap_or((value, ""));
+    // The server formats dates in the YMD order like in the MDY one.
+    let order = if order.trim().eq_ignore_ascii_case("DMY") {
+        DateOrder::Dmy
+    } else {
+        DateOrder::Mdy
+    };
+    match format.trim() {
+        f if f.eq_ignore_ascii_case("SQL") => DateStyle::Sql(order),
+        f if f.eq_ignore_ascii_case("Postgres") => DateStyle::Postgres(order),
+        f if f.eq_ignore_ascii_case("German") => DateStyle::German,
+        _ => DateStyle::Iso,
+    }
+}
+
+// Parses an `IntervalStyle` setting, such as `postgres_verbose`. Unknown formats are assumed to be `postgres`, the
+// server's default.
+fn parse_interval_style(value: &str) -> IntervalStyle {
+    match value {
+        "postgres_verbose" => IntervalStyle::PostgresVerbose,
+        "sql_standard" => IntervalStyle::SqlStandard,
+        "iso_8601" => IntervalStyle::Iso8601,
+        _ => IntervalStyle::Postgres,
+    }
+}
+
+/// The transaction status of a session.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TransactionStatus {
+    /// The session is not in a transaction block.
+    Idle,
+    /// The session is in a transaction block.
+    InTransaction,
+    /// The session is in a failed transaction block, in which queries are rejected until it is rolled back.
+    Failed,
 }
 
+#[derive(Default)]
+struct CachedTypeInfo {
+    /// The statements querying the catalog, prepared on first use and keyed by their query.
+    statements: HashMap<&'static str, Statement>,
+    types: HashMap<Oid, Type>,
+}
+
+const MAX_POOLED_BUFFERS: usize = 4;
+
 pub struct InnerClient {
     sender: mpsc::UnboundedSender<Request>,
-    /// A buffer to use when writing out postgres commands.
-    buffer: Mutex<BytesMut>,
+    /// A pool of buffers to use when writing out postgres commands.
+    buffers: Mutex<Vec<BytesMut>>,
+    instrumentation: Instrumentation,
+    interceptors: Mutex<Interceptors>,
+    session: Arc<Session>,
+    cached_typeinfo: Mutex<CachedTypeInfo>,
+    statement_cache: Mutex<StatementCache>,
+    resolve_types: bool,
+    cancel_token: Mutex<Arc<CancelToken>>,
+    #[cfg(feature = "runtime")]
+    query_timeout: Option<Duration>,
+    /// The slots of the requests in flight, if their number is limited.
+    inflight_limit: Option<Arc<Semaphore>>,
 }
 
 impl InnerClient {
-    pub fn send(&self, messages: RequestMessages) -> Result<Responses, Error> {
+    /// Waits for a slot to send a request in, if the number of requests in flight is limited.
+    ///
+    /// Slots are granted in the order they were waited for.
+    pub async fn reserve(&self) -> RequestPermit {
+        match &self.inflight_limit {
+            // The semaphore is never closed.
+            Some(semaphore) => RequestPermit {
+                _permit: semaphore.clone().acquire_owned().await.ok(),
+            },
+            None => RequestPermit::exempt(),
+        }
+    }
+
+    pub fn send(
+        &self,
+        permit: RequestPermit,
+        messages: RequestMessages,
+    ) -> Result<Responses, Error> {
         let (sender, receiver) = mpsc::channel(1);
-        let request = Request { messages, sender };
+        let state = Arc::new(RequestState::new());
+        let request = Request {
+            messages,
+            sender,
+            state: state.clone(),
+            in_flight: Some(InFlight::new(&self.session, permit)),
+        };
         self.sender
             .unbounded_send(request)
             .map_err(|_| Error::closed())?;
@@ -77,20 +372,106 @@ impl InnerClient {
         Ok(Responses {
             receiver,
             cur: BackendMessages::empty(),
+            state,
         })
     }
 
     /// Call the given function with a buffer to be used when writing out
     /// postgres commands.
+    ///
+    /// The buffer is taken from a pool, so that concurrent callers don't wait on each other while encoding. Once the
+    /// messages split off of it are written out, its allocation is reused by the next caller.
     pub fn with_buf<F, R>(&self, f: F) -> R
     where
         F: FnOnce(&mut BytesMut) -> R,
     {
-        let mut buffer = self.buffer.lock();
+        let mut buffer = self.buffers.lock().pop().unwrap_or_default();
         let r = f(&mut buffer);
         buffer.clear();
+        let mut buffers = self.buffers.lock();
+        if buffers.len() < MAX_POOLED_BUFFERS {
+            buffers.push(buffer);
+        }
         r
     }
+
+    pub fn instrumentation(&self) -> &Instrumentation {
+        &self.instrumentation
+    }
+
+    pub fn interceptors(&self) -> Interceptors {
+        self.interceptors.lock().clone()
+    }
+
+    pub fn cancel_token(&self) -> Arc<CancelToken> {
+        self.cancel_token.lock().clone()
+    }
+
+    #[cfg(feature = "runtime")]
+    pub fn query_timeout(&self) -> Option<Duration> {
+        self.query_timeout
+    }
+
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    pub fn statement_epoch(&self) -> u64 {
+        self.session.statement_epoch.load(Ordering::Relaxed)
+    }
+
+    /// Records that the request being encoded replaces the unnamed statement, which is the case of Parse messages
+    /// without a statement name and of simple queries, returning the new generation of the unnamed statement.
+    pub fn replace_unnamed_statement(&self) -> u64 {
+        self.session
+            .unnamed_statement
+            .fetch_add(1, Ordering::Relaxed)
+            + 1
+    }
+
+    pub fn unnamed_statement(&self) -> u64 {
+        self.session.unnamed_statement.load(Ordering::Relaxed)
+    }
+
+    /// Returns the settings determining the text format of the values sent by the server.
+    pub fn text_settings(&self) -> TextSettings {
+        let parameters = self.session.parameters.lock();
+        let date_style = match parameters.get("DateStyle") {
+            Some(date_style) => parse_date_style(date_style),
+            None => DateStyle::Iso,
+        };
+        let interval_style = match parameters.get("IntervalStyle") {
+            Some(interval_style) => parse_interval_style(interval_style),
+            None => IntervalStyle::Postgres,
+        };
+        TextSettings::new()
+            .date_style(date_style)
+            .interval_style(interval_style)
+    }
+
+    pub fn typeinfo_statement(&self, query: &str) -> Option<Statement> {
+        let statement = self.cached_typeinfo.lock().statements.get(query).cloned()?;
+        Some(statement).filter(|s| !s.is_deallocated(self.statement_epoch()))
+    }
+
+    pub fn set_typeinfo_statement(&self, query: &'static str, statement: &Statement) {
+        self.cached_typeinfo
+            .lock()
+            .statements
+            .insert(query, statement.clone());
+    }
+
+    pub fn resolves_types(&self) -> bool {
+        self.resolve_types
+    }
+
+    pub fn type_(&self, oid: Oid) -> Option<Type> {
+        self.cached_typeinfo.lock().types.get(&oid).cloned()
+    }
+
+    pub fn set_type(&self, oid: Oid, type_: &Type) {
+        self.cached_typeinfo.lock().types.insert(oid, type_.clone());
+    }
 }
 
 #[cfg(feature = "runtime")]
@@ -98,6 +479,7 @@ impl InnerClient {
 pub(crate) struct SocketConfig {
     pub addr: Addr,
     pub hostname: Option<String>,
+    pub ssl_server_name: Option<String>,
     pub port: u16,
     pub connect_timeout: Option<Duration>,
     pub tcp_user_timeout: Option<Duration>,
@@ -118,31 +500,49 @@ pub(crate) enum Addr {
 /// through this client object.
 pub struct Client {
     inner: Arc<InnerClient>,
-    #[cfg(feature = "runtime")]
-    socket_config: Option<SocketConfig>,
-    ssl_mode: SslMode,
-    process_id: i32,
-    secret_key: i32,
+    security: ConnectionSecurity,
 }
 
 impl Client {
     pub(crate) fn new(
         sender: mpsc::UnboundedSender<Request>,
-        ssl_mode: SslMode,
+        config: &Config,
         process_id: i32,
         secret_key: i32,
+        security: ConnectionSecurity,
+        instrumentation: Instrumentation,
+        session: Arc<Session>,
     ) -> Client {
-        Client {
-            inner: Arc::new(InnerClient {
-                sender,
-                buffer: Default::default(),
-            }),
+        let cancel_token = CancelToken {
             #[cfg(feature = "runtime")]
             socket_config: None,
-            ssl_mode,
+            #[cfg(feature = "runtime")]
+            session: Arc::downgrade(&session),
+            tls_settings: TlsSettings::new(config),
             process_id,
             secret_key,
-        }
+        };
+        let client = Client {
+            inner: Arc::new(InnerClient {
+                sender,
+                buffers: Default::default(),
+                instrumentation,
+                interceptors: Default::default(),
+                session,
+                cached_typeinfo: Default::default(),
+                statement_cache: Mutex::new(StatementCache::new(config.statement_cache_capacity)),
+                resolve_types: config.resolve_types,
+                cancel_token: Mutex::new(Arc::new(cancel_token)),
+                #[cfg(feature = "runtime")]
+                query_timeout: config.query_timeout,
+                inflight_limit: config
+                    .max_inflight_requests
+                    .map(|limit| Arc::new(Semaphore::new(limit))),
+            }),
+            security,
+        };
+        client.register_types(config.types.iter().cloned());
+        client
     }
 
     pub(crate) fn inner(&self) -> &Arc<InnerClient> {
@@ -151,13 +551,50 @@ impl Client {
 
     #[cfg(feature = "runtime")]
     pub(crate) fn set_socket_config(&mut self, socket_config: SocketConfig) {
-        self.socket_config = Some(socket_config);
+        let mut cancel_token = self.inner.cancel_token.lock();
+        *cancel_token = Arc::new(CancelToken {
+            socket_config: Some(socket_config),
+            ..(**cancel_token).clone()
+        });
+    }
+
+    /// Registers an interceptor invoked around the operations performed by the client.
+    ///
+    /// Interceptors are invoked in the order they were registered. See the [`interceptor`](crate::interceptor) module
+    /// for details.
+    pub fn add_interceptor(&mut self, interceptor: Arc<dyn Interceptor>) {
+        self.inner.interceptors.lock().push(interceptor);
+    }
+
+    /// Returns the statistics of the statements executed by the client.
+    ///
+    /// Statistics are only collected if enabled with [`Config::statement_stats`], and this returns an empty list
+    /// otherwise. See the [`stats`](crate::stats) module for details.
+    ///
+    /// [`Config::statement_stats`]: crate::Config::statement_stats
+    pub fn statement_stats(&self) -> Vec<StatementStats> {
+        self.inner.instrumentation().statement_stats()
+    }
+
+    /// Discards the statistics collected so far by the client.
+    pub fn reset_statement_stats(&self) {
+        self.inner.instrumentation().reset_statement_stats()
     }
 
     /// Creates a new prepared statement.
     ///
     /// Prepared statements can be executed repeatedly, and may contain query parameters (indicated by `$1`, `$2`, etc),
     /// which are set when executed. Prepared statements can only be used with the connection that created them.
+    ///
+    /// If a schema change alters the columns returned by a statement, the server refuses to execute it with a
+    /// "cached plan must not change result type" error. The same happens with a "prepared statement does not exist"
+    /// error if the statement was deallocated behind the client's back. When this happens outside of a transaction,
+    /// `query_raw` and `execute_raw`, along with the methods built on them, transparently prepare the statement again
+    /// and retry once. The original statement stays stale though, and should be replaced by a newly prepared one to
+    /// avoid the extra round trips. Inside of a transaction, the error is returned since the transaction is aborted.
+    ///
+    /// Statements deallocated by a `DISCARD ALL` or `DEALLOCATE ALL` command executed through the client are instead
+    /// prepared again before being used, including inside of transactions.
     pub async fn prepare(&self, query: &str) -> Result<Statement, Error> {
         self.prepare_typed(query, &[]).await
     }
@@ -174,8 +611,122 @@ impl Client {
         prepare::prepare(&self.inner, query, parameter_types, false).await
     }
 
-    pub(crate) async fn prepare_unnamed(&self, query: &str) -> Result<Statement, Error> {
-        prepare::prepare(&self.inner, query, &[], true).await
+    /// Like `prepare_typed`, but prepares the unnamed statement rather than a statement of its own.
+    ///
+    /// The session has a single unnamed statement, which is replaced whenever another query is prepared into it. This
+    /// is the case of other unnamed statements, but also of queries executed from a string rather than a `Statement`,
+    /// and of simple queries such as those started by `batch_execute` or `transaction`. Since nothing has to be
+    /// deallocated, this avoids running out of statement names when connected through a pooler such as PgBouncer in
+    /// transaction mode, which doesn't keep named statements around between transactions.
+    ///
+    /// Using the statement once it has been replaced returns an error rather than executing the other query, so two
+    /// unnamed statements can't be used concurrently. The statements of `prepare` and `prepare_typed` should be used
+    /// for queries executed repeatedly.
+    pub async fn prepare_unnamed(
+        &self,
+        query: &str,
+        parameter_types: &[Type],
+    ) -> Result<Statement, Error> {
+        prepare::prepare(&self.inner, query, parameter_types, true).await
+    }
+
+    /// Returns the parameter and column types of a query, without executing it.
+    ///
+    /// Unlike `prepare`, this doesn't create a prepared statement on the server: the query is only parsed into the
+    /// unnamed statement, which is replaced by the next query executed from a string. The columns report the table
+    /// column they are read from, if any, with `Column::table_oid` and `Column::column_id`.
+    pub async fn describe(&self, query: &str) -> Result<StatementDescription, Error> {
+        self.describe_typed(query, &[]).await
+    }
+
+    /// Like `describe`, but allows the types of query parameters to be explicitly specified.
+    ///
+    /// The list of types may be smaller than the number of parameters - the types of the remaining parameters will be
+    /// inferred.
+    pub async fn describe_typed(
+        &self,
+        query: &str,
+        parameter_types: &[Type],
+    ) -> Result<StatementDescription, Error> {
+        let statement = self.prepare_unnamed(query, parameter_types).await?;
+        Ok(StatementDescription::new(&statement))
+    }
+
+    /// Looks up the type with the given OID in the server's catalog.
+    ///
+    /// This resolves a type fully, including the variants of an enum, the fields of a composite type and the types
+    /// arrays, ranges and domains are made of. Resolved types are cached by the client, so each is only looked up
+    /// once. Statements resolve the types of their parameters and columns the same way when prepared, unless disabled
+    /// with [`Config::resolve_types`], in which case types which aren't built into Postgres are reported as `TEXT`.
+    ///
+    /// An error is returned if no type has this OID.
+    ///
+    /// [`Config::resolve_types`]: crate::Config::resolve_types
+    pub async fn resolve_type(&self, oid: Oid) -> Result<Type, Error> {
+        prepare::resolve_type(&self.inner, oid).await
+    }
+
+    /// Adds types to those known to the client, as if they had been resolved from the server's catalog.
+    ///
+    /// Parameters and columns of these types are reported with them without looking them up, which lets enums,
+    /// composites and other user-defined types be decoded when [`Config::resolve_types`] is disabled. The OIDs of the
+    /// types must match those assigned by the server, and the types they are made of are registered along with them.
+    /// Types built into Postgres are ignored, and types can also be registered for every connection with
+    /// [`Config::register_type`].
+    ///
+    /// Only statements prepared after this call are affected.
+    ///
+    /// [`Config::resolve_types`]: crate::Config::resolve_types
+    /// [`Config::register_type`]: crate::Config::register_type
+    pub fn register_types<I>(&self, types: I)
+    where
+        I: IntoIterator<Item = Type>,
+    {
+        for type_ in types {
+            prepare::register_type(&self.inner, &type_);
+        }
+    }
+
+    /// Returns the tables of a schema, ordered by name.
+    ///
+    /// Views, materialized views and foreign tables are included, as detailed in the [`schema`](crate::schema)
+    /// module.
+    pub async fn tables(&self, schema: &str) -> Result<Vec<Table>, Error> {
+        schema::tables(self, schema).await
+    }
+
+    /// Returns the columns of the table with the given OID, ordered by their number.
+    ///
+    /// Their types are resolved like with `resolve_type`.
+    pub async fn table_columns(&self, table: Oid) -> Result<Vec<TableColumn>, Error> {
+        schema::table_columns(self, table).await
+    }
+
+    /// Returns the primary key of the table with the given OID, or `None` if it has none.
+    pub async fn primary_key(&self, table: Oid) -> Result<Option<PrimaryKey>, Error> {
+        schema::primary_key(self, table).await
+    }
+
+    /// Returns the foreign keys of the table with the given OID, ordered by name.
+    pub async fn foreign_keys(&self, table: Oid) -> Result<Vec<ForeignKey>, Error> {
+        schema::foreign_keys(self, table).await
+    }
+
+    /// Returns the indexes of the table with the given OID, ordered by name.
+    pub async fn indexes(&self, table: Oid) -> Result<Vec<Index>, Error> {
+        schema::indexes(self, table).await
+    }
+
+    /// Creates an empty large object, returning its OID.
+    ///
+    /// See the [`large_object`](crate::large_object) module for details.
+    pub async fn create_large_object(&self) -> Result<Oid, Error> {
+        large_object::create(&self.inner).await
+    }
+
+    /// Deletes the large object with the given OID.
+    pub async fn unlink_large_object(&self, oid: Oid) -> Result<(), Error> {
+        large_object::unlink(&self.inner, oid).await
     }
 
     /// Executes a statement, returning a vector of the resulting rows.
@@ -194,12 +745,127 @@ impl Client {
     where
         T: ?Sized + ToStatement,
     {
-        self.query_raw(statement, slice_iter(params))
+        self.query_with_opts(statement, params, QueryOptions::new())
+            .await
+    }
+
+    /// Like [`query`], but with options controlling the execution of the query.
+    ///
+    /// [`query`]: #method.query
+    pub async fn query_with_opts<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+        options: QueryOptions,
+    ) -> Result<Vec<Row>, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        let query = async {
+            self.query_raw(statement, slice_iter(params))
+                .await?
+                .try_collect()
+                .await
+        };
+        query_options::with_timeout(&self.inner, options, query).await
+    }
+
+    /// Like [`query`], but returns at most `max_rows` rows.
+    ///
+    /// The limit is enforced by the server, which stops executing the statement once it returned `max_rows` rows,
+    /// without the query having to be rewritten with a `LIMIT` clause. All of the rows are returned if `max_rows` is
+    /// negative or 0.
+    ///
+    /// [`query`]: #method.query
+    pub async fn query_with_limit<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+        max_rows: i32,
+    ) -> Result<Vec<Row>, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        self.query_raw_with_limit(statement, slice_iter(params), max_rows)
             .await?
             .try_collect()
             .await
     }
 
+    /// Like [`query`], but collects the rows into a compact [`Rows`] container.
+    ///
+    /// The values of all of the rows are stored in a single buffer rather than in one per row, which takes much less
+    /// memory for results made of many small rows.
+    ///
+    /// [`query`]: #method.query
+    pub async fn query_rows<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Rows, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        let stream = self.query_raw(statement, slice_iter(params)).await?;
+        Rows::collect(stream).await
+    }
+
+    /// Like `query`, but executes the query with a statement prepared and cached by the client.
+    ///
+    /// The first time a query is executed by this method, it is prepared as a named statement kept in a least
+    /// recently used cache of the client, keyed by its text. Later executions of the same query reuse the statement
+    /// instead of preparing it again. The capacity of the cache is set by `Config::statement_cache_capacity`, and
+    /// statements evicted from it are closed.
+    ///
+    /// If a schema change alters the columns returned by a cached statement, the server refuses to execute it with a
+    /// "cached plan must not change result type" error. The statement is then evicted and, outside of a transaction,
+    /// prepared again and retried once. Inside of a transaction, the error is returned since the transaction is
+    /// aborted, and the query is prepared again the next time it is executed.
+    pub async fn query_cached(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, Error> {
+        let statement = self.cached_statement(query).await?;
+        let result = match query::query(&self.inner, statement, slice_iter(params)).await {
+            Err(e) if needs_reprepare(&e) => {
+                self.evict_cached_statement(query);
+                if self.transaction_status() != TransactionStatus::Idle {
+                    return Err(e);
+                }
+                let statement = self.cached_statement(query).await?;
+                query::query(&self.inner, statement, slice_iter(params)).await
+            }
+            result => result,
+        };
+        result?.try_collect().await
+    }
+
+    /// Closes the statements cached by `query_cached`, once they are no longer used elsewhere.
+    pub fn clear_statement_cache(&self) {
+        self.inner.statement_cache.lock().clear();
+    }
+
+    pub(crate) fn evict_cached_statement(&self, query: &str) {
+        self.inner.statement_cache.lock().remove(query);
+    }
+
+    pub(crate) async fn cached_statement(&self, query: &str) -> Result<Statement, Error> {
+        let cached = self.inner.statement_cache.lock().get(query);
+        if let Some(statement) = cached {
+            if !statement.is_deallocated(self.inner.statement_epoch()) {
+                return Ok(statement);
+            }
+        }
+
+        let statement = self.prepare(query).await?;
+        self.inner
+            .statement_cache
+            .lock()
+            .insert(query, statement.clone());
+        Ok(statement)
+    }
+
     /// Executes a statement which returns a single row, returning it.
     ///
     /// Returns an error if the query does not return exactly one row.
@@ -223,100 +889,502 @@ impl Client {
             .and_then(|res| res.ok_or_else(Error::row_count))
     }
 
-    /// Executes a statements which returns zero or one rows, returning it.
+    /// Executes a statements which returns zero or one rows, returning it.
+    ///
+    /// Returns an error if the query returns more than one row.
+    ///
+    /// A statement may contain parameters, specified by `$n`, where `n` is the index of the parameter of the list
+    /// provided, 1-indexed.
+    ///
+    /// The `statement` argument can either be a `Statement`, or a raw query string. If the same statement will be
+    /// repeatedly executed (perhaps with different query parameters), consider preparing the statement up front
+    /// with the `prepare` method.
+    pub async fn query_opt<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<Row>, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        let query = async {
+            let stream = self.query_raw(statement, slice_iter(params)).await?;
+            pin_mut!(stream);
+
+            let mut first = None;
+
+            // Originally this was two calls to `try_next().await?`,
+            // once for the first element, and second to error if more than one.
+            //
+            // However, this new form with only one .await in a loop generates
+            // slightly smaller codegen/stack usage for the resulting future.
+            while let Some(row) = stream.try_next().await? {
+                if first.is_some() {
+                    return Err(Error::row_count());
+                }
+
+                first = Some(row);
+            }
+
+            Ok(first)
+        };
+        query_options::with_timeout(&self.inner, QueryOptions::new(), query).await
+    }
+
+    /// Executes a statement which returns a single row of a single column, returning its value.
+    ///
+    /// Returns an error if the query does not return exactly one row, or if the row does not have exactly one column.
+    ///
+    /// A statement may contain parameters, specified by `$n`, where `n` is the index of the parameter of the list
+    /// provided, 1-indexed.
+    ///
+    /// The `statement` argument can either be a `Statement`, or a raw query string. If the same statement will be
+    /// repeatedly executed (perhaps with different query parameters), consider preparing the statement up front
+    /// with the `prepare` method.
+    pub async fn query_scalar<R>(
+        &self,
+        statement: &(impl ?Sized + ToStatement),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<R, Error>
+    where
+        R: FromSqlOwned,
+    {
+        self.query_scalar_opt(statement, params)
+            .await
+            .and_then(|res| res.ok_or_else(Error::row_count))
+    }
+
+    /// Executes a statement which returns zero or one rows of a single column, returning its value.
+    ///
+    /// Returns an error if the query returns more than one row, or if the row does not have exactly one column.
+    ///
+    /// A statement may contain parameters, specified by `$n`, where `n` is the index of the parameter of the list
+    /// provided, 1-indexed.
+    ///
+    /// The `statement` argument can either be a `Statement`, or a raw query string. If the same statement will be
+    /// repeatedly executed (perhaps with different query parameters), consider preparing the statement up front
+    /// with the `prepare` method.
+    pub async fn query_scalar_opt<R>(
+        &self,
+        statement: &(impl ?Sized + ToStatement),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<R>, Error>
+    where
+        R: FromSqlOwned,
+    {
+        match self.query_opt(statement, params).await? {
+            Some(row) if row.len() != 1 => Err(Error::column_count(row.len())),
+            Some(row) => row.try_get(0).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Executes a statement, returning the resulting rows deserialized into values of a type implementing `FromRow`.
+    ///
+    /// The fields of the values are read from the columns of the same name, as with `Row::try_into`.
+    ///
+    /// A statement may contain parameters, specified by `$n`, where `n` is the index of the parameter of the list
+    /// provided, 1-indexed.
+    ///
+    /// The `statement` argument can either be a `Statement`, or a raw query string. If the same statement will be
+    /// repeatedly executed (perhaps with different query parameters), consider preparing the statement up front
+    /// with the `prepare` method.
+    pub async fn query_as<R>(
+        &self,
+        statement: &(impl ?Sized + ToStatement),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<R>, Error>
+    where
+        R: FromRow,
+    {
+        self.query_raw(statement, slice_iter(params))
+            .await?
+            .and_then(|row| future::ready(row.try_into()))
+            .try_collect()
+            .await
+    }
+
+    /// The maximally flexible version of [`query`].
+    ///
+    /// A statement may contain parameters, specified by `$n`, where `n` is the index of the parameter of the list
+    /// provided, 1-indexed.
+    ///
+    /// The `statement` argument can either be a `Statement`, or a raw query string. If the same statement will be
+    /// repeatedly executed (perhaps with different query parameters), consider preparing the statement up front
+    /// with the `prepare` method.
+    ///
+    /// [`query`]: #method.query
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn async_main(client: &tokio_postgres::Client) -> Result<(), tokio_postgres::Error> {
+    /// use tokio_postgres::types::ToSql;
+    /// use futures_util::{pin_mut, TryStreamExt};
+    ///
+    /// let params: Vec<String> = vec![
+    ///     "first param".into(),
+    ///     "second param".into(),
+    /// ];
+    /// let mut it = client.query_raw(
+    ///     "SELECT foo FROM bar WHERE biz = $1 AND baz = $2",
+    ///     params,
+    /// ).await?;
+    ///
+    /// pin_mut!(it);
+    /// while let Some(row) = it.try_next().await? {
+    ///     let foo: i32 = row.get("foo");
+    ///     println!("foo: {}", foo);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn query_raw<T, P, I>(&self, statement: &T, params: I) -> Result<RowStream, Error>
+    where
+        T: ?Sized + ToStatement,
+        P: BorrowToSql,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let statement = statement.__convert().into_statement(self).await?;
+        if !self.can_reprepare(&statement) {
+            return query::query(&self.inner, statement, params).await;
+        }
+
+        let interceptors = self.inner.interceptors();
+        let permit = self.inner.reserve().await;
+        let buf = match query::encode_execute_streamed(
+            &self.inner,
+            &interceptors,
+            Operation::Query,
+            &statement,
+            params,
+        )? {
+            RequestMessages::Single(FrontendMessage::Raw(buf)) => buf,
+            // Streamed values can't be sent again with a statement prepared again.
+            messages => {
+                return query::query_messages(&self.inner, permit, statement, messages).await
+            }
+        };
+        match query::query_encoded(&self.inner, permit, statement.clone(), buf.clone()).await {
+            Err(e) if needs_reprepare(&e) => match self.reprepare(&statement).await? {
+                Some(new) => {
+                    let permit = self.inner.reserve().await;
+                    let buf = query::rebind(&buf, &new);
+                    query::query_encoded(&self.inner, permit, new, buf).await
+                }
+                None => Err(e),
+            },
+            result => result,
+        }
+    }
+
+    /// Like [`query_raw`], but returns at most `max_rows` rows, as detailed in [`query_with_limit`].
+    ///
+    /// Once the stream has been exhausted, its `is_suspended` method tells whether the statement had more rows to
+    /// return.
+    ///
+    /// [`query_raw`]: #method.query_raw
+    /// [`query_with_limit`]: #method.query_with_limit
+    pub async fn query_raw_with_limit<T, P, I>(
+        &self,
+        statement: &T,
+        params: I,
+        max_rows: i32,
+    ) -> Result<RowStream, Error>
+    where
+        T: ?Sized + ToStatement,
+        P: BorrowToSql,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let statement = statement.__convert().into_statement(self).await?;
+        let interceptors = self.inner.interceptors();
+        let permit = self.inner.reserve().await;
+        let messages = query::encode_execute_limited(
+            &self.inner,
+            &interceptors,
+            Operation::Query,
+            &statement,
+            params,
+            max_rows.max(0),
+        )?;
+        query::query_messages(&self.inner, permit, statement, messages).await
+    }
+
+    /// Like [`query_raw`], but returns the rows in their wire format, without decoding them.
+    ///
+    /// The stream yields the bodies of the `DataRow` messages sent by the server, and also returns the body of the
+    /// `RowDescription` message describing the rows. They can be forwarded to another peer speaking the Postgres
+    /// protocol, as detailed in the [`passthrough`](crate::passthrough) module.
+    ///
+    /// [`query_raw`]: #method.query_raw
+    pub async fn query_raw_passthrough<T, P, I>(
+        &self,
+        statement: &T,
+        params: I,
+    ) -> Result<PassthroughStream, Error>
+    where
+        T: ?Sized + ToStatement,
+        P: BorrowToSql,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let statement = statement.__convert().into_statement(self).await?;
+        passthrough::query(&self.inner, statement, params).await
+    }
+
+    /// Like [`query_raw`], but returns all of the messages of the response, as they were received from the server.
+    ///
+    /// The messages can be spliced into another connection speaking the Postgres protocol, as detailed in the
+    /// [`passthrough`](crate::passthrough) module. Errors reported by the server are forwarded like any other message
+    /// rather than returned.
+    ///
+    /// [`query_raw`]: #method.query_raw
+    pub async fn query_raw_forward<T, P, I>(
+        &self,
+        statement: &T,
+        params: I,
+    ) -> Result<ForwardStream, Error>
+    where
+        T: ?Sized + ToStatement,
+        P: BorrowToSql,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let statement = statement.__convert().into_statement(self).await?;
+        passthrough::forward(&self.inner, statement, params).await
+    }
+
+    /// Pass text directly to the Postgres backend to allow it to sort out typing itself and
+    /// to save a roundtrip
+    ///
+    /// The parameters are converted into the text format with `ToText`, and their types inferred by the server from
+    /// the query. The rows are returned in the text format, and their values can still be read with `Row::get`.
+    /// Columns whose types aren't built into Postgres only have their actual types if the client already resolved
+    /// them, for example by preparing a statement using them. Otherwise they are reported as `TEXT`, and their values
+    /// read as strings.
+    ///
+    /// Values already encoded in the binary format, like large `BYTEA` values, can be passed as `BinaryValue`s instead,
+    /// whose types are declared rather than inferred.
+    pub async fn query_raw_txt<'a, P, I>(&self, query: &str, params: I) -> Result<RowStream, Error>
+    where
+        P: ToText,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        query::query_txt(&self.inner, query, params).await
+    }
+
+    /// Like `query_raw_txt`, but for a statement which returns a single row, returning it.
+    ///
+    /// Returns an error if the query does not return exactly one row.
+    pub async fn query_one_txt<P, I>(&self, query: &str, params: I) -> Result<Row, Error>
+    where
+        P: ToText,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.query_opt_txt(query, params)
+            .await
+            .and_then(|res| res.ok_or_else(Error::row_count))
+    }
+
+    /// Like `query_raw_txt`, but for a statement which returns zero or one rows, returning it.
+    ///
+    /// Returns an error if the query returns more than one row.
+    pub async fn query_opt_txt<P, I>(&self, query: &str, params: I) -> Result<Option<Row>, Error>
+    where
+        P: ToText,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let stream = self.query_raw_txt(query, params).await?;
+        pin_mut!(stream);
+
+        let mut first = None;
+        while let Some(row) = stream.try_next().await? {
+            if first.is_some() {
+                return Err(Error::row_count());
+            }
+
+            first = Some(row);
+        }
+
+        Ok(first)
+    }
+
+    /// Executes a query with parameters already encoded, in the formats given, returning rows in the format requested.
+    ///
+    /// The types of the parameters are inferred by the server from the query. `param_formats` follows the rules of the
+    /// protocol's `Bind` message: it can be empty, so that all parameters are in the text format, have a single format
+    /// applying to all of them, or one format per parameter. This lets proxies relay the values they receive as is,
+    /// and choose per query whether rows are returned in the text or binary format. The values of the rows can be read
+    /// with `Row::get` in either format.
+    pub async fn query_with_format<P, I>(
+        &self,
+        query: &str,
+        params: I,
+        param_formats: &[Format],
+        result_format: Format,
+    ) -> Result<RowStream, Error>
+    where
+        P: AsRef<[u8]>,
+        I: IntoIterator<Item = Option<P>>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        query::query_with_format(&self.inner, query, params, param_formats, result_format).await
+    }
+
+    /// Like [`query_raw`], but for a query whose parameters are encoded against the types inferred by the server.
+    ///
+    /// The query is parsed and described first, then bound with the parameters encoded in the binary format once the
+    /// server has described their types. Unlike passing a query string to `query_raw`, both steps happen in the same
+    /// request, without a `Sync` in between, so that the unnamed statement can't be replaced by another query, and
+    /// the request is kept on the same server by connection poolers like PgBouncer in transaction mode.
+    ///
+    /// [`query_raw`]: #method.query_raw
+    pub async fn query_raw_inferred<P, I>(&self, query: &str, params: I) -> Result<RowStream, Error>
+    where
+        P: BorrowToSql,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        infer::query(&self.inner, query, params).await
+    }
+
+    /// Sends a buffer of frontend messages built by the caller to the server, returning the messages of its response.
     ///
-    /// Returns an error if the query returns more than one row.
+    /// This is an escape hatch for features of the protocol the client doesn't cover. The buffer must contain a complete
+    /// request, ended by a `Sync` or simple `Query` message, as detailed in the [`raw`](crate::raw) module. The request
+    /// is sent right away, even if the limit set by `Config::max_inflight_requests` is reached.
+    pub fn request_raw(&self, messages: Bytes) -> Result<RawResponses, Error> {
+        raw::request(&self.inner, messages)
+    }
+
+    /// Executes a statement, returning a stream of Arrow record batches of at most `batch_size` rows each.
     ///
-    /// A statement may contain parameters, specified by `$n`, where `n` is the index of the parameter of the list
-    /// provided, 1-indexed.
+    /// The statement is prepared first, and an error is returned without executing it if the type of one of its columns
+    /// can't be converted to Arrow. See the [`arrow`](crate::arrow) module for details.
     ///
-    /// The `statement` argument can either be a `Statement`, or a raw query string. If the same statement will be
-    /// repeatedly executed (perhaps with different query parameters), consider preparing the statement up front
-    /// with the `prepare` method.
-    pub async fn query_opt<T>(
+    /// Requires the `arrow` Cargo feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `batch_size` is 0, or if the number of parameters provided does not match the number expected.
+    #[cfg(feature = "arrow")]
+    pub async fn query_arrow<T>(
         &self,
         statement: &T,
         params: &[&(dyn ToSql + Sync)],
-    ) -> Result<Option<Row>, Error>
+        batch_size: usize,
+    ) -> Result<RecordBatchStream, Error>
     where
         T: ?Sized + ToStatement,
     {
-        let stream = self.query_raw(statement, slice_iter(params)).await?;
-        pin_mut!(stream);
-
-        let mut first = None;
-
-        // Originally this was two calls to `try_next().await?`,
-        // once for the first element, and second to error if more than one.
-        //
-        // However, this new form with only one .await in a loop generates
-        // slightly smaller codegen/stack usage for the resulting future.
-        while let Some(row) = stream.try_next().await? {
-            if first.is_some() {
-                return Err(Error::row_count());
-            }
-
-            first = Some(row);
-        }
-
-        Ok(first)
+        let statement = statement.__convert().into_statement(self).await?;
+        crate::arrow::schema(statement.columns())?;
+        let rows = self.query_raw(&statement, slice_iter(params)).await?;
+        RecordBatchStream::new(rows, statement.columns(), batch_size)
     }
 
-    /// The maximally flexible version of [`query`].
+    /// Executes a statement, returning its rows in batches of at most `chunk_size` rows.
+    ///
+    /// The statement is bound to a portal in a new transaction, and the batches are fetched from it one at a time as
+    /// [`RowChunks::next`] is called, so the memory used doesn't grow with the number of rows of the result. The
+    /// transaction is rolled back when the `RowChunks` is dropped, or committed by [`RowChunks::finish`].
     ///
     /// A statement may contain parameters, specified by `$n`, where `n` is the index of the parameter of the list
     /// provided, 1-indexed.
     ///
-    /// The `statement` argument can either be a `Statement`, or a raw query string. If the same statement will be
-    /// repeatedly executed (perhaps with different query parameters), consider preparing the statement up front
-    /// with the `prepare` method.
+    /// # Panics
     ///
-    /// [`query`]: #method.query
+    /// Panics if `chunk_size` is not positive, or if the number of parameters provided does not match the number
+    /// expected.
     ///
     /// # Examples
     ///
     /// ```no_run
-    /// # async fn async_main(client: &tokio_postgres::Client) -> Result<(), tokio_postgres::Error> {
-    /// use tokio_postgres::types::ToSql;
-    /// use futures_util::{pin_mut, TryStreamExt};
-    ///
-    /// let params: Vec<String> = vec![
-    ///     "first param".into(),
-    ///     "second param".into(),
-    /// ];
-    /// let mut it = client.query_raw(
-    ///     "SELECT foo FROM bar WHERE biz = $1 AND baz = $2",
-    ///     params,
-    /// ).await?;
-    ///
-    /// pin_mut!(it);
-    /// while let Some(row) = it.try_next().await? {
-    ///     let foo: i32 = row.get("foo");
-    ///     println!("foo: {}", foo);
+    /// # async fn f(client: &mut tokio_postgres::Client) -> Result<(), tokio_postgres::Error> {
+    /// let mut chunks = client.query_chunked("SELECT id FROM events", &[], 1000).await?;
+    /// while let Some(rows) = chunks.next().await? {
+    ///     for row in rows {
+    ///         let id: i32 = row.get(0);
+    ///         println!("event {}", id);
+    ///     }
     /// }
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn query_raw<T, P, I>(&self, statement: &T, params: I) -> Result<RowStream, Error>
+    pub async fn query_chunked<T>(
+        &mut self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+        chunk_size: i32,
+    ) -> Result<RowChunks<'_>, Error>
     where
         T: ?Sized + ToStatement,
-        P: BorrowToSql,
-        I: IntoIterator<Item = P>,
-        I::IntoIter: ExactSizeIterator,
     {
-        let statement = statement.__convert().into_statement(self).await?;
-        query::query(&self.inner, statement, params).await
+        let transaction = self.transaction().await?;
+        RowChunks::new(transaction, statement, params, chunk_size).await
     }
 
-    /// Pass text directly to the Postgres backend to allow it to sort out typing itself and
-    /// to save a roundtrip
-    pub async fn query_raw_txt<'a, S, I>(&self, query: &str, params: I) -> Result<RowStream, Error>
-    where
-        S: AsRef<str>,
-        I: IntoIterator<Item = Option<S>>,
-        I::IntoIter: ExactSizeIterator,
-    {
-        query::query_txt(&self.inner, query, params).await
+    /// Declares a cursor `WITH HOLD` over the rows of a query.
+    ///
+    /// The rows are fetched from the cursor with [`fetch`], and it remains open across the commits of transactions,
+    /// which suits paging through a large result while committing work between pages. Declared outside of a
+    /// transaction, the query runs to completion up front and its rows are kept by the server until the cursor is
+    /// closed. Declared inside of one, they are kept once it commits, and the cursor is gone if it rolls back.
+    ///
+    /// The query is embedded in a `DECLARE` statement, so it must be a string rather than a prepared statement. It may
+    /// contain parameters, specified by `$n`, where `n` is the index of the parameter of the list provided, 1-indexed.
+    ///
+    /// The cursor is closed when dropped, or by [`close_cursor`], which returns any error encountered.
+    ///
+    /// [`fetch`]: #method.fetch
+    /// [`close_cursor`]: #method.close_cursor
+    pub async fn declare_cursor(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Cursor, Error> {
+        cursor::declare(self, query, params).await
+    }
+
+    /// Fetches the next rows of a cursor, returning at most `count` of them.
+    ///
+    /// Returns fewer rows than requested once the end of the cursor is reached.
+    pub async fn fetch(&self, cursor: &Cursor, count: i64) -> Result<Vec<Row>, Error> {
+        cursor::fetch(self, cursor, count).await
+    }
+
+    /// Closes a cursor.
+    ///
+    /// This is equivalent to `Cursor`'s `Drop` implementation, but provides any error encountered to the caller.
+    pub async fn close_cursor(&self, cursor: Cursor) -> Result<(), Error> {
+        cursor::close(self, cursor).await
+    }
+
+    /// Continues execution of a portal, returning a vector of the resulting rows.
+    ///
+    /// This is like `Transaction::query_portal`, for portals created with `Statement::bind`. The connection must still
+    /// be in the transaction block in which the portal was created.
+    pub async fn query_portal(&self, portal: &Portal, max_rows: i32) -> Result<Vec<Row>, Error> {
+        self.query_portal_raw(portal, max_rows)
+            .await?
+            .try_collect()
+            .await
+    }
+
+    /// The maximally flexible version of [`query_portal`].
+    ///
+    /// [`query_portal`]: #method.query_portal
+    pub async fn query_portal_raw(
+        &self,
+        portal: &Portal,
+        max_rows: i32,
+    ) -> Result<RowStream, Error> {
+        query::query_portal(self.inner(), portal, max_rows).await
     }
 
     /// Executes a statement, returning the number of rows modified.
@@ -337,7 +1405,24 @@ impl Client {
     where
         T: ?Sized + ToStatement,
     {
-        self.execute_raw(statement, slice_iter(params)).await
+        self.execute_with_opts(statement, params, QueryOptions::new())
+            .await
+    }
+
+    /// Like [`execute`], but with options controlling the execution of the statement.
+    ///
+    /// [`execute`]: #method.execute
+    pub async fn execute_with_opts<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+        options: QueryOptions,
+    ) -> Result<u64, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        let execute = self.execute_raw(statement, slice_iter(params));
+        query_options::with_timeout(&self.inner, options, execute).await
     }
 
     /// The maximally flexible version of [`execute`].
@@ -358,7 +1443,125 @@ impl Client {
         I::IntoIter: ExactSizeIterator,
     {
         let statement = statement.__convert().into_statement(self).await?;
-        query::execute(self.inner(), statement, params).await
+        if !self.can_reprepare(&statement) {
+            return query::execute(self.inner(), statement, params).await;
+        }
+
+        let interceptors = self.inner.interceptors();
+        let permit = self.inner.reserve().await;
+        let buf = match query::encode_execute_streamed(
+            &self.inner,
+            &interceptors,
+            Operation::Execute,
+            &statement,
+            params,
+        )? {
+            RequestMessages::Single(FrontendMessage::Raw(buf)) => buf,
+            // Streamed values can't be sent again with a statement prepared again.
+            messages => {
+                return query::execute_messages(
+                    &self.inner,
+                    permit,
+                    &interceptors,
+                    statement,
+                    messages,
+                )
+                .await
+            }
+        };
+        let result = query::execute_encoded(
+            &self.inner,
+            permit,
+            &interceptors,
+            statement.clone(),
+            buf.clone(),
+        )
+        .await;
+        match result {
+            Err(e) if needs_reprepare(&e) => match self.reprepare(&statement).await? {
+                Some(new) => {
+                    let permit = self.inner.reserve().await;
+                    let buf = query::rebind(&buf, &new);
+                    query::execute_encoded(&self.inner, permit, &interceptors, new, buf).await
+                }
+                None => Err(e),
+            },
+            result => result,
+        }
+    }
+
+    /// Executes a statement once for each row of parameters, returning the total number of rows modified.
+    ///
+    /// The statement is prepared once, and its executions are all sent at once with a single `Sync` message, so that
+    /// they take a single round trip and run in a single implicit transaction unless they are executed inside of an
+    /// explicit one. This is much faster than calling `execute` for each row when inserting many rows, and unlike
+    /// `COPY`, allows for clauses like `ON CONFLICT`.
+    ///
+    /// If a row fails, none of the rows take effect, and the returned error holds the index of the failing row.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn async_main(client: &tokio_postgres::Client) -> Result<(), tokio_postgres::ExecuteManyError> {
+    /// let rows = [(1i32, "foo"), (2, "bar")];
+    /// let inserted = client
+    ///     .execute_many(
+    ///         "INSERT INTO foo (id, name) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+    ///         rows.iter().map(|(id, name)| [id as &(dyn ToSql + Sync), name]),
+    ///     )
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// # use tokio_postgres::types::ToSql;
+    /// ```
+    pub async fn execute_many<T, P, I, R>(
+        &self,
+        statement: &T,
+        rows: R,
+    ) -> Result<u64, ExecuteManyError>
+    where
+        T: ?Sized + ToStatement,
+        P: BorrowToSql,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+        R: IntoIterator<Item = I>,
+    {
+        let statement = statement.__convert().into_statement(self).await?;
+        let execute =
+            async { Ok(execute_many::execute_many(self.inner(), &statement, rows).await) };
+        query_options::with_timeout(&self.inner, QueryOptions::new(), execute).await?
+    }
+
+    // Statements can only be retried if the failed attempt ran in its own implicit transaction.
+    fn can_reprepare(&self, statement: &Statement) -> bool {
+        statement.prepared_with().is_some() && self.transaction_status() == TransactionStatus::Idle
+    }
+
+    // Statements deallocated by a `DISCARD ALL` or `DEALLOCATE ALL` issued through the client are prepared again. The
+    // new statement is kept with the deallocated one, so that it is only prepared once per deallocation.
+    pub(crate) async fn refresh_statement(
+        &self,
+        statement: &Statement,
+    ) -> Result<Statement, Error> {
+        let epoch = self.inner.statement_epoch();
+        if !statement.is_deallocated(epoch) {
+            return Ok(statement.clone());
+        }
+        if let Some(replacement) = statement.replacement(epoch) {
+            return Ok(replacement);
+        }
+
+        let (sql, types) = statement.prepared_with().unwrap();
+        let replacement = prepare::prepare(&self.inner, sql, types, false).await?;
+        statement.set_replacement(&replacement);
+        Ok(replacement)
+    }
+
+    // Returns `None` if the parameter types changed too, since the parameters were encoded for the old ones.
+    async fn reprepare(&self, statement: &Statement) -> Result<Option<Statement>, Error> {
+        let (sql, types) = statement.prepared_with().unwrap();
+        let new = prepare::prepare(&self.inner, sql, types, false).await?;
+        Ok(Some(new).filter(|new| new.params() == statement.params()))
     }
 
     /// Executes a `COPY FROM STDIN` statement, returning a sink used to write the copy data.
@@ -385,6 +1588,18 @@ impl Client {
         copy_out::copy_out(self.inner(), statement).await
     }
 
+    /// Executes a query using the simple query protocol which starts a copy in both directions, returning a duplex
+    /// stream used to exchange the copy data.
+    ///
+    /// This is used by streaming replication, whose `START_REPLICATION` command requires a connection opened in a
+    /// replication mode. See the [`replication`](crate::replication) module for a decoder of its messages.
+    pub async fn copy_both_simple<T>(&self, query: &str) -> Result<CopyBothDuplex<T>, Error>
+    where
+        T: Buf + 'static + Send,
+    {
+        copy_both::copy_both_simple(self.inner(), query).await
+    }
+
     /// Executes a sequence of SQL statements using the simple query protocol, returning the resulting rows.
     ///
     /// Statements should be separated by semicolons. If an error occurs, execution of the sequence will stop at that
@@ -398,8 +1613,10 @@ impl Client {
     /// Prepared statements should be use for any query which contains user-specified data, as they provided the
     /// functionality to safely embed that data in the request. Do not form statements via string concatenation and pass
     /// them to this method!
+    /// If values must be embedded in a statement, quote them with the [`escape`](crate::escape) module.
     pub async fn simple_query(&self, query: &str) -> Result<Vec<SimpleQueryMessage>, Error> {
-        self.simple_query_raw(query).await?.try_collect().await
+        let query = async { self.simple_query_raw(query).await?.try_collect().await };
+        query_options::with_timeout(&self.inner, QueryOptions::new(), query).await
     }
 
     pub(crate) async fn simple_query_raw(&self, query: &str) -> Result<SimpleQueryStream, Error> {
@@ -416,8 +1633,130 @@ impl Client {
     /// Prepared statements should be use for any query which contains user-specified data, as they provided the
     /// functionality to safely embed that data in the request. Do not form statements via string concatenation and pass
     /// them to this method!
+    /// If values must be embedded in a statement, quote them with the [`escape`](crate::escape) module.
     pub async fn batch_execute(&self, query: &str) -> Result<(), Error> {
-        simple_query::batch_execute(self.inner(), query).await
+        let query = simple_query::batch_execute(self.inner(), query);
+        query_options::with_timeout(&self.inner, QueryOptions::new(), query).await
+    }
+
+    /// Executes a sequence of SQL statements with parameters in a single round trip, returning their command tags.
+    ///
+    /// The statements are separated by semicolons, which are found outside of literals, quoted identifiers and
+    /// comments. Each statement is executed with the extended query protocol, all of them before a single `Sync`, so
+    /// that they run in a single implicit transaction unless the batch is executed inside of an explicit one: if a
+    /// statement fails, none of them take effect. Statements which can't run in a transaction block, like `VACUUM`,
+    /// can't be executed this way, nor can `BEGIN ATOMIC` function bodies, whose semicolons would split them.
+    ///
+    /// The statements share the parameters, which they reference as `$1`, `$2`, etc. throughout the batch. Like with
+    /// `query_raw_txt`, they are passed in the text format and their types are inferred by the server from each
+    /// statement, so a parameter must be used in a way that determines its type.
+    pub async fn batch_execute_params<P, I>(
+        &self,
+        query: &str,
+        params: I,
+    ) -> Result<Vec<String>, Error>
+    where
+        P: ToText,
+        I: IntoIterator<Item = P>,
+    {
+        let query = batch::batch_execute_params(self.inner(), query, params);
+        query_options::with_timeout(&self.inner, QueryOptions::new(), query).await
+    }
+
+    /// Listens on a channel, returning a stream of the notifications sent on it.
+    ///
+    /// The channel is passed to `LISTEN` as a quoted identifier, so it is matched exactly like with `notify`.
+    /// Notifications of the channel are delivered to its streams rather than reported by `Connection::poll_message`.
+    /// Once all the streams of a channel are dropped, the session stops listening on it with `UNLISTEN`.
+    ///
+    /// Inside of a transaction, notifications are only received once the transaction commits.
+    pub async fn listen(&self, channel: &str) -> Result<NotificationStream, Error> {
+        listen::listen(&self.inner, channel).await
+    }
+
+    /// Stops listening on a channel, ending its streams.
+    ///
+    /// The channel is passed to `UNLISTEN` as a quoted identifier, like with `listen`.
+    pub async fn unlisten(&self, channel: &str) -> Result<(), Error> {
+        listen::unlisten(&self.inner, channel).await
+    }
+
+    /// Stops listening on all channels, ending their streams.
+    pub async fn unlisten_all(&self) -> Result<(), Error> {
+        listen::unlisten_all(&self.inner).await
+    }
+
+    /// Sends a notification to the sessions listening on a channel.
+    ///
+    /// The notification is sent with a `NOTIFY` command using the simple query protocol, in which the channel is
+    /// quoted and the payload escaped, so they don't need to be sanitized. The channel is matched exactly, like a
+    /// quoted identifier: listening with `LISTEN Jobs` subscribes to the `jobs` channel, not to `Jobs`.
+    ///
+    /// Inside of a transaction, the notification is only delivered once the transaction commits.
+    pub async fn notify(&self, channel: &str, payload: &str) -> Result<(), Error> {
+        listen::notify(&self.inner, channel, payload).await
+    }
+
+    /// Sets the value of a runtime parameter of the session, like `SET`.
+    ///
+    /// The name and value are passed to `set_config` as parameters rather than embedded into a `SET` command, so they
+    /// don't need to be quoted or escaped. If `local` is `true`, the value only applies until the end of the current
+    /// transaction, like with `SET LOCAL`.
+    pub async fn set_parameter(&self, name: &str, value: &str, local: bool) -> Result<(), Error> {
+        self.execute("SELECT set_config($1, $2, $3)", &[&name, &value, &local])
+            .await
+            .map(|_| ())
+    }
+
+    /// Returns the current value of a runtime parameter of the session, like `SHOW`.
+    ///
+    /// Unlike `parameter`, this queries the server, so it works for all parameters rather than only the reported ones.
+    /// Returns an error if the parameter doesn't exist.
+    pub async fn get_parameter(&self, name: &str) -> Result<String, Error> {
+        self.query_scalar("SELECT current_setting($1)", &[&name])
+            .await
+    }
+
+    /// Explains a query, returning the plan chosen by the server.
+    ///
+    /// The query is run through `EXPLAIN (FORMAT JSON)` with the given options, and the resulting JSON is parsed into a
+    /// tree of plan nodes. Parameters are bound as they would be if the query was executed.
+    ///
+    /// Note that when the `analyze` option is enabled the query is actually executed, with all of its side effects.
+    ///
+    /// Requires the `with-serde_json-1` Cargo feature.
+    #[cfg(feature = "with-serde_json-1")]
+    pub async fn explain(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+        options: ExplainOptions,
+    ) -> Result<Plan, Error> {
+        let row = self.query_one(&*options.to_sql(query), params).await?;
+        let plan = row.try_get::<_, serde_json_1::Value>(0)?;
+        Plan::from_json(&plan).map_err(|e| Error::from_sql(e, 0))
+    }
+
+    /// Creates a pipeline of statements to be sent to the server at once.
+    ///
+    /// See the [`pipeline`](crate::pipeline) module for details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn async_main(client: &tokio_postgres::Client) -> Result<(), tokio_postgres::Error> {
+    /// let mut pipeline = client.pipeline();
+    /// pipeline
+    ///     .execute("INSERT INTO foo (bar) VALUES ($1)", &[&1i32])
+    ///     .query("SELECT bar FROM foo", &[]);
+    /// for result in pipeline.run().await {
+    ///     println!("{:?}", result?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn pipeline(&self) -> Pipeline<'_> {
+        Pipeline::new(self)
     }
 
     /// Begins a new database transaction.
@@ -436,13 +1775,14 @@ impl Client {
                 }
 
                 let buf = self.client.inner().with_buf(|buf| {
+                    self.client.inner().replace_unnamed_statement();
                     frontend::query("ROLLBACK", buf).unwrap();
                     buf.split().freeze()
                 });
-                let _ = self
-                    .client
-                    .inner()
-                    .send(RequestMessages::Single(FrontendMessage::Raw(buf)));
+                let _ = self.client.inner().send(
+                    RequestPermit::exempt(),
+                    RequestMessages::Single(FrontendMessage::Raw(buf)),
+                );
             }
         }
 
@@ -456,7 +1796,9 @@ impl Client {
                 client: self,
                 done: false,
             };
-            self.batch_execute("BEGIN").await?;
+            Span::transaction(self.inner.instrumentation(), "BEGIN")
+                .instrument(self.batch_execute("BEGIN"))
+                .await?;
             cleaner.done = true;
         }
 
@@ -474,13 +1816,7 @@ impl Client {
     /// Constructs a cancellation token that can later be used to request cancellation of a query running on the
     /// connection associated with this client.
     pub fn cancel_token(&self) -> CancelToken {
-        CancelToken {
-            #[cfg(feature = "runtime")]
-            socket_config: self.socket_config.clone(),
-            ssl_mode: self.ssl_mode,
-            process_id: self.process_id,
-            secret_key: self.secret_key,
-        }
+        (*self.inner.cancel_token()).clone()
     }
 
     /// Attempts to cancel an in-progress query.
@@ -509,6 +1845,116 @@ impl Client {
         self.cancel_token().cancel_query_raw(stream, tls).await
     }
 
+    /// Returns the value of a runtime parameter reported by the server.
+    ///
+    /// The server reports the values of a fixed set of parameters, such as `server_version` or `TimeZone`, when the
+    /// connection is established and whenever they change afterwards. The returned value is the latest one received by
+    /// the connection, so the connection must be polled for changes to be observed. Parameters which aren't reported
+    /// return `None`; use `SHOW` to query those.
+    pub fn parameter(&self, name: &str) -> Option<String> {
+        self.inner.session.parameters.lock().get(name).cloned()
+    }
+
+    /// Returns a stream of the changes of the runtime parameters reported by the server.
+    ///
+    /// Like with `parameter`, changes are only received while the connection is polled. The stream only returns the
+    /// changes received after it is created, and ends when the connection closes.
+    pub fn parameter_changes(&self) -> ParameterChanges {
+        ParameterChanges {
+            receiver: self.inner.session.subscribe_parameters(),
+        }
+    }
+
+    /// Returns the version of the server, as reported by the `server_version` parameter.
+    ///
+    /// `None` is returned if the parameter isn't reported or can't be parsed, in which case its raw value can still be
+    /// retrieved with `parameter`.
+    pub fn server_version(&self) -> Option<ServerVersion> {
+        self.parameter("server_version")?.parse().ok()
+    }
+
+    /// Returns the encoding of the database, as reported by the `server_encoding` parameter.
+    pub fn server_encoding(&self) -> Option<String> {
+        self.parameter("server_encoding")
+    }
+
+    /// Returns the encoding used by the connection, as reported by the `client_encoding` parameter.
+    pub fn client_encoding(&self) -> Option<String> {
+        self.parameter("client_encoding")
+    }
+
+    /// Returns the time zone of the session, as reported by the `TimeZone` parameter.
+    pub fn timezone(&self) -> Option<String> {
+        self.parameter("TimeZone")
+    }
+
+    /// Returns the display format of dates and times, as reported by the `DateStyle` parameter.
+    pub fn date_style(&self) -> Option<String> {
+        self.parameter("DateStyle")
+    }
+
+    /// Returns the display format of intervals, as reported by the `IntervalStyle` parameter.
+    pub fn interval_style(&self) -> Option<String> {
+        self.parameter("IntervalStyle")
+    }
+
+    /// Returns the application name of the session, as reported by the `application_name` parameter.
+    pub fn application_name(&self) -> Option<String> {
+        self.parameter("application_name")
+    }
+
+    /// Returns whether the session user is a superuser, as reported by the `is_superuser` parameter.
+    pub fn is_superuser(&self) -> Option<bool> {
+        self.parameter("is_superuser").map(|value| value == "on")
+    }
+
+    /// Returns whether backslashes are treated literally in string literals, as reported by the
+    /// `standard_conforming_strings` parameter.
+    pub fn standard_conforming_strings(&self) -> Option<bool> {
+        self.parameter("standard_conforming_strings")
+            .map(|value| value == "on")
+    }
+
+    /// Returns the transaction status of the session.
+    ///
+    /// The status is the one reported by the server once it completed the last request received by the connection,
+    /// so it doesn't account for requests still in flight. In particular, errors are returned as soon as the server
+    /// reports them, possibly before the status changes. Connection pools can use it to discard connections returned
+    /// in the middle of a transaction.
+    pub fn transaction_status(&self) -> TransactionStatus {
+        match self
+            .inner
+            .session
+            .transaction_status
+            .load(Ordering::Relaxed)
+        {
+            b'T' => TransactionStatus::InTransaction,
+            b'E' => TransactionStatus::Failed,
+            _ => TransactionStatus::Idle,
+        }
+    }
+
+    /// Returns the process ID of the backend serving the connection, as sent by the server during startup.
+    ///
+    /// This is the value returned by `pg_backend_pid()`, which can be passed to `pg_terminate_backend` or looked up in
+    /// `pg_stat_activity`.
+    pub fn backend_pid(&self) -> i32 {
+        self.inner.cancel_token.lock().process_id
+    }
+
+    /// Returns the secret key sent by the server during startup, which is required to cancel the requests of the
+    /// connection.
+    ///
+    /// Prefer [`cancel_token`](Client::cancel_token) to cancel requests.
+    pub fn backend_secret_key(&self) -> i32 {
+        self.inner.cancel_token.lock().secret_key
+    }
+
+    /// Returns how the connection was authenticated and encrypted.
+    pub fn connection_security(&self) -> ConnectionSecurity {
+        self.security
+    }
+
     /// Determines if the connection to the server has already closed.
     ///
     /// In that case, all future queries will fail.
@@ -516,6 +1962,64 @@ impl Client {
         self.inner.sender.is_closed()
     }
 
+    /// Determines if the connection to the server is healthy.
+    ///
+    /// This is the case until the connection closes, or a keepalive round trip enabled with
+    /// [`Config::keepalive_query_interval`] isn't responded to before the next one is due, and again once a request
+    /// completes.
+    ///
+    /// [`Config::keepalive_query_interval`]: crate::Config::keepalive_query_interval
+    pub fn is_healthy(&self) -> bool {
+        !self.is_closed() && self.inner.session.healthy.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of requests sent to the connection whose responses haven't completed yet.
+    ///
+    /// This includes the requests queued by the connection, and those written to the server which it hasn't finished
+    /// responding to, but not those waiting for a slot under the limit set by [`Config::max_inflight_requests`].
+    ///
+    /// [`Config::max_inflight_requests`]: crate::Config::max_inflight_requests
+    pub fn pending_requests(&self) -> usize {
+        self.inner.session.pending_requests.load(Ordering::Relaxed)
+    }
+
+    /// Closes the connection gracefully.
+    ///
+    /// Unlike dropping the client, this waits for the connection to close: the requests queued before the call are
+    /// sent and their responses received first, then a Terminate message is sent to the server and the socket is shut
+    /// down. Requests queued after the call, like those closing statements dropped in the meantime, are discarded, and
+    /// their number is returned.
+    ///
+    /// The connection must be polled, typically by a spawned task, for this method to complete.
+    pub async fn close(self) -> Result<usize, Error> {
+        let (sender, receiver) = oneshot::channel();
+        let request = Request {
+            messages: RequestMessages::Close(sender),
+            // Nothing is responded to the request.
+            sender: mpsc::channel(0).0,
+            state: Arc::new(RequestState::new()),
+            in_flight: None,
+        };
+        self.inner
+            .sender
+            .unbounded_send(request)
+            .map_err(|_| Error::closed())?;
+
+        receiver.await.map_err(|_| Error::closed())
+    }
+
+    /// Like `close`, but fails with a timeout error if the connection hasn't closed within `timeout`.
+    ///
+    /// In that case, the connection stops accepting requests but keeps closing in the background once the requests
+    /// queued before the call have completed.
+    #[cfg(feature = "runtime")]
+    pub async fn close_timeout(self, timeout: Duration) -> Result<usize, Error> {
+        match time::timeout(timeout, self.close()).await {
+            Ok(result) => result,
+            Err(_) => Err(Error::timeout()),
+        }
+    }
+
     #[doc(hidden)]
     pub fn __private_api_close(&mut self) {
         self.inner.sender.close_channel()
@@ -527,3 +2031,22 @@ impl fmt::Debug for Client {
         f.debug_struct("Client").finish()
     }
 }
+
+// Detects statements whose plan changed result type, or which were deallocated without the client noticing, for
+// example by a connection pooler.
+fn needs_reprepare(e: &Error) -> bool {
+    // The message is translated according to lc_messages, unlike the name of the function raising the error.
+    match e.as_db_error() {
+        Some(e) => matches!(
+            (e.code(), e.routine()),
+            (
+                &SqlState::FEATURE_NOT_SUPPORTED,
+                Some("RevalidateCachedQuery")
+            ) | (
+                &SqlState::INVALID_SQL_STATEMENT_NAME,
+                Some("FetchPreparedStatement")
+            )
+        ),
+        None => false,
+    }
+}
@@ -1,12 +1,15 @@
+use crate::metrics::{MetricsHandle, WireStats};
 use bytes::{Buf, Bytes, BytesMut};
 use fallible_iterator::FallibleIterator;
 use postgres_protocol::message::backend;
 use postgres_protocol::message::frontend::CopyData;
 use std::io;
-use tokio_util::codec::{Decoder, Encoder};
+use tokio_util::codec::Decoder;
 
 pub enum FrontendMessage {
     Raw(Bytes),
+    /// Bytes continuing a message started by a previous one.
+    Continuation(Bytes),
     CopyData(CopyData<Box<dyn Buf + Send>>),
 }
 
@@ -14,6 +17,8 @@ pub enum BackendMessage {
     Normal {
         messages: BackendMessages,
         request_complete: bool,
+        /// Whether the messages complete a command deallocating all prepared statements of the session.
+        resets_statements: bool,
     },
     Async(backend::Message),
 }
@@ -24,6 +29,19 @@ impl BackendMessages {
     pub fn empty() -> BackendMessages {
         BackendMessages(BytesMut::new())
     }
+
+    /// Returns the transaction status of the `ReadyForQuery` message terminating the messages of a completed request.
+    pub fn ready_status(&self) -> Option<u8> {
+        self.0.last().copied()
+    }
+
+    /// Returns the next message without parsing it, including its header.
+    pub fn next_raw(&mut self) -> io::Result<Option<Bytes>> {
+        match backend::Header::parse(&self.0)? {
+            Some(header) => Ok(Some(self.0.split_to(header.len() as usize + 1).freeze())),
+            None => Ok(None),
+        }
+    }
 }
 
 impl FallibleIterator for BackendMessages {
@@ -37,19 +55,8 @@ impl FallibleIterator for BackendMessages {
 
 pub struct PostgresCodec {
     pub max_message_size: Option<usize>,
-}
-
-impl Encoder<FrontendMessage> for PostgresCodec {
-    type Error = io::Error;
-
-    fn encode(&mut self, item: FrontendMessage, dst: &mut BytesMut) -> io::Result<()> {
-        match item {
-            FrontendMessage::Raw(buf) => dst.extend_from_slice(&buf),
-            FrontendMessage::CopyData(data) => data.write(dst),
-        }
-
-        Ok(())
-    }
+    pub metrics: Option<MetricsHandle>,
+    pub stats: WireStats,
 }
 
 impl Decoder for PostgresCodec {
@@ -59,6 +66,7 @@ impl Decoder for PostgresCodec {
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<BackendMessage>, io::Error> {
         let mut idx = 0;
         let mut request_complete = false;
+        let mut resets_statements = false;
 
         while let Some(header) = backend::Header::parse(&src[idx..])? {
             let len = header.len() as usize + 1;
@@ -80,6 +88,8 @@ impl Decoder for PostgresCodec {
                 | backend::NOTIFICATION_RESPONSE_TAG
                 | backend::PARAMETER_STATUS_TAG => {
                     if idx == 0 {
+                        self.stats
+                            .record_received(header.tag(), len, self.metrics.as_ref());
                         let message = backend::Message::parse(src)?.unwrap();
                         return Ok(Some(BackendMessage::Async(message)));
                     } else {
@@ -89,7 +99,18 @@ impl Decoder for PostgresCodec {
                 _ => {}
             }
 
+            if header.tag() == backend::COMMAND_COMPLETE_TAG
+                && matches!(
+                    &src[idx + 5..idx + len],
+                    b"DISCARD ALL\0" | b"DEALLOCATE ALL\0"
+                )
+            {
+                resets_statements = true;
+            }
+
             idx += len;
+            self.stats
+                .record_received(header.tag(), len, self.metrics.as_ref());
 
             if header.tag() == backend::READY_FOR_QUERY_TAG {
                 request_complete = true;
@@ -103,6 +124,7 @@ impl Decoder for PostgresCodec {
             Ok(Some(BackendMessage::Normal {
                 messages: BackendMessages(src.split_to(idx)),
                 request_complete,
+                resets_statements,
             }))
         }
     }
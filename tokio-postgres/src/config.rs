@@ -1,27 +1,42 @@
 //! Connection configuration.
 
 #[cfg(feature = "runtime")]
-use crate::connect::connect;
+use crate::connect::{connect, connect_with};
 use crate::connect_raw::connect_raw;
+#[cfg(feature = "runtime")]
+use crate::connect_socket::configure_tcp;
+use crate::gss::GssContext;
+use crate::instrument::time_connect;
+#[cfg(feature = "runtime")]
+use crate::instrument::Span;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::keepalive::KeepaliveConfig;
+use crate::metrics::{Metrics, MetricsHandle};
+use crate::security::{AuthenticationMethod, Encryption};
 #[cfg(feature = "runtime")]
 use crate::tls::MakeTlsConnect;
 use crate::tls::TlsConnect;
+use crate::types::Type;
 #[cfg(feature = "runtime")]
 use crate::Socket;
 use crate::{Client, Connection, Error};
+#[cfg(feature = "runtime")]
+use socket2::SockRef;
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 #[cfg(unix)]
 use std::ffi::OsStr;
+use std::future::Future;
 use std::net::IpAddr;
 use std::ops::Deref;
 #[cfg(unix)]
 use std::os::unix::ffi::OsStrExt;
 #[cfg(unix)]
 use std::path::{Path, PathBuf};
+use std::pin::Pin;
 use std::str;
 use std::str::FromStr;
+use std::sync::Arc;
 use std::time::Duration;
 use std::{error, fmt, iter, mem};
 use tokio::io::{AsyncRead, AsyncWrite};
@@ -36,6 +51,12 @@ pub enum TargetSessionAttrs {
     ReadWrite,
     /// The session allow only reads.
     ReadOnly,
+    /// The server must not be in hot standby mode.
+    Primary,
+    /// The server must be in hot standby mode.
+    Standby,
+    /// A server in hot standby mode is preferred, but any server is accepted if none is available.
+    PreferStandby,
 }
 
 /// TLS configuration.
@@ -48,6 +69,23 @@ pub enum SslMode {
     Prefer,
     /// Require the use of TLS.
     Require,
+    /// Require the use of TLS, and verify that the server's certificate is issued by a trusted certificate authority.
+    VerifyCa,
+    /// Require the use of TLS, and verify that the server's certificate is issued by a trusted certificate authority
+    /// and matches the host, or the server name set with `ssl_server_name`.
+    VerifyFull,
+}
+
+/// GSSAPI encryption configuration.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GssEncMode {
+    /// Do not use GSSAPI encryption.
+    Disable,
+    /// Attempt to connect with GSSAPI encryption but allow sessions without.
+    Prefer,
+    /// Require the use of GSSAPI encryption.
+    Require,
 }
 
 /// Channel binding configuration.
@@ -72,18 +110,314 @@ pub enum LoadBalanceHosts {
     Random,
 }
 
+/// Replication mode configuration.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ReplicationMode {
+    /// Physical replication, where the session only accepts replication commands.
+    Physical,
+    /// Logical replication, where the session is connected to a database and also accepts SQL commands.
+    Logical,
+}
+
 /// A host specification.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Host {
     /// A TCP hostname.
     Tcp(String),
-    /// A path to a directory containing the server's Unix socket.
+    /// A path to a directory containing the server's Unix socket, or to the socket itself.
     ///
-    /// This variant is only available on Unix platforms.
+    /// On Linux, a path starting with `@` names a directory in the abstract socket namespace. This variant is only
+    /// available on Unix platforms.
     #[cfg(unix)]
     Unix(PathBuf),
 }
 
+/// Credentials overriding those of a [`Config`] when connecting to a specific host.
+///
+/// Any field left unset falls back to the corresponding setting of the configuration.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct HostCredentials {
+    user: Option<String>,
+    password: Option<Vec<u8>>,
+    dbname: Option<String>,
+}
+
+impl HostCredentials {
+    /// Creates a new, empty set of credentials.
+    pub fn new() -> HostCredentials {
+        HostCredentials::default()
+    }
+
+    /// Sets the user to authenticate with.
+    pub fn user(&mut self, user: &str) -> &mut HostCredentials {
+        self.user = Some(user.to_string());
+        self
+    }
+
+    /// Gets the user to authenticate with, if one has been set.
+    pub fn get_user(&self) -> Option<&str> {
+        self.user.as_deref()
+    }
+
+    /// Sets the password to authenticate with.
+    ///
+    /// This takes precedence over both the password and the password provider of the configuration.
+    pub fn password<T>(&mut self, password: T) -> &mut HostCredentials
+    where
+        T: AsRef<[u8]>,
+    {
+        self.password = Some(password.as_ref().to_vec());
+        self
+    }
+
+    /// Gets the password to authenticate with, if one has been set.
+    pub fn get_password(&self) -> Option<&[u8]> {
+        self.password.as_deref()
+    }
+
+    /// Sets the name of the database to connect to.
+    pub fn dbname(&mut self, dbname: &str) -> &mut HostCredentials {
+        self.dbname = Some(dbname.to_string());
+        self
+    }
+
+    /// Gets the name of the database to connect to, if one has been set.
+    pub fn get_dbname(&self) -> Option<&str> {
+        self.dbname.as_deref()
+    }
+}
+
+impl fmt::Debug for HostCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct Redaction {}
+        impl fmt::Debug for Redaction {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "_")
+            }
+        }
+
+        f.debug_struct("HostCredentials")
+            .field("user", &self.user)
+            .field("password", &self.password.as_ref().map(|_| Redaction {}))
+            .field("dbname", &self.dbname)
+            .finish()
+    }
+}
+
+type PasswordFuture =
+    Pin<Box<dyn Future<Output = Result<Vec<u8>, Box<dyn error::Error + Sync + Send>>> + Send>>;
+
+/// An asynchronous callback producing the password used to authenticate.
+#[derive(Clone)]
+pub(crate) struct PasswordProvider(Arc<dyn Fn() -> PasswordFuture + Sync + Send>);
+
+impl PasswordProvider {
+    pub(crate) async fn password(&self) -> Result<Vec<u8>, Box<dyn error::Error + Sync + Send>> {
+        (self.0)().await
+    }
+}
+
+impl PartialEq for PasswordProvider {
+    fn eq(&self, other: &PasswordProvider) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for PasswordProvider {}
+
+impl fmt::Debug for PasswordProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PasswordProvider").finish_non_exhaustive()
+    }
+}
+
+type GssContextResult = Result<Box<dyn GssContext>, Box<dyn error::Error + Sync + Send>>;
+
+/// A callback creating the GSSAPI security context for a host.
+#[derive(Clone)]
+pub(crate) struct GssContextProvider(Arc<dyn Fn(&str) -> GssContextResult + Sync + Send>);
+
+impl GssContextProvider {
+    pub(crate) fn context(&self, host: &str) -> GssContextResult {
+        (self.0)(host)
+    }
+}
+
+impl PartialEq for GssContextProvider {
+    fn eq(&self, other: &GssContextProvider) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for GssContextProvider {}
+
+impl fmt::Debug for GssContextProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GssContextProvider").finish_non_exhaustive()
+    }
+}
+
+/// A callback producing the sqlcommenter tags appended to outgoing SQL.
+#[derive(Clone)]
+pub(crate) struct SqlCommenter(Arc<dyn Fn() -> Vec<(String, String)> + Sync + Send>);
+
+impl SqlCommenter {
+    pub(crate) fn tags(&self) -> Vec<(String, String)> {
+        (self.0)()
+    }
+}
+
+impl PartialEq for SqlCommenter {
+    fn eq(&self, other: &SqlCommenter) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for SqlCommenter {}
+
+impl fmt::Debug for SqlCommenter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SqlCommenter").finish_non_exhaustive()
+    }
+}
+
+/// A statement which took longer than the threshold configured with [`Config::log_slow_statements`] to execute.
+#[derive(Debug)]
+pub struct SlowStatement<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) query: Option<&'a str>,
+    pub(crate) duration: Duration,
+    pub(crate) rows: Option<u64>,
+}
+
+impl<'a> SlowStatement<'a> {
+    /// Returns the name of the prepared statement which was executed.
+    ///
+    /// This is empty for queries executed without preparing a statement first, such as with `query_raw_txt`.
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// Returns the SQL which was executed, if it was sent along with the execution.
+    pub fn query(&self) -> Option<&'a str> {
+        self.query
+    }
+
+    /// Returns the time taken by the statement, measured from the moment it was sent until the server reported its
+    /// completion.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns the number of rows returned or affected by the statement, if the server reported one.
+    pub fn rows(&self) -> Option<u64> {
+        self.rows
+    }
+}
+
+/// A callback invoked with statements exceeding the slow statement threshold.
+#[derive(Clone)]
+pub(crate) struct SlowStatementCallback(Arc<dyn Fn(&SlowStatement<'_>) + Sync + Send>);
+
+impl SlowStatementCallback {
+    pub(crate) fn call(&self, statement: &SlowStatement<'_>) {
+        (self.0)(statement)
+    }
+}
+
+impl PartialEq for SlowStatementCallback {
+    fn eq(&self, other: &SlowStatementCallback) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for SlowStatementCallback {}
+
+impl fmt::Debug for SlowStatementCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlowStatementCallback")
+            .finish_non_exhaustive()
+    }
+}
+
+type AfterConnectFuture<'a> = Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>>;
+
+/// An asynchronous callback run with each newly established connection.
+#[derive(Clone)]
+pub(crate) struct AfterConnectCallback(
+    Arc<dyn for<'a> Fn(&'a Client) -> AfterConnectFuture<'a> + Sync + Send>,
+);
+
+impl AfterConnectCallback {
+    #[cfg(feature = "runtime")]
+    pub(crate) async fn call(&self, client: &Client) -> Result<(), Error> {
+        (self.0)(client).await
+    }
+}
+
+impl PartialEq for AfterConnectCallback {
+    fn eq(&self, other: &AfterConnectCallback) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for AfterConnectCallback {}
+
+impl fmt::Debug for AfterConnectCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AfterConnectCallback")
+            .finish_non_exhaustive()
+    }
+}
+
+/// A change in the state of a connection, reported to the callback configured with [`Config::lifecycle_callback`].
+#[derive(Debug, Copy, Clone)]
+#[non_exhaustive]
+pub enum LifecycleEvent<'a> {
+    /// The startup handshake with a server is starting over a newly opened stream.
+    Connecting {
+        /// The hostname of the server, if known.
+        host: Option<&'a str>,
+        /// The port of the server, if known.
+        port: Option<u16>,
+    },
+    /// The stream has been encrypted, with TLS or GSSAPI.
+    Encrypted(Encryption),
+    /// The server has authenticated the client.
+    Authenticated(AuthenticationMethod),
+    /// The connection has been established and is ready to process queries.
+    Ready,
+    /// The client has been dropped and the connection is terminating the session.
+    Terminating,
+    /// The connection has been closed, or failed to be established, with the error which caused it if any.
+    Closed(Option<&'a Error>),
+}
+
+/// A callback invoked with the lifecycle events of connections.
+#[derive(Clone)]
+pub(crate) struct LifecycleCallback(Arc<dyn Fn(&LifecycleEvent<'_>) + Sync + Send>);
+
+impl LifecycleCallback {
+    pub(crate) fn call(&self, event: &LifecycleEvent<'_>) {
+        (self.0)(event)
+    }
+}
+
+impl PartialEq for LifecycleCallback {
+    fn eq(&self, other: &LifecycleCallback) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for LifecycleCallback {}
+
+impl fmt::Debug for LifecycleCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LifecycleCallback").finish_non_exhaustive()
+    }
+}
+
 /// Connection configuration.
 ///
 /// Configuration can be parsed from libpq-style connection strings. These strings come in two formats:
@@ -98,14 +432,38 @@ pub enum Host {
 /// * `user` - The username to authenticate with. Defaults to the user executing this process.
 /// * `password` - The password to authenticate with.
 /// * `dbname` - The name of the database to connect to. Defaults to the username.
-/// * `options` - Command line options used to configure the server.
+/// * `options` - Command line options used to configure the server. Runtime parameters set with `-c name=value` or
+///     `--name=value` are instead sent individually as the session starts up, as with [`Config::startup_param`].
+///     Options are separated by whitespace, and whitespace and `\` characters within an option should be
+///     backslash-escaped.
 /// * `application_name` - Sets the `application_name` parameter on the server.
+/// * `fallback_application_name` - Sets the `application_name` parameter on the server if `application_name` is not
+///     set. With the `default-application-name` feature enabled, defaults to the name of the running executable.
 /// * `sslmode` - Controls usage of TLS. If set to `disable`, TLS will not be used. If set to `prefer`, TLS will be used
-///     if available, but not used otherwise. If set to `require`, TLS will be forced to be used. Defaults to `prefer`.
+///     if available, but not used otherwise. If set to `require`, TLS will be forced to be used. If set to `verify-ca`
+///     or `verify-full`, TLS will be forced to be used and the server's certificate will be verified, along with the
+///     host it was issued for with `verify-full`. Otherwise, verification is left to the TLS connector. Defaults to
+///     `prefer`.
+/// * `sslrootcert` - The path to a file containing the PEM-encoded certificates of the certificate authorities trusted
+///     to issue the server's certificate, in place of the ones configured on the TLS connector. If set, the server's
+///     certificate is verified as with `verify-ca`, unless `sslmode` is `verify-full`.
+/// * `gssencmode` - Controls usage of GSSAPI transport encryption. If set to `disable`, GSSAPI encryption will not be
+///     used. If set to `prefer`, GSSAPI encryption will be used if available, but not used otherwise. If set to
+///     `require`, GSSAPI encryption will be forced to be used. A GSSAPI encrypted session takes precedence over TLS.
+///     GSSAPI encryption is only available if a context provider has been configured with
+///     [`Config::gss_context_provider`]; without one, `prefer` behaves like `disable`. Defaults to `prefer`.
+/// * `sslpassword` - The password used to decrypt the client's TLS private key. This is not used by this crate itself,
+///     but is made available to TLS connectors which load client keys.
+/// * `sslsni` - Controls the use of the TLS Server Name Indication extension. A value of 0 disables SNI and nonzero
+///     integers enable it. Defaults to 1.
+/// * `sslservername` - The server name used for the TLS handshake, both for SNI and to verify the server's
+///     certificate, in place of the host. This is useful when connecting through a TLS-terminating proxy whose address
+///     differs from the name of the server it presents.
 /// * `host` - The host to connect to. On Unix platforms, if the host starts with a `/` character it is treated as the
-///     path to the directory containing Unix domain sockets. Otherwise, it is treated as a hostname. Multiple hosts
-///     can be specified, separated by commas. Each host will be tried in turn when connecting. Required if connecting
-///     with the `connect` method.
+///     path to the directory containing Unix domain sockets, or to a socket file itself. On Linux, if the host starts
+///     with a `@` character it is treated as a directory in the abstract socket namespace. Otherwise, it is treated as
+///     a hostname. Multiple hosts can be specified, separated by commas. Each host will be tried in turn when
+///     connecting. Required if connecting with the `connect` method.
 /// * `hostaddr` - Numeric IP address of host to connect to. This should be in the standard IPv4 address format,
 ///     e.g., 172.28.40.9. If your machine supports IPv6, you can also use those addresses.
 ///     If this parameter is not specified, the value of `host` will be looked up to find the corresponding IP address,
@@ -122,6 +480,9 @@ pub enum Host {
 /// * `port` - The port to connect to. Multiple ports can be specified, separated by commas. The number of ports must be
 ///     either 1, in which case it will be used for all hosts, or the same as the number of hosts. Defaults to 5432 if
 ///     omitted or the empty string.
+/// * `requirepeer` - The operating system user the server must be running as when connecting with a Unix socket. The
+///     credentials of the server process are checked before the startup packet is sent, and the connection fails if
+///     they don't match. This is only supported on Linux, macOS and BSD systems, and is ignored for TCP connections.
 /// * `connect_timeout` - The time limit in seconds applied to each socket-level connection attempt. Note that hostnames
 ///     can resolve to multiple IP addresses, and this limit is applied to each address. Defaults to no timeout.
 /// * `tcp_user_timeout` - The time limit that transmitted data may remain unacknowledged before a connection is forcibly closed.
@@ -137,7 +498,10 @@ pub enum Host {
 ///     This option is ignored when connecting with Unix sockets.
 /// * `target_session_attrs` - Specifies requirements of the session. If set to `read-write`, the client will check that
 ///     the `transaction_read_write` session parameter is set to `on`. This can be used to connect to the primary server
-///     in a database cluster as opposed to the secondary read-only mirrors. Defaults to `all`.
+///     in a database cluster as opposed to the secondary read-only mirrors. If set to `read-only`, the parameter must
+///     instead be set to `off`. If set to `primary` or `standby`, the client will check whether the server is in hot
+///     standby mode with `pg_is_in_recovery()`. If set to `prefer-standby`, the hosts are first searched for a standby,
+///     and then tried again accepting any server. Defaults to `all`.
 /// * `channel_binding` - Controls usage of channel binding in the authentication process. If set to `disable`, channel
 ///     binding will not be used. If set to `prefer`, channel binding will be used if available, but not used otherwise.
 ///     If set to `require`, the authentication process will fail if channel binding is not used. Defaults to `prefer`.
@@ -147,6 +511,28 @@ pub enum Host {
 ///     `disable`, hosts and addresses will be tried in the order provided. If set to `random`, hosts will be tried
 ///     in a random order, and the IP addresses resolved from a hostname will also be tried in a random order. Defaults
 ///     to `disable`.
+/// * `replication` - Starts the session in replication mode. If set to `true`, the session is in physical replication
+///     mode, and only accepts replication commands. If set to `database`, the session is in logical replication mode
+///     on the database it is connected to, and also accepts SQL commands. Defaults to `false`.
+/// * `log_slow_statements` - Logs statements which take longer than this to execute, measured client-side. The value
+///     is an integer with an optional unit of `us`, `ms`, `s`, `min` or `h`, and is in milliseconds if no unit is
+///     given, as with the server's `log_min_duration_statement` parameter. A negative value disables logging, which
+///     is the default.
+/// * `max_backend_message_size` - The maximum size in bytes of the messages received from the server. Defaults to no
+///     limit.
+/// * `statement_stats` - Controls the collection of per-statement statistics. A value of 0 disables collection and
+///     nonzero integers enable it. Defaults to off.
+/// * `resolve_types` - Controls the resolution of types which aren't built into Postgres from the server's catalog. A
+///     value of 0 disables resolution and nonzero integers enable it. Defaults to on.
+/// * `statement_cache_capacity` - The number of statements kept prepared by `Client::query_cached`. Defaults to 100.
+/// * `query_timeout` - The time limit applied by the client to each query, in the same format as
+///     `log_slow_statements`. A negative value disables the limit, which is the default.
+/// * `keepalive_query_interval` - The interval of the round trips made by the connection while it is idle,
+///     in the same format as `log_slow_statements`. A negative value disables the checks, which is the default.
+/// * `max_inflight_requests` - The maximum number of requests in flight. A value of 0, the default, disables the limit.
+///
+/// Settings involving callbacks, such as [`Config::password_provider`] or [`Config::metrics`], can only be configured
+/// with the setters of `Config`.
 ///
 /// ## Examples
 ///
@@ -194,13 +580,25 @@ pub enum Host {
 pub struct Config {
     pub(crate) user: Option<String>,
     pub(crate) password: Option<Vec<u8>>,
+    pub(crate) password_provider: Option<PasswordProvider>,
     pub(crate) dbname: Option<String>,
     pub(crate) options: Option<String>,
+    pub(crate) startup_params: Vec<(String, String)>,
     pub(crate) application_name: Option<String>,
+    pub(crate) fallback_application_name: Option<String>,
     pub(crate) ssl_mode: SslMode,
+    pub(crate) ssl_password: Option<Vec<u8>>,
+    pub(crate) ssl_sni: bool,
+    pub(crate) ssl_server_name: Option<String>,
+    pub(crate) ssl_root_cert: Option<PathBuf>,
+    pub(crate) gss_enc_mode: GssEncMode,
+    pub(crate) gss_context_provider: Option<GssContextProvider>,
+    pub(crate) sql_commenter: Option<SqlCommenter>,
     pub(crate) host: Vec<Host>,
     pub(crate) hostaddr: Vec<IpAddr>,
     pub(crate) port: Vec<u16>,
+    pub(crate) host_credentials: BTreeMap<usize, HostCredentials>,
+    pub(crate) requirepeer: Option<String>,
     pub(crate) connect_timeout: Option<Duration>,
     pub(crate) tcp_user_timeout: Option<Duration>,
     pub(crate) keepalives: bool,
@@ -209,7 +607,21 @@ pub struct Config {
     pub(crate) target_session_attrs: TargetSessionAttrs,
     pub(crate) channel_binding: ChannelBinding,
     pub(crate) load_balance_hosts: LoadBalanceHosts,
+    pub(crate) replication_mode: Option<ReplicationMode>,
     pub(crate) max_backend_message_size: Option<usize>,
+    pub(crate) log_slow_statements: Option<Duration>,
+    pub(crate) slow_statement_callback: Option<SlowStatementCallback>,
+    pub(crate) metrics: Option<MetricsHandle>,
+    pub(crate) statement_stats: bool,
+    pub(crate) resolve_types: bool,
+    pub(crate) types: Vec<Type>,
+    pub(crate) statement_cache_capacity: usize,
+    pub(crate) query_timeout: Option<Duration>,
+    pub(crate) keepalive_query_interval: Option<Duration>,
+    pub(crate) max_inflight_requests: Option<usize>,
+    pub(crate) lifecycle_callback: Option<LifecycleCallback>,
+    pub(crate) after_connect: Vec<String>,
+    pub(crate) after_connect_callback: Option<AfterConnectCallback>,
 }
 
 impl Default for Config {
@@ -224,13 +636,28 @@ impl Config {
         Config {
             user: None,
             password: None,
+            password_provider: None,
             dbname: None,
             options: None,
+            startup_params: vec![],
             application_name: None,
+            #[cfg(feature = "default-application-name")]
+            fallback_application_name: default_application_name(),
+            #[cfg(not(feature = "default-application-name"))]
+            fallback_application_name: None,
             ssl_mode: SslMode::Prefer,
+            ssl_password: None,
+            ssl_sni: true,
+            ssl_server_name: None,
+            ssl_root_cert: None,
+            gss_enc_mode: GssEncMode::Prefer,
+            gss_context_provider: None,
+            sql_commenter: None,
             host: vec![],
             hostaddr: vec![],
             port: vec![],
+            host_credentials: BTreeMap::new(),
+            requirepeer: None,
             connect_timeout: None,
             tcp_user_timeout: None,
             keepalives: true,
@@ -243,7 +670,21 @@ impl Config {
             target_session_attrs: TargetSessionAttrs::Any,
             channel_binding: ChannelBinding::Prefer,
             load_balance_hosts: LoadBalanceHosts::Disable,
+            replication_mode: None,
             max_backend_message_size: None,
+            log_slow_statements: None,
+            slow_statement_callback: None,
+            metrics: None,
+            statement_stats: false,
+            resolve_types: true,
+            types: vec![],
+            statement_cache_capacity: 100,
+            query_timeout: None,
+            keepalive_query_interval: None,
+            max_inflight_requests: None,
+            lifecycle_callback: None,
+            after_connect: vec![],
+            after_connect_callback: None,
         }
     }
 
@@ -276,6 +717,37 @@ impl Config {
         self.password.as_deref()
     }
 
+    /// Sets an asynchronous callback which produces the password to authenticate with.
+    ///
+    /// The callback is invoked during authentication of every connection attempt, and only if the server actually
+    /// requests a password. This allows short-lived credentials such as AWS RDS IAM or GCP Cloud SQL IAM tokens to be
+    /// fetched on demand rather than being baked into the configuration. If set, it takes precedence over a password
+    /// configured with the `password` method.
+    pub fn password_provider<F, Fut, T, E>(&mut self, provider: F) -> &mut Config
+    where
+        F: Fn() -> Fut + Sync + Send + 'static,
+        Fut: Future<Output = Result<T, E>> + Send + 'static,
+        T: AsRef<[u8]>,
+        E: Into<Box<dyn error::Error + Sync + Send>>,
+    {
+        self.password_provider = Some(PasswordProvider(Arc::new(move || {
+            let future = provider();
+            Box::pin(async move {
+                future
+                    .await
+                    .map(|password| password.as_ref().to_vec())
+                    .map_err(Into::into)
+            })
+        })));
+        self
+    }
+
+    /// Reports whether a password callback has been configured with the
+    /// `password_provider` method.
+    pub fn has_password_provider(&self) -> bool {
+        self.password_provider.is_some()
+    }
+
     /// Sets the name of the database to connect to.
     ///
     /// Defaults to the user.
@@ -302,6 +774,24 @@ impl Config {
         self.options.as_deref()
     }
 
+    /// Appends a parameter to the startup message sent to the server, such as a runtime parameter of the session or a
+    /// routing hint understood by a connection pooler.
+    ///
+    /// For runtime parameters, this is equivalent to passing `-c key=value` in the `options`, without having to escape
+    /// the value. The keys set by the client itself (`user`, `database`, `options`, `application_name`,
+    /// `client_encoding` and `replication`) must be configured through their own methods instead, and connecting
+    /// will fail if they are used here.
+    pub fn startup_param(&mut self, key: &str, value: &str) -> &mut Config {
+        self.startup_params
+            .push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Gets the startup parameters set with the `startup_param` method, in the order they have been set.
+    pub fn get_startup_params(&self) -> &[(String, String)] {
+        &self.startup_params
+    }
+
     /// Sets the value of the `application_name` runtime parameter.
     pub fn application_name(&mut self, application_name: &str) -> &mut Config {
         self.application_name = Some(application_name.to_string());
@@ -314,6 +804,20 @@ impl Config {
         self.application_name.as_deref()
     }
 
+    /// Sets the value of the `application_name` runtime parameter to use if one is not set with the
+    /// `application_name` method.
+    ///
+    /// With the `default-application-name` feature enabled, defaults to the name of the running executable.
+    pub fn fallback_application_name(&mut self, fallback_application_name: &str) -> &mut Config {
+        self.fallback_application_name = Some(fallback_application_name.to_string());
+        self
+    }
+
+    /// Gets the fallback value of the `application_name` runtime parameter, if one has been set.
+    pub fn get_fallback_application_name(&self) -> Option<&str> {
+        self.fallback_application_name.as_deref()
+    }
+
     /// Sets the SSL configuration.
     ///
     /// Defaults to `prefer`.
@@ -327,11 +831,144 @@ impl Config {
         self.ssl_mode
     }
 
+    /// Sets the password used to decrypt the client's TLS private key.
+    ///
+    /// This is not used by this crate itself, but can be passed on to the TLS connector loading the key.
+    pub fn ssl_password<T>(&mut self, ssl_password: T) -> &mut Config
+    where
+        T: AsRef<[u8]>,
+    {
+        self.ssl_password = Some(ssl_password.as_ref().to_vec());
+        self
+    }
+
+    /// Gets the password used to decrypt the client's TLS private key, if one has been configured with the
+    /// `ssl_password` method.
+    pub fn get_ssl_password(&self) -> Option<&[u8]> {
+        self.ssl_password.as_deref()
+    }
+
+    /// Controls the use of the TLS Server Name Indication extension.
+    ///
+    /// TLS connectors which can't disable SNI ignore this setting. Defaults to `true`.
+    pub fn ssl_sni(&mut self, ssl_sni: bool) -> &mut Config {
+        self.ssl_sni = ssl_sni;
+        self
+    }
+
+    /// Reports whether the TLS Server Name Indication extension is used.
+    pub fn get_ssl_sni(&self) -> bool {
+        self.ssl_sni
+    }
+
+    /// Sets the server name used for the TLS handshake.
+    ///
+    /// The name is sent with SNI and used to verify the server's certificate, in place of the host being connected
+    /// to. This allows connecting through a TLS-terminating proxy, or to an IP address, while still verifying the
+    /// certificate of the server behind it.
+    pub fn ssl_server_name(&mut self, ssl_server_name: &str) -> &mut Config {
+        self.ssl_server_name = Some(ssl_server_name.to_string());
+        self
+    }
+
+    /// Gets the server name used for the TLS handshake, if one has been configured with the `ssl_server_name`
+    /// method.
+    pub fn get_ssl_server_name(&self) -> Option<&str> {
+        self.ssl_server_name.as_deref()
+    }
+
+    /// Sets the path to a file containing the certificates of the certificate authorities trusted to issue the
+    /// server's certificate.
+    ///
+    /// The certificates replace the ones configured on the TLS connector, and the server's certificate is verified
+    /// against them even if the SSL mode is `prefer` or `require`.
+    pub fn ssl_root_cert<T>(&mut self, ssl_root_cert: T) -> &mut Config
+    where
+        T: AsRef<Path>,
+    {
+        self.ssl_root_cert = Some(ssl_root_cert.as_ref().to_path_buf());
+        self
+    }
+
+    /// Gets the path to the trusted certificate authorities, if one has been configured with the `ssl_root_cert`
+    /// method.
+    pub fn get_ssl_root_cert(&self) -> Option<&Path> {
+        self.ssl_root_cert.as_deref()
+    }
+
+    /// Sets the GSSAPI encryption configuration.
+    ///
+    /// Defaults to `prefer`.
+    pub fn gss_enc_mode(&mut self, gss_enc_mode: GssEncMode) -> &mut Config {
+        self.gss_enc_mode = gss_enc_mode;
+        self
+    }
+
+    /// Gets the GSSAPI encryption configuration.
+    pub fn get_gss_enc_mode(&self) -> GssEncMode {
+        self.gss_enc_mode
+    }
+
+    /// Sets a callback creating the GSSAPI security contexts used to encrypt and authenticate connections.
+    ///
+    /// The callback is passed the hostname of the server being connected to, and is invoked for each connection
+    /// attempt before GSSAPI encryption is requested from the server. If it fails, for example because no credentials
+    /// are available, the connection proceeds unencrypted by GSSAPI unless `gssencmode` is `require`.
+    ///
+    /// It is invoked again if the server requests GSSAPI or SSPI authentication, which fails if no context can be
+    /// created.
+    pub fn gss_context_provider<F, C, E>(&mut self, provider: F) -> &mut Config
+    where
+        F: Fn(&str) -> Result<C, E> + Sync + Send + 'static,
+        C: GssContext + 'static,
+        E: Into<Box<dyn error::Error + Sync + Send>>,
+    {
+        self.gss_context_provider =
+            Some(GssContextProvider(Arc::new(move |host| {
+                match provider(host) {
+                    Ok(context) => Ok(Box::new(context) as Box<dyn GssContext>),
+                    Err(e) => Err(e.into()),
+                }
+            })));
+        self
+    }
+
+    /// Returns `true` if a GSSAPI context provider has been configured.
+    pub fn has_gss_context_provider(&self) -> bool {
+        self.gss_context_provider.is_some()
+    }
+
+    /// Sets a callback producing [sqlcommenter] tags which are appended as a comment to the SQL of outgoing queries.
+    ///
+    /// The callback is invoked whenever a statement is prepared or a simple query is sent, on the task performing the
+    /// operation. This allows the trace context of the current span to be propagated to the server, for example by
+    /// returning a `traceparent` tag, so that server-side logs and `pg_stat_statements` can be correlated with
+    /// distributed traces. Tags are sorted and URL-encoded, and queries which already contain a comment are left
+    /// unchanged.
+    ///
+    /// Note that the comment becomes part of the text of prepared statements, so it reflects the context in which a
+    /// statement was prepared rather than the one it is executed in.
+    ///
+    /// [sqlcommenter]: https://google.github.io/sqlcommenter/spec/
+    pub fn sqlcommenter<F>(&mut self, commenter: F) -> &mut Config
+    where
+        F: Fn() -> Vec<(String, String)> + Sync + Send + 'static,
+    {
+        self.sql_commenter = Some(SqlCommenter(Arc::new(commenter)));
+        self
+    }
+
+    /// Returns `true` if an sqlcommenter callback has been configured.
+    pub fn has_sqlcommenter(&self) -> bool {
+        self.sql_commenter.is_some()
+    }
+
     /// Adds a host to the configuration.
     ///
     /// Multiple hosts can be specified by calling this method multiple times, and each will be tried in order. On Unix
-    /// systems, a host starting with a `/` is interpreted as a path to a directory containing Unix domain sockets.
-    /// There must be either no hosts, or the same number of hosts as hostaddrs.
+    /// systems, a host starting with a `/` is interpreted as a path to a directory containing Unix domain sockets, or
+    /// to a socket file. On Linux, a host starting with a `@` is interpreted as a directory in the abstract socket
+    /// namespace. There must be either no hosts, or the same number of hosts as hostaddrs.
     pub fn host(&mut self, host: &str) -> &mut Config {
         #[cfg(unix)]
         {
@@ -339,6 +976,12 @@ impl Config {
                 return self.host_path(host);
             }
         }
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            if host.starts_with('@') {
+                return self.host_path(host);
+            }
+        }
 
         self.host.push(Host::Tcp(host.to_string()));
         self
@@ -356,7 +999,8 @@ impl Config {
 
     /// Adds a Unix socket host to the configuration.
     ///
-    /// Unlike `host`, this method allows non-UTF8 paths.
+    /// The path is either a directory containing the server's socket, whose file name is derived from the port, or the
+    /// socket file itself. Unlike `host`, this method allows non-UTF8 paths.
     #[cfg(unix)]
     pub fn host_path<T>(&mut self, host: T) -> &mut Config
     where
@@ -366,6 +1010,19 @@ impl Config {
         self
     }
 
+    /// Adds a Unix socket host to the configuration, for the directory containing the server's socket.
+    ///
+    /// Unlike with `host_path`, the path is always treated as a directory, and the socket file name is derived from
+    /// the port of the host, as `.s.PGSQL.<port>`.
+    #[cfg(unix)]
+    pub fn socket_dir<T>(&mut self, dir: T) -> &mut Config
+    where
+        T: AsRef<Path>,
+    {
+        // The trailing separator prevents the path from being opened as a socket file when connecting.
+        self.host_path(dir.as_ref().join(""))
+    }
+
     /// Adds a hostaddr to the configuration.
     ///
     /// Multiple hostaddrs can be specified by calling this method multiple times, and each will be tried in order.
@@ -390,6 +1047,57 @@ impl Config {
         &self.port
     }
 
+    /// Sets the operating system user the server must be running as when connecting with a Unix socket.
+    ///
+    /// The credentials of the server process are checked before the startup packet is sent, and the connection fails
+    /// if they don't match. This is only supported on Linux, macOS and BSD systems, and is ignored for TCP connections.
+    pub fn requirepeer(&mut self, requirepeer: &str) -> &mut Config {
+        self.requirepeer = Some(requirepeer.to_string());
+        self
+    }
+
+    /// Gets the user the server must be running as, if one has been set with the `requirepeer` method.
+    pub fn get_requirepeer(&self) -> Option<&str> {
+        self.requirepeer.as_deref()
+    }
+
+    /// Sets credentials which override the `user`, `password` and `dbname` of the configuration when connecting to
+    /// the host at `index`.
+    ///
+    /// Hosts are indexed in the order they were added with `host`, `host_path` or `hostaddr`. This allows, for
+    /// example, a disaster recovery site to be attempted with different credentials than the primary.
+    pub fn host_credentials(&mut self, index: usize, credentials: HostCredentials) -> &mut Config {
+        self.host_credentials.insert(index, credentials);
+        self
+    }
+
+    /// Gets the credentials overriding the configuration for the host at `index`, if they have been set with the
+    /// `host_credentials` method.
+    pub fn get_host_credentials(&self, index: usize) -> Option<&HostCredentials> {
+        self.host_credentials.get(&index)
+    }
+
+    #[cfg(feature = "runtime")]
+    pub(crate) fn for_host(&self, index: usize) -> Cow<'_, Config> {
+        let credentials = match self.host_credentials.get(&index) {
+            Some(credentials) => credentials,
+            None => return Cow::Borrowed(self),
+        };
+
+        let mut config = self.clone();
+        if let Some(user) = &credentials.user {
+            config.user = Some(user.clone());
+        }
+        if let Some(password) = &credentials.password {
+            config.password = Some(password.clone());
+            config.password_provider = None;
+        }
+        if let Some(dbname) = &credentials.dbname {
+            config.dbname = Some(dbname.clone());
+        }
+        Cow::Owned(config)
+    }
+
     /// Sets the timeout applied to socket-level connection attempts.
     ///
     /// Note that hostnames can resolve to multiple IP addresses, and this timeout will apply to each address of each
@@ -524,6 +1232,20 @@ impl Config {
         self.load_balance_hosts
     }
 
+    /// Sets the replication mode of the session.
+    ///
+    /// Sessions are in the normal mode by default. Replication is started with the `START_REPLICATION` command,
+    /// whose data is streamed with `Client::copy_both_simple`.
+    pub fn replication_mode(&mut self, replication_mode: ReplicationMode) -> &mut Config {
+        self.replication_mode = Some(replication_mode);
+        self
+    }
+
+    /// Gets the replication mode of the session, if one has been set with the `replication_mode` method.
+    pub fn get_replication_mode(&self) -> Option<ReplicationMode> {
+        self.replication_mode
+    }
+
     /// Set limit for backend messages size.
     pub fn max_backend_message_size(&mut self, max_backend_message_size: usize) -> &mut Config {
         self.max_backend_message_size = Some(max_backend_message_size);
@@ -535,6 +1257,247 @@ impl Config {
         self.max_backend_message_size
     }
 
+    /// Sets the threshold above which the execution of a statement is considered slow.
+    ///
+    /// Slow statements are reported to the callback configured with the `slow_statement_callback` method, or logged
+    /// at the warn level if there is none. The duration is measured client-side, from the moment a statement is sent
+    /// until the server reports its completion, so it includes network latency and, for queries returning a stream,
+    /// the time taken to consume the rows.
+    pub fn log_slow_statements(&mut self, threshold: Duration) -> &mut Config {
+        self.log_slow_statements = Some(threshold);
+        self
+    }
+
+    /// Gets the threshold above which the execution of a statement is considered slow, if one has been set with the
+    /// `log_slow_statements` method.
+    pub fn get_log_slow_statements(&self) -> Option<Duration> {
+        self.log_slow_statements
+    }
+
+    /// Sets a callback invoked with the statements exceeding the threshold set with the `log_slow_statements` method,
+    /// instead of logging them.
+    ///
+    /// The callback is invoked on the task consuming the results of the statement.
+    pub fn slow_statement_callback<F>(&mut self, callback: F) -> &mut Config
+    where
+        F: Fn(&SlowStatement<'_>) + Sync + Send + 'static,
+    {
+        self.slow_statement_callback = Some(SlowStatementCallback(Arc::new(callback)));
+        self
+    }
+
+    /// Returns `true` if a slow statement callback has been configured.
+    pub fn has_slow_statement_callback(&self) -> bool {
+        self.slow_statement_callback.is_some()
+    }
+
+    /// Sets the handler invoked with the metrics produced by connections and their clients.
+    ///
+    /// See the [`metrics`](crate::metrics) module for details.
+    pub fn metrics(&mut self, metrics: Arc<dyn Metrics>) -> &mut Config {
+        self.metrics = Some(MetricsHandle(metrics));
+        self
+    }
+
+    /// Gets the metrics handler, if one has been set with the `metrics` method.
+    pub fn get_metrics(&self) -> Option<&Arc<dyn Metrics>> {
+        self.metrics.as_ref().map(|metrics| &metrics.0)
+    }
+
+    /// Controls the collection of statement statistics by clients.
+    ///
+    /// See the [`stats`](crate::stats) module for details. Defaults to `false`.
+    pub fn statement_stats(&mut self, statement_stats: bool) -> &mut Config {
+        self.statement_stats = statement_stats;
+        self
+    }
+
+    /// Reports whether clients collect statement statistics.
+    pub fn get_statement_stats(&self) -> bool {
+        self.statement_stats
+    }
+
+    /// Controls the resolution of types which aren't built into Postgres when statements are prepared.
+    ///
+    /// The types of parameters and columns which are enums, composites, domains or other user-defined types are looked
+    /// up in the server's catalog the first time they're seen, and cached by the client. Disabling this avoids the
+    /// extra queries, for example with servers which don't allow reading the catalog, but such types are then
+    /// reported as `TEXT`, unless registered with `register_type`. Defaults to `true`.
+    pub fn resolve_types(&mut self, resolve_types: bool) -> &mut Config {
+        self.resolve_types = resolve_types;
+        self
+    }
+
+    /// Reports whether clients resolve types which aren't built into Postgres.
+    pub fn get_resolve_types(&self) -> bool {
+        self.resolve_types
+    }
+
+    /// Adds a type to those known to clients from the start, as if it had been resolved from the server's catalog.
+    ///
+    /// Parameters and columns of this type are reported with it without looking it up, which lets enums, composites
+    /// and other user-defined types be decoded when `resolve_types` is disabled. The OID of the type must match the
+    /// one assigned by the server, and the types it is made of are registered along with it. Can be called multiple
+    /// times to register multiple types.
+    pub fn register_type(&mut self, type_: Type) -> &mut Config {
+        self.types.push(type_);
+        self
+    }
+
+    /// Gets the types registered with the `register_type` method.
+    pub fn get_types(&self) -> &[Type] {
+        &self.types
+    }
+
+    /// Sets the number of statements kept prepared by `Client::query_cached`.
+    ///
+    /// The least recently used statement is closed once the cache is full. A capacity of 0 disables the cache, so
+    /// that statements are prepared again each time. Defaults to 100.
+    pub fn statement_cache_capacity(&mut self, statement_cache_capacity: usize) -> &mut Config {
+        self.statement_cache_capacity = statement_cache_capacity;
+        self
+    }
+
+    /// Gets the number of statements kept prepared by `Client::query_cached`.
+    pub fn get_statement_cache_capacity(&self) -> usize {
+        self.statement_cache_capacity
+    }
+
+    /// Sets the time limit applied by the client to queries.
+    ///
+    /// The limit applies to the methods of `Client` which wait for the complete results of a query, such as `query`,
+    /// `execute` or `batch_execute`, and can be overridden for a single query with `QueryOptions`. Once it expires,
+    /// a cancellation request is sent for the query and a timeout error is returned. Unlike the `statement_timeout`
+    /// server parameter, the limit isn't reset by connection poolers. Defaults to no limit.
+    ///
+    /// Like libpq, the cancellation request is sent without TLS. The limit requires the `runtime` Cargo feature
+    /// (enabled by default), and is ignored without it.
+    pub fn query_timeout(&mut self, query_timeout: Duration) -> &mut Config {
+        self.query_timeout = Some(query_timeout);
+        self
+    }
+
+    /// Gets the time limit applied by the client to queries, if one has been set with the `query_timeout` method.
+    pub fn get_query_timeout(&self) -> Option<&Duration> {
+        self.query_timeout.as_ref()
+    }
+
+    /// Sets the interval of the round trips made by the connection while it is idle.
+    ///
+    /// Once no request has been sent for this long, the connection sends a `Sync` message and waits for the server to
+    /// respond, which keeps the connection open through NAT gateways and firewalls dropping idle connections, and
+    /// finds out about dead connections before the next query is sent. `Client::is_healthy` reports whether the
+    /// server responded before the next round trip was due. Defaults to no round trips.
+    ///
+    /// The round trips require the `runtime` Cargo feature (enabled by default), and the connection to be polled
+    /// within a Tokio runtime with its time driver enabled. They are ignored without the feature.
+    pub fn keepalive_query_interval(&mut self, keepalive_query_interval: Duration) -> &mut Config {
+        self.keepalive_query_interval = Some(keepalive_query_interval);
+        self
+    }
+
+    /// Gets the interval of the round trips made by idle connections, if one has been set with the
+    /// `keepalive_query_interval` method.
+    pub fn get_keepalive_query_interval(&self) -> Option<&Duration> {
+        self.keepalive_query_interval.as_ref()
+    }
+
+    /// Sets the maximum number of requests the client sends to its connection before their responses complete.
+    ///
+    /// Once the limit is reached, queries and other requests wait for one of the requests in flight to complete before
+    /// they are encoded and sent, rather than piling up in the queue of the connection. Waiting requests are sent in
+    /// the order they started waiting in, so that concurrent callers get their turn fairly. `Client::pending_requests`
+    /// reports the number of requests in flight. A limit of 0 removes the limit, which is the default.
+    ///
+    /// A request holds its slot until the server has sent all of its response, so a caller holding as many unconsumed
+    /// row streams as the limit can't issue another request until one of them is consumed or dropped. Requests sent
+    /// while a value is dropped, like closing a portal, and raw requests sent with `Client::request_raw` never wait,
+    /// but count towards the limit.
+    pub fn max_inflight_requests(&mut self, max_inflight_requests: usize) -> &mut Config {
+        self.max_inflight_requests = Some(max_inflight_requests).filter(|limit| *limit > 0);
+        self
+    }
+
+    /// Gets the maximum number of requests in flight, if one has been set with the `max_inflight_requests` method.
+    pub fn get_max_inflight_requests(&self) -> Option<usize> {
+        self.max_inflight_requests
+    }
+
+    /// Sets a callback invoked as connections move through their lifecycle.
+    ///
+    /// The callback is invoked as each connection attempt starts, is encrypted, authenticated and becomes ready, and
+    /// once the connection terminates or fails. Connection attempts to each host and address are reported separately,
+    /// so a single call to `connect` may produce several sequences of events. Events are reported on the task
+    /// establishing or polling the connection, so the callback should not block.
+    pub fn lifecycle_callback<F>(&mut self, callback: F) -> &mut Config
+    where
+        F: Fn(&LifecycleEvent<'_>) + Sync + Send + 'static,
+    {
+        self.lifecycle_callback = Some(LifecycleCallback(Arc::new(callback)));
+        self
+    }
+
+    /// Returns `true` if a lifecycle callback has been configured.
+    pub fn has_lifecycle_callback(&self) -> bool {
+        self.lifecycle_callback.is_some()
+    }
+
+    /// Adds statements run on each connection once it is established, before it is handed to the caller.
+    ///
+    /// The statements are run with `Client::batch_execute`, so a string can contain several statements separated by
+    /// semicolons, such as `SET search_path = app` or `SET statement_timeout = '5s'`. Since they run as part of
+    /// `connect`, they also apply to the connections reopened by clients which reconnect automatically, and no query
+    /// of the caller can run before them. If they fail, the connection attempt fails with their error. Can be called
+    /// multiple times to add more statements, which are run in order.
+    pub fn after_connect(&mut self, statements: &str) -> &mut Config {
+        self.after_connect.push(statements.to_string());
+        self
+    }
+
+    /// Gets the statements added with the `after_connect` method.
+    pub fn get_after_connect(&self) -> &[String] {
+        &self.after_connect
+    }
+
+    /// Sets an asynchronous callback run on each connection once it is established, after the statements added with
+    /// `after_connect`.
+    ///
+    /// Like those statements, the callback runs before the connection is handed to the caller, and an error returned
+    /// by the callback fails the connection attempt.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_postgres::Config;
+    ///
+    /// let tenant = "acme".to_string();
+    /// let mut config = Config::new();
+    /// config.after_connect_callback(move |client| {
+    ///     let tenant = tenant.clone();
+    ///     Box::pin(async move {
+    ///         client
+    ///             .execute("SELECT set_config('app.tenant', $1, false)", &[&tenant])
+    ///             .await?;
+    ///         Ok(())
+    ///     })
+    /// });
+    /// ```
+    pub fn after_connect_callback<F>(&mut self, callback: F) -> &mut Config
+    where
+        F: for<'a> Fn(&'a Client) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>>
+            + Sync
+            + Send
+            + 'static,
+    {
+        self.after_connect_callback = Some(AfterConnectCallback(Arc::new(callback)));
+        self
+    }
+
+    /// Reports whether a callback has been configured with the `after_connect_callback` method.
+    pub fn has_after_connect_callback(&self) -> bool {
+        self.after_connect_callback.is_some()
+    }
+
     fn param(&mut self, key: &str, value: &str) -> Result<(), Error> {
         match key {
             "user" => {
@@ -547,20 +1510,50 @@ impl Config {
                 self.dbname(value);
             }
             "options" => {
-                self.options(value);
+                let options = self.startup_options(value)?;
+                if !options.is_empty() {
+                    self.options(&options);
+                }
             }
             "application_name" => {
                 self.application_name(value);
             }
+            "fallback_application_name" => {
+                self.fallback_application_name(value);
+            }
             "sslmode" => {
                 let mode = match value {
                     "disable" => SslMode::Disable,
                     "prefer" => SslMode::Prefer,
                     "require" => SslMode::Require,
-                    _ => return Err(Error::config_parse(Box::new(InvalidValue("sslmode")))),
+                    "verify-ca" => SslMode::VerifyCa,
+                    "verify-full" => SslMode::VerifyFull,
+                    _ => return Err(invalid_value("sslmode")),
                 };
                 self.ssl_mode(mode);
             }
+            "sslpassword" => {
+                self.ssl_password(value);
+            }
+            "sslsni" => {
+                let sni = value.parse::<u64>().map_err(|_| invalid_value("sslsni"))?;
+                self.ssl_sni(sni != 0);
+            }
+            "sslservername" => {
+                self.ssl_server_name(value);
+            }
+            "sslrootcert" => {
+                self.ssl_root_cert(value);
+            }
+            "gssencmode" => {
+                let mode = match value {
+                    "disable" => GssEncMode::Disable,
+                    "prefer" => GssEncMode::Prefer,
+                    "require" => GssEncMode::Require,
+                    _ => return Err(invalid_value("gssencmode")),
+                };
+                self.gss_enc_mode(mode);
+            }
             "host" => {
                 for host in value.split(',') {
                     self.host(host);
@@ -568,9 +1561,7 @@ impl Config {
             }
             "hostaddr" => {
                 for hostaddr in value.split(',') {
-                    let addr = hostaddr
-                        .parse()
-                        .map_err(|_| Error::config_parse(Box::new(InvalidValue("hostaddr"))))?;
+                    let addr = hostaddr.parse().map_err(|_| invalid_value("hostaddr"))?;
                     self.hostaddr(addr);
                 }
             }
@@ -579,16 +1570,18 @@ impl Config {
                     let port = if port.is_empty() {
                         5432
                     } else {
-                        port.parse()
-                            .map_err(|_| Error::config_parse(Box::new(InvalidValue("port"))))?
+                        port.parse().map_err(|_| invalid_value("port"))?
                     };
                     self.port(port);
                 }
             }
+            "requirepeer" => {
+                self.requirepeer(value);
+            }
             "connect_timeout" => {
                 let timeout = value
                     .parse::<i64>()
-                    .map_err(|_| Error::config_parse(Box::new(InvalidValue("connect_timeout"))))?;
+                    .map_err(|_| invalid_value("connect_timeout"))?;
                 if timeout > 0 {
                     self.connect_timeout(Duration::from_secs(timeout as u64));
                 }
@@ -596,7 +1589,7 @@ impl Config {
             "tcp_user_timeout" => {
                 let timeout = value
                     .parse::<i64>()
-                    .map_err(|_| Error::config_parse(Box::new(InvalidValue("tcp_user_timeout"))))?;
+                    .map_err(|_| invalid_value("tcp_user_timeout"))?;
                 if timeout > 0 {
                     self.tcp_user_timeout(Duration::from_secs(timeout as u64));
                 }
@@ -605,32 +1598,32 @@ impl Config {
             "keepalives" => {
                 let keepalives = value
                     .parse::<u64>()
-                    .map_err(|_| Error::config_parse(Box::new(InvalidValue("keepalives"))))?;
+                    .map_err(|_| invalid_value("keepalives"))?;
                 self.keepalives(keepalives != 0);
             }
             #[cfg(not(target_arch = "wasm32"))]
             "keepalives_idle" => {
                 let keepalives_idle = value
                     .parse::<i64>()
-                    .map_err(|_| Error::config_parse(Box::new(InvalidValue("keepalives_idle"))))?;
+                    .map_err(|_| invalid_value("keepalives_idle"))?;
                 if keepalives_idle > 0 {
                     self.keepalives_idle(Duration::from_secs(keepalives_idle as u64));
                 }
             }
             #[cfg(not(target_arch = "wasm32"))]
             "keepalives_interval" => {
-                let keepalives_interval = value.parse::<i64>().map_err(|_| {
-                    Error::config_parse(Box::new(InvalidValue("keepalives_interval")))
-                })?;
+                let keepalives_interval = value
+                    .parse::<i64>()
+                    .map_err(|_| invalid_value("keepalives_interval"))?;
                 if keepalives_interval > 0 {
                     self.keepalives_interval(Duration::from_secs(keepalives_interval as u64));
                 }
             }
             #[cfg(not(target_arch = "wasm32"))]
             "keepalives_retries" => {
-                let keepalives_retries = value.parse::<u32>().map_err(|_| {
-                    Error::config_parse(Box::new(InvalidValue("keepalives_retries")))
-                })?;
+                let keepalives_retries = value
+                    .parse::<u32>()
+                    .map_err(|_| invalid_value("keepalives_retries"))?;
                 self.keepalives_retries(keepalives_retries);
             }
             "target_session_attrs" => {
@@ -638,10 +1631,11 @@ impl Config {
                     "any" => TargetSessionAttrs::Any,
                     "read-write" => TargetSessionAttrs::ReadWrite,
                     "read-only" => TargetSessionAttrs::ReadOnly,
+                    "primary" => TargetSessionAttrs::Primary,
+                    "standby" => TargetSessionAttrs::Standby,
+                    "prefer-standby" => TargetSessionAttrs::PreferStandby,
                     _ => {
-                        return Err(Error::config_parse(Box::new(InvalidValue(
-                            "target_session_attrs",
-                        ))));
+                        return Err(invalid_value("target_session_attrs"));
                     }
                 };
                 self.target_session_attrs(target_session_attrs);
@@ -651,11 +1645,7 @@ impl Config {
                     "disable" => ChannelBinding::Disable,
                     "prefer" => ChannelBinding::Prefer,
                     "require" => ChannelBinding::Require,
-                    _ => {
-                        return Err(Error::config_parse(Box::new(InvalidValue(
-                            "channel_binding",
-                        ))))
-                    }
+                    _ => return Err(invalid_value("channel_binding")),
                 };
                 self.channel_binding(channel_binding);
             }
@@ -663,24 +1653,83 @@ impl Config {
                 let load_balance_hosts = match value {
                     "disable" => LoadBalanceHosts::Disable,
                     "random" => LoadBalanceHosts::Random,
-                    _ => {
-                        return Err(Error::config_parse(Box::new(InvalidValue(
-                            "load_balance_hosts",
-                        ))))
-                    }
+                    _ => return Err(invalid_value("load_balance_hosts")),
                 };
                 self.load_balance_hosts(load_balance_hosts);
             }
+            "replication" => {
+                let replication_mode = match value {
+                    "true" | "on" | "yes" | "1" => Some(ReplicationMode::Physical),
+                    "database" => Some(ReplicationMode::Logical),
+                    "false" | "off" | "no" | "0" => None,
+                    _ => return Err(invalid_value("replication")),
+                };
+                self.replication_mode = replication_mode;
+            }
             "max_backend_message_size" => {
-                let limit = value.parse::<usize>().map_err(|_| {
-                    Error::config_parse(Box::new(InvalidValue("max_backend_message_size")))
-                })?;
+                let limit = value
+                    .parse::<usize>()
+                    .map_err(|_| invalid_value("max_backend_message_size"))?;
                 if limit > 0 {
                     self.max_backend_message_size(limit);
                 }
             }
+            "log_slow_statements" => {
+                let threshold =
+                    parse_duration_ms(value).ok_or_else(|| invalid_value("log_slow_statements"))?;
+                match threshold {
+                    Some(threshold) => {
+                        self.log_slow_statements(threshold);
+                    }
+                    None => self.log_slow_statements = None,
+                }
+            }
+            "statement_stats" => {
+                let statement_stats = value
+                    .parse::<u64>()
+                    .map_err(|_| invalid_value("statement_stats"))?;
+                self.statement_stats(statement_stats != 0);
+            }
+            "resolve_types" => {
+                let resolve_types = value
+                    .parse::<u64>()
+                    .map_err(|_| invalid_value("resolve_types"))?;
+                self.resolve_types(resolve_types != 0);
+            }
+            "statement_cache_capacity" => {
+                let capacity = value
+                    .parse::<usize>()
+                    .map_err(|_| invalid_value("statement_cache_capacity"))?;
+                self.statement_cache_capacity(capacity);
+            }
+            "query_timeout" => {
+                let timeout =
+                    parse_duration_ms(value).ok_or_else(|| invalid_value("query_timeout"))?;
+                match timeout {
+                    Some(timeout) => {
+                        self.query_timeout(timeout);
+                    }
+                    None => self.query_timeout = None,
+                }
+            }
+            "keepalive_query_interval" => {
+                let interval = parse_duration_ms(value)
+                    .ok_or_else(|| invalid_value("keepalive_query_interval"))?;
+                match interval {
+                    Some(interval) => {
+                        self.keepalive_query_interval(interval);
+                    }
+                    None => self.keepalive_query_interval = None,
+                }
+            }
+            "max_inflight_requests" => {
+                let limit = value
+                    .parse::<usize>()
+                    .map_err(|_| invalid_value("max_inflight_requests"))?;
+                self.max_inflight_requests(limit);
+            }
             key => {
-                return Err(Error::config_parse(Box::new(UnknownOption(
+                return Err(Error::config_parse(Box::new(ParseError::UnknownOption(
                     key.to_string(),
                 ))));
             }
@@ -697,7 +1746,8 @@ impl Config {
     where
         T: MakeTlsConnect<Socket>,
     {
-        connect(tls, self).await
+        let connect = time_connect(self, connect(tls, self));
+        Span::connect(self).instrument(connect).await
     }
 
     /// Connects to a PostgreSQL database over an arbitrary stream.
@@ -712,10 +1762,145 @@ impl Config {
         S: AsyncRead + AsyncWrite + Unpin,
         T: TlsConnect<S>,
     {
-        connect_raw(stream, tls, true, self).await
+        let hostname = self.host.iter().find_map(|host| match host {
+            Host::Tcp(host) => Some(&**host),
+            #[cfg(unix)]
+            Host::Unix(_) => None,
+        });
+        time_connect(self, connect_raw(stream, tls, true, hostname, None, self)).await
+    }
+
+    #[doc(hidden)]
+    #[cfg(feature = "runtime")]
+    pub async fn __private_api_connect_with<S, T, F>(
+        &self,
+        tls: T,
+        connect_socket: F,
+    ) -> Result<(Client, Connection<S, T::Stream>), Error>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+        T: MakeTlsConnect<S>,
+        F: FnMut(&Host, u16) -> Result<(S, Option<IpAddr>), Error>,
+    {
+        let connect = time_connect(self, connect_with(tls, self, connect_socket));
+        Span::connect(self).instrument(connect).await
+    }
+
+    #[doc(hidden)]
+    #[cfg(feature = "runtime")]
+    pub fn __private_api_configure_tcp(&self, stream: &std::net::TcpStream) -> Result<(), Error> {
+        configure_tcp(
+            SockRef::from(stream),
+            self.tcp_user_timeout,
+            if self.keepalives {
+                Some(&self.keepalive_config)
+            } else {
+                None
+            },
+        )
     }
 }
 
+impl Config {
+    // Moves the runtime parameters set with `-c name=value` or `--name=value` out of command line options, returning
+    // the remaining options. As with the server, options are separated by whitespace, which can be escaped with a
+    // backslash.
+    fn startup_options(&mut self, value: &str) -> Result<String, Error> {
+        let mut args = vec![];
+        let mut arg = String::new();
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => arg.extend(chars.next()),
+                c if c.is_ascii_whitespace() => {
+                    if !arg.is_empty() {
+                        args.push(mem::take(&mut arg));
+                    }
+                }
+                c => arg.push(c),
+            }
+        }
+        if !arg.is_empty() {
+            args.push(arg);
+        }
+
+        let mut options = vec![];
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let parameter = if arg == "-c" {
+                args.next().ok_or_else(|| invalid_value("options"))?
+            } else if let Some(parameter) =
+                arg.strip_prefix("--").or_else(|| arg.strip_prefix("-c"))
+            {
+                parameter.to_string()
+            } else {
+                options.push(arg.replace('\\', "\\\\").replace(' ', "\\ "));
+                continue;
+            };
+
+            let (name, value) = parameter
+                .split_once('=')
+                .filter(|(name, _)| !name.is_empty())
+                .ok_or_else(|| invalid_value("options"))?;
+            // The server accepts dashes in place of underscores in the names of parameters.
+            match &*name.replace('-', "_") {
+                "application_name" => {
+                    self.application_name(value);
+                }
+                name if RESERVED_STARTUP_PARAMS.contains(&name) => {
+                    return Err(invalid_value("options"))
+                }
+                name => {
+                    self.startup_param(name, value);
+                }
+            }
+        }
+
+        Ok(options.join(" "))
+    }
+}
+
+// The startup parameters set by the client itself, which can't be overridden with `Config::startup_param`.
+pub(crate) const RESERVED_STARTUP_PARAMS: &[&str] = &[
+    "user",
+    "database",
+    "options",
+    "application_name",
+    "client_encoding",
+    "replication",
+];
+
+// Parses a duration in the format of the server's time parameters, returning `None` for negative values.
+fn parse_duration_ms(value: &str) -> Option<Option<Duration>> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '-')
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let amount = amount.parse::<i64>().ok()?;
+    if amount < 0 {
+        return Some(None);
+    }
+    let amount = amount as u64;
+
+    let duration = match unit.trim_start() {
+        "us" => Duration::from_micros(amount),
+        "" | "ms" => Duration::from_millis(amount),
+        "s" => Duration::from_secs(amount),
+        "min" => Duration::from_secs(amount.checked_mul(60)?),
+        "h" => Duration::from_secs(amount.checked_mul(60 * 60)?),
+        _ => return None,
+    };
+    Some(Some(duration))
+}
+
+#[cfg(feature = "default-application-name")]
+fn default_application_name() -> Option<String> {
+    let exe = std::env::current_exe().ok()?;
+    let name = exe.file_stem()?.to_str()?;
+    Some(name.to_string())
+}
+
 impl FromStr for Config {
     type Err = Error;
 
@@ -741,13 +1926,28 @@ impl fmt::Debug for Config {
         config_dbg = config_dbg
             .field("user", &self.user)
             .field("password", &self.password.as_ref().map(|_| Redaction {}))
+            .field("password_provider", &self.password_provider)
             .field("dbname", &self.dbname)
             .field("options", &self.options)
+            .field("startup_params", &self.startup_params)
             .field("application_name", &self.application_name)
+            .field("fallback_application_name", &self.fallback_application_name)
             .field("ssl_mode", &self.ssl_mode)
+            .field(
+                "ssl_password",
+                &self.ssl_password.as_ref().map(|_| Redaction {}),
+            )
+            .field("ssl_sni", &self.ssl_sni)
+            .field("ssl_server_name", &self.ssl_server_name)
+            .field("ssl_root_cert", &self.ssl_root_cert)
+            .field("gss_enc_mode", &self.gss_enc_mode)
+            .field("gss_context_provider", &self.gss_context_provider)
+            .field("sql_commenter", &self.sql_commenter)
             .field("host", &self.host)
             .field("hostaddr", &self.hostaddr)
             .field("port", &self.port)
+            .field("host_credentials", &self.host_credentials)
+            .field("requirepeer", &self.requirepeer)
             .field("connect_timeout", &self.connect_timeout)
             .field("tcp_user_timeout", &self.tcp_user_timeout)
             .field("keepalives", &self.keepalives);
@@ -763,31 +1963,60 @@ impl fmt::Debug for Config {
         config_dbg
             .field("target_session_attrs", &self.target_session_attrs)
             .field("channel_binding", &self.channel_binding)
+            .field("replication_mode", &self.replication_mode)
+            .field("log_slow_statements", &self.log_slow_statements)
+            .field("slow_statement_callback", &self.slow_statement_callback)
+            .field("metrics", &self.metrics)
+            .field("statement_stats", &self.statement_stats)
+            .field("resolve_types", &self.resolve_types)
+            .field("types", &self.types)
+            .field("statement_cache_capacity", &self.statement_cache_capacity)
+            .field("query_timeout", &self.query_timeout)
+            .field("keepalive_query_interval", &self.keepalive_query_interval)
+            .field("max_inflight_requests", &self.max_inflight_requests)
+            .field("lifecycle_callback", &self.lifecycle_callback)
+            .field("after_connect", &self.after_connect)
+            .field("after_connect_callback", &self.after_connect_callback)
             .finish()
     }
 }
 
-#[derive(Debug)]
-struct UnknownOption(String);
+/// An error parsing a connection string, naming the option at fault.
+///
+/// This is the source of the errors returned when parsing a `Config` from a string, other than those caused by the
+/// syntax of the string itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// The option is not recognized.
+    UnknownOption(String),
+    /// The value of the option is invalid.
+    InvalidValue(String),
+}
 
-impl fmt::Display for UnknownOption {
-    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(fmt, "unknown option `{}`", self.0)
+impl ParseError {
+    /// Returns the name of the option at fault.
+    pub fn key(&self) -> &str {
+        match self {
+            ParseError::UnknownOption(key) | ParseError::InvalidValue(key) => key,
+        }
     }
 }
 
-impl error::Error for UnknownOption {}
-
-#[derive(Debug)]
-struct InvalidValue(&'static str);
-
-impl fmt::Display for InvalidValue {
+impl fmt::Display for ParseError {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(fmt, "invalid value for option `{}`", self.0)
+        match self {
+            ParseError::UnknownOption(key) => write!(fmt, "unknown option `{}`", key),
+            ParseError::InvalidValue(key) => write!(fmt, "invalid value for option `{}`", key),
+        }
     }
 }
 
-impl error::Error for InvalidValue {}
+impl error::Error for ParseError {}
+
+fn invalid_value(key: &str) -> Error {
+    Error::config_parse(Box::new(ParseError::InvalidValue(key.to_string())))
+}
 
 struct Parser<'a> {
     s: &'a str,
@@ -1003,10 +2232,15 @@ impl<'a> UrlParser<'a> {
     }
 
     fn parse_credentials(&mut self) -> Result<(), Error> {
-        let creds = match self.take_until(&['@']) {
-            Some(creds) => creds,
+        // The credentials are part of the authority, so an `@` in the path or the query string, such as the one of an
+        // abstract socket host, doesn't delimit them.
+        let authority_len = self.s.find(&['/', '?'][..]).unwrap_or(self.s.len());
+        let pos = match self.s[..authority_len].find('@') {
+            Some(pos) => pos,
             None => return Ok(()),
         };
+        let (creds, tail) = self.s.split_at(pos);
+        self.s = tail;
         self.eat_byte();
 
         let mut it = creds.splitn(2, ':');
@@ -1035,7 +2269,7 @@ impl<'a> UrlParser<'a> {
             let (host, port) = if chunk.starts_with('[') {
                 let idx = match chunk.find(']') {
                     Some(idx) => idx,
-                    None => return Err(Error::config_parse(InvalidValue("host").into())),
+                    None => return Err(invalid_value("host")),
                 };
 
                 let host = &chunk[1..idx];
@@ -1045,7 +2279,7 @@ impl<'a> UrlParser<'a> {
                 } else if remaining.is_empty() {
                     None
                 } else {
-                    return Err(Error::config_parse(InvalidValue("host").into()));
+                    return Err(invalid_value("host"));
                 };
 
                 (host, port)
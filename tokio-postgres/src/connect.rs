@@ -6,10 +6,19 @@ use crate::tls::MakeTlsConnect;
 use crate::{Client, Config, Connection, Error, SimpleQueryMessage, Socket};
 use futures_util::{future, pin_mut, Future, FutureExt, Stream};
 use rand::seq::SliceRandom;
+use std::net::IpAddr;
 use std::task::Poll;
 use std::{cmp, io};
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net;
 
+struct Target {
+    index: usize,
+    addr: Host,
+    hostname: Option<String>,
+    port: u16,
+}
+
 pub async fn connect<T>(
     mut tls: T,
     config: &Config,
@@ -17,6 +26,85 @@ pub async fn connect<T>(
 where
     T: MakeTlsConnect<Socket>,
 {
+    let targets = targets(config)?;
+    let mut error = None;
+    for target_session_attrs in session_attrs_passes(config.target_session_attrs) {
+        for target in &targets {
+            let config = config.for_host(target.index);
+            let result = connect_host(
+                target.addr.clone(),
+                target.hostname.clone(),
+                target.port,
+                target_session_attrs,
+                &mut tls,
+                &config,
+            )
+            .await;
+            match result {
+                Ok((client, connection)) => return Ok((client, connection)),
+                Err(e) => error = Some(e),
+            }
+        }
+    }
+
+    Err(error.unwrap())
+}
+
+/// Connects over sockets opened by a caller-provided function rather than by tokio.
+///
+/// The function is given the host or address to connect to along with the port, and returns the socket along with
+/// the IP address it is connected to for TCP sockets.
+pub async fn connect_with<S, T, F>(
+    mut tls: T,
+    config: &Config,
+    mut connect_socket: F,
+) -> Result<(Client, Connection<S, T::Stream>), Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    T: MakeTlsConnect<S>,
+    F: FnMut(&Host, u16) -> Result<(S, Option<IpAddr>), Error>,
+{
+    let targets = targets(config)?;
+    let mut error = None;
+    for target_session_attrs in session_attrs_passes(config.target_session_attrs) {
+        for target in &targets {
+            let config = config.for_host(target.index);
+            let result = async {
+                let (socket, ip) = connect_socket(&target.addr, target.port)?;
+                let addr = match (ip, &target.addr) {
+                    (Some(ip), _) => Addr::Tcp(ip),
+                    #[cfg(unix)]
+                    (None, Host::Unix(path)) => Addr::Unix(path.clone()),
+                    (None, Host::Tcp(_)) => {
+                        return Err(Error::connect(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            "missing address of TCP socket",
+                        )))
+                    }
+                };
+                let hostname = target.hostname.as_deref();
+                connect_stream(
+                    socket,
+                    addr,
+                    hostname,
+                    target.port,
+                    target_session_attrs,
+                    &mut tls,
+                    &config,
+                )
+                .await
+            };
+            match result.await {
+                Ok((client, connection)) => return Ok((client, connection)),
+                Err(e) => error = Some(e),
+            }
+        }
+    }
+
+    Err(error.unwrap())
+}
+
+fn targets(config: &Config) -> Result<Vec<Target>, Error> {
     if config.host.is_empty() && config.hostaddr.is_empty() {
         return Err(Error::config("both host and hostaddr are missing".into()));
     }
@@ -42,12 +130,18 @@ where
         return Err(Error::config("invalid number of ports".into()));
     }
 
+    if config.host_credentials.keys().any(|&i| i >= num_hosts) {
+        return Err(Error::config(
+            "host credentials set for a nonexistent host".into(),
+        ));
+    }
+
     let mut indices = (0..num_hosts).collect::<Vec<_>>();
     if config.load_balance_hosts == LoadBalanceHosts::Random {
         indices.shuffle(&mut rand::thread_rng());
     }
 
-    let mut error = None;
+    let mut targets = Vec::with_capacity(num_hosts);
     for i in indices {
         let host = config.host.get(i);
         let hostaddr = config.hostaddr.get(i);
@@ -74,19 +168,22 @@ where
             None => host.cloned().unwrap(),
         };
 
-        match connect_host(addr, hostname, port, &mut tls, config).await {
-            Ok((client, connection)) => return Ok((client, connection)),
-            Err(e) => error = Some(e),
-        }
+        targets.push(Target {
+            index: i,
+            addr,
+            hostname,
+            port,
+        });
     }
 
-    Err(error.unwrap())
+    Ok(targets)
 }
 
 async fn connect_host<T>(
     host: Host,
     hostname: Option<String>,
     port: u16,
+    target_session_attrs: TargetSessionAttrs,
     tls: &mut T,
     config: &Config,
 ) -> Result<(Client, Connection<Socket, T::Stream>), Error>
@@ -106,9 +203,16 @@ where
 
             let mut last_err = None;
             for addr in addrs {
-                match connect_once(Addr::Tcp(addr.ip()), hostname.as_deref(), port, tls, config)
-                    .await
-                {
+                let result = connect_once(
+                    Addr::Tcp(addr.ip()),
+                    hostname.as_deref(),
+                    port,
+                    target_session_attrs,
+                    tls,
+                    config,
+                )
+                .await;
+                match result {
                     Ok(stream) => return Ok(stream),
                     Err(e) => {
                         last_err = Some(e);
@@ -126,7 +230,15 @@ where
         }
         #[cfg(unix)]
         Host::Unix(path) => {
-            connect_once(Addr::Unix(path), hostname.as_deref(), port, tls, config).await
+            connect_once(
+                Addr::Unix(path),
+                hostname.as_deref(),
+                port,
+                target_session_attrs,
+                tls,
+                config,
+            )
+            .await
         }
     }
 }
@@ -135,6 +247,7 @@ async fn connect_once<T>(
     addr: Addr,
     hostname: Option<&str>,
     port: u16,
+    target_session_attrs: TargetSessionAttrs,
     tls: &mut T,
     config: &Config,
 ) -> Result<(Client, Connection<Socket, T::Stream>), Error>
@@ -151,68 +264,51 @@ where
         } else {
             None
         },
+        config.requirepeer.as_deref(),
     )
     .await?;
 
+    connect_stream(
+        socket,
+        addr,
+        hostname,
+        port,
+        target_session_attrs,
+        tls,
+        config,
+    )
+    .await
+}
+
+async fn connect_stream<S, T>(
+    socket: S,
+    addr: Addr,
+    hostname: Option<&str>,
+    port: u16,
+    target_session_attrs: TargetSessionAttrs,
+    tls: &mut T,
+    config: &Config,
+) -> Result<(Client, Connection<S, T::Stream>), Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    T: MakeTlsConnect<S>,
+{
+    // The server name overrides the host for the TLS handshake only.
+    let server_name = config.ssl_server_name.as_deref().or(hostname);
     let tls = tls
-        .make_tls_connect(hostname.unwrap_or(""))
+        .make_tls_connect(server_name.unwrap_or(""))
         .map_err(|e| Error::tls(e.into()))?;
-    let has_hostname = hostname.is_some();
-    let (mut client, mut connection) = connect_raw(socket, tls, has_hostname, config).await?;
-
-    if config.target_session_attrs != TargetSessionAttrs::Any {
-        let rows = client.simple_query_raw("SHOW transaction_read_only");
-        pin_mut!(rows);
+    let has_hostname = server_name.is_some();
+    let (mut client, mut connection) =
+        connect_raw(socket, tls, has_hostname, hostname, Some(port), config).await?;
 
-        let rows = future::poll_fn(|cx| {
-            if connection.poll_unpin(cx)?.is_ready() {
-                return Poll::Ready(Err(Error::closed()));
-            }
-
-            rows.as_mut().poll(cx)
-        })
-        .await?;
-        pin_mut!(rows);
-
-        loop {
-            let next = future::poll_fn(|cx| {
-                if connection.poll_unpin(cx)?.is_ready() {
-                    return Poll::Ready(Some(Err(Error::closed())));
-                }
-
-                rows.as_mut().poll_next(cx)
-            });
-
-            match next.await.transpose()? {
-                Some(SimpleQueryMessage::Row(row)) => {
-                    let read_only_result = row.try_get(0)?;
-                    if read_only_result == Some("on")
-                        && config.target_session_attrs == TargetSessionAttrs::ReadWrite
-                    {
-                        return Err(Error::connect(io::Error::new(
-                            io::ErrorKind::PermissionDenied,
-                            "database does not allow writes",
-                        )));
-                    } else if read_only_result == Some("off")
-                        && config.target_session_attrs == TargetSessionAttrs::ReadOnly
-                    {
-                        return Err(Error::connect(io::Error::new(
-                            io::ErrorKind::PermissionDenied,
-                            "database is not read only",
-                        )));
-                    } else {
-                        break;
-                    }
-                }
-                Some(_) => {}
-                None => return Err(Error::closed()),
-            }
-        }
-    }
+    check_session_attrs(&client, &mut connection, target_session_attrs).await?;
+    after_connect(&client, &mut connection, config).await?;
 
     client.set_socket_config(SocketConfig {
         addr,
         hostname: hostname.map(|s| s.to_string()),
+        ssl_server_name: config.ssl_server_name.clone(),
         port,
         connect_timeout: config.connect_timeout,
         tcp_user_timeout: config.tcp_user_timeout,
@@ -225,3 +321,115 @@ where
 
     Ok((client, connection))
 }
+
+async fn check_session_attrs<S, T>(
+    client: &Client,
+    connection: &mut Connection<S, T>,
+    target_session_attrs: TargetSessionAttrs,
+) -> Result<(), Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    let (query, rejected, message) = match target_session_attrs {
+        TargetSessionAttrs::Any => return Ok(()),
+        TargetSessionAttrs::ReadWrite => (
+            "SHOW transaction_read_only",
+            "on",
+            "database does not allow writes",
+        ),
+        TargetSessionAttrs::ReadOnly => (
+            "SHOW transaction_read_only",
+            "off",
+            "database is not read only",
+        ),
+        TargetSessionAttrs::Primary => (
+            "SELECT pg_is_in_recovery()",
+            "t",
+            "database is in hot standby mode",
+        ),
+        TargetSessionAttrs::Standby | TargetSessionAttrs::PreferStandby => (
+            "SELECT pg_is_in_recovery()",
+            "f",
+            "database is not in hot standby mode",
+        ),
+    };
+
+    let rows = drive(connection, client.simple_query_raw(query)).await?;
+    pin_mut!(rows);
+
+    loop {
+        let next = future::poll_fn(|cx| {
+            if connection.poll_unpin(cx)?.is_ready() {
+                return Poll::Ready(Some(Err(Error::closed())));
+            }
+
+            rows.as_mut().poll_next(cx)
+        });
+
+        match next.await.transpose()? {
+            Some(SimpleQueryMessage::Row(row)) => {
+                if row.try_get(0)? == Some(rejected) {
+                    return Err(Error::connect(io::Error::new(
+                        io::ErrorKind::PermissionDenied,
+                        message,
+                    )));
+                }
+                return Ok(());
+            }
+            Some(_) => {}
+            None => return Err(Error::closed()),
+        }
+    }
+}
+
+/// Runs the statements and the callback configured with `Config::after_connect` and
+/// `Config::after_connect_callback`.
+async fn after_connect<S, T>(
+    client: &Client,
+    connection: &mut Connection<S, T>,
+    config: &Config,
+) -> Result<(), Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    for statements in &config.after_connect {
+        drive(connection, client.batch_execute(statements)).await?;
+    }
+    if let Some(callback) = &config.after_connect_callback {
+        drive(connection, callback.call(client)).await?;
+    }
+
+    Ok(())
+}
+
+/// Polls a future using the client along with its connection, which isn't yet polled by the caller.
+async fn drive<S, T, F, R>(connection: &mut Connection<S, T>, future: F) -> Result<R, Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    T: AsyncRead + AsyncWrite + Unpin,
+    F: Future<Output = Result<R, Error>>,
+{
+    pin_mut!(future);
+    future::poll_fn(|cx| {
+        if connection.poll_unpin(cx)?.is_ready() {
+            return Poll::Ready(Err(Error::closed()));
+        }
+
+        future.as_mut().poll(cx)
+    })
+    .await
+}
+
+/// Returns the session requirements of each pass over the hosts.
+///
+/// With `PreferStandby`, the hosts are first searched for a standby, and any of them is accepted if none is found.
+fn session_attrs_passes(target_session_attrs: TargetSessionAttrs) -> Vec<TargetSessionAttrs> {
+    match target_session_attrs {
+        TargetSessionAttrs::PreferStandby => {
+            vec![TargetSessionAttrs::Standby, TargetSessionAttrs::Any]
+        }
+        target_session_attrs => vec![target_session_attrs],
+    }
+}
@@ -0,0 +1,254 @@
+use crate::config::{GssContextProvider, GssEncMode};
+use crate::gss::{GssContext, GssStep};
+use crate::Error;
+use bytes::{Buf, BufMut, BytesMut};
+use futures_util::ready;
+use postgres_protocol::message::frontend;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf};
+
+// The server rejects packets larger than this, including the length prefix.
+const MAX_PACKET_SIZE: usize = 16 * 1024;
+// Leaves ample room for the overhead added by wrapping.
+const MAX_PLAINTEXT_SIZE: usize = 8 * 1024;
+
+pub enum GssOutcome<S> {
+    Encrypted(GssStream<S>),
+    Raw(S),
+}
+
+pub async fn connect_gss<S>(
+    mut stream: S,
+    mode: GssEncMode,
+    provider: Option<&GssContextProvider>,
+    hostname: Option<&str>,
+) -> Result<GssOutcome<S>, Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (provider, hostname) = match (mode, provider, hostname) {
+        (GssEncMode::Disable, _, _) => return Ok(GssOutcome::Raw(stream)),
+        (_, Some(provider), Some(hostname)) => (provider, hostname),
+        (GssEncMode::Prefer, _, _) => return Ok(GssOutcome::Raw(stream)),
+        (_, None, _) => {
+            return Err(Error::gss("no GSSAPI context provider configured".into()));
+        }
+        (_, _, None) => {
+            return Err(Error::gss(
+                "no hostname provided for GSSAPI encryption".into(),
+            ));
+        }
+    };
+
+    // As with libpq checking for a credential cache, failing to create a context means GSSAPI encryption isn't
+    // available to this client, which is only an error if it is required.
+    let mut context = match provider.context(hostname) {
+        Ok(context) => context,
+        Err(_) if mode == GssEncMode::Prefer => return Ok(GssOutcome::Raw(stream)),
+        Err(e) => return Err(Error::gss(e)),
+    };
+
+    let mut buf = BytesMut::new();
+    frontend::gssenc_request(&mut buf);
+    stream.write_all(&buf).await.map_err(Error::io)?;
+
+    let mut buf = [0];
+    stream.read_exact(&mut buf).await.map_err(Error::io)?;
+
+    if buf[0] != b'G' {
+        if GssEncMode::Require == mode {
+            return Err(Error::gss(
+                "server does not support GSSAPI encryption".into(),
+            ));
+        } else {
+            return Ok(GssOutcome::Raw(stream));
+        }
+    }
+
+    let mut token = None;
+    loop {
+        let step = context.step(token.as_deref()).map_err(Error::gss)?;
+        let (output, complete) = match step {
+            GssStep::Continue(output) => (Some(output), false),
+            GssStep::Complete(output) => (output, true),
+        };
+
+        if let Some(output) = output {
+            write_packet(&mut stream, &output).await?;
+        }
+
+        if complete {
+            break;
+        }
+
+        token = Some(read_packet(&mut stream).await?);
+    }
+
+    Ok(GssOutcome::Encrypted(GssStream::new(stream, context)))
+}
+
+async fn write_packet<S>(stream: &mut S, token: &[u8]) -> Result<(), Error>
+where
+    S: AsyncWrite + Unpin,
+{
+    if token.len() + 4 > MAX_PACKET_SIZE {
+        return Err(Error::gss("GSSAPI token too large".into()));
+    }
+
+    let mut buf = BytesMut::with_capacity(token.len() + 4);
+    buf.put_u32(token.len() as u32);
+    buf.put_slice(token);
+    stream.write_all(&buf).await.map_err(Error::io)
+}
+
+async fn read_packet<S>(stream: &mut S) -> Result<Vec<u8>, Error>
+where
+    S: AsyncRead + Unpin,
+{
+    let mut len = [0; 4];
+    stream.read_exact(&mut len).await.map_err(Error::io)?;
+
+    // Errors during the handshake are reported with a regular ErrorResponse message, which can be told apart from a
+    // length prefix by its tag.
+    if len[0] == b'E' {
+        return Err(Error::gss(
+            "server reported an error during the GSSAPI handshake".into(),
+        ));
+    }
+
+    let len = u32::from_be_bytes(len) as usize;
+    if len + 4 > MAX_PACKET_SIZE {
+        return Err(Error::gss("GSSAPI token too large".into()));
+    }
+
+    let mut token = vec![0; len];
+    stream.read_exact(&mut token).await.map_err(Error::io)?;
+    Ok(token)
+}
+
+/// A stream encrypted with a GSSAPI security context.
+pub struct GssStream<S> {
+    stream: S,
+    context: Box<dyn GssContext>,
+    read_buf: BytesMut,
+    plaintext: BytesMut,
+    write_buf: BytesMut,
+}
+
+impl<S> GssStream<S> {
+    fn new(stream: S, context: Box<dyn GssContext>) -> GssStream<S> {
+        GssStream {
+            stream,
+            context,
+            read_buf: BytesMut::new(),
+            plaintext: BytesMut::new(),
+            write_buf: BytesMut::new(),
+        }
+    }
+}
+
+impl<S> GssStream<S>
+where
+    S: AsyncWrite + Unpin,
+{
+    fn poll_drain(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while !self.write_buf.is_empty() {
+            let n = ready!(Pin::new(&mut self.stream).poll_write(cx, &self.write_buf))?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.write_buf.advance(n);
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S> AsyncRead for GssStream<S>
+where
+    S: AsyncRead + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = &mut *self;
+
+        loop {
+            if !this.plaintext.is_empty() {
+                let len = usize::min(this.plaintext.len(), buf.remaining());
+                buf.put_slice(&this.plaintext.split_to(len));
+                return Poll::Ready(Ok(()));
+            }
+
+            if this.read_buf.len() >= 4 {
+                let len = (&this.read_buf[..4]).get_u32() as usize;
+                if len + 4 > MAX_PACKET_SIZE {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "GSSAPI packet too large",
+                    )));
+                }
+
+                if this.read_buf.len() >= len + 4 {
+                    this.read_buf.advance(4);
+                    let packet = this.read_buf.split_to(len);
+                    let data = this.context.unwrap(&packet).map_err(io::Error::other)?;
+                    this.plaintext.extend_from_slice(&data);
+                    continue;
+                }
+            }
+
+            let mut chunk = [0; MAX_PACKET_SIZE];
+            let mut chunk = ReadBuf::new(&mut chunk);
+            ready!(Pin::new(&mut this.stream).poll_read(cx, &mut chunk))?;
+            if chunk.filled().is_empty() {
+                if this.read_buf.is_empty() {
+                    return Poll::Ready(Ok(()));
+                }
+                return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into()));
+            }
+            this.read_buf.extend_from_slice(chunk.filled());
+        }
+    }
+}
+
+impl<S> AsyncWrite for GssStream<S>
+where
+    S: AsyncWrite + Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = &mut *self;
+        ready!(this.poll_drain(cx))?;
+
+        let len = usize::min(buf.len(), MAX_PLAINTEXT_SIZE);
+        let packet = this.context.wrap(&buf[..len]).map_err(io::Error::other)?;
+        if packet.len() + 4 > MAX_PACKET_SIZE {
+            return Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "GSSAPI packet too large",
+            )));
+        }
+        this.write_buf.put_u32(packet.len() as u32);
+        this.write_buf.put_slice(&packet);
+
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.poll_drain(cx))?;
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.poll_drain(cx))?;
+        Pin::new(&mut self.stream).poll_shutdown(cx)
+    }
+}
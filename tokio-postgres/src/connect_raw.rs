@@ -1,7 +1,14 @@
+use crate::client::Session;
 use crate::codec::{BackendMessage, BackendMessages, FrontendMessage, PostgresCodec};
-use crate::config::{self, Config};
-use crate::connect_tls::connect_tls;
+use crate::config::{self, Config, LifecycleEvent, ReplicationMode};
+use crate::connect_gss::{connect_gss, GssOutcome};
+use crate::connect_tls::{connect_tls, TlsSettings};
+use crate::framed::Framed;
+use crate::gss::GssStep;
+use crate::instrument::Instrumentation;
 use crate::maybe_tls_stream::MaybeTlsStream;
+use crate::metrics::WireStats;
+use crate::security::{AuthenticationMethod, ConnectionSecurity, Encryption};
 use crate::tls::{TlsConnect, TlsStream};
 use crate::{Client, Connection, Error};
 use bytes::BytesMut;
@@ -17,12 +24,12 @@ use std::borrow::Cow;
 use std::collections::{HashMap, VecDeque};
 use std::io;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 use tokio::io::{AsyncRead, AsyncWrite};
-use tokio_util::codec::Framed;
 
 pub struct StartupStream<S, T> {
-    inner: Framed<MaybeTlsStream<S, T>, PostgresCodec>,
+    inner: Framed<MaybeTlsStream<S, T>>,
     buf: BackendMessages,
     delayed: VecDeque<BackendMessage>,
 }
@@ -83,19 +90,72 @@ pub async fn connect_raw<S, T>(
     stream: S,
     tls: T,
     has_hostname: bool,
+    hostname: Option<&str>,
+    port: Option<u16>,
     config: &Config,
 ) -> Result<(Client, Connection<S, T::Stream>), Error>
 where
     S: AsyncRead + AsyncWrite + Unpin,
     T: TlsConnect<S>,
 {
-    let stream = connect_tls(stream, config.ssl_mode, tls, has_hostname).await?;
+    let lifecycle = config.lifecycle_callback.as_ref();
+    if let Some(lifecycle) = lifecycle {
+        lifecycle.call(&LifecycleEvent::Connecting {
+            host: hostname,
+            port,
+        });
+    }
+
+    let result = handshake(stream, tls, has_hostname, hostname, port, config).await;
+    if let (Some(lifecycle), Err(e)) = (lifecycle, &result) {
+        lifecycle.call(&LifecycleEvent::Closed(Some(e)));
+    }
+    result
+}
+
+async fn handshake<S, T>(
+    stream: S,
+    tls: T,
+    has_hostname: bool,
+    hostname: Option<&str>,
+    port: Option<u16>,
+    config: &Config,
+) -> Result<(Client, Connection<S, T::Stream>), Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    T: TlsConnect<S>,
+{
+    let lifecycle = config.lifecycle_callback.as_ref();
+
+    let gss = connect_gss(
+        stream,
+        config.gss_enc_mode,
+        config.gss_context_provider.as_ref(),
+        hostname,
+    )
+    .await?;
+    let stream = match gss {
+        GssOutcome::Encrypted(stream) => MaybeTlsStream::Gss(stream),
+        GssOutcome::Raw(stream) => {
+            connect_tls(stream, &TlsSettings::new(config), tls, has_hostname).await?
+        }
+    };
+    let encryption = match stream {
+        MaybeTlsStream::Raw(_) => Encryption::None,
+        MaybeTlsStream::Tls(_) => Encryption::Tls,
+        MaybeTlsStream::Gss(_) => Encryption::Gss,
+    };
+    if let (Some(lifecycle), Encryption::Tls | Encryption::Gss) = (lifecycle, encryption) {
+        lifecycle.call(&LifecycleEvent::Encrypted(encryption));
+    }
 
     let mut stream = StartupStream {
         inner: Framed::new(
             stream,
             PostgresCodec {
                 max_message_size: config.max_backend_message_size,
+                metrics: config.metrics.clone(),
+                stats: WireStats::default(),
             },
         ),
         buf: BackendMessages::empty(),
@@ -108,13 +168,41 @@ where
         .map_or_else(|| Cow::Owned(whoami::username()), Cow::Borrowed);
 
     startup(&mut stream, config, &user).await?;
-    authenticate(&mut stream, config, &user).await?;
+    let authentication_method = authenticate(&mut stream, config, &user, hostname).await?;
+    if let Some(lifecycle) = lifecycle {
+        lifecycle.call(&LifecycleEvent::Authenticated(authentication_method));
+    }
     let (process_id, secret_key, parameters) = read_info(&mut stream).await?;
 
     let (sender, receiver) = mpsc::unbounded();
 
-    let client = Client::new(sender, config.ssl_mode, process_id, secret_key);
-    let connection = Connection::new(stream.inner, stream.delayed, parameters, receiver);
+    let security = ConnectionSecurity {
+        authentication_method,
+        encryption,
+    };
+    let instrumentation = Instrumentation::new(config, &user, hostname, port, process_id);
+    let session = Arc::new(Session::new(parameters.clone()));
+    let client = Client::new(
+        sender,
+        config,
+        process_id,
+        secret_key,
+        security,
+        instrumentation,
+        session.clone(),
+    );
+    let connection = Connection::new(
+        stream.inner,
+        stream.delayed,
+        parameters,
+        session,
+        receiver,
+        config.lifecycle_callback.clone(),
+        config.keepalive_query_interval,
+    );
+    if let Some(lifecycle) = lifecycle {
+        lifecycle.call(&LifecycleEvent::Ready);
+    }
 
     Ok((client, connection))
 }
@@ -136,9 +224,27 @@ where
     if let Some(options) = &config.options {
         params.push(("options", &**options));
     }
-    if let Some(application_name) = &config.application_name {
+    if let Some(application_name) = config
+        .application_name
+        .as_ref()
+        .or(config.fallback_application_name.as_ref())
+    {
         params.push(("application_name", &**application_name));
     }
+    if let Some(replication_mode) = config.replication_mode {
+        let value = match replication_mode {
+            ReplicationMode::Physical => "true",
+            ReplicationMode::Logical => "database",
+        };
+        params.push(("replication", value));
+    }
+    for (key, value) in &config.startup_params {
+        if config::RESERVED_STARTUP_PARAMS.contains(&&**key) {
+            let msg = format!("startup parameter `{}` is set by the client", key);
+            return Err(Error::config(msg.into()));
+        }
+        params.push((key, value));
+    }
 
     let mut buf = BytesMut::new();
     frontend::startup_message(params, &mut buf).map_err(Error::encode)?;
@@ -153,44 +259,43 @@ async fn authenticate<S, T>(
     stream: &mut StartupStream<S, T>,
     config: &Config,
     user: &str,
-) -> Result<(), Error>
+    hostname: Option<&str>,
+) -> Result<AuthenticationMethod, Error>
 where
     S: AsyncRead + AsyncWrite + Unpin,
     T: TlsStream + Unpin,
 {
-    match stream.try_next().await.map_err(Error::io)? {
+    let method = match stream.try_next().await.map_err(Error::io)? {
         Some(Message::AuthenticationOk) => {
             can_skip_channel_binding(config)?;
-            return Ok(());
+            return Ok(AuthenticationMethod::None);
         }
         Some(Message::AuthenticationCleartextPassword) => {
             can_skip_channel_binding(config)?;
 
-            let pass = config
-                .password
-                .as_ref()
-                .ok_or_else(|| Error::config("password missing".into()))?;
+            let pass = password(config).await?;
 
-            authenticate_password(stream, pass).await?;
+            authenticate_password(stream, &pass).await?;
+            AuthenticationMethod::Cleartext
         }
         Some(Message::AuthenticationMd5Password(body)) => {
             can_skip_channel_binding(config)?;
 
-            let pass = config
-                .password
-                .as_ref()
-                .ok_or_else(|| Error::config("password missing".into()))?;
+            let pass = password(config).await?;
 
-            let output = authentication::md5_hash(user.as_bytes(), pass, body.salt());
+            let output = authentication::md5_hash(user.as_bytes(), &pass, body.salt());
             authenticate_password(stream, output.as_bytes()).await?;
+            AuthenticationMethod::Md5
         }
-        Some(Message::AuthenticationSasl(body)) => {
-            authenticate_sasl(stream, body, config).await?;
+        Some(Message::AuthenticationSasl(body)) => authenticate_sasl(stream, body, config).await?,
+        Some(Message::AuthenticationGss) | Some(Message::AuthenticationSspi) => {
+            can_skip_channel_binding(config)?;
+
+            // The server ends the exchange of tokens with `AuthenticationOk`, which is read along with them.
+            authenticate_gss(stream, config, hostname).await?;
+            return Ok(AuthenticationMethod::Gss);
         }
-        Some(Message::AuthenticationKerberosV5)
-        | Some(Message::AuthenticationScmCredential)
-        | Some(Message::AuthenticationGss)
-        | Some(Message::AuthenticationSspi) => {
+        Some(Message::AuthenticationKerberosV5) | Some(Message::AuthenticationScmCredential) => {
             return Err(Error::authentication(
                 "unsupported authentication method".into(),
             ))
@@ -198,16 +303,29 @@ where
         Some(Message::ErrorResponse(body)) => return Err(Error::db(body)),
         Some(m) => return Err(Error::unexpected_message(m)),
         None => return Err(Error::closed()),
-    }
+    };
 
     match stream.try_next().await.map_err(Error::io)? {
-        Some(Message::AuthenticationOk) => Ok(()),
+        Some(Message::AuthenticationOk) => Ok(method),
         Some(Message::ErrorResponse(body)) => Err(Error::db(body)),
         Some(m) => Err(Error::unexpected_message(m)),
         None => Err(Error::closed()),
     }
 }
 
+async fn password(config: &Config) -> Result<Cow<'_, [u8]>, Error> {
+    if let Some(provider) = &config.password_provider {
+        let password = provider.password().await.map_err(Error::authentication)?;
+        return Ok(Cow::Owned(password));
+    }
+
+    config
+        .password
+        .as_deref()
+        .map(Cow::Borrowed)
+        .ok_or_else(|| Error::config("password missing".into()))
+}
+
 fn can_skip_channel_binding(config: &Config) -> Result<(), Error> {
     match config.channel_binding {
         config::ChannelBinding::Disable | config::ChannelBinding::Prefer => Ok(()),
@@ -238,15 +356,12 @@ async fn authenticate_sasl<S, T>(
     stream: &mut StartupStream<S, T>,
     body: AuthenticationSaslBody,
     config: &Config,
-) -> Result<(), Error>
+) -> Result<AuthenticationMethod, Error>
 where
     S: AsyncRead + AsyncWrite + Unpin,
     T: TlsStream + Unpin,
 {
-    let password = config
-        .password
-        .as_ref()
-        .ok_or_else(|| Error::config("password missing".into()))?;
+    let password = password(config).await?;
 
     let mut has_scram = false;
     let mut has_scram_plus = false;
@@ -259,13 +374,26 @@ where
         }
     }
 
-    let channel_binding = stream
-        .inner
-        .get_ref()
-        .channel_binding()
-        .tls_server_end_point
-        .filter(|_| config.channel_binding != config::ChannelBinding::Disable)
-        .map(sasl::ChannelBinding::tls_server_end_point);
+    // PostgreSQL only implements the tls-server-end-point channel binding type, and doesn't advertise the types it
+    // accepts, so tls-exporter data is never used.
+    let channel_binding = if config.channel_binding == config::ChannelBinding::Disable {
+        None
+    } else {
+        let channel_binding = stream.inner.get_ref().channel_binding();
+        if channel_binding.tls_server_end_point.is_none()
+            && channel_binding.tls_exporter.is_some()
+            && config.channel_binding == config::ChannelBinding::Require
+        {
+            return Err(Error::authentication(
+                "channel binding requires tls-server-end-point data, but the TLS stream only provides \
+                 tls-exporter data, which the server doesn't support"
+                    .into(),
+            ));
+        }
+        channel_binding
+            .tls_server_end_point
+            .map(sasl::ChannelBinding::tls_server_end_point)
+    };
 
     let (channel_binding, mechanism) = if has_scram_plus {
         match channel_binding {
@@ -285,7 +413,7 @@ where
         can_skip_channel_binding(config)?;
     }
 
-    let mut scram = ScramSha256::new(password, channel_binding);
+    let mut scram = ScramSha256::new(&password, channel_binding);
 
     let mut buf = BytesMut::new();
     frontend::sasl_initial_response(mechanism, scram.message(), &mut buf).map_err(Error::encode)?;
@@ -323,7 +451,70 @@ where
         .finish(body.data())
         .map_err(|e| Error::authentication(e.into()))?;
 
-    Ok(())
+    if mechanism == sasl::SCRAM_SHA_256_PLUS {
+        Ok(AuthenticationMethod::ScramSha256Plus)
+    } else {
+        Ok(AuthenticationMethod::ScramSha256)
+    }
+}
+
+async fn authenticate_gss<S, T>(
+    stream: &mut StartupStream<S, T>,
+    config: &Config,
+    hostname: Option<&str>,
+) -> Result<(), Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    let provider = config
+        .gss_context_provider
+        .as_ref()
+        .ok_or_else(|| Error::authentication("no GSSAPI context provider configured".into()))?;
+    let hostname = hostname.ok_or_else(|| {
+        Error::authentication("no hostname provided for GSSAPI authentication".into())
+    })?;
+    let mut context = provider.context(hostname).map_err(Error::authentication)?;
+
+    let mut token = None;
+    let mut complete = false;
+    loop {
+        if !complete {
+            let step = context
+                .step(token.as_deref())
+                .map_err(Error::authentication)?;
+            let output = match step {
+                GssStep::Continue(output) => Some(output),
+                GssStep::Complete(output) => {
+                    complete = true;
+                    output
+                }
+            };
+
+            if let Some(output) = output {
+                let mut buf = BytesMut::new();
+                frontend::gss_response(&output, &mut buf).map_err(Error::encode)?;
+                stream
+                    .send(FrontendMessage::Raw(buf.freeze()))
+                    .await
+                    .map_err(Error::io)?;
+            }
+        }
+
+        match stream.try_next().await.map_err(Error::io)? {
+            Some(Message::AuthenticationOk) => return Ok(()),
+            Some(Message::AuthenticationGssContinue(_)) if complete => {
+                return Err(Error::authentication(
+                    "server continued GSSAPI authentication after the context was established"
+                        .into(),
+                ))
+            }
+            Some(Message::AuthenticationGssContinue(body)) => token = Some(body.data().to_vec()),
+            Some(Message::ErrorResponse(body)) => return Err(Error::db(body)),
+            Some(m) => return Err(Error::unexpected_message(m)),
+            None => return Err(Error::closed()),
+        }
+    }
 }
 
 async fn read_info<S, T>(
@@ -2,8 +2,24 @@ use crate::client::Addr;
 use crate::keepalive::KeepaliveConfig;
 use crate::{Error, Socket};
 use socket2::{SockRef, TcpKeepalive};
+#[cfg(unix)]
+use std::ffi::CStr;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::ffi::OsString;
+#[cfg(unix)]
+use std::fs;
 use std::future::Future;
 use std::io;
+#[cfg(unix)]
+use std::mem::MaybeUninit;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+#[cfg(unix)]
+use std::os::unix::fs::FileTypeExt;
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
+#[cfg(unix)]
+use std::ptr;
 use std::time::Duration;
 use tokio::net::TcpStream;
 #[cfg(unix)]
@@ -14,10 +30,9 @@ pub(crate) async fn connect_socket(
     addr: &Addr,
     port: u16,
     connect_timeout: Option<Duration>,
-    #[cfg_attr(not(target_os = "linux"), allow(unused_variables))] tcp_user_timeout: Option<
-        Duration,
-    >,
+    tcp_user_timeout: Option<Duration>,
     keepalive_config: Option<&KeepaliveConfig>,
+    #[cfg_attr(not(unix), allow(unused_variables))] requirepeer: Option<&str>,
 ) -> Result<Socket, Error> {
     match addr {
         Addr::Tcp(ip) => {
@@ -25,32 +40,121 @@ pub(crate) async fn connect_socket(
                 connect_with_timeout(TcpStream::connect((*ip, port)), connect_timeout).await?;
 
             stream.set_nodelay(true).map_err(Error::connect)?;
-
-            let sock_ref = SockRef::from(&stream);
-            #[cfg(target_os = "linux")]
-            {
-                sock_ref
-                    .set_tcp_user_timeout(tcp_user_timeout)
-                    .map_err(Error::connect)?;
-            }
-
-            if let Some(keepalive_config) = keepalive_config {
-                sock_ref
-                    .set_tcp_keepalive(&TcpKeepalive::from(keepalive_config))
-                    .map_err(Error::connect)?;
-            }
+            configure_tcp(SockRef::from(&stream), tcp_user_timeout, keepalive_config)?;
 
             Ok(Socket::new_tcp(stream))
         }
         #[cfg(unix)]
-        Addr::Unix(dir) => {
-            let path = dir.join(format!(".s.PGSQL.{}", port));
+        Addr::Unix(path) => {
+            let path = socket_path(path, port);
             let socket = connect_with_timeout(UnixStream::connect(path), connect_timeout).await?;
+            if let Some(requirepeer) = requirepeer {
+                check_peer(&socket, requirepeer)?;
+            }
             Ok(Socket::new_unix(socket))
         }
     }
 }
 
+/// Returns the path of the socket of the server listening on `port` in the directory at `path`, or `path` itself if
+/// it is a socket file.
+#[cfg(unix)]
+fn socket_path(path: &Path, port: u16) -> PathBuf {
+    let file_name = format!(".s.PGSQL.{}", port);
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        if let Some(name) = path.as_os_str().as_bytes().strip_prefix(b"@") {
+            // tokio connects to a socket in the abstract namespace when its path starts with a nul byte.
+            let mut path = vec![0];
+            path.extend_from_slice(name);
+            return PathBuf::from(OsString::from_vec(path)).join(file_name);
+        }
+    }
+
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => path.to_path_buf(),
+        _ => path.join(file_name),
+    }
+}
+
+/// Checks that the server at the other end of a Unix socket is running as the `requirepeer` user.
+#[cfg(unix)]
+fn check_peer(socket: &UnixStream, requirepeer: &str) -> Result<(), Error> {
+    let uid = socket.peer_cred().map_err(Error::connect)?.uid();
+    let user = user_name(uid).map_err(Error::connect)?;
+    if user != requirepeer {
+        return Err(Error::connect(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!(
+                "requirepeer parameter is set to \"{}\", but the server is running as \"{}\"",
+                requirepeer, user
+            ),
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn user_name(uid: libc::uid_t) -> io::Result<String> {
+    let mut buf = vec![0; 1024];
+    loop {
+        let mut passwd = MaybeUninit::<libc::passwd>::uninit();
+        let mut result = ptr::null_mut();
+        // SAFETY: the buffers are valid for the duration of the call, and `result` is either null or points to
+        // `passwd`, whose strings point to `buf`.
+        let ret = unsafe {
+            libc::getpwuid_r(
+                uid,
+                passwd.as_mut_ptr(),
+                buf.as_mut_ptr(),
+                buf.len(),
+                &mut result,
+            )
+        };
+        match ret {
+            0 if result.is_null() => {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("local user with ID {} does not exist", uid),
+                ))
+            }
+            // SAFETY: `result` points to `passwd`, which was initialized by the call.
+            0 => {
+                return Ok(unsafe { CStr::from_ptr((*result).pw_name) }
+                    .to_string_lossy()
+                    .into_owned())
+            }
+            libc::ERANGE => buf.resize(buf.len() * 2, 0),
+            ret => return Err(io::Error::from_raw_os_error(ret)),
+        }
+    }
+}
+
+pub(crate) fn configure_tcp(
+    sock_ref: SockRef<'_>,
+    #[cfg_attr(not(target_os = "linux"), allow(unused_variables))] tcp_user_timeout: Option<
+        Duration,
+    >,
+    keepalive_config: Option<&KeepaliveConfig>,
+) -> Result<(), Error> {
+    #[cfg(target_os = "linux")]
+    {
+        sock_ref
+            .set_tcp_user_timeout(tcp_user_timeout)
+            .map_err(Error::connect)?;
+    }
+
+    if let Some(keepalive_config) = keepalive_config {
+        sock_ref
+            .set_tcp_keepalive(&TcpKeepalive::from(keepalive_config))
+            .map_err(Error::connect)?;
+    }
+
+    Ok(())
+}
+
 async fn connect_with_timeout<F, T>(connect: F, timeout: Option<Duration>) -> Result<T, Error>
 where
     F: Future<Output = io::Result<T>>,
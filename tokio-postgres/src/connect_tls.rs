@@ -1,28 +1,57 @@
-use crate::config::SslMode;
+use crate::config::{Config, SslMode};
 use crate::maybe_tls_stream::MaybeTlsStream;
 use crate::tls::private::ForcePrivateApi;
-use crate::tls::TlsConnect;
+use crate::tls::{CertificateVerification, TlsConnect};
 use crate::Error;
 use bytes::BytesMut;
 use postgres_protocol::message::frontend;
+use std::path::PathBuf;
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
+/// The TLS settings of a connection, which are also used by requests to cancel its queries.
+#[derive(Debug, Clone)]
+pub(crate) struct TlsSettings {
+    pub mode: SslMode,
+    pub sni: bool,
+    pub root_cert: Option<PathBuf>,
+}
+
+impl TlsSettings {
+    pub fn new(config: &Config) -> TlsSettings {
+        TlsSettings {
+            mode: config.ssl_mode,
+            sni: config.ssl_sni,
+            root_cert: config.ssl_root_cert.clone(),
+        }
+    }
+
+    fn verification(&self) -> CertificateVerification {
+        match self.mode {
+            SslMode::VerifyFull => CertificateVerification::Full,
+            SslMode::VerifyCa => CertificateVerification::Ca,
+            // Like libpq, the server's certificate is verified whenever a root certificate is configured.
+            _ if self.root_cert.is_some() => CertificateVerification::Ca,
+            _ => CertificateVerification::Default,
+        }
+    }
+}
+
 pub async fn connect_tls<S, T>(
     mut stream: S,
-    mode: SslMode,
-    tls: T,
+    settings: &TlsSettings,
+    mut tls: T,
     has_hostname: bool,
 ) -> Result<MaybeTlsStream<S, T::Stream>, Error>
 where
     S: AsyncRead + AsyncWrite + Unpin,
     T: TlsConnect<S>,
 {
-    match mode {
+    match settings.mode {
         SslMode::Disable => return Ok(MaybeTlsStream::Raw(stream)),
         SslMode::Prefer if !tls.can_connect(ForcePrivateApi) => {
             return Ok(MaybeTlsStream::Raw(stream))
         }
-        SslMode::Prefer | SslMode::Require => {}
+        _ => {}
     }
 
     let mut buf = BytesMut::new();
@@ -33,10 +62,10 @@ where
     stream.read_exact(&mut buf).await.map_err(Error::io)?;
 
     if buf[0] != b'S' {
-        if SslMode::Require == mode {
-            return Err(Error::tls("server does not support TLS".into()));
-        } else {
+        if SslMode::Prefer == settings.mode {
             return Ok(MaybeTlsStream::Raw(stream));
+        } else {
+            return Err(Error::tls("server does not support TLS".into()));
         }
     }
 
@@ -44,6 +73,9 @@ where
         return Err(Error::tls("no hostname provided for TLS handshake".into()));
     }
 
+    tls.set_server_name_indication(settings.sni);
+    tls.set_verification(settings.verification(), settings.root_cert.as_deref())
+        .map_err(Error::tls)?;
     let stream = tls
         .connect(stream)
         .await
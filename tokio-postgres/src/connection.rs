@@ -1,34 +1,122 @@
-use crate::codec::{BackendMessage, BackendMessages, FrontendMessage, PostgresCodec};
+use crate::client::{InFlight, Session};
+use crate::codec::{BackendMessage, BackendMessages, FrontendMessage};
+use crate::config::{LifecycleCallback, LifecycleEvent};
+use crate::copy_both::CopyBothReceiver;
 use crate::copy_in::CopyInReceiver;
 use crate::error::DbError;
+use crate::framed::Framed;
+use crate::infer::InferReceiver;
 use crate::maybe_tls_stream::MaybeTlsStream;
+use crate::metrics::WireStats;
+use crate::streamed::StreamedRequest;
 use crate::{AsyncMessage, Error, Notification};
 use bytes::BytesMut;
 use fallible_iterator::FallibleIterator;
-use futures_channel::mpsc;
+use futures_channel::{mpsc, oneshot};
 use futures_util::{ready, stream::FusedStream, Sink, Stream, StreamExt};
 use log::{info, trace};
+use parking_lot::Mutex;
 use postgres_protocol::message::backend::Message;
 use postgres_protocol::message::frontend;
 use std::collections::{HashMap, VecDeque};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Duration;
 use tokio::io::{AsyncRead, AsyncWrite};
-use tokio_util::codec::Framed;
+#[cfg(feature = "runtime")]
+use tokio::time::{self, Instant, Sleep};
 
 pub enum RequestMessages {
     Single(FrontendMessage),
     CopyIn(CopyInReceiver),
+    CopyBoth(CopyBothReceiver),
+    Infer(InferReceiver),
+    Streamed(StreamedRequest),
+    /// Closes the connection once the requests queued before it have completed, reporting the number of requests
+    /// discarded after it.
+    Close(oneshot::Sender<usize>),
+}
+
+const REQUEST_QUEUED: u8 = 0;
+const REQUEST_ACTIVE: u8 = 1;
+const REQUEST_DONE: u8 = 2;
+
+/// The progress of a request, tracked by the connection.
+///
+/// The server handles requests in order, so a request is active from the time the previous one completes until its
+/// own completion.
+pub struct RequestState {
+    progress: AtomicU8,
+    abandoned: AtomicBool,
+    /// The notices raised by the server while handling the request.
+    notices: Mutex<Vec<DbError>>,
+}
+
+impl RequestState {
+    pub fn new() -> RequestState {
+        RequestState {
+            progress: AtomicU8::new(REQUEST_QUEUED),
+            abandoned: AtomicBool::new(false),
+            notices: Mutex::new(vec![]),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.progress.load(Ordering::Relaxed) == REQUEST_ACTIVE
+    }
+
+    #[cfg(feature = "runtime")]
+    fn is_done(&self) -> bool {
+        self.progress.load(Ordering::Relaxed) == REQUEST_DONE
+    }
+
+    /// Determines if the responses to the request are no longer waited for.
+    #[cfg(feature = "runtime")]
+    pub fn is_abandoned(&self) -> bool {
+        self.abandoned.load(Ordering::Relaxed)
+    }
+
+    pub fn abandon(&self) {
+        self.abandoned.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns the notices raised by the server while handling the request so far.
+    pub fn notices(&self) -> Vec<DbError> {
+        self.notices.lock().clone()
+    }
+
+    fn set(&self, progress: u8) {
+        self.progress.store(progress, Ordering::Relaxed);
+    }
 }
 
 pub struct Request {
     pub messages: RequestMessages,
     pub sender: mpsc::Sender<BackendMessages>,
+    pub state: Arc<RequestState>,
+    /// Counts the request as pending until it completes, or `None` for requests closing the connection.
+    pub in_flight: Option<InFlight>,
 }
 
 pub struct Response {
     sender: mpsc::Sender<BackendMessages>,
+    state: Arc<RequestState>,
+    /// When the request was taken from the queue to be written, or `None` for keepalives.
+    sent: Option<std::time::Instant>,
+    /// Released once the response completes, or `None` for keepalives.
+    _in_flight: Option<InFlight>,
+}
+
+/// The round trips made by an idle connection, as enabled by `Config::keepalive_query_interval`.
+#[cfg(feature = "runtime")]
+struct KeepaliveQuery {
+    interval: Duration,
+    sleep: Pin<Box<Sleep>>,
+    /// The state of the last keepalive sent.
+    request: Option<Arc<RequestState>>,
 }
 
 #[derive(PartialEq, Debug)]
@@ -44,16 +132,26 @@ enum State {
 /// server, and should generally be spawned off onto an executor to run in the background.
 ///
 /// `Connection` implements `Future`, and only resolves when the connection is closed, either because a fatal error has
-/// occurred, or because its associated `Client` has dropped and all outstanding work has completed.
+/// occurred, or because its associated `Client` has dropped or has been closed with `Client::close`, and all
+/// outstanding work has completed.
 #[must_use = "futures do nothing unless polled"]
 pub struct Connection<S, T> {
-    stream: Framed<MaybeTlsStream<S, T>, PostgresCodec>,
+    stream: Framed<MaybeTlsStream<S, T>>,
     parameters: HashMap<String, String>,
+    session: Arc<Session>,
     receiver: mpsc::UnboundedReceiver<Request>,
     pending_request: Option<RequestMessages>,
     pending_responses: VecDeque<BackendMessage>,
     responses: VecDeque<Response>,
     state: State,
+    lifecycle: Option<LifecycleCallback>,
+    #[cfg(feature = "runtime")]
+    keepalive: Option<KeepaliveQuery>,
+    /// Notified once the connection has been closed by `Client::close`.
+    close: Option<oneshot::Sender<usize>>,
+    /// The number of requests discarded since `Client::close` was called.
+    discarded: usize,
+    closed: bool,
 }
 
 impl<S, T> Connection<S, T>
@@ -62,19 +160,46 @@ where
     T: AsyncRead + AsyncWrite + Unpin,
 {
     pub(crate) fn new(
-        stream: Framed<MaybeTlsStream<S, T>, PostgresCodec>,
+        stream: Framed<MaybeTlsStream<S, T>>,
         pending_responses: VecDeque<BackendMessage>,
         parameters: HashMap<String, String>,
+        session: Arc<Session>,
         receiver: mpsc::UnboundedReceiver<Request>,
+        lifecycle: Option<LifecycleCallback>,
+        #[cfg_attr(not(feature = "runtime"), allow(unused_variables))]
+        keepalive_query_interval: Option<Duration>,
     ) -> Connection<S, T> {
         Connection {
             stream,
             parameters,
+            session,
             receiver,
             pending_request: None,
             pending_responses,
             responses: VecDeque::new(),
             state: State::Active,
+            lifecycle,
+            #[cfg(feature = "runtime")]
+            keepalive: keepalive_query_interval.map(|interval| KeepaliveQuery {
+                interval,
+                sleep: Box::pin(time::sleep(interval)),
+                request: None,
+            }),
+            close: None,
+            discarded: 0,
+            closed: false,
+        }
+    }
+
+    fn report_closed(&mut self, error: Option<&Error>) {
+        if self.closed {
+            return;
+        }
+        self.closed = true;
+        self.session.set_healthy(false);
+        self.session.close_listeners();
+        if let Some(lifecycle) = &self.lifecycle {
+            lifecycle.call(&LifecycleEvent::Closed(error));
         }
     }
 
@@ -101,7 +226,10 @@ where
         loop {
             let message = match self.poll_response(cx)? {
                 Poll::Ready(Some(message)) => message,
-                Poll::Ready(None) => return Err(Error::closed()),
+                Poll::Ready(None) => {
+                    self.session.set_server_closed();
+                    return Err(Error::closed());
+                }
                 Poll::Pending => {
                     trace!("poll_read: waiting on response");
                     return Ok(None);
@@ -111,6 +239,18 @@ where
             let (mut messages, request_complete) = match message {
                 BackendMessage::Async(Message::NoticeResponse(body)) => {
                     let error = DbError::parse(&mut body.fields()).map_err(Error::parse)?;
+                    #[cfg(feature = "tracing")]
+                    tracing::info!(
+                        severity = error.severity(),
+                        code = error.code().code(),
+                        "{}",
+                        error.message(),
+                    );
+                    // The server handles requests in order, so the notice was raised by the request it is responding
+                    // to.
+                    if let Some(response) = self.responses.front() {
+                        response.state.notices.lock().push(error.clone());
+                    }
                     return Ok(Some(AsyncMessage::Notice(error)));
                 }
                 BackendMessage::Async(Message::NotificationResponse(body)) => {
@@ -119,26 +259,49 @@ where
                         channel: body.channel().map_err(Error::parse)?.to_string(),
                         payload: body.message().map_err(Error::parse)?.to_string(),
                     };
+                    // Notifications of the channels subscribed to with `Client::listen` only go to their streams.
+                    if self.session.route_notification(&notification) {
+                        continue;
+                    }
                     return Ok(Some(AsyncMessage::Notification(notification)));
                 }
                 BackendMessage::Async(Message::ParameterStatus(body)) => {
-                    self.parameters.insert(
-                        body.name().map_err(Error::parse)?.to_string(),
-                        body.value().map_err(Error::parse)?.to_string(),
-                    );
+                    let name = body.name().map_err(Error::parse)?.to_string();
+                    let value = body.value().map_err(Error::parse)?.to_string();
+                    self.session.update_parameter(name.clone(), value.clone());
+                    self.parameters.insert(name, value);
                     continue;
                 }
                 BackendMessage::Async(_) => unreachable!(),
                 BackendMessage::Normal {
                     messages,
                     request_complete,
-                } => (messages, request_complete),
+                    resets_statements,
+                } => {
+                    if resets_statements {
+                        self.session.statement_epoch.fetch_add(1, Ordering::Relaxed);
+                    }
+                    (messages, request_complete)
+                }
             };
 
+            if request_complete {
+                if let Some(status) = messages.ready_status() {
+                    self.session
+                        .transaction_status
+                        .store(status, Ordering::Relaxed);
+                }
+            }
+
             let mut response = match self.responses.pop_front() {
                 Some(response) => response,
                 None => match messages.next().map_err(Error::parse)? {
-                    Some(Message::ErrorResponse(error)) => return Err(Error::db(error)),
+                    // The server only sends errors outside of a request when its backend exits, like when it's
+                    // terminated.
+                    Some(Message::ErrorResponse(error)) => {
+                        self.session.set_server_closed();
+                        return Err(Error::db(error));
+                    }
                     Some(m) => return Err(Error::unexpected_message(m)),
                     None => return Err(Error::closed()),
                 },
@@ -147,13 +310,17 @@ where
             match response.sender.poll_ready(cx) {
                 Poll::Ready(Ok(())) => {
                     let _ = response.sender.start_send(messages);
-                    if !request_complete {
+                    if request_complete {
+                        self.complete(&mut response);
+                    } else {
                         self.responses.push_front(response);
                     }
                 }
                 Poll::Ready(Err(_)) => {
                     // we need to keep paging through the rest of the messages even if the receiver's hung up
-                    if !request_complete {
+                    if request_complete {
+                        self.complete(&mut response);
+                    } else {
                         self.responses.push_front(response);
                     }
                 }
@@ -162,6 +329,7 @@ where
                     self.pending_responses.push_back(BackendMessage::Normal {
                         messages,
                         request_complete,
+                        resets_statements: false,
                     });
                     trace!("poll_read: waiting on sender");
                     return Ok(None);
@@ -170,6 +338,25 @@ where
         }
     }
 
+    fn complete(&mut self, response: &mut Response) {
+        response.state.set(REQUEST_DONE);
+        if let Some(sent) = response.sent.take() {
+            let codec = self.stream.codec_mut();
+            codec
+                .stats
+                .record_round_trip(sent.elapsed(), codec.metrics.as_ref());
+        }
+        self.session.set_healthy(true);
+        if let Some(next) = self.responses.front() {
+            self.activate(&next.state);
+        }
+    }
+
+    fn activate(&self, state: &Arc<RequestState>) {
+        state.set(REQUEST_ACTIVE);
+        *self.session.active_request.lock() = Some(state.clone());
+    }
+
     fn poll_request(&mut self, cx: &mut Context<'_>) -> Poll<Option<RequestMessages>> {
         if let Some(messages) = self.pending_request.take() {
             trace!("retrying pending request");
@@ -180,17 +367,92 @@ where
             return Poll::Ready(None);
         }
 
-        match self.receiver.poll_next_unpin(cx) {
-            Poll::Ready(Some(request)) => {
-                trace!("polled new request");
-                self.responses.push_back(Response {
-                    sender: request.sender,
-                });
-                Poll::Ready(Some(request.messages))
+        loop {
+            let request = match self.receiver.poll_next_unpin(cx) {
+                Poll::Ready(Some(request)) => request,
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            };
+
+            if self.close.is_some() {
+                trace!("discarding request queued after close");
+                self.discarded += 1;
+                continue;
+            }
+            if let RequestMessages::Close(sender) = request.messages {
+                trace!("closing, no longer accepting requests");
+                self.close = Some(sender);
+                self.receiver.close();
+                continue;
+            }
+
+            trace!("polled new request");
+            #[cfg(feature = "runtime")]
+            if let Some(keepalive) = &mut self.keepalive {
+                let deadline = Instant::now() + keepalive.interval;
+                keepalive.sleep.as_mut().reset(deadline);
+            }
+            if self.responses.is_empty() {
+                self.activate(&request.state);
+            }
+            self.responses.push_back(Response {
+                sender: request.sender,
+                state: request.state,
+                sent: Some(std::time::Instant::now()),
+                _in_flight: request.in_flight,
+            });
+            return Poll::Ready(Some(request.messages));
+        }
+    }
+
+    /// Queues a keepalive round trip once the connection has been idle for the keepalive interval.
+    ///
+    /// The connection is reported as unhealthy if the previous keepalive hasn't completed by then.
+    #[cfg(feature = "runtime")]
+    fn poll_keepalive(&mut self, cx: &mut Context<'_>) {
+        let keepalive = match &mut self.keepalive {
+            Some(keepalive) => keepalive,
+            None => return,
+        };
+        if self.state != State::Active || keepalive.sleep.as_mut().poll(cx).is_pending() {
+            return;
+        }
+
+        let deadline = Instant::now() + keepalive.interval;
+        keepalive.sleep.as_mut().reset(deadline);
+        // Registers the task to be woken up once the interval elapses again.
+        let _ = keepalive.sleep.as_mut().poll(cx);
+
+        if let Some(request) = keepalive.request.take() {
+            if !request.is_done() {
+                trace!("keepalive timed out");
+                self.session.set_healthy(false);
+                keepalive.request = Some(request);
+                return;
             }
-            Poll::Ready(None) => Poll::Ready(None),
-            Poll::Pending => Poll::Pending,
         }
+
+        if !self.responses.is_empty() || self.pending_request.is_some() {
+            return;
+        }
+
+        trace!("sending keepalive");
+        let state = Arc::new(RequestState::new());
+        keepalive.request = Some(state.clone());
+        // Nothing waits for the response, which is dropped once received.
+        let (sender, _) = mpsc::channel(0);
+        self.activate(&state);
+        self.responses.push_back(Response {
+            sender,
+            state,
+            sent: None,
+            _in_flight: None,
+        });
+        let mut request = BytesMut::new();
+        frontend::sync(&mut request);
+        self.pending_request = Some(RequestMessages::Single(FrontendMessage::Raw(
+            request.freeze(),
+        )));
     }
 
     fn poll_write(&mut self, cx: &mut Context<'_>) -> Result<bool, Error> {
@@ -214,6 +476,9 @@ where
                 Poll::Ready(None) if self.responses.is_empty() && self.state == State::Active => {
                     trace!("poll_write: at eof, terminating");
                     self.state = State::Terminating;
+                    if let Some(lifecycle) = &self.lifecycle {
+                        lifecycle.call(&LifecycleEvent::Terminating);
+                    }
                     let mut request = BytesMut::new();
                     frontend::terminate(&mut request);
                     RequestMessages::Single(FrontendMessage::Raw(request.freeze()))
@@ -259,6 +524,57 @@ where
                         .map_err(Error::io)?;
                     self.pending_request = Some(RequestMessages::CopyIn(receiver));
                 }
+                RequestMessages::CopyBoth(mut receiver) => {
+                    let message = match receiver.poll_next_unpin(cx) {
+                        Poll::Ready(Some(message)) => message,
+                        Poll::Ready(None) => {
+                            trace!("poll_write: finished copy_both request");
+                            continue;
+                        }
+                        Poll::Pending => {
+                            trace!("poll_write: waiting on copy_both stream");
+                            self.pending_request = Some(RequestMessages::CopyBoth(receiver));
+                            return Ok(true);
+                        }
+                    };
+                    Pin::new(&mut self.stream)
+                        .start_send(message)
+                        .map_err(Error::io)?;
+                    self.pending_request = Some(RequestMessages::CopyBoth(receiver));
+                }
+                RequestMessages::Infer(mut receiver) => {
+                    let message = match receiver.poll_next_unpin(cx) {
+                        Poll::Ready(Some(message)) => message,
+                        Poll::Ready(None) => {
+                            trace!("poll_write: finished infer request");
+                            continue;
+                        }
+                        Poll::Pending => {
+                            trace!("poll_write: waiting on infer stream");
+                            self.pending_request = Some(RequestMessages::Infer(receiver));
+                            return Ok(true);
+                        }
+                    };
+                    Pin::new(&mut self.stream)
+                        .start_send(message)
+                        .map_err(Error::io)?;
+                    self.pending_request = Some(RequestMessages::Infer(receiver));
+                }
+                RequestMessages::Streamed(mut request) => {
+                    let message = match request.next().map_err(Error::io)? {
+                        Some(message) => message,
+                        None => {
+                            trace!("poll_write: finished streamed request");
+                            continue;
+                        }
+                    };
+                    Pin::new(&mut self.stream)
+                        .start_send(message)
+                        .map_err(Error::io)?;
+                    self.pending_request = Some(RequestMessages::Streamed(request));
+                }
+                // Close requests are handled as they are polled.
+                RequestMessages::Close(_) => unreachable!(),
             }
         }
     }
@@ -285,6 +601,9 @@ where
         {
             Poll::Ready(()) => {
                 trace!("poll_shutdown: complete");
+                if let Some(close) = self.close.take() {
+                    let _ = close.send(self.discarded);
+                }
                 Poll::Ready(Ok(()))
             }
             Poll::Pending => {
@@ -299,6 +618,12 @@ where
         self.parameters.get(name).map(|s| &**s)
     }
 
+    /// Returns the number of bytes and messages exchanged with the server so far, including during the establishment
+    /// of the connection.
+    pub fn wire_stats(&self) -> &WireStats {
+        &self.stream.codec().stats
+    }
+
     /// Polls for asynchronous messages from the server.
     ///
     /// The server can send notices as well as notifications asynchronously to the client. Applications that wish to
@@ -309,8 +634,23 @@ where
     pub fn poll_message(
         &mut self,
         cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<AsyncMessage, Error>>> {
+        let poll = self.poll_connection(cx);
+        match &poll {
+            Poll::Ready(None) => self.report_closed(None),
+            Poll::Ready(Some(Err(e))) => self.report_closed(Some(e)),
+            _ => {}
+        }
+        poll
+    }
+
+    fn poll_connection(
+        &mut self,
+        cx: &mut Context<'_>,
     ) -> Poll<Option<Result<AsyncMessage, Error>>> {
         let message = self.poll_read(cx)?;
+        #[cfg(feature = "runtime")]
+        self.poll_keepalive(cx);
         let want_flush = self.poll_write(cx)?;
         if want_flush {
             self.poll_flush(cx)?;
@@ -326,6 +666,15 @@ where
     }
 }
 
+impl<S, T> Drop for Connection<S, T> {
+    fn drop(&mut self) {
+        // A connection dropped without having been polled to completion is closed as well.
+        if let (false, Some(lifecycle)) = (self.closed, &self.lifecycle) {
+            lifecycle.call(&LifecycleEvent::Closed(None));
+        }
+    }
+}
+
 impl<S, T> Future for Connection<S, T>
 where
     S: AsyncRead + AsyncWrite + Unpin,
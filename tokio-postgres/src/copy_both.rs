@@ -0,0 +1,219 @@
+use crate::client::{InnerClient, Responses};
+use crate::codec::FrontendMessage;
+use crate::connection::RequestMessages;
+use crate::interceptor::Operation;
+use crate::{simple_query, Error};
+use bytes::{Buf, Bytes, BytesMut};
+use futures_channel::mpsc;
+use futures_util::{future, ready, Sink, SinkExt, Stream, StreamExt};
+use log::debug;
+use pin_project_lite::pin_project;
+use postgres_protocol::message::backend::Message;
+use postgres_protocol::message::frontend;
+use postgres_protocol::message::frontend::CopyData;
+use std::marker::{PhantomData, PhantomPinned};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+enum CopyBothMessage {
+    Message(FrontendMessage),
+    Done,
+}
+
+pub struct CopyBothReceiver {
+    receiver: mpsc::Receiver<CopyBothMessage>,
+    done: bool,
+}
+
+impl CopyBothReceiver {
+    fn new(receiver: mpsc::Receiver<CopyBothMessage>) -> CopyBothReceiver {
+        CopyBothReceiver {
+            receiver,
+            done: false,
+        }
+    }
+}
+
+impl Stream for CopyBothReceiver {
+    type Item = FrontendMessage;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<FrontendMessage>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+
+        // The server doesn't accept `CopyFail` in copy-both mode, so an abandoned copy is ended as a complete one.
+        // There is no `Sync` to send either, since the copy was started by a simple query.
+        match ready!(self.receiver.poll_next_unpin(cx)) {
+            Some(CopyBothMessage::Message(message)) => Poll::Ready(Some(message)),
+            Some(CopyBothMessage::Done) | None => {
+                self.done = true;
+                let mut buf = BytesMut::new();
+                frontend::copy_done(&mut buf);
+                Poll::Ready(Some(FrontendMessage::Raw(buf.freeze())))
+            }
+        }
+    }
+}
+
+enum DuplexState {
+    Active,
+    Closing,
+    Reading,
+    Done,
+}
+
+pin_project! {
+    /// A duplex stream of copy-both mode data, as used by streaming replication.
+    ///
+    /// The `Stream` half yields the `CopyData` messages sent by the server, and ends when the server ends its side of
+    /// the copy. Each item written to the `Sink` half is sent to the server as one `CopyData` message.
+    ///
+    /// The copy should be explicitly completed via the `Sink::close` or `finish` methods. If it is not, it is ended
+    /// when the duplex is dropped, but any error reported by the server is lost.
+    pub struct CopyBothDuplex<T> {
+        #[pin]
+        sender: mpsc::Sender<CopyBothMessage>,
+        responses: Responses,
+        state: DuplexState,
+        #[pin]
+        _p: PhantomPinned,
+        _p2: PhantomData<T>,
+    }
+}
+
+impl<T> CopyBothDuplex<T>
+where
+    T: Buf + 'static + Send,
+{
+    /// A poll-based version of `finish`.
+    pub fn poll_finish(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        loop {
+            match self.state {
+                DuplexState::Active => {
+                    let mut this = self.as_mut().project();
+                    ready!(this.sender.as_mut().poll_ready(cx)).map_err(|_| Error::closed())?;
+                    this.sender
+                        .start_send(CopyBothMessage::Done)
+                        .map_err(|_| Error::closed())?;
+                    *this.state = DuplexState::Closing;
+                }
+                DuplexState::Closing => {
+                    let this = self.as_mut().project();
+                    ready!(this.sender.poll_close(cx)).map_err(|_| Error::closed())?;
+                    *this.state = DuplexState::Reading;
+                }
+                DuplexState::Reading => {
+                    let this = self.as_mut().project();
+                    match ready!(this.responses.poll_next(cx))? {
+                        Message::CopyData(_) | Message::CopyDone => {}
+                        Message::CommandComplete(_) => {
+                            *this.state = DuplexState::Done;
+                            return Poll::Ready(Ok(()));
+                        }
+                        m => return Poll::Ready(Err(Error::unexpected_message(m))),
+                    }
+                }
+                DuplexState::Done => return Poll::Ready(Ok(())),
+            }
+        }
+    }
+
+    /// Completes the copy, discarding any data the server sends before acknowledging the end of the copy.
+    ///
+    /// The `Sink::close` method is equivalent to `finish`.
+    pub async fn finish(mut self: Pin<&mut Self>) -> Result<(), Error> {
+        future::poll_fn(|cx| self.as_mut().poll_finish(cx)).await
+    }
+}
+
+impl<T> Stream for CopyBothDuplex<T> {
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        if let DuplexState::Done = this.state {
+            return Poll::Ready(None);
+        }
+
+        match ready!(this.responses.poll_next(cx)?) {
+            Message::CopyData(body) => Poll::Ready(Some(Ok(body.into_bytes()))),
+            Message::CopyDone => Poll::Ready(None),
+            m => Poll::Ready(Some(Err(Error::unexpected_message(m)))),
+        }
+    }
+}
+
+impl<T> Sink<T> for CopyBothDuplex<T>
+where
+    T: Buf + 'static + Send,
+{
+    type Error = Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        self.project()
+            .sender
+            .poll_ready(cx)
+            .map_err(|_| Error::closed())
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Error> {
+        let data: Box<dyn Buf + Send> = Box::new(item);
+        let data = CopyData::new(data).map_err(Error::encode)?;
+        self.project()
+            .sender
+            .start_send(CopyBothMessage::Message(FrontendMessage::CopyData(data)))
+            .map_err(|_| Error::closed())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        self.project()
+            .sender
+            .poll_flush(cx)
+            .map_err(|_| Error::closed())
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        self.poll_finish(cx)
+    }
+}
+
+pub async fn copy_both_simple<T>(
+    client: &InnerClient,
+    query: &str,
+) -> Result<CopyBothDuplex<T>, Error>
+where
+    T: Buf + 'static + Send,
+{
+    let query = client
+        .interceptors()
+        .before_sql(Operation::SimpleQuery, query)?;
+
+    debug!("executing copy both query {}", query);
+
+    let permit = client.reserve().await;
+    let buf = simple_query::encode(client, &query)?;
+
+    let (mut sender, receiver) = mpsc::channel(1);
+    let receiver = CopyBothReceiver::new(receiver);
+    let mut responses = client.send(permit, RequestMessages::CopyBoth(receiver))?;
+
+    sender
+        .send(CopyBothMessage::Message(FrontendMessage::Raw(buf)))
+        .await
+        .map_err(|_| Error::closed())?;
+
+    match responses.next().await? {
+        Message::CopyBothResponse(_) => {}
+        m => return Err(Error::unexpected_message(m)),
+    }
+
+    Ok(CopyBothDuplex {
+        sender,
+        responses,
+        state: DuplexState::Active,
+        _p: PhantomPinned,
+        _p2: PhantomData,
+    })
+}
@@ -1,39 +1,73 @@
-use crate::client::{InnerClient, Responses};
+use crate::client::{InnerClient, RequestPermit, Responses};
 use crate::codec::FrontendMessage;
 use crate::connection::RequestMessages;
+use crate::instrument::Span;
 use crate::query::extract_row_affected;
 use crate::{query, slice_iter, Error, Statement};
-use bytes::{Buf, BufMut, BytesMut};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
 use futures_channel::mpsc;
 use futures_util::{future, ready, Sink, SinkExt, Stream, StreamExt};
 use log::debug;
+use parking_lot::Mutex;
 use pin_project_lite::pin_project;
 use postgres_protocol::message::backend::Message;
 use postgres_protocol::message::frontend;
 use postgres_protocol::message::frontend::CopyData;
 use std::marker::{PhantomData, PhantomPinned};
 use std::pin::Pin;
-use std::task::{Context, Poll};
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+
+const DEFAULT_FLUSH_THRESHOLD: usize = 4096;
 
 enum CopyInMessage {
     Message(FrontendMessage),
+    Data(CopyData<Box<dyn Buf + Send>>),
     Done,
 }
 
+#[derive(Default)]
+struct ProgressState {
+    progress: CopyInProgress,
+    done: bool,
+    wakers: Vec<Waker>,
+}
+
+impl ProgressState {
+    fn wake(&mut self) {
+        for waker in self.wakers.drain(..) {
+            waker.wake();
+        }
+    }
+}
+
 pub struct CopyInReceiver {
     receiver: mpsc::Receiver<CopyInMessage>,
+    progress: Arc<Mutex<ProgressState>>,
     done: bool,
 }
 
 impl CopyInReceiver {
-    fn new(receiver: mpsc::Receiver<CopyInMessage>) -> CopyInReceiver {
+    fn new(
+        receiver: mpsc::Receiver<CopyInMessage>,
+        progress: Arc<Mutex<ProgressState>>,
+    ) -> CopyInReceiver {
         CopyInReceiver {
             receiver,
+            progress,
             done: false,
         }
     }
 }
 
+impl Drop for CopyInReceiver {
+    fn drop(&mut self) {
+        let mut progress = self.progress.lock();
+        progress.done = true;
+        progress.wake();
+    }
+}
+
 impl Stream for CopyInReceiver {
     type Item = FrontendMessage;
 
@@ -44,6 +78,7 @@ impl Stream for CopyInReceiver {
 
         match ready!(self.receiver.poll_next_unpin(cx)) {
             Some(CopyInMessage::Message(message)) => Poll::Ready(Some(message)),
+            Some(CopyInMessage::Data(data)) => Poll::Ready(Some(FrontendMessage::CopyData(data))),
             Some(CopyInMessage::Done) => {
                 self.done = true;
                 let mut buf = BytesMut::new();
@@ -62,6 +97,55 @@ impl Stream for CopyInReceiver {
     }
 }
 
+/// The progress of a `COPY ... FROM STDIN` query, as reported by `CopyInSink`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct CopyInProgress {
+    bytes_written: u64,
+    chunks_written: u64,
+}
+
+impl CopyInProgress {
+    /// Returns the number of bytes of data written to the connection.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Returns the number of `CopyData` messages the data was written in.
+    pub fn chunks_written(&self) -> u64 {
+        self.chunks_written
+    }
+}
+
+/// A stream of the progress of a `COPY ... FROM STDIN` query, returned by `CopyInSink::progress`.
+///
+/// The current progress is yielded first, followed by the progress each time more data is written to the connection.
+/// Updates made in quick succession are coalesced. The stream ends once the copy is over.
+pub struct CopyInProgressStream {
+    progress: Arc<Mutex<ProgressState>>,
+    last: Option<CopyInProgress>,
+}
+
+impl Stream for CopyInProgressStream {
+    type Item = CopyInProgress;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<CopyInProgress>> {
+        let progress = self.progress.clone();
+        let mut progress = progress.lock();
+        if self.last != Some(progress.progress) {
+            self.last = Some(progress.progress);
+            return Poll::Ready(Some(progress.progress));
+        }
+        if progress.done {
+            return Poll::Ready(None);
+        }
+
+        if !progress.wakers.iter().any(|w| w.will_wake(cx.waker())) {
+            progress.wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
 enum SinkState {
     Active,
     Closing,
@@ -73,12 +157,19 @@ pin_project! {
     ///
     /// The copy *must* be explicitly completed via the `Sink::close` or `finish` methods. If it is
     /// not, the copy will be aborted.
+    ///
+    /// Data is sent to the connection as it is written, and the sink only accepts more once the connection has
+    /// taken the previous data, so that bulk loads are slowed down to the pace of the server rather than buffered in
+    /// memory. The progress of the copy can be followed with `bytes_written` or the `progress` stream.
     pub struct CopyInSink<T> {
         #[pin]
         sender: mpsc::Sender<CopyInMessage>,
         responses: Responses,
         buf: BytesMut,
+        flush_threshold: usize,
+        progress: Arc<Mutex<ProgressState>>,
         state: SinkState,
+        span: Span,
         #[pin]
         _p: PhantomPinned,
         _p2: PhantomData<T>,
@@ -112,6 +203,7 @@ where
                     match ready!(this.responses.poll_next(cx))? {
                         Message::CommandComplete(body) => {
                             let rows = extract_row_affected(&body)?;
+                            this.span.record_rows(rows);
                             return Poll::Ready(Ok(rows));
                         }
                         m => return Poll::Ready(Err(Error::unexpected_message(m))),
@@ -124,10 +216,46 @@ where
     /// Completes the copy, returning the number of rows inserted.
     ///
     /// The `Sink::close` method is equivalent to `finish`, except that it does not return the
-    /// number of rows.
+    /// number of rows. The amount of data written is then available from `bytes_written` and
+    /// `chunks_written`.
     pub async fn finish(mut self: Pin<&mut Self>) -> Result<u64, Error> {
         future::poll_fn(|cx| self.as_mut().poll_finish(cx)).await
     }
+
+    /// Sets the number of bytes buffered by the sink before they are sent to the connection.
+    ///
+    /// Items smaller than this are merged into the buffer, and larger items are sent as is. Flushing the sink sends
+    /// the buffer regardless of its size. Defaults to 4096.
+    pub fn set_flush_threshold(self: Pin<&mut Self>, flush_threshold: usize) {
+        *self.project().flush_threshold = flush_threshold;
+    }
+
+    /// Returns the number of bytes buffered by the sink before they are sent to the connection.
+    pub fn flush_threshold(&self) -> usize {
+        self.flush_threshold
+    }
+
+    /// Returns the number of bytes of data written to the connection so far.
+    ///
+    /// This doesn't include the data still buffered by the sink.
+    pub fn bytes_written(&self) -> u64 {
+        self.progress.lock().progress.bytes_written
+    }
+
+    /// Returns the number of `CopyData` messages written to the connection so far.
+    pub fn chunks_written(&self) -> u64 {
+        self.progress.lock().progress.chunks_written
+    }
+
+    /// Returns a stream of the progress of the copy.
+    ///
+    /// The stream is independent of the sink, so that it can be polled by another task while data is written.
+    pub fn progress(&self) -> CopyInProgressStream {
+        CopyInProgressStream {
+            progress: self.progress.clone(),
+            last: None,
+        }
+    }
 }
 
 impl<T> Sink<T> for CopyInSink<T>
@@ -146,7 +274,7 @@ where
     fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Error> {
         let this = self.project();
 
-        let data: Box<dyn Buf + Send> = if item.remaining() > 4096 {
+        let data: Box<dyn Buf + Send> = if item.remaining() > *this.flush_threshold {
             if this.buf.is_empty() {
                 Box::new(item)
             } else {
@@ -154,17 +282,14 @@ where
             }
         } else {
             this.buf.put(item);
-            if this.buf.len() > 4096 {
+            if this.buf.len() > *this.flush_threshold {
                 Box::new(this.buf.split().freeze())
             } else {
                 return Ok(());
             }
         };
 
-        let data = CopyData::new(data).map_err(Error::encode)?;
-        this.sender
-            .start_send(CopyInMessage::Message(FrontendMessage::CopyData(data)))
-            .map_err(|_| Error::closed())
+        send_data(this.sender, this.progress, data)
     }
 
     fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
@@ -173,11 +298,7 @@ where
         if !this.buf.is_empty() {
             ready!(this.sender.as_mut().poll_ready(cx)).map_err(|_| Error::closed())?;
             let data: Box<dyn Buf + Send> = Box::new(this.buf.split().freeze());
-            let data = CopyData::new(data).map_err(Error::encode)?;
-            this.sender
-                .as_mut()
-                .start_send(CopyInMessage::Message(FrontendMessage::CopyData(data)))
-                .map_err(|_| Error::closed())?;
+            send_data(this.sender.as_mut(), this.progress, data)?;
         }
 
         this.sender.poll_flush(cx).map_err(|_| Error::closed())
@@ -188,17 +309,65 @@ where
     }
 }
 
+/// Hands data over to the connection, accounting for it in the progress of the copy.
+///
+/// The data is counted as soon as it is queued, so that the progress is up to date once the sink has been flushed,
+/// even if the connection hasn't been polled since.
+fn send_data(
+    sender: Pin<&mut mpsc::Sender<CopyInMessage>>,
+    progress: &Mutex<ProgressState>,
+    data: Box<dyn Buf + Send>,
+) -> Result<(), Error> {
+    let len = data.remaining();
+    let data = CopyData::new(data).map_err(Error::encode)?;
+    sender
+        .start_send(CopyInMessage::Data(data))
+        .map_err(|_| Error::closed())?;
+
+    let mut progress = progress.lock();
+    progress.progress.bytes_written += len as u64;
+    progress.progress.chunks_written += 1;
+    progress.wake();
+    Ok(())
+}
+
 pub async fn copy_in<T>(client: &InnerClient, statement: Statement) -> Result<CopyInSink<T>, Error>
 where
     T: Buf + 'static + Send,
 {
     debug!("executing copy in statement {}", statement.name());
 
+    let permit = client.reserve().await;
     let buf = query::encode(client, &statement, slice_iter(&[]))?;
 
+    let span = Span::copy_in(client.instrumentation(), &statement);
+    let progress = Arc::new(Mutex::new(ProgressState::default()));
+    let (sender, responses) = span
+        .instrument(start(client, permit, buf, progress.clone()))
+        .await?;
+
+    Ok(CopyInSink {
+        sender,
+        responses,
+        buf: BytesMut::new(),
+        flush_threshold: DEFAULT_FLUSH_THRESHOLD,
+        progress,
+        state: SinkState::Active,
+        span,
+        _p: PhantomPinned,
+        _p2: PhantomData,
+    })
+}
+
+async fn start(
+    client: &InnerClient,
+    permit: RequestPermit,
+    buf: Bytes,
+    progress: Arc<Mutex<ProgressState>>,
+) -> Result<(mpsc::Sender<CopyInMessage>, Responses), Error> {
     let (mut sender, receiver) = mpsc::channel(1);
-    let receiver = CopyInReceiver::new(receiver);
-    let mut responses = client.send(RequestMessages::CopyIn(receiver))?;
+    let receiver = CopyInReceiver::new(receiver, progress);
+    let mut responses = client.send(permit, RequestMessages::CopyIn(receiver))?;
 
     sender
         .send(CopyInMessage::Message(FrontendMessage::Raw(buf)))
@@ -206,12 +375,10 @@ where
         .map_err(|_| Error::closed())?;
 
     match responses.next().await? {
-        Message::ParseComplete => {
-            match responses.next().await? {
-                Message::BindComplete => {}
-                m => return Err(Error::unexpected_message(m)),
-            }
-        }
+        Message::ParseComplete => match responses.next().await? {
+            Message::BindComplete => {}
+            m => return Err(Error::unexpected_message(m)),
+        },
         Message::BindComplete => {}
         m => return Err(Error::unexpected_message(m)),
     }
@@ -221,12 +388,5 @@ where
         m => return Err(Error::unexpected_message(m)),
     }
 
-    Ok(CopyInSink {
-        sender,
-        responses,
-        buf: BytesMut::new(),
-        state: SinkState::Active,
-        _p: PhantomPinned,
-        _p2: PhantomData,
-    })
+    Ok((sender, responses))
 }
@@ -0,0 +1,233 @@
+//! Options of `COPY` statements.
+//!
+//! [`CopyOptions`] builds the `COPY ... FROM STDIN` and `COPY ... TO STDOUT` statements passed to `Client::copy_in`
+//! and `Client::copy_out`, taking care of quoting the format options.
+//!
+//! ```
+//! use tokio_postgres::copy_options::{CopyFormat, CopyOptions};
+//!
+//! let options = CopyOptions::new()
+//!     .format(CopyFormat::Csv)
+//!     .delimiter(';')
+//!     .header(true);
+//! assert_eq!(
+//!     options.copy_in("foo (id, name)"),
+//!     "COPY foo (id, name) FROM STDIN (FORMAT csv, DELIMITER E';', HEADER)",
+//! );
+//! ```
+//!
+//! The [`dump`] and [`restore`] functions run these statements directly, copying data out of a table or query into a
+//! stream, and from a stream into a table. [`transfer`] combines both to copy data between connections, for example to
+//! migrate a table to another database, without buffering more than a few messages in memory: data is only read from
+//! the source as fast as the target accepts it.
+
+use crate::{CopyOutStream, Error, GenericClient};
+use bytes::Buf;
+use futures_util::{pin_mut, SinkExt, Stream};
+use std::fmt::Write;
+
+/// The format of the data exchanged by a `COPY` statement.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CopyFormat {
+    /// The text format, with one line per row and tab-separated columns.
+    #[default]
+    Text,
+    /// The comma-separated values format.
+    Csv,
+    /// The binary format, to be used with the `binary_copy` module.
+    Binary,
+}
+
+/// Options of a `COPY` statement.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CopyOptions {
+    format: CopyFormat,
+    delimiter: Option<char>,
+    null: Option<String>,
+    header: bool,
+    quote: Option<char>,
+    escape: Option<char>,
+    encoding: Option<String>,
+}
+
+impl CopyOptions {
+    /// Creates options using the server's defaults, which is the text format.
+    pub fn new() -> CopyOptions {
+        CopyOptions::default()
+    }
+
+    /// Sets the format of the data.
+    pub fn format(mut self, format: CopyFormat) -> CopyOptions {
+        self.format = format;
+        self
+    }
+
+    /// Sets the character separating columns.
+    ///
+    /// Defaults to a tab in the text format and a comma in the CSV format.
+    pub fn delimiter(mut self, delimiter: char) -> CopyOptions {
+        self.delimiter = Some(delimiter);
+        self
+    }
+
+    /// Sets the string representing a null value.
+    ///
+    /// Defaults to `\N` in the text format and an unquoted empty string in the CSV format.
+    pub fn null(mut self, null: &str) -> CopyOptions {
+        self.null = Some(null.to_string());
+        self
+    }
+
+    /// Controls whether the data starts with a line containing the names of the columns.
+    ///
+    /// Defaults to `false`.
+    pub fn header(mut self, header: bool) -> CopyOptions {
+        self.header = header;
+        self
+    }
+
+    /// Sets the character quoting values in the CSV format.
+    ///
+    /// Defaults to a double quote.
+    pub fn quote(mut self, quote: char) -> CopyOptions {
+        self.quote = Some(quote);
+        self
+    }
+
+    /// Sets the character escaping the quote character in the CSV format.
+    ///
+    /// Defaults to the quote character.
+    pub fn escape(mut self, escape: char) -> CopyOptions {
+        self.escape = Some(escape);
+        self
+    }
+
+    /// Sets the encoding of the data.
+    ///
+    /// Defaults to the client encoding.
+    pub fn encoding(mut self, encoding: &str) -> CopyOptions {
+        self.encoding = Some(encoding.to_string());
+        self
+    }
+
+    /// Returns a statement copying data into a target.
+    ///
+    /// The target is interpolated as is, and is a table name optionally followed by a list of columns.
+    pub fn copy_in(&self, target: &str) -> String {
+        self.to_sql(target, "FROM STDIN")
+    }
+
+    /// Returns a statement copying data out of a source.
+    ///
+    /// The source is interpolated as is, and is either a table name optionally followed by a list of columns, or a
+    /// parenthesized query.
+    pub fn copy_out(&self, source: &str) -> String {
+        self.to_sql(source, "TO STDOUT")
+    }
+
+    fn to_sql(&self, relation: &str, direction: &str) -> String {
+        let format = match self.format {
+            CopyFormat::Text => "text",
+            CopyFormat::Csv => "csv",
+            CopyFormat::Binary => "binary",
+        };
+        let mut sql = format!("COPY {} {} (FORMAT {}", relation, direction, format);
+
+        let mut option = |name: &str, value: &str| {
+            let _ = write!(sql, ", {} {}", name, literal(value));
+        };
+        if let Some(delimiter) = self.delimiter {
+            option("DELIMITER", delimiter.encode_utf8(&mut [0; 4]));
+        }
+        if let Some(null) = &self.null {
+            option("NULL", null);
+        }
+        if let Some(quote) = self.quote {
+            option("QUOTE", quote.encode_utf8(&mut [0; 4]));
+        }
+        if let Some(escape) = self.escape {
+            option("ESCAPE", escape.encode_utf8(&mut [0; 4]));
+        }
+        if let Some(encoding) = &self.encoding {
+            option("ENCODING", encoding);
+        }
+        if self.header {
+            sql.push_str(", HEADER");
+        }
+
+        sql.push(')');
+        sql
+    }
+}
+
+/// Copies data out of a source, which is either a table name optionally followed by a list of columns, or a
+/// parenthesized query.
+///
+/// This executes the statement returned by [`CopyOptions::copy_out`].
+pub async fn dump<C>(
+    client: &C,
+    source: &str,
+    options: &CopyOptions,
+) -> Result<CopyOutStream, Error>
+where
+    C: GenericClient + Sync,
+{
+    client.copy_out(&*options.copy_out(source)).await
+}
+
+/// Copies the data of a stream into a target, which is a table name optionally followed by a list of columns,
+/// returning the number of rows copied.
+///
+/// This executes the statement returned by [`CopyOptions::copy_in`]. The data must be in the format set by the
+/// options. If the stream returns an error, the copy is aborted and the error is returned.
+pub async fn restore<C, S, T>(
+    client: &C,
+    target: &str,
+    options: &CopyOptions,
+    data: S,
+) -> Result<u64, Error>
+where
+    C: GenericClient + Sync,
+    S: Stream<Item = Result<T, Error>>,
+    T: Buf + 'static + Send,
+{
+    let sink = client.copy_in(&*options.copy_in(target)).await?;
+    pin_mut!(sink);
+    pin_mut!(data);
+    sink.as_mut().send_all(&mut data).await?;
+    sink.finish().await
+}
+
+/// Copies data from a source on one connection into a target on another one, returning the number of rows copied.
+///
+/// The source and target are the ones of [`dump`] and [`restore`], and the data is exchanged in the format set by
+/// the options. With the binary format, the columns of the target must have the same types as the ones of the
+/// source.
+pub async fn transfer<C, D>(
+    source_client: &C,
+    source: &str,
+    target_client: &D,
+    target: &str,
+    options: &CopyOptions,
+) -> Result<u64, Error>
+where
+    C: GenericClient + Sync,
+    D: GenericClient + Sync,
+{
+    let data = dump(source_client, source, options).await?;
+    restore(target_client, target, options, data).await
+}
+
+// Escape string syntax is used so that the literals don't depend on `standard_conforming_strings`.
+fn literal(value: &str) -> String {
+    let mut literal = String::from("E'");
+    for c in value.chars() {
+        if c == '\'' || c == '\\' {
+            literal.push(c);
+        }
+        literal.push(c);
+    }
+    literal.push('\'');
+    literal
+}
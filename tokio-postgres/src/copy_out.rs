@@ -1,6 +1,7 @@
-use crate::client::{InnerClient, Responses};
+use crate::client::{InnerClient, RequestPermit, Responses};
 use crate::codec::FrontendMessage;
 use crate::connection::RequestMessages;
+use crate::instrument::Span;
 use crate::{query, slice_iter, Error, Statement};
 use bytes::Bytes;
 use futures_util::{ready, Stream};
@@ -14,16 +15,23 @@ use std::task::{Context, Poll};
 pub async fn copy_out(client: &InnerClient, statement: Statement) -> Result<CopyOutStream, Error> {
     debug!("executing copy out statement {}", statement.name());
 
+    let permit = client.reserve().await;
     let buf = query::encode(client, &statement, slice_iter(&[]))?;
-    let responses = start(client, buf).await?;
+    let responses = Span::copy_out(client.instrumentation(), &statement)
+        .instrument(start(client, permit, buf))
+        .await?;
     Ok(CopyOutStream {
         responses,
         _p: PhantomPinned,
     })
 }
 
-async fn start(client: &InnerClient, buf: Bytes) -> Result<Responses, Error> {
-    let mut responses = client.send(RequestMessages::Single(FrontendMessage::Raw(buf)))?;
+async fn start(
+    client: &InnerClient,
+    permit: RequestPermit,
+    buf: Bytes,
+) -> Result<Responses, Error> {
+    let mut responses = client.send(permit, RequestMessages::Single(FrontendMessage::Raw(buf)))?;
 
     match responses.next().await? {
         Message::ParseComplete => match responses.next().await? {
@@ -0,0 +1,78 @@
+use crate::client::{InnerClient, RequestPermit};
+use crate::codec::FrontendMessage;
+use crate::connection::RequestMessages;
+use crate::types::ToSql;
+use crate::{Client, Error, Row};
+use postgres_protocol::message::frontend;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A cursor declared `WITH HOLD`.
+///
+/// Unlike a `Portal`, a cursor isn't tied to the transaction in which it is declared: its remaining rows are kept by
+/// the server once that transaction commits, so they can be fetched in later transactions or outside of any. The cursor
+/// is closed when dropped.
+///
+/// Cursors can only be used with the connection that declared them.
+pub struct Cursor {
+    client: Weak<InnerClient>,
+    name: String,
+    closed: bool,
+}
+
+impl Drop for Cursor {
+    fn drop(&mut self) {
+        if self.closed {
+            return;
+        }
+
+        if let Some(client) = self.client.upgrade() {
+            let buf = client.with_buf(|buf| {
+                client.replace_unnamed_statement();
+                frontend::query(&format!("CLOSE {}", self.name), buf).unwrap();
+                buf.split().freeze()
+            });
+            let _ = client.send(
+                RequestPermit::exempt(),
+                RequestMessages::Single(FrontendMessage::Raw(buf)),
+            );
+        }
+    }
+}
+
+impl Cursor {
+    /// Returns the name of the cursor.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+pub(crate) async fn declare(
+    client: &Client,
+    query: &str,
+    params: &[&(dyn ToSql + Sync)],
+) -> Result<Cursor, Error> {
+    let name = format!("c{}", NEXT_ID.fetch_add(1, Ordering::SeqCst));
+    let declare = format!("DECLARE {} NO SCROLL CURSOR WITH HOLD FOR {}", name, query);
+    client.execute(&*declare, params).await?;
+
+    Ok(Cursor {
+        client: Arc::downgrade(client.inner()),
+        name,
+        closed: false,
+    })
+}
+
+pub(crate) async fn fetch(client: &Client, cursor: &Cursor, count: i64) -> Result<Vec<Row>, Error> {
+    let fetch = format!("FETCH FORWARD {} FROM {}", count, cursor.name);
+    client.query(&*fetch, &[]).await
+}
+
+pub(crate) async fn close(client: &Client, mut cursor: Cursor) -> Result<(), Error> {
+    cursor.closed = true;
+    client
+        .batch_execute(&format!("CLOSE {}", cursor.name))
+        .await
+}
@@ -0,0 +1,98 @@
+//! A connection pool manager for `deadpool`.
+//!
+//! A [`Manager`] opens connections with a [`Config`], and checks connections returned to the pool before they are
+//! handed out again: connections which have closed, which were returned in the middle of a transaction, or which
+//! don't answer a trivial query anymore are discarded.
+//!
+//! Requires the `deadpool` Cargo feature.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tokio_postgres::deadpool::{Manager, Pool};
+//! use tokio_postgres::NoTls;
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//!
+//! let config = "host=localhost user=postgres".parse()?;
+//! let pool = Pool::builder(Manager::new(config, NoTls))
+//!     .max_size(16)
+//!     .build()?;
+//!
+//! let client = pool.get().await?;
+//! let rows = client.query("SELECT name FROM users", &[]).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::tls::{MakeTlsConnect, TlsConnect};
+use crate::{Client, Config, Error, Socket, TransactionStatus};
+use async_trait::async_trait;
+use deadpool_0_10::managed::{self, Metrics, RecycleError, RecycleResult};
+use log::debug;
+
+/// A connection pool of clients opened by a [`Manager`].
+pub type Pool<T> = managed::Pool<Manager<T>>;
+
+/// A client checked out of a [`Pool`].
+pub type Object<T> = managed::Object<Manager<T>>;
+
+/// The error returned when a client can't be checked out of a [`Pool`].
+pub type PoolError = managed::PoolError<Error>;
+
+/// A `deadpool` manager opening and validating connections.
+///
+/// See the [module level documentation](self) for details.
+pub struct Manager<T> {
+    config: Config,
+    tls: T,
+}
+
+impl<T> Manager<T> {
+    /// Creates a manager opening connections with the given configuration and TLS connector.
+    ///
+    /// The connections are processed by tasks spawned on the current tokio runtime.
+    pub fn new(config: Config, tls: T) -> Manager<T> {
+        Manager { config, tls }
+    }
+
+    /// Returns the configuration used to open connections.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+}
+
+#[async_trait]
+impl<T> managed::Manager for Manager<T>
+where
+    T: MakeTlsConnect<Socket> + Clone + Send + Sync + 'static,
+    T::Stream: Send + 'static,
+    T::TlsConnect: Send,
+    <T::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    type Type = Client;
+    type Error = Error;
+
+    async fn create(&self) -> Result<Client, Error> {
+        let (client, connection) = self.config.connect(self.tls.clone()).await?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                debug!("connection error: {}", e);
+            }
+        });
+        Ok(client)
+    }
+
+    async fn recycle(&self, client: &mut Client, _: &Metrics) -> RecycleResult<Error> {
+        if client.is_closed() {
+            return Err(RecycleError::StaticMessage("connection closed"));
+        }
+        if client.transaction_status() != TransactionStatus::Idle {
+            return Err(RecycleError::StaticMessage(
+                "connection returned in a transaction",
+            ));
+        }
+
+        client.simple_query("").await?;
+        Ok(())
+    }
+}
@@ -2,6 +2,7 @@
 
 use fallible_iterator::FallibleIterator;
 use postgres_protocol::message::backend::{ErrorFields, ErrorResponseBody, Message};
+use postgres_types::Oid;
 use std::error::{self, Error as _Error};
 use std::fmt;
 use std::io;
@@ -305,6 +306,33 @@ impl DbError {
     pub fn routine(&self) -> Option<&str> {
         self.routine.as_deref()
     }
+
+    /// Determines if the error is transient, so that retrying the failed transaction can be expected to succeed.
+    ///
+    /// This is the case of serialization failures and deadlocks, which abort a transaction because of concurrent
+    /// ones.
+    pub fn is_retryable(&self) -> bool {
+        self.code == SqlState::T_R_SERIALIZATION_FAILURE
+            || self.code == SqlState::T_R_DEADLOCK_DETECTED
+    }
+
+    /// Returns the integrity constraint violated, if the error is an integrity constraint violation.
+    pub fn constraint_violation(&self) -> Option<ConstraintViolation<'_>> {
+        let kind = match self.code.class() {
+            SqlStateClass::IntegrityConstraintViolation => match self.code {
+                SqlState::NOT_NULL_VIOLATION => ConstraintKind::NotNull,
+                SqlState::FOREIGN_KEY_VIOLATION => ConstraintKind::ForeignKey,
+                SqlState::UNIQUE_VIOLATION => ConstraintKind::Unique,
+                SqlState::CHECK_VIOLATION => ConstraintKind::Check,
+                SqlState::EXCLUSION_VIOLATION => ConstraintKind::Exclusion,
+                SqlState::RESTRICT_VIOLATION => ConstraintKind::Restrict,
+                _ => ConstraintKind::Other,
+            },
+            _ => return None,
+        };
+
+        Some(ConstraintViolation { error: self, kind })
+    }
 }
 
 impl fmt::Display for DbError {
@@ -322,6 +350,69 @@ impl fmt::Display for DbError {
 
 impl error::Error for DbError {}
 
+/// The kind of an integrity constraint.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConstraintKind {
+    /// A `NOT NULL` constraint.
+    NotNull,
+    /// A foreign key.
+    ForeignKey,
+    /// A unique constraint or index, including primary keys.
+    Unique,
+    /// A `CHECK` constraint, of a table or a domain.
+    Check,
+    /// An exclusion constraint.
+    Exclusion,
+    /// A foreign key with an `ON DELETE RESTRICT` or `ON UPDATE RESTRICT` action.
+    Restrict,
+    /// A constraint reported with the generic `INTEGRITY_CONSTRAINT_VIOLATION` code, or a code unknown to this crate.
+    Other,
+}
+
+/// An integrity constraint violation, as reported by the server.
+///
+/// Returned by [`DbError::constraint_violation`].
+#[derive(Debug, Copy, Clone)]
+pub struct ConstraintViolation<'a> {
+    error: &'a DbError,
+    kind: ConstraintKind,
+}
+
+impl<'a> ConstraintViolation<'a> {
+    /// Returns the kind of the violated constraint.
+    pub fn kind(&self) -> ConstraintKind {
+        self.kind
+    }
+
+    /// Returns the name of the violated constraint, or of the unique index for unique violations.
+    ///
+    /// Not reported for `NOT NULL` violations, which are identified by their column instead.
+    pub fn constraint(&self) -> Option<&'a str> {
+        self.error.constraint()
+    }
+
+    /// Returns the name of the schema of the table, or of the domain for violations of domain constraints.
+    pub fn schema(&self) -> Option<&'a str> {
+        self.error.schema()
+    }
+
+    /// Returns the name of the table the constraint is defined on.
+    pub fn table(&self) -> Option<&'a str> {
+        self.error.table()
+    }
+
+    /// Returns the name of the column, for `NOT NULL` violations.
+    pub fn column(&self) -> Option<&'a str> {
+        self.error.column()
+    }
+
+    /// Returns the name of the domain, for violations of domain constraints.
+    pub fn datatype(&self) -> Option<&'a str> {
+        self.error.datatype()
+    }
+}
+
 /// Represents the position of an error in a query.
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum ErrorPosition {
@@ -341,8 +432,11 @@ enum Kind {
     Io,
     UnexpectedMessage(Message),
     Tls,
+    Gss,
+    Interceptor,
     ToSql(usize),
     FromSql(usize),
+    FromRow,
     Column(String),
     Parameters(usize, usize),
     Closed,
@@ -353,6 +447,10 @@ enum Kind {
     ConfigParse,
     Config,
     RowCount,
+    ColumnCount(usize),
+    UnknownType(Oid),
+    UnnamedStatement,
+    ForeignStatement,
     #[cfg(feature = "runtime")]
     Connect,
     Timeout,
@@ -383,8 +481,11 @@ impl fmt::Display for Error {
                 write!(fmt, "unexpected message from server: {:?}", msg)?
             }
             Kind::Tls => fmt.write_str("error performing TLS handshake")?,
+            Kind::Gss => fmt.write_str("error performing GSSAPI encryption handshake")?,
+            Kind::Interceptor => fmt.write_str("operation rejected by interceptor")?,
             Kind::ToSql(idx) => write!(fmt, "error serializing parameter {}", idx)?,
             Kind::FromSql(idx) => write!(fmt, "error deserializing column {}", idx)?,
+            Kind::FromRow => fmt.write_str("error deserializing row")?,
             Kind::Column(column) => write!(fmt, "invalid column `{}`", column)?,
             Kind::Parameters(real, expected) => {
                 write!(fmt, "expected {expected} parameters but got {real}")?
@@ -397,6 +498,14 @@ impl fmt::Display for Error {
             Kind::ConfigParse => fmt.write_str("invalid connection string")?,
             Kind::Config => fmt.write_str("invalid configuration")?,
             Kind::RowCount => fmt.write_str("query returned an unexpected number of rows")?,
+            Kind::ColumnCount(count) => write!(fmt, "expected 1 column but got {count}")?,
+            Kind::UnknownType(oid) => write!(fmt, "unknown type with OID {}", oid)?,
+            Kind::UnnamedStatement => {
+                fmt.write_str("unnamed statement was replaced by another query")?
+            }
+            Kind::ForeignStatement => {
+                fmt.write_str("statement was prepared by another connection")?
+            }
             #[cfg(feature = "runtime")]
             Kind::Connect => fmt.write_str("error connecting to server")?,
             Kind::Timeout => fmt.write_str("timeout waiting for server")?,
@@ -432,6 +541,11 @@ impl Error {
         self.0.kind == Kind::Closed
     }
 
+    /// Determines if the error was caused by a query exceeding its time limit.
+    pub fn is_timeout(&self) -> bool {
+        self.0.kind == Kind::Timeout
+    }
+
     /// Returns the SQLSTATE error code associated with the error.
     ///
     /// This is a convenience method that downcasts the cause to a `DbError` and returns its code.
@@ -439,6 +553,14 @@ impl Error {
         self.as_db_error().map(DbError::code)
     }
 
+    /// Determines if the error was caused by a transient condition, so that retrying the failed transaction can be
+    /// expected to succeed.
+    ///
+    /// This is a convenience method that downcasts the cause to a `DbError` and calls its `is_retryable` method.
+    pub fn is_retryable(&self) -> bool {
+        self.as_db_error().is_some_and(DbError::is_retryable)
+    }
+
     fn new(kind: Kind, cause: Option<Box<dyn error::Error + Sync + Send>>) -> Error {
         Error(Box::new(ErrorInner { kind, cause }))
     }
@@ -476,6 +598,10 @@ impl Error {
         Error::new(Kind::FromSql(idx), Some(e))
     }
 
+    pub(crate) fn from_row(e: Box<dyn error::Error + Sync + Send>) -> Error {
+        Error::new(Kind::FromRow, Some(e))
+    }
+
     pub(crate) fn column(column: String) -> Error {
         Error::new(Kind::Column(column), None)
     }
@@ -488,6 +614,14 @@ impl Error {
         Error::new(Kind::Tls, Some(e))
     }
 
+    pub(crate) fn gss(e: Box<dyn error::Error + Sync + Send>) -> Error {
+        Error::new(Kind::Gss, Some(e))
+    }
+
+    pub(crate) fn interceptor(e: Box<dyn error::Error + Sync + Send>) -> Error {
+        Error::new(Kind::Interceptor, Some(e))
+    }
+
     pub(crate) fn io(e: io::Error) -> Error {
         Error::new(Kind::Io, Some(Box::new(e)))
     }
@@ -508,13 +642,83 @@ impl Error {
         Error::new(Kind::RowCount, None)
     }
 
+    pub(crate) fn column_count(count: usize) -> Error {
+        Error::new(Kind::ColumnCount(count), None)
+    }
+
+    pub(crate) fn unknown_type(oid: Oid) -> Error {
+        Error::new(Kind::UnknownType(oid), None)
+    }
+
+    pub(crate) fn unnamed_statement() -> Error {
+        Error::new(Kind::UnnamedStatement, None)
+    }
+
+    pub(crate) fn foreign_statement() -> Error {
+        Error::new(Kind::ForeignStatement, None)
+    }
+
     #[cfg(feature = "runtime")]
     pub(crate) fn connect(e: io::Error) -> Error {
         Error::new(Kind::Connect, Some(Box::new(e)))
     }
 
+    pub(crate) fn timeout() -> Error {
+        Error::new(Kind::Timeout, None)
+    }
+
+    /// Determines if the error was caused by the network, so that retrying the operation may succeed.
+    #[cfg(feature = "runtime")]
+    pub(crate) fn is_network(&self) -> bool {
+        matches!(self.0.kind, Kind::Io | Kind::Connect | Kind::Timeout)
+    }
+
     #[doc(hidden)]
     pub fn __private_api_timeout() -> Error {
-        Error::new(Kind::Timeout, None)
+        Error::timeout()
+    }
+
+    #[doc(hidden)]
+    pub fn __private_api_closed() -> Error {
+        Error::closed()
+    }
+
+    #[doc(hidden)]
+    pub fn __private_api_io(e: io::Error) -> Error {
+        Error::io(e)
+    }
+
+    #[doc(hidden)]
+    pub fn __private_api_config(e: Box<dyn error::Error + Sync + Send>) -> Error {
+        Error::config(e)
+    }
+
+    #[doc(hidden)]
+    #[cfg(feature = "runtime")]
+    pub fn __private_api_connect(e: io::Error) -> Error {
+        Error::connect(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SqlState, SqlStateClass};
+
+    #[test]
+    fn sqlstate_class() {
+        assert_eq!(
+            SqlState::T_R_SERIALIZATION_FAILURE.class(),
+            SqlStateClass::TransactionRollback
+        );
+        assert_eq!(SqlState::NO_DATA.class(), SqlStateClass::NoData);
+        assert_eq!(
+            SqlState::from_code("08XYZ").class(),
+            SqlStateClass::ConnectionException
+        );
+        assert_eq!(
+            SqlState::from_code("ZZ001").class(),
+            SqlStateClass::Other("ZZ".into())
+        );
+        assert_eq!(SqlStateClass::PlPgsqlError.code(), "P0");
     }
 }
@@ -280,6 +280,57 @@ impl SqlState {
         }
     }
 
+    /// Returns the class of the `SqlState`, identified by the first two characters of its error code.
+    pub fn class(&self) -> SqlStateClass {
+        let code = self.code();
+        match code.get(..2).unwrap_or(code) {
+            "00" => SqlStateClass::SuccessfulCompletion,
+            "01" => SqlStateClass::Warning,
+            "02" => SqlStateClass::NoData,
+            "03" => SqlStateClass::SqlStatementNotYetComplete,
+            "08" => SqlStateClass::ConnectionException,
+            "09" => SqlStateClass::TriggeredActionException,
+            "0A" => SqlStateClass::FeatureNotSupported,
+            "0B" => SqlStateClass::InvalidTransactionInitiation,
+            "0F" => SqlStateClass::LocatorException,
+            "0L" => SqlStateClass::InvalidGrantor,
+            "0P" => SqlStateClass::InvalidRoleSpecification,
+            "0Z" => SqlStateClass::DiagnosticsException,
+            "20" => SqlStateClass::CaseNotFound,
+            "21" => SqlStateClass::CardinalityViolation,
+            "22" => SqlStateClass::DataException,
+            "23" => SqlStateClass::IntegrityConstraintViolation,
+            "24" => SqlStateClass::InvalidCursorState,
+            "25" => SqlStateClass::InvalidTransactionState,
+            "26" => SqlStateClass::InvalidSqlStatementName,
+            "27" => SqlStateClass::TriggeredDataChangeViolation,
+            "28" => SqlStateClass::InvalidAuthorizationSpecification,
+            "2B" => SqlStateClass::DependentPrivilegeDescriptorsStillExist,
+            "2D" => SqlStateClass::InvalidTransactionTermination,
+            "2F" => SqlStateClass::SqlRoutineException,
+            "34" => SqlStateClass::InvalidCursorName,
+            "38" => SqlStateClass::ExternalRoutineException,
+            "39" => SqlStateClass::ExternalRoutineInvocationException,
+            "3B" => SqlStateClass::SavepointException,
+            "3D" => SqlStateClass::InvalidCatalogName,
+            "3F" => SqlStateClass::InvalidSchemaName,
+            "40" => SqlStateClass::TransactionRollback,
+            "42" => SqlStateClass::SyntaxErrorOrAccessRuleViolation,
+            "44" => SqlStateClass::WithCheckOptionViolation,
+            "53" => SqlStateClass::InsufficientResources,
+            "54" => SqlStateClass::ProgramLimitExceeded,
+            "55" => SqlStateClass::ObjectNotInPrerequisiteState,
+            "57" => SqlStateClass::OperatorIntervention,
+            "58" => SqlStateClass::SystemError,
+            "72" => SqlStateClass::SnapshotFailure,
+            "F0" => SqlStateClass::ConfigurationFileError,
+            "HV" => SqlStateClass::ForeignDataWrapperError,
+            "P0" => SqlStateClass::PlPgsqlError,
+            "XX" => SqlStateClass::InternalError,
+            class => SqlStateClass::Other(class.into()),
+        }
+    }
+
     /// 00000
     pub const SUCCESSFUL_COMPLETION: SqlState = SqlState(Inner::E00000);
 
@@ -1081,6 +1132,152 @@ impl SqlState {
     pub const INDEX_CORRUPTED: SqlState = SqlState(Inner::EXX002);
 }
 
+/// The class of a SQLSTATE error code.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[non_exhaustive]
+pub enum SqlStateClass {
+    /// Class 00 - Successful Completion
+    SuccessfulCompletion,
+    /// Class 01 - Warning
+    Warning,
+    /// Class 02 - No Data (this is also a warning class per the SQL standard)
+    NoData,
+    /// Class 03 - SQL Statement Not Yet Complete
+    SqlStatementNotYetComplete,
+    /// Class 08 - Connection Exception
+    ConnectionException,
+    /// Class 09 - Triggered Action Exception
+    TriggeredActionException,
+    /// Class 0A - Feature Not Supported
+    FeatureNotSupported,
+    /// Class 0B - Invalid Transaction Initiation
+    InvalidTransactionInitiation,
+    /// Class 0F - Locator Exception
+    LocatorException,
+    /// Class 0L - Invalid Grantor
+    InvalidGrantor,
+    /// Class 0P - Invalid Role Specification
+    InvalidRoleSpecification,
+    /// Class 0Z - Diagnostics Exception
+    DiagnosticsException,
+    /// Class 20 - Case Not Found
+    CaseNotFound,
+    /// Class 21 - Cardinality Violation
+    CardinalityViolation,
+    /// Class 22 - Data Exception
+    DataException,
+    /// Class 23 - Integrity Constraint Violation
+    IntegrityConstraintViolation,
+    /// Class 24 - Invalid Cursor State
+    InvalidCursorState,
+    /// Class 25 - Invalid Transaction State
+    InvalidTransactionState,
+    /// Class 26 - Invalid SQL Statement Name
+    InvalidSqlStatementName,
+    /// Class 27 - Triggered Data Change Violation
+    TriggeredDataChangeViolation,
+    /// Class 28 - Invalid Authorization Specification
+    InvalidAuthorizationSpecification,
+    /// Class 2B - Dependent Privilege Descriptors Still Exist
+    DependentPrivilegeDescriptorsStillExist,
+    /// Class 2D - Invalid Transaction Termination
+    InvalidTransactionTermination,
+    /// Class 2F - SQL Routine Exception
+    SqlRoutineException,
+    /// Class 34 - Invalid Cursor Name
+    InvalidCursorName,
+    /// Class 38 - External Routine Exception
+    ExternalRoutineException,
+    /// Class 39 - External Routine Invocation Exception
+    ExternalRoutineInvocationException,
+    /// Class 3B - Savepoint Exception
+    SavepointException,
+    /// Class 3D - Invalid Catalog Name
+    InvalidCatalogName,
+    /// Class 3F - Invalid Schema Name
+    InvalidSchemaName,
+    /// Class 40 - Transaction Rollback
+    TransactionRollback,
+    /// Class 42 - Syntax Error or Access Rule Violation
+    SyntaxErrorOrAccessRuleViolation,
+    /// Class 44 - WITH CHECK OPTION Violation
+    WithCheckOptionViolation,
+    /// Class 53 - Insufficient Resources
+    InsufficientResources,
+    /// Class 54 - Program Limit Exceeded
+    ProgramLimitExceeded,
+    /// Class 55 - Object Not In Prerequisite State
+    ObjectNotInPrerequisiteState,
+    /// Class 57 - Operator Intervention
+    OperatorIntervention,
+    /// Class 58 - System Error (errors external to PostgreSQL itself)
+    SystemError,
+    /// Class 72 - Snapshot Failure
+    SnapshotFailure,
+    /// Class F0 - Configuration File Error
+    ConfigurationFileError,
+    /// Class HV - Foreign Data Wrapper Error (SQL/MED)
+    ForeignDataWrapperError,
+    /// Class P0 - PL/pgSQL Error
+    PlPgsqlError,
+    /// Class XX - Internal Error
+    InternalError,
+    /// A class not known to this crate.
+    Other(Box<str>),
+}
+
+impl SqlStateClass {
+    /// Returns the two character code of the class.
+    pub fn code(&self) -> &str {
+        match self {
+            SqlStateClass::SuccessfulCompletion => "00",
+            SqlStateClass::Warning => "01",
+            SqlStateClass::NoData => "02",
+            SqlStateClass::SqlStatementNotYetComplete => "03",
+            SqlStateClass::ConnectionException => "08",
+            SqlStateClass::TriggeredActionException => "09",
+            SqlStateClass::FeatureNotSupported => "0A",
+            SqlStateClass::InvalidTransactionInitiation => "0B",
+            SqlStateClass::LocatorException => "0F",
+            SqlStateClass::InvalidGrantor => "0L",
+            SqlStateClass::InvalidRoleSpecification => "0P",
+            SqlStateClass::DiagnosticsException => "0Z",
+            SqlStateClass::CaseNotFound => "20",
+            SqlStateClass::CardinalityViolation => "21",
+            SqlStateClass::DataException => "22",
+            SqlStateClass::IntegrityConstraintViolation => "23",
+            SqlStateClass::InvalidCursorState => "24",
+            SqlStateClass::InvalidTransactionState => "25",
+            SqlStateClass::InvalidSqlStatementName => "26",
+            SqlStateClass::TriggeredDataChangeViolation => "27",
+            SqlStateClass::InvalidAuthorizationSpecification => "28",
+            SqlStateClass::DependentPrivilegeDescriptorsStillExist => "2B",
+            SqlStateClass::InvalidTransactionTermination => "2D",
+            SqlStateClass::SqlRoutineException => "2F",
+            SqlStateClass::InvalidCursorName => "34",
+            SqlStateClass::ExternalRoutineException => "38",
+            SqlStateClass::ExternalRoutineInvocationException => "39",
+            SqlStateClass::SavepointException => "3B",
+            SqlStateClass::InvalidCatalogName => "3D",
+            SqlStateClass::InvalidSchemaName => "3F",
+            SqlStateClass::TransactionRollback => "40",
+            SqlStateClass::SyntaxErrorOrAccessRuleViolation => "42",
+            SqlStateClass::WithCheckOptionViolation => "44",
+            SqlStateClass::InsufficientResources => "53",
+            SqlStateClass::ProgramLimitExceeded => "54",
+            SqlStateClass::ObjectNotInPrerequisiteState => "55",
+            SqlStateClass::OperatorIntervention => "57",
+            SqlStateClass::SystemError => "58",
+            SqlStateClass::SnapshotFailure => "72",
+            SqlStateClass::ConfigurationFileError => "F0",
+            SqlStateClass::ForeignDataWrapperError => "HV",
+            SqlStateClass::PlPgsqlError => "P0",
+            SqlStateClass::InternalError => "XX",
+            SqlStateClass::Other(code) => code,
+        }
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Debug)]
 #[allow(clippy::upper_case_acronyms)]
 enum Inner {
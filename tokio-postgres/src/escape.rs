@@ -0,0 +1,272 @@
+//! Client-side quoting of literals and identifiers.
+//!
+//! Parameters should be bound by the server whenever possible, with `query` and its variants. Some statements can't
+//! take parameters though, like DDL statements or `SET`, and some connection poolers don't support the extended query
+//! protocol at all. In those cases, values can be quoted with this module and interpolated into a parameter-free
+//! query run with `simple_query` or `batch_execute`.
+//!
+//! The [`format_sql!`](crate::format_sql) macro builds such a query like `format!`, quoting each of its arguments as
+//! a literal, or as an identifier if it is wrapped in [`Ident`]. The [`Literal`] and [`Ident`] wrappers can also be
+//! used on their own, since they quote their values when they are formatted.
+//!
+//! # Examples
+//!
+//! ```
+//! use tokio_postgres::escape::Ident;
+//! use tokio_postgres::format_sql;
+//!
+//! let role = "app's role";
+//! let timeout: Option<i32> = Some(5000);
+//! let query = format_sql!(
+//!     "SET ROLE {}; SET statement_timeout = {}",
+//!     Ident(role),
+//!     timeout,
+//! );
+//! assert_eq!(query, r#"SET ROLE "app's role"; SET statement_timeout = 5000"#);
+//! ```
+
+use postgres_protocol::escape::{escape_identifier, escape_literal};
+use std::fmt;
+use std::fmt::Write;
+
+/// Quotes a string as a SQL string literal, surrounding it with single quotes.
+///
+/// If the string contains backslashes, the literal is written with the escape string syntax, like ` E'a\\b'`, so that
+/// it is read correctly regardless of the `standard_conforming_strings` setting of the server.
+pub fn quote_literal(s: &str) -> String {
+    escape_literal(s)
+}
+
+/// Quotes a string as a SQL identifier, surrounding it with double quotes.
+///
+/// Quoted identifiers are case sensitive: `quote_ident("Users")` refers to a different table than the unquoted
+/// `Users`, which is folded to lower case by the server.
+pub fn quote_ident(s: &str) -> String {
+    escape_identifier(s)
+}
+
+/// A trait for types which can be written as SQL literals.
+///
+/// Literals must be self-contained, so that they can't merge with the surrounding text: negative numbers are quoted,
+/// since `5 -{}` would otherwise become the start of a `--` comment.
+pub trait ToLiteral {
+    /// Appends the value to `out` as a SQL literal.
+    fn write_literal(&self, out: &mut String);
+}
+
+impl<T: ToLiteral + ?Sized> ToLiteral for &T {
+    fn write_literal(&self, out: &mut String) {
+        (**self).write_literal(out)
+    }
+}
+
+/// `None` is written as `NULL`.
+impl<T: ToLiteral> ToLiteral for Option<T> {
+    fn write_literal(&self, out: &mut String) {
+        match self {
+            Some(value) => value.write_literal(out),
+            None => out.push_str("NULL"),
+        }
+    }
+}
+
+impl ToLiteral for str {
+    fn write_literal(&self, out: &mut String) {
+        out.push_str(&escape_literal(self))
+    }
+}
+
+impl ToLiteral for String {
+    fn write_literal(&self, out: &mut String) {
+        self.as_str().write_literal(out)
+    }
+}
+
+impl ToLiteral for bool {
+    fn write_literal(&self, out: &mut String) {
+        out.push_str(if *self { "TRUE" } else { "FALSE" })
+    }
+}
+
+macro_rules! integer_literal {
+    ($($t:ty),*) => {
+        $(
+            impl ToLiteral for $t {
+                fn write_literal(&self, out: &mut String) {
+                    write_number(out, &self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+integer_literal!(i8, i16, i32, i64, u8, u16, u32, u64);
+
+// Negative numbers are quoted, and cast by the server to the type expected from the context.
+fn write_number(out: &mut String, number: &str) {
+    if number.starts_with('-') {
+        write!(out, "'{}'", number).unwrap()
+    } else {
+        out.push_str(number)
+    }
+}
+
+macro_rules! float_literal {
+    ($($t:ty),*) => {
+        $(
+            /// Non-finite values are written as the quoted `'NaN'`, `'Infinity'` and `'-Infinity'` strings, which the
+            /// server casts to floating point types.
+            impl ToLiteral for $t {
+                fn write_literal(&self, out: &mut String) {
+                    if self.is_nan() {
+                        out.push_str("'NaN'")
+                    } else if self.is_infinite() {
+                        out.push_str(if *self > 0. { "'Infinity'" } else { "'-Infinity'" })
+                    } else {
+                        write_number(out, &format!("{:?}", self))
+                    }
+                }
+            }
+        )*
+    };
+}
+
+float_literal!(f32, f64);
+
+/// Bytes are written as a `bytea` literal in the hex format.
+impl ToLiteral for [u8] {
+    fn write_literal(&self, out: &mut String) {
+        out.push_str(" E'\\\\x");
+        for byte in self {
+            write!(out, "{:02x}", byte).unwrap();
+        }
+        out.push('\'')
+    }
+}
+
+impl ToLiteral for Vec<u8> {
+    fn write_literal(&self, out: &mut String) {
+        self.as_slice().write_literal(out)
+    }
+}
+
+/// A wrapper formatting a value as a SQL literal.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Literal<T>(pub T);
+
+impl<T: ToLiteral> fmt::Display for Literal<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+        self.0.write_literal(&mut out);
+        fmt.write_str(&out)
+    }
+}
+
+/// A wrapper formatting a string as a quoted SQL identifier.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Ident<T>(pub T);
+
+impl<T: AsRef<str>> fmt::Display for Ident<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str(&escape_identifier(self.0.as_ref()))
+    }
+}
+
+/// A trait for the arguments of the [`format_sql!`](crate::format_sql) macro.
+///
+/// Values implementing [`ToLiteral`] are quoted as literals, and values wrapped in [`Ident`] as identifiers.
+pub trait ToSqlArg {
+    /// Appends the quoted value to `out`.
+    fn write_sql_arg(&self, out: &mut String);
+}
+
+impl<T: ToLiteral + ?Sized> ToSqlArg for T {
+    fn write_sql_arg(&self, out: &mut String) {
+        self.write_literal(out)
+    }
+}
+
+impl<T: AsRef<str>> ToSqlArg for Ident<T> {
+    fn write_sql_arg(&self, out: &mut String) {
+        out.push_str(&escape_identifier(self.0.as_ref()))
+    }
+}
+
+impl<T: ToLiteral> ToSqlArg for Literal<T> {
+    fn write_sql_arg(&self, out: &mut String) {
+        self.0.write_literal(out)
+    }
+}
+
+#[doc(hidden)]
+pub struct SqlArg<'a, T: ?Sized>(pub &'a T);
+
+impl<T: ToSqlArg + ?Sized> fmt::Display for SqlArg<'_, T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+        self.0.write_sql_arg(&mut out);
+        fmt.write_str(&out)
+    }
+}
+
+/// Builds a query without parameters like `format!`, quoting its arguments.
+///
+/// Arguments are quoted as literals, or as identifiers if they are wrapped in [`Ident`](crate::escape::Ident), as
+/// detailed in the [`escape`](crate::escape) module. Arguments are taken by reference, and the format string is left
+/// as is.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_postgres::escape::Ident;
+/// use tokio_postgres::format_sql;
+///
+/// let query = format_sql!("SELECT * FROM {} WHERE id = 5 -{}", Ident("my table"), -3);
+/// assert_eq!(query, r#"SELECT * FROM "my table" WHERE id = 5 -'-3'"#);
+/// ```
+#[macro_export]
+macro_rules! format_sql {
+    ($fmt:literal $(, $arg:expr)* $(,)?) => {
+        format!($fmt $(, $crate::escape::SqlArg(&$arg))*)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Ident, Literal};
+
+    #[test]
+    fn literals() {
+        assert_eq!(Literal("it's").to_string(), "'it''s'");
+        assert_eq!(Literal(r"a\b").to_string(), r" E'a\\b'");
+        assert_eq!(Literal(None::<&str>).to_string(), "NULL");
+        assert_eq!(Literal(Some(-3i64)).to_string(), "'-3'");
+        assert_eq!(Literal(3u8).to_string(), "3");
+        assert_eq!(Literal(-1.5f32).to_string(), "'-1.5'");
+        assert_eq!(Literal(true).to_string(), "TRUE");
+        assert_eq!(Literal(1.5f64).to_string(), "1.5");
+        assert_eq!(Literal(1e100f64).to_string(), "1e100");
+        assert_eq!(Literal(f64::NEG_INFINITY).to_string(), "'-Infinity'");
+        assert_eq!(Literal(&[0u8, 255][..]).to_string(), r" E'\\x00ff'");
+        assert_eq!(Ident("my \"table\"").to_string(), r#""my ""table""""#);
+    }
+
+    #[test]
+    fn format_sql() {
+        let name = "it's".to_string();
+        assert_eq!(
+            format_sql!(
+                "SELECT 5 -{} FROM {} WHERE name = {} AND {}",
+                -3,
+                Ident("t"),
+                name,
+                None::<bool>,
+            ),
+            r#"SELECT 5 -'-3' FROM "t" WHERE name = 'it''s' AND NULL"#
+        );
+        assert_eq!(
+            format_sql!("SELECT {}", Literal(&[1u8][..])),
+            r"SELECT  E'\\x01'"
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use crate::client::{InnerClient, RequestPermit, Responses};
+use crate::codec::FrontendMessage;
+use crate::connection::RequestMessages;
+use crate::interceptor::{Completion, Operation};
+use crate::query::{self, extract_row_affected};
+use crate::types::BorrowToSql;
+use crate::{Error, Statement};
+use bytes::{BufMut, BytesMut};
+use postgres_protocol::message::backend::Message;
+use postgres_protocol::message::frontend;
+use std::error;
+use std::fmt;
+
+// The length of the Sync message ending the messages encoded by `encode_execute`.
+const SYNC_LEN: usize = 5;
+
+/// An error executing a statement with `Client::execute_many`.
+///
+/// Since all rows are executed in a single implicit transaction, none of them take effect when one fails.
+#[derive(Debug)]
+pub struct ExecuteManyError {
+    row: Option<usize>,
+    error: Error,
+}
+
+impl ExecuteManyError {
+    pub(crate) fn new(row: Option<usize>, error: Error) -> ExecuteManyError {
+        ExecuteManyError { row, error }
+    }
+
+    /// Returns the index of the row of parameters which failed, or `None` if the error isn't specific to a row, like
+    /// a failure to prepare the statement.
+    pub fn row(&self) -> Option<usize> {
+        self.row
+    }
+
+    /// Returns the underlying error.
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+
+    /// Consumes the `ExecuteManyError`, returning the underlying error.
+    pub fn into_error(self) -> Error {
+        self.error
+    }
+}
+
+impl fmt::Display for ExecuteManyError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.row {
+            Some(row) => write!(fmt, "error executing row {}: {}", row, self.error),
+            None => fmt::Display::fmt(&self.error, fmt),
+        }
+    }
+}
+
+impl error::Error for ExecuteManyError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<Error> for ExecuteManyError {
+    fn from(error: Error) -> ExecuteManyError {
+        ExecuteManyError::new(None, error)
+    }
+}
+
+pub async fn execute_many<P, I, R>(
+    client: &InnerClient,
+    statement: &Statement,
+    rows: R,
+) -> Result<u64, ExecuteManyError>
+where
+    P: BorrowToSql,
+    I: IntoIterator<Item = P>,
+    I::IntoIter: ExactSizeIterator,
+    R: IntoIterator<Item = I>,
+{
+    let interceptors = client.interceptors();
+    let permit = client.reserve().await;
+    let mut buf = BytesMut::new();
+    let mut count = 0;
+    for (idx, params) in rows.into_iter().enumerate() {
+        let encoded =
+            query::encode_execute(client, &interceptors, Operation::Execute, statement, params)
+                .map_err(|e| ExecuteManyError::new(Some(idx), e))?;
+        // The rows are synced once all of them are executed.
+        buf.put_slice(&encoded[..encoded.len() - SYNC_LEN]);
+        count += 1;
+    }
+    if count == 0 {
+        return Ok(0);
+    }
+    frontend::sync(&mut buf);
+
+    let mut completion = Completion::new(client, Operation::Execute, None);
+    let result = read_results(client, permit, buf, count).await;
+    completion.complete(
+        Some(statement),
+        result
+            .as_ref()
+            .map(|rows| Some(*rows))
+            .map_err(|e| &e.error),
+    );
+    result
+}
+
+async fn read_results(
+    client: &InnerClient,
+    permit: RequestPermit,
+    buf: BytesMut,
+    count: usize,
+) -> Result<u64, ExecuteManyError> {
+    let mut responses = client.send(
+        permit,
+        RequestMessages::Single(FrontendMessage::Raw(buf.freeze())),
+    )?;
+
+    let mut total = 0;
+    for idx in 0..count {
+        total += read_result(&mut responses)
+            .await
+            .map_err(|e| ExecuteManyError::new(Some(idx), e))?;
+    }
+
+    match responses.next().await? {
+        Message::ReadyForQuery(_) => Ok(total),
+        m => Err(Error::unexpected_message(m).into()),
+    }
+}
+
+async fn read_result(responses: &mut Responses) -> Result<u64, Error> {
+    match responses.next().await? {
+        Message::BindComplete => {}
+        m => return Err(Error::unexpected_message(m)),
+    }
+
+    loop {
+        match responses.next().await? {
+            Message::DataRow(_) => {}
+            Message::CommandComplete(body) => return extract_row_affected(&body),
+            Message::EmptyQueryResponse => return Ok(0),
+            m => return Err(Error::unexpected_message(m)),
+        }
+    }
+}
@@ -0,0 +1,347 @@
+//! Structured query plans.
+//!
+//! [`Client::explain`] runs `EXPLAIN (FORMAT JSON)` on a query and returns the plan chosen by the server as a tree of
+//! [`PlanNode`]s.
+//!
+//! Requires the `with-serde_json-1` Cargo feature.
+//!
+//! [`Client::explain`]: crate::Client::explain
+
+use serde_json_1::{Map, Value};
+use std::error;
+use std::fmt;
+use std::time::Duration;
+
+/// Options controlling the output of `EXPLAIN`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct ExplainOptions {
+    analyze: bool,
+    buffers: bool,
+    verbose: bool,
+    timing: Option<bool>,
+}
+
+impl ExplainOptions {
+    /// Creates options producing the estimated plan only.
+    pub fn new() -> ExplainOptions {
+        ExplainOptions::default()
+    }
+
+    /// Controls whether the query is actually executed, so that the plan includes actual row counts and timings.
+    ///
+    /// Note that with this option, the query is executed with all of its side effects. Wrap data-modifying queries in
+    /// a transaction which is rolled back to avoid them. Defaults to `false`.
+    pub fn analyze(mut self, analyze: bool) -> ExplainOptions {
+        self.analyze = analyze;
+        self
+    }
+
+    /// Controls whether the plan includes buffer usage.
+    ///
+    /// Defaults to `false`.
+    pub fn buffers(mut self, buffers: bool) -> ExplainOptions {
+        self.buffers = buffers;
+        self
+    }
+
+    /// Controls whether the plan includes additional information such as output columns.
+    ///
+    /// Defaults to `false`.
+    pub fn verbose(mut self, verbose: bool) -> ExplainOptions {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Controls whether the actual time spent in each node is measured when the query is analyzed.
+    ///
+    /// Defaults to the server's default, which is to measure it.
+    pub fn timing(mut self, timing: bool) -> ExplainOptions {
+        self.timing = Some(timing);
+        self
+    }
+
+    pub(crate) fn to_sql(self, query: &str) -> String {
+        let mut sql = String::from("EXPLAIN (FORMAT JSON");
+        if self.analyze {
+            sql.push_str(", ANALYZE");
+        }
+        if self.buffers {
+            sql.push_str(", BUFFERS");
+        }
+        if self.verbose {
+            sql.push_str(", VERBOSE");
+        }
+        match self.timing {
+            Some(true) => sql.push_str(", TIMING TRUE"),
+            Some(false) => sql.push_str(", TIMING FALSE"),
+            None => {}
+        }
+        sql.push_str(") ");
+        sql.push_str(query);
+        sql
+    }
+}
+
+/// A query plan.
+#[derive(Debug, Clone)]
+pub struct Plan {
+    root: PlanNode,
+    planning_time: Option<Duration>,
+    execution_time: Option<Duration>,
+}
+
+impl Plan {
+    /// Parses the output of `EXPLAIN (FORMAT JSON)`.
+    pub fn from_json(value: &Value) -> Result<Plan, Box<dyn error::Error + Sync + Send>> {
+        let plan = value
+            .as_array()
+            .and_then(|plans| plans.first())
+            .and_then(Value::as_object)
+            .ok_or(InvalidPlan("expected an array of plans"))?;
+
+        Ok(Plan {
+            root: PlanNode::from_json(plan.get("Plan").ok_or(InvalidPlan("missing plan"))?)?,
+            planning_time: milliseconds(plan, "Planning Time"),
+            execution_time: milliseconds(plan, "Execution Time"),
+        })
+    }
+
+    /// Returns the root node of the plan.
+    pub fn root(&self) -> &PlanNode {
+        &self.root
+    }
+
+    /// Returns the time taken to plan the query, if the query was analyzed.
+    pub fn planning_time(&self) -> Option<Duration> {
+        self.planning_time
+    }
+
+    /// Returns the time taken to execute the query, if the query was analyzed.
+    pub fn execution_time(&self) -> Option<Duration> {
+        self.execution_time
+    }
+}
+
+/// A node of a query plan.
+#[derive(Debug, Clone)]
+pub struct PlanNode {
+    node_type: String,
+    startup_cost: f64,
+    total_cost: f64,
+    plan_rows: f64,
+    plan_width: u64,
+    actual_startup_time: Option<Duration>,
+    actual_total_time: Option<Duration>,
+    actual_rows: Option<f64>,
+    actual_loops: Option<f64>,
+    buffers: Option<Buffers>,
+    children: Vec<PlanNode>,
+    properties: Map<String, Value>,
+}
+
+impl PlanNode {
+    fn from_json(value: &Value) -> Result<PlanNode, Box<dyn error::Error + Sync + Send>> {
+        let node = value
+            .as_object()
+            .ok_or(InvalidPlan("expected a plan node"))?;
+
+        let children = match node.get("Plans") {
+            Some(Value::Array(plans)) => plans
+                .iter()
+                .map(PlanNode::from_json)
+                .collect::<Result<_, _>>()?,
+            Some(_) => return Err(InvalidPlan("expected an array of child plans").into()),
+            None => vec![],
+        };
+
+        let properties = node
+            .iter()
+            .filter(|(key, _)| *key != "Plans")
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
+        Ok(PlanNode {
+            node_type: node
+                .get("Node Type")
+                .and_then(Value::as_str)
+                .ok_or(InvalidPlan("missing node type"))?
+                .to_string(),
+            startup_cost: number(node, "Startup Cost")?,
+            total_cost: number(node, "Total Cost")?,
+            plan_rows: number(node, "Plan Rows")?,
+            plan_width: number(node, "Plan Width")? as u64,
+            actual_startup_time: milliseconds(node, "Actual Startup Time"),
+            actual_total_time: milliseconds(node, "Actual Total Time"),
+            actual_rows: node.get("Actual Rows").and_then(Value::as_f64),
+            actual_loops: node.get("Actual Loops").and_then(Value::as_f64),
+            buffers: Buffers::from_json(node),
+            children,
+            properties,
+        })
+    }
+
+    /// Returns the type of the node, such as `Seq Scan` or `Hash Join`.
+    pub fn node_type(&self) -> &str {
+        &self.node_type
+    }
+
+    /// Returns the name of the relation scanned by the node, if any.
+    pub fn relation_name(&self) -> Option<&str> {
+        self.property("Relation Name").and_then(Value::as_str)
+    }
+
+    /// Returns the estimated cost of the node before it can return its first row.
+    pub fn startup_cost(&self) -> f64 {
+        self.startup_cost
+    }
+
+    /// Returns the estimated cost of the node to return all of its rows.
+    pub fn total_cost(&self) -> f64 {
+        self.total_cost
+    }
+
+    /// Returns the estimated number of rows returned by the node.
+    pub fn plan_rows(&self) -> f64 {
+        self.plan_rows
+    }
+
+    /// Returns the estimated average width of the rows returned by the node, in bytes.
+    pub fn plan_width(&self) -> u64 {
+        self.plan_width
+    }
+
+    /// Returns the actual time taken by the node to return its first row, averaged over its loops.
+    ///
+    /// This is only available if the query was analyzed with timing enabled.
+    pub fn actual_startup_time(&self) -> Option<Duration> {
+        self.actual_startup_time
+    }
+
+    /// Returns the actual time taken by the node to return all of its rows, averaged over its loops.
+    ///
+    /// This is only available if the query was analyzed with timing enabled.
+    pub fn actual_total_time(&self) -> Option<Duration> {
+        self.actual_total_time
+    }
+
+    /// Returns the actual number of rows returned by the node, averaged over its loops.
+    ///
+    /// This is only available if the query was analyzed.
+    pub fn actual_rows(&self) -> Option<f64> {
+        self.actual_rows
+    }
+
+    /// Returns the number of times the node was executed.
+    ///
+    /// This is only available if the query was analyzed.
+    pub fn actual_loops(&self) -> Option<f64> {
+        self.actual_loops
+    }
+
+    /// Returns the buffer usage of the node.
+    ///
+    /// This is only available if the query was explained with the `buffers` option.
+    pub fn buffers(&self) -> Option<&Buffers> {
+        self.buffers.as_ref()
+    }
+
+    /// Returns the child nodes of the node.
+    pub fn children(&self) -> &[PlanNode] {
+        &self.children
+    }
+
+    /// Returns a property of the node by its name in the JSON output, such as `Filter` or `Index Name`.
+    ///
+    /// This gives access to the properties specific to some node types which aren't otherwise exposed.
+    pub fn property(&self, name: &str) -> Option<&Value> {
+        self.properties.get(name)
+    }
+
+    /// Returns an iterator over the node and all of its descendants, in depth-first order.
+    pub fn iter(&self) -> impl Iterator<Item = &PlanNode> + '_ {
+        let mut stack = vec![self];
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(node.children.iter().rev());
+            Some(node)
+        })
+    }
+}
+
+/// The buffer usage of a plan node, in blocks.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Buffers {
+    shared_hit: u64,
+    shared_read: u64,
+    shared_dirtied: u64,
+    shared_written: u64,
+    temp_read: u64,
+    temp_written: u64,
+}
+
+impl Buffers {
+    fn from_json(node: &Map<String, Value>) -> Option<Buffers> {
+        let blocks = |key| node.get(key).and_then(Value::as_u64);
+        Some(Buffers {
+            shared_hit: blocks("Shared Hit Blocks")?,
+            shared_read: blocks("Shared Read Blocks")?,
+            shared_dirtied: blocks("Shared Dirtied Blocks")?,
+            shared_written: blocks("Shared Written Blocks")?,
+            temp_read: blocks("Temp Read Blocks")?,
+            temp_written: blocks("Temp Written Blocks")?,
+        })
+    }
+
+    /// Returns the number of shared blocks found in the buffer cache.
+    pub fn shared_hit(&self) -> u64 {
+        self.shared_hit
+    }
+
+    /// Returns the number of shared blocks read from disk.
+    pub fn shared_read(&self) -> u64 {
+        self.shared_read
+    }
+
+    /// Returns the number of shared blocks modified.
+    pub fn shared_dirtied(&self) -> u64 {
+        self.shared_dirtied
+    }
+
+    /// Returns the number of shared blocks written to disk.
+    pub fn shared_written(&self) -> u64 {
+        self.shared_written
+    }
+
+    /// Returns the number of temporary blocks read.
+    pub fn temp_read(&self) -> u64 {
+        self.temp_read
+    }
+
+    /// Returns the number of temporary blocks written.
+    pub fn temp_written(&self) -> u64 {
+        self.temp_written
+    }
+}
+
+fn number(node: &Map<String, Value>, key: &'static str) -> Result<f64, InvalidPlan> {
+    node.get(key)
+        .and_then(Value::as_f64)
+        .ok_or(InvalidPlan(key))
+}
+
+fn milliseconds(node: &Map<String, Value>, key: &str) -> Option<Duration> {
+    node.get(key)
+        .and_then(Value::as_f64)
+        .map(|ms| Duration::from_secs_f64(ms.max(0.) / 1000.))
+}
+
+#[derive(Debug)]
+struct InvalidPlan(&'static str);
+
+impl fmt::Display for InvalidPlan {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "invalid plan: {}", self.0)
+    }
+}
+
+impl error::Error for InvalidPlan {}
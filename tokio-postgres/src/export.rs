@@ -0,0 +1,378 @@
+//! Export of the rows of a query as CSV or newline-delimited JSON.
+//!
+//! [`ExportStream`] renders the rows of a [`RowStream`] as they are received, so a query result can be sent as the
+//! body of an HTTP response without being buffered in memory.
+//!
+//! ```no_run
+//! use futures_util::TryStreamExt;
+//! use tokio_postgres::export::{ExportFormat, ExportStream};
+//! # async fn f(client: &tokio_postgres::Client) -> Result<(), tokio_postgres::Error> {
+//!
+//! let rows = client
+//!     .query_raw_txt("SELECT id, name FROM users", Vec::<Option<&str>>::new())
+//!     .await?;
+//! let body = ExportStream::new(rows, ExportFormat::Ndjson)
+//!     .try_fold(Vec::new(), |mut body, chunk| async move {
+//!         body.extend_from_slice(&chunk);
+//!         Ok(body)
+//!     })
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::types::{FromSql, Kind, Type};
+use crate::{Column, Error, Row, RowStream};
+use bytes::Bytes;
+use futures_util::{ready, Stream};
+use pin_project_lite::pin_project;
+use postgres_types::Format;
+use std::borrow::Cow;
+use std::fmt::Write;
+use std::pin::Pin;
+use std::str;
+use std::task::{Context, Poll};
+
+/// The format rows are exported in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ExportFormat {
+    /// Comma-separated values, with a header line of the column names.
+    ///
+    /// Values are rendered as in a `COPY ... (FORMAT csv)`: `NULL` is an empty field and an empty string is a quoted
+    /// empty field.
+    Csv,
+    /// One JSON object per line, mapping column names to values.
+    ///
+    /// Booleans and numbers are rendered as JSON booleans and numbers, except for the special floating point and
+    /// `NUMERIC` values, which are rendered as strings. `JSON` and `JSONB` values are embedded as they are, and other
+    /// values are rendered as strings.
+    Ndjson,
+}
+
+pin_project! {
+    /// A stream of the rows of a query, rendered as CSV or newline-delimited JSON.
+    ///
+    /// Each item of the stream holds a line of the output. Rows can be in either format: rows in the text format,
+    /// returned by `query_raw_txt`, can hold values of any type, while rows in the binary format can hold booleans,
+    /// integers, floating point numbers, `NUMERIC`, text, enums, `BYTEA`, `UUID`, `JSON` and `JSONB` values, along
+    /// with domains over those types. Values of other types in the binary format result in an error.
+    pub struct ExportStream {
+        #[pin]
+        rows: RowStream,
+        format: ExportFormat,
+        header: bool,
+        done: bool,
+    }
+}
+
+impl ExportStream {
+    /// Creates a stream rendering the rows of a `RowStream` in the provided format.
+    pub fn new(rows: RowStream, format: ExportFormat) -> ExportStream {
+        ExportStream {
+            rows,
+            format,
+            header: format == ExportFormat::Csv,
+            done: false,
+        }
+    }
+}
+
+impl Stream for ExportStream {
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        if *this.done {
+            return Poll::Ready(None);
+        }
+
+        let mut buf = String::new();
+        match ready!(this.rows.as_mut().poll_next(cx)) {
+            Some(Ok(row)) => {
+                if *this.header {
+                    *this.header = false;
+                    write_header(row.columns(), &mut buf);
+                }
+                match write_row(&row, *this.format, &mut buf) {
+                    Ok(()) => Poll::Ready(Some(Ok(Bytes::from(buf)))),
+                    Err(e) => Poll::Ready(Some(Err(e))),
+                }
+            }
+            Some(Err(e)) => Poll::Ready(Some(Err(e))),
+            None => {
+                *this.done = true;
+                match this.rows.columns() {
+                    Some(columns) if *this.header => {
+                        write_header(columns, &mut buf);
+                        Poll::Ready(Some(Ok(Bytes::from(buf))))
+                    }
+                    _ => Poll::Ready(None),
+                }
+            }
+        }
+    }
+}
+
+enum Value<'a> {
+    Null,
+    Bool(bool),
+    Number(Cow<'a, str>),
+    Json(&'a str),
+    Text(Cow<'a, str>),
+}
+
+fn write_header(columns: &[Column], buf: &mut String) {
+    for (i, column) in columns.iter().enumerate() {
+        if i > 0 {
+            buf.push(',');
+        }
+        write_csv_field(column.name(), buf);
+    }
+    buf.push('\n');
+}
+
+fn write_row(row: &Row, format: ExportFormat, buf: &mut String) -> Result<(), Error> {
+    if format == ExportFormat::Ndjson {
+        buf.push('{');
+    }
+
+    for (i, column) in row.columns().iter().enumerate() {
+        let value = value(column.type_(), row.output_format(), row.col_buffer(i))
+            .map_err(|e| Error::from_sql(e, i))?;
+
+        match format {
+            ExportFormat::Csv => {
+                if i > 0 {
+                    buf.push(',');
+                }
+                match value {
+                    Value::Null => {}
+                    Value::Bool(true) => buf.push('t'),
+                    Value::Bool(false) => buf.push('f'),
+                    Value::Number(s) | Value::Text(s) => write_csv_field(&s, buf),
+                    Value::Json(s) => write_csv_field(s, buf),
+                }
+            }
+            ExportFormat::Ndjson => {
+                if i > 0 {
+                    buf.push(',');
+                }
+                write_json_string(column.name(), buf);
+                buf.push(':');
+                match value {
+                    Value::Null => buf.push_str("null"),
+                    Value::Bool(true) => buf.push_str("true"),
+                    Value::Bool(false) => buf.push_str("false"),
+                    Value::Number(s) => buf.push_str(&s),
+                    Value::Json(s) => write_json(s, buf),
+                    Value::Text(s) => write_json_string(&s, buf),
+                }
+            }
+        }
+    }
+
+    if format == ExportFormat::Ndjson {
+        buf.push('}');
+    }
+    buf.push('\n');
+    Ok(())
+}
+
+type BoxError = Box<dyn std::error::Error + Sync + Send>;
+
+fn value<'a>(ty: &Type, format: Format, raw: Option<&'a [u8]>) -> Result<Value<'a>, BoxError> {
+    let raw = match raw {
+        Some(raw) => raw,
+        None => return Ok(Value::Null),
+    };
+
+    match format {
+        Format::Text => text_value(ty, str::from_utf8(raw)?),
+        Format::Binary => binary_value(ty, raw),
+    }
+}
+
+fn text_value<'a>(ty: &Type, s: &'a str) -> Result<Value<'a>, BoxError> {
+    let value = match *ty {
+        Type::BOOL => Value::Bool(s == "t"),
+        Type::INT2
+        | Type::INT4
+        | Type::INT8
+        | Type::OID
+        | Type::FLOAT4
+        | Type::FLOAT8
+        | Type::NUMERIC => match s {
+            "NaN" | "Infinity" | "-Infinity" => Value::Text(Cow::Borrowed(s)),
+            _ => Value::Number(Cow::Borrowed(s)),
+        },
+        Type::JSON | Type::JSONB => Value::Json(s),
+        _ => match ty.kind() {
+            Kind::Domain(inner) => return text_value(inner, s),
+            _ => Value::Text(Cow::Borrowed(s)),
+        },
+    };
+    Ok(value)
+}
+
+fn binary_value<'a>(ty: &Type, raw: &'a [u8]) -> Result<Value<'a>, BoxError> {
+    let value = match *ty {
+        Type::BOOL => Value::Bool(bool::from_sql(ty, raw)?),
+        Type::INT2 => Value::Number(Cow::Owned(i16::from_sql(ty, raw)?.to_string())),
+        Type::INT4 => Value::Number(Cow::Owned(i32::from_sql(ty, raw)?.to_string())),
+        Type::INT8 => Value::Number(Cow::Owned(i64::from_sql(ty, raw)?.to_string())),
+        Type::OID => Value::Number(Cow::Owned(u32::from_sql(ty, raw)?.to_string())),
+        Type::FLOAT4 => {
+            let f = f32::from_sql(ty, raw)?;
+            float_value(f64::from(f), f.to_string())
+        }
+        Type::FLOAT8 => {
+            let f = f64::from_sql(ty, raw)?;
+            float_value(f, f.to_string())
+        }
+        Type::NUMERIC => numeric_value(raw)?,
+        Type::JSON => Value::Json(str::from_utf8(raw)?),
+        Type::JSONB => match raw.split_first() {
+            Some((1, json)) => Value::Json(str::from_utf8(json)?),
+            _ => return Err("unsupported JSONB encoding version".into()),
+        },
+        Type::BYTEA => {
+            let mut s = String::with_capacity(2 + raw.len() * 2);
+            s.push_str("\\x");
+            for byte in raw {
+                write!(s, "{:02x}", byte).unwrap();
+            }
+            Value::Text(Cow::Owned(s))
+        }
+        Type::UUID => {
+            if raw.len() != 16 {
+                return Err("invalid message length: uuid size mismatch".into());
+            }
+            let mut s = String::with_capacity(36);
+            for (i, byte) in raw.iter().enumerate() {
+                if let 4 | 6 | 8 | 10 = i {
+                    s.push('-');
+                }
+                write!(s, "{:02x}", byte).unwrap();
+            }
+            Value::Text(Cow::Owned(s))
+        }
+        _ => match ty.kind() {
+            Kind::Domain(inner) => return binary_value(inner, raw),
+            Kind::Enum(_) => Value::Text(Cow::Borrowed(str::from_utf8(raw)?)),
+            _ if <&str as FromSql>::accepts(ty) => Value::Text(Cow::Borrowed(str::from_utf8(raw)?)),
+            _ => {
+                return Err(format!(
+                    "values of type {} can't be exported in the binary format",
+                    ty
+                )
+                .into())
+            }
+        },
+    };
+    Ok(value)
+}
+
+fn float_value(f: f64, s: String) -> Value<'static> {
+    if f.is_nan() {
+        Value::Text(Cow::Borrowed("NaN"))
+    } else if f.is_infinite() {
+        Value::Text(Cow::Borrowed(if f > 0. { "Infinity" } else { "-Infinity" }))
+    } else {
+        Value::Number(Cow::Owned(s))
+    }
+}
+
+// Renders a value in the binary format of `NUMERIC` like the server does in the text format: a sign, a weight, a
+// display scale and base 10000 digits.
+fn numeric_value(raw: &[u8]) -> Result<Value<'static>, BoxError> {
+    if raw.len() < 8 {
+        return Err("invalid message length: numeric header".into());
+    }
+    let u16_at = |i: usize| u16::from_be_bytes([raw[i], raw[i + 1]]);
+    let ndigits = usize::from(u16_at(0));
+    let weight = i32::from(u16_at(2) as i16);
+    let sign = u16_at(4);
+    let dscale = usize::from(u16_at(6));
+    if raw.len() != 8 + ndigits * 2 {
+        return Err("invalid message length: numeric digits".into());
+    }
+    let digit = |i: i32| {
+        if i >= 0 && (i as usize) < ndigits {
+            u16_at(8 + i as usize * 2)
+        } else {
+            0
+        }
+    };
+
+    match sign {
+        0x0000 | 0x4000 => {}
+        0xC000 => return Ok(Value::Text(Cow::Borrowed("NaN"))),
+        0xD000 => return Ok(Value::Text(Cow::Borrowed("Infinity"))),
+        0xF000 => return Ok(Value::Text(Cow::Borrowed("-Infinity"))),
+        _ => return Err("invalid numeric sign".into()),
+    }
+
+    let mut s = String::new();
+    if sign == 0x4000 {
+        s.push('-');
+    }
+    if weight < 0 {
+        s.push('0');
+    } else {
+        write!(s, "{}", digit(0)).unwrap();
+        for i in 1..=weight {
+            write!(s, "{:04}", digit(i)).unwrap();
+        }
+    }
+    if dscale > 0 {
+        s.push('.');
+        let start = s.len();
+        let mut i = weight + 1;
+        while s.len() - start < dscale {
+            write!(s, "{:04}", digit(i)).unwrap();
+            i += 1;
+        }
+        s.truncate(start + dscale);
+    }
+    Ok(Value::Number(Cow::Owned(s)))
+}
+
+fn write_csv_field(s: &str, buf: &mut String) {
+    if s.is_empty() || s.contains([',', '"', '\n', '\r']) {
+        buf.push('"');
+        for c in s.chars() {
+            if c == '"' {
+                buf.push('"');
+            }
+            buf.push(c);
+        }
+        buf.push('"');
+    } else {
+        buf.push_str(s);
+    }
+}
+
+// Embeds a JSON value on a single line. Line breaks can only be whitespace between the tokens of a valid value.
+fn write_json(s: &str, buf: &mut String) {
+    buf.extend(s.trim().chars().map(|c| match c {
+        '\n' | '\r' => ' ',
+        c => c,
+    }));
+}
+
+fn write_json_string(s: &str, buf: &mut String) {
+    buf.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => buf.push_str("\\\""),
+            '\\' => buf.push_str("\\\\"),
+            '\n' => buf.push_str("\\n"),
+            '\r' => buf.push_str("\\r"),
+            '\t' => buf.push_str("\\t"),
+            c if c < ' ' => write!(buf, "\\u{:04x}", c as u32).unwrap(),
+            c => buf.push(c),
+        }
+    }
+    buf.push('"');
+}
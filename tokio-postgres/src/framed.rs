@@ -0,0 +1,255 @@
+use crate::codec::{BackendMessage, FrontendMessage, PostgresCodec};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use futures_util::{ready, Sink, Stream};
+use std::collections::VecDeque;
+use std::io::{self, IoSlice};
+use std::iter;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_util::codec::Decoder;
+use tokio_util::io::poll_read_buf;
+
+const INITIAL_CAPACITY: usize = 8 * 1024;
+const BACKPRESSURE_BOUNDARY: usize = INITIAL_CAPACITY;
+
+// Messages at least this large are written from their own buffer rather than copied into the shared write buffer.
+const LARGE_MESSAGE_SIZE: usize = 4 * 1024;
+
+const MAX_WRITE_SLICES: usize = 64;
+
+/// The frontend messages waiting to be written to the socket.
+///
+/// Small messages are copied into a single buffer, which is reused once its contents are written. Large messages are
+/// instead queued as their own chunks, and written along with the others by vectored writes, so that large bind
+/// parameters aren't copied a second time after being encoded.
+struct WriteBuffer {
+    chunks: VecDeque<Bytes>,
+    buf: BytesMut,
+    len: usize,
+}
+
+impl WriteBuffer {
+    fn new() -> WriteBuffer {
+        WriteBuffer {
+            chunks: VecDeque::new(),
+            buf: BytesMut::with_capacity(INITIAL_CAPACITY),
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, chunk: Bytes) {
+        if chunk.len() < LARGE_MESSAGE_SIZE {
+            self.buf.extend_from_slice(&chunk);
+        } else {
+            self.seal();
+            self.len += chunk.len();
+            self.chunks.push_back(chunk);
+        }
+    }
+
+    /// Queues the contents of the shared buffer as a chunk, so that messages added afterwards are written after it.
+    fn seal(&mut self) {
+        if !self.buf.is_empty() {
+            let chunk = self.buf.split().freeze();
+            self.len += chunk.len();
+            self.chunks.push_back(chunk);
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len + self.buf.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn poll_write<T>(&mut self, io: Pin<&mut T>, cx: &mut Context<'_>) -> Poll<io::Result<usize>>
+    where
+        T: AsyncWrite,
+    {
+        let mut slices = [IoSlice::new(&[]); MAX_WRITE_SLICES];
+        let mut count = 0;
+        let chunks = self
+            .chunks
+            .iter()
+            .map(|chunk| &chunk[..])
+            .chain(iter::once(&self.buf[..]))
+            .filter(|chunk| !chunk.is_empty());
+        for (slice, chunk) in slices.iter_mut().zip(chunks) {
+            *slice = IoSlice::new(chunk);
+            count += 1;
+        }
+
+        if io.is_write_vectored() {
+            io.poll_write_vectored(cx, &slices[..count])
+        } else {
+            io.poll_write(cx, &slices[0])
+        }
+    }
+
+    fn advance(&mut self, mut n: usize) {
+        while let Some(chunk) = self.chunks.front_mut() {
+            if n < chunk.len() {
+                chunk.advance(n);
+                self.len -= n;
+                return;
+            }
+
+            n -= chunk.len();
+            self.len -= chunk.len();
+            self.chunks.pop_front();
+        }
+
+        self.buf.advance(n);
+    }
+}
+
+/// A framed stream of backend and frontend messages over a socket.
+pub struct Framed<T> {
+    io: T,
+    codec: PostgresCodec,
+    read_buf: BytesMut,
+    eof: bool,
+    write_buf: WriteBuffer,
+    // The number of bytes sent as of the last flush.
+    flushed: u64,
+}
+
+impl<T> Framed<T> {
+    pub fn new(io: T, codec: PostgresCodec) -> Framed<T> {
+        Framed {
+            io,
+            codec,
+            read_buf: BytesMut::with_capacity(INITIAL_CAPACITY),
+            eof: false,
+            write_buf: WriteBuffer::new(),
+            flushed: 0,
+        }
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.io
+    }
+
+    pub fn codec(&self) -> &PostgresCodec {
+        &self.codec
+    }
+
+    pub fn codec_mut(&mut self) -> &mut PostgresCodec {
+        &mut self.codec
+    }
+}
+
+impl<T> Stream for Framed<T>
+where
+    T: AsyncRead + Unpin,
+{
+    type Item = io::Result<BackendMessage>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        loop {
+            if this.eof {
+                return match this.codec.decode_eof(&mut this.read_buf) {
+                    Ok(message) => Poll::Ready(message.map(Ok)),
+                    Err(e) => {
+                        this.read_buf.clear();
+                        Poll::Ready(Some(Err(e)))
+                    }
+                };
+            }
+
+            if let Some(message) = this.codec.decode(&mut this.read_buf)? {
+                return Poll::Ready(Some(Ok(message)));
+            }
+
+            this.read_buf.reserve(1);
+            if ready!(poll_read_buf(
+                Pin::new(&mut this.io),
+                cx,
+                &mut this.read_buf
+            ))? == 0
+            {
+                this.eof = true;
+            }
+        }
+    }
+}
+
+impl<T> Sink<FrontendMessage> for Framed<T>
+where
+    T: AsyncWrite + Unpin,
+{
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if self.write_buf.len() >= BACKPRESSURE_BOUNDARY {
+            self.poll_flush(cx)
+        } else {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: FrontendMessage) -> io::Result<()> {
+        let this = &mut *self;
+        let metrics = this.codec.metrics.as_ref();
+
+        match item {
+            FrontendMessage::Raw(buf) => {
+                this.codec.stats.record_sent(&buf, metrics);
+                this.write_buf.push(buf);
+            }
+            FrontendMessage::Continuation(buf) => {
+                this.codec.stats.record_sent_bytes(buf.len(), metrics);
+                this.write_buf.push(buf);
+            }
+            FrontendMessage::CopyData(data) => {
+                let mut data = data.write_header(&mut this.write_buf.buf);
+                let len = data.remaining();
+                this.codec.stats.record_sent_message(b'd', len + 5, metrics);
+                if len < LARGE_MESSAGE_SIZE {
+                    this.write_buf.buf.put(data);
+                } else {
+                    this.write_buf.push(data.copy_to_bytes(len));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = &mut *self;
+
+        while !this.write_buf.is_empty() {
+            let n = ready!(this.write_buf.poll_write(Pin::new(&mut this.io), cx))?;
+            if n == 0 {
+                return Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::WriteZero,
+                    "failed to write frame to transport",
+                )));
+            }
+            this.write_buf.advance(n);
+        }
+
+        ready!(Pin::new(&mut this.io).poll_flush(cx))?;
+
+        let sent = this.codec.stats.bytes_sent();
+        if sent > this.flushed {
+            this.codec
+                .stats
+                .record_flush(sent - this.flushed, this.codec.metrics.as_ref());
+            this.flushed = sent;
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_flush(cx))?;
+        Pin::new(&mut self.io).poll_shutdown(cx)
+    }
+}
@@ -1,7 +1,11 @@
 use crate::query::RowStream;
-use crate::types::{BorrowToSql, ToSql, Type};
-use crate::{Client, Error, Row, Statement, ToStatement, Transaction};
+use crate::types::{BorrowToSql, FromRow, FromSqlOwned, ToSql, ToText, Type};
+use crate::{
+    Client, CopyInSink, CopyOutStream, Error, Row, Statement, StatementDescription, ToStatement,
+    Transaction,
+};
 use async_trait::async_trait;
+use bytes::Buf;
 
 mod private {
     pub trait Sealed {}
@@ -48,6 +52,33 @@ pub trait GenericClient: private::Sealed {
     where
         T: ?Sized + ToStatement + Sync + Send;
 
+    /// Like `Client::query_scalar`.
+    async fn query_scalar<R>(
+        &self,
+        statement: &(impl ?Sized + ToStatement + Sync + Send),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<R, Error>
+    where
+        R: FromSqlOwned + Send;
+
+    /// Like `Client::query_scalar_opt`.
+    async fn query_scalar_opt<R>(
+        &self,
+        statement: &(impl ?Sized + ToStatement + Sync + Send),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<R>, Error>
+    where
+        R: FromSqlOwned + Send;
+
+    /// Like `Client::query_as`.
+    async fn query_as<R>(
+        &self,
+        statement: &(impl ?Sized + ToStatement + Sync + Send),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<R>, Error>
+    where
+        R: FromRow + Send;
+
     /// Like `Client::query_raw`.
     async fn query_raw<T, P, I>(&self, statement: &T, params: I) -> Result<RowStream, Error>
     where
@@ -57,14 +88,28 @@ pub trait GenericClient: private::Sealed {
         I::IntoIter: ExactSizeIterator;
 
     /// Like `Client::query_raw_txt`.
-    async fn query_raw_txt<'a,  S, I>(
+    async fn query_raw_txt<'a,  P, I>(
         &self,
         statement: &str,
         params: I,
     ) -> Result<RowStream, Error>
     where
-        S: AsRef<str> + Sync + Send,
-        I: IntoIterator<Item = Option<S>> + Sync + Send,
+        P: ToText + Sync + Send,
+        I: IntoIterator<Item = P> + Sync + Send,
+        I::IntoIter: ExactSizeIterator + Sync + Send;
+
+    /// Like `Client::query_one_txt`.
+    async fn query_one_txt<P, I>(&self, statement: &str, params: I) -> Result<Row, Error>
+    where
+        P: ToText + Sync + Send,
+        I: IntoIterator<Item = P> + Sync + Send,
+        I::IntoIter: ExactSizeIterator + Sync + Send;
+
+    /// Like `Client::query_opt_txt`.
+    async fn query_opt_txt<P, I>(&self, statement: &str, params: I) -> Result<Option<Row>, Error>
+    where
+        P: ToText + Sync + Send,
+        I: IntoIterator<Item = P> + Sync + Send,
         I::IntoIter: ExactSizeIterator + Sync + Send;
 
     /// Like `Client::prepare`.
@@ -77,6 +122,20 @@ pub trait GenericClient: private::Sealed {
         parameter_types: &[Type],
     ) -> Result<Statement, Error>;
 
+    /// Like `Client::describe`.
+    async fn describe(&self, query: &str) -> Result<StatementDescription, Error>;
+
+    /// Like `Client::copy_in`.
+    async fn copy_in<T, U>(&self, statement: &T) -> Result<CopyInSink<U>, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+        U: Buf + 'static + Send;
+
+    /// Like `Client::copy_out`.
+    async fn copy_out<T>(&self, statement: &T) -> Result<CopyOutStream, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send;
+
     /// Like `Client::transaction`.
     async fn transaction(&mut self) -> Result<Transaction<'_>, Error>;
 
@@ -137,6 +196,39 @@ impl GenericClient for Client {
         self.query_opt(statement, params).await
     }
 
+    async fn query_scalar<R>(
+        &self,
+        statement: &(impl ?Sized + ToStatement + Sync + Send),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<R, Error>
+    where
+        R: FromSqlOwned + Send,
+    {
+        self.query_scalar(statement, params).await
+    }
+
+    async fn query_scalar_opt<R>(
+        &self,
+        statement: &(impl ?Sized + ToStatement + Sync + Send),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<R>, Error>
+    where
+        R: FromSqlOwned + Send,
+    {
+        self.query_scalar_opt(statement, params).await
+    }
+
+    async fn query_as<R>(
+        &self,
+        statement: &(impl ?Sized + ToStatement + Sync + Send),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<R>, Error>
+    where
+        R: FromRow + Send,
+    {
+        self.query_as(statement, params).await
+    }
+
     async fn query_raw<T, P, I>(&self, statement: &T, params: I) -> Result<RowStream, Error>
     where
         T: ?Sized + ToStatement + Sync + Send,
@@ -147,15 +239,33 @@ impl GenericClient for Client {
         self.query_raw(statement, params).await
     }
 
-    async fn query_raw_txt<'a,  S, I>(&self, statement: &str, params: I) -> Result<RowStream, Error>
+    async fn query_raw_txt<'a,  P, I>(&self, statement: &str, params: I) -> Result<RowStream, Error>
     where
-        S: AsRef<str> + Sync + Send,
-        I: IntoIterator<Item = Option<S>> + Sync + Send,
+        P: ToText + Sync + Send,
+        I: IntoIterator<Item = P> + Sync + Send,
         I::IntoIter: ExactSizeIterator + Sync + Send,
     {
         self.query_raw_txt(statement, params).await
     }
 
+    async fn query_one_txt<P, I>(&self, statement: &str, params: I) -> Result<Row, Error>
+    where
+        P: ToText + Sync + Send,
+        I: IntoIterator<Item = P> + Sync + Send,
+        I::IntoIter: ExactSizeIterator + Sync + Send,
+    {
+        self.query_one_txt(statement, params).await
+    }
+
+    async fn query_opt_txt<P, I>(&self, statement: &str, params: I) -> Result<Option<Row>, Error>
+    where
+        P: ToText + Sync + Send,
+        I: IntoIterator<Item = P> + Sync + Send,
+        I::IntoIter: ExactSizeIterator + Sync + Send,
+    {
+        self.query_opt_txt(statement, params).await
+    }
+
     async fn prepare(&self, query: &str) -> Result<Statement, Error> {
         self.prepare(query).await
     }
@@ -168,6 +278,25 @@ impl GenericClient for Client {
         self.prepare_typed(query, parameter_types).await
     }
 
+    async fn describe(&self, query: &str) -> Result<StatementDescription, Error> {
+        self.describe(query).await
+    }
+
+    async fn copy_in<T, U>(&self, statement: &T) -> Result<CopyInSink<U>, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+        U: Buf + 'static + Send,
+    {
+        self.copy_in(statement).await
+    }
+
+    async fn copy_out<T>(&self, statement: &T) -> Result<CopyOutStream, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+    {
+        self.copy_out(statement).await
+    }
+
     async fn transaction(&mut self) -> Result<Transaction<'_>, Error> {
         self.transaction().await
     }
@@ -232,6 +361,39 @@ impl GenericClient for Transaction<'_> {
         self.query_opt(statement, params).await
     }
 
+    async fn query_scalar<R>(
+        &self,
+        statement: &(impl ?Sized + ToStatement + Sync + Send),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<R, Error>
+    where
+        R: FromSqlOwned + Send,
+    {
+        self.query_scalar(statement, params).await
+    }
+
+    async fn query_scalar_opt<R>(
+        &self,
+        statement: &(impl ?Sized + ToStatement + Sync + Send),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<R>, Error>
+    where
+        R: FromSqlOwned + Send,
+    {
+        self.query_scalar_opt(statement, params).await
+    }
+
+    async fn query_as<R>(
+        &self,
+        statement: &(impl ?Sized + ToStatement + Sync + Send),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<R>, Error>
+    where
+        R: FromRow + Send,
+    {
+        self.query_as(statement, params).await
+    }
+
     async fn query_raw<T, P, I>(&self, statement: &T, params: I) -> Result<RowStream, Error>
     where
         T: ?Sized + ToStatement + Sync + Send,
@@ -242,15 +404,33 @@ impl GenericClient for Transaction<'_> {
         self.query_raw(statement, params).await
     }
 
-    async fn query_raw_txt<'a,  S, I>(&self, statement: &str, params: I) -> Result<RowStream, Error>
+    async fn query_raw_txt<'a,  P, I>(&self, statement: &str, params: I) -> Result<RowStream, Error>
     where
-        S: AsRef<str> + Sync + Send,
-        I: IntoIterator<Item = Option<S>> + Sync + Send,
+        P: ToText + Sync + Send,
+        I: IntoIterator<Item = P> + Sync + Send,
         I::IntoIter: ExactSizeIterator + Sync + Send,
     {
         self.query_raw_txt(statement, params).await
     }
 
+    async fn query_one_txt<P, I>(&self, statement: &str, params: I) -> Result<Row, Error>
+    where
+        P: ToText + Sync + Send,
+        I: IntoIterator<Item = P> + Sync + Send,
+        I::IntoIter: ExactSizeIterator + Sync + Send,
+    {
+        self.query_one_txt(statement, params).await
+    }
+
+    async fn query_opt_txt<P, I>(&self, statement: &str, params: I) -> Result<Option<Row>, Error>
+    where
+        P: ToText + Sync + Send,
+        I: IntoIterator<Item = P> + Sync + Send,
+        I::IntoIter: ExactSizeIterator + Sync + Send,
+    {
+        self.query_opt_txt(statement, params).await
+    }
+
     async fn prepare(&self, query: &str) -> Result<Statement, Error> {
         self.prepare(query).await
     }
@@ -263,6 +443,25 @@ impl GenericClient for Transaction<'_> {
         self.prepare_typed(query, parameter_types).await
     }
 
+    async fn describe(&self, query: &str) -> Result<StatementDescription, Error> {
+        self.describe(query).await
+    }
+
+    async fn copy_in<T, U>(&self, statement: &T) -> Result<CopyInSink<U>, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+        U: Buf + 'static + Send,
+    {
+        self.copy_in(statement).await
+    }
+
+    async fn copy_out<T>(&self, statement: &T) -> Result<CopyOutStream, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+    {
+        self.copy_out(statement).await
+    }
+
     #[allow(clippy::needless_lifetimes)]
     async fn transaction<'a>(&'a mut self) -> Result<Transaction<'a>, Error> {
         self.transaction().await
@@ -0,0 +1,38 @@
+//! GSSAPI transport encryption and authentication support.
+//!
+//! This crate does not link against a GSSAPI implementation itself. Instead, a [`GssContext`] backed by the
+//! GSSAPI library of your choice is supplied through [`Config::gss_context_provider`], and the connection takes care
+//! of the `GSSENCRequest` handshake and the framing of the wrapped messages, as well as of the exchange of tokens
+//! when the server requests GSSAPI or SSPI authentication.
+//!
+//! [`Config::gss_context_provider`]: crate::Config::gss_context_provider
+
+use std::error::Error;
+
+/// The outcome of a single step of security context establishment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GssStep {
+    /// The context is not yet established. The token must be sent to the server, and its reply passed to the next
+    /// call to [`GssContext::step`].
+    Continue(Vec<u8>),
+    /// The context is established. A final token, if present, is sent to the server.
+    Complete(Option<Vec<u8>>),
+}
+
+/// A client-side GSSAPI security context used to encrypt or authenticate a connection.
+///
+/// This typically wraps `gss_init_sec_context`, `gss_wrap` and `gss_unwrap` for the `postgres` service principal of
+/// the server being connected to. Contexts used for authentication are only stepped, and never wrap or unwrap
+/// messages.
+pub trait GssContext: Send {
+    /// Performs a step of security context establishment.
+    ///
+    /// The first call is passed `None`; subsequent calls are passed the token most recently received from the server.
+    fn step(&mut self, token: Option<&[u8]>) -> Result<GssStep, Box<dyn Error + Sync + Send>>;
+
+    /// Encrypts and signs a message to the server.
+    fn wrap(&mut self, data: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Sync + Send>>;
+
+    /// Decrypts and verifies a message from the server.
+    fn unwrap(&mut self, data: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Sync + Send>>;
+}
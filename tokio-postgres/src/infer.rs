@@ -0,0 +1,186 @@
+use crate::client::{InnerClient, Responses};
+use crate::codec::FrontendMessage;
+use crate::connection::RequestMessages;
+use crate::instrument::Span;
+use crate::interceptor::{Completion, Operation};
+use crate::query::{self, RowStream};
+use crate::types::{BorrowToSql, Type};
+use crate::{prepare, Error, Statement};
+use bytes::BytesMut;
+use fallible_iterator::FallibleIterator;
+use futures_channel::mpsc;
+use futures_util::{ready, SinkExt, Stream, StreamExt};
+use log::debug;
+use postgres_protocol::message::backend::Message;
+use postgres_protocol::message::frontend;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+enum InferMessage {
+    Message(FrontendMessage),
+    Done(FrontendMessage),
+}
+
+/// The messages of a query whose parameters are encoded once their types are described by the server.
+///
+/// The statement is described after a Flush rather than a Sync, so that it is parsed and executed in the same
+/// request. If the client gives up before executing it, the request is ended with a Sync.
+pub struct InferReceiver {
+    receiver: mpsc::Receiver<InferMessage>,
+    done: bool,
+}
+
+impl InferReceiver {
+    fn new(receiver: mpsc::Receiver<InferMessage>) -> InferReceiver {
+        InferReceiver {
+            receiver,
+            done: false,
+        }
+    }
+}
+
+impl Stream for InferReceiver {
+    type Item = FrontendMessage;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<FrontendMessage>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+
+        match ready!(self.receiver.poll_next_unpin(cx)) {
+            Some(InferMessage::Message(message)) => Poll::Ready(Some(message)),
+            Some(InferMessage::Done(message)) => {
+                self.done = true;
+                Poll::Ready(Some(message))
+            }
+            None => {
+                self.done = true;
+                let mut buf = BytesMut::new();
+                frontend::sync(&mut buf);
+                Poll::Ready(Some(FrontendMessage::Raw(buf.freeze())))
+            }
+        }
+    }
+}
+
+pub async fn query<P, I>(
+    client: &Arc<InnerClient>,
+    query: &str,
+    params: I,
+) -> Result<RowStream, Error>
+where
+    P: BorrowToSql,
+    I: IntoIterator<Item = P>,
+    I::IntoIter: ExactSizeIterator,
+{
+    let interceptors = client.interceptors();
+    let sql = query;
+    let query = interceptors.before_sql(Operation::Query, query)?;
+    debug!("inferring parameter types of query: {}", query);
+
+    let annotated = client.instrumentation().annotate(&query);
+    let (mut sender, mut responses, statement) = describe(client, sql, &annotated).await?;
+
+    // Dropping the sender on an encoding error ends the request.
+    let buf = query::encode_execute(client, &interceptors, Operation::Query, &statement, params)?;
+    sender
+        .send(InferMessage::Done(FrontendMessage::Raw(buf)))
+        .await
+        .map_err(|_| Error::closed())?;
+
+    let span = Span::query(client.instrumentation(), &statement);
+    let mut completion = Completion::new(client, Operation::Query, None);
+    let result = span
+        .instrument(async {
+            match responses.next().await? {
+                Message::BindComplete => Ok(()),
+                m => Err(Error::unexpected_message(m)),
+            }
+        })
+        .await;
+    if let Err(e) = result {
+        completion.complete(Some(&statement), Err(&e));
+        return Err(e);
+    }
+
+    Ok(RowStream::new(
+        client, statement, responses, span, completion,
+    ))
+}
+
+// The types of the statement can't be looked up in the catalog while the request is in progress, so the request is
+// ended and the statement described again once the types it uses which aren't known yet are resolved.
+async fn describe(
+    client: &Arc<InnerClient>,
+    sql: &str,
+    query: &str,
+) -> Result<(mpsc::Sender<InferMessage>, Responses, Statement), Error> {
+    loop {
+        let permit = client.reserve().await;
+        let buf = client.with_buf(|buf| {
+            client.replace_unnamed_statement();
+            frontend::parse("", query, std::iter::empty(), buf).map_err(Error::encode)?;
+            frontend::describe(b'S', "", buf).map_err(Error::encode)?;
+            frontend::flush(buf);
+            Ok(buf.split().freeze())
+        })?;
+
+        let (mut sender, receiver) = mpsc::channel(1);
+        let mut responses =
+            client.send(permit, RequestMessages::Infer(InferReceiver::new(receiver)))?;
+        sender
+            .send(InferMessage::Message(FrontendMessage::Raw(buf)))
+            .await
+            .map_err(|_| Error::closed())?;
+
+        match responses.next().await? {
+            Message::ParseComplete => {}
+            m => return Err(Error::unexpected_message(m)),
+        }
+
+        let parameter_description = match responses.next().await? {
+            Message::ParameterDescription(body) => body,
+            m => return Err(Error::unexpected_message(m)),
+        };
+
+        let row_description = match responses.next().await? {
+            Message::RowDescription(body) => Some(body),
+            Message::NoData => None,
+            m => return Err(Error::unexpected_message(m)),
+        };
+
+        let mut unknown = vec![];
+        if client.resolves_types() {
+            let mut it = parameter_description.parameters();
+            while let Some(oid) = it.next().map_err(Error::parse)? {
+                if prepare::cached_type(client, oid).is_none() {
+                    unknown.push(oid);
+                }
+            }
+            if let Some(row_description) = &row_description {
+                let mut it = row_description.fields();
+                while let Some(field) = it.next().map_err(Error::parse)? {
+                    if prepare::cached_type(client, field.type_oid()).is_none() {
+                        unknown.push(field.type_oid());
+                    }
+                }
+            }
+        }
+
+        if unknown.is_empty() {
+            let statement = query::make_statement(
+                sql.to_string(),
+                parameter_description,
+                row_description,
+                |oid| prepare::cached_type(client, oid).unwrap_or(Type::TEXT),
+            )?;
+            return Ok((sender, responses, statement));
+        }
+
+        drop((sender, responses));
+        for oid in unknown {
+            prepare::resolve_type(client, oid).await?;
+        }
+    }
+}
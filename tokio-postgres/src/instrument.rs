@@ -0,0 +1,451 @@
+//! Instrumentation of client operations.
+//!
+//! Operations are wrapped in `tracing` spans carrying the OpenTelemetry database semantic convention attributes, and
+//! outgoing SQL can be annotated with sqlcommenter-style comments. Operations are timed and reported to the metrics
+//! handler, and statements exceeding the configured slow statement threshold are logged.
+//!
+//! Without the `tracing` feature, `Span` is a zero-sized placeholder and all of its methods compile to nothing, so
+//! call sites don't need to be conditionally compiled.
+
+use crate::config::{SlowStatement, SlowStatementCallback, SqlCommenter};
+use crate::interceptor::Operation;
+use crate::metrics::MetricsHandle;
+use crate::stats::{StatementStats, StatsCollector};
+use crate::{Config, Error, Statement};
+use log::warn;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use std::borrow::Cow;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// The characters left unescaped by JavaScript's `encodeURIComponent`, as required by the sqlcommenter specification.
+const COMMENT_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'!')
+    .remove(b'~')
+    .remove(b'*')
+    .remove(b'\'')
+    .remove(b'(')
+    .remove(b')');
+
+/// Per-connection state used to instrument client operations.
+pub(crate) struct Instrumentation {
+    #[cfg(feature = "tracing")]
+    namespace: Option<String>,
+    #[cfg(feature = "tracing")]
+    server_address: Option<String>,
+    #[cfg(feature = "tracing")]
+    server_port: Option<u16>,
+    #[cfg(feature = "tracing")]
+    process_id: Option<i32>,
+    sql_commenter: Option<SqlCommenter>,
+    slow_statements: Option<SlowStatementLog>,
+    metrics: Option<MetricsHandle>,
+    stats: Option<Arc<StatsCollector>>,
+}
+
+#[derive(Clone)]
+struct SlowStatementLog {
+    threshold: Duration,
+    callback: Option<SlowStatementCallback>,
+}
+
+impl Instrumentation {
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub(crate) fn new(
+        config: &Config,
+        user: &str,
+        hostname: Option<&str>,
+        port: Option<u16>,
+        process_id: i32,
+    ) -> Instrumentation {
+        Instrumentation {
+            #[cfg(feature = "tracing")]
+            namespace: Some(config.dbname.as_deref().unwrap_or(user).to_string()),
+            #[cfg(feature = "tracing")]
+            server_address: hostname.map(|s| s.to_string()),
+            #[cfg(feature = "tracing")]
+            server_port: port,
+            #[cfg(feature = "tracing")]
+            process_id: Some(process_id),
+            sql_commenter: config.sql_commenter.clone(),
+            slow_statements: config
+                .log_slow_statements
+                .map(|threshold| SlowStatementLog {
+                    threshold,
+                    callback: config.slow_statement_callback.clone(),
+                }),
+            metrics: config.metrics.clone(),
+            stats: if config.statement_stats {
+                Some(Arc::default())
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Starts timing an operation, if its outcome is reported to a metrics handler, the slow statement log or the
+    /// statement statistics.
+    pub(crate) fn timer(&self, operation: Operation, query: Option<&str>) -> Option<Timer> {
+        // Only the execution of statements is subject to the slow statement log.
+        let slow_statements = self
+            .slow_statements
+            .clone()
+            .filter(|_| matches!(operation, Operation::Query | Operation::Execute));
+        if slow_statements.is_none() && self.metrics.is_none() && self.stats.is_none() {
+            return None;
+        }
+
+        Some(Timer {
+            operation,
+            query: query
+                .filter(|_| slow_statements.is_some() || self.stats.is_some())
+                .map(str::to_string),
+            slow_statements,
+            metrics: self.metrics.clone(),
+            stats: self.stats.clone(),
+            start: Instant::now(),
+        })
+    }
+
+    /// Returns a snapshot of the statement statistics, if they are collected.
+    pub(crate) fn statement_stats(&self) -> Vec<StatementStats> {
+        self.stats
+            .as_ref()
+            .map_or_else(Vec::new, |stats| stats.snapshot())
+    }
+
+    /// Resets the statement statistics, if they are collected.
+    pub(crate) fn reset_statement_stats(&self) {
+        if let Some(stats) = &self.stats {
+            stats.reset();
+        }
+    }
+
+    /// Stops associating a prepared statement with its SQL in the statement statistics.
+    pub(crate) fn statement_closed(&self, name: &str) {
+        if let Some(stats) = &self.stats {
+            stats.closed(name);
+        }
+    }
+
+    /// Appends the tags produced by the configured sqlcommenter, if any, to a query.
+    pub(crate) fn annotate<'a>(&self, query: &'a str) -> Cow<'a, str> {
+        match &self.sql_commenter {
+            Some(commenter) => append_comment(query, commenter.tags()),
+            None => Cow::Borrowed(query),
+        }
+    }
+}
+
+/// Appends an sqlcommenter comment carrying `tags` to `query`.
+///
+/// Queries already containing a comment are left unchanged, as required by the specification.
+fn append_comment(query: &str, mut tags: Vec<(String, String)>) -> Cow<'_, str> {
+    if tags.is_empty() || query.contains("/*") || query.contains("--") {
+        return Cow::Borrowed(query);
+    }
+
+    tags.sort();
+    let mut comment = String::from("/*");
+    for (i, (key, value)) in tags.iter().enumerate() {
+        if i > 0 {
+            comment.push(',');
+        }
+        let key = utf8_percent_encode(key, COMMENT_ENCODE_SET).to_string();
+        let value = utf8_percent_encode(value, COMMENT_ENCODE_SET).to_string();
+        comment.push_str(&key.replace('\'', "\\'"));
+        comment.push_str("='");
+        comment.push_str(&value.replace('\'', "\\'"));
+        comment.push('\'');
+    }
+    comment.push_str("*/");
+
+    let trimmed = query.trim_end();
+    Cow::Owned(match trimmed.strip_suffix(';') {
+        Some(statement) => format!("{} {};", statement.trim_end(), comment),
+        None => format!("{} {}", trimmed, comment),
+    })
+}
+
+/// Measures the execution time of an operation.
+pub(crate) struct Timer {
+    operation: Operation,
+    slow_statements: Option<SlowStatementLog>,
+    metrics: Option<MetricsHandle>,
+    stats: Option<Arc<StatsCollector>>,
+    query: Option<String>,
+    start: Instant,
+}
+
+impl Timer {
+    /// Reports the outcome of the operation to the metrics handler and the statement statistics, and the statement if
+    /// it has been running for longer than the slow statement threshold.
+    pub(crate) fn finish(self, statement: Option<&Statement>, result: Result<Option<u64>, &Error>) {
+        let duration = self.start.elapsed();
+
+        if let Some(metrics) = &self.metrics {
+            if self.operation == Operation::Prepare {
+                metrics.0.prepare(duration, result.err());
+            } else {
+                metrics.0.query(self.operation, duration, result.err());
+                if let Ok(Some(rows)) = result {
+                    metrics.0.rows(self.operation, rows);
+                }
+            }
+        }
+
+        if let Some(stats) = &self.stats {
+            let name = statement.map_or("", |s| s.name());
+            match (self.operation, self.query.as_deref(), result) {
+                (Operation::Prepare, Some(query), Ok(_)) => stats.prepared(name, query),
+                (operation, query, result) => {
+                    stats.record(operation, name, query, duration, result.map_err(|_| ()))
+                }
+            }
+        }
+
+        match (&self.slow_statements, result) {
+            (Some(log), Ok(rows)) if duration >= log.threshold => {
+                log.report(statement, self.query.as_deref(), duration, rows)
+            }
+            _ => {}
+        }
+    }
+}
+
+impl SlowStatementLog {
+    fn report(
+        &self,
+        statement: Option<&Statement>,
+        query: Option<&str>,
+        duration: Duration,
+        rows: Option<u64>,
+    ) {
+        let statement = SlowStatement {
+            name: statement.map_or("", |s| s.name()),
+            query,
+            duration,
+            rows,
+        };
+        match &self.callback {
+            Some(callback) => callback.call(&statement),
+            None => match query {
+                Some(query) => warn!(
+                    "slow query took {:?} ({} rows): {}",
+                    duration,
+                    rows.unwrap_or(0),
+                    query
+                ),
+                None => warn!(
+                    "slow execution of statement {} took {:?} ({} rows)",
+                    statement.name,
+                    duration,
+                    rows.unwrap_or(0)
+                ),
+            },
+        }
+    }
+}
+
+/// Runs a connection attempt, reporting its duration to the metrics handler.
+pub(crate) async fn time_connect<F, T>(config: &Config, future: F) -> Result<T, Error>
+where
+    F: Future<Output = Result<T, Error>>,
+{
+    let metrics = match &config.metrics {
+        Some(metrics) => metrics,
+        None => return future.await,
+    };
+
+    let start = Instant::now();
+    let result = future.await;
+    metrics.0.connect(start.elapsed(), result.as_ref().err());
+    result
+}
+
+#[derive(Clone)]
+pub(crate) struct Span {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct Entered;
+
+#[cfg(feature = "tracing")]
+macro_rules! span {
+    ($name:literal, $instrumentation:expr, $($fields:tt)*) => {{
+        let instrumentation: &Instrumentation = $instrumentation;
+        Span {
+            span: tracing::debug_span!(
+                $name,
+                otel.kind = "client",
+                otel.status_code = tracing::field::Empty,
+                otel.status_message = tracing::field::Empty,
+                db.system = "postgresql",
+                db.namespace = instrumentation.namespace.as_deref(),
+                server.address = instrumentation.server_address.as_deref(),
+                server.port = instrumentation.server_port,
+                pid = instrumentation.process_id,
+                $($fields)*
+                rows = tracing::field::Empty,
+                db.response.status_code = tracing::field::Empty,
+                error.type = tracing::field::Empty,
+            ),
+        }
+    }};
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! span {
+    ($name:literal, $instrumentation:expr, $($fields:tt)*) => {
+        Span {}
+    };
+}
+
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+impl Span {
+    #[cfg(feature = "runtime")]
+    pub(crate) fn connect(config: &Config) -> Span {
+        #[cfg(feature = "tracing")]
+        let instrumentation = Instrumentation {
+            namespace: config.dbname.clone().or_else(|| config.user.clone()),
+            server_address: None,
+            server_port: None,
+            process_id: None,
+            sql_commenter: None,
+            slow_statements: None,
+            metrics: None,
+            stats: None,
+        };
+        span!(
+            "connect",
+            &instrumentation,
+            db.user = config.user.as_deref(),
+        )
+    }
+
+    pub(crate) fn prepare(instrumentation: &Instrumentation, query: &str) -> Span {
+        span!(
+            "prepare",
+            instrumentation,
+            db.query.text = query,
+            statement = tracing::field::Empty,
+            params = tracing::field::Empty,
+        )
+    }
+
+    pub(crate) fn query(instrumentation: &Instrumentation, statement: &Statement) -> Span {
+        span!(
+            "query",
+            instrumentation,
+            statement = statement.name(),
+            params = statement.params().len(),
+        )
+    }
+
+    pub(crate) fn query_txt(instrumentation: &Instrumentation, query: &str) -> Span {
+        span!("query", instrumentation, db.query.text = query,)
+    }
+
+    pub(crate) fn query_portal(instrumentation: &Instrumentation, statement: &Statement) -> Span {
+        span!(
+            "query_portal",
+            instrumentation,
+            statement = statement.name(),
+            params = statement.params().len(),
+        )
+    }
+
+    pub(crate) fn execute(instrumentation: &Instrumentation, statement: &Statement) -> Span {
+        span!(
+            "execute",
+            instrumentation,
+            statement = statement.name(),
+            params = statement.params().len(),
+        )
+    }
+
+    pub(crate) fn copy_in(instrumentation: &Instrumentation, statement: &Statement) -> Span {
+        span!(
+            "copy_in",
+            instrumentation,
+            statement = statement.name(),
+            params = statement.params().len(),
+        )
+    }
+
+    pub(crate) fn copy_out(instrumentation: &Instrumentation, statement: &Statement) -> Span {
+        span!(
+            "copy_out",
+            instrumentation,
+            statement = statement.name(),
+            params = statement.params().len(),
+        )
+    }
+
+    pub(crate) fn transaction(instrumentation: &Instrumentation, query: &str) -> Span {
+        span!(
+            "transaction",
+            instrumentation,
+            db.operation.name = query.split(' ').next(),
+            db.query.text = query,
+        )
+    }
+
+    pub(crate) fn record_statement(&self, statement: &Statement) {
+        #[cfg(feature = "tracing")]
+        {
+            self.span.record("statement", statement.name());
+            self.span.record("params", statement.params().len());
+        }
+    }
+
+    pub(crate) fn record_rows(&self, rows: u64) {
+        #[cfg(feature = "tracing")]
+        self.span.record("rows", rows);
+    }
+
+    pub(crate) fn record_error(&self, error: &Error) {
+        #[cfg(feature = "tracing")]
+        {
+            let code = error.code().map(|code| code.code());
+            self.span.record("db.response.status_code", code);
+            self.span.record("error.type", code.unwrap_or("_OTHER"));
+            self.span.record("otel.status_code", "ERROR");
+            self.span
+                .record("otel.status_message", tracing::field::display(error));
+        }
+    }
+
+    /// Enters the span for the duration of a synchronous operation, such as a poll of a stream.
+    #[cfg(feature = "tracing")]
+    pub(crate) fn enter(&self) -> tracing::span::Entered<'_> {
+        self.span.enter()
+    }
+
+    /// Enters the span for the duration of a synchronous operation, such as a poll of a stream.
+    #[cfg(not(feature = "tracing"))]
+    pub(crate) fn enter(&self) -> Entered {
+        Entered
+    }
+
+    /// Runs a future within the span, recording the error it fails with, if any.
+    pub(crate) async fn instrument<F, T>(&self, future: F) -> Result<T, Error>
+    where
+        F: Future<Output = Result<T, Error>>,
+    {
+        #[cfg(feature = "tracing")]
+        let future = tracing::Instrument::instrument(future, self.span.clone());
+
+        let result = future.await;
+        if let Err(e) = &result {
+            self.record_error(e);
+        }
+        result
+    }
+}
@@ -0,0 +1,270 @@
+//! Interception of the queries executed by a client.
+//!
+//! Interceptors registered with [`Client::add_interceptor`] are invoked, in the order they were registered, for every
+//! operation performed by the client. They can inspect and rewrite SQL before it is sent to the server, inspect and
+//! replace the parameters a statement is executed with, and observe the outcome of each operation. This allows
+//! cross-cutting concerns such as tenancy guards, comment injection or shadow traffic to be implemented without
+//! wrapping the whole client API.
+//!
+//! [`Client::add_interceptor`]: crate::Client::add_interceptor
+
+use crate::client::InnerClient;
+use crate::instrument::Timer;
+use crate::types::ToSql;
+use crate::{Error, Statement};
+use std::borrow::Cow;
+use std::error;
+use std::sync::Arc;
+
+/// An operation performed by a client.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Operation {
+    /// A statement is prepared.
+    Prepare,
+    /// A statement is executed, returning rows.
+    Query,
+    /// A statement is executed, returning the number of rows affected.
+    Execute,
+    /// SQL is executed with the simple query protocol.
+    SimpleQuery,
+}
+
+/// A hook invoked around the operations performed by a client.
+///
+/// All methods have default implementations which do nothing, so implementors only need to override the ones they are
+/// interested in.
+pub trait Interceptor: Send + Sync {
+    /// Called before SQL is sent to the server, when a statement is prepared or SQL is executed with the simple query
+    /// protocol.
+    ///
+    /// The SQL may be rewritten in place. Returning an error aborts the operation before anything is sent to the
+    /// server.
+    fn before_sql(
+        &self,
+        operation: Operation,
+        sql: &mut String,
+    ) -> Result<(), Box<dyn error::Error + Sync + Send>> {
+        let _ = (operation, sql);
+        Ok(())
+    }
+
+    /// Called before a prepared statement is executed.
+    ///
+    /// Parameters may be replaced, but their number is fixed by the statement. Returning an error aborts the
+    /// operation before anything is sent to the server.
+    fn before_execute(
+        &self,
+        operation: Operation,
+        statement: &Statement,
+        params: &mut Params<'_>,
+    ) -> Result<(), Box<dyn error::Error + Sync + Send>> {
+        let _ = (operation, statement, params);
+        Ok(())
+    }
+
+    /// Called once an operation sent to the server has completed, successfully or not.
+    ///
+    /// For operations returning a stream, this is called once the stream has been exhausted or has produced an
+    /// error.
+    fn after(&self, outcome: &Outcome<'_>) {
+        let _ = outcome;
+    }
+}
+
+enum Param<'a> {
+    Borrowed(&'a dyn ToSql),
+    Owned(Box<dyn ToSql + Sync + Send>),
+}
+
+/// The parameters a statement is about to be executed with.
+pub struct Params<'a> {
+    params: Vec<Param<'a>>,
+}
+
+impl<'a> Params<'a> {
+    pub(crate) fn new<I>(params: I) -> Params<'a>
+    where
+        I: IntoIterator<Item = &'a dyn ToSql>,
+    {
+        Params {
+            params: params.into_iter().map(Param::Borrowed).collect(),
+        }
+    }
+
+    /// Returns the number of parameters.
+    pub fn len(&self) -> usize {
+        self.params.len()
+    }
+
+    /// Returns `true` if there are no parameters.
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    /// Returns the parameter at the given index, if it exists.
+    pub fn get(&self, idx: usize) -> Option<&dyn ToSql> {
+        self.params.get(idx).map(|param| match param {
+            Param::Borrowed(param) => *param,
+            Param::Owned(param) => &**param as &dyn ToSql,
+        })
+    }
+
+    /// Replaces the parameter at the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is out of bounds.
+    pub fn set(&mut self, idx: usize, param: Box<dyn ToSql + Sync + Send>) {
+        self.params[idx] = Param::Owned(param);
+    }
+
+    /// Returns an iterator over the parameters.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &dyn ToSql> + '_ {
+        (0..self.len()).map(move |idx| self.get(idx).unwrap())
+    }
+}
+
+/// The outcome of an operation performed by a client.
+pub struct Outcome<'a> {
+    pub(crate) operation: Operation,
+    pub(crate) sql: Option<&'a str>,
+    pub(crate) statement: Option<&'a Statement>,
+    pub(crate) result: Result<Option<u64>, &'a Error>,
+}
+
+impl<'a> Outcome<'a> {
+    /// Returns the operation which completed.
+    pub fn operation(&self) -> Operation {
+        self.operation
+    }
+
+    /// Returns the SQL sent to the server, for operations which send SQL.
+    pub fn sql(&self) -> Option<&'a str> {
+        self.sql
+    }
+
+    /// Returns the statement prepared or executed, for operations involving one.
+    pub fn statement(&self) -> Option<&'a Statement> {
+        self.statement
+    }
+
+    /// Returns the number of rows affected, for successful operations reporting one.
+    pub fn rows(&self) -> Option<u64> {
+        self.result.ok().flatten()
+    }
+
+    /// Returns the error the operation failed with, if any.
+    pub fn error(&self) -> Option<&'a Error> {
+        self.result.err()
+    }
+}
+
+/// The interceptors registered with a client.
+#[derive(Clone)]
+pub(crate) struct Interceptors(Arc<[Arc<dyn Interceptor>]>);
+
+impl Default for Interceptors {
+    fn default() -> Interceptors {
+        Interceptors(Arc::new([]))
+    }
+}
+
+impl Interceptors {
+    pub(crate) fn push(&mut self, interceptor: Arc<dyn Interceptor>) {
+        let mut interceptors = self.0.to_vec();
+        interceptors.push(interceptor);
+        self.0 = interceptors.into();
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn before_sql<'a>(
+        &self,
+        operation: Operation,
+        sql: &'a str,
+    ) -> Result<Cow<'a, str>, Error> {
+        if self.is_empty() {
+            return Ok(Cow::Borrowed(sql));
+        }
+
+        let mut sql = sql.to_string();
+        for interceptor in self.0.iter() {
+            interceptor
+                .before_sql(operation, &mut sql)
+                .map_err(Error::interceptor)?;
+        }
+        Ok(Cow::Owned(sql))
+    }
+
+    pub(crate) fn before_execute(
+        &self,
+        operation: Operation,
+        statement: &Statement,
+        params: &mut Params<'_>,
+    ) -> Result<(), Error> {
+        for interceptor in self.0.iter() {
+            interceptor
+                .before_execute(operation, statement, params)
+                .map_err(Error::interceptor)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn after(&self, outcome: &Outcome<'_>) {
+        for interceptor in self.0.iter() {
+            interceptor.after(outcome);
+        }
+    }
+}
+
+/// Reports the outcome of an operation returning a stream once it completes.
+///
+/// The operation is timed from the creation of the completion on, and its outcome is reported to the metrics handler
+/// as well as the interceptors.
+pub(crate) struct Completion {
+    interceptors: Interceptors,
+    timer: Option<Timer>,
+    operation: Operation,
+    sql: Option<String>,
+    done: bool,
+}
+
+impl Completion {
+    pub(crate) fn new(client: &InnerClient, operation: Operation, sql: Option<&str>) -> Completion {
+        let interceptors = client.interceptors();
+        let timer = client.instrumentation().timer(operation, sql);
+        // The SQL is only needed if there is somebody to report it to.
+        let sql = sql.filter(|_| !interceptors.is_empty()).map(str::to_string);
+        Completion {
+            interceptors,
+            timer,
+            operation,
+            sql,
+            done: false,
+        }
+    }
+
+    pub(crate) fn complete(
+        &mut self,
+        statement: Option<&Statement>,
+        result: Result<Option<u64>, &Error>,
+    ) {
+        if self.done {
+            return;
+        }
+        self.done = true;
+
+        if let Some(timer) = self.timer.take() {
+            timer.finish(statement, result);
+        }
+        self.interceptors.after(&Outcome {
+            operation: self.operation,
+            sql: self.sql.as_deref(),
+            statement,
+            result,
+        });
+    }
+}
@@ -0,0 +1,400 @@
+//! Large objects.
+//!
+//! Values of `bytea` columns are limited to 1 GB, and are sent and received in their entirety. Large objects are
+//! stored apart from the tables referencing them by OID, can hold up to 4 TB, and are read and written piecewise
+//! through a [`LargeObject`] handle implementing the `AsyncRead`, `AsyncWrite` and `AsyncSeek` traits of tokio.
+//!
+//! Large objects are created with [`Client::create_large_object`] and deleted with
+//! [`Client::unlink_large_object`]. Since the server closes the handles of large objects at the end of the transaction
+//! they are opened in, they are opened with [`Transaction::open_large_object`].
+//!
+//! The server-side `lo_*` functions are called through the fast-path interface of the protocol, which doesn't
+//! require preparing statements.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tokio::io::{AsyncReadExt, AsyncWriteExt};
+//! use tokio_postgres::large_object::Mode;
+//!
+//! # async fn run(client: &mut tokio_postgres::Client) -> Result<(), Box<dyn std::error::Error>> {
+//! let transaction = client.transaction().await?;
+//! let oid = transaction.create_large_object().await?;
+//!
+//! let mut object = transaction.open_large_object(oid, Mode::ReadWrite).await?;
+//! object.write_all(b"hello world").await?;
+//! object.close().await?;
+//!
+//! let mut object = transaction.open_large_object(oid, Mode::Read).await?;
+//! let mut data = vec![];
+//! object.read_to_end(&mut data).await?;
+//! assert_eq!(data, b"hello world");
+//! object.close().await?;
+//!
+//! transaction.commit().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Client::create_large_object`]: crate::Client::create_large_object
+//! [`Client::unlink_large_object`]: crate::Client::unlink_large_object
+//! [`Transaction::open_large_object`]: crate::Transaction::open_large_object
+
+use crate::client::InnerClient;
+use crate::codec::FrontendMessage;
+use crate::connection::RequestMessages;
+use crate::types::{FromSqlOwned, IsNull, Oid, ToSql, Type};
+use crate::Error;
+use bytes::{Buf, Bytes};
+use futures_util::future::BoxFuture;
+use futures_util::ready;
+use postgres_protocol::message::backend::Message;
+use postgres_protocol::message::frontend;
+use std::io::{self, SeekFrom};
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncSeek, AsyncWrite, ReadBuf};
+
+// The OIDs of the server-side functions, which are fixed in the catalog.
+const LO_CREATE: Oid = 715;
+const LO_OPEN: Oid = 952;
+const LO_CLOSE: Oid = 953;
+const LOREAD: Oid = 954;
+const LOWRITE: Oid = 955;
+const LO_UNLINK: Oid = 964;
+const LO_LSEEK64: Oid = 3170;
+const LO_TRUNCATE64: Oid = 3172;
+
+const INV_WRITE: i32 = 0x0002_0000;
+const INV_READ: i32 = 0x0004_0000;
+
+const SEEK_SET: i32 = 0;
+const SEEK_CUR: i32 = 1;
+const SEEK_END: i32 = 2;
+
+// The largest number of bytes read or written by a single function call.
+const CHUNK_SIZE: usize = 256 * 1024;
+
+/// The access mode of a large object.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Mode {
+    /// The object is read as of the snapshot of the transaction when it was opened, ignoring later changes.
+    Read,
+    /// The object is written, and can't be read.
+    Write,
+    /// The object is read and written, reads reflecting the changes committed by other transactions as well as the
+    /// ones made with the handle.
+    ReadWrite,
+}
+
+impl Mode {
+    fn flags(self) -> i32 {
+        match self {
+            Mode::Read => INV_READ,
+            Mode::Write => INV_WRITE,
+            Mode::ReadWrite => INV_READ | INV_WRITE,
+        }
+    }
+}
+
+/// Calls a server-side function through the fast-path interface, with arguments and result in the binary format.
+async fn call(
+    client: &InnerClient,
+    function: Oid,
+    args: &[(&(dyn ToSql + Sync), Type)],
+) -> Result<Option<Bytes>, Error> {
+    let permit = client.reserve().await;
+    let buf = client.with_buf(|buf| {
+        let mut error_idx = 0;
+        let r = frontend::function_call(
+            function,
+            Some(1),
+            args.iter().enumerate(),
+            |(idx, (arg, ty)), buf| match arg.to_sql_checked(ty, buf) {
+                Ok(IsNull::No) => Ok(postgres_protocol::IsNull::No),
+                Ok(IsNull::Yes) => Ok(postgres_protocol::IsNull::Yes),
+                Err(e) => {
+                    error_idx = idx;
+                    Err(e)
+                }
+            },
+            1,
+            buf,
+        );
+        match r {
+            Ok(()) => Ok(buf.split().freeze()),
+            Err(frontend::BindError::Conversion(e)) => Err(Error::to_sql(e, error_idx)),
+            Err(frontend::BindError::Serialization(e)) => Err(Error::encode(e)),
+        }
+    })?;
+
+    let mut responses = client.send(permit, RequestMessages::Single(FrontendMessage::Raw(buf)))?;
+
+    let value = match responses.next().await? {
+        Message::FunctionCallResponse(body) => body.into_bytes(),
+        m => return Err(Error::unexpected_message(m)),
+    };
+    match responses.next().await? {
+        Message::ReadyForQuery(_) => Ok(value),
+        m => Err(Error::unexpected_message(m)),
+    }
+}
+
+fn decode<T>(ty: &Type, value: Option<Bytes>) -> Result<T, Error>
+where
+    T: FromSqlOwned,
+{
+    T::from_sql_nullable(ty, value.as_deref()).map_err(|e| Error::from_sql(e, 0))
+}
+
+pub(crate) async fn create(client: &InnerClient) -> Result<Oid, Error> {
+    // An OID of 0 lets the server assign one.
+    let oid = call(client, LO_CREATE, &[(&0u32, Type::OID)]).await?;
+    decode(&Type::OID, oid)
+}
+
+pub(crate) async fn unlink(client: &InnerClient, oid: Oid) -> Result<(), Error> {
+    call(client, LO_UNLINK, &[(&oid, Type::OID)]).await?;
+    Ok(())
+}
+
+pub(crate) async fn open<'a>(
+    client: &Arc<InnerClient>,
+    oid: Oid,
+    mode: Mode,
+) -> Result<LargeObject<'a>, Error> {
+    let fd = call(
+        client,
+        LO_OPEN,
+        &[(&oid, Type::OID), (&mode.flags(), Type::INT4)],
+    )
+    .await?;
+
+    Ok(LargeObject {
+        client: client.clone(),
+        oid,
+        fd: decode(&Type::INT4, fd)?,
+        pending: None,
+        read_buf: Bytes::new(),
+        _p: PhantomData,
+    })
+}
+
+async fn read(client: &InnerClient, fd: i32, len: usize) -> Result<Bytes, Error> {
+    let data = call(
+        client,
+        LOREAD,
+        &[(&fd, Type::INT4), (&(len as i32), Type::INT4)],
+    )
+    .await?;
+    Ok(data.unwrap_or_default())
+}
+
+async fn write(client: &InnerClient, fd: i32, data: &[u8]) -> Result<usize, Error> {
+    let written = call(client, LOWRITE, &[(&fd, Type::INT4), (&data, Type::BYTEA)]).await?;
+    decode::<i32>(&Type::INT4, written).map(|n| n as usize)
+}
+
+async fn seek(client: &InnerClient, fd: i32, offset: i64, whence: i32) -> Result<u64, Error> {
+    let position = call(
+        client,
+        LO_LSEEK64,
+        &[
+            (&fd, Type::INT4),
+            (&offset, Type::INT8),
+            (&whence, Type::INT4),
+        ],
+    )
+    .await?;
+    decode::<i64>(&Type::INT8, position).map(|n| n as u64)
+}
+
+enum Pending {
+    Read(BoxFuture<'static, Result<Bytes, Error>>),
+    Write(BoxFuture<'static, Result<usize, Error>>),
+    Seek(BoxFuture<'static, Result<u64, Error>>),
+}
+
+/// A handle to an open large object.
+///
+/// The object is read, written and repositioned with the `AsyncRead`, `AsyncWrite` and `AsyncSeek` traits of tokio,
+/// each operation calling a server-side function. Reads and writes transfer at most 256 KiB per call, so they should
+/// go through buffers of about that size.
+///
+/// The handle is only valid in the transaction it was opened in, which closes it if it isn't closed explicitly.
+pub struct LargeObject<'a> {
+    client: Arc<InnerClient>,
+    oid: Oid,
+    fd: i32,
+    pending: Option<Pending>,
+    /// Bytes read from the server but not yet returned, which are ahead of the position seen by the caller.
+    read_buf: Bytes,
+    _p: PhantomData<&'a ()>,
+}
+
+impl LargeObject<'_> {
+    /// Returns the OID of the object.
+    pub fn oid(&self) -> Oid {
+        self.oid
+    }
+
+    /// Truncates or extends the object to a length in bytes.
+    ///
+    /// The object is extended with zeroes. Its position is unchanged.
+    pub async fn truncate(&mut self, len: u64) -> Result<(), Error> {
+        self.complete_pending().await?;
+        call(
+            &self.client,
+            LO_TRUNCATE64,
+            &[(&self.fd, Type::INT4), (&(len as i64), Type::INT8)],
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Closes the handle.
+    pub async fn close(mut self) -> Result<(), Error> {
+        self.complete_pending().await?;
+        call(&self.client, LO_CLOSE, &[(&self.fd, Type::INT4)]).await?;
+        Ok(())
+    }
+
+    async fn complete_pending(&mut self) -> Result<(), Error> {
+        futures_util::future::poll_fn(|cx| self.poll_pending(cx)).await?;
+        // The position of the server must match the one seen by the caller.
+        let rewind = self.read_buf.len() as i64;
+        if rewind > 0 {
+            self.read_buf.clear();
+            seek(&self.client, self.fd, -rewind, SEEK_CUR).await?;
+        }
+        Ok(())
+    }
+
+    /// Drives the pending operation, if any, to completion.
+    fn poll_pending(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        let result = match &mut self.pending {
+            Some(Pending::Read(future)) => ready!(future.as_mut().poll(cx)).map(|data| {
+                self.read_buf = data;
+            }),
+            Some(Pending::Write(future)) => ready!(future.as_mut().poll(cx)).map(|_| ()),
+            Some(Pending::Seek(future)) => ready!(future.as_mut().poll(cx)).map(|_| ()),
+            None => Ok(()),
+        };
+        self.pending = None;
+        Poll::Ready(result)
+    }
+}
+
+impl AsyncRead for LargeObject<'_> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = &mut *self;
+        if !matches!(this.pending, Some(Pending::Read(_))) {
+            ready!(this.poll_pending(cx)).map_err(io::Error::other)?;
+            if this.read_buf.is_empty() {
+                let client = this.client.clone();
+                let fd = this.fd;
+                let len = buf.remaining().min(CHUNK_SIZE);
+                this.pending = Some(Pending::Read(Box::pin(async move {
+                    read(&client, fd, len).await
+                })));
+            }
+        }
+        ready!(this.poll_pending(cx)).map_err(io::Error::other)?;
+
+        let len = buf.remaining().min(this.read_buf.len());
+        buf.put_slice(&this.read_buf[..len]);
+        this.read_buf.advance(len);
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for LargeObject<'_> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = &mut *self;
+        if !matches!(this.pending, Some(Pending::Write(_))) {
+            ready!(this.poll_pending(cx)).map_err(io::Error::other)?;
+            let client = this.client.clone();
+            let fd = this.fd;
+            let data = Bytes::copy_from_slice(&buf[..buf.len().min(CHUNK_SIZE)]);
+            let rewind = this.read_buf.len() as i64;
+            this.read_buf.clear();
+            this.pending = Some(Pending::Write(Box::pin(async move {
+                if rewind > 0 {
+                    seek(&client, fd, -rewind, SEEK_CUR).await?;
+                }
+                write(&client, fd, &data).await
+            })));
+        }
+
+        let future = match &mut this.pending {
+            Some(Pending::Write(future)) => future,
+            _ => unreachable!(),
+        };
+        let result = ready!(future.as_mut().poll(cx));
+        this.pending = None;
+        Poll::Ready(result.map_err(io::Error::other))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // Bytes read ahead are kept, since they don't need to be flushed.
+        match self.pending {
+            Some(Pending::Read(_)) => Poll::Ready(Ok(())),
+            _ => self.poll_pending(cx).map_err(io::Error::other),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+impl AsyncSeek for LargeObject<'_> {
+    fn start_seek(mut self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+        let this = &mut *self;
+        if this.pending.is_some() {
+            return Err(io::Error::other(
+                "other operation is pending, call poll_complete before start_seek",
+            ));
+        }
+
+        let (offset, whence) = match position {
+            SeekFrom::Start(offset) => (offset as i64, SEEK_SET),
+            // The bytes read ahead are ahead of the position seen by the caller.
+            SeekFrom::Current(offset) => (offset - this.read_buf.len() as i64, SEEK_CUR),
+            SeekFrom::End(offset) => (offset, SEEK_END),
+        };
+        this.read_buf.clear();
+        let client = this.client.clone();
+        let fd = this.fd;
+        this.pending = Some(Pending::Seek(Box::pin(async move {
+            seek(&client, fd, offset, whence).await
+        })));
+        Ok(())
+    }
+
+    fn poll_complete(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        let this = &mut *self;
+        match &mut this.pending {
+            Some(Pending::Seek(future)) => {
+                let result = ready!(future.as_mut().poll(cx));
+                this.pending = None;
+                Poll::Ready(result.map_err(io::Error::other))
+            }
+            // Without a seek in progress, the position is meaningless: the other operations are only completed so
+            // that one can start.
+            _ => this
+                .poll_pending(cx)
+                .map(|r| r.map(|()| 0).map_err(io::Error::other)),
+        }
+    }
+}
@@ -104,6 +104,11 @@
 //! | Feature | Description | Extra dependencies | Default |
 //! | ------- | ----------- | ------------------ | ------- |
 //! | `runtime` | Enable convenience API for the connection process based on the `tokio` crate. | [tokio](https://crates.io/crates/tokio) 1.0 with the features `net` and `time` | yes |
+//! | `arrow` | Enable the `arrow` module converting query results and binary COPY data to and from Apache Arrow record batches. | [arrow-array](https://crates.io/crates/arrow-array) and [arrow-schema](https://crates.io/crates/arrow-schema) 60 | no |
+//! | `deadpool` | Enable the `deadpool` module providing a manager for `deadpool` connection pools. | [deadpool](https://crates.io/crates/deadpool) 0.10 | no |
+//! | `default-application-name` | Default the `fallback_application_name` configuration option to the name of the running executable. | - | no |
+//! | `migrations` | Enable the `migrations` module applying schema migrations. | - | no |
+//! | `tracing` | Enable `tracing` spans with OpenTelemetry database semantic convention attributes for connection, prepare, query, transaction and COPY operations, and events for the notices sent by the server. | [tracing](https://crates.io/crates/tracing) 0.1 | no |
 //! | `array-impls` | Enables `ToSql` and `FromSql` trait impls for arrays | - | no |
 //! | `with-bit-vec-0_6` | Enable support for the `bit-vec` crate. | [bit-vec](https://crates.io/crates/bit-vec) 0.6 | no |
 //! | `with-chrono-0_4` | Enable support for the `chrono` crate. | [chrono](https://crates.io/crates/chrono) 0.4 | no |
@@ -118,22 +123,34 @@
 //! | `with-time-0_3` | Enable support for the 0.3 version of the `time` crate. | [time](https://crates.io/crates/time/0.3.0) 0.3 | no |
 #![warn(rust_2018_idioms, clippy::all, missing_docs)]
 
-pub use crate::cancel_token::CancelToken;
-pub use crate::client::Client;
+#[cfg(feature = "runtime")]
+pub use crate::cancel_token::{CancelOptions, CancelOutcome};
+pub use crate::cancel_token::{CancelToken, QueryCancelToken};
+pub use crate::client::{Client, TransactionStatus};
 pub use crate::config::Config;
 pub use crate::connection::Connection;
-pub use crate::copy_in::CopyInSink;
+pub use crate::copy_both::CopyBothDuplex;
+pub use crate::copy_in::{CopyInProgress, CopyInProgressStream, CopyInSink};
 pub use crate::copy_out::CopyOutStream;
+pub use crate::cursor::Cursor;
 use crate::error::DbError;
 pub use crate::error::Error;
+pub use crate::execute_many::ExecuteManyError;
 pub use crate::generic_client::GenericClient;
+pub use crate::listen::NotificationStream;
+pub use crate::parameter_changes::ParameterChanges;
 pub use crate::portal::Portal;
+pub use crate::portal_cursor::PortalCursor;
 pub use crate::query::RowStream;
+pub use crate::query_options::QueryOptions;
 pub use crate::row::{Row, SimpleQueryRow};
-pub use crate::simple_query::SimpleQueryStream;
+pub use crate::row_chunks::RowChunks;
+pub use crate::rows::{RowRef, Rows, RowsIter};
+pub use crate::server_version::{ParseServerVersionError, ServerVersion};
+pub use crate::simple_query::{SimpleColumn, SimpleQueryStream};
 #[cfg(feature = "runtime")]
 pub use crate::socket::Socket;
-pub use crate::statement::{Column, Statement};
+pub use crate::statement::{Column, Statement, StatementDescription};
 #[cfg(feature = "runtime")]
 use crate::tls::MakeTlsConnect;
 pub use crate::tls::NoTls;
@@ -142,6 +159,9 @@ pub use crate::transaction::Transaction;
 pub use crate::transaction_builder::{IsolationLevel, TransactionBuilder};
 use crate::types::ToSql;
 
+#[cfg(feature = "arrow")]
+pub mod arrow;
+mod batch;
 pub mod binary_copy;
 mod bind;
 #[cfg(feature = "runtime")]
@@ -153,26 +173,64 @@ mod codec;
 pub mod config;
 #[cfg(feature = "runtime")]
 mod connect;
+mod connect_gss;
 mod connect_raw;
 #[cfg(feature = "runtime")]
 mod connect_socket;
 mod connect_tls;
 mod connection;
+mod copy_both;
 mod copy_in;
+pub mod copy_options;
 mod copy_out;
+mod cursor;
+#[cfg(feature = "deadpool")]
+pub mod deadpool;
 pub mod error;
+pub mod escape;
+mod execute_many;
+#[cfg(feature = "with-serde_json-1")]
+pub mod explain;
+pub mod export;
+mod framed;
 mod generic_client;
+pub mod gss;
+mod infer;
+mod instrument;
+pub mod interceptor;
 #[cfg(not(target_arch = "wasm32"))]
 mod keepalive;
+pub mod large_object;
+mod listen;
 mod maybe_tls_stream;
+pub mod metrics;
+#[cfg(feature = "migrations")]
+pub mod migrations;
+mod parameter_changes;
+pub mod passthrough;
+pub mod pipeline;
 mod portal;
+mod portal_cursor;
 mod prepare;
 mod query;
+mod query_options;
+pub mod raw;
+pub mod replication;
+#[cfg(feature = "retry")]
+pub mod retry;
 pub mod row;
+mod row_chunks;
+mod rows;
+pub mod schema;
+pub mod security;
+mod server_version;
 mod simple_query;
 #[cfg(feature = "runtime")]
 mod socket;
 mod statement;
+mod statement_cache;
+pub mod stats;
+mod streamed;
 pub mod tls;
 mod to_statement;
 mod transaction;
@@ -234,7 +292,8 @@ pub enum AsyncMessage {
     Notice(DbError),
     /// A notification.
     ///
-    /// Connections can subscribe to notifications with the `LISTEN` command.
+    /// Connections can subscribe to notifications with the `LISTEN` command. Notifications of the channels subscribed
+    /// to with `Client::listen` are delivered to their streams instead.
     Notification(Notification),
 }
 
@@ -245,9 +304,12 @@ pub enum SimpleQueryMessage {
     /// A row of data.
     Row(SimpleQueryRow),
     /// A statement in the query has completed.
-    ///
-    /// The number of rows modified or selected is returned.
-    CommandComplete(u64),
+    CommandComplete {
+        /// The command tag of the statement, such as `INSERT 0 2`, or an empty string for an empty statement.
+        tag: String,
+        /// The number of rows modified or selected.
+        rows: u64,
+    },
 }
 
 fn slice_iter<'a>(
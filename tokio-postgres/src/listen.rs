@@ -0,0 +1,99 @@
+use crate::client::{InnerClient, RequestPermit};
+use crate::codec::FrontendMessage;
+use crate::connection::RequestMessages;
+use crate::{simple_query, Error, Notification};
+use futures_channel::mpsc;
+use futures_util::{Stream, StreamExt};
+use log::debug;
+use postgres_protocol::escape::{escape_identifier, escape_literal};
+use std::pin::Pin;
+use std::sync::{Arc, Weak};
+use std::task::{Context, Poll};
+
+pub async fn listen(client: &Arc<InnerClient>, channel: &str) -> Result<NotificationStream, Error> {
+    debug!("listening on channel {}", channel);
+
+    // The stream is registered first so that no notification sent once `LISTEN` completes is missed. If `LISTEN`
+    // fails, dropping the stream unregisters it.
+    let stream = NotificationStream {
+        client: Arc::downgrade(client),
+        channel: channel.to_string(),
+        receiver: client.session().subscribe(channel),
+    };
+    simple_query::batch_execute(client, &format!("LISTEN {}", escape_identifier(channel))).await?;
+
+    Ok(stream)
+}
+
+pub async fn unlisten(client: &InnerClient, channel: &str) -> Result<(), Error> {
+    debug!("unlistening on channel {}", channel);
+
+    client.session().close_channel(channel);
+    simple_query::batch_execute(client, &format!("UNLISTEN {}", escape_identifier(channel))).await
+}
+
+pub async fn unlisten_all(client: &InnerClient) -> Result<(), Error> {
+    debug!("unlistening on all channels");
+
+    client.session().close_channels();
+    simple_query::batch_execute(client, "UNLISTEN *").await
+}
+
+pub async fn notify(client: &InnerClient, channel: &str, payload: &str) -> Result<(), Error> {
+    let query = format!(
+        "NOTIFY {}, {}",
+        escape_identifier(channel),
+        escape_literal(payload)
+    );
+    simple_query::batch_execute(client, &query).await
+}
+
+/// A stream of the notifications sent on a channel.
+///
+/// The stream ends when the connection closes. Once the last stream of a channel is dropped, the session stops
+/// listening on it.
+#[derive(Debug)]
+pub struct NotificationStream {
+    client: Weak<InnerClient>,
+    channel: String,
+    receiver: mpsc::UnboundedReceiver<Notification>,
+}
+
+impl NotificationStream {
+    /// Returns the channel of the stream.
+    pub fn channel(&self) -> &str {
+        &self.channel
+    }
+}
+
+impl Stream for NotificationStream {
+    type Item = Notification;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Notification>> {
+        self.receiver.poll_next_unpin(cx)
+    }
+}
+
+impl Drop for NotificationStream {
+    fn drop(&mut self) {
+        self.receiver.close();
+
+        let client = match self.client.upgrade() {
+            Some(client) => client,
+            None => return,
+        };
+        if !client.session().unsubscribe(&self.channel) {
+            return;
+        }
+
+        let query = format!("UNLISTEN {}", escape_identifier(&self.channel));
+        let buf = match simple_query::encode(&client, &query) {
+            Ok(buf) => buf,
+            Err(_) => return,
+        };
+        let _ = client.send(
+            RequestPermit::exempt(),
+            RequestMessages::Single(FrontendMessage::Raw(buf)),
+        );
+    }
+}
@@ -1,5 +1,6 @@
+use crate::connect_gss::GssStream;
 use crate::tls::{ChannelBinding, TlsStream};
-use std::io;
+use std::io::{self, IoSlice};
 use std::pin::Pin;
 use std::task::{Context, Poll};
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
@@ -7,6 +8,7 @@ use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 pub enum MaybeTlsStream<S, T> {
     Raw(S),
     Tls(T),
+    Gss(GssStream<S>),
 }
 
 impl<S, T> AsyncRead for MaybeTlsStream<S, T>
@@ -22,6 +24,7 @@ where
         match &mut *self {
             MaybeTlsStream::Raw(s) => Pin::new(s).poll_read(cx, buf),
             MaybeTlsStream::Tls(s) => Pin::new(s).poll_read(cx, buf),
+            MaybeTlsStream::Gss(s) => Pin::new(s).poll_read(cx, buf),
         }
     }
 }
@@ -39,6 +42,27 @@ where
         match &mut *self {
             MaybeTlsStream::Raw(s) => Pin::new(s).poll_write(cx, buf),
             MaybeTlsStream::Tls(s) => Pin::new(s).poll_write(cx, buf),
+            MaybeTlsStream::Gss(s) => Pin::new(s).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        match &mut *self {
+            MaybeTlsStream::Raw(s) => Pin::new(s).poll_write_vectored(cx, bufs),
+            MaybeTlsStream::Tls(s) => Pin::new(s).poll_write_vectored(cx, bufs),
+            MaybeTlsStream::Gss(s) => Pin::new(s).poll_write_vectored(cx, bufs),
+        }
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        match self {
+            MaybeTlsStream::Raw(s) => s.is_write_vectored(),
+            MaybeTlsStream::Tls(s) => s.is_write_vectored(),
+            MaybeTlsStream::Gss(s) => s.is_write_vectored(),
         }
     }
 
@@ -46,6 +70,7 @@ where
         match &mut *self {
             MaybeTlsStream::Raw(s) => Pin::new(s).poll_flush(cx),
             MaybeTlsStream::Tls(s) => Pin::new(s).poll_flush(cx),
+            MaybeTlsStream::Gss(s) => Pin::new(s).poll_flush(cx),
         }
     }
 
@@ -53,6 +78,7 @@ where
         match &mut *self {
             MaybeTlsStream::Raw(s) => Pin::new(s).poll_shutdown(cx),
             MaybeTlsStream::Tls(s) => Pin::new(s).poll_shutdown(cx),
+            MaybeTlsStream::Gss(s) => Pin::new(s).poll_shutdown(cx),
         }
     }
 }
@@ -64,7 +90,7 @@ where
 {
     fn channel_binding(&self) -> ChannelBinding {
         match self {
-            MaybeTlsStream::Raw(_) => ChannelBinding::none(),
+            MaybeTlsStream::Raw(_) | MaybeTlsStream::Gss(_) => ChannelBinding::none(),
             MaybeTlsStream::Tls(s) => s.channel_binding(),
         }
     }
@@ -0,0 +1,409 @@
+//! Metrics hooks.
+//!
+//! A [`Metrics`] implementation registered with [`Config::metrics`] is invoked by the client and connection internals
+//! as operations complete and data is exchanged with the server. It can be used to feed Prometheus, StatsD or any
+//! other metrics system without wrapping every call into the client.
+//!
+//! Independently of any handler, connections count the bytes and messages they exchange with the server, and keep a
+//! histogram of the round trips of their requests, which can be inspected with [`Connection::wire_stats`].
+//!
+//! [`Config::metrics`]: crate::Config::metrics
+//! [`Connection::wire_stats`]: crate::Connection::wire_stats
+
+use crate::interceptor::Operation;
+use crate::Error;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// An event in the life of a pooled connection.
+///
+/// This crate doesn't pool connections itself. Pool implementations can report these events to the handler
+/// returned by [`Config::get_metrics`] so that all connection metrics end up in one place.
+///
+/// [`Config::get_metrics`]: crate::Config::get_metrics
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PoolEvent {
+    /// A new connection was added to the pool.
+    Created,
+    /// A connection was checked out of the pool, after waiting for the given duration.
+    Checkout(Duration),
+    /// A connection was returned to the pool.
+    Checkin,
+    /// No connection could be checked out of the pool in time.
+    Timeout,
+    /// A connection was removed from the pool.
+    Closed,
+}
+
+/// A handler for the metrics produced by a client.
+///
+/// All methods have default implementations which do nothing, so implementors only need to override the ones they are
+/// interested in. Methods are called on the task performing the operation, or on the connection's task for the byte
+/// counters, and should not block.
+pub trait Metrics: Send + Sync {
+    /// Called once a connection attempt has completed, successfully or not.
+    fn connect(&self, duration: Duration, error: Option<&Error>) {
+        let _ = (duration, error);
+    }
+
+    /// Called once a statement has been prepared, successfully or not.
+    fn prepare(&self, duration: Duration, error: Option<&Error>) {
+        let _ = (duration, error);
+    }
+
+    /// Called once a query has completed, successfully or not.
+    ///
+    /// For queries returning a stream, the duration includes the time taken to consume the rows.
+    fn query(&self, operation: Operation, duration: Duration, error: Option<&Error>) {
+        let _ = (operation, duration, error);
+    }
+
+    /// Called with the number of rows returned or affected by a successful query, if the server reported one.
+    fn rows(&self, operation: Operation, rows: u64) {
+        let _ = (operation, rows);
+    }
+
+    /// Called when data is written to the server.
+    fn bytes_sent(&self, bytes: u64) {
+        let _ = bytes;
+    }
+
+    /// Called when data is read from the server.
+    fn bytes_received(&self, bytes: u64) {
+        let _ = bytes;
+    }
+
+    /// Called for each message written to the server, with its type tag.
+    ///
+    /// The startup message, which has no tag, is reported with a tag of 0.
+    fn message_sent(&self, tag: u8) {
+        let _ = tag;
+    }
+
+    /// Called for each message read from the server, with its type tag.
+    fn message_received(&self, tag: u8) {
+        let _ = tag;
+    }
+
+    /// Called when the connection has flushed the data written to the server, with the number of bytes flushed.
+    ///
+    /// Requests made while the connection is busy are written together and flushed at once, so the flushes show how
+    /// well requests are batched.
+    fn flush(&self, bytes: u64) {
+        let _ = bytes;
+    }
+
+    /// Called once the server has completed a request, with the time elapsed since the connection started writing it.
+    ///
+    /// The server handles requests in order, so the round trip of a pipelined request includes the time spent waiting
+    /// for the requests queued before it. Keepalive round trips, as enabled by `Config::keepalive_query_interval`, are
+    /// not reported.
+    fn round_trip(&self, duration: Duration) {
+        let _ = duration;
+    }
+
+    /// Called by connection pools with the events of their connections.
+    fn pool(&self, event: PoolEvent) {
+        let _ = event;
+    }
+}
+
+/// The bytes and messages exchanged by a connection with the server.
+///
+/// Messages are counted by their type tag, as defined by the [protocol]. The startup message, which has no tag, is
+/// counted with a tag of 0.
+///
+/// [protocol]: https://www.postgresql.org/docs/current/protocol-message-formats.html
+#[derive(Clone)]
+pub struct WireStats {
+    bytes_sent: u64,
+    bytes_received: u64,
+    messages_sent: Box<[u64; 256]>,
+    messages_received: Box<[u64; 256]>,
+    flushes: u64,
+    round_trips: Histogram,
+}
+
+impl Default for WireStats {
+    fn default() -> WireStats {
+        WireStats {
+            bytes_sent: 0,
+            bytes_received: 0,
+            messages_sent: Box::new([0; 256]),
+            messages_received: Box::new([0; 256]),
+            flushes: 0,
+            round_trips: Histogram::default(),
+        }
+    }
+}
+
+impl WireStats {
+    /// Returns the number of bytes written to the server.
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes_sent
+    }
+
+    /// Returns the number of bytes read from the server.
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received
+    }
+
+    /// Returns the number of messages with the given tag written to the server.
+    pub fn messages_sent(&self, tag: u8) -> u64 {
+        self.messages_sent[usize::from(tag)]
+    }
+
+    /// Returns the number of messages with the given tag read from the server.
+    pub fn messages_received(&self, tag: u8) -> u64 {
+        self.messages_received[usize::from(tag)]
+    }
+
+    /// Returns an iterator over the tags and counts of the messages written to the server.
+    ///
+    /// Tags of which no message was written are skipped.
+    pub fn sent(&self) -> impl Iterator<Item = (u8, u64)> + '_ {
+        counts(&self.messages_sent)
+    }
+
+    /// Returns an iterator over the tags and counts of the messages read from the server.
+    ///
+    /// Tags of which no message was read are skipped.
+    pub fn received(&self) -> impl Iterator<Item = (u8, u64)> + '_ {
+        counts(&self.messages_received)
+    }
+
+    /// Returns the number of times data written to the server was flushed.
+    pub fn flushes(&self) -> u64 {
+        self.flushes
+    }
+
+    /// Returns the histogram of the round trips of the requests completed by the server.
+    ///
+    /// See [`Metrics::round_trip`] for details.
+    pub fn round_trips(&self) -> &Histogram {
+        &self.round_trips
+    }
+
+    /// Records the messages encoded in a buffer written to the server.
+    pub(crate) fn record_sent(&mut self, mut buf: &[u8], metrics: Option<&MetricsHandle>) {
+        self.bytes_sent += buf.len() as u64;
+        if let Some(metrics) = metrics {
+            metrics.0.bytes_sent(buf.len() as u64);
+        }
+
+        while buf.len() >= 5 {
+            // Frontend message tags are never 0, while the length prefix of the untagged startup message always
+            // starts with one.
+            let (tag, len) = match buf[0] {
+                0 => (
+                    0,
+                    u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]) as usize,
+                ),
+                tag => (
+                    tag,
+                    u32::from_be_bytes([buf[1], buf[2], buf[3], buf[4]]) as usize + 1,
+                ),
+            };
+            self.messages_sent[usize::from(tag)] += 1;
+            if let Some(metrics) = metrics {
+                metrics.0.message_sent(tag);
+            }
+            buf = &buf[len.clamp(1, buf.len())..];
+        }
+    }
+
+    /// Records bytes written to the server in the middle of a message.
+    pub(crate) fn record_sent_bytes(&mut self, len: usize, metrics: Option<&MetricsHandle>) {
+        self.bytes_sent += len as u64;
+        if let Some(metrics) = metrics {
+            metrics.0.bytes_sent(len as u64);
+        }
+    }
+
+    /// Records a message written to the server.
+    pub(crate) fn record_sent_message(
+        &mut self,
+        tag: u8,
+        len: usize,
+        metrics: Option<&MetricsHandle>,
+    ) {
+        self.bytes_sent += len as u64;
+        self.messages_sent[usize::from(tag)] += 1;
+        if let Some(metrics) = metrics {
+            metrics.0.bytes_sent(len as u64);
+            metrics.0.message_sent(tag);
+        }
+    }
+
+    /// Records a message read from the server.
+    pub(crate) fn record_received(&mut self, tag: u8, len: usize, metrics: Option<&MetricsHandle>) {
+        self.bytes_received += len as u64;
+        self.messages_received[usize::from(tag)] += 1;
+        if let Some(metrics) = metrics {
+            metrics.0.bytes_received(len as u64);
+            metrics.0.message_received(tag);
+        }
+    }
+
+    /// Records a flush of the data written to the server.
+    pub(crate) fn record_flush(&mut self, bytes: u64, metrics: Option<&MetricsHandle>) {
+        self.flushes += 1;
+        if let Some(metrics) = metrics {
+            metrics.0.flush(bytes);
+        }
+    }
+
+    /// Records the round trip of a request.
+    pub(crate) fn record_round_trip(
+        &mut self,
+        duration: Duration,
+        metrics: Option<&MetricsHandle>,
+    ) {
+        self.round_trips.record(duration);
+        if let Some(metrics) = metrics {
+            metrics.0.round_trip(duration);
+        }
+    }
+}
+
+fn counts(counts: &[u64; 256]) -> impl Iterator<Item = (u8, u64)> + '_ {
+    counts
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| **count != 0)
+        .map(|(tag, count)| (tag as u8, *count))
+}
+
+impl fmt::Debug for WireStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct Counts<'a>(&'a [u64; 256]);
+
+        impl fmt::Debug for Counts<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_map()
+                    .entries(counts(self.0).map(|(tag, count)| (char::from(tag), count)))
+                    .finish()
+            }
+        }
+
+        f.debug_struct("WireStats")
+            .field("bytes_sent", &self.bytes_sent)
+            .field("bytes_received", &self.bytes_received)
+            .field("messages_sent", &Counts(&self.messages_sent))
+            .field("messages_received", &Counts(&self.messages_received))
+            .field("flushes", &self.flushes)
+            .field("round_trips", &self.round_trips)
+            .finish()
+    }
+}
+
+// The number of buckets of a histogram, the last one being unbounded.
+const BUCKETS: usize = 32;
+
+/// A histogram of durations.
+///
+/// The upper bounds of the buckets are powers of two microseconds, from 1 µs to about 18 minutes, followed by an
+/// unbounded bucket.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Histogram {
+    counts: [u64; BUCKETS],
+    sum: Duration,
+}
+
+impl Histogram {
+    /// Returns the number of durations recorded.
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Returns the sum of the durations recorded.
+    pub fn sum(&self) -> Duration {
+        self.sum
+    }
+
+    /// Returns an iterator over the inclusive upper bounds of the buckets and the number of durations recorded in each.
+    ///
+    /// The bound of the last bucket is `Duration::MAX`. Unlike Prometheus histograms, the counts aren't cumulative.
+    pub fn buckets(&self) -> impl Iterator<Item = (Duration, u64)> + '_ {
+        self.counts.iter().enumerate().map(|(i, count)| {
+            let bound = if i == BUCKETS - 1 {
+                Duration::MAX
+            } else {
+                Duration::from_micros(1 << i)
+            };
+            (bound, *count)
+        })
+    }
+
+    pub(crate) fn record(&mut self, duration: Duration) {
+        let micros = duration.as_micros();
+        // The index of the smallest power of two greater than or equal to the duration.
+        let bucket = if micros <= 1 {
+            0
+        } else {
+            (u128::BITS - (micros - 1).leading_zeros()) as usize
+        };
+        self.counts[bucket.min(BUCKETS - 1)] += 1;
+        self.sum = self.sum.saturating_add(duration);
+    }
+}
+
+impl fmt::Debug for Histogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Histogram")
+            .field("count", &self.count())
+            .field("sum", &self.sum)
+            .finish()
+    }
+}
+
+/// The metrics handler configured for a client.
+#[derive(Clone)]
+pub(crate) struct MetricsHandle(pub(crate) Arc<dyn Metrics>);
+
+impl PartialEq for MetricsHandle {
+    fn eq(&self, other: &MetricsHandle) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for MetricsHandle {}
+
+impl fmt::Debug for MetricsHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Metrics").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Histogram;
+    use std::time::Duration;
+
+    #[test]
+    fn histogram_buckets() {
+        let mut histogram = Histogram::default();
+        histogram.record(Duration::ZERO);
+        histogram.record(Duration::from_micros(2));
+        histogram.record(Duration::from_micros(3));
+        histogram.record(Duration::from_secs(3600));
+
+        let buckets = histogram
+            .buckets()
+            .filter(|(_, count)| *count != 0)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            buckets,
+            [
+                (Duration::from_micros(1), 1),
+                (Duration::from_micros(2), 1),
+                (Duration::from_micros(4), 1),
+                (Duration::MAX, 1),
+            ]
+        );
+        assert_eq!(histogram.count(), 4);
+        assert_eq!(histogram.sum(), Duration::from_micros(3_600_000_005));
+    }
+}
@@ -0,0 +1,218 @@
+//! Schema migrations.
+//!
+//! [`Migrations`] holds an ordered set of migrations, each identified by a version number, and applies the ones a
+//! database hasn't seen yet. The versions which have been applied are recorded in a table of the database, created
+//! on the first run. Runners of the same set of migrations exclude each other with an advisory lock, so several
+//! instances of a service can run them concurrently on startup.
+//!
+//! Each migration runs in its own transaction along with the recording of its version, so a failing migration leaves
+//! the ones before it applied and is retried by the next run.
+//!
+//! Requires the `migrations` Cargo feature.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tokio_postgres::migrations::Migrations;
+//! # async fn run(client: &mut tokio_postgres::Client) -> Result<(), tokio_postgres::Error> {
+//!
+//! let mut migrations = Migrations::new();
+//! migrations
+//!     .sql(1, "create users", "CREATE TABLE users (id INT8 PRIMARY KEY, name TEXT NOT NULL)")
+//!     .function(2, "add admin", |transaction| {
+//!         Box::pin(async move {
+//!             transaction
+//!                 .execute("INSERT INTO users (id, name) VALUES (0, $1)", &[&"admin"])
+//!                 .await?;
+//!             Ok(())
+//!         })
+//!     });
+//!
+//! let applied = migrations.run(client).await?;
+//! println!("applied migrations {:?}", applied);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{Client, Error, Transaction};
+use postgres_protocol::escape::escape_identifier;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+
+type MigrationFn = Box<
+    dyn for<'a> Fn(
+            &'a Transaction<'_>,
+        ) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>>
+        + Send
+        + Sync,
+>;
+
+enum Kind {
+    Sql(String),
+    Function(MigrationFn),
+}
+
+struct Migration {
+    version: i64,
+    name: String,
+    kind: Kind,
+}
+
+/// A set of migrations to apply to a database.
+pub struct Migrations {
+    table: String,
+    migrations: Vec<Migration>,
+}
+
+impl fmt::Debug for Migrations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Migrations")
+            .field("table", &self.table)
+            .field(
+                "migrations",
+                &self
+                    .migrations
+                    .iter()
+                    .map(|m| (m.version, &m.name))
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl Default for Migrations {
+    fn default() -> Migrations {
+        Migrations::new()
+    }
+}
+
+impl Migrations {
+    /// Creates an empty set of migrations, recorded in the `schema_migrations` table.
+    pub fn new() -> Migrations {
+        Migrations {
+            table: "schema_migrations".to_string(),
+            migrations: vec![],
+        }
+    }
+
+    /// Sets the name of the table recording the applied migrations.
+    ///
+    /// The name is quoted, so it is case sensitive and can't be qualified with a schema: the table is created in the
+    /// first schema of the search path. Defaults to `schema_migrations`.
+    pub fn table(&mut self, table: &str) -> &mut Migrations {
+        self.table = table.to_string();
+        self
+    }
+
+    /// Adds a migration executing SQL statements.
+    ///
+    /// The statements are executed like with `Client::batch_execute`, so there can be several of them separated by
+    /// semicolons. They can't include statements which can't run in a transaction, such as
+    /// `CREATE INDEX CONCURRENTLY`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a migration with the same version was already added.
+    pub fn sql(&mut self, version: i64, name: &str, sql: &str) -> &mut Migrations {
+        self.push(version, name, Kind::Sql(sql.to_string()))
+    }
+
+    /// Adds a migration running a function with the transaction the migration is applied in.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a migration with the same version was already added.
+    pub fn function<F>(&mut self, version: i64, name: &str, f: F) -> &mut Migrations
+    where
+        F: for<'a> Fn(
+                &'a Transaction<'_>,
+            ) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.push(version, name, Kind::Function(Box::new(f)))
+    }
+
+    fn push(&mut self, version: i64, name: &str, kind: Kind) -> &mut Migrations {
+        let idx = match self
+            .migrations
+            .binary_search_by_key(&version, |m| m.version)
+        {
+            Ok(_) => panic!("duplicate migration version {}", version),
+            Err(idx) => idx,
+        };
+        self.migrations.insert(
+            idx,
+            Migration {
+                version,
+                name: name.to_string(),
+                kind,
+            },
+        );
+        self
+    }
+
+    /// Applies the migrations which haven't been applied to the database yet, in the order of their versions.
+    ///
+    /// Returns the versions of the migrations applied by this run. Versions recorded in the table which aren't part of
+    /// this set are ignored.
+    pub async fn run(&self, client: &mut Client) -> Result<Vec<i64>, Error> {
+        client
+            .execute("SELECT pg_advisory_lock(hashtext($1))", &[&self.table])
+            .await?;
+        let result = self.run_locked(client).await;
+        let unlock = client
+            .execute("SELECT pg_advisory_unlock(hashtext($1))", &[&self.table])
+            .await;
+        let applied = result?;
+        unlock?;
+        Ok(applied)
+    }
+
+    async fn run_locked(&self, client: &mut Client) -> Result<Vec<i64>, Error> {
+        let table = escape_identifier(&self.table);
+        client
+            .batch_execute(&format!(
+                "CREATE TABLE IF NOT EXISTS {} (
+                    version INT8 PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    applied_at TIMESTAMPTZ NOT NULL DEFAULT now()
+                )",
+                table
+            ))
+            .await?;
+
+        let rows = client
+            .query(&format!("SELECT version FROM {}", table), &[])
+            .await?;
+        let mut done = rows
+            .iter()
+            .map(|row| row.try_get::<_, i64>(0))
+            .collect::<Result<Vec<_>, _>>()?;
+        done.sort_unstable();
+
+        let insert = format!("INSERT INTO {} (version, name) VALUES ($1, $2)", table);
+        let mut applied = vec![];
+        for migration in &self.migrations {
+            if done.binary_search(&migration.version).is_ok() {
+                continue;
+            }
+
+            let transaction = client.transaction().await?;
+            match &migration.kind {
+                Kind::Sql(sql) => transaction.batch_execute(sql).await?,
+                Kind::Function(f) => f(&transaction).await?,
+            }
+            transaction
+                .execute(&insert, &[&migration.version, &migration.name])
+                .await?;
+            transaction.commit().await?;
+
+            applied.push(migration.version);
+        }
+
+        Ok(applied)
+    }
+}
@@ -0,0 +1,20 @@
+use futures_channel::mpsc;
+use futures_util::{Stream, StreamExt};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A stream of the changes of the runtime parameters reported by the server.
+///
+/// Each item is the name of a parameter along with its new value. The stream ends when the connection closes.
+#[derive(Debug)]
+pub struct ParameterChanges {
+    pub(crate) receiver: mpsc::UnboundedReceiver<(String, String)>,
+}
+
+impl Stream for ParameterChanges {
+    type Item = (String, String);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<(String, String)>> {
+        self.receiver.poll_next_unpin(cx)
+    }
+}
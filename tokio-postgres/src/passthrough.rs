@@ -0,0 +1,356 @@
+//! Pass-through of query results in their wire format.
+//!
+//! [`Client::query_raw_passthrough`] executes a statement like [`Client::query_raw`], but yields the bodies of the
+//! `DataRow` messages sent by the server as is, rather than decoding them into [`Row`]s, along with the body of the
+//! `RowDescription` message describing them. This is the cheapest way for proxies to relay results to another peer
+//! speaking the Postgres protocol, since [`write_row_description`] and [`write_data_row`] frame the messages back
+//! exactly as they were received.
+//!
+//! [`Client::query_raw_forward`] goes further and returns all of the messages of the response to the statement, from
+//! `BindComplete` to `ReadyForQuery`, without parsing most of them. They can be written as is to a buffer with
+//! [`ForwardStream::write_to`], or sent to any sink of `Bytes` since the stream yields them, and a filter can drop the
+//! ones the downstream peer doesn't expect. Asynchronous messages, such as notices, are handled by the connection as
+//! usual and never forwarded.
+//!
+//! Values are returned in the binary format, which the format codes of the row description report.
+//!
+//! [`Client::query_raw_passthrough`]: crate::Client::query_raw_passthrough
+//! [`Client::query_raw_forward`]: crate::Client::query_raw_forward
+//! [`Client::query_raw`]: crate::Client::query_raw
+//! [`Row`]: crate::Row
+
+use crate::client::{InnerClient, Responses};
+use crate::codec::FrontendMessage;
+use crate::connection::RequestMessages;
+use crate::error::DbError;
+use crate::instrument::Span;
+use crate::interceptor::{Completion, Operation};
+use crate::query::{self, extract_row_affected};
+use crate::types::BorrowToSql;
+use crate::{Error, Statement};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use futures_util::{ready, Stream, TryStreamExt};
+use pin_project_lite::pin_project;
+use postgres_protocol::message::backend::{
+    DataRowBody, Message, RowDescriptionBody, COMMAND_COMPLETE_TAG, DATA_ROW_TAG,
+    ERROR_RESPONSE_TAG, READY_FOR_QUERY_TAG, ROW_DESCRIPTION_TAG,
+};
+use postgres_protocol::message::frontend;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+pub(crate) async fn query<P, I>(
+    client: &InnerClient,
+    statement: Statement,
+    params: I,
+) -> Result<PassthroughStream, Error>
+where
+    P: BorrowToSql,
+    I: IntoIterator<Item = P>,
+    I::IntoIter: ExactSizeIterator,
+{
+    let interceptors = client.interceptors();
+    let permit = client.reserve().await;
+    let buf = query::encode_execute(client, &interceptors, Operation::Query, &statement, params)?;
+    let buf = describe_portal(&buf)?;
+
+    let span = Span::query(client.instrumentation(), &statement);
+    let mut completion = Completion::new(client, Operation::Query, None);
+    let result = span
+        .instrument(async {
+            let mut responses = query::start(client, permit, buf).await?;
+            let row_description = match responses.next().await? {
+                Message::RowDescription(body) => Some(body),
+                Message::NoData => None,
+                m => return Err(Error::unexpected_message(m)),
+            };
+            Ok((responses, row_description))
+        })
+        .await;
+    let (responses, row_description) = match result {
+        Ok(result) => result,
+        Err(e) => {
+            completion.complete(Some(&statement), Err(&e));
+            return Err(e);
+        }
+    };
+
+    Ok(PassthroughStream {
+        statement,
+        responses,
+        row_description,
+        rows_affected: None,
+        command_tag: None,
+        span,
+        completion,
+    })
+}
+
+// Inserts a Describe message of the unnamed portal after the Bind message starting the messages encoded by
+// `encode_execute`, so that the server describes the rows in the format they are returned in.
+fn describe_portal(buf: &Bytes) -> Result<Bytes, Error> {
+    let bind_len = 1 + (&buf[1..5]).get_i32() as usize;
+
+    let mut described = BytesMut::with_capacity(buf.len() + 7);
+    described.put_slice(&buf[..bind_len]);
+    frontend::describe(b'P', "", &mut described).map_err(Error::encode)?;
+    described.put_slice(&buf[bind_len..]);
+    Ok(described.freeze())
+}
+
+pin_project! {
+    /// A stream of rows in their wire format.
+    ///
+    /// Created with [`Client::query_raw_passthrough`](crate::Client::query_raw_passthrough).
+    pub struct PassthroughStream {
+        statement: Statement,
+        responses: Responses,
+        row_description: Option<RowDescriptionBody>,
+        rows_affected: Option<u64>,
+        command_tag: Option<String>,
+        span: Span,
+        completion: Completion,
+    }
+}
+
+impl Stream for PassthroughStream {
+    type Item = Result<DataRowBody, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let _entered = this.span.enter();
+
+        loop {
+            let message = match ready!(this.responses.poll_next(cx)) {
+                Ok(message) => message,
+                Err(e) => {
+                    this.span.record_error(&e);
+                    this.completion.complete(Some(this.statement), Err(&e));
+                    return Poll::Ready(Some(Err(e)));
+                }
+            };
+
+            match message {
+                Message::DataRow(body) => return Poll::Ready(Some(Ok(body))),
+                Message::CommandComplete(body) => {
+                    let rows = extract_row_affected(&body)?;
+                    this.span.record_rows(rows);
+                    *this.rows_affected = Some(rows);
+
+                    if let Ok(tag) = body.tag() {
+                        *this.command_tag = Some(tag.to_string());
+                    }
+                }
+                Message::EmptyQueryResponse | Message::PortalSuspended => {}
+                Message::ReadyForQuery(_) => {
+                    this.completion
+                        .complete(Some(this.statement), Ok(*this.rows_affected));
+                    return Poll::Ready(None);
+                }
+                m => {
+                    let e = Error::unexpected_message(m);
+                    this.completion.complete(Some(this.statement), Err(&e));
+                    return Poll::Ready(Some(Err(e)));
+                }
+            }
+        }
+    }
+}
+
+impl PassthroughStream {
+    /// Returns the statement being executed.
+    pub fn statement(&self) -> &Statement {
+        &self.statement
+    }
+
+    /// Returns the description of the rows, or `None` if the statement doesn't return any.
+    pub fn row_description(&self) -> Option<&RowDescriptionBody> {
+        self.row_description.as_ref()
+    }
+
+    /// Returns the number of rows affected by the query.
+    ///
+    /// This function will return `None` until the stream has been exhausted.
+    pub fn rows_affected(&self) -> Option<u64> {
+        self.rows_affected
+    }
+
+    /// Returns the command tag of this query.
+    ///
+    /// This is only available after the stream has been exhausted.
+    pub fn command_tag(&self) -> Option<String> {
+        self.command_tag.clone()
+    }
+}
+
+/// Writes the `RowDescription` message with the given body, as it was sent by the server.
+pub fn write_row_description(body: &RowDescriptionBody, buf: &mut BytesMut) {
+    write_message(ROW_DESCRIPTION_TAG, body.column_count(), body.buffer(), buf);
+}
+
+/// Writes the `DataRow` message with the given body, as it was sent by the server.
+pub fn write_data_row(body: &DataRowBody, buf: &mut BytesMut) {
+    write_message(DATA_ROW_TAG, body.column_count(), body.buffer(), buf);
+}
+
+// Both messages start with the number of columns, which isn't part of the buffer of their body.
+fn write_message(tag: u8, columns: u16, body: &[u8], buf: &mut BytesMut) {
+    buf.reserve(7 + body.len());
+    buf.put_u8(tag);
+    buf.put_i32((6 + body.len()) as i32);
+    buf.put_u16(columns);
+    buf.put_slice(body);
+}
+
+pub(crate) async fn forward<P, I>(
+    client: &InnerClient,
+    statement: Statement,
+    params: I,
+) -> Result<ForwardStream, Error>
+where
+    P: BorrowToSql,
+    I: IntoIterator<Item = P>,
+    I::IntoIter: ExactSizeIterator,
+{
+    let interceptors = client.interceptors();
+    let permit = client.reserve().await;
+    let buf = query::encode_execute(client, &interceptors, Operation::Query, &statement, params)?;
+    let buf = describe_portal(&buf)?;
+
+    let span = Span::query(client.instrumentation(), &statement);
+    let mut completion = Completion::new(client, Operation::Query, None);
+    let responses = match client.send(permit, RequestMessages::Single(FrontendMessage::Raw(buf))) {
+        Ok(responses) => responses,
+        Err(e) => {
+            completion.complete(Some(&statement), Err(&e));
+            return Err(e);
+        }
+    };
+
+    Ok(ForwardStream {
+        statement,
+        responses,
+        filter: None,
+        rows_affected: None,
+        error: None,
+        done: false,
+        span,
+        completion,
+    })
+}
+
+/// A stream of the messages of the response to a statement, as they were received from the server.
+///
+/// Created with [`Client::query_raw_forward`](crate::Client::query_raw_forward).
+pub struct ForwardStream {
+    statement: Statement,
+    responses: Responses,
+    filter: Option<Box<dyn FnMut(u8) -> bool + Send>>,
+    rows_affected: Option<u64>,
+    error: Option<Error>,
+    done: bool,
+    span: Span,
+    completion: Completion,
+}
+
+impl ForwardStream {
+    /// Only forwards the messages for which the predicate, called with the tag of each message, returns `true`.
+    ///
+    /// The tags are the `*_TAG` constants of the `postgres_protocol::message::backend` module.
+    pub fn filter_messages<F>(mut self, filter: F) -> ForwardStream
+    where
+        F: FnMut(u8) -> bool + Send + 'static,
+    {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Writes all of the remaining messages to a buffer.
+    pub async fn write_to(&mut self, buf: &mut BytesMut) -> Result<(), Error> {
+        while let Some(message) = self.try_next().await? {
+            buf.put_slice(&message);
+        }
+        Ok(())
+    }
+
+    /// Returns the statement being executed.
+    pub fn statement(&self) -> &Statement {
+        &self.statement
+    }
+
+    /// Returns the number of rows affected by the query.
+    ///
+    /// This function will return `None` until the stream has been exhausted.
+    pub fn rows_affected(&self) -> Option<u64> {
+        self.rows_affected
+    }
+
+    /// Returns the error reported by the server, if the statement failed.
+    ///
+    /// Its `ErrorResponse` message is forwarded like any other, so it is only available once it has been.
+    pub fn db_error(&self) -> Option<&DbError> {
+        self.error.as_ref().and_then(Error::as_db_error)
+    }
+}
+
+impl Stream for ForwardStream {
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let _entered = this.span.enter();
+
+        while !this.done {
+            let message = match ready!(this.responses.poll_next_raw(cx)) {
+                Ok(message) => message,
+                Err(e) => {
+                    this.span.record_error(&e);
+                    this.completion.complete(Some(&this.statement), Err(&e));
+                    this.done = true;
+                    return Poll::Ready(Some(Err(e)));
+                }
+            };
+
+            // The few messages giving the outcome of the statement are parsed to report it.
+            match message[0] {
+                COMMAND_COMPLETE_TAG => {
+                    if let Some(Message::CommandComplete(body)) = parse(&message)? {
+                        let rows = extract_row_affected(&body)?;
+                        this.span.record_rows(rows);
+                        this.rows_affected = Some(rows);
+                    }
+                }
+                ERROR_RESPONSE_TAG => {
+                    if let Some(Message::ErrorResponse(body)) = parse(&message)? {
+                        let e = Error::db(body);
+                        this.span.record_error(&e);
+                        this.error = Some(e);
+                    }
+                }
+                READY_FOR_QUERY_TAG => {
+                    let result = match &this.error {
+                        Some(e) => Err(e),
+                        None => Ok(this.rows_affected),
+                    };
+                    this.completion.complete(Some(&this.statement), result);
+                    this.done = true;
+                }
+                _ => {}
+            }
+
+            let forwarded = match &mut this.filter {
+                Some(filter) => filter(message[0]),
+                None => true,
+            };
+            if forwarded {
+                return Poll::Ready(Some(Ok(message)));
+            }
+        }
+
+        Poll::Ready(None)
+    }
+}
+
+fn parse(message: &[u8]) -> Result<Option<Message>, Error> {
+    Message::parse(&mut BytesMut::from(message)).map_err(Error::parse)
+}
@@ -0,0 +1,289 @@
+//! Pipelined execution of statements.
+//!
+//! A [`Pipeline`] created with [`Client::pipeline`] queues statements along with their parameters, and sends them all
+//! to the server at once when run, without waiting for the results of a statement before sending the next one. This
+//! saves a round trip per statement, which dominates the execution time of short statements over high latency
+//! connections.
+//!
+//! With [`Pipeline::run`], each statement is executed independently of the others, in its own implicit transaction
+//! unless the pipeline is run inside of an explicit one, so a failing statement does not prevent the following ones
+//! from being executed.
+//!
+//! [`Pipeline::run_batch`] instead sends the statements as a single batch ending with one `Sync` message, so that the
+//! server executes them in a single implicit transaction: they're all committed together, and a failing statement
+//! rolls back the ones executed before it and skips the following ones.
+//!
+//! [`Client::pipeline`]: crate::Client::pipeline
+
+use crate::client::Responses;
+use crate::codec::FrontendMessage;
+use crate::connection::RequestMessages;
+use crate::interceptor::{Completion, Operation};
+use crate::query::{self, extract_row_affected};
+use crate::to_statement::private::ToStatementType;
+use crate::types::{Format, TextSettings, ToSql};
+use crate::{slice_iter, Client, Error, Row, Statement, ToStatement};
+use bytes::{BufMut, BytesMut};
+use futures_util::future;
+use postgres_protocol::message::backend::Message;
+use postgres_protocol::message::frontend;
+
+// The length of the Sync message ending the messages encoded by `encode_execute`.
+const SYNC_LEN: usize = 5;
+
+enum Kind {
+    Query,
+    Execute,
+}
+
+impl Kind {
+    fn operation(&self) -> Operation {
+        match self {
+            Kind::Query => Operation::Query,
+            Kind::Execute => Operation::Execute,
+        }
+    }
+}
+
+struct Op<'a> {
+    kind: Kind,
+    statement: ToStatementType<'a>,
+    params: Vec<&'a (dyn ToSql + Sync)>,
+}
+
+/// The result of a statement executed in a pipeline.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum PipelineResult {
+    /// The rows returned by a statement queued with `query`.
+    Rows(Vec<Row>),
+    /// The number of rows modified by a statement queued with `execute`.
+    RowsAffected(u64),
+}
+
+impl PipelineResult {
+    /// Returns the rows returned by the statement, if it was queued with `query`.
+    pub fn rows(&self) -> Option<&[Row]> {
+        match self {
+            PipelineResult::Rows(rows) => Some(rows),
+            PipelineResult::RowsAffected(_) => None,
+        }
+    }
+
+    /// Consumes the result, returning the rows returned by the statement if it was queued with `query`.
+    pub fn into_rows(self) -> Option<Vec<Row>> {
+        match self {
+            PipelineResult::Rows(rows) => Some(rows),
+            PipelineResult::RowsAffected(_) => None,
+        }
+    }
+
+    /// Returns the number of rows modified by the statement, if it was queued with `execute`.
+    pub fn rows_affected(&self) -> Option<u64> {
+        match self {
+            PipelineResult::Rows(_) => None,
+            PipelineResult::RowsAffected(rows) => Some(*rows),
+        }
+    }
+}
+
+/// A queue of statements to be sent to the server at once.
+///
+/// Created with [`Client::pipeline`](crate::Client::pipeline).
+pub struct Pipeline<'a> {
+    client: &'a Client,
+    ops: Vec<Op<'a>>,
+}
+
+impl<'a> Pipeline<'a> {
+    pub(crate) fn new(client: &'a Client) -> Pipeline<'a> {
+        Pipeline {
+            client,
+            ops: vec![],
+        }
+    }
+
+    fn push<T>(&mut self, kind: Kind, statement: &'a T, params: &[&'a (dyn ToSql + Sync)])
+    where
+        T: ?Sized + ToStatement,
+    {
+        self.ops.push(Op {
+            kind,
+            statement: statement.__convert(),
+            params: params.to_vec(),
+        });
+    }
+
+    /// Queues a statement returning rows.
+    ///
+    /// Like `Client::query`, the statement can either be a `Statement` or a raw query string.
+    pub fn query<T>(&mut self, statement: &'a T, params: &[&'a (dyn ToSql + Sync)]) -> &mut Self
+    where
+        T: ?Sized + ToStatement,
+    {
+        self.push(Kind::Query, statement, params);
+        self
+    }
+
+    /// Queues a statement returning the number of rows modified.
+    ///
+    /// Like `Client::execute`, the statement can either be a `Statement` or a raw query string.
+    pub fn execute<T>(&mut self, statement: &'a T, params: &[&'a (dyn ToSql + Sync)]) -> &mut Self
+    where
+        T: ?Sized + ToStatement,
+    {
+        self.push(Kind::Execute, statement, params);
+        self
+    }
+
+    /// Returns the number of statements queued.
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Returns `true` if no statement has been queued.
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Sends the queued statements to the server and waits for their results.
+    ///
+    /// Results are returned in the order the statements were queued. Raw query strings are prepared first, which
+    /// takes one additional round trip for the whole pipeline.
+    pub async fn run(self) -> Vec<Result<PipelineResult, Error>> {
+        let client = self.client;
+        let statements = self.prepare().await;
+
+        future::join_all(
+            self.ops
+                .iter()
+                .zip(statements)
+                .map(|(op, statement)| async move {
+                    let statement = statement?;
+                    match op.kind {
+                        Kind::Query => client
+                            .query(&statement, &op.params)
+                            .await
+                            .map(PipelineResult::Rows),
+                        Kind::Execute => client
+                            .execute(&statement, &op.params)
+                            .await
+                            .map(PipelineResult::RowsAffected),
+                    }
+                }),
+        )
+        .await
+    }
+
+    /// Sends the queued statements to the server as a single batch, executed in one implicit transaction.
+    ///
+    /// Results are returned in the order the statements were queued. If a statement fails, its error is returned and
+    /// none of the statements take effect. Inside of an explicit transaction, the transaction is aborted instead.
+    /// Raw query strings are prepared first, which takes one additional round trip for the whole pipeline.
+    pub async fn run_batch(self) -> Result<Vec<PipelineResult>, Error> {
+        let inner = self.client.inner();
+        let statements = self
+            .prepare()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let interceptors = inner.interceptors();
+        let permit = inner.reserve().await;
+        let mut buf = BytesMut::new();
+        for (op, statement) in self.ops.iter().zip(&statements) {
+            let encoded = query::encode_execute(
+                inner,
+                &interceptors,
+                op.kind.operation(),
+                statement,
+                slice_iter(&op.params),
+            )?;
+            // The statements are synced once all of them are executed.
+            buf.put_slice(&encoded[..encoded.len() - SYNC_LEN]);
+        }
+        frontend::sync(&mut buf);
+
+        let mut responses = inner.send(
+            permit,
+            RequestMessages::Single(FrontendMessage::Raw(buf.freeze())),
+        )?;
+        let mut results = Vec::with_capacity(self.ops.len());
+        for (op, statement) in self.ops.iter().zip(&statements) {
+            let mut completion = Completion::new(inner, op.kind.operation(), None);
+            match read_result(&mut responses, &op.kind, statement).await {
+                Ok(result) => {
+                    let rows = match &result {
+                        PipelineResult::Rows(rows) => rows.len() as u64,
+                        PipelineResult::RowsAffected(rows) => *rows,
+                    };
+                    completion.complete(Some(statement), Ok(Some(rows)));
+                    results.push(result);
+                }
+                Err(e) => {
+                    completion.complete(Some(statement), Err(&e));
+                    return Err(e);
+                }
+            }
+        }
+
+        match responses.next().await? {
+            Message::ReadyForQuery(_) => Ok(results),
+            m => Err(Error::unexpected_message(m)),
+        }
+    }
+
+    async fn prepare(&self) -> Vec<Result<Statement, Error>> {
+        let client = self.client;
+
+        // All statements are prepared before any is executed so that they are executed in order. They are prepared as
+        // named statements since concurrently prepared unnamed statements would replace each other.
+        future::join_all(self.ops.iter().map(|op| async move {
+            match op.statement {
+                ToStatementType::Statement(statement) => client.refresh_statement(statement).await,
+                ToStatementType::Query(query) => client.prepare(query).await,
+            }
+        }))
+        .await
+    }
+}
+
+async fn read_result(
+    responses: &mut Responses,
+    kind: &Kind,
+    statement: &Statement,
+) -> Result<PipelineResult, Error> {
+    match responses.next().await? {
+        Message::BindComplete => {}
+        m => return Err(Error::unexpected_message(m)),
+    }
+
+    let mut rows = vec![];
+    loop {
+        match responses.next().await? {
+            Message::DataRow(body) => {
+                if let Kind::Query = kind {
+                    rows.push(Row::new(
+                        statement.clone(),
+                        body,
+                        Format::Binary,
+                        TextSettings::new(),
+                    )?);
+                }
+            }
+            Message::CommandComplete(body) => {
+                return match kind {
+                    Kind::Query => Ok(PipelineResult::Rows(rows)),
+                    Kind::Execute => extract_row_affected(&body).map(PipelineResult::RowsAffected),
+                };
+            }
+            Message::EmptyQueryResponse => {
+                return match kind {
+                    Kind::Query => Ok(PipelineResult::Rows(rows)),
+                    Kind::Execute => Ok(PipelineResult::RowsAffected(0)),
+                };
+            }
+            m => return Err(Error::unexpected_message(m)),
+        }
+    }
+}
@@ -1,25 +1,35 @@
-use crate::client::InnerClient;
+use crate::client::{InnerClient, RequestPermit};
 use crate::codec::FrontendMessage;
 use crate::connection::RequestMessages;
-use crate::Statement;
+use crate::{Error, Statement};
+use postgres_protocol::message::backend::Message;
 use postgres_protocol::message::frontend;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Weak};
 
 struct Inner {
     client: Weak<InnerClient>,
     name: String,
     statement: Statement,
+    closed: AtomicBool,
 }
 
 impl Drop for Inner {
     fn drop(&mut self) {
+        if *self.closed.get_mut() {
+            return;
+        }
+
         if let Some(client) = self.client.upgrade() {
             let buf = client.with_buf(|buf| {
                 frontend::close(b'P', &self.name, buf).unwrap();
                 frontend::sync(buf);
                 buf.split().freeze()
             });
-            let _ = client.send(RequestMessages::Single(FrontendMessage::Raw(buf)));
+            let _ = client.send(
+                RequestPermit::exempt(),
+                RequestMessages::Single(FrontendMessage::Raw(buf)),
+            );
         }
     }
 }
@@ -37,6 +47,7 @@ impl Portal {
             client: Arc::downgrade(client),
             name,
             statement,
+            closed: AtomicBool::new(false),
         }))
     }
 
@@ -44,7 +55,33 @@ impl Portal {
         &self.0.name
     }
 
-    pub(crate) fn statement(&self) -> &Statement {
+    /// Returns the statement the portal was bound from.
+    ///
+    /// The statement can be bound again with different parameters, without being prepared again.
+    pub fn statement(&self) -> &Statement {
         &self.0.statement
     }
+
+    /// Closes the portal.
+    ///
+    /// This is equivalent to `Portal`'s `Drop` implementation, but waits for the server to close the portal and
+    /// provides any error encountered to the caller. Clones of the portal can't be queried once it is closed.
+    pub async fn close(self) -> Result<(), Error> {
+        let client = self.0.client.upgrade().ok_or_else(Error::closed)?;
+        self.0.closed.store(true, Ordering::SeqCst);
+
+        let permit = client.reserve().await;
+        let buf = client.with_buf(|buf| {
+            frontend::close(b'P', &self.0.name, buf).unwrap();
+            frontend::sync(buf);
+            buf.split().freeze()
+        });
+        let mut responses =
+            client.send(permit, RequestMessages::Single(FrontendMessage::Raw(buf)))?;
+
+        match responses.next().await? {
+            Message::CloseComplete => Ok(()),
+            m => Err(Error::unexpected_message(m)),
+        }
+    }
 }
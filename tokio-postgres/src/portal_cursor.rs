@@ -0,0 +1,74 @@
+use crate::{Error, Portal, Row, Transaction};
+use futures_util::{pin_mut, TryStreamExt};
+
+/// The rows of a portal, fetched incrementally.
+///
+/// Returned by [`Transaction::bind_cursor`]. Each call to [`fetch_next_chunk`] resumes the execution of the portal,
+/// returning at most the requested number of rows, until all of its rows have been returned. Unlike a [`Cursor`], no
+/// `FETCH` statement is involved, and the portal only lasts for the duration of the transaction which bound it.
+///
+/// The portal is closed when the `PortalCursor` is dropped, unless it has been cloned with [`portal`].
+///
+/// [`fetch_next_chunk`]: PortalCursor::fetch_next_chunk
+/// [`Cursor`]: crate::Cursor
+/// [`portal`]: PortalCursor::portal
+pub struct PortalCursor<'a> {
+    transaction: &'a Transaction<'a>,
+    portal: Portal,
+    done: bool,
+}
+
+impl<'a> PortalCursor<'a> {
+    pub(crate) fn new(transaction: &'a Transaction<'a>, portal: Portal) -> PortalCursor<'a> {
+        PortalCursor {
+            transaction,
+            portal,
+            done: false,
+        }
+    }
+
+    /// Returns the portal the rows are fetched from.
+    pub fn portal(&self) -> &Portal {
+        &self.portal
+    }
+
+    /// Determines if all of the rows of the portal have been returned.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Fetches at most `max_rows` of the remaining rows of the portal, returning them along with whether more rows
+    /// may remain.
+    ///
+    /// All of the remaining rows are returned if `max_rows` is negative or 0. The server only finds out that no rows
+    /// remain once it runs out of them, so if the last rows exactly fill a chunk, more rows are reported to remain and
+    /// the next chunk is empty. Once all of the rows have been returned, no rows are returned without querying the
+    /// server.
+    pub async fn fetch_next_chunk(&mut self, max_rows: i32) -> Result<(Vec<Row>, bool), Error> {
+        if self.done {
+            return Ok((vec![], false));
+        }
+
+        let stream = self
+            .transaction
+            .query_portal_raw(&self.portal, max_rows)
+            .await?;
+        pin_mut!(stream);
+
+        let mut rows = vec![];
+        while let Some(row) = stream.try_next().await? {
+            rows.push(row);
+        }
+        self.done = !stream.is_suspended();
+
+        Ok((rows, !self.done))
+    }
+
+    /// Closes the portal.
+    ///
+    /// This is equivalent to dropping the `PortalCursor`, but waits for the server to close the portal and provides
+    /// any error encountered to the caller.
+    pub async fn close(self) -> Result<(), Error> {
+        self.portal.close().await
+    }
+}
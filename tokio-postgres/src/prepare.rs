@@ -1,16 +1,52 @@
 use crate::client::InnerClient;
 use crate::codec::FrontendMessage;
 use crate::connection::RequestMessages;
-use crate::types::{Oid, Type};
-use crate::{Column, Error, Statement};
+use crate::instrument::Span;
+use crate::interceptor::{Operation, Outcome};
+use crate::types::{Field, Kind, Oid, Type};
+use crate::{query, slice_iter, Column, Error, Row, Statement};
 use bytes::Bytes;
 use fallible_iterator::FallibleIterator;
+use futures_util::TryStreamExt;
 use log::debug;
 use postgres_protocol::message::backend::Message;
 use postgres_protocol::message::frontend;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
+const TYPEINFO_QUERY: &str = "\
+SELECT t.typname, t.typtype, t.typelem, r.rngsubtype, t.typbasetype, n.nspname, t.typrelid
+FROM pg_catalog.pg_type t
+LEFT OUTER JOIN pg_catalog.pg_range r ON r.rngtypid = t.oid
+INNER JOIN pg_catalog.pg_namespace n ON t.typnamespace = n.oid
+WHERE t.oid = $1
+";
+
+const TYPEINFO_ENUM_QUERY: &str = "\
+SELECT enumlabel
+FROM pg_catalog.pg_enum
+WHERE enumtypid = $1
+ORDER BY enumsortorder
+";
+
+const TYPEINFO_COMPOSITE_QUERY: &str = "\
+SELECT attname, atttypid
+FROM pg_catalog.pg_attribute
+WHERE attrelid = $1
+AND NOT attisdropped
+AND attnum > 0
+ORDER BY attnum
+";
+
+// Multirange types only exist since Postgres 14, so this is only queried for them.
+const TYPEINFO_MULTIRANGE_QUERY: &str = "\
+SELECT rngsubtype
+FROM pg_catalog.pg_range
+WHERE rngmultitypid = $1
+";
+
 static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
 
 pub async fn prepare(
@@ -18,15 +54,57 @@ pub async fn prepare(
     query: &str,
     types: &[Type],
     unnamed: bool,
+) -> Result<Statement, Error> {
+    let sql = query;
+    let interceptors = client.interceptors();
+    let query = interceptors.before_sql(Operation::Prepare, query)?;
+
+    let span = Span::prepare(client.instrumentation(), &query);
+    let timer = client
+        .instrumentation()
+        .timer(Operation::Prepare, Some(&query));
+    let result = span
+        .instrument(prepare_inner(client, sql, &query, types, unnamed))
+        .await;
+    if let Some(timer) = timer {
+        timer.finish(result.as_ref().ok(), result.as_ref().map(|_| None));
+    }
+    interceptors.after(&Outcome {
+        operation: Operation::Prepare,
+        sql: Some(&query),
+        statement: result.as_ref().ok(),
+        result: result.as_ref().map(|_| None),
+    });
+
+    let statement = result?;
+    span.record_statement(&statement);
+
+    Ok(statement)
+}
+
+async fn prepare_inner(
+    client: &Arc<InnerClient>,
+    sql: &str,
+    query: &str,
+    types: &[Type],
+    unnamed: bool,
 ) -> Result<Statement, Error> {
     let name = if unnamed {
         String::new()
     } else {
         format!("s{}", NEXT_ID.fetch_add(1, Ordering::SeqCst))
     };
+    // Read before the statement is prepared, so that a concurrent reset can only make it look stale.
+    let epoch = client.statement_epoch();
 
+    let permit = client.reserve().await;
     let buf = encode(client, &name, query, types)?;
-    let mut responses = client.send(RequestMessages::Single(FrontendMessage::Raw(buf)))?;
+    let generation = if unnamed {
+        Some(client.replace_unnamed_statement())
+    } else {
+        None
+    };
+    let mut responses = client.send(permit, RequestMessages::Single(FrontendMessage::Raw(buf)))?;
 
     match responses.next().await? {
         Message::ParseComplete => {}
@@ -47,7 +125,7 @@ pub async fn prepare(
     let mut parameters = vec![];
     let mut it = parameter_description.parameters();
     while let Some(oid) = it.next().map_err(Error::parse)? {
-        let type_ = get_type(oid);
+        let type_ = statement_type(client, oid).await?;
         parameters.push(type_);
     }
 
@@ -55,7 +133,7 @@ pub async fn prepare(
     if let Some(row_description) = row_description {
         let mut it = row_description.fields();
         while let Some(field) = it.next().map_err(Error::parse)? {
-            let type_ = get_type(field.type_oid());
+            let type_ = statement_type(client, field.type_oid()).await?;
             let column = Column {
                 name: field.name().to_string(),
                 table_oid: Some(field.table_oid()).filter(|n| *n != 0),
@@ -67,9 +145,22 @@ pub async fn prepare(
     }
 
     if unnamed {
-        Ok(Statement::unnamed(parameters, columns))
+        Ok(Statement::unnamed(
+            sql.to_string(),
+            generation,
+            parameters,
+            columns,
+        ))
     } else {
-        Ok(Statement::named(client, name, parameters, columns))
+        Ok(Statement::named(
+            client,
+            name,
+            sql.to_string(),
+            types.to_vec(),
+            epoch,
+            parameters,
+            columns,
+        ))
     }
 }
 
@@ -85,8 +176,9 @@ pub(crate) fn encode(
         debug!("preparing query {} with types {:?}: {}", name, types, query);
     }
 
+    let query = client.instrumentation().annotate(query);
     client.with_buf(|buf| {
-        frontend::parse(name, query, types.iter().map(Type::oid), buf).map_err(Error::encode)?;
+        frontend::parse(name, &query, types.iter().map(Type::oid), buf).map_err(Error::encode)?;
         frontend::describe(b'S', name, buf).map_err(Error::encode)?;
         frontend::sync(buf);
         Ok(buf.split().freeze())
@@ -100,3 +192,121 @@ pub fn get_type(oid: Oid) -> Type {
 
     Type::TEXT
 }
+
+/// Returns the type of an OID if it's built into Postgres or was already resolved by the client.
+pub fn cached_type(client: &InnerClient, oid: Oid) -> Option<Type> {
+    Type::from_oid(oid).or_else(|| client.type_(oid))
+}
+
+/// Caches a type which isn't built into Postgres, along with the types it is made of.
+pub fn register_type(client: &InnerClient, type_: &Type) {
+    if Type::from_oid(type_.oid()).is_some() {
+        return;
+    }
+
+    match type_.kind() {
+        Kind::Array(member)
+        | Kind::Range(member)
+        | Kind::Multirange(member)
+        | Kind::Domain(member) => register_type(client, member),
+        Kind::Composite(fields) => {
+            for field in fields {
+                register_type(client, field.type_());
+            }
+        }
+        _ => {}
+    }
+    client.set_type(type_.oid(), type_);
+}
+
+// Types which aren't built in are looked up in the catalog, unless disabled with `Config::resolve_types`.
+async fn statement_type(client: &Arc<InnerClient>, oid: Oid) -> Result<Type, Error> {
+    if let Some(type_) = cached_type(client, oid) {
+        return Ok(type_);
+    }
+
+    if client.resolves_types() {
+        resolve_type(client, oid).await
+    } else {
+        Ok(Type::TEXT)
+    }
+}
+
+pub fn resolve_type(
+    client: &Arc<InnerClient>,
+    oid: Oid,
+) -> Pin<Box<dyn Future<Output = Result<Type, Error>> + Send + '_>> {
+    Box::pin(async move {
+        if let Some(type_) = cached_type(client, oid) {
+            return Ok(type_);
+        }
+
+        let rows = typeinfo(client, TYPEINFO_QUERY, oid).await?;
+        let row = rows.first().ok_or_else(|| Error::unknown_type(oid))?;
+
+        let name: String = row.try_get(0)?;
+        let type_: i8 = row.try_get(1)?;
+        let elem_oid: Oid = row.try_get(2)?;
+        let rngsubtype: Option<Oid> = row.try_get(3)?;
+        let basetype: Oid = row.try_get(4)?;
+        let schema: String = row.try_get(5)?;
+        let relid: Oid = row.try_get(6)?;
+
+        let kind = if type_ == b'e' as i8 {
+            let rows = typeinfo(client, TYPEINFO_ENUM_QUERY, oid).await?;
+            let variants = rows
+                .iter()
+                .map(|row| row.try_get(0))
+                .collect::<Result<_, _>>()?;
+            Kind::Enum(variants)
+        } else if type_ == b'p' as i8 {
+            Kind::Pseudo
+        } else if type_ == b'm' as i8 {
+            let rows = typeinfo(client, TYPEINFO_MULTIRANGE_QUERY, oid).await?;
+            let row = rows.first().ok_or_else(|| Error::unknown_type(oid))?;
+            Kind::Multirange(resolve_type(client, row.try_get(0)?).await?)
+        } else if basetype != 0 {
+            Kind::Domain(resolve_type(client, basetype).await?)
+        } else if elem_oid != 0 {
+            Kind::Array(resolve_type(client, elem_oid).await?)
+        } else if relid != 0 {
+            let rows = typeinfo(client, TYPEINFO_COMPOSITE_QUERY, relid).await?;
+            let mut fields = vec![];
+            for row in rows {
+                let name = row.try_get(0)?;
+                let type_ = resolve_type(client, row.try_get(1)?).await?;
+                fields.push(Field::new(name, type_));
+            }
+            Kind::Composite(fields)
+        } else if let Some(rngsubtype) = rngsubtype {
+            Kind::Range(resolve_type(client, rngsubtype).await?)
+        } else {
+            Kind::Simple
+        };
+
+        let type_ = Type::new(name, oid, kind, schema);
+        client.set_type(oid, &type_);
+
+        Ok(type_)
+    })
+}
+
+async fn typeinfo(
+    client: &Arc<InnerClient>,
+    query: &'static str,
+    oid: Oid,
+) -> Result<Vec<Row>, Error> {
+    let statement = match client.typeinfo_statement(query) {
+        Some(statement) => statement,
+        None => {
+            let statement = prepare(client, query, &[], false).await?;
+            client.set_typeinfo_statement(query, &statement);
+            statement
+        }
+    };
+
+    query::query(client, statement, slice_iter(&[&oid]))
+        .await?
+        .try_collect()
+        .await
+}
@@ -1,22 +1,26 @@
-use crate::client::{InnerClient, Responses};
+use crate::client::{InnerClient, RequestPermit, Responses};
 use crate::codec::FrontendMessage;
 use crate::connection::RequestMessages;
-use crate::types::{BorrowToSql, IsNull};
-use crate::{Column, Error, Portal, Row, Statement};
-use bytes::{BufMut, Bytes, BytesMut};
+use crate::error::DbError;
+use crate::instrument::Span;
+use crate::interceptor::{Completion, Interceptors, Operation, Outcome, Params};
+use crate::streamed::{StreamedRequest, StreamedValue};
+use crate::types::{BorrowToSql, IsNull, Oid, ToText, Type};
+use crate::{Column, Error, Portal, QueryCancelToken, Row, Statement};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
 use fallible_iterator::FallibleIterator;
-use futures_util::{ready, Stream};
+use futures_util::{future, ready, Stream};
 use log::{debug, log_enabled, Level};
 use pin_project_lite::pin_project;
 use postgres_protocol::message::backend::{
     CommandCompleteBody, Message, ParameterDescriptionBody, RowDescriptionBody,
 };
 use postgres_protocol::message::frontend;
-use postgres_types::Format;
+use postgres_types::{Format, TextSettings};
 use std::fmt;
 use std::marker::PhantomPinned;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 use std::task::{Context, Poll};
 
 struct BorrowToSqlParamsDebug<'a, T>(&'a [T]);
@@ -42,66 +46,104 @@ where
     I: IntoIterator<Item = P>,
     I::IntoIter: ExactSizeIterator,
 {
-    let buf = if log_enabled!(Level::Debug) {
-        let params = params.into_iter().collect::<Vec<_>>();
-        debug!(
-            "executing statement {} with parameters: {:?}",
-            statement.name(),
-            BorrowToSqlParamsDebug(params.as_slice()),
-        );
-        encode(client, &statement, params)?
-    } else {
-        encode(client, &statement, params)?
-    };
+    let interceptors = client.interceptors();
+    let permit = client.reserve().await;
+    let messages =
+        encode_execute_streamed(client, &interceptors, Operation::Query, &statement, params)?;
+    query_messages(client, permit, statement, messages).await
+}
 
-    let responses = start(client, buf).await?;
+/// Like `query`, but with the Bind, Execute and Sync messages already encoded by `encode_execute`.
+///
+/// The permit must be reserved before the messages are encoded.
+pub async fn query_encoded(
+    client: &InnerClient,
+    permit: RequestPermit,
+    statement: Statement,
+    buf: Bytes,
+) -> Result<RowStream, Error> {
+    query_messages(
+        client,
+        permit,
+        statement,
+        RequestMessages::Single(FrontendMessage::Raw(buf)),
+    )
+    .await
+}
 
-    Ok(RowStream {
-        statement: None,
-        responses,
-        rows_affected: None,
-        command_tag: None,
-        status: None,
-        output_format: Format::Binary,
-        parameter_description: None,
-        _p: PhantomPinned,
-    })
+/// Like `query_encoded`, but with messages which may stream parameter values, encoded by `encode_execute_streamed`.
+pub async fn query_messages(
+    client: &InnerClient,
+    permit: RequestPermit,
+    statement: Statement,
+    messages: RequestMessages,
+) -> Result<RowStream, Error> {
+    let span = Span::query(client.instrumentation(), &statement);
+    let mut completion = Completion::new(client, Operation::Query, None);
+    let responses = match span
+        .instrument(start_messages(client, permit, messages))
+        .await
+    {
+        Ok(responses) => responses,
+        Err(e) => {
+            completion.complete(Some(&statement), Err(&e));
+            return Err(e);
+        }
+    };
+
+    Ok(RowStream::new(
+        client, statement, responses, span, completion,
+    ))
 }
 
-pub async fn query_txt<S, I>(
+pub async fn query_txt<P, I>(
     client: &Arc<InnerClient>,
     query: &str,
     params: I,
 ) -> Result<RowStream, Error>
 where
-    S: AsRef<str>,
-    I: IntoIterator<Item = Option<S>>,
+    P: ToText,
+    I: IntoIterator<Item = P>,
     I::IntoIter: ExactSizeIterator,
 {
-    let params = params.into_iter();
-
+    // The types of parameters in the text format are left to the server to infer, while those in the binary format
+    // are declared.
+    let params = params.into_iter().collect::<Vec<_>>();
+
+    let interceptors = client.interceptors();
+    let sql = query.to_string();
+    let query = interceptors.before_sql(Operation::Query, query)?;
+    let annotated = client.instrumentation().annotate(&query);
+    let permit = client.reserve().await;
     let buf = client.with_buf(|buf| {
         // Prepare
-        frontend::parse("", query, std::iter::empty(), buf).map_err(Error::encode)?;
+        client.replace_unnamed_statement();
+        frontend::parse("", &annotated, params.iter().map(ToText::type_oid), buf)
+            .map_err(Error::encode)?;
 
-        // Bind, pass params as text, retrieve as binary
+        // Bind, pass params as text unless already encoded, retrieve as text
+        let mut error_idx = 0;
         match frontend::bind(
-            "",                 // empty string selects the unnamed portal
-            "",                 // unnamed prepared statement
-            std::iter::empty(), // all parameters use the default format (text)
-            params,
-            |param, buf| match param {
-                Some(param) => {
-                    buf.put_slice(param.as_ref().as_bytes());
-                    Ok(postgres_protocol::IsNull::No)
+            "", // empty string selects the unnamed portal
+            "", // unnamed prepared statement
+            params.iter().map(|p| match p.encode_format() {
+                Format::Text => 0,
+                Format::Binary => 1,
+            }),
+            params.iter().enumerate(),
+            |(idx, param), buf| match param.to_text(buf) {
+                Ok(IsNull::No) => Ok(postgres_protocol::IsNull::No),
+                Ok(IsNull::Yes) => Ok(postgres_protocol::IsNull::Yes),
+                Err(e) => {
+                    error_idx = idx;
+                    Err(e)
                 }
-                None => Ok(postgres_protocol::IsNull::Yes),
             },
             Some(0), // all text
             buf,
         ) {
             Ok(()) => Ok(()),
-            Err(frontend::BindError::Conversion(e)) => Err(Error::to_sql(e, 0)),
+            Err(frontend::BindError::Conversion(e)) => Err(Error::to_sql(e, error_idx)),
             Err(frontend::BindError::Serialization(e)) => Err(Error::encode(e)),
         }?;
 
@@ -117,16 +159,93 @@ where
         Ok(buf.split().freeze())
     })?;
 
-    // now read the responses
-    let responses = start(client, buf).await?;
+    start_described(client, permit, &query, sql, buf, Format::Text).await
+}
+
+pub async fn query_with_format<P, I>(
+    client: &Arc<InnerClient>,
+    query: &str,
+    params: I,
+    param_formats: &[Format],
+    result_format: Format,
+) -> Result<RowStream, Error>
+where
+    P: AsRef<[u8]>,
+    I: IntoIterator<Item = Option<P>>,
+    I::IntoIter: ExactSizeIterator,
+{
+    let interceptors = client.interceptors();
+    let sql = query.to_string();
+    let query = interceptors.before_sql(Operation::Query, query)?;
+    let annotated = client.instrumentation().annotate(&query);
+    let permit = client.reserve().await;
+    let buf = client.with_buf(|buf| {
+        client.replace_unnamed_statement();
+        frontend::parse("", &annotated, std::iter::empty(), buf).map_err(Error::encode)?;
+
+        match frontend::bind(
+            "",
+            "",
+            param_formats.iter().map(|format| *format as i16),
+            params,
+            |param, buf| match param {
+                Some(param) => {
+                    buf.put_slice(param.as_ref());
+                    Ok(postgres_protocol::IsNull::No)
+                }
+                None => Ok(postgres_protocol::IsNull::Yes),
+            },
+            Some(result_format as i16),
+            buf,
+        ) {
+            Ok(()) => Ok(()),
+            Err(frontend::BindError::Conversion(e)) => Err(Error::to_sql(e, 0)),
+            Err(frontend::BindError::Serialization(e)) => Err(Error::encode(e)),
+        }?;
+
+        frontend::describe(b'S', "", buf).map_err(Error::encode)?;
+        frontend::execute("", 0, buf).map_err(Error::encode)?;
+        frontend::sync(buf);
+
+        Ok(buf.split().freeze())
+    })?;
+
+    start_described(client, permit, &query, sql, buf, result_format).await
+}
+
+// Starts the execution of a statement which is only described along with its rows.
+async fn start_described(
+    client: &Arc<InnerClient>,
+    permit: RequestPermit,
+    query: &str,
+    sql: String,
+    buf: Bytes,
+    output_format: Format,
+) -> Result<RowStream, Error> {
+    let span = Span::query_txt(client.instrumentation(), query);
+    let mut completion = Completion::new(client, Operation::Query, Some(query));
+    let responses = match span.instrument(start(client, permit, buf)).await {
+        Ok(responses) => responses,
+        Err(e) => {
+            completion.complete(None, Err(&e));
+            return Err(e);
+        }
+    };
 
     Ok(RowStream {
         parameter_description: None,
+        cancel_token: QueryCancelToken::new(client.cancel_token(), responses.state().clone()),
         statement: None,
+        sql: Some(sql),
+        client: Some(Arc::downgrade(client)),
+        completion,
         responses,
         command_tag: None,
+        suspended: false,
         status: None,
-        output_format: Format::Text,
+        output_format,
+        text_settings: client.text_settings(),
+        span,
         _p: PhantomPinned,
         rows_affected: None,
     })
@@ -137,22 +256,31 @@ pub async fn query_portal(
     portal: &Portal,
     max_rows: i32,
 ) -> Result<RowStream, Error> {
+    let permit = client.reserve().await;
     let buf = client.with_buf(|buf| {
         frontend::execute(portal.name(), max_rows, buf).map_err(Error::encode)?;
         frontend::sync(buf);
         Ok(buf.split().freeze())
     })?;
 
-    let responses = client.send(RequestMessages::Single(FrontendMessage::Raw(buf)))?;
+    let completion = Completion::new(client, Operation::Query, None);
+    let responses = client.send(permit, RequestMessages::Single(FrontendMessage::Raw(buf)))?;
 
     Ok(RowStream {
         parameter_description: None,
+        cancel_token: QueryCancelToken::new(client.cancel_token(), responses.state().clone()),
         statement: Some(portal.statement().clone()),
+        sql: None,
+        client: None,
+        completion,
         responses,
         rows_affected: None,
         command_tag: None,
+        suspended: false,
         status: None,
         output_format: Format::Binary,
+        text_settings: TextSettings::new(),
+        span: Span::query_portal(client.instrumentation(), portal.statement()),
         _p: PhantomPinned,
     })
 }
@@ -180,36 +308,231 @@ where
     I: IntoIterator<Item = P>,
     I::IntoIter: ExactSizeIterator,
 {
-    let buf = if log_enabled!(Level::Debug) {
+    let interceptors = client.interceptors();
+    let permit = client.reserve().await;
+    let messages = encode_execute_streamed(
+        client,
+        &interceptors,
+        Operation::Execute,
+        &statement,
+        params,
+    )?;
+    execute_messages(client, permit, &interceptors, statement, messages).await
+}
+
+/// Like `execute`, but with the Bind, Execute and Sync messages already encoded by `encode_execute`.
+pub async fn execute_encoded(
+    client: &InnerClient,
+    permit: RequestPermit,
+    interceptors: &Interceptors,
+    statement: Statement,
+    buf: Bytes,
+) -> Result<u64, Error> {
+    execute_messages(
+        client,
+        permit,
+        interceptors,
+        statement,
+        RequestMessages::Single(FrontendMessage::Raw(buf)),
+    )
+    .await
+}
+
+/// Like `execute_encoded`, but with messages which may stream parameter values, encoded by
+/// `encode_execute_streamed`.
+pub async fn execute_messages(
+    client: &InnerClient,
+    permit: RequestPermit,
+    interceptors: &Interceptors,
+    statement: Statement,
+    messages: RequestMessages,
+) -> Result<u64, Error> {
+    let span = Span::execute(client.instrumentation(), &statement);
+    let timer = client.instrumentation().timer(Operation::Execute, None);
+    let result = span
+        .instrument(async {
+            let mut responses = start_messages(client, permit, messages).await?;
+
+            let mut rows = 0;
+            loop {
+                match responses.next().await? {
+                    Message::DataRow(_) => {}
+                    Message::CommandComplete(body) => {
+                        rows = extract_row_affected(&body)?;
+                    }
+                    Message::EmptyQueryResponse => rows = 0,
+                    Message::ReadyForQuery(_) => return Ok(rows),
+                    m => return Err(Error::unexpected_message(m)),
+                }
+            }
+        })
+        .await;
+    if let Some(timer) = timer {
+        timer.finish(Some(&statement), result.as_ref().map(|rows| Some(*rows)));
+    }
+    interceptors.after(&Outcome {
+        operation: Operation::Execute,
+        sql: None,
+        statement: Some(&statement),
+        result: result.as_ref().map(|rows| Some(*rows)),
+    });
+
+    let rows = result?;
+    span.record_rows(rows);
+
+    Ok(rows)
+}
+
+pub fn encode_execute<P, I>(
+    client: &InnerClient,
+    interceptors: &Interceptors,
+    operation: Operation,
+    statement: &Statement,
+    params: I,
+) -> Result<Bytes, Error>
+where
+    P: BorrowToSql,
+    I: IntoIterator<Item = P>,
+    I::IntoIter: ExactSizeIterator,
+{
+    encode_execute_with(client, interceptors, operation, statement, params, None, 0)
+}
+
+/// Like `encode_execute`, but leaves the values of `Streamed` parameters out of the buffer, to be written in chunks.
+pub fn encode_execute_streamed<P, I>(
+    client: &InnerClient,
+    interceptors: &Interceptors,
+    operation: Operation,
+    statement: &Statement,
+    params: I,
+) -> Result<RequestMessages, Error>
+where
+    P: BorrowToSql,
+    I: IntoIterator<Item = P>,
+    I::IntoIter: ExactSizeIterator,
+{
+    encode_execute_limited(client, interceptors, operation, statement, params, 0)
+}
+
+/// Like `encode_execute_streamed`, but suspends the execution of the portal once it returned `max_rows` rows, and
+/// then closes it. All of the rows are returned if `max_rows` is 0.
+pub fn encode_execute_limited<P, I>(
+    client: &InnerClient,
+    interceptors: &Interceptors,
+    operation: Operation,
+    statement: &Statement,
+    params: I,
+    max_rows: i32,
+) -> Result<RequestMessages, Error>
+where
+    P: BorrowToSql,
+    I: IntoIterator<Item = P>,
+    I::IntoIter: ExactSizeIterator,
+{
+    let mut values = vec![];
+    let buf = encode_execute_with(
+        client,
+        interceptors,
+        operation,
+        statement,
+        params,
+        Some(&mut values),
+        max_rows,
+    )?;
+    if values.is_empty() {
+        Ok(RequestMessages::Single(FrontendMessage::Raw(buf)))
+    } else {
+        Ok(RequestMessages::Streamed(StreamedRequest::new(buf, values)))
+    }
+}
+
+fn encode_execute_with<P, I>(
+    client: &InnerClient,
+    interceptors: &Interceptors,
+    operation: Operation,
+    statement: &Statement,
+    params: I,
+    streamed: Option<&mut Vec<StreamedValue>>,
+    max_rows: i32,
+) -> Result<Bytes, Error>
+where
+    P: BorrowToSql,
+    I: IntoIterator<Item = P>,
+    I::IntoIter: ExactSizeIterator,
+{
+    if !interceptors.is_empty() {
+        let params = params.into_iter().collect::<Vec<_>>();
+        let mut params = Params::new(params.iter().map(|p| p.borrow_to_sql()));
+        interceptors.before_execute(operation, statement, &mut params)?;
+        let params = params.iter().collect::<Vec<_>>();
+        return encode_logged(client, statement, params, streamed, max_rows);
+    }
+
+    encode_logged(client, statement, params, streamed, max_rows)
+}
+
+fn encode_logged<P, I>(
+    client: &InnerClient,
+    statement: &Statement,
+    params: I,
+    streamed: Option<&mut Vec<StreamedValue>>,
+    max_rows: i32,
+) -> Result<Bytes, Error>
+where
+    P: BorrowToSql,
+    I: IntoIterator<Item = P>,
+    I::IntoIter: ExactSizeIterator,
+{
+    if log_enabled!(Level::Debug) {
         let params = params.into_iter().collect::<Vec<_>>();
         debug!(
             "executing statement {} with parameters: {:?}",
             statement.name(),
             BorrowToSqlParamsDebug(params.as_slice()),
         );
-        encode(client, &statement, params)?
+        encode_with(client, statement, params, streamed, max_rows)
     } else {
-        encode(client, &statement, params)?
-    };
+        encode_with(client, statement, params, streamed, max_rows)
+    }
+}
 
-    let mut responses = start(client, buf).await?;
+/// Replaces the statement bound by messages encoded by `encode_execute` with another one.
+///
+/// The statements must have the same parameter types, since the parameters are already encoded.
+pub fn rebind(buf: &Bytes, statement: &Statement) -> Bytes {
+    // The Bind message starts the buffer: its tag and length, then the empty portal name and the statement name.
+    let len = (&buf[1..5]).get_i32() as usize;
+    let name_end = 6 + buf[6..].iter().position(|b| *b == 0).unwrap();
+    let name = statement.name().as_bytes();
+
+    let mut rebound = BytesMut::with_capacity(buf.len() - (name_end - 6) + name.len());
+    rebound.put_u8(b'B');
+    rebound.put_i32((len - (name_end - 6) + name.len()) as i32);
+    rebound.put_u8(0);
+    rebound.put_slice(name);
+    rebound.put_slice(&buf[name_end..]);
+    rebound.freeze()
+}
 
-    let mut rows = 0;
-    loop {
-        match responses.next().await? {
-            Message::DataRow(_) => {}
-            Message::CommandComplete(body) => {
-                rows = extract_row_affected(&body)?;
-            }
-            Message::EmptyQueryResponse => rows = 0,
-            Message::ReadyForQuery(_) => return Ok(rows),
-            m => return Err(Error::unexpected_message(m)),
-        }
-    }
+pub async fn start(
+    client: &InnerClient,
+    permit: RequestPermit,
+    buf: Bytes,
+) -> Result<Responses, Error> {
+    start_messages(
+        client,
+        permit,
+        RequestMessages::Single(FrontendMessage::Raw(buf)),
+    )
+    .await
 }
 
-async fn start(client: &InnerClient, buf: Bytes) -> Result<Responses, Error> {
-    let mut responses = client.send(RequestMessages::Single(FrontendMessage::Raw(buf)))?;
+async fn start_messages(
+    client: &InnerClient,
+    permit: RequestPermit,
+    messages: RequestMessages,
+) -> Result<Responses, Error> {
+    let mut responses = client.send(permit, messages)?;
 
     loop {
         match responses.next().await? {
@@ -220,15 +543,17 @@ async fn start(client: &InnerClient, buf: Bytes) -> Result<Responses, Error> {
     }
 }
 
-fn make_statement(
+pub fn make_statement(
+    sql: String,
     parameter_description: ParameterDescriptionBody,
     row_description: Option<RowDescriptionBody>,
+    get_type: impl Fn(Oid) -> Type,
 ) -> Result<Statement, Error> {
     let mut parameters = vec![];
     let mut it = parameter_description.parameters();
 
     while let Some(oid) = it.next().map_err(Error::parse).unwrap() {
-        let type_ = crate::prepare::get_type(oid);
+        let type_ = get_type(oid);
         parameters.push(type_);
     }
 
@@ -238,7 +563,7 @@ fn make_statement(
         let mut it = row_description.fields();
 
         while let Some(field) = it.next().map_err(Error::parse)? {
-            let type_ = crate::prepare::get_type(field.type_oid());
+            let type_ = get_type(field.type_oid());
             let column = Column {
                 name: field.name().to_string(),
                 table_oid: Some(field.table_oid()).filter(|n| *n != 0),
@@ -250,18 +575,38 @@ fn make_statement(
         }
     }
 
-    Ok(Statement::unnamed(parameters, columns))
+    Ok(Statement::unnamed(sql, None, parameters, columns))
 }
 
 pub fn encode<P, I>(client: &InnerClient, statement: &Statement, params: I) -> Result<Bytes, Error>
+where
+    P: BorrowToSql,
+    I: IntoIterator<Item = P>,
+    I::IntoIter: ExactSizeIterator,
+{
+    encode_with(client, statement, params, None, 0)
+}
+
+fn encode_with<P, I>(
+    client: &InnerClient,
+    statement: &Statement,
+    params: I,
+    streamed: Option<&mut Vec<StreamedValue>>,
+    max_rows: i32,
+) -> Result<Bytes, Error>
 where
     P: BorrowToSql,
     I: IntoIterator<Item = P>,
     I::IntoIter: ExactSizeIterator,
 {
     client.with_buf(|buf| {
-        encode_bind(statement, params, "", buf)?;
-        frontend::execute("", 0, buf).map_err(Error::encode)?;
+        encode_bind_with(client, statement, params, "", buf, streamed)?;
+        frontend::execute("", max_rows, buf).map_err(Error::encode)?;
+        if max_rows > 0 {
+            // In a transaction, the unnamed portal of a suspended execution would otherwise remain open until the
+            // next query.
+            frontend::close(b'P', "", buf).map_err(Error::encode)?;
+        }
         frontend::sync(buf);
 
         Ok(buf.split().freeze())
@@ -269,16 +614,39 @@ where
 }
 
 pub fn encode_bind<P, I>(
+    client: &InnerClient,
+    statement: &Statement,
+    params: I,
+    portal: &str,
+    buf: &mut BytesMut,
+) -> Result<(), Error>
+where
+    P: BorrowToSql,
+    I: IntoIterator<Item = P>,
+    I::IntoIter: ExactSizeIterator,
+{
+    encode_bind_with(client, statement, params, portal, buf, None)
+}
+
+// Values of `Streamed` parameters are left out of the buffer when `streamed` is set, and buffered otherwise. `buf`
+// must be empty in that case, so that the offsets of the values are those of the encoded messages.
+fn encode_bind_with<P, I>(
+    client: &InnerClient,
     statement: &Statement,
     params: I,
     portal: &str,
     buf: &mut BytesMut,
+    mut streamed: Option<&mut Vec<StreamedValue>>,
 ) -> Result<(), Error>
 where
     P: BorrowToSql,
     I: IntoIterator<Item = P>,
     I::IntoIter: ExactSizeIterator,
 {
+    if statement.is_replaced(client.unnamed_statement()) {
+        return Err(Error::unnamed_statement());
+    }
+
     let param_types = statement.params();
     let params = params.into_iter();
 
@@ -294,17 +662,31 @@ where
     let params = params.into_iter();
 
     let mut error_idx = 0;
-    let r = frontend::bind(
+    let r = frontend::bind_deferred(
         portal,
         statement.name(),
         param_formats,
         params.zip(param_types).enumerate(),
-        |(idx, (param, ty)), buf| match param.borrow_to_sql().to_sql_checked(ty, buf) {
-            Ok(IsNull::No) => Ok(postgres_protocol::IsNull::No),
-            Ok(IsNull::Yes) => Ok(postgres_protocol::IsNull::Yes),
-            Err(e) => {
-                error_idx = idx;
-                Err(e)
+        |(idx, (param, ty)), buf| {
+            let param = param.borrow_to_sql();
+            if let Some(streamed) = &mut streamed {
+                if let Some(r) = param.to_sql_stream(ty) {
+                    let (len, chunks) = r.inspect_err(|_| error_idx = idx)?;
+                    streamed.push(StreamedValue {
+                        offset: buf.len(),
+                        len,
+                        chunks,
+                    });
+                    return Ok(frontend::BindValue::Deferred(len));
+                }
+            }
+            match param.to_sql_checked(ty, buf) {
+                Ok(IsNull::No) => Ok(frontend::BindValue::Written),
+                Ok(IsNull::Yes) => Ok(frontend::BindValue::Null),
+                Err(e) => {
+                    error_idx = idx;
+                    Err(e)
+                }
             }
         },
         Some(1),
@@ -321,67 +703,213 @@ pin_project! {
     /// A stream of table rows.
     pub struct RowStream {
         statement: Option<Statement>,
+        // The query of a statement which is only described once executed.
+        sql: Option<String>,
+        // The client of a statement which is only described once executed, whose resolved types it uses.
+        client: Option<Weak<InnerClient>>,
         responses: Responses,
         rows_affected: Option<u64>,
         command_tag: Option<String>,
+        suspended: bool,
         output_format: Format,
+        text_settings: TextSettings,
         status: Option<u8>,
         parameter_description: Option<ParameterDescriptionBody>,
+        span: Span,
+        completion: Completion,
+        cancel_token: QueryCancelToken,
 
         #[pin]
         _p: PhantomPinned,
     }
 }
 
+impl RowStream {
+    /// Creates a stream of the rows of a statement bound in the binary format.
+    pub(crate) fn new(
+        client: &InnerClient,
+        statement: Statement,
+        responses: Responses,
+        span: Span,
+        completion: Completion,
+    ) -> RowStream {
+        RowStream {
+            cancel_token: QueryCancelToken::new(client.cancel_token(), responses.state().clone()),
+            statement: Some(statement),
+            sql: None,
+            client: None,
+            completion,
+            responses,
+            rows_affected: None,
+            command_tag: None,
+            suspended: false,
+            status: None,
+            output_format: Format::Binary,
+            text_settings: TextSettings::new(),
+            parameter_description: None,
+            span,
+            _p: PhantomPinned,
+        }
+    }
+}
+
 impl Stream for RowStream {
     type Item = Result<Row, Error>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let this = self.project();
+        let _entered = this.span.enter();
 
         loop {
-            match ready!(this.responses.poll_next(cx)?) {
+            let message = match ready!(this.responses.poll_next(cx)) {
+                Ok(message) => message,
+                Err(e) => {
+                    this.span.record_error(&e);
+                    this.completion.complete(this.statement.as_ref(), Err(&e));
+                    return Poll::Ready(Some(Err(e)));
+                }
+            };
+
+            let message = match describe(
+                message,
+                this.sql,
+                this.client,
+                this.parameter_description,
+                this.statement,
+            )? {
+                Some(message) => message,
+                None => continue,
+            };
+
+            match message {
                 Message::DataRow(body) => {
                     return Poll::Ready(Some(Ok(Row::new(
                         this.statement.as_ref().unwrap().clone(),
                         body,
                         *this.output_format,
+                        this.text_settings.clone(),
                     )?)))
                 }
                 Message::CommandComplete(body) => {
-                    *this.rows_affected = Some(extract_row_affected(&body)?);
+                    let rows = extract_row_affected(&body)?;
+                    this.span.record_rows(rows);
+                    *this.rows_affected = Some(rows);
 
                     if let Ok(tag) = body.tag() {
                         *this.command_tag = Some(tag.to_string());
                     }
                 }
-                Message::ParameterDescription(body) => {
-                    *this.parameter_description = Some(body);
-                }
-                Message::NoData => {
-                    *this.statement = Some(make_statement(
-                        this.parameter_description.take().unwrap(),
-                        None,
-                    )?);
-                }
-                Message::RowDescription(body) => {
-                    *this.statement = Some(make_statement(
-                        this.parameter_description.take().unwrap(),
-                        Some(body),
-                    )?);
-                }
-                Message::EmptyQueryResponse | Message::PortalSuspended => {}
+                Message::EmptyQueryResponse | Message::CloseComplete => {}
+                Message::PortalSuspended => *this.suspended = true,
                 Message::ReadyForQuery(status) => {
                     *this.status = Some(status.status());
+                    this.completion
+                        .complete(this.statement.as_ref(), Ok(*this.rows_affected));
                     return Poll::Ready(None);
                 }
-                m => return Poll::Ready(Some(Err(Error::unexpected_message(m)))),
+                m => {
+                    let e = Error::unexpected_message(m);
+                    this.completion.complete(this.statement.as_ref(), Err(&e));
+                    return Poll::Ready(Some(Err(e)));
+                }
             }
         }
     }
 }
 
+// Builds the statement of a query which is only described along with its rows, returning the messages which don't
+// describe it.
+fn describe(
+    message: Message,
+    sql: &mut Option<String>,
+    client: &Option<Weak<InnerClient>>,
+    parameter_description: &mut Option<ParameterDescriptionBody>,
+    statement: &mut Option<Statement>,
+) -> Result<Option<Message>, Error> {
+    let row_description = match message {
+        Message::ParameterDescription(body) => {
+            *parameter_description = Some(body);
+            return Ok(None);
+        }
+        Message::NoData => None,
+        Message::RowDescription(body) => Some(body),
+        message => return Ok(Some(message)),
+    };
+
+    // Types which aren't built in can't be looked up while the rows are streamed, so only those already resolved by
+    // the client are known, and the others are read as text.
+    let client = client.as_ref().and_then(Weak::upgrade);
+    *statement = Some(make_statement(
+        sql.take().unwrap_or_default(),
+        parameter_description.take().unwrap(),
+        row_description,
+        |oid| match &client {
+            Some(client) => crate::prepare::cached_type(client, oid).unwrap_or(Type::TEXT),
+            None => crate::prepare::get_type(oid),
+        },
+    )?);
+    Ok(None)
+}
+
 impl RowStream {
+    /// Returns the columns of the rows of the stream.
+    ///
+    /// The columns of a query which was not prepared, such as with `query_raw_txt`, are only known once the server has
+    /// described its rows. This function returns `None` until then, which the `ready` method waits for.
+    pub fn columns(&self) -> Option<&[Column]> {
+        self.statement.as_ref().map(Statement::columns)
+    }
+
+    pub(crate) fn statement(&self) -> Option<&Statement> {
+        self.statement.as_ref()
+    }
+
+    /// A poll-based version of `ready`.
+    pub fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        let this = self.project();
+        let _entered = this.span.enter();
+
+        while this.statement.is_none() {
+            let result = match ready!(this.responses.poll_next(cx)) {
+                Ok(message) => describe(
+                    message,
+                    this.sql,
+                    this.client,
+                    this.parameter_description,
+                    this.statement,
+                ),
+                Err(e) => Err(e),
+            };
+            let e = match result {
+                Ok(None) => continue,
+                Ok(Some(m)) => Error::unexpected_message(m),
+                Err(e) => e,
+            };
+            this.span.record_error(&e);
+            this.completion.complete(None, Err(&e));
+            return Poll::Ready(Err(e));
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    /// Waits until the columns of the rows of the stream are known, without receiving any row.
+    ///
+    /// Once this returns, `columns` returns `Some`, even if the query returns no rows. This returns immediately for
+    /// prepared statements, whose columns are always known.
+    pub async fn ready(mut self: Pin<&mut Self>) -> Result<(), Error> {
+        future::poll_fn(|cx| self.as_mut().poll_ready(cx)).await
+    }
+
+    /// Returns a token which can be used to cancel the query of the stream, without affecting the other queries of
+    /// the connection.
+    ///
+    /// The stream is only returned once the server has started sending the results of the query, so the token can't
+    /// cancel a query which produces no output for a long time.
+    pub fn cancel_token(&self) -> QueryCancelToken {
+        self.cancel_token.clone()
+    }
+
     /// Returns the number of rows affected by the query.
     ///
     /// This function will return `None` until the stream has been exhausted.
@@ -396,10 +924,27 @@ impl RowStream {
         self.command_tag.clone()
     }
 
+    /// Determines if the execution of the portal of the stream was suspended once the requested number of rows were
+    /// returned, so that it can be resumed to return the remaining rows.
+    ///
+    /// This is only available after the stream has been exhausted.
+    pub fn is_suspended(&self) -> bool {
+        self.suspended
+    }
+
     /// Returns if the connection is ready for querying, with the status of the connection.
     ///
     /// This might be available only after the stream has been exhausted.
     pub fn ready_status(&self) -> Option<u8> {
         self.status
     }
+
+    /// Returns the notices raised by the server while executing the query, such as those of `RAISE NOTICE`
+    /// statements or of `CREATE TABLE IF NOT EXISTS` statements skipping an existing table.
+    ///
+    /// Notices are received along with the rows, so the list is only complete once the stream has been exhausted.
+    /// They are still reported by `Connection::poll_message` as well.
+    pub fn notices(&self) -> Vec<DbError> {
+        self.responses.state().notices()
+    }
 }
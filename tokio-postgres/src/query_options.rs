@@ -0,0 +1,109 @@
+use crate::client::InnerClient;
+#[cfg(feature = "runtime")]
+use crate::config::SslMode;
+#[cfg(feature = "runtime")]
+use crate::connect_tls::TlsSettings;
+use crate::Error;
+#[cfg(feature = "runtime")]
+use crate::{cancel_query, NoTls};
+#[cfg(feature = "runtime")]
+use log::debug;
+use std::future::Future;
+use std::time::Duration;
+#[cfg(feature = "runtime")]
+use tokio::time;
+
+/// Options controlling the execution of a single query.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct QueryOptions {
+    timeout: Option<Duration>,
+}
+
+impl QueryOptions {
+    /// Creates options using the defaults of the connection.
+    pub fn new() -> QueryOptions {
+        QueryOptions::default()
+    }
+
+    /// Sets the time limit applied by the client to the query.
+    ///
+    /// Defaults to the limit set with `Config::query_timeout`. Requires the `runtime` Cargo feature (enabled by
+    /// default), and is ignored without it.
+    pub fn timeout(mut self, timeout: Duration) -> QueryOptions {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Gets the time limit applied by the client to the query, if one has been set with the `timeout` method.
+    pub fn get_timeout(&self) -> Option<&Duration> {
+        self.timeout.as_ref()
+    }
+}
+
+/// Runs a query, cancelling it if it exceeds its time limit.
+#[cfg(feature = "runtime")]
+pub(crate) async fn with_timeout<F, T>(
+    client: &InnerClient,
+    options: QueryOptions,
+    future: F,
+) -> Result<T, Error>
+where
+    F: Future<Output = Result<T, Error>>,
+{
+    let timeout = match options.timeout.or_else(|| client.query_timeout()) {
+        Some(timeout) => timeout,
+        None => return future.await,
+    };
+
+    match time::timeout(timeout, future).await {
+        Ok(result) => result,
+        Err(_) => {
+            cancel(client).await;
+            Err(Error::timeout())
+        }
+    }
+}
+
+#[cfg(not(feature = "runtime"))]
+pub(crate) async fn with_timeout<F, T>(
+    _: &InnerClient,
+    _: QueryOptions,
+    future: F,
+) -> Result<T, Error>
+where
+    F: Future<Output = Result<T, Error>>,
+{
+    future.await
+}
+
+/// Cancels the request the server is processing if its responses were abandoned, which is the case of the request of
+/// a query which timed out.
+///
+/// The query may not have been started yet if earlier requests are still in progress, in which case it isn't cancelled
+/// and the server runs it once they complete.
+#[cfg(feature = "runtime")]
+async fn cancel(client: &InnerClient) {
+    let state = match client.session().active_request.lock().clone() {
+        Some(state) => state,
+        None => return,
+    };
+    if !state.is_active() || !state.is_abandoned() {
+        return;
+    }
+
+    let token = client.cancel_token();
+    let result = cancel_query::cancel_query(
+        token.socket_config.clone(),
+        &TlsSettings {
+            mode: SslMode::Disable,
+            ..token.tls_settings.clone()
+        },
+        &mut NoTls,
+        token.process_id,
+        token.secret_key,
+    )
+    .await;
+    if let Err(e) = result {
+        debug!("error cancelling timed out query: {}", e);
+    }
+}
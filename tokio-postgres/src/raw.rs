@@ -0,0 +1,119 @@
+//! Requests made of frontend messages built by the caller.
+//!
+//! [`Client::request_raw`] sends a buffer of frontend messages, such as those written by the
+//! [`postgres_protocol::message::frontend`] module, to the server as is, and returns the messages of its response.
+//! This allows using features of the protocol which the client doesn't cover without reimplementing the connection.
+//!
+//! The request must be complete: the server only answers with a `ReadyForQuery` message once it has received the
+//! `Sync` message ending an extended query, or after a simple `Query` message. The connection waits for this
+//! message before handling the next request of the client, so a request missing it blocks the connection.
+//! Sub-protocols which expect more messages from the client once started, like `COPY FROM STDIN`, aren't supported.
+//!
+//! Asynchronous messages, such as notices and notifications, are handled by the connection as usual, and the
+//! transaction status of the client is updated from the `ReadyForQuery` message.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # async fn async_main(client: &tokio_postgres::Client) -> Result<(), tokio_postgres::Error> {
+//! use bytes::BytesMut;
+//! use futures_util::TryStreamExt;
+//! use postgres_protocol::message::backend::Message;
+//! use postgres_protocol::message::frontend;
+//!
+//! let mut buf = BytesMut::new();
+//! frontend::query("SELECT 1", &mut buf).unwrap();
+//!
+//! let mut responses = client.request_raw(buf.freeze())?;
+//! while let Some(message) = responses.try_next().await? {
+//!     if let Message::DataRow(_) = message {
+//!         println!("got a row");
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Client::request_raw`]: crate::Client::request_raw
+
+use crate::client::{InnerClient, RequestPermit, Responses};
+use crate::codec::FrontendMessage;
+use crate::connection::RequestMessages;
+use crate::Error;
+use bytes::Bytes;
+use futures_util::Stream;
+use postgres_protocol::message::backend::{Message, READY_FOR_QUERY_TAG};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+pub(crate) fn request(client: &InnerClient, buf: Bytes) -> Result<RawResponses, Error> {
+    let responses = client.send(
+        RequestPermit::exempt(),
+        RequestMessages::Single(FrontendMessage::Raw(buf)),
+    )?;
+    Ok(RawResponses {
+        responses,
+        done: false,
+    })
+}
+
+/// A stream of the messages of the response to a raw request.
+///
+/// The stream ends after the `ReadyForQuery` message, which it yields. Errors reported by the server are yielded as
+/// `Err`s, but don't end the stream, since the server keeps answering until the end of the request.
+///
+/// Created with [`Client::request_raw`](crate::Client::request_raw).
+pub struct RawResponses {
+    responses: Responses,
+    done: bool,
+}
+
+impl RawResponses {
+    /// Like `poll_next`, but returns the messages as they were received, including their tag and length, and error
+    /// responses.
+    pub fn poll_next_bytes(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes, Error>>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+
+        let message = match self.responses.poll_next_raw(cx) {
+            Poll::Ready(Ok(message)) => message,
+            Poll::Ready(Err(e)) => {
+                self.done = true;
+                return Poll::Ready(Some(Err(e)));
+            }
+            Poll::Pending => return Poll::Pending,
+        };
+        if message[0] == READY_FOR_QUERY_TAG {
+            self.done = true;
+        }
+        Poll::Ready(Some(Ok(message)))
+    }
+}
+
+impl Stream for RawResponses {
+    type Item = Result<Message, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+
+        match self.responses.poll_next(cx) {
+            Poll::Ready(Ok(message)) => {
+                if let Message::ReadyForQuery(_) = message {
+                    self.done = true;
+                }
+                Poll::Ready(Some(Ok(message)))
+            }
+            Poll::Ready(Err(e)) => {
+                // Errors reported by the server are followed by the rest of the response.
+                if e.as_db_error().is_none() {
+                    self.done = true;
+                }
+                Poll::Ready(Some(Err(e)))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! Streaming replication.
+//!
+//! Replication requires a connection opened in a replication mode, set with `Config::replication_mode`. The stream
+//! of a replication slot is started by executing `START_REPLICATION` with `Client::copy_both_simple`, and its data is
+//! then decoded by wrapping the returned duplex in a [`ReplicationStream`], or in a [`LogicalReplicationStream`] for
+//! a logical slot using the `pgoutput` plugin.
+//!
+//! The server must be kept informed of the progress of the client with standby status updates, both to release the
+//! WAL it no longer needs and to avoid being disconnected after `wal_sender_timeout`. A primary keepalive message
+//! whose `reply` is set asks for an update immediately.
+//!
+//! ```no_run
+//! use futures_util::StreamExt;
+//! use postgres_protocol::message::backend::ReplicationMessage;
+//! use std::time::SystemTime;
+//! use tokio_postgres::replication::ReplicationStream;
+//! use tokio_postgres::types::PgLsn;
+//! use tokio_postgres::Client;
+//!
+//! # async fn stream(client: &Client) -> Result<(), tokio_postgres::Error> {
+//! let duplex = client
+//!     .copy_both_simple::<bytes::Bytes>("START_REPLICATION SLOT my_slot PHYSICAL 0/0")
+//!     .await?;
+//! let stream = ReplicationStream::new(duplex);
+//! futures_util::pin_mut!(stream);
+//!
+//! while let Some(message) = stream.next().await {
+//!     match message? {
+//!         ReplicationMessage::XLogData(body) => {
+//!             let lsn = PgLsn::from(body.wal_end());
+//!             stream
+//!                 .as_mut()
+//!                 .standby_status_update(lsn, lsn, lsn, SystemTime::now(), 0)
+//!                 .await?;
+//!         }
+//!         ReplicationMessage::PrimaryKeepAlive(body) if body.reply() == 1 => {
+//!             let lsn = PgLsn::from(body.wal_end());
+//!             stream
+//!                 .as_mut()
+//!                 .standby_status_update(lsn, lsn, lsn, SystemTime::now(), 0)
+//!                 .await?;
+//!         }
+//!         _ => {}
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::copy_both::CopyBothDuplex;
+use crate::types::PgLsn;
+use crate::Error;
+use bytes::{Bytes, BytesMut};
+use futures_util::{ready, SinkExt, Stream};
+use pin_project_lite::pin_project;
+use postgres_protocol::message::backend::{LogicalReplicationMessage, ReplicationMessage};
+use postgres_protocol::message::frontend;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// The number of seconds from the Unix epoch to the Postgres epoch, midnight on 2000-01-01.
+const POSTGRES_EPOCH_OFFSET: u64 = 946_684_800;
+
+pin_project! {
+    /// A stream of replication messages.
+    ///
+    /// The data of the `XLogData` messages is left raw, as its format depends on the kind of the replication slot.
+    pub struct ReplicationStream {
+        #[pin]
+        duplex: CopyBothDuplex<Bytes>,
+    }
+}
+
+impl ReplicationStream {
+    /// Creates a new replication stream from the duplex returned by `START_REPLICATION`.
+    pub fn new(duplex: CopyBothDuplex<Bytes>) -> ReplicationStream {
+        ReplicationStream { duplex }
+    }
+
+    /// Sends a standby status update to the server.
+    ///
+    /// `write_lsn`, `flush_lsn` and `apply_lsn` are the positions up to which WAL was respectively received, durably
+    /// stored, and applied by the client. `reply` is 1 to ask the server to reply immediately with a keepalive
+    /// message, or 0 otherwise.
+    pub async fn standby_status_update(
+        self: Pin<&mut Self>,
+        write_lsn: PgLsn,
+        flush_lsn: PgLsn,
+        apply_lsn: PgLsn,
+        timestamp: SystemTime,
+        reply: u8,
+    ) -> Result<(), Error> {
+        let mut buf = BytesMut::new();
+        frontend::standby_status_update(
+            write_lsn.into(),
+            flush_lsn.into(),
+            apply_lsn.into(),
+            postgres_timestamp(timestamp),
+            reply,
+            &mut buf,
+        );
+
+        self.project().duplex.send(buf.freeze()).await
+    }
+
+    /// Ends the replication, discarding any data the server sends before acknowledging the end of the stream.
+    pub async fn finish(self: Pin<&mut Self>) -> Result<(), Error> {
+        self.project().duplex.finish().await
+    }
+}
+
+impl Stream for ReplicationStream {
+    type Item = Result<ReplicationMessage<Bytes>, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        match ready!(this.duplex.poll_next(cx)) {
+            Some(Ok(buf)) => {
+                Poll::Ready(Some(ReplicationMessage::parse(&buf).map_err(Error::parse)))
+            }
+            Some(Err(e)) => Poll::Ready(Some(Err(e))),
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+pin_project! {
+    /// A stream of logical replication messages of the `pgoutput` plugin.
+    ///
+    /// The data of the `XLogData` messages is decoded into [`LogicalReplicationMessage`]s. The plugin must be asked
+    /// for its text format protocol, which is the default, and must not be asked to stream in-progress transactions.
+    pub struct LogicalReplicationStream {
+        #[pin]
+        stream: ReplicationStream,
+    }
+}
+
+impl LogicalReplicationStream {
+    /// Creates a new logical replication stream from the duplex returned by `START_REPLICATION`.
+    pub fn new(duplex: CopyBothDuplex<Bytes>) -> LogicalReplicationStream {
+        LogicalReplicationStream {
+            stream: ReplicationStream::new(duplex),
+        }
+    }
+
+    /// Sends a standby status update to the server.
+    ///
+    /// Like `ReplicationStream::standby_status_update`.
+    pub async fn standby_status_update(
+        self: Pin<&mut Self>,
+        write_lsn: PgLsn,
+        flush_lsn: PgLsn,
+        apply_lsn: PgLsn,
+        timestamp: SystemTime,
+        reply: u8,
+    ) -> Result<(), Error> {
+        self.project()
+            .stream
+            .standby_status_update(write_lsn, flush_lsn, apply_lsn, timestamp, reply)
+            .await
+    }
+
+    /// Ends the replication.
+    ///
+    /// Like `ReplicationStream::finish`.
+    pub async fn finish(self: Pin<&mut Self>) -> Result<(), Error> {
+        self.project().stream.finish().await
+    }
+}
+
+impl Stream for LogicalReplicationStream {
+    type Item = Result<ReplicationMessage<LogicalReplicationMessage>, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        match ready!(this.stream.poll_next(cx)) {
+            Some(Ok(ReplicationMessage::XLogData(body))) => {
+                let body = body
+                    .map_data(|buf| LogicalReplicationMessage::parse(&buf))
+                    .map_err(Error::parse)?;
+                Poll::Ready(Some(Ok(ReplicationMessage::XLogData(body))))
+            }
+            Some(Ok(ReplicationMessage::PrimaryKeepAlive(body))) => {
+                Poll::Ready(Some(Ok(ReplicationMessage::PrimaryKeepAlive(body))))
+            }
+            Some(Ok(_)) => Poll::Ready(Some(Err(Error::parse(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "unexpected replication message",
+            ))))),
+            Some(Err(e)) => Poll::Ready(Some(Err(e))),
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+fn postgres_timestamp(timestamp: SystemTime) -> i64 {
+    let epoch = UNIX_EPOCH + Duration::from_secs(POSTGRES_EPOCH_OFFSET);
+    match timestamp.duration_since(epoch) {
+        Ok(duration) => duration.as_micros() as i64,
+        Err(e) => -(e.duration().as_micros() as i64),
+    }
+}
@@ -0,0 +1,328 @@
+//! Automatic reconnection and retries.
+//!
+//! A [`RetryingClient`] owns the configuration of its connection, and transparently opens a new connection the next
+//! time it is used once the previous one has closed. Statements are prepared and cached per connection, so they are
+//! prepared again on new connections.
+//!
+//! Since a request interrupted by a broken connection may or may not have been executed by the server, only
+//! operations marked as idempotent are replayed: those run with [`RetryingClient::query_idempotent`],
+//! [`RetryingClient::execute_idempotent`] or [`RetryingClient::retry`] are retried according to a [`RetryPolicy`]
+//! when the connection breaks, a new connection can't be opened, or the server aborts them with a serialization
+//! failure or a deadlock. A fatal error, or one reporting that the server is shutting down, counts as a broken
+//! connection, and the next attempt is made on a new connection. Other operations return their errors as is.
+//!
+//! Requires the `retry` Cargo feature.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tokio_postgres::retry::{RetryPolicy, RetryingClient};
+//! use tokio_postgres::NoTls;
+//! # async fn run() -> Result<(), tokio_postgres::Error> {
+//!
+//! let config = "host=localhost user=postgres".parse()?;
+//! let client = RetryingClient::connect(config, NoTls, RetryPolicy::new()).await?;
+//! let rows = client
+//!     .query_idempotent("SELECT name FROM users WHERE id = $1", &[&1i64])
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::{Severity, SqlState, SqlStateClass};
+use crate::tls::{MakeTlsConnect, TlsConnect};
+use crate::types::ToSql;
+use crate::{Client, Config, Error, Row, Socket};
+use futures_util::lock::Mutex;
+use log::debug;
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time;
+
+/// The number of retries and the delays between them applied to idempotent operations.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a policy retrying up to 3 times, with delays starting at 100 milliseconds and doubling up to 5 seconds.
+    pub fn new() -> RetryPolicy {
+        RetryPolicy::default()
+    }
+
+    /// Sets the maximum number of times an operation is retried after its first attempt.
+    ///
+    /// Defaults to 3.
+    pub fn max_retries(mut self, max_retries: u32) -> RetryPolicy {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Sets the delay before the first retry, which doubles with each following retry.
+    ///
+    /// Defaults to 100 milliseconds.
+    pub fn initial_backoff(mut self, initial_backoff: Duration) -> RetryPolicy {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Sets the maximum delay between two retries.
+    ///
+    /// Defaults to 5 seconds.
+    pub fn max_backoff(mut self, max_backoff: Duration) -> RetryPolicy {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .checked_mul(1 << retry.min(31))
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff))
+    }
+}
+
+/// A client which reconnects once its connection has closed, and retries idempotent operations.
+///
+/// See the [module level documentation](self) for details.
+pub struct RetryingClient<T> {
+    config: Config,
+    tls: T,
+    policy: RetryPolicy,
+    client: Mutex<Arc<Client>>,
+    reconnects: AtomicU64,
+}
+
+impl<T> RetryingClient<T>
+where
+    T: MakeTlsConnect<Socket> + Clone + Send + Sync + 'static,
+    T::Stream: Send + 'static,
+    T::TlsConnect: Send,
+    <T::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    /// Opens a connection to a PostgreSQL database.
+    ///
+    /// The connection is processed by a task spawned on the current tokio runtime, as are the connections opened to
+    /// replace it.
+    pub async fn connect(
+        config: Config,
+        tls: T,
+        policy: RetryPolicy,
+    ) -> Result<RetryingClient<T>, Error> {
+        let client = connect(&config, &tls).await?;
+        Ok(RetryingClient {
+            config,
+            tls,
+            policy,
+            client: Mutex::new(Arc::new(client)),
+            reconnects: AtomicU64::new(0),
+        })
+    }
+
+    /// Returns the client of the current connection, opening a new connection first if it has closed.
+    ///
+    /// Statements prepared with the returned client can only be used with it, and are lost with its connection.
+    pub async fn client(&self) -> Result<Arc<Client>, Error> {
+        self.client_replacing(None).await
+    }
+
+    // Like `client`, but also opens a new connection if the current client is `broken`, since the connection task may
+    // not have noticed yet that its connection was lost.
+    async fn client_replacing(&self, broken: Option<&Arc<Client>>) -> Result<Arc<Client>, Error> {
+        let mut client = self.client.lock().await;
+        if client.is_closed() || broken.is_some_and(|broken| Arc::ptr_eq(&client, broken)) {
+            debug!("reconnecting after the connection closed");
+            *client = Arc::new(connect(&self.config, &self.tls).await?);
+            self.reconnects.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(client.clone())
+    }
+
+    /// Returns the configuration used to open connections.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Returns the policy applied to idempotent operations.
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// Returns the number of connections opened to replace closed ones.
+    pub fn reconnects(&self) -> u64 {
+        self.reconnects.load(Ordering::Relaxed)
+    }
+
+    /// Executes a statement, returning the resulting rows.
+    ///
+    /// Like `Client::query_cached`, the statement is prepared once per connection. It isn't retried.
+    pub async fn query(
+        &self,
+        statement: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, Error> {
+        self.client().await?.query_cached(statement, params).await
+    }
+
+    /// Executes a statement, returning the number of rows modified.
+    ///
+    /// Like with `query`, the statement is prepared once per connection. It isn't retried.
+    pub async fn execute(
+        &self,
+        statement: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, Error> {
+        let client = self.client().await?;
+        let statement = client.cached_statement(statement).await?;
+        client.execute(&statement, params).await
+    }
+
+    /// Like `query`, but retries the statement according to the policy of the client.
+    ///
+    /// The statement must be idempotent, since it may have been executed by the server when it is retried after its
+    /// connection broke.
+    pub async fn query_idempotent(
+        &self,
+        statement: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, Error> {
+        self.retry(|client| async move { client.query_cached(statement, params).await })
+            .await
+    }
+
+    /// Like `execute`, but retries the statement according to the policy of the client.
+    ///
+    /// The statement must be idempotent, since it may have been executed by the server when it is retried after its
+    /// connection broke.
+    pub async fn execute_idempotent(
+        &self,
+        statement: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, Error> {
+        self.retry(|client| async move {
+            let statement = client.cached_statement(statement).await?;
+            client.execute(&statement, params).await
+        })
+        .await
+    }
+
+    /// Runs an operation with the client of the current connection, retrying it according to the policy of the
+    /// client.
+    ///
+    /// The operation is retried when its connection breaks, a new connection can't be opened, or it fails with an
+    /// error for which `Error::is_retryable` returns `true`. It must be idempotent, since it may have been partially
+    /// executed by the server when it is retried.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn run(
+    /// #     client: &tokio_postgres::retry::RetryingClient<tokio_postgres::NoTls>,
+    /// # ) -> Result<(), tokio_postgres::Error> {
+    /// let name = "steven";
+    /// let id = client
+    ///     .retry(|client| async move {
+    ///         client
+    ///             .execute("INSERT INTO users (name) VALUES ($1) ON CONFLICT DO NOTHING", &[&name])
+    ///             .await?;
+    ///         let row = client.query_one("SELECT id FROM users WHERE name = $1", &[&name]).await?;
+    ///         Ok(row.get::<_, i64>(0))
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn retry<F, Fut, R>(&self, mut f: F) -> Result<R, Error>
+    where
+        F: FnMut(Arc<Client>) -> Fut,
+        Fut: Future<Output = Result<R, Error>>,
+    {
+        let mut retries = 0;
+        let mut broken = None;
+        loop {
+            let (client, result) = match self.client_replacing(broken.take().as_ref()).await {
+                Ok(client) => (Some(client.clone()), f(client).await),
+                Err(e) => (None, Err(e)),
+            };
+            let e = match result {
+                Ok(r) => return Ok(r),
+                Err(e) => e,
+            };
+
+            let retryable = match client {
+                Some(client) if e.is_closed() || client.is_closed() || is_connection_lost(&e) => {
+                    broken = Some(client);
+                    true
+                }
+                Some(_) => e.is_retryable(),
+                // Errors opening a connection are always retried.
+                None => true,
+            };
+            if !retryable || retries >= self.policy.max_retries {
+                return Err(e);
+            }
+
+            let backoff = self.policy.backoff(retries);
+            debug!("retrying in {:?} after error: {}", backoff, e);
+            time::sleep(backoff).await;
+            retries += 1;
+        }
+    }
+}
+
+impl<T> fmt::Debug for RetryingClient<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryingClient")
+            .field("config", &self.config)
+            .field("policy", &self.policy)
+            .field("reconnects", &self.reconnects)
+            .finish()
+    }
+}
+
+// Returns whether an error reports that the server is closing the connection, such as when the backend is terminated.
+//
+// These errors are received before the connection task notices that the connection closed.
+fn is_connection_lost(e: &Error) -> bool {
+    let e = match e.as_db_error() {
+        Some(e) => e,
+        None => return false,
+    };
+
+    matches!(
+        e.parsed_severity(),
+        Some(Severity::Fatal) | Some(Severity::Panic)
+    ) || e.code().class() == SqlStateClass::ConnectionException
+        || *e.code() == SqlState::ADMIN_SHUTDOWN
+        || *e.code() == SqlState::CRASH_SHUTDOWN
+        || *e.code() == SqlState::CANNOT_CONNECT_NOW
+}
+
+async fn connect<T>(config: &Config, tls: &T) -> Result<Client, Error>
+where
+    T: MakeTlsConnect<Socket> + Clone,
+    T::Stream: Send + 'static,
+{
+    let (client, connection) = config.connect(tls.clone()).await?;
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            debug!("connection error: {}", e);
+        }
+    });
+    Ok(client)
+}
@@ -3,11 +3,12 @@
 use crate::row::sealed::{AsName, Sealed};
 use crate::simple_query::SimpleColumn;
 use crate::statement::Column;
-use crate::types::{FromSql, Type, WrongType};
+use crate::types::{FromRow, FromSql, FromSqlOwned, NamedRow, Type, WrongType};
 use crate::{Error, Statement};
 use fallible_iterator::FallibleIterator;
 use postgres_protocol::message::backend::DataRowBody;
-use postgres_types::{Format, WrongFormat};
+use postgres_types::{Format, TextSettings, WrongFormat};
+use std::error;
 use std::fmt;
 use std::ops::Range;
 use std::str;
@@ -41,6 +42,12 @@ pub trait RowIndex: Sealed {
     fn __idx<T>(&self, columns: &[T]) -> Option<usize>
     where
         T: AsName;
+
+    #[doc(hidden)]
+    #[inline]
+    fn __statement_idx(&self, statement: &Statement) -> Option<usize> {
+        self.__idx(statement.columns())
+    }
 }
 
 impl Sealed for usize {}
@@ -78,6 +85,11 @@ impl RowIndex for str {
             .iter()
             .position(|d| d.as_name().eq_ignore_ascii_case(self))
     }
+
+    #[inline]
+    fn __statement_idx(&self, statement: &Statement) -> Option<usize> {
+        statement.column_index(self)
+    }
 }
 
 impl<'a, T> Sealed for &'a T where T: ?Sized + Sealed {}
@@ -93,12 +105,18 @@ where
     {
         T::__idx(*self, columns)
     }
+
+    #[inline]
+    fn __statement_idx(&self, statement: &Statement) -> Option<usize> {
+        T::__statement_idx(*self, statement)
+    }
 }
 
 /// A row of data returned from the database by a query.
 pub struct Row {
     statement: Statement,
     output_format: Format,
+    text_settings: TextSettings,
     body: DataRowBody,
     ranges: Vec<Option<Range<usize>>>,
 }
@@ -116,6 +134,7 @@ impl Row {
         statement: Statement,
         body: DataRowBody,
         output_format: Format,
+        text_settings: TextSettings,
     ) -> Result<Row, Error> {
         let ranges = body.ranges().collect().map_err(Error::parse)?;
         Ok(Row {
@@ -123,6 +142,7 @@ impl Row {
             body,
             ranges,
             output_format,
+            text_settings,
         })
     }
 
@@ -145,6 +165,12 @@ impl Row {
     ///
     /// The value can be specified either by its numeric index in the row, or by its column name.
     ///
+    /// Rows returned in the text format, like those of `Client::query_raw_txt`, are read with `FromSql::from_sql_text`.
+    /// Their values can also be read as strings whatever their type.
+    ///
+    /// Values read as `&str`, `&[u8]`, `Cow<str>` or `Cow<[u8]>` borrow from the buffer of the row rather than being
+    /// copied.
+    ///
     /// # Panics
     ///
     /// Panics if the index is out of bounds or if the value cannot be converted to the specified type.
@@ -169,29 +195,99 @@ impl Row {
         self.get_inner(&idx)
     }
 
+    /// Like `Row::try_get`, but only takes the numeric index of the column.
+    ///
+    /// This skips the resolution of the index, which makes it the fastest way to read the values of rows with many
+    /// columns in a loop.
+    pub fn try_get_by_index<'a, T>(&'a self, idx: usize) -> Result<T, Error>
+    where
+        T: FromSql<'a>,
+    {
+        if idx >= self.ranges.len() {
+            return Err(Error::column(idx.to_string()));
+        }
+        self.get_at(idx)
+    }
+
+    /// Returns the raw bytes of a value of the row, in the format of the row, or `None` if it is `NULL`.
+    ///
+    /// The value can be specified either by its numeric index in the row, or by its column name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    #[track_caller]
+    pub fn get_raw<I>(&self, idx: I) -> Option<&[u8]>
+    where
+        I: RowIndex + fmt::Display,
+    {
+        match self.try_get_raw(&idx) {
+            Ok(ok) => ok,
+            Err(err) => panic!("error retrieving column {}: {}", idx, err),
+        }
+    }
+
+    /// Like `Row::get_raw`, but returns a `Result` rather than panicking.
+    pub fn try_get_raw<I>(&self, idx: I) -> Result<Option<&[u8]>, Error>
+    where
+        I: RowIndex + fmt::Display,
+    {
+        match idx.__statement_idx(&self.statement) {
+            Some(idx) => Ok(self.col_buffer(idx)),
+            None => Err(Error::column(idx.to_string())),
+        }
+    }
+
+    /// Deserializes the row into a value of a type implementing `FromRow`, whose fields are read from the columns of
+    /// the same name.
+    ///
+    /// `TryInto::try_into` takes precedence over this method where the `TryInto` trait is in scope, as it is in the
+    /// prelude of the 2021 edition; call it as `Row::try_into(&row)` there.
+    pub fn try_into<T>(&self) -> Result<T, Error>
+    where
+        T: FromRow,
+    {
+        T::from_row(self).map_err(|e| match e.downcast::<Error>() {
+            Ok(e) => *e,
+            Err(e) => Error::from_row(e),
+        })
+    }
+
     fn get_inner<'a, I, T>(&'a self, idx: &I) -> Result<T, Error>
     where
         I: RowIndex + fmt::Display,
         T: FromSql<'a>,
     {
-        let idx = match idx.__idx(self.columns()) {
-            Some(idx) => idx,
-            None => return Err(Error::column(idx.to_string())),
-        };
-
-        let ty = self.columns()[idx].type_();
-        if !T::accepts(ty) {
-            return Err(Error::from_sql(
-                Box::new(WrongType::new::<T>(ty.clone())),
-                idx,
-            ));
+        match idx.__statement_idx(&self.statement) {
+            Some(idx) => self.get_at(idx),
+            None => Err(Error::column(idx.to_string())),
         }
+    }
+
+    fn get_at<'a, T>(&'a self, idx: usize) -> Result<T, Error>
+    where
+        T: FromSql<'a>,
+    {
+        decode(
+            self.columns()[idx].type_(),
+            self.col_buffer(idx),
+            self.output_format,
+            &self.text_settings,
+            idx,
+        )
+    }
+
+    /// Returns the format the values of the row are in.
+    pub(crate) fn output_format(&self) -> Format {
+        self.output_format
+    }
 
-        FromSql::from_sql_nullable(ty, self.col_buffer(idx)).map_err(|e| Error::from_sql(e, idx))
+    pub(crate) fn text_settings(&self) -> &TextSettings {
+        &self.text_settings
     }
 
     /// Get the raw bytes for the column at the given index.
-    fn col_buffer(&self, idx: usize) -> Option<&[u8]> {
+    pub(crate) fn col_buffer(&self, idx: usize) -> Option<&[u8]> {
         let range = self.ranges[idx].to_owned()?;
         Some(&self.body.buffer()[range])
     }
@@ -218,6 +314,45 @@ impl Row {
     }
 }
 
+/// Deserializes the raw value of the column at `idx`, of type `ty`.
+pub(crate) fn decode<'a, T>(
+    mut ty: &Type,
+    raw: Option<&'a [u8]>,
+    output_format: Format,
+    text_settings: &TextSettings,
+    idx: usize,
+) -> Result<T, Error>
+where
+    T: FromSql<'a>,
+{
+    if !T::accepts(ty) {
+        // Values in the text format can be read as strings whatever their type.
+        if output_format == Format::Text && T::accepts(&Type::TEXT) {
+            ty = &Type::TEXT;
+        } else {
+            return Err(Error::from_sql(
+                Box::new(WrongType::new::<T>(ty.clone())),
+                idx,
+            ));
+        }
+    }
+
+    match output_format {
+        Format::Binary => FromSql::from_sql_nullable(ty, raw),
+        Format::Text => FromSql::from_sql_text_nullable(ty, raw, text_settings),
+    }
+    .map_err(|e| Error::from_sql(e, idx))
+}
+
+impl NamedRow for Row {
+    fn get_named<T>(&self, name: &str) -> Result<T, Box<dyn error::Error + Sync + Send>>
+    where
+        T: FromSqlOwned,
+    {
+        self.try_get(name).map_err(Into::into)
+    }
+}
+
 impl AsName for SimpleColumn {
     fn as_name(&self) -> &str {
         self.name()
@@ -0,0 +1,75 @@
+use crate::types::ToSql;
+use crate::{Error, Portal, Row, ToStatement, Transaction};
+
+/// The batches of rows of a query, fetched incrementally.
+///
+/// Returned by [`Client::query_chunked`] and [`Transaction::query_chunked`]. The query is bound to a portal in a
+/// transaction of its own, and each call to [`next`] fetches at most the requested number of rows from it, so the rows
+/// of the query are never all held in memory at once.
+///
+/// The transaction lasts until the `RowChunks` is dropped, which rolls it back, or until [`finish`] is called, which
+/// commits it.
+///
+/// [`Client::query_chunked`]: crate::Client::query_chunked
+/// [`next`]: RowChunks::next
+/// [`finish`]: RowChunks::finish
+pub struct RowChunks<'a> {
+    transaction: Transaction<'a>,
+    portal: Portal,
+    chunk_size: i32,
+    done: bool,
+}
+
+impl<'a> RowChunks<'a> {
+    pub(crate) async fn new<T>(
+        transaction: Transaction<'a>,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+        chunk_size: i32,
+    ) -> Result<RowChunks<'a>, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        assert!(chunk_size > 0, "chunk size must be positive");
+
+        let portal = transaction.bind(statement, params).await?;
+        Ok(RowChunks {
+            transaction,
+            portal,
+            chunk_size,
+            done: false,
+        })
+    }
+
+    /// Fetches the next batch of rows.
+    ///
+    /// Batches hold `chunk_size` rows, except for the last one which may hold fewer. Returns `None` once all of the rows
+    /// have been returned.
+    pub async fn next(&mut self) -> Result<Option<Vec<Row>>, Error> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let rows = self
+            .transaction
+            .query_portal(&self.portal, self.chunk_size)
+            .await?;
+        if rows.len() < self.chunk_size as usize {
+            self.done = true;
+        }
+
+        if rows.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(rows))
+        }
+    }
+
+    /// Closes the query and commits the transaction it ran in.
+    ///
+    /// Dropping the `RowChunks` rolls the transaction back instead, which only matters if the query made changes.
+    pub async fn finish(self) -> Result<(), Error> {
+        drop(self.portal);
+        self.transaction.commit().await
+    }
+}
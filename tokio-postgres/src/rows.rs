@@ -0,0 +1,350 @@
+use crate::row::{decode, RowIndex};
+use crate::statement::Column;
+use crate::types::FromSql;
+use crate::{Error, Row, RowStream, Statement};
+use futures_util::{pin_mut, TryStreamExt};
+use postgres_types::{Format, TextSettings};
+use std::fmt;
+use std::iter::FusedIterator;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// The rows returned by a query, buffered in a compact columnar container.
+///
+/// Unlike a `Vec<Row>`, where each row holds its own buffer and a reference to its statement, the values of all of
+/// the rows are stored in a single buffer, along with their offsets. This makes large results of small rows much
+/// cheaper to hold in memory. Rows are accessed by their index, and cloning a `Rows` only clones a reference to its
+/// buffers.
+///
+/// Returned by `Client::query_rows` and `Transaction::query_rows`.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn run(client: &tokio_postgres::Client) -> Result<(), tokio_postgres::Error> {
+/// let rows = client.query_rows("SELECT id, name FROM users", &[]).await?;
+/// for i in 0..rows.len() {
+///     let name: &str = rows.get(i, "name");
+///     println!("{}", name);
+/// }
+///
+/// let ids: Vec<i32> = rows.try_get_column("id")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Rows(Arc<RowsInner>);
+
+struct RowsInner {
+    statement: Statement,
+    output_format: Format,
+    text_settings: TextSettings,
+    len: usize,
+    data: Vec<u8>,
+    /// The offsets of the values in `data`, row after row, followed by the end of the last one.
+    offsets: Vec<usize>,
+    /// A bitmap of the values which are `NULL`.
+    nulls: Vec<u64>,
+}
+
+impl fmt::Debug for Rows {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Rows")
+            .field("columns", &self.columns())
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl Rows {
+    /// Collects the rows of a stream.
+    pub(crate) async fn collect(stream: RowStream) -> Result<Rows, Error> {
+        pin_mut!(stream);
+        stream.as_mut().ready().await?;
+
+        let mut rows = RowsInner {
+            statement: stream.statement().unwrap().clone(),
+            output_format: Format::Binary,
+            text_settings: TextSettings::new(),
+            len: 0,
+            data: vec![],
+            offsets: vec![0],
+            nulls: vec![],
+        };
+        while let Some(row) = stream.try_next().await? {
+            if rows.len == 0 {
+                rows.output_format = row.output_format();
+                rows.text_settings = row.text_settings().clone();
+            }
+            rows.push(&row);
+        }
+        rows.data.shrink_to_fit();
+        rows.offsets.shrink_to_fit();
+        rows.nulls.shrink_to_fit();
+
+        Ok(Rows(Arc::new(rows)))
+    }
+
+    /// Returns information about the columns of the rows.
+    pub fn columns(&self) -> &[Column] {
+        self.0.statement.columns()
+    }
+
+    /// Determines if there are no rows.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of rows.
+    pub fn len(&self) -> usize {
+        self.0.len
+    }
+
+    /// Returns the row at index `row`, or `None` if it is out of bounds.
+    pub fn row(&self, row: usize) -> Option<RowRef<'_>> {
+        if row < self.len() {
+            Some(RowRef { rows: self, row })
+        } else {
+            None
+        }
+    }
+
+    /// Returns an iterator over the rows.
+    pub fn iter(&self) -> RowsIter<'_> {
+        RowsIter {
+            rows: self,
+            range: 0..self.len(),
+        }
+    }
+
+    /// Deserializes a value of the row at index `row`.
+    ///
+    /// The value can be specified either by its numeric index in the row, or by its column name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either index is out of bounds or if the value cannot be converted to the specified type.
+    #[track_caller]
+    pub fn get<'a, I, T>(&'a self, row: usize, idx: I) -> T
+    where
+        I: RowIndex + fmt::Display,
+        T: FromSql<'a>,
+    {
+        match self.try_get(row, &idx) {
+            Ok(ok) => ok,
+            Err(err) => panic!("error retrieving column {} of row {}: {}", idx, row, err),
+        }
+    }
+
+    /// Like `Rows::get`, but returns a `Result` rather than panicking.
+    pub fn try_get<'a, I, T>(&'a self, row: usize, idx: I) -> Result<T, Error>
+    where
+        I: RowIndex + fmt::Display,
+        T: FromSql<'a>,
+    {
+        if row >= self.len() {
+            return Err(Error::row_count());
+        }
+        let idx = self.column_index(&idx)?;
+        self.get_at(row, idx)
+    }
+
+    /// Deserializes the values of a column of all of the rows.
+    ///
+    /// The column can be specified either by its numeric index, or by its name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds or if a value cannot be converted to the specified type.
+    #[track_caller]
+    pub fn get_column<'a, I, T>(&'a self, idx: I) -> Vec<T>
+    where
+        I: RowIndex + fmt::Display,
+        T: FromSql<'a>,
+    {
+        match self.try_get_column(&idx) {
+            Ok(ok) => ok,
+            Err(err) => panic!("error retrieving column {}: {}", idx, err),
+        }
+    }
+
+    /// Like `Rows::get_column`, but returns a `Result` rather than panicking.
+    pub fn try_get_column<'a, I, T>(&'a self, idx: I) -> Result<Vec<T>, Error>
+    where
+        I: RowIndex + fmt::Display,
+        T: FromSql<'a>,
+    {
+        let idx = self.column_index(&idx)?;
+        (0..self.len()).map(|row| self.get_at(row, idx)).collect()
+    }
+
+    fn column_index<I>(&self, idx: &I) -> Result<usize, Error>
+    where
+        I: RowIndex + fmt::Display,
+    {
+        idx.__statement_idx(&self.0.statement)
+            .ok_or_else(|| Error::column(idx.to_string()))
+    }
+
+    fn get_at<'a, T>(&'a self, row: usize, idx: usize) -> Result<T, Error>
+    where
+        T: FromSql<'a>,
+    {
+        decode(
+            self.columns()[idx].type_(),
+            self.raw(row, idx),
+            self.0.output_format,
+            &self.0.text_settings,
+            idx,
+        )
+    }
+
+    fn raw(&self, row: usize, idx: usize) -> Option<&[u8]> {
+        let cell = row * self.columns().len() + idx;
+        if self.0.nulls[cell / 64] & (1 << (cell % 64)) != 0 {
+            return None;
+        }
+        Some(&self.0.data[self.0.offsets[cell]..self.0.offsets[cell + 1]])
+    }
+}
+
+impl RowsInner {
+    fn push(&mut self, row: &Row) {
+        for idx in 0..row.len() {
+            let cell = self.offsets.len() - 1;
+            if cell / 64 == self.nulls.len() {
+                self.nulls.push(0);
+            }
+            match row.col_buffer(idx) {
+                Some(buf) => self.data.extend_from_slice(buf),
+                None => self.nulls[cell / 64] |= 1 << (cell % 64),
+            }
+            self.offsets.push(self.data.len());
+        }
+        self.len += 1;
+    }
+}
+
+impl<'a> IntoIterator for &'a Rows {
+    type Item = RowRef<'a>;
+    type IntoIter = RowsIter<'a>;
+
+    fn into_iter(self) -> RowsIter<'a> {
+        self.iter()
+    }
+}
+
+/// A row of a [`Rows`] container.
+#[derive(Copy, Clone)]
+pub struct RowRef<'a> {
+    rows: &'a Rows,
+    row: usize,
+}
+
+impl fmt::Debug for RowRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RowRef")
+            .field("columns", &self.columns())
+            .finish()
+    }
+}
+
+impl<'a> RowRef<'a> {
+    /// Returns information about the columns of data in the row.
+    pub fn columns(&self) -> &'a [Column] {
+        self.rows.columns()
+    }
+
+    /// Determines if the row contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of values in the row.
+    pub fn len(&self) -> usize {
+        self.columns().len()
+    }
+
+    /// Returns the index of the row in its container.
+    pub fn index(&self) -> usize {
+        self.row
+    }
+
+    /// Deserializes a value from the row.
+    ///
+    /// The value can be specified either by its numeric index in the row, or by its column name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds or if the value cannot be converted to the specified type.
+    #[track_caller]
+    pub fn get<I, T>(&self, idx: I) -> T
+    where
+        I: RowIndex + fmt::Display,
+        T: FromSql<'a>,
+    {
+        self.rows.get(self.row, idx)
+    }
+
+    /// Like `RowRef::get`, but returns a `Result` rather than panicking.
+    pub fn try_get<I, T>(&self, idx: I) -> Result<T, Error>
+    where
+        I: RowIndex + fmt::Display,
+        T: FromSql<'a>,
+    {
+        self.rows.try_get(self.row, idx)
+    }
+
+    /// Returns the raw bytes of a value of the row, in the format of the row, or `None` if it is `NULL`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    #[track_caller]
+    pub fn get_raw<I>(&self, idx: I) -> Option<&'a [u8]>
+    where
+        I: RowIndex + fmt::Display,
+    {
+        match self.rows.column_index(&idx) {
+            Ok(idx) => self.rows.raw(self.row, idx),
+            Err(err) => panic!("error retrieving column {}: {}", idx, err),
+        }
+    }
+}
+
+/// An iterator over the rows of a [`Rows`] container.
+#[derive(Debug, Clone)]
+pub struct RowsIter<'a> {
+    rows: &'a Rows,
+    range: Range<usize>,
+}
+
+impl<'a> Iterator for RowsIter<'a> {
+    type Item = RowRef<'a>;
+
+    fn next(&mut self) -> Option<RowRef<'a>> {
+        let row = self.range.next()?;
+        Some(RowRef {
+            rows: self.rows,
+            row,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for RowsIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let row = self.range.next_back()?;
+        Some(RowRef {
+            rows: self.rows,
+            row,
+        })
+    }
+}
+
+impl ExactSizeIterator for RowsIter<'_> {}
+
+impl FusedIterator for RowsIter<'_> {}
@@ -0,0 +1,337 @@
+//! Introspection of the tables of a database.
+//!
+//! [`Client::tables`] lists the tables of a schema, and the other methods describe a table given its OID: its columns
+//! with [`Client::table_columns`], its keys with [`Client::primary_key`] and [`Client::foreign_keys`], and its indexes
+//! with [`Client::indexes`]. The OID and column numbers match the ones reported by [`Column::table_oid`] and
+//! [`Column::column_id`], so the columns of a result can be traced back to the table they come from.
+//!
+//! [`Client::tables`]: crate::Client::tables
+//! [`Client::table_columns`]: crate::Client::table_columns
+//! [`Client::primary_key`]: crate::Client::primary_key
+//! [`Client::foreign_keys`]: crate::Client::foreign_keys
+//! [`Client::indexes`]: crate::Client::indexes
+//! [`Column::table_oid`]: crate::Column::table_oid
+//! [`Column::column_id`]: crate::Column::column_id
+
+use crate::types::{Oid, Type};
+use crate::{Client, Error};
+
+const TABLES_QUERY: &str = "
+SELECT c.oid, c.relname, c.relkind
+FROM pg_catalog.pg_class c
+INNER JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+WHERE n.nspname = $1 AND c.relkind IN ('r', 'p', 'v', 'm', 'f')
+ORDER BY c.relname
+";
+
+const COLUMNS_QUERY: &str = "
+SELECT a.attname, a.attnum, a.atttypid, a.attnotnull, pg_catalog.pg_get_expr(d.adbin, d.adrelid)
+FROM pg_catalog.pg_attribute a
+LEFT OUTER JOIN pg_catalog.pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+WHERE a.attrelid = $1 AND a.attnum > 0 AND NOT a.attisdropped
+ORDER BY a.attnum
+";
+
+const CONSTRAINTS_QUERY: &str = "
+SELECT con.conname, con.conkey,
+    ARRAY(
+        SELECT a.attname
+        FROM unnest(con.conkey) WITH ORDINALITY k(attnum, i)
+        INNER JOIN pg_catalog.pg_attribute a ON a.attrelid = con.conrelid AND a.attnum = k.attnum
+        ORDER BY k.i
+    ),
+    con.confrelid, con.confkey,
+    ARRAY(
+        SELECT a.attname
+        FROM unnest(con.confkey) WITH ORDINALITY k(attnum, i)
+        INNER JOIN pg_catalog.pg_attribute a ON a.attrelid = con.confrelid AND a.attnum = k.attnum
+        ORDER BY k.i
+    )
+FROM pg_catalog.pg_constraint con
+WHERE con.conrelid = $1 AND con.contype = $2
+ORDER BY con.conname
+";
+
+const INDEXES_QUERY: &str = "
+SELECT i.indexrelid, c.relname, i.indisunique, i.indisprimary,
+    ARRAY(SELECT pg_catalog.pg_get_indexdef(i.indexrelid, k, true) FROM generate_series(1, i.indnkeyatts) k),
+    pg_catalog.pg_get_indexdef(i.indexrelid)
+FROM pg_catalog.pg_index i
+INNER JOIN pg_catalog.pg_class c ON c.oid = i.indexrelid
+WHERE i.indrelid = $1
+ORDER BY c.relname
+";
+
+/// The kind of a table.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TableKind {
+    /// An ordinary table.
+    Table,
+    /// A partitioned table, whose rows are stored in its partitions.
+    PartitionedTable,
+    /// A view.
+    View,
+    /// A materialized view.
+    MaterializedView,
+    /// A foreign table.
+    ForeignTable,
+}
+
+/// A table of a schema.
+#[derive(Debug, Clone)]
+pub struct Table {
+    oid: Oid,
+    name: String,
+    kind: TableKind,
+}
+
+impl Table {
+    /// Returns the OID of the table.
+    pub fn oid(&self) -> Oid {
+        self.oid
+    }
+
+    /// Returns the name of the table.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the kind of the table.
+    pub fn kind(&self) -> TableKind {
+        self.kind
+    }
+}
+
+/// A column of a table.
+#[derive(Debug, Clone)]
+pub struct TableColumn {
+    name: String,
+    column_id: i16,
+    type_: Type,
+    nullable: bool,
+    default: Option<String>,
+}
+
+impl TableColumn {
+    /// Returns the name of the column.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the number of the column within the table, starting at 1.
+    pub fn column_id(&self) -> i16 {
+        self.column_id
+    }
+
+    /// Returns the type of the column.
+    pub fn type_(&self) -> &Type {
+        &self.type_
+    }
+
+    /// Returns `true` if the column can contain `NULL` values.
+    pub fn is_nullable(&self) -> bool {
+        self.nullable
+    }
+
+    /// Returns the expression of the default value of the column, if it has one.
+    pub fn default(&self) -> Option<&str> {
+        self.default.as_deref()
+    }
+}
+
+/// The primary key of a table.
+#[derive(Debug, Clone)]
+pub struct PrimaryKey {
+    name: String,
+    column_ids: Vec<i16>,
+    columns: Vec<String>,
+}
+
+impl PrimaryKey {
+    /// Returns the name of the constraint.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the numbers of the columns of the key, in order.
+    pub fn column_ids(&self) -> &[i16] {
+        &self.column_ids
+    }
+
+    /// Returns the names of the columns of the key, in order.
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+}
+
+/// A foreign key of a table.
+#[derive(Debug, Clone)]
+pub struct ForeignKey {
+    name: String,
+    column_ids: Vec<i16>,
+    columns: Vec<String>,
+    referenced_table: Oid,
+    referenced_column_ids: Vec<i16>,
+    referenced_columns: Vec<String>,
+}
+
+impl ForeignKey {
+    /// Returns the name of the constraint.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the numbers of the referencing columns, in order.
+    pub fn column_ids(&self) -> &[i16] {
+        &self.column_ids
+    }
+
+    /// Returns the names of the referencing columns, in order.
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// Returns the OID of the referenced table.
+    pub fn referenced_table(&self) -> Oid {
+        self.referenced_table
+    }
+
+    /// Returns the numbers of the referenced columns, in the order matching the referencing ones.
+    pub fn referenced_column_ids(&self) -> &[i16] {
+        &self.referenced_column_ids
+    }
+
+    /// Returns the names of the referenced columns, in the order matching the referencing ones.
+    pub fn referenced_columns(&self) -> &[String] {
+        &self.referenced_columns
+    }
+}
+
+/// An index of a table.
+#[derive(Debug, Clone)]
+pub struct Index {
+    oid: Oid,
+    name: String,
+    unique: bool,
+    primary: bool,
+    columns: Vec<String>,
+    definition: String,
+}
+
+impl Index {
+    /// Returns the OID of the index.
+    pub fn oid(&self) -> Oid {
+        self.oid
+    }
+
+    /// Returns the name of the index.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns `true` if the index enforces the uniqueness of its keys.
+    pub fn is_unique(&self) -> bool {
+        self.unique
+    }
+
+    /// Returns `true` if the index is the one of the primary key of the table.
+    pub fn is_primary(&self) -> bool {
+        self.primary
+    }
+
+    /// Returns the key columns of the index, in order.
+    ///
+    /// Keys which are expressions rather than plain columns are returned as the text of the expression.
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// Returns the `CREATE INDEX` command defining the index.
+    pub fn definition(&self) -> &str {
+        &self.definition
+    }
+}
+
+pub(crate) async fn tables(client: &Client, schema: &str) -> Result<Vec<Table>, Error> {
+    let rows = client.query(TABLES_QUERY, &[&schema]).await?;
+    rows.iter()
+        .map(|row| {
+            let kind = match row.try_get::<_, i8>(2)? as u8 {
+                b'p' => TableKind::PartitionedTable,
+                b'v' => TableKind::View,
+                b'm' => TableKind::MaterializedView,
+                b'f' => TableKind::ForeignTable,
+                _ => TableKind::Table,
+            };
+            Ok(Table {
+                oid: row.try_get(0)?,
+                name: row.try_get(1)?,
+                kind,
+            })
+        })
+        .collect()
+}
+
+pub(crate) async fn table_columns(client: &Client, table: Oid) -> Result<Vec<TableColumn>, Error> {
+    let rows = client.query(COLUMNS_QUERY, &[&table]).await?;
+    let mut columns = Vec::with_capacity(rows.len());
+    for row in rows {
+        columns.push(TableColumn {
+            name: row.try_get(0)?,
+            column_id: row.try_get(1)?,
+            type_: client.resolve_type(row.try_get(2)?).await?,
+            nullable: !row.try_get::<_, bool>(3)?,
+            default: row.try_get(4)?,
+        });
+    }
+    Ok(columns)
+}
+
+pub(crate) async fn primary_key(client: &Client, table: Oid) -> Result<Option<PrimaryKey>, Error> {
+    let row = client
+        .query_opt(CONSTRAINTS_QUERY, &[&table, &(b'p' as i8)])
+        .await?;
+    row.map(|row| {
+        Ok(PrimaryKey {
+            name: row.try_get(0)?,
+            column_ids: row.try_get(1)?,
+            columns: row.try_get(2)?,
+        })
+    })
+    .transpose()
+}
+
+pub(crate) async fn foreign_keys(client: &Client, table: Oid) -> Result<Vec<ForeignKey>, Error> {
+    let rows = client
+        .query(CONSTRAINTS_QUERY, &[&table, &(b'f' as i8)])
+        .await?;
+    rows.iter()
+        .map(|row| {
+            Ok(ForeignKey {
+                name: row.try_get(0)?,
+                column_ids: row.try_get(1)?,
+                columns: row.try_get(2)?,
+                referenced_table: row.try_get(3)?,
+                referenced_column_ids: row.try_get(4)?,
+                referenced_columns: row.try_get(5)?,
+            })
+        })
+        .collect()
+}
+
+pub(crate) async fn indexes(client: &Client, table: Oid) -> Result<Vec<Index>, Error> {
+    let rows = client.query(INDEXES_QUERY, &[&table]).await?;
+    rows.iter()
+        .map(|row| {
+            Ok(Index {
+                oid: row.try_get(0)?,
+                name: row.try_get(1)?,
+                unique: row.try_get(2)?,
+                primary: row.try_get(3)?,
+                columns: row.try_get(4)?,
+                definition: row.try_get(5)?,
+            })
+        })
+        .collect()
+}
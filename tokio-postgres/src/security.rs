@@ -0,0 +1,58 @@
+//! Information about the security of established connections.
+
+/// The authentication method performed when establishing a connection.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AuthenticationMethod {
+    /// The server did not request authentication.
+    None,
+    /// A cleartext password was sent to the server.
+    Cleartext,
+    /// An MD5-hashed password was sent to the server.
+    Md5,
+    /// `SCRAM-SHA-256` authentication, without channel binding.
+    ScramSha256,
+    /// `SCRAM-SHA-256-PLUS` authentication, with channel binding to the TLS session.
+    ScramSha256Plus,
+    /// GSSAPI authentication, requested by the server for either its `gss` or `sspi` method.
+    Gss,
+}
+
+/// The transport encryption of a connection.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Encryption {
+    /// The connection is not encrypted.
+    None,
+    /// The connection is encrypted with TLS.
+    Tls,
+    /// The connection is encrypted with GSSAPI.
+    Gss,
+}
+
+/// A summary of how a connection was authenticated and encrypted.
+///
+/// This can be used to assert that a connection meets an application's security expectations, independently of how
+/// the connection was configured.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ConnectionSecurity {
+    pub(crate) authentication_method: AuthenticationMethod,
+    pub(crate) encryption: Encryption,
+}
+
+impl ConnectionSecurity {
+    /// Returns the authentication method requested by the server and performed by the client.
+    pub fn authentication_method(&self) -> AuthenticationMethod {
+        self.authentication_method
+    }
+
+    /// Returns the transport encryption of the connection.
+    pub fn encryption(&self) -> Encryption {
+        self.encryption
+    }
+
+    /// Returns `true` if authentication was bound to the TLS session with channel binding.
+    pub fn channel_binding(&self) -> bool {
+        self.authentication_method == AuthenticationMethod::ScramSha256Plus
+    }
+}
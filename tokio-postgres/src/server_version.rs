@@ -0,0 +1,160 @@
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The version of a Postgres server.
+///
+/// Parsed from the `server_version` parameter reported by the server, like `16.2`, `9.6.24` or `17beta1`. Any text
+/// following the version, like the `(Debian 16.2-1.pgdg120+2)` suffix added by some distributions, is ignored.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ServerVersion {
+    version: String,
+    major: u32,
+    minor: u32,
+    patch: u32,
+    pre_release: Option<String>,
+}
+
+impl ServerVersion {
+    /// Returns the major version, like `16` for `16.2`.
+    ///
+    /// Before Postgres 10, major versions were made of two numbers: `9` is returned for `9.6.24`, whose major version
+    /// is `9.6`.
+    pub fn major(&self) -> u32 {
+        self.major
+    }
+
+    /// Returns the minor version, like `2` for `16.2`, or `6` for `9.6.24`.
+    pub fn minor(&self) -> u32 {
+        self.minor
+    }
+
+    /// Returns the patch version of servers older than Postgres 10, like `24` for `9.6.24`, or 0.
+    pub fn patch(&self) -> u32 {
+        self.patch
+    }
+
+    /// Returns the development stage of a version which isn't a release, like `beta1` for `17beta1`, `rc1` or `devel`.
+    pub fn pre_release(&self) -> Option<&str> {
+        self.pre_release.as_deref()
+    }
+
+    /// Returns the version as an integer, in the format of the `server_version_num` parameter.
+    ///
+    /// This is `160002` for `16.2` and `90624` for `9.6.24`, and can be used to compare versions.
+    pub fn num(&self) -> u32 {
+        if self.major >= 10 {
+            self.major * 10000 + self.minor
+        } else {
+            self.major * 10000 + self.minor * 100 + self.patch
+        }
+    }
+
+    /// Returns the version as reported by the server.
+    pub fn as_str(&self) -> &str {
+        &self.version
+    }
+}
+
+impl fmt::Display for ServerVersion {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str(&self.version)
+    }
+}
+
+impl FromStr for ServerVersion {
+    type Err = ParseServerVersionError;
+
+    fn from_str(s: &str) -> Result<ServerVersion, ParseServerVersionError> {
+        let version = s.split_whitespace().next().ok_or(ParseServerVersionError)?;
+
+        let end = version
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(version.len());
+        let (numbers, pre_release) = version.split_at(end);
+        if pre_release
+            .chars()
+            .any(|c| !c.is_ascii_alphanumeric() && c != '_')
+        {
+            return Err(ParseServerVersionError);
+        }
+
+        let mut numbers = numbers.split('.').map(|n| n.parse::<u32>());
+        let major = match numbers.next() {
+            Some(Ok(major)) => major,
+            _ => return Err(ParseServerVersionError),
+        };
+        let mut next = || match numbers.next() {
+            Some(Ok(n)) => Ok(n),
+            Some(Err(_)) => Err(ParseServerVersionError),
+            None => Ok(0),
+        };
+        let minor = next()?;
+        let patch = next()?;
+        if numbers.next().is_some() {
+            return Err(ParseServerVersionError);
+        }
+
+        Ok(ServerVersion {
+            version: s.to_string(),
+            major,
+            minor,
+            patch,
+            pre_release: Some(pre_release)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string),
+        })
+    }
+}
+
+/// An error parsing a `ServerVersion`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseServerVersionError;
+
+impl fmt::Display for ParseServerVersionError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("invalid server version")
+    }
+}
+
+impl error::Error for ParseServerVersionError {}
+
+#[cfg(test)]
+mod tests {
+    use super::ServerVersion;
+
+    fn parse(s: &str) -> (u32, u32, u32, Option<String>, u32) {
+        let version = s.parse::<ServerVersion>().unwrap();
+        assert_eq!(version.as_str(), s);
+        (
+            version.major(),
+            version.minor(),
+            version.patch(),
+            version.pre_release().map(str::to_string),
+            version.num(),
+        )
+    }
+
+    #[test]
+    fn parse_versions() {
+        assert_eq!(parse("16.2"), (16, 2, 0, None, 160002));
+        assert_eq!(parse("9.6.24"), (9, 6, 24, None, 90624));
+        assert_eq!(
+            parse("16beta1"),
+            (16, 0, 0, Some("beta1".to_string()), 160000)
+        );
+        assert_eq!(
+            parse("17devel"),
+            (17, 0, 0, Some("devel".to_string()), 170000)
+        );
+        assert_eq!(
+            parse("15.4 (Debian 15.4-1.pgdg120+1)"),
+            (15, 4, 0, None, 150004)
+        );
+
+        "".parse::<ServerVersion>().unwrap_err();
+        "beta".parse::<ServerVersion>().unwrap_err();
+        "16.x".parse::<ServerVersion>().unwrap_err();
+        "1.2.3.4".parse::<ServerVersion>().unwrap_err();
+    }
+}
@@ -1,8 +1,10 @@
 use crate::client::{InnerClient, Responses};
 use crate::codec::FrontendMessage;
 use crate::connection::RequestMessages;
+use crate::interceptor::{Completion, Operation, Outcome};
 use crate::query::extract_row_affected;
-use crate::{Error, SimpleQueryMessage, SimpleQueryRow};
+use crate::types::Type;
+use crate::{prepare, Error, SimpleQueryMessage, SimpleQueryRow};
 use bytes::Bytes;
 use fallible_iterator::FallibleIterator;
 use futures_util::{ready, Stream};
@@ -12,60 +14,107 @@ use postgres_protocol::message::backend::Message;
 use postgres_protocol::message::frontend;
 use std::marker::PhantomPinned;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 use std::task::{Context, Poll};
 
 /// Information about a column of a single query row.
 #[derive(Debug)]
 pub struct SimpleColumn {
     name: String,
+    type_: Type,
 }
 
 impl SimpleColumn {
-    pub(crate) fn new(name: String) -> SimpleColumn {
-        SimpleColumn { name }
+    pub(crate) fn new(name: String, type_: Type) -> SimpleColumn {
+        SimpleColumn { name, type_ }
     }
 
     /// Returns the name of the column.
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    /// Returns the type of the column.
+    ///
+    /// Types which aren't built in can't be looked up while the results are streamed, so only those already resolved
+    /// by the client are known, and the others are reported as `TEXT`. Values are always in the text format.
+    pub fn type_(&self) -> &Type {
+        &self.type_
+    }
 }
 
-pub async fn simple_query(client: &InnerClient, query: &str) -> Result<SimpleQueryStream, Error> {
+pub async fn simple_query(
+    client: &Arc<InnerClient>,
+    query: &str,
+) -> Result<SimpleQueryStream, Error> {
+    let interceptors = client.interceptors();
+    let query = interceptors.before_sql(Operation::SimpleQuery, query)?;
+
     debug!("executing simple query: {}", query);
 
-    let buf = encode(client, query)?;
-    let responses = client.send(RequestMessages::Single(FrontendMessage::Raw(buf)))?;
+    let permit = client.reserve().await;
+    let buf = encode(client, &query)?;
+    let responses = client.send(permit, RequestMessages::Single(FrontendMessage::Raw(buf)))?;
 
     Ok(SimpleQueryStream {
+        client: Arc::downgrade(client),
         responses,
         columns: None,
+        rows_affected: None,
+        completion: Completion::new(client, Operation::SimpleQuery, Some(&query)),
         _p: PhantomPinned,
     })
 }
 
 pub async fn batch_execute(client: &InnerClient, query: &str) -> Result<(), Error> {
+    let interceptors = client.interceptors();
+    let query = interceptors.before_sql(Operation::SimpleQuery, query)?;
+
     debug!("executing statement batch: {}", query);
 
-    let buf = encode(client, query)?;
-    let mut responses = client.send(RequestMessages::Single(FrontendMessage::Raw(buf)))?;
-
-    loop {
-        match responses.next().await? {
-            Message::ReadyForQuery(_) => return Ok(()),
-            Message::CommandComplete(_)
-            | Message::EmptyQueryResponse
-            | Message::RowDescription(_)
-            | Message::DataRow(_) => {}
-            m => return Err(Error::unexpected_message(m)),
+    let permit = client.reserve().await;
+    let buf = encode(client, &query)?;
+    let timer = client
+        .instrumentation()
+        .timer(Operation::SimpleQuery, Some(&query));
+    let mut responses = client.send(permit, RequestMessages::Single(FrontendMessage::Raw(buf)))?;
+
+    let mut rows = None;
+    let result = loop {
+        let message = match responses.next().await {
+            Ok(message) => message,
+            Err(e) => break Err(e),
+        };
+
+        match message {
+            Message::ReadyForQuery(_) => break Ok(()),
+            Message::CommandComplete(body) => match extract_row_affected(&body) {
+                Ok(n) => rows = Some(n),
+                Err(e) => break Err(e),
+            },
+            Message::EmptyQueryResponse | Message::RowDescription(_) | Message::DataRow(_) => {}
+            m => break Err(Error::unexpected_message(m)),
         }
+    };
+
+    if let Some(timer) = timer {
+        timer.finish(None, result.as_ref().map(|()| rows));
     }
+    interceptors.after(&Outcome {
+        operation: Operation::SimpleQuery,
+        sql: Some(&query),
+        statement: None,
+        result: result.as_ref().map(|()| rows),
+    });
+
+    result
 }
 
-fn encode(client: &InnerClient, query: &str) -> Result<Bytes, Error> {
+pub(crate) fn encode(client: &InnerClient, query: &str) -> Result<Bytes, Error> {
+    let query = client.instrumentation().annotate(query);
     client.with_buf(|buf| {
-        frontend::query(query, buf).map_err(Error::encode)?;
+        client.replace_unnamed_statement();
+        frontend::query(&query, buf).map_err(Error::encode)?;
         Ok(buf.split().freeze())
     })
 }
@@ -73,8 +122,11 @@ fn encode(client: &InnerClient, query: &str) -> Result<Bytes, Error> {
 pin_project! {
     /// A stream of simple query results.
     pub struct SimpleQueryStream {
+        client: Weak<InnerClient>,
         responses: Responses,
         columns: Option<Arc<[SimpleColumn]>>,
+        rows_affected: Option<u64>,
+        completion: Completion,
         #[pin]
         _p: PhantomPinned,
     }
@@ -86,18 +138,44 @@ impl Stream for SimpleQueryStream {
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let this = self.project();
         loop {
-            match ready!(this.responses.poll_next(cx)?) {
+            let message = match ready!(this.responses.poll_next(cx)) {
+                Ok(message) => message,
+                Err(e) => {
+                    this.completion.complete(None, Err(&e));
+                    return Poll::Ready(Some(Err(e)));
+                }
+            };
+
+            match message {
                 Message::CommandComplete(body) => {
                     let rows = extract_row_affected(&body)?;
-                    return Poll::Ready(Some(Ok(SimpleQueryMessage::CommandComplete(rows))));
+                    let tag = body.tag().map_err(Error::parse)?.to_string();
+                    *this.rows_affected = Some(rows);
+                    return Poll::Ready(Some(Ok(SimpleQueryMessage::CommandComplete {
+                        tag,
+                        rows,
+                    })));
                 }
                 Message::EmptyQueryResponse => {
-                    return Poll::Ready(Some(Ok(SimpleQueryMessage::CommandComplete(0))));
+                    return Poll::Ready(Some(Ok(SimpleQueryMessage::CommandComplete {
+                        tag: String::new(),
+                        rows: 0,
+                    })));
                 }
                 Message::RowDescription(body) => {
+                    let client = this.client.upgrade();
                     let columns = body
                         .fields()
-                        .map(|f| Ok(SimpleColumn::new(f.name().to_string())))
+                        .map(|f| {
+                            let type_ = match &client {
+                                Some(client) => prepare::cached_type(client, f.type_oid()),
+                                None => Type::from_oid(f.type_oid()),
+                            };
+                            Ok(SimpleColumn::new(
+                                f.name().to_string(),
+                                type_.unwrap_or(Type::TEXT),
+                            ))
+                        })
                         .collect::<Vec<_>>()
                         .map_err(Error::parse)?
                         .into();
@@ -111,8 +189,15 @@ impl Stream for SimpleQueryStream {
                     };
                     return Poll::Ready(Some(Ok(SimpleQueryMessage::Row(row))));
                 }
-                Message::ReadyForQuery(_) => return Poll::Ready(None),
-                m => return Poll::Ready(Some(Err(Error::unexpected_message(m)))),
+                Message::ReadyForQuery(_) => {
+                    this.completion.complete(None, Ok(*this.rows_affected));
+                    return Poll::Ready(None);
+                }
+                m => {
+                    let e = Error::unexpected_message(m);
+                    this.completion.complete(None, Err(&e));
+                    return Poll::Ready(Some(Err(e)));
+                }
             }
         }
     }
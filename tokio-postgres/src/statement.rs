@@ -1,34 +1,96 @@
-use crate::client::InnerClient;
+use crate::client::{InnerClient, RequestPermit};
 use crate::codec::FrontendMessage;
 use crate::connection::RequestMessages;
-use crate::types::Type;
+use crate::types::{BorrowToSql, ToSql, Type};
+use crate::{bind, slice_iter, Client, Error, Portal};
+use parking_lot::Mutex;
 use postgres_protocol::message::frontend;
-use std::sync::{Arc, Weak};
+use std::collections::HashMap;
+use std::ptr;
+use std::sync::{Arc, OnceLock, Weak};
 
 #[derive(Debug)]
 enum StatementInner {
     Unnamed {
+        sql: String,
+        // The generation of the session's unnamed statement the statement was prepared as, if it can be executed by
+        // requests of its own, which must fail once it has been replaced.
+        generation: Option<u64>,
         params: Vec<Type>,
         columns: Vec<Column>,
+        names: OnceLock<ColumnNames>,
     },
     Named {
         client: Weak<InnerClient>,
         name: String,
+        // The query and parameter types the statement was prepared with, before being rewritten by interceptors.
+        sql: String,
+        types: Vec<Type>,
+        // The statement epoch of the session when the statement was prepared.
+        epoch: u64,
+        // The statement prepared again once this one was deallocated by a session reset, reused by the following
+        // executions as long as it is prepared.
+        replacement: Mutex<Option<Statement>>,
         params: Vec<Type>,
         columns: Vec<Column>,
+        names: OnceLock<ColumnNames>,
     },
 }
 
+// The indices of the columns of a statement by name, built on the first lookup by name and shared by all rows of the
+// statement, so that rows don't scan their columns on every lookup. Duplicate names map to their first column, like a
+// scan would find.
+#[derive(Debug)]
+struct ColumnNames {
+    exact: HashMap<String, usize>,
+    // ASCII lowercased names, for case insensitive lookups.
+    folded: HashMap<String, usize>,
+}
+
+impl ColumnNames {
+    fn new(columns: &[Column]) -> ColumnNames {
+        let mut exact = HashMap::with_capacity(columns.len());
+        let mut folded = HashMap::with_capacity(columns.len());
+        for (idx, column) in columns.iter().enumerate() {
+            exact.entry(column.name().to_string()).or_insert(idx);
+            folded
+                .entry(column.name().to_ascii_lowercase())
+                .or_insert(idx);
+        }
+
+        ColumnNames { exact, folded }
+    }
+
+    fn get(&self, name: &str) -> Option<usize> {
+        if let Some(idx) = self.exact.get(name) {
+            return Some(*idx);
+        }
+
+        // FIXME ASCII-only case insensitivity isn't really the right thing to
+        // do. Postgres itself uses a dubious wrapper around tolower and JDBC
+        // uses the US locale.
+        if name.bytes().any(|b| b.is_ascii_uppercase()) {
+            self.folded.get(&name.to_ascii_lowercase()).copied()
+        } else {
+            self.folded.get(name).copied()
+        }
+    }
+}
+
 impl Drop for StatementInner {
     fn drop(&mut self) {
         if let StatementInner::Named { client, name, .. } = self {
             if let Some(client) = client.upgrade() {
+                client.instrumentation().statement_closed(name);
                 let buf = client.with_buf(|buf| {
                     frontend::close(b'S', name, buf).unwrap();
                     frontend::sync(buf);
                     buf.split().freeze()
                 });
-                let _ = client.send(RequestMessages::Single(FrontendMessage::Raw(buf)));
+                let _ = client.send(
+                    RequestPermit::exempt(),
+                    RequestMessages::Single(FrontendMessage::Raw(buf)),
+                );
             }
         }
     }
@@ -44,19 +106,38 @@ impl Statement {
     pub(crate) fn named(
         inner: &Arc<InnerClient>,
         name: String,
+        sql: String,
+        types: Vec<Type>,
+        epoch: u64,
         params: Vec<Type>,
         columns: Vec<Column>,
     ) -> Statement {
         Statement(Arc::new(StatementInner::Named {
             client: Arc::downgrade(inner),
             name,
+            sql,
+            types,
+            epoch,
+            replacement: Mutex::new(None),
             params,
+            names: OnceLock::new(),
             columns,
         }))
     }
 
-    pub(crate) fn unnamed(params: Vec<Type>, columns: Vec<Column>) -> Self {
-        Statement(Arc::new(StatementInner::Unnamed { params, columns }))
+    pub(crate) fn unnamed(
+        sql: String,
+        generation: Option<u64>,
+        params: Vec<Type>,
+        columns: Vec<Column>,
+    ) -> Self {
+        Statement(Arc::new(StatementInner::Unnamed {
+            sql,
+            generation,
+            params,
+            names: OnceLock::new(),
+            columns,
+        }))
     }
 
     pub(crate) fn name(&self) -> &str {
@@ -66,6 +147,66 @@ impl Statement {
         }
     }
 
+    /// Returns the query and parameter types a named statement was prepared with.
+    pub(crate) fn prepared_with(&self) -> Option<(&str, &[Type])> {
+        match &*self.0 {
+            StatementInner::Unnamed { .. } => None,
+            StatementInner::Named { sql, types, .. } => Some((sql, types)),
+        }
+    }
+
+    /// Returns whether a named statement was prepared before the last deallocation of the session's statements.
+    pub(crate) fn is_deallocated(&self, epoch: u64) -> bool {
+        match &*self.0 {
+            StatementInner::Unnamed { .. } => false,
+            StatementInner::Named {
+                epoch: prepared, ..
+            } => *prepared != epoch,
+        }
+    }
+
+    /// Returns the statement which replaced a deallocated named statement, if it is still prepared at the given
+    /// statement epoch.
+    pub(crate) fn replacement(&self, epoch: u64) -> Option<Statement> {
+        match &*self.0 {
+            StatementInner::Unnamed { .. } => None,
+            StatementInner::Named { replacement, .. } => replacement
+                .lock()
+                .clone()
+                .filter(|replacement| !replacement.is_deallocated(epoch)),
+        }
+    }
+
+    /// Records the statement prepared to replace a deallocated named statement.
+    pub(crate) fn set_replacement(&self, statement: &Statement) {
+        if let StatementInner::Named { replacement, .. } = &*self.0 {
+            *replacement.lock() = Some(statement.clone());
+        }
+    }
+
+    /// Returns whether an unnamed statement was replaced since it was prepared, given the current generation of the
+    /// session's unnamed statement.
+    pub(crate) fn is_replaced(&self, generation: u64) -> bool {
+        match &*self.0 {
+            StatementInner::Unnamed {
+                generation: Some(prepared),
+                ..
+            } => *prepared != generation,
+            _ => false,
+        }
+    }
+
+    /// Returns the query the statement was prepared from.
+    ///
+    /// This is the query as passed to the client, before any rewriting by interceptors. Statements created implicitly
+    /// by executing a query from a string also return that query.
+    pub fn sql(&self) -> &str {
+        match &*self.0 {
+            StatementInner::Unnamed { sql, .. } => sql,
+            StatementInner::Named { sql, .. } => sql,
+        }
+    }
+
     /// Returns the expected types of the statement's parameters.
     pub fn params(&self) -> &[Type] {
         match &*self.0 {
@@ -81,10 +222,116 @@ impl Statement {
             StatementInner::Named { columns, .. } => columns,
         }
     }
+
+    /// Prepares the statement again, updating its parameter and column types.
+    ///
+    /// Once a schema change alters the columns returned by a statement, such as an `ALTER TABLE` adding a column to a
+    /// table queried with `SELECT *`, the server refuses to execute it, and its columns no longer match the table. This
+    /// prepares the query again with the parameter types the statement was prepared with, and replaces the statement
+    /// with the new one. Unnamed statements are prepared again as unnamed statements, with their current parameter
+    /// types.
+    ///
+    /// Clones of the statement made before the refresh keep the old statement, which is closed once they are all
+    /// dropped. Since the schema change also affects the statement cached by `Client::query_cached` for the same query,
+    /// that statement is evicted from the cache, and prepared again on its next use.
+    ///
+    /// The client must be the one the statement was prepared with. An error is returned otherwise for named statements.
+    pub async fn refresh(&mut self, client: &Client) -> Result<(), Error> {
+        let statement = match &*self.0 {
+            StatementInner::Unnamed { sql, params, .. } => {
+                client.prepare_unnamed(sql, params).await?
+            }
+            StatementInner::Named {
+                client: prepared_by,
+                sql,
+                types,
+                ..
+            } => {
+                if !ptr::eq(prepared_by.as_ptr(), Arc::as_ptr(client.inner())) {
+                    return Err(Error::foreign_statement());
+                }
+                client.prepare_typed(sql, types).await?
+            }
+        };
+        client.evict_cached_statement(self.sql());
+        *self = statement;
+        Ok(())
+    }
+
+    /// Returns the index of the column of the provided name, matched case insensitively if no column has exactly
+    /// that name.
+    pub(crate) fn column_index(&self, name: &str) -> Option<usize> {
+        let (columns, names) = match &*self.0 {
+            StatementInner::Unnamed { columns, names, .. } => (columns, names),
+            StatementInner::Named { columns, names, .. } => (columns, names),
+        };
+        names.get_or_init(|| ColumnNames::new(columns)).get(name)
+    }
+
+    /// Binds the statement to a set of parameters, creating a `Portal` which can be incrementally queried.
+    ///
+    /// This is like `Transaction::bind`, but doesn't require access to the transaction. A statement can be bound any
+    /// number of times, each creating a new portal without preparing the statement again. Portals only last for the
+    /// duration of the transaction in which they are created, so the statement's connection must be in a transaction
+    /// block when it is bound.
+    ///
+    /// Returns an error if the statement is unnamed or its connection has closed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of parameters provided does not match the number expected.
+    pub async fn bind(&self, params: &[&(dyn ToSql + Sync)]) -> Result<Portal, Error> {
+        self.bind_raw(slice_iter(params)).await
+    }
+
+    /// A maximally flexible version of [`bind`].
+    ///
+    /// [`bind`]: #method.bind
+    pub async fn bind_raw<P, I>(&self, params: I) -> Result<Portal, Error>
+    where
+        P: BorrowToSql,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let client = match &*self.0 {
+            StatementInner::Unnamed { .. } => None,
+            StatementInner::Named { client, .. } => client.upgrade(),
+        }
+        .ok_or_else(Error::closed)?;
+        bind::bind(&client, self.clone(), params).await
+    }
+}
+
+/// The parameter and column types of a query, as described by the server.
+///
+/// Created with [`Client::describe`](crate::Client::describe).
+#[derive(Debug, Clone)]
+pub struct StatementDescription {
+    params: Vec<Type>,
+    columns: Vec<Column>,
+}
+
+impl StatementDescription {
+    pub(crate) fn new(statement: &Statement) -> StatementDescription {
+        StatementDescription {
+            params: statement.params().to_vec(),
+            columns: statement.columns().to_vec(),
+        }
+    }
+
+    /// Returns the expected types of the query's parameters.
+    pub fn params(&self) -> &[Type] {
+        &self.params
+    }
+
+    /// Returns information about the columns returned by the query.
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
 }
 
 /// Information about a column of a query.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Column {
     pub(crate) name: String,
     pub(crate) table_oid: Option<u32>,
@@ -0,0 +1,68 @@
+use crate::Statement;
+use std::collections::HashMap;
+
+struct Entry {
+    statement: Statement,
+    last_used: u64,
+}
+
+/// A least recently used cache of prepared statements, keyed by their query.
+///
+/// Evicted statements are closed once they are no longer used elsewhere, like any other statement.
+pub struct StatementCache {
+    capacity: usize,
+    clock: u64,
+    entries: HashMap<String, Entry>,
+}
+
+impl StatementCache {
+    pub fn new(capacity: usize) -> StatementCache {
+        StatementCache {
+            capacity,
+            clock: 0,
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn get(&mut self, query: &str) -> Option<Statement> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(query)?;
+        entry.last_used = self.clock;
+        Some(entry.statement.clone())
+    }
+
+    pub fn insert(&mut self, query: &str, statement: Statement) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        // Evictions only happen once the cache is full, so a linear scan for the oldest entry is cheap enough.
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(query) {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(query, _)| query.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        self.clock += 1;
+        self.entries.insert(
+            query.to_string(),
+            Entry {
+                statement,
+                last_used: self.clock,
+            },
+        );
+    }
+
+    pub fn remove(&mut self, query: &str) {
+        self.entries.remove(query);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
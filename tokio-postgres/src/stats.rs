@@ -0,0 +1,302 @@
+//! Client-side statement statistics.
+//!
+//! When enabled with [`Config::statement_stats`], a client aggregates the number of executions, errors, rows and the
+//! latency of the statements it runs, similarly to the server's `pg_stat_statements` extension but measured from the
+//! client's perspective. Statistics are retrieved with [`Client::statement_stats`].
+//!
+//! Statements are keyed by their normalized SQL, in which comments are stripped, whitespace is collapsed and literals
+//! are replaced with `?`, so that queries differing only in their inlined values are aggregated together.
+//!
+//! [`Config::statement_stats`]: crate::Config::statement_stats
+//! [`Client::statement_stats`]: crate::Client::statement_stats
+
+use crate::interceptor::Operation;
+use parking_lot::Mutex;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+// Each power of two of microseconds is split into this many buckets, bounding the error of percentiles to 12.5%.
+const SUB_BUCKETS: u64 = 8;
+const SUB_BUCKET_BITS: u32 = 3;
+
+/// The statistics of a statement.
+#[derive(Debug, Clone)]
+pub struct StatementStats {
+    query: String,
+    calls: u64,
+    errors: u64,
+    rows: u64,
+    total_time: Duration,
+    max_time: Duration,
+    histogram: BTreeMap<u16, u64>,
+}
+
+impl StatementStats {
+    fn new(query: String) -> StatementStats {
+        StatementStats {
+            query,
+            calls: 0,
+            errors: 0,
+            rows: 0,
+            total_time: Duration::from_secs(0),
+            max_time: Duration::from_secs(0),
+            histogram: BTreeMap::new(),
+        }
+    }
+
+    fn record(&mut self, duration: Duration, result: Result<Option<u64>, ()>) {
+        self.calls += 1;
+        match result {
+            Ok(rows) => self.rows += rows.unwrap_or(0),
+            Err(()) => self.errors += 1,
+        }
+        self.total_time += duration;
+        self.max_time = self.max_time.max(duration);
+        *self.histogram.entry(bucket(duration)).or_insert(0) += 1;
+    }
+
+    /// Returns the normalized SQL of the statement.
+    ///
+    /// This is the name of the statement instead if its SQL isn't known.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Returns the number of times the statement was executed.
+    pub fn calls(&self) -> u64 {
+        self.calls
+    }
+
+    /// Returns the number of executions of the statement which failed.
+    pub fn errors(&self) -> u64 {
+        self.errors
+    }
+
+    /// Returns the total number of rows returned or affected by the statement.
+    pub fn rows(&self) -> u64 {
+        self.rows
+    }
+
+    /// Returns the total time spent executing the statement.
+    pub fn total_time(&self) -> Duration {
+        self.total_time
+    }
+
+    /// Returns the mean time spent executing the statement.
+    pub fn mean_time(&self) -> Duration {
+        match self.calls {
+            0 => Duration::from_secs(0),
+            calls => Duration::from_nanos((self.total_time.as_nanos() / u128::from(calls)) as u64),
+        }
+    }
+
+    /// Returns the longest time spent executing the statement.
+    pub fn max_time(&self) -> Duration {
+        self.max_time
+    }
+
+    /// Returns an estimate of the time within which the given fraction of the executions of the statement completed.
+    ///
+    /// Estimates are accurate to within 12.5%.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `quantile` is not between 0 and 1.
+    pub fn percentile(&self, quantile: f64) -> Duration {
+        assert!(
+            (0.0..=1.0).contains(&quantile),
+            "quantile must be between 0 and 1"
+        );
+
+        let rank = (quantile * self.calls as f64).ceil().max(1.) as u64;
+        let mut count = 0;
+        for (bucket, n) in &self.histogram {
+            count += n;
+            if count >= rank {
+                return upper_bound(*bucket).min(self.max_time);
+            }
+        }
+        self.max_time
+    }
+
+    /// Returns an estimate of the time within which 99% of the executions of the statement completed.
+    pub fn p99_time(&self) -> Duration {
+        self.percentile(0.99)
+    }
+}
+
+fn bucket(duration: Duration) -> u16 {
+    let micros = duration.as_micros().min(u128::from(u64::MAX)) as u64;
+    if micros < SUB_BUCKETS {
+        return micros as u16;
+    }
+
+    let exp = 63 - micros.leading_zeros();
+    let sub = (micros >> (exp - SUB_BUCKET_BITS)) & (SUB_BUCKETS - 1);
+    ((exp - SUB_BUCKET_BITS + 1) as u64 * SUB_BUCKETS + sub) as u16
+}
+
+fn upper_bound(bucket: u16) -> Duration {
+    let bucket = u64::from(bucket);
+    if bucket < SUB_BUCKETS {
+        return Duration::from_micros(bucket);
+    }
+
+    let exp = (bucket / SUB_BUCKETS) as u32 + SUB_BUCKET_BITS - 1;
+    let sub = bucket % SUB_BUCKETS;
+    let upper = (SUB_BUCKETS + sub + 1)
+        .checked_shl(exp - SUB_BUCKET_BITS)
+        .unwrap_or(u64::MAX);
+    Duration::from_micros(upper - 1)
+}
+
+/// Normalizes SQL for use as a statistics key.
+///
+/// Comments are stripped, whitespace is collapsed and string and numeric literals are replaced with `?`.
+pub(crate) fn normalize(query: &str) -> String {
+    let mut normalized = String::with_capacity(query.len());
+    let mut chars = query.chars().peekable();
+    let mut space = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '-' if chars.peek() == Some(&'-') => {
+                for c in &mut chars {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+                space = true;
+                continue;
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = ' ';
+                for c in &mut chars {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+                space = true;
+                continue;
+            }
+            c if c.is_whitespace() => {
+                space = true;
+                continue;
+            }
+            _ => {}
+        }
+
+        if space && !normalized.is_empty() {
+            normalized.push(' ');
+        }
+        space = false;
+
+        match c {
+            '\'' => {
+                while let Some(c) = chars.next() {
+                    if c == '\'' {
+                        if chars.peek() == Some(&'\'') {
+                            chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                }
+                normalized.push('?');
+            }
+            '"' => {
+                normalized.push(c);
+                for c in &mut chars {
+                    normalized.push(c);
+                    if c == '"' {
+                        break;
+                    }
+                }
+            }
+            c if c.is_ascii_digit() && !ends_with_identifier(&normalized) => {
+                while let Some(c) = chars.peek() {
+                    if c.is_ascii_alphanumeric() || *c == '.' {
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                normalized.push('?');
+            }
+            c => normalized.push(c),
+        }
+    }
+
+    normalized
+}
+
+// Digits following an identifier character or the `$` of a parameter are part of it rather than a literal.
+fn ends_with_identifier(s: &str) -> bool {
+    s.chars()
+        .next_back()
+        .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+#[derive(Default)]
+struct State {
+    statements: HashMap<String, StatementStats>,
+    // The keys of prepared statements, by statement name.
+    names: HashMap<String, String>,
+}
+
+/// Aggregates the statistics of the statements executed by a client.
+#[derive(Default)]
+pub(crate) struct StatsCollector(Mutex<State>);
+
+impl StatsCollector {
+    pub(crate) fn prepared(&self, name: &str, query: &str) {
+        if !name.is_empty() {
+            self.0
+                .lock()
+                .names
+                .insert(name.to_string(), normalize(query));
+        }
+    }
+
+    pub(crate) fn closed(&self, name: &str) {
+        self.0.lock().names.remove(name);
+    }
+
+    pub(crate) fn record(
+        &self,
+        operation: Operation,
+        name: &str,
+        query: Option<&str>,
+        duration: Duration,
+        result: Result<Option<u64>, ()>,
+    ) {
+        if operation == Operation::Prepare {
+            return;
+        }
+
+        let mut state = self.0.lock();
+        let key = match query {
+            Some(query) => normalize(query),
+            None => state
+                .names
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| name.to_string()),
+        };
+        state
+            .statements
+            .entry(key)
+            .or_insert_with_key(|key| StatementStats::new(key.clone()))
+            .record(duration, result);
+    }
+
+    pub(crate) fn snapshot(&self) -> Vec<StatementStats> {
+        self.0.lock().statements.values().cloned().collect()
+    }
+
+    pub(crate) fn reset(&self) {
+        self.0.lock().statements.clear();
+    }
+}
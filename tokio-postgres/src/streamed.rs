@@ -0,0 +1,155 @@
+use crate::codec::FrontendMessage;
+use crate::types::Chunks;
+use bytes::Bytes;
+use std::collections::VecDeque;
+use std::io;
+
+/// A parameter value left out of the buffer of a Bind message, to be written in chunks.
+pub struct StreamedValue {
+    /// The offset of the buffer at which the value is written.
+    pub offset: usize,
+    pub len: usize,
+    pub chunks: Chunks,
+}
+
+enum Segment {
+    Message(Bytes),
+    // The bytes of a message following the chunks of a value.
+    Continuation(Bytes),
+    Value { remaining: usize, chunks: Chunks },
+}
+
+/// The messages of a request starting with a Bind message whose streamed values are pulled while it is written.
+pub struct StreamedRequest {
+    segments: VecDeque<Segment>,
+}
+
+impl StreamedRequest {
+    pub fn new(buf: Bytes, values: Vec<StreamedValue>) -> StreamedRequest {
+        // The length of the Bind message accounts for the values missing from the buffer.
+        let streamed = values.iter().map(|v| v.len).sum::<usize>();
+        let bind_end = 1 + u32::from_be_bytes([buf[1], buf[2], buf[3], buf[4]]) as usize - streamed;
+
+        let mut segments = VecDeque::with_capacity(values.len() * 2 + 2);
+        let mut start = 0;
+        for value in values {
+            let segment = buf.slice(start..value.offset);
+            if start == 0 {
+                segments.push_back(Segment::Message(segment));
+            } else {
+                segments.push_back(Segment::Continuation(segment));
+            }
+            segments.push_back(Segment::Value {
+                remaining: value.len,
+                chunks: value.chunks,
+            });
+            start = value.offset;
+        }
+        segments.push_back(Segment::Continuation(buf.slice(start..bind_end)));
+        segments.push_back(Segment::Message(buf.slice(bind_end..)));
+
+        StreamedRequest { segments }
+    }
+
+    /// Returns the next message to write, or `None` once the request has been entirely written.
+    ///
+    /// Since the server can't make sense of a partially written message, an error is returned when a value can't be
+    /// read or doesn't match its length, and the connection must be closed.
+    pub fn next(&mut self) -> io::Result<Option<FrontendMessage>> {
+        loop {
+            let message = match self.segments.pop_front() {
+                Some(Segment::Message(buf) | Segment::Continuation(buf)) if buf.is_empty() => {
+                    continue
+                }
+                Some(Segment::Message(buf)) => FrontendMessage::Raw(buf),
+                Some(Segment::Continuation(buf)) => FrontendMessage::Continuation(buf),
+                Some(Segment::Value {
+                    remaining,
+                    mut chunks,
+                }) => match chunks.next().transpose()? {
+                    Some(chunk) if chunk.len() <= remaining => {
+                        self.segments.push_front(Segment::Value {
+                            remaining: remaining - chunk.len(),
+                            chunks,
+                        });
+                        if chunk.is_empty() {
+                            continue;
+                        }
+                        FrontendMessage::Continuation(chunk)
+                    }
+                    None if remaining == 0 => continue,
+                    _ => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            "streamed value doesn't match its encoded length",
+                        ))
+                    }
+                },
+                None => return Ok(None),
+            };
+            return Ok(Some(message));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StreamedRequest, StreamedValue};
+    use crate::codec::FrontendMessage;
+    use bytes::{BufMut, Bytes, BytesMut};
+
+    fn streamed(value: &'static [u8], len: usize) -> StreamedRequest {
+        // A Bind message with a single value, followed by a Sync message.
+        let mut buf = BytesMut::new();
+        buf.put_u8(b'B');
+        buf.put_i32(16 + len as i32);
+        buf.put_slice(b"\0\0");
+        buf.put_i16(0);
+        buf.put_i16(1);
+        buf.put_i32(len as i32);
+        let offset = buf.len();
+        buf.put_i16(0);
+        buf.put_slice(b"S\0\0\0\x04");
+
+        let chunks = value.chunks(2).map(|c| Ok(Bytes::from_static(c)));
+        StreamedRequest::new(
+            buf.freeze(),
+            vec![StreamedValue {
+                offset,
+                len,
+                chunks: Box::new(chunks),
+            }],
+        )
+    }
+
+    #[test]
+    fn streamed_request() {
+        let mut request = streamed(b"hello", 5);
+        let mut written = BytesMut::new();
+        let mut messages = 0;
+        while let Some(message) = request.next().unwrap() {
+            match message {
+                FrontendMessage::Raw(buf) => {
+                    messages += 1;
+                    written.put_slice(&buf);
+                }
+                FrontendMessage::Continuation(buf) => written.put_slice(&buf),
+                FrontendMessage::CopyData(_) => unreachable!(),
+            }
+        }
+        assert_eq!(messages, 2);
+        assert_eq!(
+            &written[..],
+            &b"B\0\0\0\x15\0\0\0\0\0\x01\0\0\0\x05hello\0\0S\0\0\0\x04"[..]
+        );
+
+        let mut request = streamed(b"hello", 4);
+        let result = loop {
+            match request.next() {
+                Ok(Some(_)) => {}
+                result => break result,
+            }
+        };
+        assert!(result.is_err());
+    }
+}
@@ -2,6 +2,7 @@
 
 use std::error::Error;
 use std::future::Future;
+use std::path::Path;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 use std::{fmt, io};
@@ -12,8 +13,16 @@ pub(crate) mod private {
 }
 
 /// Channel binding information returned from a TLS handshake.
+///
+/// `TlsStream` implementations for TLS stacks other than the ones shipped with this workspace can
+/// use this type to enable `SCRAM-SHA-256-PLUS` authentication. PostgreSQL only supports the
+/// `tls-server-end-point` binding type, so `tls-exporter` data alone doesn't enable it: with
+/// `channel_binding=prefer` plain `SCRAM-SHA-256` is used instead, and with `channel_binding=require`
+/// the connection fails.
+#[derive(Debug, Clone, Default)]
 pub struct ChannelBinding {
     pub(crate) tls_server_end_point: Option<Vec<u8>>,
+    pub(crate) tls_exporter: Option<Vec<u8>>,
 }
 
 impl ChannelBinding {
@@ -21,15 +30,81 @@ impl ChannelBinding {
     pub fn none() -> ChannelBinding {
         ChannelBinding {
             tls_server_end_point: None,
+            tls_exporter: None,
         }
     }
 
     /// Creates a `ChannelBinding` containing `tls-server-end-point` channel binding information.
+    ///
+    /// The data is the hash of the server's certificate, as described in [RFC 5929].
+    ///
+    /// [RFC 5929]: https://tools.ietf.org/html/rfc5929#section-4
     pub fn tls_server_end_point(tls_server_end_point: Vec<u8>) -> ChannelBinding {
         ChannelBinding {
             tls_server_end_point: Some(tls_server_end_point),
+            tls_exporter: None,
+        }
+    }
+
+    /// Creates a `ChannelBinding` containing `tls-exporter` channel binding information.
+    ///
+    /// The data is the exported keying material of the TLS session, as described in [RFC 9266].
+    ///
+    /// [RFC 9266]: https://tools.ietf.org/html/rfc9266
+    pub fn tls_exporter(tls_exporter: Vec<u8>) -> ChannelBinding {
+        ChannelBinding {
+            tls_server_end_point: None,
+            tls_exporter: Some(tls_exporter),
         }
     }
+
+    /// Adds `tls-server-end-point` channel binding information.
+    pub fn with_tls_server_end_point(mut self, tls_server_end_point: Vec<u8>) -> ChannelBinding {
+        self.tls_server_end_point = Some(tls_server_end_point);
+        self
+    }
+
+    /// Adds `tls-exporter` channel binding information.
+    pub fn with_tls_exporter(mut self, tls_exporter: Vec<u8>) -> ChannelBinding {
+        self.tls_exporter = Some(tls_exporter);
+        self
+    }
+
+    /// Returns the `tls-server-end-point` channel binding information, if present.
+    pub fn get_tls_server_end_point(&self) -> Option<&[u8]> {
+        self.tls_server_end_point.as_deref()
+    }
+
+    /// Returns the `tls-exporter` channel binding information, if present.
+    pub fn get_tls_exporter(&self) -> Option<&[u8]> {
+        self.tls_exporter.as_deref()
+    }
+
+    /// Returns `true` if no channel binding information is present.
+    pub fn is_none(&self) -> bool {
+        self.tls_server_end_point.is_none() && self.tls_exporter.is_none()
+    }
+}
+
+/// The verification of the server's certificate requested by the connection configuration.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CertificateVerification {
+    /// The verification configured on the TLS connector applies.
+    ///
+    /// This is requested by the `prefer` and `require` SSL modes.
+    Default,
+    /// The server's certificate must be issued by a trusted certificate authority, but may have been issued for any
+    /// host.
+    ///
+    /// This is requested by the `verify-ca` SSL mode, or by the `prefer` and `require` modes if a root certificate is
+    /// configured.
+    Ca,
+    /// The server's certificate must be issued by a trusted certificate authority for the domain name the connector
+    /// was created with.
+    ///
+    /// This is requested by the `verify-full` SSL mode.
+    Full,
 }
 
 /// A constructor of `TlsConnect`ors.
@@ -62,6 +137,33 @@ pub trait TlsConnect<S> {
     /// Returns a future performing a TLS handshake over the stream.
     fn connect(self, stream: S) -> Self::Future;
 
+    /// Enables or disables the TLS Server Name Indication extension for the handshake.
+    ///
+    /// This is called before `connect` with the value of `Config::ssl_sni`. The default implementation ignores it,
+    /// which is appropriate for implementations which can't disable SNI.
+    fn set_server_name_indication(&mut self, enabled: bool) {
+        let _ = enabled;
+    }
+
+    /// Configures the verification of the server's certificate.
+    ///
+    /// This is called before `connect` with the verification requested by `Config::ssl_mode`, and the path set with
+    /// `Config::ssl_root_cert`, whose certificates must replace the trusted certificate authorities of the connector.
+    /// An error aborts the connection, so implementations must return one if they can't enforce the verification.
+    ///
+    /// The default implementation returns an error unless the default verification is requested without a root
+    /// certificate.
+    fn set_verification(
+        &mut self,
+        verification: CertificateVerification,
+        root_cert: Option<&Path>,
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        match (verification, root_cert) {
+            (CertificateVerification::Default, None) => Ok(()),
+            _ => Err("the TLS connector does not support certificate verification settings".into()),
+        }
+    }
+
     #[doc(hidden)]
     fn can_connect(&self, _: private::ForcePrivateApi) -> bool {
         true
@@ -103,6 +205,14 @@ impl<S> TlsConnect<S> for NoTls {
     fn can_connect(&self, _: private::ForcePrivateApi) -> bool {
         false
     }
+
+    fn set_verification(
+        &mut self,
+        _: CertificateVerification,
+        _: Option<&Path>,
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        Ok(())
+    }
 }
 
 /// The future returned by `NoTls`.
@@ -1,7 +1,7 @@
 use crate::to_statement::private::{Sealed, ToStatementType};
 use crate::Statement;
 
-mod private {
+pub(crate) mod private {
     use crate::{Client, Error, Statement};
 
     pub trait Sealed {}
@@ -15,8 +15,8 @@ mod private {
     impl<'a> ToStatementType<'a> {
         pub async fn into_statement(self, client: &Client) -> Result<Statement, Error> {
             match self {
-                ToStatementType::Statement(s) => Ok(s.clone()),
-                ToStatementType::Query(s) => client.prepare_unnamed(s).await,
+                ToStatementType::Statement(s) => client.refresh_statement(s).await,
+                ToStatementType::Query(s) => client.prepare_unnamed(s, &[]).await,
             }
         }
     }
@@ -1,16 +1,25 @@
+#[cfg(feature = "arrow")]
+use crate::arrow::RecordBatchStream;
+use crate::client::RequestPermit;
 use crate::codec::FrontendMessage;
 use crate::connection::RequestMessages;
 use crate::copy_out::CopyOutStream;
+use crate::instrument::Span;
+use crate::large_object::{self, LargeObject, Mode};
+use crate::passthrough::{ForwardStream, PassthroughStream};
+use crate::pipeline::Pipeline;
 use crate::query::RowStream;
+use crate::schema::{ForeignKey, Index, PrimaryKey, Table, TableColumn};
 #[cfg(feature = "runtime")]
 use crate::tls::MakeTlsConnect;
 use crate::tls::TlsConnect;
-use crate::types::{BorrowToSql, ToSql, Type};
+use crate::types::{BorrowToSql, Format, FromRow, FromSqlOwned, Oid, ToSql, ToText, Type};
 #[cfg(feature = "runtime")]
 use crate::Socket;
 use crate::{
-    bind, query, slice_iter, CancelToken, Client, CopyInSink, Error, Portal, Row,
-    SimpleQueryMessage, Statement, ToStatement,
+    bind, query, slice_iter, CancelToken, Client, CopyInSink, Cursor, Error, ExecuteManyError,
+    Portal, PortalCursor, QueryOptions, Row, RowChunks, Rows, SimpleQueryMessage, Statement,
+    StatementDescription, ToStatement,
 };
 use bytes::Buf;
 use futures_util::TryStreamExt;
@@ -45,13 +54,14 @@ impl<'a> Drop for Transaction<'a> {
             "ROLLBACK".to_string()
         };
         let buf = self.client.inner().with_buf(|buf| {
+            self.client.inner().replace_unnamed_statement();
             frontend::query(&query, buf).unwrap();
             buf.split().freeze()
         });
-        let _ = self
-            .client
-            .inner()
-            .send(RequestMessages::Single(FrontendMessage::Raw(buf)));
+        let _ = self.client.inner().send(
+            RequestPermit::exempt(),
+            RequestMessages::Single(FrontendMessage::Raw(buf)),
+        );
     }
 }
 
@@ -72,7 +82,9 @@ impl<'a> Transaction<'a> {
         } else {
             "COMMIT".to_string()
         };
-        self.client.batch_execute(&query).await
+        Span::transaction(self.client.inner().instrumentation(), &query)
+            .instrument(self.client.batch_execute(&query))
+            .await
     }
 
     /// Rolls the transaction back, discarding all changes made within it.
@@ -85,7 +97,9 @@ impl<'a> Transaction<'a> {
         } else {
             "ROLLBACK".to_string()
         };
-        self.client.batch_execute(&query).await
+        Span::transaction(self.client.inner().instrumentation(), &query)
+            .instrument(self.client.batch_execute(&query))
+            .await
     }
 
     /// Like `Client::prepare`.
@@ -102,6 +116,77 @@ impl<'a> Transaction<'a> {
         self.client.prepare_typed(query, parameter_types).await
     }
 
+    /// Like `Client::prepare_unnamed`.
+    pub async fn prepare_unnamed(
+        &self,
+        query: &str,
+        parameter_types: &[Type],
+    ) -> Result<Statement, Error> {
+        self.client.prepare_unnamed(query, parameter_types).await
+    }
+
+    /// Like `Client::describe`.
+    pub async fn describe(&self, query: &str) -> Result<StatementDescription, Error> {
+        self.client.describe(query).await
+    }
+
+    /// Like `Client::describe_typed`.
+    pub async fn describe_typed(
+        &self,
+        query: &str,
+        parameter_types: &[Type],
+    ) -> Result<StatementDescription, Error> {
+        self.client.describe_typed(query, parameter_types).await
+    }
+
+    /// Like `Client::resolve_type`.
+    pub async fn resolve_type(&self, oid: Oid) -> Result<Type, Error> {
+        self.client.resolve_type(oid).await
+    }
+
+    /// Like `Client::tables`.
+    pub async fn tables(&self, schema: &str) -> Result<Vec<Table>, Error> {
+        self.client.tables(schema).await
+    }
+
+    /// Like `Client::table_columns`.
+    pub async fn table_columns(&self, table: Oid) -> Result<Vec<TableColumn>, Error> {
+        self.client.table_columns(table).await
+    }
+
+    /// Like `Client::primary_key`.
+    pub async fn primary_key(&self, table: Oid) -> Result<Option<PrimaryKey>, Error> {
+        self.client.primary_key(table).await
+    }
+
+    /// Like `Client::foreign_keys`.
+    pub async fn foreign_keys(&self, table: Oid) -> Result<Vec<ForeignKey>, Error> {
+        self.client.foreign_keys(table).await
+    }
+
+    /// Like `Client::indexes`.
+    pub async fn indexes(&self, table: Oid) -> Result<Vec<Index>, Error> {
+        self.client.indexes(table).await
+    }
+
+    /// Like `Client::create_large_object`.
+    pub async fn create_large_object(&self) -> Result<Oid, Error> {
+        self.client.create_large_object().await
+    }
+
+    /// Like `Client::unlink_large_object`.
+    pub async fn unlink_large_object(&self, oid: Oid) -> Result<(), Error> {
+        self.client.unlink_large_object(oid).await
+    }
+
+    /// Opens the large object with the given OID.
+    ///
+    /// The handle is only valid until the end of the transaction. See the [`large_object`](crate::large_object)
+    /// module for details.
+    pub async fn open_large_object(&self, oid: Oid, mode: Mode) -> Result<LargeObject<'_>, Error> {
+        large_object::open(self.client.inner(), oid, mode).await
+    }
+
     /// Like `Client::query`.
     pub async fn query<T>(
         &self,
@@ -114,6 +199,57 @@ impl<'a> Transaction<'a> {
         self.client.query(statement, params).await
     }
 
+    /// Like `Client::query_with_opts`.
+    pub async fn query_with_opts<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+        options: QueryOptions,
+    ) -> Result<Vec<Row>, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        self.client
+            .query_with_opts(statement, params, options)
+            .await
+    }
+
+    /// Like `Client::query_with_limit`.
+    pub async fn query_with_limit<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+        max_rows: i32,
+    ) -> Result<Vec<Row>, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        self.client
+            .query_with_limit(statement, params, max_rows)
+            .await
+    }
+
+    /// Like `Client::query_rows`.
+    pub async fn query_rows<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Rows, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        self.client.query_rows(statement, params).await
+    }
+
+    /// Like `Client::query_cached`.
+    pub async fn query_cached(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, Error> {
+        self.client.query_cached(query, params).await
+    }
+
     /// Like `Client::query_one`.
     pub async fn query_one<T>(
         &self,
@@ -138,6 +274,42 @@ impl<'a> Transaction<'a> {
         self.client.query_opt(statement, params).await
     }
 
+    /// Like `Client::query_scalar`.
+    pub async fn query_scalar<R>(
+        &self,
+        statement: &(impl ?Sized + ToStatement),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<R, Error>
+    where
+        R: FromSqlOwned,
+    {
+        self.client.query_scalar(statement, params).await
+    }
+
+    /// Like `Client::query_scalar_opt`.
+    pub async fn query_scalar_opt<R>(
+        &self,
+        statement: &(impl ?Sized + ToStatement),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<R>, Error>
+    where
+        R: FromSqlOwned,
+    {
+        self.client.query_scalar_opt(statement, params).await
+    }
+
+    /// Like `Client::query_as`.
+    pub async fn query_as<R>(
+        &self,
+        statement: &(impl ?Sized + ToStatement),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<R>, Error>
+    where
+        R: FromRow,
+    {
+        self.client.query_as(statement, params).await
+    }
+
     /// Like `Client::query_raw`.
     pub async fn query_raw<T, P, I>(&self, statement: &T, params: I) -> Result<RowStream, Error>
     where
@@ -149,16 +321,142 @@ impl<'a> Transaction<'a> {
         self.client.query_raw(statement, params).await
     }
 
+    /// Like `Client::query_raw_with_limit`.
+    pub async fn query_raw_with_limit<T, P, I>(
+        &self,
+        statement: &T,
+        params: I,
+        max_rows: i32,
+    ) -> Result<RowStream, Error>
+    where
+        T: ?Sized + ToStatement,
+        P: BorrowToSql,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.client
+            .query_raw_with_limit(statement, params, max_rows)
+            .await
+    }
+
+    /// Like `Client::query_raw_passthrough`.
+    pub async fn query_raw_passthrough<T, P, I>(
+        &self,
+        statement: &T,
+        params: I,
+    ) -> Result<PassthroughStream, Error>
+    where
+        T: ?Sized + ToStatement,
+        P: BorrowToSql,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.client.query_raw_passthrough(statement, params).await
+    }
+
+    /// Like `Client::query_raw_forward`.
+    pub async fn query_raw_forward<T, P, I>(
+        &self,
+        statement: &T,
+        params: I,
+    ) -> Result<ForwardStream, Error>
+    where
+        T: ?Sized + ToStatement,
+        P: BorrowToSql,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.client.query_raw_forward(statement, params).await
+    }
+
     /// Like `Client::query_raw_txt`.
-    pub async fn query_raw_txt< S, I>(&self, query: &str, params: I) -> Result<RowStream, Error>
+    pub async fn query_raw_txt<P, I>(&self, query: &str, params: I) -> Result<RowStream, Error>
     where
-        S: AsRef<str>,
-        I: IntoIterator<Item = Option<S>>,
+        P: ToText,
+        I: IntoIterator<Item = P>,
         I::IntoIter: ExactSizeIterator,
     {
         self.client.query_raw_txt(query, params).await
     }
 
+    /// Like `Client::query_one_txt`.
+    pub async fn query_one_txt<P, I>(&self, query: &str, params: I) -> Result<Row, Error>
+    where
+        P: ToText,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.client.query_one_txt(query, params).await
+    }
+
+    /// Like `Client::query_opt_txt`.
+    pub async fn query_opt_txt<P, I>(&self, query: &str, params: I) -> Result<Option<Row>, Error>
+    where
+        P: ToText,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.client.query_opt_txt(query, params).await
+    }
+
+    /// Like `Client::query_with_format`.
+    pub async fn query_with_format<P, I>(
+        &self,
+        query: &str,
+        params: I,
+        param_formats: &[Format],
+        result_format: Format,
+    ) -> Result<RowStream, Error>
+    where
+        P: AsRef<[u8]>,
+        I: IntoIterator<Item = Option<P>>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.client
+            .query_with_format(query, params, param_formats, result_format)
+            .await
+    }
+
+    /// Like `Client::query_raw_inferred`.
+    pub async fn query_raw_inferred<P, I>(&self, query: &str, params: I) -> Result<RowStream, Error>
+    where
+        P: BorrowToSql,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.client.query_raw_inferred(query, params).await
+    }
+
+    /// Like `Client::query_arrow`.
+    #[cfg(feature = "arrow")]
+    pub async fn query_arrow<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+        batch_size: usize,
+    ) -> Result<RecordBatchStream, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        self.client.query_arrow(statement, params, batch_size).await
+    }
+
+    /// Like `Client::query_chunked`.
+    ///
+    /// The portal is bound in a savepoint of this transaction.
+    pub async fn query_chunked<T>(
+        &mut self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+        chunk_size: i32,
+    ) -> Result<RowChunks<'_>, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        let transaction = self.transaction().await?;
+        RowChunks::new(transaction, statement, params, chunk_size).await
+    }
+
     /// Like `Client::execute`.
     pub async fn execute<T>(
         &self,
@@ -171,6 +469,21 @@ impl<'a> Transaction<'a> {
         self.client.execute(statement, params).await
     }
 
+    /// Like `Client::execute_with_opts`.
+    pub async fn execute_with_opts<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+        options: QueryOptions,
+    ) -> Result<u64, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        self.client
+            .execute_with_opts(statement, params, options)
+            .await
+    }
+
     /// Like `Client::execute_iter`.
     pub async fn execute_raw<P, I, T>(&self, statement: &T, params: I) -> Result<u64, Error>
     where
@@ -182,6 +495,22 @@ impl<'a> Transaction<'a> {
         self.client.execute_raw(statement, params).await
     }
 
+    /// Like `Client::execute_many`.
+    pub async fn execute_many<T, P, I, R>(
+        &self,
+        statement: &T,
+        rows: R,
+    ) -> Result<u64, ExecuteManyError>
+    where
+        T: ?Sized + ToStatement,
+        P: BorrowToSql,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+        R: IntoIterator<Item = I>,
+    {
+        self.client.execute_many(statement, rows).await
+    }
+
     /// Binds a statement to a set of parameters, creating a `Portal` which can be incrementally queried.
     ///
     /// Portals only last for the duration of the transaction in which they are created, and can only be used on the
@@ -215,6 +544,23 @@ impl<'a> Transaction<'a> {
         bind::bind(self.client.inner(), statement, params).await
     }
 
+    /// Binds a statement to a set of parameters, returning a `PortalCursor` which fetches the resulting rows in chunks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of parameters provided does not match the number expected.
+    pub async fn bind_cursor<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<PortalCursor<'_>, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        let portal = self.bind(statement, params).await?;
+        Ok(PortalCursor::new(self, portal))
+    }
+
     /// Continues execution of a portal, returning a stream of the resulting rows.
     ///
     /// Unlike `query`, portals can be incrementally evaluated by limiting the number of rows returned in each call to
@@ -237,6 +583,27 @@ impl<'a> Transaction<'a> {
         query::query_portal(self.client.inner(), portal, max_rows).await
     }
 
+    /// Like `Client::declare_cursor`.
+    ///
+    /// The rows of the cursor are kept by the server once the transaction commits.
+    pub async fn declare_cursor(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Cursor, Error> {
+        self.client.declare_cursor(query, params).await
+    }
+
+    /// Like `Client::fetch`.
+    pub async fn fetch(&self, cursor: &Cursor, count: i64) -> Result<Vec<Row>, Error> {
+        self.client.fetch(cursor, count).await
+    }
+
+    /// Like `Client::close_cursor`.
+    pub async fn close_cursor(&self, cursor: Cursor) -> Result<(), Error> {
+        self.client.close_cursor(cursor).await
+    }
+
     /// Like `Client::copy_in`.
     pub async fn copy_in<T, U>(&self, statement: &T) -> Result<CopyInSink<U>, Error>
     where
@@ -264,6 +631,49 @@ impl<'a> Transaction<'a> {
         self.client.batch_execute(query).await
     }
 
+    /// Like `Client::batch_execute_params`.
+    pub async fn batch_execute_params<P, I>(
+        &self,
+        query: &str,
+        params: I,
+    ) -> Result<Vec<String>, Error>
+    where
+        P: ToText,
+        I: IntoIterator<Item = P>,
+    {
+        self.client.batch_execute_params(query, params).await
+    }
+
+    /// Like `Client::notify`.
+    pub async fn notify(&self, channel: &str, payload: &str) -> Result<(), Error> {
+        self.client.notify(channel, payload).await
+    }
+
+    /// Like `Client::unlisten`.
+    pub async fn unlisten(&self, channel: &str) -> Result<(), Error> {
+        self.client.unlisten(channel).await
+    }
+
+    /// Like `Client::unlisten_all`.
+    pub async fn unlisten_all(&self) -> Result<(), Error> {
+        self.client.unlisten_all().await
+    }
+
+    /// Like `Client::set_parameter`.
+    pub async fn set_parameter(&self, name: &str, value: &str, local: bool) -> Result<(), Error> {
+        self.client.set_parameter(name, value, local).await
+    }
+
+    /// Like `Client::get_parameter`.
+    pub async fn get_parameter(&self, name: &str) -> Result<String, Error> {
+        self.client.get_parameter(name).await
+    }
+
+    /// Like `Client::pipeline`.
+    pub fn pipeline(&self) -> Pipeline<'_> {
+        self.client.pipeline()
+    }
+
     /// Like `Client::cancel_token`.
     pub fn cancel_token(&self) -> CancelToken {
         self.client.cancel_token()
@@ -308,7 +718,9 @@ impl<'a> Transaction<'a> {
         let depth = self.savepoint.as_ref().map_or(0, |sp| sp.depth) + 1;
         let name = name.unwrap_or_else(|| format!("sp_{}", depth));
         let query = format!("SAVEPOINT {}", name);
-        self.batch_execute(&query).await?;
+        Span::transaction(self.client.inner().instrumentation(), &query)
+            .instrument(self.batch_execute(&query))
+            .await?;
 
         Ok(Transaction {
             client: self.client,
@@ -317,6 +729,17 @@ impl<'a> Transaction<'a> {
         })
     }
 
+    /// Returns the name of the savepoint of this transaction, or `None` if it isn't nested.
+    pub fn savepoint_name(&self) -> Option<&str> {
+        self.savepoint.as_ref().map(|sp| &*sp.name)
+    }
+
+    /// Returns the nesting depth of this transaction, which is 0 if it isn't nested and is incremented by each
+    /// savepoint.
+    pub fn depth(&self) -> u32 {
+        self.savepoint.as_ref().map_or(0, |sp| sp.depth)
+    }
+
     /// Returns a reference to the underlying `Client`.
     pub fn client(&self) -> &Client {
         self.client
@@ -1,4 +1,6 @@
+use crate::instrument::Span;
 use crate::{Client, Error, Transaction};
+use postgres_protocol::escape::{escape_identifier, escape_literal};
 
 /// The isolation level of a database transaction.
 #[derive(Debug, Copy, Clone)]
@@ -20,11 +22,16 @@ pub enum IsolationLevel {
 }
 
 /// A builder for database transactions.
+///
+/// The transaction is started by a single query, which also imports its snapshot and sets its local parameters if
+/// any, so that it never runs without them.
 pub struct TransactionBuilder<'a> {
     client: &'a mut Client,
     isolation_level: Option<IsolationLevel>,
     read_only: Option<bool>,
     deferrable: Option<bool>,
+    snapshot: Option<String>,
+    settings: Vec<(String, String)>,
 }
 
 impl<'a> TransactionBuilder<'a> {
@@ -34,6 +41,8 @@ impl<'a> TransactionBuilder<'a> {
             isolation_level: None,
             read_only: None,
             deferrable: None,
+            snapshot: None,
+            settings: vec![],
         }
     }
 
@@ -59,9 +68,29 @@ impl<'a> TransactionBuilder<'a> {
         self
     }
 
+    /// Makes the transaction see the same data as another transaction, by importing the snapshot it exported with
+    /// `pg_export_snapshot()`.
+    ///
+    /// The isolation level of the transaction must be `RepeatableRead` or `Serializable`.
+    pub fn snapshot(mut self, snapshot_id: &str) -> Self {
+        self.snapshot = Some(snapshot_id.to_string());
+        self
+    }
+
+    /// Sets a configuration parameter for the duration of the transaction, like `SET LOCAL`.
+    ///
+    /// Parameters are set in the order of the calls to this method.
+    pub fn set_local(mut self, name: &str, value: &str) -> Self {
+        self.settings.push((name.to_string(), value.to_string()));
+        self
+    }
+
     /// Begins the transaction.
     ///
     /// The transaction will roll back by default - use the `commit` method to commit it.
+    ///
+    /// If the snapshot can't be imported or a parameter can't be set, the transaction is rolled back and an error is
+    /// returned.
     pub async fn start(self) -> Result<Transaction<'a>, Error> {
         let mut query = "START TRANSACTION".to_string();
         let mut first = true;
@@ -106,8 +135,25 @@ impl<'a> TransactionBuilder<'a> {
             query.push_str(s);
         }
 
-        self.client.batch_execute(&query).await?;
+        if let Some(snapshot) = &self.snapshot {
+            query.push_str("; SET TRANSACTION SNAPSHOT ");
+            query.push_str(&escape_literal(snapshot));
+        }
+
+        for (name, value) in &self.settings {
+            query.push_str("; SET LOCAL ");
+            query.push_str(&escape_identifier(name));
+            query.push_str(" = ");
+            query.push_str(&escape_literal(value));
+        }
+
+        let result = Span::transaction(self.client.inner().instrumentation(), &query)
+            .instrument(self.client.batch_execute(&query))
+            .await;
 
-        Ok(Transaction::new(self.client))
+        // Dropping the transaction rolls it back if it was started before a following statement failed.
+        let transaction = Transaction::new(self.client);
+        result?;
+        Ok(transaction)
     }
 }
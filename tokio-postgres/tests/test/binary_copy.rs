@@ -1,7 +1,10 @@
 use crate::connect;
 use futures_util::{pin_mut, TryStreamExt};
-use tokio_postgres::binary_copy::{BinaryCopyInWriter, BinaryCopyOutStream};
-use tokio_postgres::types::Type;
+use tokio_postgres::binary_copy::{
+    BinaryCopyInWriter, BinaryCopyOutRow, BinaryCopyOutStream, FromCopyRow, ToCopyRow,
+};
+use tokio_postgres::types::{ToSql, Type};
+use tokio_postgres::Error;
 
 #[tokio::test]
 async fn write_basic() {
@@ -201,3 +204,179 @@ async fn read_big_rows() {
         assert_eq!(row.get::<&[u8]>(1), &vec![i as u8; 128 * 1024][..]);
     }
 }
+
+struct Foo {
+    id: i32,
+    bar: Option<String>,
+}
+
+impl ToCopyRow for Foo {
+    fn to_copy_row(&self) -> Vec<&(dyn ToSql + Sync)> {
+        vec![&self.id, &self.bar]
+    }
+}
+
+impl FromCopyRow for Foo {
+    fn from_copy_row(row: &BinaryCopyOutRow) -> Result<Foo, Error> {
+        Ok(Foo {
+            id: row.try_get(0)?,
+            bar: row.try_get(1)?,
+        })
+    }
+}
+
+#[tokio::test]
+async fn typed_rows() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute("CREATE TEMPORARY TABLE foo (id INT, bar TEXT)")
+        .await
+        .unwrap();
+
+    let sink = client
+        .copy_in("COPY foo (id, bar) FROM STDIN BINARY")
+        .await
+        .unwrap();
+    let writer = BinaryCopyInWriter::new(sink, &[Type::INT4, Type::TEXT]);
+    pin_mut!(writer);
+    for foo in &[
+        Foo {
+            id: 1,
+            bar: Some("foobar".to_string()),
+        },
+        Foo { id: 2, bar: None },
+    ] {
+        writer.as_mut().write_row(foo).await.unwrap();
+    }
+    assert_eq!(writer.rows_written(), 2);
+    assert_eq!(writer.finish().await.unwrap(), 2);
+
+    let stream = client
+        .copy_out("COPY foo (id, bar) TO STDOUT BINARY")
+        .await
+        .unwrap();
+    let stream = BinaryCopyOutStream::new(stream, &[Type::INT4, Type::TEXT]);
+    pin_mut!(stream);
+    let mut rows = vec![];
+    while let Some(row) = stream.try_next().await.unwrap() {
+        rows.push(row.decode::<Foo>().unwrap());
+    }
+    assert_eq!(stream.rows_read(), 2);
+
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].id, 1);
+    assert_eq!(rows[0].bar.as_deref(), Some("foobar"));
+    assert_eq!(rows[1].id, 2);
+    assert_eq!(rows[1].bar, None);
+}
+
+#[cfg(feature = "arrow")]
+#[tokio::test]
+async fn arrow_round_trip() {
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Int32Type, IntervalMonthDayNanoType};
+    use arrow_array::{Array, Int64Array, RecordBatch};
+    use std::sync::Arc;
+    use tokio_postgres::arrow::{RecordBatchCopyInWriter, RecordBatchCopyOutStream, Schema};
+
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute(
+            "CREATE TEMPORARY TABLE foo (
+                id INT4,
+                flag BOOL,
+                name TEXT,
+                price NUMERIC,
+                data JSONB,
+                key UUID,
+                at TIMESTAMPTZ,
+                span INTERVAL
+            );
+            CREATE TEMPORARY TABLE bar (LIKE foo);
+            INSERT INTO foo VALUES
+                (1, true, 'alice', 1.50, '{\"a\": 1}', 'a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11',
+                 '2000-01-01 00:00:00+00', '1 month 2 days 3 microseconds'),
+                (2, NULL, NULL, NULL, NULL, NULL, NULL, NULL),
+                (3, false, 'bob', 'NaN', '[]', '00000000-0000-0000-0000-000000000000',
+                 '1970-01-01 00:00:00+00', '-1 hour');",
+        )
+        .await
+        .unwrap();
+
+    let columns = [
+        ("id", Type::INT4),
+        ("flag", Type::BOOL),
+        ("name", Type::TEXT),
+        ("price", Type::NUMERIC),
+        ("data", Type::JSONB),
+        ("key", Type::UUID),
+        ("at", Type::TIMESTAMPTZ),
+        ("span", Type::INTERVAL),
+    ];
+    let stream = client
+        .copy_out("COPY (SELECT * FROM foo ORDER BY id) TO STDOUT (FORMAT binary)")
+        .await
+        .unwrap();
+    let stream = RecordBatchCopyOutStream::new(stream, &columns, 2).unwrap();
+    assert_eq!(stream.schema().field(2).name(), "name");
+    let batches = stream.try_collect::<Vec<_>>().await.unwrap();
+    assert_eq!(
+        batches.iter().map(|b| b.num_rows()).collect::<Vec<_>>(),
+        [2, 1]
+    );
+
+    let batch = &batches[0];
+    assert_eq!(
+        batch.column(0).as_primitive::<Int32Type>().values(),
+        &[1, 2]
+    );
+    assert!(batch.column(1).as_boolean().value(0));
+    assert!(batch.column(1).is_null(1));
+    assert_eq!(batch.column(3).as_string::<i32>().value(0), "1.50");
+    assert_eq!(batch.column(4).as_string::<i32>().value(0), "{\"a\": 1}");
+    let span = batch
+        .column(7)
+        .as_primitive::<IntervalMonthDayNanoType>()
+        .value(0);
+    assert_eq!((span.months, span.days, span.nanoseconds), (1, 2, 3_000));
+    assert_eq!(batches[1].column(3).as_string::<i32>().value(0), "NaN");
+
+    let types = columns.iter().map(|(_, t)| t.clone()).collect::<Vec<_>>();
+    let sink = client
+        .copy_in("COPY bar FROM STDIN (FORMAT binary)")
+        .await
+        .unwrap();
+    let writer = RecordBatchCopyInWriter::new(sink, &types).unwrap();
+    pin_mut!(writer);
+    for batch in &batches {
+        writer.as_mut().write(batch).await.unwrap();
+    }
+    assert_eq!(writer.finish().await.unwrap(), 3);
+
+    let differences = client
+        .query_one(
+            "SELECT count(*) FROM ((TABLE foo EXCEPT TABLE bar) UNION (TABLE bar EXCEPT TABLE foo)) t",
+            &[],
+        )
+        .await
+        .unwrap()
+        .get::<_, i64>(0);
+    assert_eq!(differences, 0);
+
+    let sink = client
+        .copy_in("COPY bar (id) FROM STDIN (FORMAT binary)")
+        .await
+        .unwrap();
+    let writer = RecordBatchCopyInWriter::new(sink, &[Type::INT4]).unwrap();
+    pin_mut!(writer);
+    let schema = Arc::new(Schema::new(vec![arrow_schema::Field::new(
+        "id",
+        arrow_schema::DataType::Int64,
+        true,
+    )]));
+    let batch = RecordBatch::try_new(schema, vec![Arc::new(Int64Array::from(vec![1]))]).unwrap();
+    let err = writer.as_mut().write(&batch).await.unwrap_err();
+    assert!(err.to_string().contains("Arrow type Int64"));
+}
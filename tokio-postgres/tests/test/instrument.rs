@@ -0,0 +1,128 @@
+use crate::connect;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+type Spans = Arc<Mutex<HashMap<u64, (&'static str, HashMap<&'static str, String>)>>>;
+type Events = Arc<Mutex<Vec<HashMap<&'static str, String>>>>;
+
+#[derive(Default)]
+struct Recorder {
+    next_id: AtomicU64,
+    spans: Spans,
+    events: Events,
+}
+
+struct FieldVisitor<'a>(&'a mut HashMap<&'static str, String>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name(), format!("{:?}", value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name(), value.to_string());
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let mut fields = HashMap::new();
+        span.record(&mut FieldVisitor(&mut fields));
+        self.spans
+            .lock()
+            .unwrap()
+            .insert(id, (span.metadata().name(), fields));
+        Id::from_u64(id)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        let mut spans = self.spans.lock().unwrap();
+        let (_, fields) = spans.get_mut(&span.into_u64()).unwrap();
+        values.record(&mut FieldVisitor(fields));
+    }
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = HashMap::new();
+        event.record(&mut FieldVisitor(&mut fields));
+        self.events.lock().unwrap().push(fields);
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+#[tokio::test]
+async fn spans() {
+    let recorder = Recorder::default();
+    let spans = recorder.spans.clone();
+    let events = recorder.events.clone();
+    let _guard = tracing::subscriber::set_default(recorder);
+
+    let client = connect("user=postgres").await;
+    let pid = client
+        .query_one("SELECT pg_backend_pid()", &[])
+        .await
+        .unwrap()
+        .get::<_, i32>(0)
+        .to_string();
+    let stmt = client
+        .prepare("SELECT * FROM generate_series(1, $1)")
+        .await
+        .unwrap();
+    client.query(&stmt, &[&3i32]).await.unwrap();
+    client.prepare("SELEC 1").await.unwrap_err();
+    client
+        .batch_execute("DO $$ BEGIN RAISE NOTICE 'hello from %', 'plpgsql'; END $$")
+        .await
+        .unwrap();
+
+    let spans = spans.lock().unwrap();
+    let find = |name: &str, field: &str, value: &str| {
+        spans
+            .values()
+            .find(|(n, fields)| *n == name && fields.get(field).map(|v| &**v) == Some(value))
+            .map(|(_, fields)| fields.clone())
+            .unwrap_or_else(|| panic!("no {} span with {} = {}", name, field, value))
+    };
+
+    let prepare = find(
+        "prepare",
+        "db.query.text",
+        "SELECT * FROM generate_series(1, $1)",
+    );
+    assert_eq!(prepare["otel.kind"], "client");
+    assert_eq!(prepare["db.namespace"], "postgres");
+    assert_eq!(prepare["params"], "1");
+    assert_eq!(prepare["pid"], pid);
+    let query = find("query", "statement", &prepare["statement"]);
+    assert_eq!(query["rows"], "3");
+    assert_eq!(query["params"], "1");
+    assert_eq!(query["pid"], pid);
+    assert_eq!(query["db.system"], "postgresql");
+
+    let error = find("prepare", "db.query.text", "SELEC 1");
+    assert_eq!(error["db.response.status_code"], "42601");
+    assert_eq!(error["error.type"], "42601");
+    assert_eq!(error["otel.status_code"], "ERROR");
+
+    let events = events.lock().unwrap();
+    let notice = events
+        .iter()
+        .find(|fields| fields.get("message").map(|v| &**v) == Some("hello from plpgsql"))
+        .expect("no notice event");
+    assert_eq!(notice["severity"], "NOTICE");
+    assert_eq!(notice["code"], "00000");
+}
@@ -1,26 +1,50 @@
 #![warn(rust_2018_idioms)]
 
-use bytes::{Bytes, BytesMut};
+use bytes::{BufMut, Bytes, BytesMut};
+use fallible_iterator::FallibleIterator;
 use futures_channel::mpsc;
 use futures_util::{
     future, join, pin_mut, stream, try_join, Future, FutureExt, SinkExt, StreamExt, TryStreamExt,
 };
 use pin_project_lite::pin_project;
+use postgres_protocol::message::backend::Message;
+use postgres_protocol::message::frontend;
+use std::borrow::Cow;
 use std::fmt::Write;
+use std::io::SeekFrom;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, DuplexStream};
 use tokio::net::TcpStream;
 use tokio::time;
-use tokio_postgres::error::SqlState;
+use tokio_postgres::config::LifecycleEvent;
+use tokio_postgres::copy_options::{self, CopyFormat, CopyOptions};
+use tokio_postgres::error::{ConstraintKind, SqlState, SqlStateClass};
+use tokio_postgres::export::{ExportFormat, ExportStream};
+use tokio_postgres::gss::{GssContext, GssStep};
+use tokio_postgres::interceptor::{Interceptor, Operation, Outcome, Params};
+use tokio_postgres::large_object::Mode;
+use tokio_postgres::metrics::Metrics;
+use tokio_postgres::passthrough;
+use tokio_postgres::schema::TableKind;
+use tokio_postgres::security::{AuthenticationMethod, Encryption};
 use tokio_postgres::tls::{NoTls, NoTlsStream};
-use tokio_postgres::types::{Kind, Type};
+use tokio_postgres::types::{
+    params, BinaryValue, Format, FromRow, Kind, NamedRow, Streamed, ToSql, ToText, Type,
+};
 use tokio_postgres::{
-    AsyncMessage, Client, Config, Connection, Error, IsolationLevel, SimpleQueryMessage,
+    AsyncMessage, Client, Config, Connection, Error, GenericClient, IsolationLevel, QueryOptions,
+    RowStream, ServerVersion, SimpleQueryMessage, Statement, TransactionStatus,
 };
 
 mod binary_copy;
+#[cfg(feature = "tracing")]
+mod instrument;
 mod parse;
+mod replication;
 #[cfg(feature = "runtime")]
 mod runtime;
 mod types;
@@ -103,6 +127,28 @@ async fn plain_password_ok() {
     connect("user=pass_user password=password dbname=postgres").await;
 }
 
+#[tokio::test]
+async fn password_provider() {
+    let socket = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+    let mut config = "user=pass_user dbname=postgres password=foo"
+        .parse::<Config>()
+        .unwrap();
+    config.password_provider(|| async { Ok::<_, Error>("password") });
+    let (client, connection) = config.connect_raw(socket, NoTls).await.unwrap();
+    tokio::spawn(connection.map(|r| r.unwrap()));
+
+    client.batch_execute("SELECT 1").await.unwrap();
+}
+
+#[tokio::test]
+async fn password_provider_error() {
+    let socket = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+    let mut config = "user=scram_user dbname=postgres".parse::<Config>().unwrap();
+    config.password_provider(|| async { Err::<String, _>("token service unavailable") });
+    let err = config.connect_raw(socket, NoTls).await.err().unwrap();
+    assert!(err.to_string().contains("token service unavailable"));
+}
+
 #[tokio::test]
 async fn md5_password_missing() {
     connect_raw("user=md5_user dbname=postgres")
@@ -148,363 +194,2768 @@ async fn scram_password_ok() {
 }
 
 #[tokio::test]
-async fn pipelined_prepare() {
-    let client = connect("user=postgres").await;
+async fn channel_binding_without_tls() {
+    // Channel binding is only available over TLS, so it can't be required of plain connections.
+    for s in [
+        "user=postgres channel_binding=require",
+        "user=pass_user password=password dbname=postgres channel_binding=require",
+        "user=scram_user password=password dbname=postgres channel_binding=require",
+    ] {
+        let err = connect_raw(s).await.err().unwrap();
+        assert!(
+            err.to_string().contains("did not use channel binding"),
+            "{}",
+            err
+        );
+    }
 
-    let prepare1 = client.prepare("SELECT $1::HSTORE[]");
-    let prepare2 = client.prepare("SELECT $1::BIGINT");
+    for s in [
+        "user=scram_user password=password dbname=postgres channel_binding=prefer",
+        "user=scram_user password=password dbname=postgres channel_binding=disable",
+    ] {
+        connect(s).await;
+    }
+}
 
-    let (statement1, statement2) = try_join!(prepare1, prepare2).unwrap();
+#[tokio::test]
+async fn connection_security() {
+    let cases = [
+        ("user=postgres", AuthenticationMethod::None),
+        (
+            "user=pass_user password=password dbname=postgres",
+            AuthenticationMethod::Cleartext,
+        ),
+        (
+            "user=scram_user password=password dbname=postgres",
+            AuthenticationMethod::ScramSha256,
+        ),
+    ];
+
+    for (s, method) in cases {
+        let security = connect(s).await.connection_security();
+        assert_eq!(security.authentication_method(), method);
+        assert_eq!(security.encryption(), Encryption::None);
+        assert!(!security.channel_binding());
+    }
+}
 
-    assert_eq!(statement1.params()[0].name(), "_hstore");
-    assert_eq!(statement1.columns()[0].type_().name(), "_hstore");
+struct ScriptedGssContext;
+
+impl GssContext for ScriptedGssContext {
+    fn step(
+        &mut self,
+        token: Option<&[u8]>,
+    ) -> Result<GssStep, Box<dyn std::error::Error + Sync + Send>> {
+        match token {
+            None => Ok(GssStep::Continue(b"client first".to_vec())),
+            Some(b"server token") => Ok(GssStep::Complete(Some(b"client final".to_vec()))),
+            Some(_) => Err("unexpected token".into()),
+        }
+    }
 
-    assert_eq!(statement2.params()[0], Type::INT8);
-    assert_eq!(statement2.columns()[0].type_(), &Type::INT8);
+    fn wrap(&mut self, _: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Sync + Send>> {
+        unreachable!()
+    }
+
+    fn unwrap(&mut self, _: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Sync + Send>> {
+        unreachable!()
+    }
 }
 
 #[tokio::test]
-async fn insert_select() {
-    let client = connect("user=postgres").await;
+async fn gss_authentication() {
+    async fn read_message(stream: &mut DuplexStream) -> (u8, Vec<u8>) {
+        let tag = stream.read_u8().await.unwrap();
+        let mut body = vec![0; stream.read_i32().await.unwrap() as usize - 4];
+        stream.read_exact(&mut body).await.unwrap();
+        (tag, body)
+    }
 
-    client
-        .batch_execute("CREATE TEMPORARY TABLE foo (id SERIAL, name TEXT)")
-        .await
+    fn authentication(code: i32, data: &[u8], buf: &mut BytesMut) {
+        buf.put_u8(b'R');
+        buf.put_i32(8 + data.len() as i32);
+        buf.put_i32(code);
+        buf.put_slice(data);
+    }
+
+    // Reads the startup message, and requests GSSAPI authentication.
+    async fn request_gss(server: &mut DuplexStream) {
+        let mut startup = vec![0; server.read_i32().await.unwrap() as usize - 4];
+        server.read_exact(&mut startup).await.unwrap();
+
+        let mut buf = BytesMut::new();
+        authentication(7, &[], &mut buf);
+        server.write_all(&buf).await.unwrap();
+    }
+
+    let config = "host=localhost user=postgres gssencmode=disable"
+        .parse::<Config>()
         .unwrap();
 
-    let insert = client.prepare("INSERT INTO foo (name) VALUES ($1), ($2)");
-    let select = client.prepare("SELECT id, name FROM foo ORDER BY id");
-    let (insert, select) = try_join!(insert, select).unwrap();
+    let (stream, mut server) = tokio::io::duplex(1024);
+    tokio::spawn(async move {
+        request_gss(&mut server).await;
+        server
+    });
+    let err = config.connect_raw(stream, NoTls).await.err().unwrap();
+    assert!(
+        err.to_string().contains("no GSSAPI context provider"),
+        "{}",
+        err
+    );
 
-    let insert = client.execute(&insert, &[&"alice", &"bob"]);
-    let select = client.query(&select, &[]);
-    let (_, rows) = try_join!(insert, select).unwrap();
+    let (stream, mut server) = tokio::io::duplex(1024);
+    let server = tokio::spawn(async move {
+        request_gss(&mut server).await;
 
-    assert_eq!(rows.len(), 2);
-    assert_eq!(rows[0].get::<_, i32>(0), 1);
-    assert_eq!(rows[0].get::<_, &str>(1), "alice");
-    assert_eq!(rows[1].get::<_, i32>(0), 2);
-    assert_eq!(rows[1].get::<_, &str>(1), "bob");
-}
+        let mut buf = BytesMut::new();
+        assert_eq!(
+            read_message(&mut server).await,
+            (b'p', b"client first".to_vec())
+        );
 
-#[tokio::test]
-async fn custom_enum() {
-    let client = connect("user=postgres").await;
+        authentication(8, b"server token", &mut buf);
+        server.write_all(&buf.split()).await.unwrap();
+        assert_eq!(
+            read_message(&mut server).await,
+            (b'p', b"client final".to_vec())
+        );
 
-    client
-        .batch_execute(
-            "CREATE TYPE pg_temp.mood AS ENUM (
-                'sad',
-                'ok',
-                'happy'
-            )",
-        )
-        .await
-        .unwrap();
+        authentication(0, &[], &mut buf);
+        buf.put_u8(b'K');
+        buf.put_i32(12);
+        buf.put_i32(1);
+        buf.put_i32(2);
+        buf.put_u8(b'Z');
+        buf.put_i32(5);
+        buf.put_u8(b'I');
+        server.write_all(&buf).await.unwrap();
+        server
+    });
+
+    let mut config = config;
+    config.gss_context_provider(|host: &str| {
+        assert_eq!(host, "localhost");
+        Ok::<_, std::io::Error>(ScriptedGssContext)
+    });
+    let (client, _connection) = config.connect_raw(stream, NoTls).await.unwrap();
+    assert_eq!(
+        client.connection_security().authentication_method(),
+        AuthenticationMethod::Gss
+    );
+    server.await.unwrap();
+}
 
-    let select = client.prepare("SELECT $1::mood").await.unwrap();
+#[tokio::test]
+async fn fallback_application_name() {
+    async fn application_name(s: &str) -> String {
+        let messages = connect(s)
+            .await
+            .simple_query("SHOW application_name")
+            .await
+            .unwrap();
+        match &messages[0] {
+            SimpleQueryMessage::Row(row) => row.get(0).unwrap().to_string(),
+            _ => panic!("unexpected message"),
+        }
+    }
 
-    let ty = &select.params()[0];
-    assert_eq!("mood", ty.name());
     assert_eq!(
-        &Kind::Enum(vec![
-            "sad".to_string(),
-            "ok".to_string(),
-            "happy".to_string(),
-        ]),
-        ty.kind(),
+        application_name("user=postgres fallback_application_name=fallback").await,
+        "fallback"
+    );
+    assert_eq!(
+        application_name("user=postgres application_name=app fallback_application_name=fallback")
+            .await,
+        "app"
     );
 }
 
 #[tokio::test]
-async fn custom_domain() {
-    let client = connect("user=postgres").await;
+async fn startup_params() {
+    let socket = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+    let mut config = "user=postgres".parse::<Config>().unwrap();
+    config.startup_param("search_path", "pg_catalog, public");
+    let (client, connection) = config.connect_raw(socket, NoTls).await.unwrap();
+    tokio::spawn(connection.map(|r| r.unwrap()));
+
+    let row = client.query_one("SHOW search_path", &[]).await.unwrap();
+    assert_eq!(row.get::<_, &str>(0), "pg_catalog, public");
+
+    let socket = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+    let mut config = "user=postgres".parse::<Config>().unwrap();
+    config.startup_param("user", "root");
+    let err = config.connect_raw(socket, NoTls).await.err().unwrap();
+    assert!(err.to_string().contains("startup parameter `user`"));
+}
+
+#[tokio::test]
+async fn log_slow_statements() {
+    let slow = Arc::new(Mutex::new(vec![]));
+    let mut config = "user=postgres".parse::<Config>().unwrap();
+    config
+        .log_slow_statements(Duration::from_millis(100))
+        .slow_statement_callback({
+            let slow = slow.clone();
+            move |statement| {
+                assert!(statement.duration() >= Duration::from_millis(100));
+                slow.lock()
+                    .unwrap()
+                    .push((statement.query().map(str::to_string), statement.rows()))
+            }
+        });
+    let socket = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+    let (client, connection) = config.connect_raw(socket, NoTls).await.unwrap();
+    tokio::spawn(connection.map(|r| r.unwrap()));
 
+    client.query("SELECT 1", &[]).await.unwrap();
     client
-        .batch_execute("CREATE DOMAIN pg_temp.session_id AS bytea CHECK(octet_length(VALUE) = 16)")
+        .query("SELECT pg_sleep(0.2) FROM generate_series(1, 2)", &[])
+        .await
+        .unwrap();
+    client.execute("SELECT pg_sleep(0.2)", &[]).await.unwrap();
+    client
+        .query_raw_txt("SELECT pg_sleep(0.2)", Vec::<Option<&str>>::new())
+        .await
+        .unwrap()
+        .try_collect::<Vec<_>>()
         .await
         .unwrap();
 
-    let select = client.prepare("SELECT $1::session_id").await.unwrap();
-
-    let ty = &select.params()[0];
-    assert_eq!("session_id", ty.name());
-    assert_eq!(&Kind::Domain(Type::BYTEA), ty.kind());
+    assert_eq!(
+        *slow.lock().unwrap(),
+        [
+            (None, Some(2)),
+            (None, Some(1)),
+            (Some("SELECT pg_sleep(0.2)".to_string()), Some(1)),
+        ]
+    );
 }
 
 #[tokio::test]
-async fn custom_array() {
-    let client = connect("user=postgres").await;
+async fn metrics() {
+    #[derive(Default)]
+    struct Recorder {
+        connects: AtomicU64,
+        prepares: AtomicU64,
+        queries: Mutex<Vec<(Operation, bool)>>,
+        rows: AtomicU64,
+        bytes_sent: AtomicU64,
+        bytes_received: AtomicU64,
+        flushed: AtomicU64,
+        round_trips: AtomicU64,
+    }
 
-    let select = client.prepare("SELECT $1::HSTORE[]").await.unwrap();
+    impl Metrics for Recorder {
+        fn connect(&self, _: Duration, error: Option<&Error>) {
+            assert!(error.is_none());
+            self.connects.fetch_add(1, Ordering::SeqCst);
+        }
 
-    let ty = &select.params()[0];
-    assert_eq!("_hstore", ty.name());
-    match ty.kind() {
-        Kind::Array(ty) => {
-            assert_eq!("hstore", ty.name());
-            assert_eq!(&Kind::Simple, ty.kind());
+        fn prepare(&self, _: Duration, _: Option<&Error>) {
+            self.prepares.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn query(&self, operation: Operation, _: Duration, error: Option<&Error>) {
+            self.queries
+                .lock()
+                .unwrap()
+                .push((operation, error.is_some()));
+        }
+
+        fn rows(&self, _: Operation, rows: u64) {
+            self.rows.fetch_add(rows, Ordering::SeqCst);
+        }
+
+        fn bytes_sent(&self, bytes: u64) {
+            self.bytes_sent.fetch_add(bytes, Ordering::SeqCst);
+        }
+
+        fn bytes_received(&self, bytes: u64) {
+            self.bytes_received.fetch_add(bytes, Ordering::SeqCst);
+        }
+
+        fn flush(&self, bytes: u64) {
+            self.flushed.fetch_add(bytes, Ordering::SeqCst);
+        }
+
+        fn round_trip(&self, _: Duration) {
+            self.round_trips.fetch_add(1, Ordering::SeqCst);
         }
-        _ => panic!("unexpected kind"),
     }
+
+    let recorder = Arc::new(Recorder::default());
+    let mut config = "user=postgres".parse::<Config>().unwrap();
+    config.metrics(recorder.clone());
+    let socket = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+    let (client, connection) = config.connect_raw(socket, NoTls).await.unwrap();
+    let connection = tokio::spawn(connection.map(|r| r.unwrap()));
+
+    client
+        .query("SELECT * FROM generate_series(1, 3)", &[])
+        .await
+        .unwrap();
+    client.execute("SELECT 1 / 0", &[]).await.unwrap_err();
+    client.batch_execute("SELECT 1; SELECT 2").await.unwrap();
+
+    drop(client);
+    connection.await.unwrap();
+
+    assert_eq!(recorder.connects.load(Ordering::SeqCst), 1);
+    assert_eq!(recorder.prepares.load(Ordering::SeqCst), 2);
+    assert_eq!(
+        *recorder.queries.lock().unwrap(),
+        [
+            (Operation::Query, false),
+            (Operation::Execute, true),
+            (Operation::SimpleQuery, false),
+        ]
+    );
+    assert_eq!(recorder.rows.load(Ordering::SeqCst), 4);
+    assert!(recorder.bytes_sent.load(Ordering::SeqCst) > 0);
+    assert!(recorder.bytes_received.load(Ordering::SeqCst) > 0);
+    assert_eq!(
+        recorder.flushed.load(Ordering::SeqCst),
+        recorder.bytes_sent.load(Ordering::SeqCst)
+    );
+    assert_eq!(recorder.round_trips.load(Ordering::SeqCst), 5);
 }
 
 #[tokio::test]
-async fn query_raw_txt() {
-    let client = connect("user=postgres").await;
+async fn resolve_types_disabled() {
+    let mut config = "user=postgres".parse::<Config>().unwrap();
+    config.resolve_types(false);
+    let socket = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+    let (client, connection) = config.connect_raw(socket, NoTls).await.unwrap();
+    tokio::spawn(connection.map(|r| r.unwrap()));
 
-    let rows: Vec<tokio_postgres::Row> = client
-        .query_raw_txt("SELECT 55 * $1", [Some("42")])
-        .await
-        .unwrap()
-        .try_collect()
+    client
+        .batch_execute("CREATE TYPE pg_temp.color AS ENUM ('red', 'blue')")
         .await
         .unwrap();
 
-    assert_eq!(rows.len(), 1);
-    let res: i32 = rows[0].as_text(0).unwrap().unwrap().parse::<i32>().unwrap();
-    assert_eq!(res, 55 * 42);
+    let stmt = client.prepare("SELECT $1::color").await.unwrap();
+    assert_eq!(stmt.params(), &[Type::TEXT]);
+    assert_eq!(stmt.columns()[0].type_(), &Type::TEXT);
 
-    let rows: Vec<tokio_postgres::Row> = client
-        .query_raw_txt("SELECT $1", [Some("42")])
-        .await
-        .unwrap()
-        .try_collect()
+    let row = client
+        .query_one("SELECT 'color'::regtype::oid", &[])
         .await
         .unwrap();
-
-    assert_eq!(rows.len(), 1);
-    assert_eq!(rows[0].get::<_, &str>(0), "42");
-    assert!(rows[0].body_len() > 0);
+    let type_ = client.resolve_type(row.get(0)).await.unwrap();
+    assert_eq!(type_.name(), "color");
 }
 
 #[tokio::test]
-async fn query_raw_txt_nulls() {
+async fn register_types() {
     let client = connect("user=postgres").await;
-
-    let rows: Vec<tokio_postgres::Row> = client
-        .query_raw_txt(
-            "SELECT $1 as str, $2 as n, 'null' as str2, null as n2",
-            [Some("null"), None],
+    client
+        .batch_execute(
+            "DROP TYPE IF EXISTS registered_color;
+             CREATE TYPE registered_color AS ENUM ('red', 'blue')",
         )
         .await
-        .unwrap()
-        .try_collect()
+        .unwrap();
+    let row = client
+        .query_one(
+            "SELECT 'registered_color'::regtype::oid, '_registered_color'::regtype::oid",
+            &[],
+        )
         .await
         .unwrap();
 
-    assert_eq!(rows.len(), 1);
+    let color = Type::new(
+        "registered_color".to_string(),
+        row.get(0),
+        Kind::Enum(vec!["red".to_string(), "blue".to_string()]),
+        "public".to_string(),
+    );
+    let colors = Type::new(
+        "_registered_color".to_string(),
+        row.get(1),
+        Kind::Array(color.clone()),
+        "public".to_string(),
+    );
 
-    let res = rows[0].as_text(0).unwrap();
-    assert_eq!(res, Some("null"));
+    let mut config = "user=postgres".parse::<Config>().unwrap();
+    config.resolve_types(false).register_type(colors.clone());
+    let socket = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+    let (client, connection) = config.connect_raw(socket, NoTls).await.unwrap();
+    tokio::spawn(connection.map(|r| r.unwrap()));
 
-    let res = rows[0].as_text(1).unwrap();
-    assert_eq!(res, None);
+    let stmt = client
+        .prepare("SELECT $1::registered_color, ARRAY['red']::registered_color[]")
+        .await
+        .unwrap();
+    assert_eq!(stmt.params(), &[color.clone()]);
+    assert_eq!(stmt.columns()[0].type_(), &color);
+    assert_eq!(stmt.columns()[1].type_(), &colors);
 
-    let res = rows[0].as_text(2).unwrap();
-    assert_eq!(res, Some("null"));
+    let client = connect("user=postgres resolve_types=0").await;
+    client.register_types(vec![color.clone()]);
+    let stmt = client.prepare("SELECT $1::registered_color").await.unwrap();
+    assert_eq!(stmt.columns()[0].type_(), &color);
 
-    let res = rows[0].as_text(3).unwrap();
-    assert_eq!(res, None);
+    client
+        .batch_execute("DROP TYPE registered_color")
+        .await
+        .unwrap();
 }
 
 #[tokio::test]
-async fn limit_max_backend_message_size() {
-    let client = connect("user=postgres max_backend_message_size=10000").await;
-    let small: Vec<tokio_postgres::Row> = client
-        .query_raw_txt("SELECT REPEAT('a', 20)", [] as [Option<&str>; 0])
-        .await
-        .unwrap()
-        .try_collect()
-        .await
-        .unwrap();
+async fn statement_stats() {
+    let mut config = "user=postgres".parse::<Config>().unwrap();
+    config.statement_stats(true);
+    let socket = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+    let (client, connection) = config.connect_raw(socket, NoTls).await.unwrap();
+    tokio::spawn(connection.map(|r| r.unwrap()));
 
-    assert_eq!(small.len(), 1);
-    assert_eq!(small[0].as_text(0).unwrap().unwrap().len(), 20);
+    let stmt = client.prepare("SELECT $1::INT4").await.unwrap();
+    for i in 0..3 {
+        client.query(&stmt, &[&i]).await.unwrap();
+    }
+    client.batch_execute("SELECT 1").await.unwrap();
+    client.batch_execute("SELECT   2 -- comment").await.unwrap();
+    client.batch_execute("SELECT 1 / 0").await.unwrap_err();
+
+    let mut stats = client.statement_stats();
+    stats.sort_by(|a, b| a.query().cmp(b.query()));
+    assert_eq!(stats.len(), 3);
+
+    assert_eq!(stats[0].query(), "SELECT $1::INT4");
+    assert_eq!(stats[0].calls(), 3);
+    assert_eq!(stats[0].errors(), 0);
+    assert_eq!(stats[0].rows(), 3);
+    assert!(stats[0].p99_time() <= stats[0].max_time());
+    assert!(stats[0].mean_time() <= stats[0].max_time());
+
+    assert_eq!(stats[1].query(), "SELECT ?");
+    assert_eq!(stats[1].calls(), 2);
+
+    assert_eq!(stats[2].query(), "SELECT ? / ?");
+    assert_eq!(stats[2].calls(), 1);
+    assert_eq!(stats[2].errors(), 1);
+
+    client.reset_statement_stats();
+    assert!(client.statement_stats().is_empty());
+}
 
-    let large: Result<Vec<tokio_postgres::Row>, Error> = client
-        .query_raw_txt("SELECT REPEAT('a', 2000000)", [] as [Option<&str>; 0])
-        .await
-        .unwrap()
-        .try_collect()
-        .await;
+#[tokio::test]
+async fn wire_stats() {
+    let (client, mut connection) = connect_raw("user=postgres").await.unwrap();
 
-    assert!(large.is_err());
+    let stats = connection.wire_stats().clone();
+    assert_eq!(stats.messages_sent(0), 1);
+    assert!(stats.messages_received(b'R') >= 1);
+    assert_eq!(stats.messages_received(b'Z'), 1);
+
+    let query = client.simple_query("SELECT 1 UNION ALL SELECT 2");
+    pin_mut!(query);
+    future::poll_fn(|cx| {
+        let _ = connection.poll_message(cx);
+        query.as_mut().poll(cx)
+    })
+    .await
+    .unwrap();
+
+    let after = connection.wire_stats();
+    assert_eq!(after.messages_sent(b'Q'), 1);
+    assert_eq!(after.messages_received(b'T'), 1);
+    assert_eq!(after.messages_received(b'D'), 2);
+    assert_eq!(after.messages_received(b'C'), 1);
+    assert_eq!(after.messages_received(b'Z'), 2);
+    assert!(after.bytes_sent() > stats.bytes_sent());
+    assert!(after.bytes_received() > stats.bytes_received());
+    assert_eq!(after.sent().map(|(_, n)| n).sum::<u64>(), 2);
+    assert_eq!(after.flushes(), stats.flushes() + 1);
+    assert_eq!(after.round_trips().count(), 1);
+    assert_eq!(
+        after.round_trips().buckets().map(|(_, n)| n).sum::<u64>(),
+        1
+    );
+    assert!(after.round_trips().sum() > Duration::ZERO);
 }
 
 #[tokio::test]
-async fn command_tag() {
-    let client = connect("user=postgres").await;
-
-    let row_stream = client
-        .query_raw_txt("select unnest('{1,2,3}'::int[]);", [] as [Option<&str>; 0])
-        .await
+async fn max_inflight_requests() {
+    let config = "user=postgres max_inflight_requests=0"
+        .parse::<Config>()
         .unwrap();
+    assert_eq!(config.get_max_inflight_requests(), None);
+
+    let client = connect("user=postgres max_inflight_requests=2").await;
+    let statement = client.prepare("SELECT pg_sleep(0.1)").await.unwrap();
+    assert_eq!(client.pending_requests(), 0);
+
+    let completed = Mutex::new(vec![]);
+    let queries = (0..5).map(|i| {
+        let (client, statement, completed) = (&client, &statement, &completed);
+        async move {
+            client.query(statement, &[]).await.unwrap();
+            completed.lock().unwrap().push(i);
+        }
+    });
+    let pending = async {
+        time::sleep(Duration::from_millis(50)).await;
+        client.pending_requests()
+    };
+    let (_, pending) = join!(future::join_all(queries), pending);
+
+    assert_eq!(pending, 2);
+    assert_eq!(*completed.lock().unwrap(), [0, 1, 2, 3, 4]);
+    assert_eq!(client.pending_requests(), 0);
+}
 
-    pin_mut!(row_stream);
-
-    let mut rows: Vec<tokio_postgres::Row> = Vec::new();
-    while let Some(row) = row_stream.next().await {
-        rows.push(row.unwrap());
-    }
-
-    assert_eq!(row_stream.command_tag(), Some("SELECT 3".to_string()));
+#[tokio::test]
+async fn keepalive_query_interval() {
+    let socket = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+    let mut config = "user=postgres".parse::<Config>().unwrap();
+    config.keepalive_query_interval(Duration::from_millis(20));
+    let (client, mut connection) = config.connect_raw(socket, NoTls).await.unwrap();
+
+    let idle = time::sleep(Duration::from_millis(150));
+    pin_mut!(idle);
+    future::poll_fn(|cx| {
+        let _ = connection.poll_message(cx);
+        idle.as_mut().poll(cx)
+    })
+    .await;
+
+    assert!(connection.wire_stats().messages_sent(b'S') >= 2);
+    assert!(client.is_healthy());
+
+    let query = client.query_one("SELECT 1", &[]);
+    pin_mut!(query);
+    let row = future::poll_fn(|cx| {
+        let _ = connection.poll_message(cx);
+        query.as_mut().poll(cx)
+    })
+    .await
+    .unwrap();
+    assert_eq!(row.get::<_, i32>(0), 1);
+
+    drop(connection);
+    assert!(!client.is_healthy());
 }
 
 #[tokio::test]
-async fn ready_for_query() {
+async fn pipeline() {
     let client = connect("user=postgres").await;
 
-    let row_stream = client
-        .query_raw_txt("START TRANSACTION", [] as [Option<&str>; 0])
+    client
+        .batch_execute("CREATE TEMPORARY TABLE foo (id INT PRIMARY KEY, name TEXT)")
+        .await
+        .unwrap();
+    let select = client
+        .prepare("SELECT name FROM foo ORDER BY id")
         .await
         .unwrap();
 
-    pin_mut!(row_stream);
-    while row_stream.next().await.is_none() {}
+    let mut pipeline = client.pipeline();
+    pipeline
+        .execute(
+            "INSERT INTO foo (id, name) VALUES ($1, $2)",
+            &[&1i32, &"joe"],
+        )
+        .execute(
+            "INSERT INTO foo (id, name) VALUES ($1, $2)",
+            &[&1i32, &"bob"],
+        )
+        .execute(
+            "INSERT INTO foo (id, name) VALUES ($1, $2)",
+            &[&2i32, &"alice"],
+        )
+        .query(&select, &[])
+        .query("SELECT $1::INT8 + 1", &[&41i64]);
+    assert_eq!(pipeline.len(), 5);
+
+    let results = pipeline.run().await;
+    assert_eq!(results.len(), 5);
+    assert_eq!(results[0].as_ref().unwrap().rows_affected(), Some(1));
+    assert_eq!(
+        results[1].as_ref().unwrap_err().code(),
+        Some(&SqlState::UNIQUE_VIOLATION)
+    );
+    assert_eq!(results[2].as_ref().unwrap().rows_affected(), Some(1));
+    let rows = results[3].as_ref().unwrap().rows().unwrap();
+    assert_eq!(
+        rows.iter().map(|r| r.get(0)).collect::<Vec<&str>>(),
+        ["joe", "alice"]
+    );
+    let rows = results[4].as_ref().unwrap().rows().unwrap();
+    assert_eq!(rows[0].get::<_, i64>(0), 42);
+}
+
+#[tokio::test]
+async fn pipeline_batch() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute("CREATE TEMPORARY TABLE foo (id INT PRIMARY KEY, name TEXT)")
+        .await
+        .unwrap();
+    let insert = client
+        .prepare("INSERT INTO foo (id, name) VALUES ($1, $2)")
+        .await
+        .unwrap();
+
+    let mut pipeline = client.pipeline();
+    pipeline
+        .execute(&insert, &[&1i32, &"joe"])
+        .execute(&insert, &[&1i32, &"bob"])
+        .execute(&insert, &[&2i32, &"alice"]);
+    let err = pipeline.run_batch().await.unwrap_err();
+    assert_eq!(err.code(), Some(&SqlState::UNIQUE_VIOLATION));
+
+    let mut pipeline = client.pipeline();
+    pipeline
+        .execute(&insert, &[&1i32, &"joe"])
+        .execute(&insert, &[&2i32, &"alice"])
+        .query("SELECT name FROM foo ORDER BY id", &[])
+        .execute("SELECT 1", &[]);
+    let results = pipeline.run_batch().await.unwrap();
+    assert_eq!(results.len(), 4);
+    assert_eq!(results[0].rows_affected(), Some(1));
+    assert_eq!(results[1].rows_affected(), Some(1));
+    let rows = results[2].rows().unwrap();
+    assert_eq!(
+        rows.iter().map(|r| r.get(0)).collect::<Vec<&str>>(),
+        ["joe", "alice"]
+    );
+    assert_eq!(results[3].rows_affected(), Some(1));
+    assert_eq!(client.transaction_status(), TransactionStatus::Idle);
+}
+
+#[tokio::test]
+async fn lifecycle_callback() {
+    let events = Arc::new(Mutex::new(vec![]));
+    let mut config = "host=localhost user=postgres".parse::<Config>().unwrap();
+    config.lifecycle_callback({
+        let events = events.clone();
+        move |event| {
+            let event = match event {
+                LifecycleEvent::Closed(Some(e)) => format!("Closed({})", e.code().unwrap().code()),
+                event => format!("{:?}", event),
+            };
+            events.lock().unwrap().push(event);
+        }
+    });
+
+    let socket = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+    let (client, connection) = config.connect_raw(socket, NoTls).await.unwrap();
+    let connection = tokio::spawn(connection);
+    client.batch_execute("SELECT 1").await.unwrap();
+    drop(client);
+    connection.await.unwrap().unwrap();
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            "Connecting { host: Some(\"localhost\"), port: None }",
+            "Authenticated(None)",
+            "Ready",
+            "Terminating",
+            "Closed(None)",
+        ]
+    );
+
+    events.lock().unwrap().clear();
+    config.user("pass_user").password("foo");
+    let socket = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+    config.connect_raw(socket, NoTls).await.err().unwrap();
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            "Connecting { host: Some(\"localhost\"), port: None }",
+            "Closed(28P01)",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn server_parameters() {
+    let client = connect("user=postgres application_name=params").await;
+
+    let version = client.simple_query("SHOW server_version").await.unwrap();
+    match &version[0] {
+        SimpleQueryMessage::Row(row) => assert_eq!(
+            client.server_version().as_ref().map(ServerVersion::as_str),
+            row.get(0)
+        ),
+        _ => panic!("unexpected message"),
+    }
+    assert!(client.server_version().unwrap().major() >= 9);
+    let row = client
+        .query_one("SELECT pg_backend_pid()", &[])
+        .await
+        .unwrap();
+    assert_eq!(client.backend_pid(), row.get::<_, i32>(0));
+    assert_eq!(client.client_encoding().as_deref(), Some("UTF8"));
+    assert_eq!(client.application_name().as_deref(), Some("params"));
+    assert_eq!(client.is_superuser(), Some(true));
+    assert_eq!(client.standard_conforming_strings(), Some(true));
+    assert_eq!(client.parameter("work_mem"), None);
+
+    client
+        .batch_execute("SET TimeZone = 'America/New_York'")
+        .await
+        .unwrap();
+    assert_eq!(client.timezone().as_deref(), Some("America/New_York"));
+    client
+        .batch_execute("SET DateStyle = 'SQL, DMY'")
+        .await
+        .unwrap();
+    assert_eq!(client.date_style().as_deref(), Some("SQL, DMY"));
+}
+
+#[tokio::test]
+async fn parameter_changes() {
+    let (client, connection) = connect_raw("user=postgres").await.unwrap();
+    let changes = client.parameter_changes();
+    let connection = tokio::spawn(connection.map(|r| r.unwrap()));
+
+    client
+        .batch_execute("SET TimeZone = 'Europe/Paris'; SET application_name = 'changes'")
+        .await
+        .unwrap();
+    drop(client);
+    connection.await.unwrap();
+
+    // The server reports the changes of a query once it completes, in an order of its own.
+    let mut changes = changes.collect::<Vec<_>>().await;
+    changes.sort();
+    assert_eq!(
+        changes,
+        [
+            ("TimeZone".to_string(), "Europe/Paris".to_string()),
+            ("application_name".to_string(), "changes".to_string()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn generic_describe_and_copy() {
+    async fn describe_and_copy<C: GenericClient>(client: &C) {
+        let description = client
+            .describe("SELECT id, name FROM foo WHERE id = $1")
+            .await
+            .unwrap();
+        assert_eq!(description.params(), &[Type::INT4]);
+        let columns = description.columns();
+        assert_eq!(columns.len(), 2);
+        assert_eq!(columns[0].name(), "id");
+        assert_eq!(columns[0].type_(), &Type::INT4);
+        assert_eq!(columns[1].name(), "name");
+        assert_eq!(columns[1].type_(), &Type::TEXT);
+        assert!(columns[1].table_oid().is_some());
+
+        let sink = client
+            .copy_in("COPY foo (id, name) FROM STDIN")
+            .await
+            .unwrap();
+        pin_mut!(sink);
+        sink.send(Bytes::from_static(b"1\tjim\n")).await.unwrap();
+        sink.finish().await.unwrap();
+
+        let data = client
+            .copy_out("COPY foo (id, name) TO STDOUT")
+            .await
+            .unwrap()
+            .try_fold(BytesMut::new(), |mut buf, chunk| async move {
+                buf.extend_from_slice(&chunk);
+                Ok(buf)
+            })
+            .await
+            .unwrap();
+        assert_eq!(&data[..], b"1\tjim\n");
+    }
+
+    let mut client = connect("user=postgres").await;
+    client
+        .batch_execute("CREATE TEMPORARY TABLE foo (id INT, name TEXT)")
+        .await
+        .unwrap();
+
+    let transaction = client.transaction().await.unwrap();
+    describe_and_copy(&transaction).await;
+    transaction.rollback().await.unwrap();
+
+    describe_and_copy(&client).await;
+}
+
+#[tokio::test]
+async fn describe() {
+    let client = connect("user=postgres").await;
+    client
+        .batch_execute("CREATE TEMPORARY TABLE foo (id INT, name TEXT, tags TEXT[])")
+        .await
+        .unwrap();
+
+    let description = client
+        .describe("INSERT INTO foo (id, name) VALUES ($1, $2) RETURNING tags, id + 1 AS next")
+        .await
+        .unwrap();
+    assert_eq!(description.params(), &[Type::INT4, Type::TEXT]);
+    let columns = description.columns();
+    assert_eq!(columns.len(), 2);
+    assert_eq!(columns[0].name(), "tags");
+    assert_eq!(columns[0].type_(), &Type::TEXT_ARRAY);
+    assert_eq!(columns[0].column_id(), Some(3));
+    assert!(columns[0].table_oid().is_some());
+    assert_eq!(columns[1].name(), "next");
+    assert_eq!(columns[1].column_id(), None);
+    assert_eq!(columns[1].table_oid(), None);
+
+    // The query is never executed.
+    let count = client
+        .query_one("SELECT COUNT(*) FROM foo", &[])
+        .await
+        .unwrap()
+        .get::<_, i64>(0);
+    assert_eq!(count, 0);
+
+    let description = client
+        .describe_typed("SELECT $1, $2::TEXT", &[Type::INT8])
+        .await
+        .unwrap();
+    assert_eq!(description.params(), &[Type::INT8, Type::TEXT]);
+    assert_eq!(description.columns()[0].type_(), &Type::INT8);
+
+    client.describe("SELECT * FROM missing").await.unwrap_err();
+}
+
+#[tokio::test]
+async fn stale_plan_reprepared() {
+    let mut client = connect("user=postgres").await;
+
+    client
+        .batch_execute(
+            "CREATE TEMPORARY TABLE foo (id INT);
+            INSERT INTO foo (id) VALUES (1);",
+        )
+        .await
+        .unwrap();
+    let select = client.prepare("SELECT * FROM foo").await.unwrap();
+    let update = client
+        .prepare("UPDATE foo SET id = $1 RETURNING *")
+        .await
+        .unwrap();
+    assert_eq!(client.query(&select, &[]).await.unwrap()[0].len(), 1);
+    assert_eq!(client.execute(&update, &[&1i32]).await.unwrap(), 1);
+
+    client
+        .batch_execute("ALTER TABLE foo ADD COLUMN name TEXT DEFAULT 'steven'")
+        .await
+        .unwrap();
+
+    let rows = client.query(&select, &[]).await.unwrap();
+    assert_eq!(rows[0].len(), 2);
+    assert_eq!(rows[0].get::<_, &str>("name"), "steven");
+    assert_eq!(client.execute(&update, &[&2i32]).await.unwrap(), 1);
+
+    client
+        .batch_execute("ALTER TABLE foo ADD COLUMN age INT")
+        .await
+        .unwrap();
+
+    // The failed statement aborted the transaction, so it can't be retried.
+    let transaction = client.transaction().await.unwrap();
+    let err = transaction.query(&select, &[]).await.unwrap_err();
+    assert_eq!(err.code(), Some(&SqlState::FEATURE_NOT_SUPPORTED));
+}
+
+#[tokio::test]
+async fn statement_refresh() {
+    let mut client = connect("user=postgres").await;
+
+    client
+        .batch_execute(
+            "CREATE TEMPORARY TABLE foo (id INT);
+            INSERT INTO foo (id) VALUES (1);",
+        )
+        .await
+        .unwrap();
+    let mut select = client.prepare("SELECT * FROM foo").await.unwrap();
+    let old = select.clone();
+    let mut unnamed = client
+        .prepare_unnamed("SELECT * FROM foo WHERE id = $1", &[])
+        .await
+        .unwrap();
+    assert_eq!(select.sql(), "SELECT * FROM foo");
+    assert_eq!(select.columns().len(), 1);
+
+    client
+        .batch_execute("ALTER TABLE foo ADD COLUMN name TEXT DEFAULT 'steven'")
+        .await
+        .unwrap();
+
+    select.refresh(&client).await.unwrap();
+    assert_eq!(select.sql(), "SELECT * FROM foo");
+    assert_eq!(select.columns().len(), 2);
+    assert_eq!(select.columns()[1].name(), "name");
+    assert_eq!(old.columns().len(), 1);
+
+    let transaction = client.transaction().await.unwrap();
+    let row = transaction.query_one(&select, &[]).await.unwrap();
+    assert_eq!(row.get::<_, &str>("name"), "steven");
+    let err = transaction.query(&old, &[]).await.unwrap_err();
+    assert_eq!(err.code(), Some(&SqlState::FEATURE_NOT_SUPPORTED));
+    transaction.rollback().await.unwrap();
+
+    unnamed.refresh(&client).await.unwrap();
+    assert_eq!(unnamed.params(), &[Type::INT4]);
+    assert_eq!(unnamed.columns().len(), 2);
+    let row = client.query_one(&unnamed, &[&1i32]).await.unwrap();
+    assert_eq!(row.get::<_, &str>("name"), "steven");
+
+    // Refreshing a statement evicts the cached statement of its query.
+    let rows = client.query_cached("SELECT * FROM foo", &[]).await.unwrap();
+    assert_eq!(rows[0].len(), 2);
+    client
+        .batch_execute("ALTER TABLE foo ADD COLUMN age INT DEFAULT 30")
+        .await
+        .unwrap();
+    select.refresh(&client).await.unwrap();
+    assert_eq!(select.columns().len(), 3);
+    let transaction = client.transaction().await.unwrap();
+    let rows = transaction
+        .query_cached("SELECT * FROM foo", &[])
+        .await
+        .unwrap();
+    assert_eq!(rows[0].get::<_, i32>("age"), 30);
+    transaction.rollback().await.unwrap();
+
+    let other = connect("user=postgres").await;
+    let err = select.refresh(&other).await.unwrap_err();
+    assert!(err.to_string().contains("another connection"), "{}", err);
+}
+
+#[tokio::test]
+async fn prepare_unnamed() {
+    let client = connect("user=postgres").await;
+
+    let first = client
+        .prepare_unnamed("SELECT $1::TEXT", &[Type::TEXT])
+        .await
+        .unwrap();
+    assert_eq!(first.params(), &[Type::TEXT]);
+    for value in ["a", "b"] {
+        let row = client.query_one(&first, &[&value]).await.unwrap();
+        assert_eq!(row.get::<_, &str>(0), value);
+    }
+
+    let rows = client
+        .query("SELECT name FROM pg_prepared_statements", &[])
+        .await
+        .unwrap();
+    assert!(rows.is_empty());
+
+    let second = client.prepare_unnamed("SELECT 2", &[]).await.unwrap();
+    let err = client.query(&first, &[&"c"]).await.unwrap_err();
+    assert!(err.to_string().contains("unnamed statement was replaced"));
+    assert_eq!(
+        client
+            .query_one(&second, &[])
+            .await
+            .unwrap()
+            .get::<_, i32>(0),
+        2
+    );
+
+    client.simple_query("SELECT 3").await.unwrap();
+    assert!(client.query(&second, &[]).await.is_err());
+}
+
+#[tokio::test]
+async fn session_reset_reprepared() {
+    let mut client = connect("user=postgres").await;
+
+    let select = client.prepare("SELECT $1::INT").await.unwrap();
+    client.batch_execute("DISCARD ALL").await.unwrap();
+    let row = client.query_one(&select, &[&1i32]).await.unwrap();
+    assert_eq!(row.get::<_, i32>(0), 1);
+
+    // The statement is only prepared again once per deallocation.
+    let prepared = "SELECT array_agg(name) FROM pg_prepared_statements";
+    let names = client
+        .query_one(prepared, &[])
+        .await
+        .unwrap()
+        .get::<_, Vec<String>>(0);
+    assert_eq!(names.len(), 1);
+    let row = client.query_one(&select, &[&1i32]).await.unwrap();
+    assert_eq!(row.get::<_, i32>(0), 1);
+    let row = client.query_one(prepared, &[]).await.unwrap();
+    assert_eq!(row.get::<_, Vec<String>>(0), names);
+
+    let select = client.prepare("SELECT $1::INT").await.unwrap();
+    client.batch_execute("DEALLOCATE ALL").await.unwrap();
+    let transaction = client.transaction().await.unwrap();
+    let row = transaction.query_one(&select, &[&2i32]).await.unwrap();
+    assert_eq!(row.get::<_, i32>(0), 2);
+    transaction.commit().await.unwrap();
+
+    // Deallocations the client doesn't see are detected by the error they cause.
+    let select = client.prepare("SELECT $1::INT + 0").await.unwrap();
+    let name = client
+        .query_one(
+            "SELECT name FROM pg_prepared_statements WHERE statement = 'SELECT $1::INT + 0'",
+            &[],
+        )
+        .await
+        .unwrap()
+        .get::<_, String>(0);
+    client
+        .batch_execute(&format!("DEALLOCATE {}", name))
+        .await
+        .unwrap();
+    let row = client.query_one(&select, &[&3i32]).await.unwrap();
+    assert_eq!(row.get::<_, i32>(0), 3);
+}
+
+#[tokio::test]
+async fn query_cached() {
+    async fn prepared(client: &Client) -> i64 {
+        client
+            .query_one(
+                "SELECT COUNT(*) FROM pg_prepared_statements WHERE statement LIKE 'SELECT % FROM cached%'",
+                &[],
+            )
+            .await
+            .unwrap()
+            .get(0)
+    }
+
+    let socket = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+    let mut config = "user=postgres".parse::<Config>().unwrap();
+    config.statement_cache_capacity(1);
+    let (client, connection) = config.connect_raw(socket, NoTls).await.unwrap();
+    tokio::spawn(connection.map(|r| r.unwrap()));
+
+    client
+        .batch_execute("CREATE TEMPORARY TABLE cached (id INT); INSERT INTO cached VALUES (1)")
+        .await
+        .unwrap();
+
+    for _ in 0..2 {
+        let rows = client
+            .query_cached("SELECT * FROM cached", &[])
+            .await
+            .unwrap();
+        assert_eq!(rows[0].get::<_, i32>(0), 1);
+    }
+    assert_eq!(prepared(&client).await, 1);
+
+    // Statements whose result type changed are prepared again.
+    client
+        .batch_execute("ALTER TABLE cached ADD COLUMN name TEXT DEFAULT 'alice'")
+        .await
+        .unwrap();
+    let rows = client
+        .query_cached("SELECT * FROM cached", &[])
+        .await
+        .unwrap();
+    assert_eq!(rows[0].get::<_, &str>(1), "alice");
+    // Rows keep their statement alive.
+    drop(rows);
+
+    // The least recently used statement is evicted and closed.
+    let rows = client
+        .query_cached("SELECT id FROM cached WHERE id = $1", &[&1i32])
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(prepared(&client).await, 1);
+    drop(rows);
+
+    client.clear_statement_cache();
+    assert_eq!(prepared(&client).await, 0);
+}
+
+#[tokio::test]
+async fn transaction_status() {
+    let client = connect("user=postgres").await;
+    assert_eq!(client.transaction_status(), TransactionStatus::Idle);
+
+    client.batch_execute("BEGIN").await.unwrap();
+    assert_eq!(
+        client.transaction_status(),
+        TransactionStatus::InTransaction
+    );
+
+    client.batch_execute("SELECT 1/0").await.unwrap_err();
+    // Errors are returned before the server has finished processing the request.
+    client.batch_execute("").await.unwrap();
+    assert_eq!(client.transaction_status(), TransactionStatus::Failed);
+
+    client.batch_execute("ROLLBACK").await.unwrap();
+    assert_eq!(client.transaction_status(), TransactionStatus::Idle);
+}
+
+#[tokio::test]
+async fn get_by_name() {
+    let client = connect("user=postgres").await;
+
+    let row = client
+        .query_one(
+            r#"SELECT 1::INT4 AS a, 2::INT4 AS "A", 3::INT4 AS a, 4::INT4 AS "Mixed""#,
+            &[],
+        )
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, i32>("a"), 1);
+    assert_eq!(row.get::<_, i32>("A"), 2);
+    assert_eq!(row.get::<_, i32>("mixed"), 4);
+    assert_eq!(row.get::<_, i32>("MIXED"), 4);
+    row.try_get::<_, i32>("missing").unwrap_err();
+
+    assert_eq!(row.try_get_by_index::<i32>(1).unwrap(), 2);
+    row.try_get_by_index::<i32>(4).unwrap_err();
+    row.try_get_by_index::<&str>(0).unwrap_err();
+}
+
+#[cfg(feature = "migrations")]
+#[tokio::test]
+async fn migrations() {
+    use tokio_postgres::migrations::Migrations;
+
+    let mut client = connect("user=postgres").await;
+    client
+        .batch_execute(
+            "DROP TABLE IF EXISTS migrations_test_log;
+             DROP TABLE IF EXISTS migrations_test_users",
+        )
+        .await
+        .unwrap();
+
+    let mut migrations = Migrations::new();
+    migrations
+        .table("migrations_test_log")
+        .sql(
+            2,
+            "add admin",
+            "INSERT INTO migrations_test_users (name) VALUES ('admin')",
+        )
+        .sql(
+            1,
+            "create users",
+            "CREATE TABLE migrations_test_users (id SERIAL, name TEXT NOT NULL)",
+        );
+
+    // Concurrent runners apply each migration once.
+    let mut other = connect("user=postgres").await;
+    let (applied, other_applied) =
+        try_join!(migrations.run(&mut client), migrations.run(&mut other)).unwrap();
+    let mut all = applied.into_iter().chain(other_applied).collect::<Vec<_>>();
+    all.sort_unstable();
+    assert_eq!(all, [1, 2]);
+
+    migrations
+        .function(3, "rename admin", |transaction| {
+            Box::pin(async move {
+                transaction
+                    .execute("UPDATE migrations_test_users SET name = $1", &[&"root"])
+                    .await?;
+                Ok(())
+            })
+        })
+        .sql(4, "broken", "SELECT 1 / 0");
+    let err = migrations.run(&mut client).await.unwrap_err();
+    assert_eq!(err.code(), Some(&SqlState::DIVISION_BY_ZERO));
+
+    let versions = client
+        .query(
+            "SELECT version, name FROM migrations_test_log ORDER BY version",
+            &[],
+        )
+        .await
+        .unwrap();
+    assert_eq!(versions.len(), 3);
+    assert_eq!(versions[2].get::<_, &str>(1), "rename admin");
+    let name = client
+        .query_scalar::<String>("SELECT name FROM migrations_test_users", &[])
+        .await
+        .unwrap();
+    assert_eq!(name, "root");
+
+    // The lock was released despite the failure.
+    assert!(Migrations::new()
+        .table("migrations_test_log")
+        .run(&mut other)
+        .await
+        .unwrap()
+        .is_empty());
+
+    client
+        .batch_execute("DROP TABLE migrations_test_log; DROP TABLE migrations_test_users")
+        .await
+        .unwrap();
+}
+
+#[cfg(feature = "with-serde_json-1")]
+#[tokio::test]
+async fn explain() {
+    use tokio_postgres::explain::ExplainOptions;
+
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute(
+            "CREATE TEMPORARY TABLE foo (id SERIAL PRIMARY KEY, name TEXT);
+             INSERT INTO foo (name) SELECT 'name' || i FROM generate_series(1, 10) i;",
+        )
+        .await
+        .unwrap();
+
+    let plan = client
+        .explain(
+            "SELECT * FROM foo WHERE name = $1",
+            &[&"name1"],
+            ExplainOptions::new(),
+        )
+        .await
+        .unwrap();
+    let root = plan.root();
+    assert_eq!(root.node_type(), "Seq Scan");
+    assert_eq!(root.relation_name(), Some("foo"));
+    assert!(root.total_cost() >= root.startup_cost());
+    assert!(root.property("Filter").is_some());
+    assert_eq!(root.actual_rows(), None);
+    assert_eq!(plan.execution_time(), None);
+
+    let plan = client
+        .explain(
+            "SELECT count(*) FROM foo",
+            &[],
+            ExplainOptions::new().analyze(true).buffers(true),
+        )
+        .await
+        .unwrap();
+    let root = plan.root();
+    assert_eq!(root.node_type(), "Aggregate");
+    assert_eq!(root.actual_rows(), Some(1.));
+    assert_eq!(root.actual_loops(), Some(1.));
+    assert!(root.actual_total_time().is_some());
+    assert!(plan.execution_time().is_some());
+    let scan = &root.children()[0];
+    assert_eq!(scan.node_type(), "Seq Scan");
+    assert_eq!(scan.actual_rows(), Some(10.));
+    assert!(scan.buffers().is_some());
+    assert_eq!(root.iter().count(), 2);
+}
+
+#[cfg(feature = "arrow")]
+#[tokio::test]
+async fn query_arrow() {
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Date32Type, Int32Type, TimestampMicrosecondType};
+    use arrow_array::Array;
+    use arrow_schema::{DataType, TimeUnit};
+
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute(
+            "CREATE TEMPORARY TABLE foo (
+                id INT4 NOT NULL,
+                name TEXT,
+                price NUMERIC,
+                data BYTEA,
+                day DATE,
+                at TIMESTAMP
+            );
+            INSERT INTO foo VALUES
+                (1, 'alice', 1.50, '\\x0102', '1970-01-02', '2000-01-01 00:00:01'),
+                (2, NULL, NULL, NULL, NULL, NULL),
+                (3, 'bob', -3, '', '1999-12-31', '1970-01-01');",
+        )
+        .await
+        .unwrap();
+
+    let stream = client
+        .query_arrow("SELECT * FROM foo WHERE id > $1 ORDER BY id", &[&0i32], 2)
+        .await
+        .unwrap();
+    let schema = stream.schema();
+    assert_eq!(schema.field(0).name(), "id");
+    assert_eq!(schema.field(0).data_type(), &DataType::Int32);
+    assert_eq!(schema.field(2).data_type(), &DataType::Utf8);
+    assert_eq!(
+        schema.field(5).data_type(),
+        &DataType::Timestamp(TimeUnit::Microsecond, None)
+    );
+
+    let batches = stream.try_collect::<Vec<_>>().await.unwrap();
+    assert_eq!(
+        batches.iter().map(|b| b.num_rows()).collect::<Vec<_>>(),
+        [2, 1]
+    );
+
+    let batch = &batches[0];
+    assert_eq!(
+        batch.column(0).as_primitive::<Int32Type>().values(),
+        &[1, 2]
+    );
+    let names = batch.column(1).as_string::<i32>();
+    assert_eq!(names.value(0), "alice");
+    assert!(names.is_null(1));
+    assert_eq!(batch.column(2).as_string::<i32>().value(0), "1.50");
+    assert_eq!(batch.column(3).as_binary::<i32>().value(0), [1, 2]);
+    assert_eq!(batch.column(4).as_primitive::<Date32Type>().value(0), 1);
+    assert_eq!(
+        batch
+            .column(5)
+            .as_primitive::<TimestampMicrosecondType>()
+            .value(0),
+        946_684_801_000_000
+    );
+
+    let batch = &batches[1];
+    assert_eq!(batch.column(2).as_string::<i32>().value(0), "-3");
+    assert_eq!(
+        batch.column(4).as_primitive::<Date32Type>().value(0),
+        10_956
+    );
+    assert_eq!(
+        batch
+            .column(5)
+            .as_primitive::<TimestampMicrosecondType>()
+            .value(0),
+        0
+    );
+
+    let err = client
+        .query_arrow("SELECT ARRAY[1]", &[], 10)
+        .await
+        .err()
+        .unwrap();
+    assert!(err
+        .to_string()
+        .contains("not supported by the Arrow conversions"));
+
+    let stream = client
+        .query_arrow("SELECT 'infinity'::DATE", &[], 10)
+        .await
+        .unwrap();
+    let err = stream.try_collect::<Vec<_>>().await.unwrap_err();
+    assert!(err.to_string().contains("infinite dates"));
+}
+
+#[tokio::test]
+async fn sqlcommenter() {
+    let mut config = "user=postgres".parse::<Config>().unwrap();
+    config.sqlcommenter(|| {
+        vec![
+            (
+                "traceparent".to_string(),
+                "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01".to_string(),
+            ),
+            ("route".to_string(), "/users/{id}".to_string()),
+        ]
+    });
+    let socket = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+    let (client, connection) = config.connect_raw(socket, NoTls).await.unwrap();
+    tokio::spawn(connection.map(|r| r.unwrap()));
+
+    let messages = client.simple_query("SELECT current_query()").await.unwrap();
+    match &messages[0] {
+        SimpleQueryMessage::Row(row) => assert_eq!(
+            row.get(0),
+            Some("SELECT current_query() /*route='%2Fusers%2F%7Bid%7D',traceparent='00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01'*/"),
+        ),
+        _ => panic!("unexpected message"),
+    }
+
+    let messages = client
+        .simple_query("SELECT current_query() /* existing */;")
+        .await
+        .unwrap();
+    match &messages[0] {
+        SimpleQueryMessage::Row(row) => {
+            assert_eq!(row.get(0), Some("SELECT current_query() /* existing */;"))
+        }
+        _ => panic!("unexpected message"),
+    }
+}
+
+#[tokio::test]
+async fn interceptor() {
+    type Recorded = (Operation, Option<String>, Option<u64>, bool);
+
+    #[derive(Default)]
+    struct Guard {
+        outcomes: Mutex<Vec<Recorded>>,
+    }
+
+    impl Interceptor for Guard {
+        fn before_sql(
+            &self,
+            _: Operation,
+            sql: &mut String,
+        ) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+            if sql.contains("forbidden") {
+                return Err("forbidden table".into());
+            }
+            *sql = sql.replace("$tenant", "tenant_a");
+            Ok(())
+        }
+
+        fn before_execute(
+            &self,
+            _: Operation,
+            _: &Statement,
+            params: &mut Params<'_>,
+        ) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+            if !params.is_empty() {
+                params.set(0, Box::new(42i32));
+            }
+            Ok(())
+        }
+
+        fn after(&self, outcome: &Outcome<'_>) {
+            self.outcomes.lock().unwrap().push((
+                outcome.operation(),
+                outcome.sql().map(str::to_string),
+                outcome.rows(),
+                outcome.error().is_some(),
+            ));
+        }
+    }
+
+    let guard = Arc::new(Guard::default());
+    let mut client = connect("user=postgres").await;
+    client.add_interceptor(guard.clone());
+
+    let row = client
+        .query_one("SELECT $1::INT4, '$tenant'", &[&1i32])
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, i32>(0), 42);
+    assert_eq!(row.get::<_, &str>(1), "tenant_a");
+
+    let err = client.prepare("SELECT * FROM forbidden").await.unwrap_err();
+    assert!(err.to_string().contains("forbidden table"));
+
+    client.execute("SELEC 1", &[]).await.unwrap_err();
+
+    let outcomes = guard.outcomes.lock().unwrap();
+    assert_eq!(
+        *outcomes,
+        [
+            (
+                Operation::Prepare,
+                Some("SELECT $1::INT4, 'tenant_a'".to_string()),
+                None,
+                false
+            ),
+            (Operation::Query, None, Some(1), false),
+            (Operation::Prepare, Some("SELEC 1".to_string()), None, true),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn pipelined_prepare() {
+    let client = connect("user=postgres").await;
+
+    let prepare1 = client.prepare("SELECT $1::HSTORE[]");
+    let prepare2 = client.prepare("SELECT $1::BIGINT");
+
+    let (statement1, statement2) = try_join!(prepare1, prepare2).unwrap();
+
+    assert_eq!(statement1.params()[0].name(), "_hstore");
+    assert_eq!(statement1.columns()[0].type_().name(), "_hstore");
+
+    assert_eq!(statement2.params()[0], Type::INT8);
+    assert_eq!(statement2.columns()[0].type_(), &Type::INT8);
+}
+
+#[tokio::test]
+async fn statement_sql() {
+    struct Rewrite;
+
+    impl Interceptor for Rewrite {
+        fn before_sql(
+            &self,
+            _: Operation,
+            sql: &mut String,
+        ) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+            *sql = sql.replace("$value", "1");
+            Ok(())
+        }
+    }
+
+    let mut client = connect("user=postgres").await;
+    client.add_interceptor(Arc::new(Rewrite));
+
+    let stmt = client.prepare("SELECT $1::INT4, $value").await.unwrap();
+    assert_eq!(stmt.sql(), "SELECT $1::INT4, $value");
+}
+
+#[tokio::test]
+async fn insert_select() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute("CREATE TEMPORARY TABLE foo (id SERIAL, name TEXT)")
+        .await
+        .unwrap();
+
+    let insert = client.prepare("INSERT INTO foo (name) VALUES ($1), ($2)");
+    let select = client.prepare("SELECT id, name FROM foo ORDER BY id");
+    let (insert, select) = try_join!(insert, select).unwrap();
+
+    let insert = client.execute(&insert, &[&"alice", &"bob"]);
+    let select = client.query(&select, &[]);
+    let (_, rows) = try_join!(insert, select).unwrap();
+
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].get::<_, i32>(0), 1);
+    assert_eq!(rows[0].get::<_, &str>(1), "alice");
+    assert_eq!(rows[1].get::<_, i32>(0), 2);
+    assert_eq!(rows[1].get::<_, &str>(1), "bob");
+}
+
+#[tokio::test]
+async fn large_parameters() {
+    let client = connect("user=postgres").await;
+
+    let statement = client
+        .prepare("SELECT length($1::BYTEA), $2::TEXT")
+        .await
+        .unwrap();
+
+    let large = (0..8 * 1024 * 1024).map(|i| i as u8).collect::<Vec<_>>();
+    let small = vec![1u8; 16];
+    let queries = [&large, &small, &large, &small].map(|value| {
+        let statement = statement.clone();
+        let client = &client;
+        async move {
+            let rows = client.query(&statement, &[value, &"done"]).await.unwrap();
+            assert_eq!(rows[0].get::<_, i32>(0), value.len() as i32);
+            assert_eq!(rows[0].get::<_, &str>(1), "done");
+        }
+    });
+    future::join_all(queries).await;
+
+    let row = client
+        .query_one("SELECT $1::BYTEA = $2::BYTEA", &[&large, &large])
+        .await
+        .unwrap();
+    assert!(row.get::<_, bool>(0));
+}
+
+#[tokio::test]
+async fn custom_enum() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute(
+            "CREATE TYPE pg_temp.mood AS ENUM (
+                'sad',
+                'ok',
+                'happy'
+            )",
+        )
+        .await
+        .unwrap();
+
+    let select = client.prepare("SELECT $1::mood").await.unwrap();
+
+    let ty = &select.params()[0];
+    assert_eq!("mood", ty.name());
+    assert_eq!(
+        &Kind::Enum(vec![
+            "sad".to_string(),
+            "ok".to_string(),
+            "happy".to_string(),
+        ]),
+        ty.kind(),
+    );
+}
+
+#[tokio::test]
+async fn custom_domain() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute("CREATE DOMAIN pg_temp.session_id AS bytea CHECK(octet_length(VALUE) = 16)")
+        .await
+        .unwrap();
+
+    let select = client.prepare("SELECT $1::session_id").await.unwrap();
+
+    let ty = &select.params()[0];
+    assert_eq!("session_id", ty.name());
+    assert_eq!(&Kind::Domain(Type::BYTEA), ty.kind());
+}
+
+#[tokio::test]
+async fn custom_array() {
+    let client = connect("user=postgres").await;
+
+    let select = client.prepare("SELECT $1::HSTORE[]").await.unwrap();
+
+    let ty = &select.params()[0];
+    assert_eq!("_hstore", ty.name());
+    match ty.kind() {
+        Kind::Array(ty) => {
+            assert_eq!("hstore", ty.name());
+            assert_eq!(&Kind::Simple, ty.kind());
+        }
+        _ => panic!("unexpected kind"),
+    }
+}
+
+#[tokio::test]
+async fn query_raw_txt() {
+    let client = connect("user=postgres").await;
+
+    let rows: Vec<tokio_postgres::Row> = client
+        .query_raw_txt("SELECT 55 * $1", [Some("42")])
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+
+    assert_eq!(rows.len(), 1);
+    let res: i32 = rows[0].as_text(0).unwrap().unwrap().parse::<i32>().unwrap();
+    assert_eq!(res, 55 * 42);
+
+    let rows: Vec<tokio_postgres::Row> = client
+        .query_raw_txt("SELECT $1", [Some("42")])
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].get::<_, &str>(0), "42");
+    assert!(rows[0].body_len() > 0);
+}
+
+#[tokio::test]
+async fn row_stream_ready() {
+    let client = connect("user=postgres").await;
+
+    let stream = client
+        .query_raw_txt(
+            "SELECT 1::INT4 AS id, 'foo'::TEXT AS name WHERE $1::BOOL",
+            [Some("false")],
+        )
+        .await
+        .unwrap();
+    pin_mut!(stream);
+    assert!(stream.columns().is_none());
+
+    stream.as_mut().ready().await.unwrap();
+    let columns = stream.columns().unwrap();
+    assert_eq!(columns.len(), 2);
+    assert_eq!(columns[0].name(), "id");
+    assert_eq!(columns[0].type_(), &Type::INT4);
+    assert_eq!(columns[1].name(), "name");
+    assert_eq!(columns[1].type_(), &Type::TEXT);
+    assert!(stream.next().await.is_none());
+    assert_eq!(stream.rows_affected(), Some(0));
+}
+
+#[tokio::test]
+async fn query_raw_txt_nulls() {
+    let client = connect("user=postgres").await;
+
+    let rows: Vec<tokio_postgres::Row> = client
+        .query_raw_txt(
+            "SELECT $1 as str, $2 as n, 'null' as str2, null as n2",
+            [Some("null"), None],
+        )
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+
+    assert_eq!(rows.len(), 1);
+
+    let res = rows[0].as_text(0).unwrap();
+    assert_eq!(res, Some("null"));
+
+    let res = rows[0].as_text(1).unwrap();
+    assert_eq!(res, None);
+
+    let res = rows[0].as_text(2).unwrap();
+    assert_eq!(res, Some("null"));
+
+    let res = rows[0].as_text(3).unwrap();
+    assert_eq!(res, None);
+}
+
+#[tokio::test]
+async fn query_raw_txt_to_text() {
+    let client = connect("user=postgres").await;
+
+    let time = UNIX_EPOCH + Duration::from_micros(1_234_567);
+    let params: [&(dyn ToText + Sync); 5] = [
+        &41i64,
+        &vec![Some("a\"b".to_string()), None],
+        &&b"\x00\xff"[..],
+        &time,
+        &None::<f64>,
+    ];
+    let row = client
+        .query_one_txt(
+            "SELECT $1::INT8 + 1, ($2::TEXT[])[1], ($2::TEXT[])[2], $3::BYTEA, $4::TIMESTAMPTZ, $5::FLOAT8",
+            params,
+        )
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, i64>(0), 42);
+    assert_eq!(row.get::<_, &str>(1), "a\"b");
+    assert_eq!(row.get::<_, Option<&str>>(2), None);
+    assert_eq!(row.get::<_, Vec<u8>>(3), b"\x00\xff");
+    assert_eq!(row.get::<_, SystemTime>(4), time);
+    assert_eq!(row.get::<_, Option<f64>>(5), None);
+}
+
+#[tokio::test]
+async fn query_raw_txt_binary() {
+    let client = connect("user=postgres").await;
+
+    let id = BinaryValue::new(Type::INT4.oid(), 41i32.to_be_bytes().to_vec());
+    let data = BinaryValue::new(Type::BYTEA.oid(), &b"\x00\xff"[..]);
+    let params: [&(dyn ToText + Sync); 3] = [&id, &"hello", &data];
+    let row = client
+        .query_one_txt("SELECT $1 + 1, $2::TEXT, $3, pg_typeof($1)::TEXT", params)
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, i32>(0), 42);
+    assert_eq!(row.get::<_, &str>(1), "hello");
+    assert_eq!(row.get::<_, Vec<u8>>(2), b"\x00\xff");
+    assert_eq!(row.get::<_, &str>(3), "integer");
+}
+
+#[tokio::test]
+async fn row_borrowed_values() {
+    let client = connect("user=postgres").await;
+
+    let query = "SELECT 'hello'::TEXT, '\\x00ff'::BYTEA, ARRAY['a', 'b\"c']::TEXT[], NULL::TEXT";
+    let row = client.query_one(query, &[]).await.unwrap();
+    assert_eq!(row.get_raw(0), Some(&b"hello"[..]));
+    assert_eq!(row.get_raw(3), None);
+    assert!(row.try_get_raw(4).is_err());
+    assert!(matches!(
+        row.get::<_, Cow<'_, str>>(0),
+        Cow::Borrowed("hello")
+    ));
+    assert!(matches!(
+        row.get::<_, Cow<'_, [u8]>>(1),
+        Cow::Borrowed(b"\x00\xff")
+    ));
+
+    let row = client
+        .query_one_txt(query, [] as [Option<&str>; 0])
+        .await
+        .unwrap();
+    assert_eq!(row.get_raw(1), Some(&b"\\x00ff"[..]));
+    assert!(matches!(
+        row.get::<_, Cow<'_, str>>(0),
+        Cow::Borrowed("hello")
+    ));
+    assert!(matches!(row.get::<_, Cow<'_, [u8]>>(1), Cow::Owned(ref v) if v == b"\x00\xff"));
+    let elements = row.get::<_, Vec<Cow<'_, str>>>(2);
+    assert!(matches!(elements[0], Cow::Borrowed("a")));
+    assert!(matches!(elements[1], Cow::Owned(ref s) if s == "b\"c"));
+    assert_eq!(row.get::<_, Option<Cow<'_, str>>>(3), None);
+}
+
+#[tokio::test]
+async fn params_builder() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute(
+            "CREATE TEMPORARY TABLE users (name TEXT, age INT4);
+             INSERT INTO users VALUES ('alice', 30), ('bob', 40), ('carol', NULL)",
+        )
+        .await
+        .unwrap();
+
+    let search = |name: Option<String>, min_age: Option<i32>| {
+        let client = &client;
+        async move {
+            let mut query = "SELECT name FROM users WHERE name <> $1".to_string();
+            let mut params = params!["nobody"];
+            if let Some(n) = params.push_opt(name) {
+                query += &format!(" AND name = ${}", n);
+            }
+            if let Some(n) = params.push_opt(min_age) {
+                query += &format!(" AND age >= ${}", n);
+            }
+            query += " ORDER BY name";
+
+            let rows = client.query(&query, &params.to_vec()).await.unwrap();
+            let raw = client
+                .query_raw(&query, &params)
+                .await
+                .unwrap()
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap();
+            assert_eq!(rows.len(), raw.len());
+            rows.iter()
+                .map(|row| row.get::<_, String>(0))
+                .collect::<Vec<_>>()
+        }
+    };
+
+    assert_eq!(search(None, None).await, ["alice", "bob", "carol"]);
+    assert_eq!(search(None, Some(35)).await, ["bob"]);
+    assert_eq!(search(Some("alice".to_string()), Some(20)).await, ["alice"]);
+
+    let name = "dave".to_string();
+    let params = params![&name, None::<i32>];
+    assert_eq!(params.len(), 2);
+    let inserted = client
+        .execute_raw("INSERT INTO users VALUES ($1, $2)", params)
+        .await
+        .unwrap();
+    assert_eq!(inserted, 1);
+    let age = client
+        .query_one("SELECT age FROM users WHERE name = $1", &[&name])
+        .await
+        .unwrap()
+        .get::<_, Option<i32>>(0);
+    assert_eq!(age, None);
+}
+
+#[tokio::test]
+async fn query_raw_inferred() {
+    let client = connect("user=postgres").await;
+
+    let params: [&(dyn ToSql + Sync); 2] = [&41i64, &"hello"];
+    let rows = client
+        .query_raw_inferred("SELECT $1 + 1::INT8, $2 || ' world'", params)
+        .await
+        .unwrap()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].columns()[0].type_(), &Type::INT8);
+    assert_eq!(rows[0].get::<_, i64>(0), 42);
+    assert_eq!(rows[0].get::<_, &str>(1), "hello world");
+
+    let err = client
+        .query_raw_inferred("SELEC $1", [1i32])
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), Some(&SqlState::SYNTAX_ERROR));
+
+    let err = client
+        .query_raw_inferred("SELECT $1::INT4", [1i32, 2])
+        .await
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("parameters"));
+
+    let row = client.query_one("SELECT 1", &[]).await.unwrap();
+    assert_eq!(row.get::<_, i32>(0), 1);
+}
+
+#[tokio::test]
+async fn request_raw() {
+    let client = connect("user=postgres").await;
+
+    let mut buf = BytesMut::new();
+    frontend::parse("", "SELECT $1::TEXT", None, &mut buf).unwrap();
+    frontend::bind(
+        "",
+        "",
+        Some(0),
+        Some("hello"),
+        |value, buf| {
+            buf.extend_from_slice(value.as_bytes());
+            Ok(postgres_protocol::IsNull::No)
+        },
+        Some(0),
+        &mut buf,
+    )
+    .map_err(|_| "bind failed")
+    .unwrap();
+    frontend::execute("", 0, &mut buf).unwrap();
+    frontend::sync(&mut buf);
+
+    let messages = client
+        .request_raw(buf.split().freeze())
+        .unwrap()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(messages.len(), 5);
+    assert!(matches!(messages[0], Message::ParseComplete));
+    assert!(matches!(messages[1], Message::BindComplete));
+    match &messages[2] {
+        Message::DataRow(body) => {
+            let mut ranges = body.ranges();
+            let range = ranges.next().unwrap().unwrap().unwrap();
+            assert_eq!(&body.buffer()[range], b"hello");
+        }
+        _ => panic!("unexpected message"),
+    }
+    assert!(matches!(messages[3], Message::CommandComplete(_)));
+    assert!(matches!(messages[4], Message::ReadyForQuery(_)));
+
+    frontend::query("SELECT * FROM missing; SELECT 1", &mut buf).unwrap();
+    let mut responses = client.request_raw(buf.split().freeze()).unwrap();
+    let err = responses.try_next().await.err().unwrap();
+    assert_eq!(err.code(), Some(&SqlState::UNDEFINED_TABLE));
+    let message = future::poll_fn(|cx| responses.poll_next_bytes(cx))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(message[0], b'Z');
+    assert!(responses.try_next().await.unwrap().is_none());
+
+    let row = client.query_one("SELECT 1", &[]).await.unwrap();
+    assert_eq!(row.get::<_, i32>(0), 1);
+}
+
+#[tokio::test]
+async fn query_with_format() {
+    let client = connect("user=postgres").await;
+
+    let id = 41i32.to_be_bytes();
+    let params = [Some(&id[..]), Some(b"hello"), None];
+    let formats = [Format::Binary, Format::Text, Format::Text];
+    for result_format in [Format::Binary, Format::Text] {
+        let rows = client
+            .query_with_format(
+                "SELECT $1::INT4 + 1, $2::TEXT, $3::BOOL",
+                params,
+                &formats,
+                result_format,
+            )
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(rows[0].get::<_, i32>(0), 42);
+        assert_eq!(rows[0].get::<_, &str>(1), "hello");
+        assert_eq!(rows[0].get::<_, Option<bool>>(2), None);
+    }
+
+    let err = client
+        .query_with_format(
+            "SELECT $1::INT4",
+            [Some(b"1")],
+            &[Format::Binary],
+            Format::Text,
+        )
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), Some(&SqlState::PROTOCOL_VIOLATION));
+}
+
+#[tokio::test]
+async fn query_one_txt() {
+    let client = connect("user=postgres").await;
+
+    let row = client
+        .query_one_txt("SELECT $1::INT + 1", [Some("41")])
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, i32>(0), 42);
+
+    let row = client
+        .query_opt_txt("SELECT 1 WHERE $1::BOOL", [Some("false")])
+        .await
+        .unwrap();
+    assert!(row.is_none());
+
+    client
+        .query_one_txt("SELECT 1 WHERE false", [] as [Option<&str>; 0])
+        .await
+        .err()
+        .unwrap();
+    client
+        .query_opt_txt("SELECT generate_series(1, 2)", [] as [Option<&str>; 0])
+        .await
+        .err()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn limit_max_backend_message_size() {
+    let client = connect("user=postgres max_backend_message_size=10000").await;
+    let small: Vec<tokio_postgres::Row> = client
+        .query_raw_txt("SELECT REPEAT('a', 20)", [] as [Option<&str>; 0])
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+
+    assert_eq!(small.len(), 1);
+    assert_eq!(small[0].as_text(0).unwrap().unwrap().len(), 20);
+
+    let large: Result<Vec<tokio_postgres::Row>, Error> = client
+        .query_raw_txt("SELECT REPEAT('a', 2000000)", [] as [Option<&str>; 0])
+        .await
+        .unwrap()
+        .try_collect()
+        .await;
+
+    assert!(large.is_err());
+}
+
+#[tokio::test]
+async fn command_tag() {
+    let client = connect("user=postgres").await;
+
+    let row_stream = client
+        .query_raw_txt("select unnest('{1,2,3}'::int[]);", [] as [Option<&str>; 0])
+        .await
+        .unwrap();
+
+    pin_mut!(row_stream);
+
+    let mut rows: Vec<tokio_postgres::Row> = Vec::new();
+    while let Some(row) = row_stream.next().await {
+        rows.push(row.unwrap());
+    }
+
+    assert_eq!(row_stream.command_tag(), Some("SELECT 3".to_string()));
+}
+
+#[tokio::test]
+async fn ready_for_query() {
+    let client = connect("user=postgres").await;
+
+    let row_stream = client
+        .query_raw_txt("START TRANSACTION", [] as [Option<&str>; 0])
+        .await
+        .unwrap();
+
+    pin_mut!(row_stream);
+    while row_stream.next().await.is_none() {}
+
+    assert_eq!(row_stream.ready_status(), Some(b'T'));
+
+    let row_stream = client
+        .query_raw_txt("ROLLBACK", [] as [Option<&str>; 0])
+        .await
+        .unwrap();
+
+    pin_mut!(row_stream);
+    while row_stream.next().await.is_none() {}
+
+    assert_eq!(row_stream.ready_status(), Some(b'I'));
+}
+
+#[tokio::test]
+async fn query_raw_passthrough() {
+    let client = connect("user=postgres").await;
+
+    let stream = client
+        .query_raw_passthrough(
+            "SELECT $1::INT4 AS id, 'foo' AS name, NULL::TEXT AS nothing",
+            &[&7i32],
+        )
+        .await
+        .unwrap();
+    pin_mut!(stream);
+
+    let mut buf = BytesMut::new();
+    passthrough::write_row_description(stream.row_description().unwrap(), &mut buf);
+    while let Some(row) = stream.try_next().await.unwrap() {
+        passthrough::write_data_row(&row, &mut buf);
+    }
+    assert_eq!(stream.command_tag(), Some("SELECT 1".to_string()));
+
+    // The messages are framed back as the server sent them.
+    match Message::parse(&mut buf).unwrap() {
+        Some(Message::RowDescription(body)) => {
+            let fields = body
+                .fields()
+                .map(|f| Ok((f.name().to_string(), f.type_oid(), f.format())))
+                .collect::<Vec<_>>()
+                .unwrap();
+            assert_eq!(
+                fields,
+                [
+                    ("id".to_string(), Type::INT4.oid(), 1),
+                    ("name".to_string(), Type::TEXT.oid(), 1),
+                    ("nothing".to_string(), Type::TEXT.oid(), 1),
+                ]
+            );
+        }
+        _ => panic!("expected a row description"),
+    }
+    match Message::parse(&mut buf).unwrap() {
+        Some(Message::DataRow(body)) => {
+            let values = body
+                .ranges()
+                .map(|r| Ok(r.map(|r| body.buffer()[r].to_vec())))
+                .collect::<Vec<_>>()
+                .unwrap();
+            assert_eq!(
+                values,
+                [Some(vec![0, 0, 0, 7]), Some(b"foo".to_vec()), None]
+            );
+        }
+        _ => panic!("expected a data row"),
+    }
+    assert!(buf.is_empty());
+
+    let stream = client
+        .query_raw_passthrough("SET TimeZone = 'UTC'", [] as [i32; 0])
+        .await
+        .unwrap();
+    pin_mut!(stream);
+    assert!(stream.row_description().is_none());
+    assert!(stream.try_next().await.unwrap().is_none());
+    assert_eq!(stream.command_tag(), Some("SET".to_string()));
+}
+
+#[tokio::test]
+async fn query_raw_forward() {
+    let client = connect("user=postgres").await;
+
+    let mut stream = client
+        .query_raw_forward("SELECT generate_series(1, $1)", &[&2i32])
+        .await
+        .unwrap();
+    let mut buf = BytesMut::new();
+    stream.write_to(&mut buf).await.unwrap();
+    assert_eq!(stream.rows_affected(), Some(2));
+
+    let mut tags = vec![];
+    while let Some(message) = Message::parse(&mut buf).unwrap() {
+        tags.push(match message {
+            Message::BindComplete => "BindComplete",
+            Message::RowDescription(_) => "RowDescription",
+            Message::DataRow(_) => "DataRow",
+            Message::CommandComplete(_) => "CommandComplete",
+            Message::ReadyForQuery(_) => "ReadyForQuery",
+            _ => "Other",
+        });
+    }
+    assert_eq!(
+        tags,
+        [
+            "BindComplete",
+            "RowDescription",
+            "DataRow",
+            "DataRow",
+            "CommandComplete",
+            "ReadyForQuery"
+        ]
+    );
+
+    // Errors are forwarded, and can be filtered like other messages.
+    let stream = client
+        .query_raw_forward("SELECT 1 / (random() * $1::INT4)::INT4", &[&0i32])
+        .await
+        .unwrap()
+        .filter_messages(|tag| tag != b'Z');
+    pin_mut!(stream);
+    let messages = stream.as_mut().try_collect::<Vec<_>>().await.unwrap();
+    assert_eq!(
+        messages.iter().map(|m| m[0]).collect::<Vec<_>>(),
+        [b'2', b'T', b'E']
+    );
+    assert_eq!(
+        stream.db_error().unwrap().code(),
+        &SqlState::DIVISION_BY_ZERO
+    );
+
+    client.batch_execute("SELECT 1").await.unwrap();
+}
+
+#[tokio::test]
+async fn column_extras() {
+    let client = connect("user=postgres").await;
 
-    assert_eq!(row_stream.ready_status(), Some(b'T'));
+    let rows: Vec<tokio_postgres::Row> = client
+        .query_raw_txt(
+            "select relacl, relname from pg_class limit 1",
+            [] as [Option<&str>; 0],
+        )
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
 
-    let row_stream = client
-        .query_raw_txt("ROLLBACK", [] as [Option<&str>; 0])
+    let column = rows[0].columns().get(1).unwrap();
+    assert_eq!(column.name(), "relname");
+    assert_eq!(column.type_(), &Type::NAME);
+
+    assert!(column.table_oid().unwrap() > 0);
+    assert_eq!(column.column_id(), Some(2));
+    assert_eq!(column.type_().oid(), 19);
+}
+
+#[tokio::test]
+async fn custom_composite() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute(
+            "CREATE TYPE pg_temp.inventory_item AS (
+                name TEXT,
+                supplier INTEGER,
+                price NUMERIC
+            )",
+        )
+        .await
+        .unwrap();
+
+    let select = client.prepare("SELECT $1::inventory_item").await.unwrap();
+
+    let ty = &select.params()[0];
+    assert_eq!(ty.name(), "inventory_item");
+    match ty.kind() {
+        Kind::Composite(fields) => {
+            assert_eq!(fields[0].name(), "name");
+            assert_eq!(fields[0].type_(), &Type::TEXT);
+            assert_eq!(fields[1].name(), "supplier");
+            assert_eq!(fields[1].type_(), &Type::INT4);
+            assert_eq!(fields[2].name(), "price");
+            assert_eq!(fields[2].type_(), &Type::NUMERIC);
+        }
+        _ => panic!("unexpected kind"),
+    }
+}
+
+#[tokio::test]
+async fn custom_range() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute(
+            "CREATE TYPE pg_temp.floatrange AS RANGE (
+                subtype = float8,
+                subtype_diff = float8mi
+            )",
+        )
+        .await
+        .unwrap();
+
+    let select = client.prepare("SELECT $1::floatrange").await.unwrap();
+
+    let ty = &select.params()[0];
+    assert_eq!("floatrange", ty.name());
+    assert_eq!(&Kind::Range(Type::FLOAT8), ty.kind());
+}
+
+#[tokio::test]
+async fn schema_introspection() {
+    let mut client = connect("user=postgres").await;
+    let transaction = client.transaction().await.unwrap();
+
+    transaction
+        .batch_execute(
+            "CREATE SCHEMA introspection;
+            CREATE TYPE introspection.mood AS ENUM ('sad', 'happy');
+            CREATE TABLE introspection.parent (id INT4 PRIMARY KEY, name TEXT NOT NULL DEFAULT 'x');
+            CREATE TABLE introspection.child (
+                id SERIAL PRIMARY KEY,
+                dropped INT4,
+                parent_id INT4 REFERENCES introspection.parent (id),
+                mood introspection.mood
+            );
+            ALTER TABLE introspection.child DROP COLUMN dropped;
+            CREATE UNIQUE INDEX child_mood ON introspection.child (mood, (parent_id + 1));
+            CREATE VIEW introspection.names AS SELECT name FROM introspection.parent;",
+        )
+        .await
+        .unwrap();
+
+    let tables = transaction.tables("introspection").await.unwrap();
+    assert_eq!(
+        tables
+            .iter()
+            .map(|t| (t.name(), t.kind()))
+            .collect::<Vec<_>>(),
+        [
+            ("child", TableKind::Table),
+            ("names", TableKind::View),
+            ("parent", TableKind::Table),
+        ]
+    );
+
+    // The columns of a result can be traced back to the table they come from.
+    let select = transaction
+        .prepare("SELECT parent_id, mood FROM introspection.child")
+        .await
+        .unwrap();
+    let child = select.columns()[0].table_oid().unwrap();
+
+    let columns = transaction.table_columns(child).await.unwrap();
+    assert_eq!(columns.len(), 3);
+    assert_eq!(columns[0].name(), "id");
+    assert_eq!(columns[0].type_(), &Type::INT4);
+    assert!(!columns[0].is_nullable());
+    assert!(columns[0].default().unwrap().starts_with("nextval("));
+    assert_eq!(columns[1].name(), "parent_id");
+    assert_eq!(
+        Some(columns[1].column_id()),
+        select.columns()[0].column_id()
+    );
+    assert!(columns[1].is_nullable());
+    assert_eq!(columns[1].default(), None);
+    assert_eq!(columns[2].name(), "mood");
+    assert_eq!(
+        columns[2].type_().kind(),
+        &Kind::Enum(vec!["sad".to_string(), "happy".to_string()])
+    );
+
+    let primary_key = transaction.primary_key(child).await.unwrap().unwrap();
+    assert_eq!(primary_key.name(), "child_pkey");
+    assert_eq!(primary_key.columns(), ["id"]);
+    assert_eq!(primary_key.column_ids(), [1]);
+
+    let foreign_keys = transaction.foreign_keys(child).await.unwrap();
+    assert_eq!(foreign_keys.len(), 1);
+    assert_eq!(foreign_keys[0].columns(), ["parent_id"]);
+    assert_eq!(foreign_keys[0].column_ids(), [3]);
+    assert_eq!(foreign_keys[0].referenced_columns(), ["id"]);
+    let parent = foreign_keys[0].referenced_table();
+    assert!(transaction.foreign_keys(parent).await.unwrap().is_empty());
+
+    let indexes = transaction.indexes(child).await.unwrap();
+    assert_eq!(indexes.len(), 2);
+    assert_eq!(indexes[0].name(), "child_mood");
+    assert!(indexes[0].is_unique());
+    assert!(!indexes[0].is_primary());
+    assert_eq!(indexes[0].columns(), ["mood", "(parent_id + 1)"]);
+    assert!(indexes[0]
+        .definition()
+        .starts_with("CREATE UNIQUE INDEX child_mood"));
+    assert_eq!(indexes[1].name(), "child_pkey");
+    assert!(indexes[1].is_primary());
+
+    assert!(transaction
+        .primary_key(tables[1].oid())
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn resolve_type() {
+    let mut client = connect("user=postgres").await;
+
+    client
+        .batch_execute(
+            "CREATE TYPE pg_temp.mood AS ENUM ('sad', 'happy');
+            CREATE TYPE pg_temp.mood_entry AS (day DATE, moods mood[]);
+            CREATE DOMAIN pg_temp.positive AS INT4 CHECK (VALUE > 0);
+            CREATE TYPE pg_temp.positive_range AS RANGE (subtype = positive);",
+        )
+        .await
+        .unwrap();
+
+    // Types unknown to the crate are resolved by statements as well.
+    let select = client
+        .prepare(
+            "SELECT NULL::mood_entry, 'mood_entry'::regtype::oid, 'positive_range'::regtype::oid,
+            'positive_multirange'::regtype::oid",
+        )
+        .await
+        .unwrap();
+    assert_eq!(select.columns()[0].type_().name(), "mood_entry");
+    let row = client.query_one(&select, &[]).await.unwrap();
+
+    let ty = client.resolve_type(row.get(1)).await.unwrap();
+    assert_eq!(ty.name(), "mood_entry");
+    assert!(ty.schema().starts_with("pg_temp"));
+    let fields = match ty.kind() {
+        Kind::Composite(fields) => fields,
+        _ => panic!("unexpected kind"),
+    };
+    assert_eq!(fields[0].name(), "day");
+    assert_eq!(fields[0].type_(), &Type::DATE);
+    assert_eq!(fields[1].name(), "moods");
+    let mood = match fields[1].type_().kind() {
+        Kind::Array(mood) => mood,
+        _ => panic!("unexpected kind"),
+    };
+    assert_eq!(
+        mood.kind(),
+        &Kind::Enum(vec!["sad".to_string(), "happy".to_string()])
+    );
+
+    let ty = client.resolve_type(row.get(2)).await.unwrap();
+    let positive = match ty.kind() {
+        Kind::Range(positive) => positive.clone(),
+        _ => panic!("unexpected kind"),
+    };
+    assert_eq!(positive.name(), "positive");
+    assert_eq!(positive.kind(), &Kind::Domain(Type::INT4));
+    let ty = client.resolve_type(row.get(3)).await.unwrap();
+    assert_eq!(ty.kind(), &Kind::Multirange(positive));
+
+    // Resolved types are cached, so they are still returned once dropped along with the temporary schema.
+    let mood = mood.clone();
+    client.batch_execute("DISCARD ALL").await.unwrap();
+    let transaction = client.transaction().await.unwrap();
+    let ty = transaction.resolve_type(mood.oid()).await.unwrap();
+    assert_eq!(ty, mood);
+
+    assert_eq!(
+        transaction.resolve_type(Type::INT4.oid()).await.unwrap(),
+        Type::INT4
+    );
+    assert!(transaction.resolve_type(0).await.is_err());
+}
+
+#[tokio::test]
+#[allow(clippy::get_first)]
+async fn simple_query() {
+    let client = connect("user=postgres").await;
+
+    let messages = client
+        .simple_query(
+            "CREATE TEMPORARY TABLE foo (
+                id SERIAL,
+                name TEXT
+            );
+            INSERT INTO foo (name) VALUES ('steven'), ('joe');
+            SELECT * FROM foo ORDER BY id;",
+        )
+        .await
+        .unwrap();
+
+    match &messages[0] {
+        SimpleQueryMessage::CommandComplete { tag, rows: 0 } => assert_eq!(tag, "CREATE TABLE"),
+        _ => panic!("unexpected message"),
+    }
+    match &messages[1] {
+        SimpleQueryMessage::CommandComplete { tag, rows: 2 } => assert_eq!(tag, "INSERT 0 2"),
+        _ => panic!("unexpected message"),
+    }
+    match &messages[2] {
+        SimpleQueryMessage::Row(row) => {
+            assert_eq!(row.columns().get(0).map(|c| c.name()), Some("id"));
+            assert_eq!(row.columns().get(0).map(|c| c.type_()), Some(&Type::INT4));
+            assert_eq!(row.columns().get(1).map(|c| c.name()), Some("name"));
+            assert_eq!(row.columns().get(1).map(|c| c.type_()), Some(&Type::TEXT));
+            assert_eq!(row.get(0), Some("1"));
+            assert_eq!(row.get(1), Some("steven"));
+        }
+        _ => panic!("unexpected message"),
+    }
+    match &messages[3] {
+        SimpleQueryMessage::Row(row) => {
+            assert_eq!(row.columns().get(0).map(|c| c.name()), Some("id"));
+            assert_eq!(row.columns().get(1).map(|c| c.name()), Some("name"));
+            assert_eq!(row.get(0), Some("2"));
+            assert_eq!(row.get(1), Some("joe"));
+        }
+        _ => panic!("unexpected message"),
+    }
+    match &messages[4] {
+        SimpleQueryMessage::CommandComplete { tag, rows: 2 } => assert_eq!(tag, "SELECT 2"),
+        _ => panic!("unexpected message"),
+    }
+    assert_eq!(messages.len(), 5);
+}
+
+#[tokio::test]
+async fn batch_execute_params() {
+    let client = connect("user=postgres").await;
+
+    let params: [&dyn ToText; 2] = [&"steven", &2i32];
+    let tags = client
+        .batch_execute_params(
+            "CREATE TEMPORARY TABLE foo (id INT, name TEXT);
+            -- the names; are inserted with their ids
+            INSERT INTO foo (id, name) VALUES (1, $1), ($2, 'joe; ' || $1);
+            UPDATE foo SET name = $$$;$$ || name WHERE id = $2;
+            SELECT * FROM foo;",
+            params,
+        )
+        .await
+        .unwrap();
+    assert_eq!(tags, ["CREATE TABLE", "INSERT 0 2", "UPDATE 1", "SELECT 2"]);
+
+    let rows = client
+        .query("SELECT id, name FROM foo ORDER BY id", &[])
+        .await
+        .unwrap();
+    assert_eq!(rows[0].get::<_, &str>(1), "steven");
+    assert_eq!(rows[1].get::<_, &str>(1), "$;joe; steven");
+}
+
+#[tokio::test]
+async fn batch_execute_params_error() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute("CREATE TEMPORARY TABLE foo (id INT PRIMARY KEY)")
+        .await
+        .unwrap();
+
+    let err = client
+        .batch_execute_params(
+            "INSERT INTO foo (id) VALUES ($1); INSERT INTO foo (id) VALUES ($1)",
+            [1i32],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Some(&SqlState::UNIQUE_VIOLATION));
+
+    let rows = client.query("SELECT * FROM foo", &[]).await.unwrap();
+    assert_eq!(rows.len(), 0);
+
+    client
+        .batch_execute_params("SELECT $2::INT", [1i32])
+        .await
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn execute_many() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute("CREATE TEMPORARY TABLE foo (id INT PRIMARY KEY, name TEXT)")
+        .await
+        .unwrap();
+
+    let rows = (0..1000).map(|i| {
+        [
+            Box::new(i) as Box<dyn ToSql + Sync>,
+            Box::new(i.to_string()),
+        ]
+    });
+    let inserted = client
+        .execute_many("INSERT INTO foo (id, name) VALUES ($1, $2)", rows)
+        .await
+        .unwrap();
+    assert_eq!(inserted, 1000);
+
+    let updated = client
+        .execute_many(
+            "INSERT INTO foo (id, name) VALUES ($1, 'dup') ON CONFLICT (id) DO UPDATE SET name = 'dup'",
+            [[998i32], [999], [1000]],
+        )
+        .await
+        .unwrap();
+    assert_eq!(updated, 3);
+
+    let err = client
+        .execute_many(
+            "INSERT INTO foo (id) VALUES ($1)",
+            [[1001i32], [1002], [1001]],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.row(), Some(2));
+    assert_eq!(err.error().code(), Some(&SqlState::UNIQUE_VIOLATION));
+
+    let row = client
+        .query_one("SELECT COUNT(*) FROM foo", &[])
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, i64>(0), 1001);
+
+    let empty: [[i32; 1]; 0] = [];
+    assert_eq!(
+        client
+            .execute_many("INSERT INTO foo (id) VALUES ($1)", empty)
+            .await
+            .unwrap(),
+        0
+    );
+}
+
+#[tokio::test]
+async fn query_rows() {
+    let client = connect("user=postgres").await;
+
+    let rows = client
+        .query_rows(
+            "SELECT i AS id, CASE WHEN i % 2 = 0 THEN 'name ' || i END AS name
+            FROM generate_series(1, $1) i",
+            &[&1000],
+        )
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 1000);
+    assert_eq!(rows.get::<_, i32>(0, "id"), 1);
+    assert_eq!(rows.get::<_, Option<&str>>(0, "name"), None);
+    assert_eq!(rows.get::<_, Option<&str>>(1, 1), Some("name 2"));
+    assert_eq!(rows.get::<_, &str>(999, "name"), "name 1000");
+    rows.try_get::<_, i32>(1000, "id").unwrap_err();
+    rows.try_get::<_, i32>(0, "foo").unwrap_err();
+
+    let ids = rows.get_column::<_, i32>("id");
+    assert_eq!(ids, (1..=1000).collect::<Vec<_>>());
+
+    let clone = rows.clone();
+    let names = clone
+        .iter()
+        .filter_map(|row| row.get::<_, Option<String>>("name"))
+        .count();
+    assert_eq!(names, 500);
+    assert_eq!(clone.row(4).unwrap().get::<_, i32>(0), 5);
+    assert!(clone.row(1000).is_none());
+
+    let rows = client
+        .query_rows("SELECT 1 AS id WHERE false", &[])
+        .await
+        .unwrap();
+    assert!(rows.is_empty());
+    assert_eq!(rows.columns()[0].name(), "id");
+}
+
+#[tokio::test]
+async fn query_with_limit() {
+    let mut client = connect("user=postgres").await;
+
+    let rows = client
+        .query_with_limit("SELECT generate_series(1, $1)", &[&10], 3)
+        .await
+        .unwrap();
+    let ids = rows.iter().map(|r| r.get::<_, i32>(0)).collect::<Vec<_>>();
+    assert_eq!(ids, [1, 2, 3]);
+
+    let stream = client
+        .query_raw_with_limit("SELECT generate_series(1, $1)", [&3], 3)
+        .await
+        .unwrap();
+    pin_mut!(stream);
+    let rows = stream.as_mut().try_collect::<Vec<_>>().await.unwrap();
+    assert_eq!(rows.len(), 3);
+    assert!(stream.is_suspended());
+
+    let stream = client
+        .query_raw_with_limit("SELECT generate_series(1, $1)", [&3], 4)
+        .await
+        .unwrap();
+    pin_mut!(stream);
+    let rows = stream.as_mut().try_collect::<Vec<_>>().await.unwrap();
+    assert_eq!(rows.len(), 3);
+    assert!(!stream.is_suspended());
+
+    // The suspended portal is closed, so the transaction can go on.
+    let transaction = client.transaction().await.unwrap();
+    let rows = transaction
+        .query_with_limit("SELECT generate_series(1, 10)", &[], 2)
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 2);
+    let rows = transaction
+        .query_with_limit("SELECT generate_series(1, 10)", &[], 0)
         .await
         .unwrap();
-
-    pin_mut!(row_stream);
-    while row_stream.next().await.is_none() {}
-
-    assert_eq!(row_stream.ready_status(), Some(b'I'));
+    assert_eq!(rows.len(), 10);
+    transaction.commit().await.unwrap();
 }
 
 #[tokio::test]
-async fn column_extras() {
+async fn streamed_params() {
     let client = connect("user=postgres").await;
 
-    let rows: Vec<tokio_postgres::Row> = client
-        .query_raw_txt(
-            "select relacl, relname from pg_class limit 1",
-            [] as [Option<&str>; 0],
-        )
+    client
+        .batch_execute("CREATE TEMPORARY TABLE foo (id INT, data BYTEA)")
         .await
-        .unwrap()
-        .try_collect()
+        .unwrap();
+
+    let data = Bytes::from(
+        (0..3 * 1024 * 1024 + 7)
+            .map(|i| i as u8)
+            .collect::<Vec<_>>(),
+    );
+    let inserted = client
+        .execute(
+            "INSERT INTO foo (id, data) VALUES ($1, $2)",
+            &[&1i32, &Streamed(data.clone())],
+        )
         .await
         .unwrap();
+    assert_eq!(inserted, 1);
 
-    let column = rows[0].columns().get(1).unwrap();
-    assert_eq!(column.name(), "relname");
-    assert_eq!(column.type_(), &Type::NAME);
+    let row = client
+        .query_one(
+            "SELECT data = $1, length(data) FROM foo WHERE id = $2",
+            &[&Streamed(data.clone()), &1i32],
+        )
+        .await
+        .unwrap();
+    assert!(row.get::<_, bool>(0));
+    assert_eq!(row.get::<_, i32>(1) as usize, data.len());
 
-    assert!(column.table_oid() > 0);
-    assert_eq!(column.column_id(), 2);
-    assert_eq!(column.format(), 0);
+    let row = client.query_one("SELECT data FROM foo", &[]).await.unwrap();
+    assert_eq!(row.get::<_, &[u8]>(0), &data[..]);
 
-    assert_eq!(column.type_oid(), 19);
-    assert_eq!(column.type_size(), 64);
-    assert_eq!(column.type_modifier(), -1);
+    client
+        .execute("INSERT INTO foo (id) VALUES ($1)", &[&Streamed(data)])
+        .await
+        .unwrap_err();
+    assert!(!client.is_closed());
 }
 
 #[tokio::test]
-async fn custom_composite() {
-    let client = connect("user=postgres").await;
+async fn close() {
+    let (client, connection) = connect_raw("user=postgres").await.unwrap();
+    let connection = tokio::spawn(connection);
 
-    client
-        .batch_execute(
-            "CREATE TYPE pg_temp.inventory_item AS (
-                name TEXT,
-                supplier INTEGER,
-                price NUMERIC
-            )",
-        )
-        .await
-        .unwrap();
-
-    let select = client.prepare("SELECT $1::inventory_item").await.unwrap();
+    client.batch_execute("SELECT 1").await.unwrap();
+    assert_eq!(client.close().await.unwrap(), 0);
+    connection.await.unwrap().unwrap();
 
-    let ty = &select.params()[0];
-    assert_eq!(ty.name(), "inventory_item");
-    match ty.kind() {
-        Kind::Composite(fields) => {
-            assert_eq!(fields[0].name(), "name");
-            assert_eq!(fields[0].type_(), &Type::TEXT);
-            assert_eq!(fields[1].name(), "supplier");
-            assert_eq!(fields[1].type_(), &Type::INT4);
-            assert_eq!(fields[2].name(), "price");
-            assert_eq!(fields[2].type_(), &Type::NUMERIC);
-        }
-        _ => panic!("unexpected kind"),
-    }
+    let client = connect("user=postgres").await;
+    assert_eq!(
+        client.close_timeout(Duration::from_secs(10)).await.unwrap(),
+        0
+    );
 }
 
 #[tokio::test]
-async fn custom_range() {
+async fn constraint_violation() {
     let client = connect("user=postgres").await;
 
     client
         .batch_execute(
-            "CREATE TYPE pg_temp.floatrange AS RANGE (
-                subtype = float8,
-                subtype_diff = float8mi
-            )",
+            "CREATE TEMPORARY TABLE foo (id INT CONSTRAINT foo_id_key UNIQUE, name TEXT NOT NULL);
+            INSERT INTO foo (id, name) VALUES (1, 'steven');",
         )
         .await
         .unwrap();
 
-    let select = client.prepare("SELECT $1::floatrange").await.unwrap();
-
-    let ty = &select.params()[0];
-    assert_eq!("floatrange", ty.name());
-    assert_eq!(&Kind::Range(Type::FLOAT8), ty.kind());
-}
-
-#[tokio::test]
-#[allow(clippy::get_first)]
-async fn simple_query() {
-    let client = connect("user=postgres").await;
+    let err = client
+        .execute("INSERT INTO foo (id, name) VALUES (1, 'joe')", &[])
+        .await
+        .unwrap_err();
+    let violation = err.as_db_error().unwrap().constraint_violation().unwrap();
+    assert_eq!(violation.kind(), ConstraintKind::Unique);
+    assert_eq!(violation.table(), Some("foo"));
+    assert_eq!(violation.constraint(), Some("foo_id_key"));
+    assert_eq!(
+        err.code().unwrap().class(),
+        SqlStateClass::IntegrityConstraintViolation
+    );
+    assert!(!err.is_retryable());
 
-    let messages = client
-        .simple_query(
-            "CREATE TEMPORARY TABLE foo (
-                id SERIAL,
-                name TEXT
-            );
-            INSERT INTO foo (name) VALUES ('steven'), ('joe');
-            SELECT * FROM foo ORDER BY id;",
-        )
+    let err = client
+        .execute("INSERT INTO foo (id) VALUES (2)", &[])
         .await
-        .unwrap();
+        .unwrap_err();
+    let violation = err.as_db_error().unwrap().constraint_violation().unwrap();
+    assert_eq!(violation.kind(), ConstraintKind::NotNull);
+    assert_eq!(violation.column(), Some("name"));
 
-    match messages[0] {
-        SimpleQueryMessage::CommandComplete(0) => {}
-        _ => panic!("unexpected message"),
-    }
-    match messages[1] {
-        SimpleQueryMessage::CommandComplete(2) => {}
-        _ => panic!("unexpected message"),
-    }
-    match &messages[2] {
-        SimpleQueryMessage::Row(row) => {
-            assert_eq!(row.columns().get(0).map(|c| c.name()), Some("id"));
-            assert_eq!(row.columns().get(1).map(|c| c.name()), Some("name"));
-            assert_eq!(row.get(0), Some("1"));
-            assert_eq!(row.get(1), Some("steven"));
-        }
-        _ => panic!("unexpected message"),
-    }
-    match &messages[3] {
-        SimpleQueryMessage::Row(row) => {
-            assert_eq!(row.columns().get(0).map(|c| c.name()), Some("id"));
-            assert_eq!(row.columns().get(1).map(|c| c.name()), Some("name"));
-            assert_eq!(row.get(0), Some("2"));
-            assert_eq!(row.get(1), Some("joe"));
-        }
-        _ => panic!("unexpected message"),
-    }
-    match messages[4] {
-        SimpleQueryMessage::CommandComplete(2) => {}
-        _ => panic!("unexpected message"),
-    }
-    assert_eq!(messages.len(), 5);
+    let err = client.execute("SELECT 1 / 0", &[]).await.unwrap_err();
+    assert!(err.as_db_error().unwrap().constraint_violation().is_none());
 }
 
 #[tokio::test]
@@ -524,6 +2975,88 @@ async fn cancel_query_raw() {
     }
 }
 
+#[tokio::test]
+async fn query_cancel_token() {
+    let client = connect("user=postgres").await;
+
+    // The server only flushes its output once enough rows are buffered, so the query must produce some before
+    // sleeping for the stream to be returned early.
+    let sleep = client
+        .query_raw(
+            "SELECT repeat('a', 100), pg_sleep(CASE WHEN g = 1000 THEN 100 ELSE 0 END) \
+             FROM generate_series(1, 1000) g",
+            Vec::<i32>::new(),
+        )
+        .await
+        .unwrap();
+    let cancel_token = sleep.cancel_token();
+    assert!(cancel_token.is_active());
+
+    // The query pipelined after the cancelled one still runs.
+    let next = client.query_one("SELECT 1", &[]);
+    let cancel = async {
+        time::sleep(Duration::from_millis(100)).await;
+        let socket = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+        cancel_token.cancel_query_raw(socket, NoTls).await
+    };
+    let (rows, next, cancel) = join!(sleep.try_collect::<Vec<_>>(), next, cancel);
+    assert_eq!(rows.unwrap_err().code(), Some(&SqlState::QUERY_CANCELED));
+    assert_eq!(next.unwrap().get::<_, i32>(0), 1);
+    cancel.unwrap();
+    assert!(!cancel_token.is_active());
+
+    // Cancelling a completed query doesn't cancel the one running after it.
+    let sleep = client.query_one("SELECT pg_sleep(0.5)::TEXT", &[]);
+    let cancel = async {
+        time::sleep(Duration::from_millis(100)).await;
+        let socket = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+        cancel_token.cancel_query_raw(socket, NoTls).await
+    };
+    let (row, cancel) = join!(sleep, cancel);
+    row.unwrap();
+    cancel.unwrap();
+}
+
+#[tokio::test]
+async fn query_timeout() {
+    let mut config = "host=127.0.0.1 port=5433 user=postgres"
+        .parse::<Config>()
+        .unwrap();
+    config.query_timeout(Duration::from_millis(100));
+    let (client, connection) = config.connect(NoTls).await.unwrap();
+    tokio::spawn(connection.map(|r| r.unwrap()));
+
+    let options = QueryOptions::new().timeout(Duration::from_secs(10));
+    let rows = client
+        .query_with_opts("SELECT pg_sleep(0.5)::TEXT", &[], options)
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 1);
+
+    let err = client
+        .batch_execute("SELECT pg_sleep(100)")
+        .await
+        .unwrap_err();
+    assert!(err.is_timeout());
+
+    // The timed out query was cancelled, so the next one doesn't wait for it. The server may signal a cancellation
+    // more than once though, which can cancel the next query as well.
+    let result = time::timeout(Duration::from_secs(10), client.query_one("SELECT 1", &[]))
+        .await
+        .unwrap();
+    if let Err(e) = result {
+        assert_eq!(e.code(), Some(&SqlState::QUERY_CANCELED));
+    }
+
+    let client = connect("user=postgres").await;
+    let options = QueryOptions::new().timeout(Duration::from_millis(100));
+    let err = client
+        .execute_with_opts("SELECT pg_sleep(100)", &[], options)
+        .await
+        .unwrap_err();
+    assert!(err.is_timeout());
+}
+
 #[tokio::test]
 async fn transaction_commit() {
     let mut client = connect("user=postgres").await;
@@ -715,6 +3248,65 @@ async fn transaction_builder() {
     assert_eq!(rows[0].get::<_, &str>(0), "steven");
 }
 
+#[tokio::test]
+async fn transaction_builder_settings() {
+    let mut client = connect("user=postgres").await;
+
+    let transaction = client
+        .build_transaction()
+        .isolation_level(IsolationLevel::RepeatableRead)
+        .set_local("statement_timeout", "5s")
+        .set_local("app.note", "it's")
+        .start()
+        .await
+        .unwrap();
+    let row = transaction
+        .query_one(
+            "SELECT current_setting('transaction_isolation'), \
+                current_setting('statement_timeout'), \
+                current_setting('app.note')",
+            &[],
+        )
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, &str>(0), "repeatable read");
+    assert_eq!(row.get::<_, &str>(1), "5s");
+    assert_eq!(row.get::<_, &str>(2), "it's");
+    let snapshot = transaction
+        .query_one("SELECT pg_export_snapshot()", &[])
+        .await
+        .unwrap()
+        .get::<_, String>(0);
+
+    let mut other = connect("user=postgres").await;
+    let other_transaction = other
+        .build_transaction()
+        .isolation_level(IsolationLevel::RepeatableRead)
+        .snapshot(&snapshot)
+        .start()
+        .await
+        .unwrap();
+    other_transaction.commit().await.unwrap();
+    transaction.commit().await.unwrap();
+
+    // Settings are local to the transaction.
+    let row = client
+        .query_one("SELECT current_setting('statement_timeout')", &[])
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, &str>(0), "0");
+
+    // A failure after the transaction started rolls it back.
+    let result = client
+        .build_transaction()
+        .set_local("transaction_read_only", "maybe")
+        .start()
+        .await;
+    assert!(result.is_err());
+    drop(result);
+    client.batch_execute("SELECT 1").await.unwrap();
+}
+
 #[tokio::test]
 async fn copy_in() {
     let client = connect("user=postgres").await;
@@ -755,6 +3347,38 @@ async fn copy_in() {
     assert_eq!(rows[1].get::<_, &str>(1), "joe");
 }
 
+#[tokio::test]
+async fn copy_in_progress() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute("CREATE TEMPORARY TABLE foo (id INTEGER)")
+        .await
+        .unwrap();
+
+    let sink = client.copy_in("COPY foo FROM STDIN").await.unwrap();
+    pin_mut!(sink);
+    sink.as_mut().set_flush_threshold(16);
+    assert_eq!(sink.flush_threshold(), 16);
+    let progress = sink.progress();
+
+    let mut stream =
+        stream::iter((0..100).map(|i| Ok::<_, Error>(Bytes::from(format!("{}\n", i)))));
+    sink.send_all(&mut stream).await.unwrap();
+    let rows = sink.as_mut().finish().await.unwrap();
+    assert_eq!(rows, 100);
+    assert_eq!(sink.bytes_written(), 290);
+    assert!(sink.chunks_written() > 1 && sink.chunks_written() < 100);
+
+    let progress = progress.collect::<Vec<_>>().await;
+    let last = progress.last().unwrap();
+    assert_eq!(last.bytes_written(), 290);
+    assert_eq!(last.chunks_written(), sink.chunks_written());
+    assert!(progress
+        .windows(2)
+        .all(|w| w[0].bytes_written() < w[1].bytes_written()));
+}
+
 #[tokio::test]
 async fn copy_in_large() {
     let client = connect("user=postgres").await;
@@ -834,9 +3458,141 @@ async fn copy_out() {
         .await
         .unwrap();
 
-    let stmt = client.prepare("COPY foo TO STDOUT").await.unwrap();
-    let data = client
-        .copy_out(&stmt)
+    let stmt = client.prepare("COPY foo TO STDOUT").await.unwrap();
+    let data = client
+        .copy_out(&stmt)
+        .await
+        .unwrap()
+        .try_fold(BytesMut::new(), |mut buf, chunk| async move {
+            buf.extend_from_slice(&chunk);
+            Ok(buf)
+        })
+        .await
+        .unwrap();
+    assert_eq!(&data[..], b"1\tjim\n2\tjoe\n");
+}
+
+async fn export_all(rows: RowStream, format: ExportFormat) -> Result<String, Error> {
+    let data = ExportStream::new(rows, format)
+        .try_fold(BytesMut::new(), |mut buf, chunk| async move {
+            buf.extend_from_slice(&chunk);
+            Ok(buf)
+        })
+        .await?;
+    Ok(String::from_utf8(data.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn export() {
+    let client = connect("user=postgres").await;
+
+    let query = r#"SELECT 1::INT4 AS id, 'a,"b"'::TEXT AS name, ''::TEXT AS empty, NULL::TEXT AS "null",
+        true AS flag, -12.3400 AS amount, 'NaN'::FLOAT8 AS ratio, '\x0a0b'::BYTEA AS data,
+        '{"a": [1, 2]}'::JSONB AS doc"#;
+
+    let rows = client
+        .query_raw(query, std::iter::empty::<i32>())
+        .await
+        .unwrap();
+    assert_eq!(
+        export_all(rows, ExportFormat::Csv).await.unwrap(),
+        concat!(
+            "id,name,empty,null,flag,amount,ratio,data,doc\n",
+            r#"1,"a,""b""","",,t,-12.3400,NaN,\x0a0b,"{""a"": [1, 2]}""#,
+            "\n",
+        ),
+    );
+
+    let expected = concat!(
+        r#"{"id":1,"name":"a,\"b\"","empty":"","null":null,"flag":true,"amount":-12.3400,"#,
+        r#""ratio":"NaN","data":"\\x0a0b","doc":{"a": [1, 2]}}"#,
+        "\n",
+    );
+    let rows = client
+        .query_raw(query, std::iter::empty::<i32>())
+        .await
+        .unwrap();
+    assert_eq!(
+        export_all(rows, ExportFormat::Ndjson).await.unwrap(),
+        expected
+    );
+    let rows = client
+        .query_raw_txt(query, Vec::<Option<&str>>::new())
+        .await
+        .unwrap();
+    assert_eq!(
+        export_all(rows, ExportFormat::Ndjson).await.unwrap(),
+        expected
+    );
+
+    let rows = client
+        .query_raw(
+            "SELECT 0.000012::NUMERIC(10, 8) AS a, 10000::NUMERIC AS b, \
+             'a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11'::UUID AS c",
+            std::iter::empty::<i32>(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        export_all(rows, ExportFormat::Csv).await.unwrap(),
+        "a,b,c\n0.00001200,10000,a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11\n",
+    );
+
+    let rows = client
+        .query_raw_txt("SELECT 1 AS one WHERE false", Vec::<Option<&str>>::new())
+        .await
+        .unwrap();
+    assert_eq!(export_all(rows, ExportFormat::Csv).await.unwrap(), "one\n");
+
+    let rows = client
+        .query_raw("SELECT now()", std::iter::empty::<i32>())
+        .await
+        .unwrap();
+    let err = export_all(rows, ExportFormat::Ndjson).await.unwrap_err();
+    assert!(err.to_string().contains("timestamptz"), "{}", err);
+}
+
+#[tokio::test]
+async fn copy_transfer() {
+    let source = connect("user=postgres").await;
+    let target = connect("user=postgres").await;
+
+    source
+        .batch_execute(
+            "CREATE TEMPORARY TABLE foo (id INT, name TEXT);
+            INSERT INTO foo VALUES (1, 'jim'), (2, NULL);",
+        )
+        .await
+        .unwrap();
+    target
+        .batch_execute("CREATE TEMPORARY TABLE bar (id INT, name TEXT)")
+        .await
+        .unwrap();
+
+    for format in [CopyFormat::Csv, CopyFormat::Binary] {
+        let options = CopyOptions::new()
+            .format(format)
+            .header(format == CopyFormat::Csv);
+        let rows = copy_options::transfer(
+            &source,
+            "(SELECT * FROM foo ORDER BY id)",
+            &target,
+            "bar (id, name)",
+            &options,
+        )
+        .await
+        .unwrap();
+        assert_eq!(rows, 2);
+    }
+
+    let options = CopyOptions::new().format(CopyFormat::Csv);
+    let data = stream::iter(vec![Ok::<_, Error>(Bytes::from_static(b"3,joe\n"))]);
+    let rows = copy_options::restore(&target, "bar", &options, data)
+        .await
+        .unwrap();
+    assert_eq!(rows, 1);
+
+    let data = copy_options::dump(&target, "(SELECT * FROM bar ORDER BY id, name)", &options)
         .await
         .unwrap()
         .try_fold(BytesMut::new(), |mut buf, chunk| async move {
@@ -845,7 +3601,7 @@ async fn copy_out() {
         })
         .await
         .unwrap();
-    assert_eq!(&data[..], b"1\tjim\n2\tjoe\n");
+    assert_eq!(&data[..], b"1,jim\n1,jim\n2,\n2,\n3,joe\n");
 }
 
 #[tokio::test]
@@ -888,6 +3644,46 @@ async fn notices() {
     );
 }
 
+#[tokio::test]
+async fn query_notices() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute(
+            "CREATE FUNCTION pg_temp.noisy(n INT) RETURNS INT AS $$
+             BEGIN
+                 RAISE NOTICE 'noisy %', n;
+                 RETURN n;
+             END
+             $$ LANGUAGE plpgsql",
+        )
+        .await
+        .unwrap();
+
+    let stream = client
+        .query_raw(
+            "SELECT pg_temp.noisy(n) FROM generate_series(1, 2) n",
+            std::iter::empty::<i32>(),
+        )
+        .await
+        .unwrap();
+    pin_mut!(stream);
+    let rows = stream.as_mut().try_collect::<Vec<_>>().await.unwrap();
+    assert_eq!(rows.len(), 2);
+    let notices = stream.notices();
+    assert_eq!(notices.len(), 2);
+    assert_eq!(notices[0].message(), "noisy 1");
+    assert_eq!(notices[1].message(), "noisy 2");
+
+    let stream = client
+        .query_raw("SELECT 1", std::iter::empty::<i32>())
+        .await
+        .unwrap();
+    pin_mut!(stream);
+    stream.as_mut().try_collect::<Vec<_>>().await.unwrap();
+    assert!(stream.notices().is_empty());
+}
+
 #[tokio::test]
 async fn notifications() {
     let (client, mut connection) = connect_raw("user=postgres").await.unwrap();
@@ -923,6 +3719,149 @@ async fn notifications() {
     assert_eq!(notifications[1].payload(), "world");
 }
 
+#[tokio::test]
+async fn notify() {
+    let (mut client, mut connection) = connect_raw("user=postgres").await.unwrap();
+
+    let (tx, rx) = mpsc::unbounded();
+    let stream =
+        stream::poll_fn(move |cx| connection.poll_message(cx)).map_err(|e| panic!("{}", e));
+    let connection = stream.forward(tx).map(|r| r.unwrap());
+    tokio::spawn(connection);
+
+    client
+        .batch_execute("LISTEN \"test Notify\"")
+        .await
+        .unwrap();
+    client
+        .notify("test Notify", "it's \"quoted\"")
+        .await
+        .unwrap();
+    client.notify("test notify", "ignored").await.unwrap();
+    client.notify("test Notify", "back\\slash").await.unwrap();
+
+    let transaction = client.transaction().await.unwrap();
+    transaction
+        .notify("test Notify", "committed")
+        .await
+        .unwrap();
+    transaction.commit().await.unwrap();
+
+    drop(client);
+
+    let notifications = rx
+        .filter_map(|m| match m {
+            AsyncMessage::Notification(n) => future::ready(Some(n)),
+            _ => future::ready(None),
+        })
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(notifications.len(), 3);
+    assert_eq!(notifications[0].channel(), "test Notify");
+    assert_eq!(notifications[0].payload(), "it's \"quoted\"");
+    assert_eq!(notifications[1].payload(), "back\\slash");
+    assert_eq!(notifications[2].payload(), "committed");
+}
+
+#[tokio::test]
+async fn listen() {
+    let client = connect("user=postgres").await;
+
+    let jobs = client.listen("test Listen").await.unwrap();
+    let mut jobs2 = client.listen("test Listen").await.unwrap();
+    let mut other = client.listen("test listen other").await.unwrap();
+    assert_eq!(jobs.channel(), "test Listen");
+
+    client.notify("test Listen", "hello").await.unwrap();
+    client.notify("test listen other", "world").await.unwrap();
+
+    assert_eq!(jobs2.next().await.unwrap().payload(), "hello");
+    assert_eq!(other.next().await.unwrap().payload(), "world");
+
+    // The session keeps listening on a channel until its last stream is dropped.
+    drop(jobs);
+    client.notify("test Listen", "again").await.unwrap();
+    assert_eq!(jobs2.next().await.unwrap().payload(), "again");
+
+    drop(jobs2);
+    let channels = client
+        .query("SELECT pg_listening_channels()", &[])
+        .await
+        .unwrap()
+        .iter()
+        .map(|row| row.get::<_, String>(0))
+        .collect::<Vec<_>>();
+    assert_eq!(channels, ["test listen other"]);
+
+    drop(client);
+    assert!(other.next().await.is_none());
+}
+
+#[tokio::test]
+async fn unlisten() {
+    let client = connect("user=postgres").await;
+
+    let mut jobs = client.listen("test \"Unlisten\"").await.unwrap();
+    let mut other = client.listen("test unlisten other").await.unwrap();
+    let mut third = client.listen("test unlisten third").await.unwrap();
+
+    client.unlisten("test \"Unlisten\"").await.unwrap();
+    assert!(jobs.next().await.is_none());
+
+    let listening = || async {
+        client
+            .query("SELECT pg_listening_channels() ORDER BY 1", &[])
+            .await
+            .unwrap()
+            .iter()
+            .map(|row| row.get::<_, String>(0))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        listening().await,
+        ["test unlisten other", "test unlisten third"]
+    );
+
+    client.unlisten_all().await.unwrap();
+    assert!(other.next().await.is_none());
+    assert!(third.next().await.is_none());
+    assert!(listening().await.is_empty());
+}
+
+#[tokio::test]
+async fn set_parameter() {
+    let mut client = connect("user=postgres").await;
+
+    client
+        .set_parameter("application_name", "it's \"quoted\"", false)
+        .await
+        .unwrap();
+    assert_eq!(
+        client.get_parameter("application_name").await.unwrap(),
+        "it's \"quoted\""
+    );
+
+    let transaction = client.transaction().await.unwrap();
+    transaction
+        .set_parameter("statement_timeout", "5s", true)
+        .await
+        .unwrap();
+    assert_eq!(
+        transaction
+            .get_parameter("statement_timeout")
+            .await
+            .unwrap(),
+        "5s"
+    );
+    transaction.commit().await.unwrap();
+    assert_eq!(
+        client.get_parameter("statement_timeout").await.unwrap(),
+        "0"
+    );
+
+    client.get_parameter("no_such_parameter").await.unwrap_err();
+}
+
 #[tokio::test]
 async fn query_portal() {
     let mut client = connect("user=postgres").await;
@@ -966,6 +3905,160 @@ async fn query_portal() {
     assert_eq!(r3.len(), 0);
 }
 
+#[tokio::test]
+async fn statement_bind() {
+    let client = connect("user=postgres").await;
+
+    let stmt = client
+        .prepare("SELECT generate_series(1, $1::INT)")
+        .await
+        .unwrap();
+
+    client.batch_execute("BEGIN").await.unwrap();
+
+    let portal = stmt.bind(&[&3i32]).await.unwrap();
+    assert_eq!(client.query_portal(&portal, 2).await.unwrap().len(), 2);
+
+    let other = portal.statement().bind(&[&5i32]).await.unwrap();
+    assert_eq!(client.query_portal(&other, 0).await.unwrap().len(), 5);
+    other.close().await.unwrap();
+
+    assert_eq!(client.query_portal(&portal, 2).await.unwrap().len(), 1);
+    let clone = portal.clone();
+    portal.close().await.unwrap();
+    assert!(client.query_portal(&clone, 1).await.is_err());
+
+    client.batch_execute("ROLLBACK").await.unwrap();
+}
+
+#[tokio::test]
+async fn portal_cursor() {
+    let mut client = connect("user=postgres").await;
+    let transaction = client.transaction().await.unwrap();
+
+    let mut cursor = transaction
+        .bind_cursor("SELECT generate_series(1, $1::INT)", &[&5i32])
+        .await
+        .unwrap();
+    let (rows, more) = cursor.fetch_next_chunk(2).await.unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[1].get::<_, i32>(0), 2);
+    assert!(more);
+    let (rows, more) = cursor.fetch_next_chunk(2).await.unwrap();
+    assert_eq!(rows.len(), 2);
+    assert!(more);
+    let (rows, more) = cursor.fetch_next_chunk(2).await.unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].get::<_, i32>(0), 5);
+    assert!(!more);
+    assert!(cursor.is_done());
+    let (rows, more) = cursor.fetch_next_chunk(2).await.unwrap();
+    assert!(rows.is_empty());
+    assert!(!more);
+
+    cursor = transaction
+        .bind_cursor("SELECT generate_series(1, 3)", &[])
+        .await
+        .unwrap();
+    let (rows, more) = cursor.fetch_next_chunk(0).await.unwrap();
+    assert_eq!(rows.len(), 3);
+    assert!(!more);
+
+    cursor = transaction
+        .bind_cursor("SELECT generate_series(1, 3)", &[])
+        .await
+        .unwrap();
+    cursor.fetch_next_chunk(1).await.unwrap();
+    drop(cursor);
+    let row = transaction
+        .query_one("SELECT count(*) FROM pg_cursors WHERE name <> ''", &[])
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, i64>(0), 0);
+}
+
+#[tokio::test]
+async fn cursor() {
+    let mut client = connect("user=postgres").await;
+
+    client
+        .batch_execute(
+            "CREATE TEMPORARY TABLE foo (id INT);
+            INSERT INTO foo SELECT generate_series(1, 5);",
+        )
+        .await
+        .unwrap();
+
+    let transaction = client.transaction().await.unwrap();
+    let cursor = transaction
+        .declare_cursor("SELECT id FROM foo WHERE id > $1 ORDER BY id", &[&1i32])
+        .await
+        .unwrap();
+    let rows = transaction.fetch(&cursor, 2).await.unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].get::<_, i32>(0), 2);
+    transaction.commit().await.unwrap();
+
+    let transaction = client.transaction().await.unwrap();
+    transaction.execute("DELETE FROM foo", &[]).await.unwrap();
+    let rows = transaction.fetch(&cursor, 5).await.unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[1].get::<_, i32>(0), 5);
+    transaction.commit().await.unwrap();
+
+    assert!(client.fetch(&cursor, 5).await.unwrap().is_empty());
+    client.close_cursor(cursor).await.unwrap();
+
+    let cursor = client.declare_cursor("SELECT 1", &[]).await.unwrap();
+    let name = cursor.name().to_string();
+    drop(cursor);
+    let open = client
+        .query_scalar::<i64>("SELECT count(*) FROM pg_cursors WHERE name = $1", &[&name])
+        .await
+        .unwrap();
+    assert_eq!(open, 0);
+}
+
+#[tokio::test]
+async fn query_chunked() {
+    let mut client = connect("user=postgres").await;
+
+    client
+        .batch_execute(
+            "CREATE TEMPORARY TABLE foo (id INT);
+            INSERT INTO foo SELECT generate_series(1, 5);",
+        )
+        .await
+        .unwrap();
+
+    let mut chunks = client
+        .query_chunked("SELECT id FROM foo WHERE id > $1 ORDER BY id", &[&0i32], 2)
+        .await
+        .unwrap();
+    let mut sizes = vec![];
+    let mut ids = vec![];
+    while let Some(rows) = chunks.next().await.unwrap() {
+        sizes.push(rows.len());
+        ids.extend(rows.iter().map(|row| row.get::<_, i32>(0)));
+    }
+    assert_eq!(sizes, [2, 2, 1]);
+    assert_eq!(ids, [1, 2, 3, 4, 5]);
+    assert!(chunks.next().await.unwrap().is_none());
+    chunks.finish().await.unwrap();
+
+    let mut transaction = client.transaction().await.unwrap();
+    let mut chunks = transaction
+        .query_chunked("SELECT id FROM foo ORDER BY id", &[], 5)
+        .await
+        .unwrap();
+    assert_eq!(chunks.next().await.unwrap().unwrap().len(), 5);
+    assert!(chunks.next().await.unwrap().is_none());
+    drop(chunks);
+    transaction.commit().await.unwrap();
+
+    assert_eq!(client.transaction_status(), TransactionStatus::Idle);
+}
+
 #[tokio::test]
 async fn require_channel_binding() {
     connect_raw("user=postgres channel_binding=require")
@@ -1049,6 +4142,117 @@ async fn query_one() {
         .unwrap();
 }
 
+#[tokio::test]
+async fn query_as() {
+    #[derive(Debug, PartialEq)]
+    struct Person {
+        name: String,
+        nickname: Option<String>,
+    }
+
+    impl FromRow for Person {
+        fn from_row<R>(row: &R) -> Result<Person, Box<dyn std::error::Error + Sync + Send>>
+        where
+            R: NamedRow + ?Sized,
+        {
+            let name: String = row.get_named("name")?;
+            if name.is_empty() {
+                return Err("empty name".into());
+            }
+            Ok(Person {
+                name,
+                nickname: row.get_named("nickname")?,
+            })
+        }
+    }
+
+    let client = connect("user=postgres").await;
+
+    let people = client
+        .query_as::<Person>(
+            "SELECT * FROM (VALUES ('alice', 'al'), ('bob', NULL)) AS t (name, nickname)",
+            &[],
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        people,
+        [
+            Person {
+                name: "alice".to_string(),
+                nickname: Some("al".to_string()),
+            },
+            Person {
+                name: "bob".to_string(),
+                nickname: None,
+            },
+        ]
+    );
+
+    let row = client
+        .query_one("SELECT 'carol' AS name", &[])
+        .await
+        .unwrap();
+    let err = row.try_into::<Person>().unwrap_err();
+    assert_eq!(err.to_string(), "invalid column `nickname`");
+
+    let row = client
+        .query_one("SELECT '' AS name, NULL::TEXT AS nickname", &[])
+        .await
+        .unwrap();
+    let err = row.try_into::<Person>().unwrap_err();
+    assert_eq!(err.to_string(), "error deserializing row: empty name");
+}
+
+#[tokio::test]
+async fn query_scalar() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute(
+            "
+                CREATE TEMPORARY TABLE foo (
+                    name TEXT
+                );
+                INSERT INTO foo (name) VALUES ('alice'), ('bob'), ('carol');
+            ",
+        )
+        .await
+        .unwrap();
+
+    let count = client
+        .query_scalar::<i64>("SELECT count(*) FROM foo", &[])
+        .await
+        .unwrap();
+    assert_eq!(count, 3);
+    let name = client
+        .query_scalar_opt::<String>("SELECT name FROM foo WHERE name = $1", &[&"bob"])
+        .await
+        .unwrap();
+    assert_eq!(name.as_deref(), Some("bob"));
+    let name = client
+        .query_scalar_opt::<String>("SELECT name FROM foo WHERE name = 'dave'", &[])
+        .await
+        .unwrap();
+    assert_eq!(name, None);
+
+    client
+        .query_scalar::<String>("SELECT name FROM foo WHERE name = 'dave'", &[])
+        .await
+        .err()
+        .unwrap();
+    client
+        .query_scalar::<String>("SELECT name FROM foo", &[])
+        .await
+        .err()
+        .unwrap();
+    let err = client
+        .query_scalar::<String>("SELECT name, name FROM foo WHERE name = 'alice'", &[])
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "expected 1 column but got 2");
+}
+
 #[tokio::test]
 async fn query_opt() {
     let client = connect("user=postgres").await;
@@ -1107,3 +4311,57 @@ async fn deferred_constraint() {
         .await
         .unwrap_err();
 }
+
+#[tokio::test]
+async fn large_object() {
+    let mut client = connect("user=postgres").await;
+
+    // Larger than the chunks transferred by a single call.
+    let data = (0..600_000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+
+    let transaction = client.transaction().await.unwrap();
+    let oid = transaction.create_large_object().await.unwrap();
+    let mut object = transaction
+        .open_large_object(oid, Mode::ReadWrite)
+        .await
+        .unwrap();
+    assert_eq!(object.oid(), oid);
+    object.write_all(&data).await.unwrap();
+    assert_eq!(object.stream_position().await.unwrap(), 600_000);
+
+    object.rewind().await.unwrap();
+    let mut read = vec![];
+    object.read_to_end(&mut read).await.unwrap();
+    assert_eq!(read, data);
+
+    object.seek(SeekFrom::End(-3)).await.unwrap();
+    let mut buf = [0; 10];
+    let n = object.read(&mut buf).await.unwrap();
+    assert_eq!(&buf[..n], &data[599_997..]);
+
+    object.truncate(10).await.unwrap();
+    object.seek(SeekFrom::Start(8)).await.unwrap();
+    object.write_all(b"end").await.unwrap();
+    object.close().await.unwrap();
+    transaction.commit().await.unwrap();
+
+    let transaction = client.transaction().await.unwrap();
+    let mut object = transaction
+        .open_large_object(oid, Mode::Read)
+        .await
+        .unwrap();
+    let mut read = vec![];
+    object.read_to_end(&mut read).await.unwrap();
+    assert_eq!(read, [&data[..8], b"end"].concat());
+    object.close().await.unwrap();
+    transaction.commit().await.unwrap();
+
+    client.unlink_large_object(oid).await.unwrap();
+    let transaction = client.transaction().await.unwrap();
+    let err = transaction
+        .open_large_object(oid, Mode::Read)
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), Some(&SqlState::UNDEFINED_OBJECT));
+}
@@ -1,5 +1,6 @@
+use std::error::Error;
 use std::time::Duration;
-use tokio_postgres::config::{Config, TargetSessionAttrs};
+use tokio_postgres::config::{Config, ParseError, ReplicationMode, SslMode, TargetSessionAttrs};
 
 fn check(s: &str, config: &Config) {
     assert_eq!(s.parse::<Config>().expect(s), *config, "`{}`", s);
@@ -44,6 +45,101 @@ fn settings() {
     );
 }
 
+#[test]
+fn target_session_attrs() {
+    check(
+        "target_session_attrs=primary",
+        Config::new().target_session_attrs(TargetSessionAttrs::Primary),
+    );
+    check(
+        "target_session_attrs=standby",
+        Config::new().target_session_attrs(TargetSessionAttrs::Standby),
+    );
+    check(
+        "target_session_attrs=prefer-standby",
+        Config::new().target_session_attrs(TargetSessionAttrs::PreferStandby),
+    );
+}
+
+#[test]
+fn ssl_password() {
+    check("sslpassword=hunter2", Config::new().ssl_password("hunter2"));
+}
+
+#[test]
+fn ssl_server_name() {
+    check(
+        "sslsni=0 sslservername=db.example.com",
+        Config::new()
+            .ssl_sni(false)
+            .ssl_server_name("db.example.com"),
+    );
+    "sslsni=yes".parse::<Config>().err().unwrap();
+}
+
+#[test]
+fn ssl_verify() {
+    check(
+        "sslmode=verify-full sslrootcert=/etc/ssl/root.crt",
+        Config::new()
+            .ssl_mode(SslMode::VerifyFull)
+            .ssl_root_cert("/etc/ssl/root.crt"),
+    );
+    check(
+        "sslmode=verify-ca",
+        Config::new().ssl_mode(SslMode::VerifyCa),
+    );
+}
+
+#[test]
+fn fallback_application_name() {
+    check(
+        "application_name=foo fallback_application_name=bar",
+        Config::new()
+            .application_name("foo")
+            .fallback_application_name("bar"),
+    );
+}
+
+#[test]
+fn log_slow_statements() {
+    check(
+        "log_slow_statements = 200ms",
+        Config::new().log_slow_statements(Duration::from_millis(200)),
+    );
+    check(
+        "log_slow_statements=250",
+        Config::new().log_slow_statements(Duration::from_millis(250)),
+    );
+    check(
+        "log_slow_statements='2 s'",
+        Config::new().log_slow_statements(Duration::from_secs(2)),
+    );
+    check(
+        "log_slow_statements=1min",
+        Config::new().log_slow_statements(Duration::from_secs(60)),
+    );
+    check("log_slow_statements=-1", &Config::new());
+    "log_slow_statements='2 days'"
+        .parse::<Config>()
+        .unwrap_err();
+    "log_slow_statements=fast".parse::<Config>().unwrap_err();
+}
+
+#[test]
+fn replication() {
+    check(
+        "replication=true",
+        Config::new().replication_mode(ReplicationMode::Physical),
+    );
+    check(
+        "replication=database",
+        Config::new().replication_mode(ReplicationMode::Logical),
+    );
+    check("replication=off", &Config::new());
+    "replication=logical".parse::<Config>().unwrap_err();
+}
+
 #[test]
 fn keepalive_settings() {
     check(
@@ -56,6 +152,70 @@ fn keepalive_settings() {
     );
 }
 
+#[test]
+fn client_settings() {
+    let config = Config::new()
+        .max_backend_message_size(1024)
+        .statement_stats(true)
+        .resolve_types(false)
+        .statement_cache_capacity(10)
+        .query_timeout(Duration::from_secs(5))
+        .keepalive_query_interval(Duration::from_secs(30))
+        .clone();
+    check(
+        "max_backend_message_size=1024 statement_stats=1 resolve_types=0 statement_cache_capacity=10 \
+         query_timeout=5s keepalive_query_interval=30000",
+        &config,
+    );
+    check(
+        "postgresql://?max_backend_message_size=1024&statement_stats=1&resolve_types=0&statement_cache_capacity=10\
+         &query_timeout=5s&keepalive_query_interval=30000",
+        &config,
+    );
+    check(
+        "query_timeout=-1 keepalive_query_interval=-1",
+        &Config::new(),
+    );
+}
+
+#[test]
+fn options() {
+    check(
+        r"options='-c search_path=foo -cstatement_timeout=5s --lock-timeout=1s -x -y\\ z'",
+        Config::new()
+            .options(r"-x -y\ z")
+            .startup_param("search_path", "foo")
+            .startup_param("statement_timeout", "5s")
+            .startup_param("lock_timeout", "1s"),
+    );
+    check(
+        "postgresql://?options=-c%20search_path%3Dfoo%5C%2C%5C%20bar",
+        Config::new().startup_param("search_path", "foo, bar"),
+    );
+    check(
+        "options='-c application_name=foo'",
+        Config::new().application_name("foo"),
+    );
+    "options='-c user=foo'".parse::<Config>().unwrap_err();
+    "options=-c".parse::<Config>().unwrap_err();
+    "options='-c search_path'".parse::<Config>().unwrap_err();
+}
+
+#[test]
+fn parse_errors() {
+    let key = |s: &str| {
+        let err = s.parse::<Config>().unwrap_err();
+        let err = err.source().unwrap().downcast_ref::<ParseError>().unwrap();
+        err.key().to_string()
+    };
+    assert_eq!(key("user=foo frobnicate=1"), "frobnicate");
+    assert_eq!(key("postgresql://?query_timeout=soon"), "query_timeout");
+    assert_eq!(
+        key("statement_cache_capacity=-1"),
+        "statement_cache_capacity"
+    );
+}
+
 #[test]
 fn url() {
     check("postgresql://", &Config::new());
@@ -129,6 +289,14 @@ fn url() {
             .dbname("dbname")
             .host_path("/var/lib/postgresql"),
     );
+    #[cfg(target_os = "linux")]
+    check(
+        "postgresql:///dbname?host=@pgsocket&requirepeer=postgres",
+        Config::new()
+            .dbname("dbname")
+            .host_path("@pgsocket")
+            .requirepeer("postgres"),
+    );
     #[cfg(unix)]
     check(
         "postgresql://%2Fvar%2Flib%2Fpostgresql/dbname",
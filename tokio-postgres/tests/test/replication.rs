@@ -0,0 +1,133 @@
+use crate::connect;
+use futures_util::{pin_mut, StreamExt};
+use postgres_protocol::message::backend::{
+    LogicalReplicationMessage, ReplicationMessage, TupleData,
+};
+use std::time::SystemTime;
+use tokio_postgres::replication::LogicalReplicationStream;
+use tokio_postgres::types::PgLsn;
+use tokio_postgres::SimpleQueryMessage;
+
+#[tokio::test]
+async fn logical_replication() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute(
+            "
+            DROP PUBLICATION IF EXISTS replication_test_pub;
+            DROP TABLE IF EXISTS replication_test;
+            CREATE TABLE replication_test (id INT PRIMARY KEY, name TEXT);
+            CREATE PUBLICATION replication_test_pub FOR TABLE replication_test;
+            ",
+        )
+        .await
+        .unwrap();
+
+    let repl_client = connect("user=postgres replication=database").await;
+
+    let messages = repl_client
+        .simple_query("CREATE_REPLICATION_SLOT replication_test_slot TEMPORARY LOGICAL pgoutput")
+        .await
+        .unwrap();
+    let slot_name = messages
+        .iter()
+        .find_map(|m| match m {
+            SimpleQueryMessage::Row(row) => Some(row.get("slot_name").unwrap()),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(slot_name, "replication_test_slot");
+
+    let duplex = repl_client
+        .copy_both_simple(
+            "START_REPLICATION SLOT replication_test_slot LOGICAL 0/0 \
+             (\"proto_version\" '1', \"publication_names\" 'replication_test_pub')",
+        )
+        .await
+        .unwrap();
+    let stream = LogicalReplicationStream::new(duplex);
+    pin_mut!(stream);
+
+    client
+        .execute("INSERT INTO replication_test VALUES (1, 'alice')", &[])
+        .await
+        .unwrap();
+
+    let mut messages = vec![];
+    let mut end = None;
+    while let Some(message) = stream.next().await {
+        match message.unwrap() {
+            ReplicationMessage::XLogData(body) => {
+                end = Some(body.wal_end());
+                let message = body.into_data();
+                let done = matches!(message, LogicalReplicationMessage::Commit(_));
+                messages.push(message);
+                if done {
+                    break;
+                }
+            }
+            ReplicationMessage::PrimaryKeepAlive(_) => {}
+            _ => panic!("unexpected message"),
+        }
+    }
+
+    let rel_id = match &messages[..] {
+        [LogicalReplicationMessage::Begin(_), LogicalReplicationMessage::Relation(relation), LogicalReplicationMessage::Insert(insert), LogicalReplicationMessage::Commit(_)] =>
+        {
+            assert_eq!(relation.namespace().unwrap(), "public");
+            assert_eq!(relation.name().unwrap(), "replication_test");
+            let columns = relation
+                .columns()
+                .iter()
+                .map(|c| c.name().unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(columns, ["id", "name"]);
+
+            let values = insert
+                .tuple()
+                .tuple_data()
+                .iter()
+                .map(|data| match data {
+                    TupleData::Text(text) => &text[..],
+                    _ => panic!("unexpected tuple data"),
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(values, [&b"1"[..], b"alice"]);
+
+            assert_eq!(insert.rel_id(), relation.rel_id());
+            relation.rel_id()
+        }
+        _ => panic!("unexpected messages: {:?}", messages),
+    };
+    assert!(rel_id > 0);
+
+    let lsn = PgLsn::from(end.unwrap());
+    stream
+        .as_mut()
+        .standby_status_update(lsn, lsn, lsn, SystemTime::now(), 1)
+        .await
+        .unwrap();
+    loop {
+        match stream.next().await.unwrap().unwrap() {
+            ReplicationMessage::PrimaryKeepAlive(_) => break,
+            ReplicationMessage::XLogData(_) => {}
+            _ => panic!("unexpected message"),
+        }
+    }
+
+    stream.finish().await.unwrap();
+
+    // The session is back in the normal mode once the replication ends.
+    repl_client.simple_query("IDENTIFY_SYSTEM").await.unwrap();
+
+    client
+        .batch_execute(
+            "
+            DROP PUBLICATION replication_test_pub;
+            DROP TABLE replication_test;
+            ",
+        )
+        .await
+        .unwrap();
+}
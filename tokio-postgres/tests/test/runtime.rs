@@ -1,8 +1,12 @@
 use futures_util::{join, FutureExt};
+use std::error::Error;
+use std::io;
 use std::time::Duration;
 use tokio::time;
+use tokio_postgres::config::{HostCredentials, SslMode};
 use tokio_postgres::error::SqlState;
-use tokio_postgres::{Client, NoTls};
+use tokio_postgres::gss::{GssContext, GssStep};
+use tokio_postgres::{CancelOptions, CancelOutcome, Client, Config, NoTls, SimpleQueryMessage};
 
 async fn connect(s: &str) -> Client {
     let (client, connection) = tokio_postgres::connect(s, NoTls).await.unwrap();
@@ -26,6 +30,41 @@ async fn unix_socket() {
     smoke_test("host=/var/run/postgresql port=5433 user=postgres").await;
 }
 
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn unix_socket_requirepeer() {
+    use std::os::linux::net::SocketAddrExt;
+    use std::os::unix::net::{SocketAddr, UnixListener};
+
+    // The listeners are owned by the current user, which doesn't match `requirepeer`.
+    let name = format!("tokio-postgres-{}", std::process::id());
+    let addr = SocketAddr::from_abstract_name(format!("{}/.s.PGSQL.5433", name)).unwrap();
+    let _abstract_listener = UnixListener::bind_addr(&addr).unwrap();
+
+    let path = std::env::temp_dir().join(format!("{}.sock", name));
+    let _ = std::fs::remove_file(&path);
+    let _file_listener = UnixListener::bind(&path).unwrap();
+
+    let mut configs = vec![format!("host=@{} port=5433 user=postgres", name)
+        .parse::<Config>()
+        .unwrap()];
+    let mut config = Config::new();
+    config.host_path(&path).user("postgres");
+    configs.push(config);
+
+    for mut config in configs {
+        let e = config
+            .requirepeer("tokio-postgres-nobody")
+            .connect(NoTls)
+            .await
+            .err()
+            .unwrap();
+        assert!(e.to_string().contains("requirepeer"), "{}", e);
+    }
+
+    std::fs::remove_file(&path).unwrap();
+}
+
 #[tokio::test]
 async fn tcp() {
     smoke_test("host=localhost port=5433 user=postgres").await;
@@ -49,6 +88,36 @@ async fn wrong_port_count() {
         .unwrap();
 }
 
+#[tokio::test]
+async fn host_credentials() {
+    let mut config =
+        "host=localhost,localhost port=5433 user=pass_user password=wrong dbname=postgres"
+            .parse::<Config>()
+            .unwrap();
+    config.host_credentials(1, HostCredentials::new().password("password").clone());
+
+    let (client, connection) = config.connect(NoTls).await.unwrap();
+    tokio::spawn(connection.map(|e| e.unwrap()));
+
+    let messages = client.simple_query("SELECT current_user").await.unwrap();
+    match &messages[0] {
+        SimpleQueryMessage::Row(row) => assert_eq!(row.get(0), Some("pass_user")),
+        _ => panic!("unexpected message"),
+    }
+}
+
+#[tokio::test]
+async fn host_credentials_nonexistent_host() {
+    "host=localhost port=5433 user=postgres"
+        .parse::<Config>()
+        .unwrap()
+        .host_credentials(1, HostCredentials::new())
+        .connect(NoTls)
+        .await
+        .err()
+        .unwrap();
+}
+
 #[tokio::test]
 async fn target_session_attrs_ok() {
     smoke_test("host=localhost port=5433 user=postgres target_session_attrs=read-write").await;
@@ -66,6 +135,30 @@ async fn target_session_attrs_err() {
     .unwrap();
 }
 
+#[tokio::test]
+async fn target_session_attrs_primary() {
+    smoke_test("host=localhost port=5433 user=postgres target_session_attrs=primary").await;
+}
+
+#[tokio::test]
+async fn target_session_attrs_standby_err() {
+    tokio_postgres::connect(
+        "host=localhost,127.0.0.1 port=5433 user=postgres target_session_attrs=standby",
+        NoTls,
+    )
+    .await
+    .err()
+    .unwrap();
+}
+
+#[tokio::test]
+async fn target_session_attrs_prefer_standby() {
+    smoke_test(
+        "host=localhost,127.0.0.1 port=5433 user=postgres target_session_attrs=prefer-standby",
+    )
+    .await;
+}
+
 #[tokio::test]
 async fn host_only_ok() {
     let _ = tokio_postgres::connect(
@@ -118,6 +211,68 @@ async fn hostaddr_host_both_missing() {
     .unwrap();
 }
 
+struct BogusGssContext;
+
+impl GssContext for BogusGssContext {
+    fn step(&mut self, _: Option<&[u8]>) -> Result<GssStep, Box<dyn Error + Sync + Send>> {
+        Ok(GssStep::Continue(b"bogus".to_vec()))
+    }
+
+    fn wrap(&mut self, _: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Sync + Send>> {
+        unreachable!()
+    }
+
+    fn unwrap(&mut self, _: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Sync + Send>> {
+        unreachable!()
+    }
+}
+
+fn no_gss_credentials(_: &str) -> Result<BogusGssContext, io::Error> {
+    Err(io::Error::new(io::ErrorKind::NotFound, "no credentials"))
+}
+
+#[tokio::test]
+async fn gssencmode_prefer_without_credentials() {
+    let mut config = "host=localhost port=5433 user=postgres gssencmode=prefer"
+        .parse::<Config>()
+        .unwrap();
+    config.gss_context_provider(no_gss_credentials);
+    let (client, connection) = config.connect(NoTls).await.unwrap();
+    tokio::spawn(connection.map(|e| e.unwrap()));
+
+    client.batch_execute("SELECT 1").await.unwrap();
+}
+
+#[tokio::test]
+async fn gssencmode_require_without_credentials() {
+    let mut config = "host=localhost port=5433 user=postgres gssencmode=require"
+        .parse::<Config>()
+        .unwrap();
+    config.gss_context_provider(no_gss_credentials);
+    let err = config.connect(NoTls).await.err().unwrap();
+    assert!(err.to_string().contains("no credentials"));
+}
+
+#[tokio::test]
+async fn gssencmode_require_without_provider() {
+    tokio_postgres::connect(
+        "host=localhost port=5433 user=postgres gssencmode=require",
+        NoTls,
+    )
+    .await
+    .err()
+    .unwrap();
+}
+
+#[tokio::test]
+async fn gssencmode_handshake_rejected() {
+    let mut config = "host=localhost port=5433 user=postgres gssencmode=require"
+        .parse::<Config>()
+        .unwrap();
+    config.gss_context_provider(|_| Ok::<_, io::Error>(BogusGssContext));
+    config.connect(NoTls).await.err().unwrap();
+}
+
 #[tokio::test]
 async fn cancel_query() {
     let client = connect("host=localhost port=5433 user=postgres").await;
@@ -133,3 +288,173 @@ async fn cancel_query() {
         t => panic!("unexpected return: {:?}", t),
     }
 }
+
+#[tokio::test]
+async fn cancel_query_with() {
+    let client = connect("host=localhost port=5433 user=postgres").await;
+
+    let options = CancelOptions::new()
+        .connect_timeout(Duration::from_secs(10))
+        .ssl_mode(SslMode::Disable)
+        .retries(2)
+        .retry_delay(Duration::from_millis(10));
+    let cancel_token = client.cancel_token();
+    let cancel = cancel_token.cancel_query_with(NoTls, options);
+    let cancel = time::sleep(Duration::from_millis(100)).then(|()| cancel);
+
+    let sleep = client.batch_execute("SELECT pg_sleep(100)");
+
+    match join!(sleep, cancel) {
+        (Err(ref e), CancelOutcome::Delivered) if e.code() == Some(&SqlState::QUERY_CANCELED) => {}
+        t => panic!("unexpected return: {:?}", t),
+    }
+
+    // Once the server has closed the connection, there is nothing left to cancel.
+    let (client, connection) =
+        tokio_postgres::connect("host=localhost port=5433 user=postgres", NoTls)
+            .await
+            .unwrap();
+    let connection = tokio::spawn(connection);
+    let pid = client
+        .query_one("SELECT pg_backend_pid()", &[])
+        .await
+        .unwrap()
+        .get::<_, i32>(0);
+    let other = connect("host=localhost port=5433 user=postgres").await;
+    // Wait for the backend to exit, so that the next query always finds the connection lost.
+    other
+        .execute("SELECT pg_terminate_backend($1, 5000)", &[&pid])
+        .await
+        .unwrap();
+    connection.await.unwrap().unwrap_err();
+
+    match client
+        .cancel_token()
+        .cancel_query_with(NoTls, options)
+        .await
+    {
+        CancelOutcome::BackendGone => {}
+        t => panic!("unexpected return: {:?}", t),
+    }
+}
+
+#[tokio::test]
+async fn after_connect() {
+    let mut config = "host=localhost port=5433 user=postgres"
+        .parse::<Config>()
+        .unwrap();
+    config
+        .after_connect("SET search_path = pg_catalog; SET application_name = hooked")
+        .after_connect("SET statement_timeout = '5s'")
+        .after_connect_callback(|client| {
+            Box::pin(async move {
+                client
+                    .execute("SELECT set_config('app.tenant', 'acme', false)", &[])
+                    .await?;
+                Ok(())
+            })
+        });
+    let (client, connection) = config.connect(NoTls).await.unwrap();
+    tokio::spawn(connection);
+
+    let row = client
+        .query_one(
+            "SELECT current_setting('search_path'), current_setting('application_name'),
+                current_setting('statement_timeout'), current_setting('app.tenant')",
+            &[],
+        )
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, &str>(0), "pg_catalog");
+    assert_eq!(row.get::<_, &str>(1), "hooked");
+    assert_eq!(row.get::<_, &str>(2), "5s");
+    assert_eq!(row.get::<_, &str>(3), "acme");
+
+    let mut config = "host=localhost port=5433 user=postgres"
+        .parse::<Config>()
+        .unwrap();
+    config.after_connect("SELECT 1 / 0");
+    let e = config.connect(NoTls).await.err().unwrap();
+    assert_eq!(e.code(), Some(&SqlState::DIVISION_BY_ZERO));
+}
+
+#[cfg(feature = "retry")]
+#[tokio::test]
+async fn retrying_client() {
+    use std::time::Duration;
+    use tokio_postgres::retry::{RetryPolicy, RetryingClient};
+
+    let config = "host=localhost port=5433 user=postgres"
+        .parse::<Config>()
+        .unwrap();
+    let policy = RetryPolicy::new().initial_backoff(Duration::from_millis(10));
+    let client = RetryingClient::connect(config, NoTls, policy)
+        .await
+        .unwrap();
+
+    let rows = client.query("SELECT $1::INT", &[&1i32]).await.unwrap();
+    assert_eq!(rows[0].get::<_, i32>(0), 1);
+
+    let rows = client.query("SELECT pg_backend_pid()", &[]).await.unwrap();
+    let pid = rows[0].get::<_, i32>(0);
+    let other = connect("host=localhost port=5433 user=postgres").await;
+    // Wait for the backend to exit, so that the next query always finds the connection lost.
+    other
+        .execute("SELECT pg_terminate_backend($1, 5000)", &[&pid])
+        .await
+        .unwrap();
+
+    let rows = client
+        .query_idempotent("SELECT $1::INT", &[&2i32])
+        .await
+        .unwrap();
+    assert_eq!(rows[0].get::<_, i32>(0), 2);
+    assert_eq!(client.reconnects(), 1);
+
+    let err = client
+        .retry(|client| async move { client.batch_execute("SELECT 1 / 0").await })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Some(&SqlState::DIVISION_BY_ZERO));
+    assert_eq!(client.reconnects(), 1);
+}
+
+#[cfg(feature = "deadpool")]
+#[tokio::test]
+async fn deadpool() {
+    use tokio_postgres::deadpool::{Manager, Pool};
+    use tokio_postgres::TransactionStatus;
+
+    let config = "host=localhost port=5433 user=postgres"
+        .parse::<Config>()
+        .unwrap();
+    let pool = Pool::builder(Manager::new(config, NoTls))
+        .max_size(1)
+        .build()
+        .unwrap();
+
+    let client = pool.get().await.unwrap();
+    let row = client
+        .query_one("SELECT pg_backend_pid()", &[])
+        .await
+        .unwrap();
+    let pid = row.get::<_, i32>(0);
+    drop(client);
+
+    let client = pool.get().await.unwrap();
+    let row = client
+        .query_one("SELECT pg_backend_pid()", &[])
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, i32>(0), pid);
+    client.batch_execute("BEGIN").await.unwrap();
+    drop(client);
+
+    let client = pool.get().await.unwrap();
+    assert_eq!(client.transaction_status(), TransactionStatus::Idle);
+    let row = client
+        .query_one("SELECT pg_backend_pid()", &[])
+        .await
+        .unwrap();
+    assert_ne!(row.get::<_, i32>(0), pid);
+}
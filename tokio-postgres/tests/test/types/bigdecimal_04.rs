@@ -0,0 +1,41 @@
+use bigdecimal_04::BigDecimal;
+use std::str::FromStr;
+
+use crate::connect;
+use crate::types::test_type;
+
+#[tokio::test]
+async fn test_big_decimal_params() {
+    test_type(
+        "NUMERIC",
+        &[
+            (Some(BigDecimal::from_str("0").unwrap()), "'0'"),
+            (
+                Some(BigDecimal::from_str("12345.678").unwrap()),
+                "'12345.678'",
+            ),
+            (Some(BigDecimal::from_str("-0.0001").unwrap()), "'-0.0001'"),
+            (
+                Some(BigDecimal::from_str("123456789012345678901234567890.0000000001").unwrap()),
+                "'123456789012345678901234567890.0000000001'",
+            ),
+            (Some(BigDecimal::from_str("1e40").unwrap()), "1e40"),
+            (Some(BigDecimal::from_str("-1e-40").unwrap()), "-1e-40"),
+            (None, "NULL"),
+        ],
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_big_decimal_unrepresentable() {
+    let client = connect("user=postgres").await;
+
+    for value in ["NaN", "Infinity", "-Infinity"] {
+        let row = client
+            .query_one(&*format!("SELECT '{}'::NUMERIC", value), &[])
+            .await
+            .unwrap();
+        assert!(row.try_get::<_, BigDecimal>(0).is_err());
+    }
+}
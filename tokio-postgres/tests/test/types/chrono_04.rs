@@ -1,4 +1,5 @@
 use chrono_04::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use futures_util::TryStreamExt;
 use std::fmt;
 use tokio_postgres::types::{Date, FromSqlOwned, Timestamp};
 use tokio_postgres::Client;
@@ -184,3 +185,37 @@ async fn test_special_params_without_wrapper() {
     assert_overflows::<NaiveDate>(&mut client, "'-infinity'", "date").await;
     assert_overflows::<NaiveDate>(&mut client, "'infinity'", "date").await;
 }
+
+#[tokio::test]
+async fn test_date_text_date_style() {
+    let client = connect("user=postgres").await;
+
+    for style in ["SQL, MDY", "SQL, DMY", "Postgres, DMY", "German, DMY"] {
+        client
+            .batch_execute(&format!("SET DateStyle = '{}'", style))
+            .await
+            .unwrap();
+        let rows = client
+            .query_raw_txt(
+                "SELECT '1997-12-17'::DATE, '0044-03-15 BC'::DATE",
+                [None::<&str>; 0],
+            )
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(
+            rows[0].get::<_, NaiveDate>(0),
+            NaiveDate::from_ymd_opt(1997, 12, 17).unwrap(),
+            "{}",
+            style
+        );
+        assert_eq!(
+            rows[0].get::<_, NaiveDate>(1),
+            NaiveDate::from_ymd_opt(-43, 3, 15).unwrap(),
+            "{}",
+            style
+        );
+    }
+}
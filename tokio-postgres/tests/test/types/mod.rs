@@ -7,12 +7,18 @@ use std::fmt;
 use std::net::IpAddr;
 use std::result;
 use std::str::FromStr;
-use std::time::{Duration, UNIX_EPOCH};
-use tokio_postgres::types::{FromSql, FromSqlOwned, IsNull, Kind, PgLsn, ToSql, Type, WrongType};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio_postgres::types::{
+    FromSql, FromSqlOwned, Interval, IsNull, Kind, PgLsn, ToSql, ToText, TsLexeme, TsPosition,
+    TsQuery, TsVector, TsWeight, Type, WrongType,
+};
 
 use crate::connect;
 use bytes::BytesMut;
+use futures_util::TryStreamExt;
 
+#[cfg(feature = "with-bigdecimal-0_4")]
+mod bigdecimal_04;
 #[cfg(feature = "with-bit-vec-0_6")]
 mod bit_vec_06;
 #[cfg(feature = "with-chrono-0_4")]
@@ -23,6 +29,8 @@ mod eui48_1;
 mod geo_types_06;
 #[cfg(feature = "with-geo-types-0_7")]
 mod geo_types_07;
+#[cfg(feature = "with-rust_decimal-1")]
+mod rust_decimal_1;
 #[cfg(feature = "with-serde_json-1")]
 mod serde_json_1;
 #[cfg(feature = "with-smol_str-01")]
@@ -38,7 +46,7 @@ mod uuid_1;
 
 async fn test_type<T, S>(sql_type: &str, checks: &[(T, S)])
 where
-    T: PartialEq + for<'a> FromSqlOwned + ToSql + Sync,
+    T: PartialEq + for<'a> FromSqlOwned + ToSql + ToText + Sync,
     S: fmt::Display,
 {
     let client = connect("user=postgres").await;
@@ -57,6 +65,31 @@ where
             .unwrap();
         let result = rows[0].get(0);
         assert_eq!(val, &result);
+
+        let rows = client
+            .query_raw_txt(&format!("SELECT {}::{}", repr, sql_type), [None::<&str>; 0])
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let result = rows[0].get(0);
+        assert_eq!(val, &result);
+
+        // Vectors are written as array literals, which int2vector and oidvector don't accept as input.
+        if sql_type == "int2vector" || sql_type == "oidvector" {
+            continue;
+        }
+
+        let rows = client
+            .query_raw_txt(&format!("SELECT $1::{}", sql_type), [val])
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let result = rows[0].get(0);
+        assert_eq!(val, &result);
     }
 }
 
@@ -154,6 +187,50 @@ async fn test_lsn_params() {
     .await
 }
 
+#[tokio::test]
+async fn interval() {
+    test_type(
+        "INTERVAL",
+        &[
+            (
+                Some(Interval::new(14, 3, 14_706_500_000)),
+                "'1 year 2 mons 3 days 04:05:06.5'",
+            ),
+            (
+                Some(Interval::new(10, 3, -14_706_000_000)),
+                "'1 year -2 mons +3 days -04:05:06'",
+            ),
+            (
+                Some(Interval::new(-1, -1, -1)),
+                "'-1 mon -1 day -0.000001 sec'",
+            ),
+            (Some(Interval::new(0, 0, 360_000_000_000)), "'100 hours'"),
+            (Some(Interval::default()), "'0'"),
+            (None, "NULL"),
+        ],
+    )
+    .await
+}
+
+#[tokio::test]
+async fn lsn_arithmetic() {
+    let client = connect("user=postgres").await;
+
+    let start = PgLsn::from_str("2B/FFFFFFF0").unwrap();
+    let end = start + 0x20;
+    assert_eq!(end.to_string(), "2C/10");
+    assert_eq!(end - start, 0x20);
+    assert_eq!(end - 0x20, start);
+    assert_eq!(start.checked_distance(end), None);
+    assert_eq!(PgLsn::from(u64::MAX).checked_add(1), None);
+
+    let row = client
+        .query_one("SELECT ($1::pg_lsn - $2::pg_lsn)::int8", &[&end, &start])
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, i64>(0), 0x20);
+}
+
 #[tokio::test]
 async fn test_f32_params() {
     test_type(
@@ -597,6 +674,27 @@ async fn enum_() {
     }
 }
 
+#[tokio::test]
+async fn enum_inferred() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute("CREATE TYPE pg_temp.size AS ENUM ('small', 'large')")
+        .await
+        .unwrap();
+
+    let rows = client
+        .query_raw_inferred("SELECT $1::TEXT::size", ["small"])
+        .await
+        .unwrap()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    let type_ = rows[0].columns()[0].type_();
+    assert_eq!(type_.name(), "size");
+    assert!(matches!(type_.kind(), Kind::Enum(_)));
+}
+
 #[tokio::test]
 async fn system_time() {
     test_type(
@@ -738,6 +836,94 @@ async fn ltxtquery_any() {
     .await;
 }
 
+#[tokio::test]
+async fn jsonpath() {
+    test_type(
+        "jsonpath",
+        &[
+            (Some("$.\"a\"[*]".to_owned()), "'$.\"a\"[*]'"),
+            (Some("$.\"a\"?(@ > 1)".to_owned()), "'$.\"a\"?(@ > 1)'"),
+            (None, "NULL"),
+        ],
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn tsvector() {
+    let client = connect("user=postgres").await;
+
+    let position = |position, weight| TsPosition { position, weight };
+    let vector = TsVector(vec![
+        TsLexeme {
+            word: "cat".to_string(),
+            positions: vec![position(3, TsWeight::D)],
+        },
+        TsLexeme {
+            word: "fat".to_string(),
+            positions: vec![position(2, TsWeight::A), position(4, TsWeight::C)],
+        },
+        TsLexeme {
+            word: "rat".to_string(),
+            positions: vec![],
+        },
+    ]);
+
+    let row = client
+        .query_one("SELECT 'fat:2A,4C cat:3 rat'::tsvector", &[])
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, TsVector>(0), vector);
+
+    let row = client
+        .query_one("SELECT $1::tsvector::text", &[&vector])
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, &str>(0), "'cat':3 'fat':2A,4C 'rat'");
+}
+
+#[tokio::test]
+async fn tsquery() {
+    let client = connect("user=postgres").await;
+
+    let lexeme = |word: &str, weights, prefix| {
+        Box::new(TsQuery::Lexeme {
+            word: word.to_string(),
+            weights,
+            prefix,
+        })
+    };
+    let query = TsQuery::And(
+        lexeme("fat", vec![TsWeight::A, TsWeight::B], false),
+        Box::new(TsQuery::Phrase {
+            left: Box::new(TsQuery::Not(lexeme("rat", vec![], false))),
+            right: lexeme("cat", vec![], true),
+            distance: 2,
+        }),
+    );
+
+    let row = client
+        .query_one("SELECT 'fat:AB & !rat <2> cat:*'::tsquery", &[])
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, TsQuery>(0), query);
+
+    let row = client
+        .query_one("SELECT $1::tsquery::text", &[&query])
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, &str>(0), "'fat':AB & !'rat' <2> 'cat':*");
+
+    let row = client.query_one("SELECT ''::tsquery", &[]).await.unwrap();
+    assert_eq!(row.get::<_, TsQuery>(0), TsQuery::Empty);
+
+    let err = client
+        .query_one("SELECT $1::tsquery::text", &[&TsQuery::Empty])
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("empty tsquery"), "{}", err);
+}
+
 #[tokio::test]
 async fn oidvector() {
     test_type(
@@ -759,3 +945,140 @@ async fn int2vector() {
     )
     .await;
 }
+
+#[tokio::test]
+async fn text_format() {
+    let client = connect("user=postgres").await;
+
+    let query = |sql: &'static str| {
+        let client = &client;
+        async move {
+            client
+                .query_raw_txt(sql, [None::<&str>; 0])
+                .await
+                .unwrap()
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap()
+        }
+    };
+
+    // Values of any type can be read as strings.
+    let rows = query("SELECT 1.50::NUMERIC, 42::INT4").await;
+    assert_eq!(rows[0].get::<_, &str>(0), "1.50");
+    assert_eq!(rows[0].get::<_, String>(1), "42");
+    assert_eq!(rows[0].get::<_, i32>(1), 42);
+
+    let rows = query("SELECT '[0:2]={1,NULL,3}'::INT4[], ARRAY['a\"b', 'c\\d', 'e f', NULL]").await;
+    assert_eq!(
+        rows[0].get::<_, Vec<Option<i32>>>(0),
+        [Some(1), None, Some(3)]
+    );
+    // Escaped elements can only be read into owned values.
+    assert_eq!(
+        rows[0].get::<_, Vec<Option<String>>>(1),
+        [
+            Some("a\"b".to_string()),
+            Some("c\\d".to_string()),
+            Some("e f".to_string()),
+            None
+        ]
+    );
+    assert!(rows[0].try_get::<_, Vec<Option<&str>>>(1).is_err());
+
+    client
+        .batch_execute("SET bytea_output = escape; SET TimeZone = 'America/New_York'")
+        .await
+        .unwrap();
+    let rows = query("SELECT '\\x00ff5c41'::BYTEA, '2000-01-01 12:30:00.5+02'::TIMESTAMPTZ").await;
+    assert_eq!(rows[0].get::<_, Vec<u8>>(0), b"\x00\xff\\A");
+    assert_eq!(
+        rows[0].get::<_, std::time::SystemTime>(1),
+        UNIX_EPOCH + Duration::from_millis(946_722_600_500)
+    );
+}
+
+#[tokio::test]
+async fn text_format_date_style() {
+    let client = connect("user=postgres").await;
+    client.batch_execute("SET TimeZone = 'UTC'").await.unwrap();
+
+    let time = UNIX_EPOCH + Duration::from_millis(882_344_236_250);
+    let bc = UNIX_EPOCH - Duration::from_secs(63_517_780_800);
+    for style in [
+        "ISO, MDY",
+        "SQL, MDY",
+        "SQL, DMY",
+        "Postgres, MDY",
+        "Postgres, DMY",
+        "German, DMY",
+    ] {
+        client
+            .batch_execute(&format!("SET DateStyle = '{}'", style))
+            .await
+            .unwrap();
+        let rows = client
+            .query_raw_txt(
+                "SELECT '1997-12-17 07:37:16.25'::TIMESTAMP, '1997-12-17 07:37:16.25'::TIMESTAMPTZ, \
+                 ARRAY['0044-03-15 12:00 BC'::TIMESTAMP]",
+                [None::<&str>; 0],
+            )
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(rows[0].get::<_, SystemTime>(0), time, "{}", style);
+        assert_eq!(rows[0].get::<_, SystemTime>(1), time, "{}", style);
+        assert_eq!(rows[0].get::<_, Vec<SystemTime>>(2), [bc], "{}", style);
+    }
+
+    // Only the ISO format reports the offsets of time zones.
+    client
+        .batch_execute("SET TimeZone = 'America/New_York'")
+        .await
+        .unwrap();
+    let rows = client
+        .query_raw_txt("SELECT now()", [None::<&str>; 0])
+        .await
+        .unwrap()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert!(rows[0].try_get::<_, SystemTime>(0).is_err());
+}
+
+#[tokio::test]
+async fn text_format_interval_style() {
+    let client = connect("user=postgres").await;
+
+    let intervals = [
+        Interval::new(14, 3, 14_706_500_000),
+        Interval::new(-14, -3, -14_706_500_000),
+        Interval::new(10, 3, -14_706_000_000),
+        Interval::new(0, -1, 7_380_000_000),
+        Interval::new(-1, 0, 0),
+        Interval::new(0, -3, -14_706_000_000),
+        Interval::new(0, 0, -1_000_000),
+        Interval::new(0, 0, 1),
+        Interval::default(),
+    ];
+    for style in ["postgres", "postgres_verbose", "sql_standard", "iso_8601"] {
+        client
+            .batch_execute(&format!("SET IntervalStyle = '{}'", style))
+            .await
+            .unwrap();
+        let rows = client
+            .query_raw_txt("SELECT unnest($1::INTERVAL[])", [intervals.to_vec()])
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let result = rows
+            .iter()
+            .map(|row| row.get::<_, Interval>(0))
+            .collect::<Vec<_>>();
+        assert_eq!(result, intervals, "{}", style);
+    }
+}
@@ -0,0 +1,53 @@
+use rust_decimal_1::Decimal;
+use std::str::FromStr;
+
+use crate::connect;
+use crate::types::test_type;
+
+#[tokio::test]
+async fn test_decimal_params() {
+    test_type(
+        "NUMERIC",
+        &[
+            (Some(Decimal::from_str("0").unwrap()), "'0'"),
+            (Some(Decimal::from_str("12345.678").unwrap()), "'12345.678'"),
+            (Some(Decimal::from_str("-0.0001").unwrap()), "'-0.0001'"),
+            (
+                Some(Decimal::from_str("10000.00001").unwrap()),
+                "'10000.00001'",
+            ),
+            (Some(Decimal::MAX), "'79228162514264337593543950335'"),
+            (Some(Decimal::MIN), "'-79228162514264337593543950335'"),
+            (None, "NULL"),
+        ],
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_decimal_scale() {
+    let client = connect("user=postgres").await;
+
+    let row = client
+        .query_one(
+            "SELECT 1.2300::NUMERIC, $1::NUMERIC",
+            &[&Decimal::new(12300, 4)],
+        )
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, Decimal>(0).to_string(), "1.2300");
+    assert_eq!(row.get::<_, Decimal>(1).to_string(), "1.2300");
+}
+
+#[tokio::test]
+async fn test_decimal_unrepresentable() {
+    let client = connect("user=postgres").await;
+
+    for value in ["'NaN'", "'Infinity'", "'-Infinity'", "1e40"] {
+        let row = client
+            .query_one(&*format!("SELECT {}::NUMERIC", value), &[])
+            .await
+            .unwrap();
+        assert!(row.try_get::<_, Decimal>(0).is_err());
+    }
+}